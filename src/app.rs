@@ -1,7 +1,13 @@
 use eframe::egui;
 use egui_dock::{DockArea, DockState, Style, TabViewer};
-use crate::{Tab, Plugin, AppCommand, NotificationLevel};
-use crate::plugins;
+use crate::{Tab, TabIcon, Plugin, AppCommand, NotificationAction, NotificationLevel, OpenSupport, TabInstance};
+use crate::backup::{self, BackupScheduler, RestorePoint};
+use crate::config::{AppConfig, SessionState, StartupPolicy};
+use crate::power::{BatterySaverMode, PowerMonitor, PowerProfile};
+use crate::{fonts, plugins, theme};
+use crate::welcome::WelcomeTab;
+use crate::workspace::{self, Workspace};
+use std::collections::HashSet;
 
 // ----------------------------------------------------------------------------
 // Notification System
@@ -10,13 +16,24 @@ struct NotificationInstance {
     message: String,
     level: NotificationLevel,
     remaining_time: f32,
+    action: Option<NotificationAction>,
 }
 
 // ----------------------------------------------------------------------------
 // TabViewer 实现
 // ----------------------------------------------------------------------------
+/// 最多保留的已关闭标签页数量
+const MAX_CLOSED_TABS: usize = 20;
+
 struct VerbiumTabViewer<'a> {
     command_queue: &'a mut Vec<AppCommand>,
+    attention: &'a HashSet<u64>,
+    closed_tabs: &'a mut Vec<Tab>,
+    /// 本帧绘制过的标签页
+    shown: &'a mut HashSet<u64>,
+    time: f64,
+    /// 需要注意的标签页标题的高亮色
+    highlight: egui::Color32,
 }
 
 impl<'a> TabViewer for VerbiumTabViewer<'a> {
@@ -27,20 +44,44 @@ impl<'a> TabViewer for VerbiumTabViewer<'a> {
     }
 
     fn title(&mut self, tab: &mut Self::Tab) -> egui::WidgetText {
-        tab.instance.title()
+        let title = match tab.instance.icon() {
+            Some(TabIcon::Emoji(emoji)) => format!("{} {}", emoji, tab.instance.title().text()).into(),
+            _ => tab.instance.title(),
+        };
+        if !self.attention.contains(&tab.id) {
+            return title;
+        }
+        // 每半秒在高亮色与普通色之间切换，形成闪烁提示
+        let color = if (self.time * 2.0) as i64 % 2 == 0 {
+            self.highlight
+        } else {
+            egui::Color32::GRAY
+        };
+        egui::RichText::new(format!("● {}", title.text())).color(color).into()
     }
 
     fn ui(&mut self, ui: &mut egui::Ui, tab: &mut Self::Tab) {
-        ui.push_id(tab.id, |ui| {
-            tab.instance.ui(ui, self.command_queue);
+        self.shown.insert(tab.id);
+        let command_queue = &mut *self.command_queue;
+        tab_scope(ui, tab.id, |ui| {
+            // 代替 dock 自带的滚动区域（其 ID 随节点变化），滚动位置跟随标签页
+            egui::ScrollArea::both()
+                .id_salt("tab_body")
+                .auto_shrink(false)
+                .show(ui, |ui| tab.instance.ui(ui, command_queue));
         });
     }
 
+    fn scroll_bars(&self, _tab: &Self::Tab) -> [bool; 2] {
+        [false, false]
+    }
+
     fn closeable(&mut self, _tab: &mut Self::Tab) -> bool {
         true
     }
 
-    fn on_close(&mut self, _tab: &mut Self::Tab) -> bool {
+    fn on_close(&mut self, tab: &mut Self::Tab) -> bool {
+        remember_closed_tab(self.closed_tabs, tab);
         true
     }
 
@@ -52,55 +93,178 @@ impl<'a> TabViewer for VerbiumTabViewer<'a> {
         _node: egui_dock::NodeIndex,
     ) {
         tab.instance.on_context_menu(ui, self.command_queue);
+        ui.separator();
+        if ui.button("Duplicate Tab").clicked() {
+            self.command_queue.push(AppCommand::OpenTab(Tab::new(tab.instance.duplicate())));
+            ui.close_menu();
+        }
+        if ui.button("Detach to Window").clicked() {
+            self.command_queue.push(AppCommand::DetachTab(tab.id));
+            ui.close_menu();
+        }
+    }
+}
+
+/// 在以标签页 ID 为根的 Ui 中绘制标签页内容。
+/// egui_dock 传入的 Ui 的 ID 由所在节点决定，标签页被拖到其它节点或移出到独立窗口后，
+/// 滚动位置、折叠状态等以 ID 为键保存在 egui 内存中的状态都会丢失；
+/// 这里使用只与标签页 ID 相关的顶层 Ui，使这些状态在移动前后保持一致
+fn tab_scope(ui: &mut egui::Ui, tab_id: u64, add_contents: impl FnOnce(&mut egui::Ui)) {
+    let builder = egui::UiBuilder::new()
+        .max_rect(ui.available_rect_before_wrap())
+        .layout(*ui.layout())
+        .style(ui.style().clone());
+    let mut child = egui::Ui::new(ui.ctx().clone(), ui.layer_id(), egui::Id::new(("verbium_tab", tab_id)), builder);
+    child.set_clip_rect(ui.clip_rect().intersect(child.max_rect()));
+    if !ui.is_enabled() {
+        child.disable();
+    }
+    add_contents(&mut child);
+    ui.advance_cursor_after_rect(child.min_rect());
+}
+
+/// 在文件管理器中选中该项：优先使用 FileManager1 D-Bus 接口（Nautilus、Dolphin、Nemo 等支持），
+/// 不可用时用 xdg-open 打开所在文件夹。D-Bus 调用要等文件管理器启动，因此放在后台线程中
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn reveal_with_file_manager(path: std::path::PathBuf) {
+    use std::process::{Command, Stdio};
+    std::thread::spawn(move || {
+        let path = std::fs::canonicalize(&path).unwrap_or(path);
+        let shown = Command::new("dbus-send")
+            .args([
+                "--session",
+                "--print-reply",
+                "--dest=org.freedesktop.FileManager1",
+                "/org/freedesktop/FileManager1",
+                "org.freedesktop.FileManager1.ShowItems",
+            ])
+            .arg(format!("array:string:{}", file_uri(&path)))
+            .arg("string:")
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .is_ok_and(|status| status.success());
+        if !shown {
+            let dir = if path.is_dir() { path.as_path() } else { path.parent().unwrap_or(&path) };
+            let _ = Command::new("xdg-open").arg(dir).spawn();
+        }
+    });
+}
+
+/// `file://` URI，除路径分隔符与不需转义的字符外都按字节百分号编码（dbus-send 的数组参数以逗号分隔，逗号也要编码）
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn file_uri(path: &std::path::Path) -> String {
+    use std::os::unix::ffi::OsStrExt;
+    let mut uri = String::from("file://");
+    for &byte in path.as_os_str().as_bytes() {
+        if byte.is_ascii_alphanumeric() || b"/-._~".contains(&byte) {
+            uri.push(byte as char);
+        } else {
+            uri.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    uri
+}
+
+/// 标签页是否打开着该文件。`target` 为规范化后的路径，相对路径、`..` 与符号链接指向同一文件时也算
+fn is_open_at(tab: &Tab, target: &std::path::Path) -> bool {
+    tab.instance
+        .file_path()
+        .is_some_and(|open| open == target || std::fs::canonicalize(open).is_ok_and(|open| open == target))
+}
+
+/// 通知标签页已关闭并保留一份副本，以便重新打开
+fn remember_closed_tab(closed_tabs: &mut Vec<Tab>, tab: &mut Tab) {
+    tab.instance.on_close();
+    closed_tabs.push(tab.clone());
+    if closed_tabs.len() > MAX_CLOSED_TABS {
+        closed_tabs.remove(0);
     }
 }
 
 // ----------------------------------------------------------------------------
-// Font Setup
+// File Open Dispatch
 // ----------------------------------------------------------------------------
 
-fn setup_custom_fonts(ctx: &egui::Context) {
-    let mut fonts = egui::FontDefinitions::default();
-
-    // 尝试加载系统字体以支持中文
-    // 优先寻找常见的系统路径
-    let mut font_loaded = false;
-
-    #[cfg(target_os = "windows")]
-    {
-        let windows_fonts = [
-            "C:\\Windows\\Fonts\\msyh.ttc",   // 微软雅黑
-            "C:\\Windows\\Fonts\\msyh.ttf",
-            "C:\\Windows\\Fonts\\simsun.ttc", // 宋体
-            "C:\\Windows\\Fonts\\simsun.ttf",
-        ];
-
-        for path in windows_fonts {
-            if std::path::Path::new(path).exists() {
-                if let Ok(font_data) = std::fs::read(path) {
-                    fonts.font_data.insert(
-                        "chinese_font".to_owned(),
-                        egui::FontData::from_owned(font_data),
-                    );
-                    font_loaded = true;
-                    break;
-                }
-            }
+/// 按用户关联与插件评分选择打开文件的插件
+/// 用户为扩展名指定的插件优先；其余按 OpenSupport 从高到低依次尝试，同分保持插件加载顺序
+fn open_with_best_plugin(
+    plugins: &mut [Box<dyn Plugin>],
+    config: &AppConfig,
+    path: &std::path::Path,
+) -> Option<Box<dyn TabInstance>> {
+    let preferred = config.association_for(path);
+
+    let mut candidates: Vec<(usize, OpenSupport)> = plugins
+        .iter()
+        .enumerate()
+        .map(|(idx, p)| (idx, p.open_support(path)))
+        .filter(|(_, support)| *support != OpenSupport::None)
+        .collect();
+    candidates.sort_by(|a, b| b.1.cmp(&a.1));
+
+    if let Some(name) = preferred {
+        if let Some(idx) = plugins.iter().position(|p| p.name() == name) {
+            candidates.retain(|(i, _)| *i != idx);
+            candidates.insert(0, (idx, OpenSupport::Exact));
         }
     }
 
-    // 如果加载成功，将其设为备选字体
-    if font_loaded {
-        if let Some(vec) = fonts.families.get_mut(&egui::FontFamily::Proportional) {
-            vec.push("chinese_font".to_owned());
-        }
-        if let Some(vec) = fonts.families.get_mut(&egui::FontFamily::Monospace) {
-            vec.push("chinese_font".to_owned());
+    for (idx, _) in candidates {
+        if let Some(instance) = plugins[idx].try_open_file(path) {
+            return Some(instance);
         }
     }
+    None
+}
+
+// ----------------------------------------------------------------------------
+// UI Zoom
+// ----------------------------------------------------------------------------
+
+const MIN_ZOOM: f32 = 0.5;
+const MAX_ZOOM: f32 = 3.0;
+const ZOOM_STEP: f32 = 0.1;
+/// 检查打开的文件列表是否变化、需要写入会话文件的间隔；退出时总会再检查一次
+const SESSION_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// 处理 Ctrl + = / Ctrl + - / Ctrl + 0 缩放快捷键
+/// zoom_factor 叠加在 native_pixels_per_point 之上，窗口在不同 DPI 的显示器间移动时仍保持比例
+fn handle_zoom_shortcuts(ctx: &egui::Context) {
+    let (zoom_in, zoom_out, reset) = ctx.input_mut(|i| {
+        let zoom_in = i.consume_key(egui::Modifiers::COMMAND, egui::Key::Equals)
+            | i.consume_key(egui::Modifiers::COMMAND, egui::Key::Plus);
+        let zoom_out = i.consume_key(egui::Modifiers::COMMAND, egui::Key::Minus);
+        let reset = i.consume_key(egui::Modifiers::COMMAND, egui::Key::Num0);
+        (zoom_in, zoom_out, reset)
+    });
+
+    let zoom = ctx.zoom_factor();
+    let new_zoom = if reset {
+        1.0
+    } else if zoom_in {
+        zoom + ZOOM_STEP
+    } else if zoom_out {
+        zoom - ZOOM_STEP
+    } else {
+        return;
+    };
+    ctx.set_zoom_factor(((new_zoom / ZOOM_STEP).round() * ZOOM_STEP).clamp(MIN_ZOOM, MAX_ZOOM));
+}
 
-    // 设置字体
-    ctx.set_fonts(fonts);
+/// 快速切换标签页（Ctrl + Tab）的状态
+#[derive(Default)]
+struct TabSwitcher {
+    query: String,
+    selected: usize,
+}
+
+/// 快速切换器中列出的标签页
+struct SwitcherEntry {
+    id: u64,
+    icon: Option<TabIcon>,
+    title: String,
+    detached: bool,
 }
 
 // ----------------------------------------------------------------------------
@@ -112,25 +276,122 @@ pub struct VerbiumApp {
     command_queue: Vec<AppCommand>,
     notifications: Vec<NotificationInstance>,
     show_settings: bool,
+    config: AppConfig,
+    new_association_ext: String,
+    /// 正在请求注意的标签页 ID
+    attention: HashSet<u64>,
+    /// 最近关闭的标签页（末尾为最新），用于重新打开
+    closed_tabs: Vec<Tab>,
+    /// 已移出到独立系统窗口（egui viewport）的标签页
+    detached_tabs: Vec<Tab>,
+    /// 上一帧绘制过的标签页，用于通知显示状态的变化
+    visible_tabs: HashSet<u64>,
+    power_monitor: PowerMonitor,
+    /// 上一帧省电模式是否生效，用于检测切换
+    battery_saver_active: bool,
+    /// 已写入会话文件的打开文件列表，变化时重新保存
+    session: SessionState,
+    /// 上次检查会话是否需要保存的时刻
+    session_checked: Option<std::time::Instant>,
+    tab_switcher: Option<TabSwitcher>,
+    /// 标签页获得焦点的顺序（末尾为最近），`AppCommand::InsertText` 据此选择编辑器
+    focus_history: Vec<u64>,
+    backups: BackupScheduler,
+    /// 工作区根目录，修改后保存并重新发布给插件
+    workspace: Workspace,
+    /// 打开的“从备份还原”窗口中列出的还原点
+    restore_picker: Option<Vec<RestorePoint>>,
 }
 
 impl VerbiumApp {
     pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
-        setup_custom_fonts(&cc.egui_ctx);
-        let dock_state = DockState::new(Vec::new());
+        // 缩放快捷键由宿主处理，以便持久化并通知标签页
+        cc.egui_ctx.options_mut(|o| o.zoom_with_keyboard = false);
+        // 插件在创建时读取各自的配置，已安排的还原必须在此之前应用
+        let restored = backup::apply_pending_restore(AppConfig::load().backup.keep);
         // 使用自动化注册函数
         let plugins = plugins::all_plugins();
 
+        let first_launch = AppConfig::is_first_launch();
+        let mut config = AppConfig::load();
+        let recent_changed = std::env::current_dir().is_ok_and(|dir| config.remember_workspace(dir));
+        // 首次启动时写入配置文件，之后只在最近工作区列表变化时保存
+        if first_launch || recent_changed {
+            config.save();
+        }
+        let workspace = Workspace::load();
+        workspace::install(&cc.egui_ctx, workspace.clone());
+        let has_cjk_font = fonts::setup_fonts(&cc.egui_ctx, &config.custom_fonts);
+        theme::install(&cc.egui_ctx, config.status_palette);
+        cc.egui_ctx.set_zoom_factor(config.ui_zoom.clamp(MIN_ZOOM, MAX_ZOOM));
+
+        // 根据启动策略决定初始标签页，首次启动总是显示欢迎页
+        let session = SessionState::load();
+        let mut command_queue = Vec::new();
+        let mut initial_tabs = Vec::new();
+        match config.startup {
+            StartupPolicy::RestoreSession if !first_launch => {
+                command_queue.extend(session.open_files.iter().filter(|p| p.exists()).cloned().map(AppCommand::OpenFile));
+            }
+            StartupPolicy::Empty if !first_launch => {}
+            _ => initial_tabs.push(Tab::new(Box::new(WelcomeTab::new(config.recent_workspaces.clone())))),
+        }
+        let dock_state = DockState::new(initial_tabs);
+
+        let mut notifications = match restored {
+            Some(Ok(point)) => vec![NotificationInstance {
+                message: format!("Restored backup from {}.", point.age_label()),
+                level: NotificationLevel::Success,
+                remaining_time: 6.0,
+                action: None,
+            }],
+            Some(Err(e)) => vec![NotificationInstance {
+                message: format!("Failed to restore backup: {}", e),
+                level: NotificationLevel::Error,
+                remaining_time: 6.0,
+                action: None,
+            }],
+            None => Vec::new(),
+        };
+        if !has_cjk_font {
+            notifications.push(NotificationInstance {
+                message: "No CJK font found. Add one under Settings → Fonts to display Chinese, Japanese and Korean text.".into(),
+                level: NotificationLevel::Warning,
+                remaining_time: 8.0,
+                action: None,
+            });
+        }
+
         let app = Self {
             dock_state,
             plugins,
-            command_queue: Vec::new(),
-            notifications: Vec::new(),
+            command_queue,
+            notifications,
             show_settings: false,
+            config,
+            new_association_ext: String::new(),
+            attention: HashSet::new(),
+            closed_tabs: Vec::new(),
+            detached_tabs: Vec::new(),
+            visible_tabs: HashSet::new(),
+            power_monitor: PowerMonitor::start(),
+            battery_saver_active: false,
+            session,
+            session_checked: None,
+            tab_switcher: None,
+            focus_history: Vec::new(),
+            backups: BackupScheduler::new(),
+            restore_picker: None,
+            workspace,
         };
         app
     }
 
+    fn publish_workspace(&self, ctx: &egui::Context) {
+        self.workspace.save();
+        workspace::install(ctx, self.workspace.clone());
+    }
+
     fn process_commands(&mut self, ctx: &egui::Context) {
         // 使用 while 循环处理，防止指令执行中产生新指令被遗漏
         let mut i = 0;
@@ -146,25 +407,66 @@ impl VerbiumApp {
                         all_tabs.push(tab.clone());
                         true
                     });
+                    // 独立窗口中的标签页也一并收回
+                    all_tabs.append(&mut self.detached_tabs);
                     if !all_tabs.is_empty() {
                         self.dock_state = DockState::new(all_tabs);
                     }
                 }
                 AppCommand::ResetLayout => {
+                    // 独立窗口中的标签页一并关闭
+                    let docked = self.dock_state.iter_all_tabs_mut().map(|(_, tab)| tab);
+                    for tab in docked.chain(self.detached_tabs.iter_mut()) {
+                        tab.instance.on_close();
+                    }
+                    self.detached_tabs.clear();
                     self.dock_state = DockState::new(Vec::new());
                 }
                 AppCommand::CloseTab(title) => {
+                    let closed_tabs = &mut self.closed_tabs;
                     self.dock_state.retain_tabs(|tab| {
-                        tab.instance.title().text() != title
+                        let keep = tab.instance.title().text() != title;
+                        if !keep {
+                            remember_closed_tab(closed_tabs, tab);
+                        }
+                        keep
                     });
                 }
                 AppCommand::OpenFile(path) => {
-                    for plugin in &mut self.plugins {
-                        if let Some(instance) = plugin.try_open_file(path) {
-                            self.dock_state.main_surface_mut().push_to_focused_leaf(Tab::new(instance));
+                    if let Some(instance) = open_with_best_plugin(&mut self.plugins, &self.config, path) {
+                        self.dock_state.main_surface_mut().push_to_focused_leaf(Tab::new(instance));
+                    }
+                }
+                AppCommand::OpenFileAt { path, line, column } => {
+                    // 优先复用已打开该文件的标签页（包括独立窗口中的）
+                    let target = std::fs::canonicalize(path).unwrap_or_else(|_| path.clone());
+                    let mut reused = false;
+                    let open_tabs = self.dock_state.iter_all_tabs_mut().map(|(_, tab)| tab);
+                    for tab in open_tabs.chain(self.detached_tabs.iter_mut()) {
+                        if is_open_at(tab, &target) {
+                            tab.instance.go_to(*line, *column);
+                            reused = true;
                             break;
                         }
                     }
+                    if reused {
+                        if let Some(location) = self.dock_state.find_tab_from(|tab| is_open_at(tab, &target)) {
+                            self.dock_state.set_active_tab(location);
+                        }
+                    } else if let Some(mut instance) = open_with_best_plugin(&mut self.plugins, &self.config, path) {
+                        instance.go_to(*line, *column);
+                        self.dock_state.main_surface_mut().push_to_focused_leaf(Tab::new(instance));
+                    }
+                }
+                AppCommand::OpenUrl(url) => {
+                    ctx.open_url(egui::OpenUrl::new_tab(url));
+                }
+                AppCommand::OpenInBrowser(url) => {
+                    if let Some(instance) = self.plugins.iter_mut().find_map(|p| p.create_browser(url)) {
+                        self.dock_state.main_surface_mut().push_to_focused_leaf(Tab::new(instance));
+                    } else {
+                        ctx.open_url(egui::OpenUrl::new_tab(url));
+                    }
                 }
                 AppCommand::RevealInShell(path) => {
                     #[cfg(target_os = "windows")]
@@ -181,15 +483,32 @@ impl VerbiumApp {
                         use std::process::Command;
                         let _ = Command::new("open").arg("-R").arg(path).spawn();
                     }
-                    #[cfg(target_os = "linux")]
-                    {
-                        use std::process::Command;
-                        let parent = if path.is_file() {
-                            path.parent().unwrap_or(path)
-                        } else {
-                            path
-                        };
-                        let _ = Command::new("xdg-open").arg(parent).spawn();
+                    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+                    reveal_with_file_manager(path.clone());
+                }
+                AppCommand::OpenWith { path, program } => {
+                    use std::process::Command;
+                    let mut command = match program {
+                        Some(program) => Command::new(program),
+                        #[cfg(target_os = "windows")]
+                        // 经由 ShellExecute 用默认程序打开；不经过 cmd，文件名中的 & 等字符不会被当作命令解析
+                        None => {
+                            let mut command = Command::new("rundll32");
+                            command.arg("url.dll,FileProtocolHandler");
+                            command
+                        }
+                        #[cfg(target_os = "macos")]
+                        None => Command::new("open"),
+                        #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+                        None => Command::new("xdg-open"),
+                    };
+                    if let Err(e) = command.arg(path).spawn() {
+                        self.notifications.push(NotificationInstance {
+                            message: format!("Failed to open {}: {}", path.display(), e),
+                            level: NotificationLevel::Error,
+                            remaining_time: 4.0,
+                            action: None,
+                        });
                     }
                 }
                 AppCommand::CopyToClipboard(text) => {
@@ -200,11 +519,174 @@ impl VerbiumApp {
                         message: message.clone(),
                         level: level.clone(),
                         remaining_time: 4.0,
+                        action: None,
+                    });
+                }
+                AppCommand::NotifyWithAction { message, level, action } => {
+                    self.notifications.push(NotificationInstance {
+                        message: message.clone(),
+                        level: level.clone(),
+                        remaining_time: 8.0,
+                        action: Some(action.clone()),
                     });
                 }
                 AppCommand::ToggleSettings => {
                     self.show_settings = !self.show_settings;
                 }
+                AppCommand::ReopenLastClosed => {
+                    if let Some(tab) = self.closed_tabs.pop() {
+                        self.dock_state.main_surface_mut().push_to_focused_leaf(tab);
+                    }
+                }
+                AppCommand::DetachTab(id) => {
+                    let mut detached = None;
+                    self.dock_state.retain_tabs(|tab| {
+                        if tab.id == *id {
+                            detached = Some(tab.clone());
+                            false
+                        } else {
+                            true
+                        }
+                    });
+                    if let Some(tab) = detached {
+                        self.detached_tabs.push(tab);
+                    }
+                }
+                AppCommand::RunInTerminal { cwd, program, args } => {
+                    // 优先复用当前激活的终端，其次任意已打开的终端，都没有时由插件新建
+                    let mut target = None;
+                    if let Some((_, tab)) = self.dock_state.find_active_focused() {
+                        if tab.instance.run_in_terminal(cwd, program, args) {
+                            target = Some(tab.id);
+                        }
+                    }
+                    if target.is_none() {
+                        for (_, tab) in self.dock_state.iter_all_tabs_mut() {
+                            if tab.instance.run_in_terminal(cwd, program, args) {
+                                target = Some(tab.id);
+                                break;
+                            }
+                        }
+                    }
+
+                    if let Some(id) = target {
+                        if let Some(location) = self.dock_state.find_tab_from(|tab| tab.id == id) {
+                            self.dock_state.set_active_tab(location);
+                        }
+                    } else if let Some(mut instance) = self.plugins.iter_mut().find_map(|p| p.create_terminal(ctx, cwd)) {
+                        instance.run_in_terminal(cwd, program, args);
+                        self.dock_state.main_surface_mut().push_to_focused_leaf(Tab::new(instance));
+                    } else {
+                        self.notifications.push(NotificationInstance {
+                            message: "No terminal plugin is enabled".into(),
+                            level: NotificationLevel::Warning,
+                            remaining_time: 4.0,
+                            action: None,
+                        });
+                    }
+                }
+                AppCommand::TerminalSignal { tab, signal } => {
+                    let target = match tab {
+                        Some(id) => self.dock_state.iter_all_tabs_mut().map(|(_, t)| t)
+                            .chain(self.detached_tabs.iter_mut())
+                            .find(|t| t.id == *id),
+                        None => self.dock_state.find_active_focused().map(|(_, t)| t),
+                    };
+                    let result = match target {
+                        Some(t) => t.instance.send_signal(*signal),
+                        None => Err("No terminal to send the signal to".into()),
+                    };
+                    if let Err(e) = result {
+                        self.notifications.push(NotificationInstance {
+                            message: format!("Failed to send signal: {}", e),
+                            level: NotificationLevel::Error,
+                            remaining_time: 4.0,
+                            action: None,
+                        });
+                    }
+                }
+                AppCommand::RedockTab(id) => {
+                    if let Some(pos) = self.detached_tabs.iter().position(|t| t.id == *id) {
+                        let tab = self.detached_tabs.remove(pos);
+                        self.dock_state.main_surface_mut().push_to_focused_leaf(tab);
+                    }
+                }
+                AppCommand::SetWorkspaceRoot(path) => {
+                    self.workspace.set_root(path.clone());
+                    self.publish_workspace(ctx);
+                }
+                AppCommand::AddWorkspaceFolder(path) => {
+                    if self.workspace.add_root(path.clone()) {
+                        self.publish_workspace(ctx);
+                    }
+                }
+                AppCommand::RemoveWorkspaceFolder(path) => {
+                    if self.workspace.remove_root(path) {
+                        self.publish_workspace(ctx);
+                    }
+                }
+                AppCommand::InsertText(text) => {
+                    // 从最近获得焦点的标签页开始，交给第一个接受文本的标签页
+                    let mut target = None;
+                    for id in self.focus_history.iter().rev() {
+                        let tab = self.dock_state.iter_all_tabs_mut().map(|(_, tab)| tab).find(|tab| tab.id == *id);
+                        if tab.is_some_and(|tab| tab.instance.insert_text(text)) {
+                            target = Some(*id);
+                            break;
+                        }
+                    }
+                    match target.and_then(|id| self.dock_state.find_tab_from(|tab| tab.id == id)) {
+                        Some(location) => self.dock_state.set_active_tab(location),
+                        None => self.notifications.push(NotificationInstance {
+                            message: "No open editor to insert into".into(),
+                            level: NotificationLevel::Warning,
+                            remaining_time: 4.0,
+                            action: None,
+                        }),
+                    }
+                }
+                AppCommand::NewDocument { text, language } => {
+                    if let Some(instance) = self.plugins.iter_mut().find_map(|p| p.create_document(text, language)) {
+                        self.dock_state.main_surface_mut().push_to_focused_leaf(Tab::new(instance));
+                    } else {
+                        self.notifications.push(NotificationInstance {
+                            message: "No editor plugin is enabled".into(),
+                            level: NotificationLevel::Warning,
+                            remaining_time: 4.0,
+                            action: None,
+                        });
+                    }
+                }
+                AppCommand::ApplyPatch { path, patch } => {
+                    let path = if path.is_relative() {
+                        self.workspace.roots.iter().map(|root| root.join(path)).find(|p| p.exists()).unwrap_or_else(|| path.clone())
+                    } else {
+                        path.clone()
+                    };
+                    // 优先交给已打开该文件的标签页（包括独立窗口中的），否则先打开文件
+                    let target = std::fs::canonicalize(&path).unwrap_or_else(|_| path.clone());
+                    let open_tabs = self.dock_state.iter_all_tabs_mut().map(|(_, tab)| tab);
+                    let mut applied = open_tabs
+                        .chain(self.detached_tabs.iter_mut())
+                        .find(|tab| is_open_at(tab, &target))
+                        .map(|tab| tab.instance.apply_patch(patch));
+                    if applied.is_some() {
+                        if let Some(location) = self.dock_state.find_tab_from(|tab| is_open_at(tab, &target)) {
+                            self.dock_state.set_active_tab(location);
+                        }
+                    } else if let Some(mut instance) = path.is_file().then(|| open_with_best_plugin(&mut self.plugins, &self.config, &path)).flatten() {
+                        applied = Some(instance.apply_patch(patch));
+                        self.dock_state.main_surface_mut().push_to_focused_leaf(Tab::new(instance));
+                    }
+                    if applied != Some(true) {
+                        self.notifications.push(NotificationInstance {
+                            message: format!("Can't apply the changes to {}", path.display()),
+                            level: NotificationLevel::Warning,
+                            remaining_time: 4.0,
+                            action: None,
+                        });
+                    }
+                }
             }
             i += 1;
         }
@@ -212,7 +694,555 @@ impl VerbiumApp {
     }
 }
 
+impl VerbiumApp {
+    /// 拖出停靠区域的标签页会被 egui_dock 放进主窗口内的浮动窗口，这里把它们移入独立的原生窗口
+    fn detach_floating_tabs(&mut self) {
+        let mut surfaces: Vec<egui_dock::SurfaceIndex> = self.dock_state
+            .iter_all_tabs()
+            .map(|((surface, _), _)| surface)
+            .filter(|surface| !surface.is_main())
+            .collect();
+        surfaces.sort();
+        surfaces.dedup();
+        for surface in surfaces.into_iter().rev() {
+            let tabs: Vec<Tab> = self.dock_state
+                .iter_all_tabs()
+                .filter(|((s, _), _)| *s == surface)
+                .map(|(_, tab)| tab.clone())
+                .collect();
+            self.dock_state.remove_surface(surface);
+            self.detached_tabs.extend(tabs);
+        }
+    }
+
+    /// 与上一帧比较绘制过的标签页，通知开始或停止显示的标签页
+    fn update_tab_visibility(&mut self, shown: HashSet<u64>) {
+        let docked = self.dock_state.iter_all_tabs_mut().map(|(_, tab)| tab);
+        for tab in docked.chain(self.detached_tabs.iter_mut()) {
+            let visible = shown.contains(&tab.id);
+            if visible != self.visible_tabs.contains(&tab.id) {
+                tab.instance.on_visibility_changed(visible);
+            }
+        }
+        self.visible_tabs = shown;
+    }
+
+    /// 为每个独立标签页渲染一个原生窗口，关闭窗口即关闭标签页
+    fn show_detached_tabs(&mut self, ctx: &egui::Context, shown: &mut HashSet<u64>) {
+        let mut closed = Vec::new();
+        let mut focused = None;
+        for tab in &mut self.detached_tabs {
+            let tab_id = tab.id;
+            shown.insert(tab_id);
+            let title = tab.instance.title().text().to_string();
+            let command_queue = &mut self.command_queue;
+            let viewport_id = egui::ViewportId::from_hash_of(("verbium_detached_tab", tab_id));
+            let builder = egui::ViewportBuilder::default()
+                .with_title(format!("{} - Verbium", title))
+                .with_inner_size([800.0, 600.0]);
+
+            ctx.show_viewport_immediate(viewport_id, builder, |ctx, _class| {
+                egui::TopBottomPanel::top("detached_tab_bar").show(ctx, |ui| {
+                    ui.horizontal(|ui| {
+                        if let Some(icon) = tab.instance.icon() {
+                            icon.show(ui);
+                        }
+                        ui.label(egui::RichText::new(&title).strong());
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            if ui.button("⤓ Dock Back").clicked() {
+                                command_queue.push(AppCommand::RedockTab(tab_id));
+                            }
+                        });
+                    });
+                });
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    tab_scope(ui, tab_id, |ui| {
+                        egui::ScrollArea::both()
+                            .id_salt("tab_body")
+                            .auto_shrink(false)
+                            .show(ui, |ui| tab.instance.ui(ui, command_queue));
+                    });
+                });
+                if ctx.input(|i| i.viewport().close_requested()) {
+                    closed.push(tab_id);
+                }
+                if ctx.input(|i| i.viewport().focused.unwrap_or(false)) {
+                    focused = Some(tab_id);
+                }
+            });
+        }
+
+        // 获得焦点的独立窗口不再需要提示
+        if let Some(id) = focused {
+            self.attention.remove(&id);
+        }
+
+        if !closed.is_empty() {
+            let closed_tabs = &mut self.closed_tabs;
+            self.detached_tabs.retain_mut(|tab| {
+                let keep = !closed.contains(&tab.id);
+                if !keep {
+                    remember_closed_tab(closed_tabs, tab);
+                }
+                keep
+            });
+        }
+    }
+
+    /// 快速切换器：按标题过滤所有标签页（包括独立窗口中的），回车或点击切换
+    fn show_tab_switcher(&mut self, ctx: &egui::Context) {
+        let Some(switcher) = &mut self.tab_switcher else { return };
+
+        let query = switcher.query.to_lowercase();
+        let docked = self.dock_state.iter_all_tabs().map(|(_, tab)| (tab, false));
+        let detached = self.detached_tabs.iter().map(|tab| (tab, true));
+        let entries: Vec<SwitcherEntry> = docked
+            .chain(detached)
+            .map(|(tab, detached)| SwitcherEntry {
+                id: tab.id,
+                icon: tab.instance.icon(),
+                title: tab.instance.title().text().to_string(),
+                detached,
+            })
+            .filter(|e| e.title.to_lowercase().contains(&query))
+            .collect();
+
+        let (up, down, enter, escape) = ctx.input_mut(|i| (
+            i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp),
+            i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown),
+            i.consume_key(egui::Modifiers::NONE, egui::Key::Enter),
+            i.consume_key(egui::Modifiers::NONE, egui::Key::Escape),
+        ));
+        if up {
+            switcher.selected = switcher.selected.saturating_sub(1);
+        }
+        if down {
+            switcher.selected += 1;
+        }
+        switcher.selected = switcher.selected.min(entries.len().saturating_sub(1));
+
+        let mut chosen = if enter { entries.get(switcher.selected) } else { None };
+        egui::Window::new("Switch Tab")
+            .collapsible(false)
+            .resizable(false)
+            .title_bar(false)
+            .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 60.0))
+            .show(ctx, |ui| {
+                ui.set_width(360.0);
+                ui.add(egui::TextEdit::singleline(&mut switcher.query).hint_text("Search open tabs...").desired_width(f32::INFINITY))
+                    .request_focus();
+                ui.separator();
+                if entries.is_empty() {
+                    ui.weak("No matching tabs.");
+                }
+                for (i, entry) in entries.iter().enumerate() {
+                    let response = ui.horizontal(|ui| {
+                        if let Some(icon) = &entry.icon {
+                            icon.show(ui);
+                        }
+                        let label = ui.selectable_label(i == switcher.selected, &entry.title);
+                        if entry.detached {
+                            ui.weak("(window)");
+                        }
+                        label
+                    }).inner;
+                    if response.clicked() {
+                        chosen = Some(entry);
+                    }
+                }
+            });
+
+        if let Some(entry) = chosen {
+            if entry.detached {
+                let viewport_id = egui::ViewportId::from_hash_of(("verbium_detached_tab", entry.id));
+                ctx.send_viewport_cmd_to(viewport_id, egui::ViewportCommand::Focus);
+            } else if let Some(location) = self.dock_state.find_tab_from(|tab| tab.id == entry.id) {
+                self.dock_state.set_active_tab(location);
+            }
+        }
+        if chosen.is_some() || escape {
+            self.tab_switcher = None;
+        }
+    }
+
+    /// 到达备份间隔时创建还原点，仅在失败时提示
+    fn run_scheduled_backup(&mut self) {
+        let plugins = &self.plugins;
+        if let Some(Err(e)) = self.backups.tick(&self.config.backup, || plugin_backup_files(plugins)) {
+            self.notifications.push(NotificationInstance {
+                message: format!("Automatic backup failed: {}", e),
+                level: NotificationLevel::Warning,
+                remaining_time: 4.0,
+                action: None,
+            });
+        }
+    }
+
+    /// “从备份还原”窗口
+    fn show_restore_picker(&mut self, ctx: &egui::Context) {
+        let Some(points) = &self.restore_picker else { return };
+        let mut open = true;
+        let mut chosen = None;
+        egui::Window::new("Restore from Backup")
+            .open(&mut open)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.weak("The restore is applied the next time Verbium starts and overwrites the current settings, sessions and bookmarks. The current state is backed up first.");
+                if let Some(pending) = backup::pending_restore() {
+                    ui.horizontal(|ui| {
+                        ui.label(format!("⏳ Backup from {} will be restored on next start.", pending.age_label()));
+                        if ui.button("Cancel").clicked() {
+                            backup::cancel_pending_restore();
+                        }
+                    });
+                }
+                ui.separator();
+                if points.is_empty() {
+                    ui.weak("No restore points yet.");
+                }
+                egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                    egui::Grid::new("restore_points_grid").num_columns(3).striped(true).show(ui, |ui| {
+                        for point in points {
+                            let name = point.dir.file_name().unwrap_or_default().to_string_lossy().to_string();
+                            ui.label(point.age_label()).on_hover_text(name);
+                            ui.weak(format!("{} files", point.files.len())).on_hover_text(
+                                point.files.iter().map(|f| f.path.display().to_string()).collect::<Vec<_>>().join("\n"),
+                            );
+                            if ui.button("Restore").clicked() {
+                                chosen = Some(point.clone());
+                            }
+                            ui.end_row();
+                        }
+                    });
+                });
+            });
+
+        if let Some(point) = chosen {
+            self.restore_backup(&point);
+            open = false;
+        }
+        if !open {
+            self.restore_picker = None;
+        }
+    }
+
+    /// 安排在下次启动时还原：运行中的插件退出时会保存内存中的配置，立即覆盖的文件会被写回
+    fn restore_backup(&mut self, point: &RestorePoint) {
+        let (message, level) = match backup::schedule_restore(point) {
+            Ok(()) => (
+                format!("Backup from {} will be restored the next time Verbium starts.", point.age_label()),
+                NotificationLevel::Success,
+            ),
+            Err(e) => (format!("Failed to schedule restore: {}", e), NotificationLevel::Error),
+        };
+        self.notifications.push(NotificationInstance {
+            message,
+            level,
+            remaining_time: 6.0,
+            action: None,
+        });
+    }
+
+    /// 缩放倍数变化（快捷键或设置面板）时保存配置并通知所有标签页
+    fn sync_zoom(&mut self, ctx: &egui::Context) {
+        let zoom = ctx.zoom_factor();
+        if (zoom - self.config.ui_zoom).abs() < f32::EPSILON {
+            return;
+        }
+        self.config.ui_zoom = zoom;
+        self.config.save();
+        let docked = self.dock_state.iter_all_tabs_mut().map(|(_, tab)| tab);
+        for tab in docked.chain(self.detached_tabs.iter_mut()) {
+            tab.instance.on_zoom_changed(zoom);
+        }
+    }
+
+    /// 打开的文件列表变化时写入会话文件，供下次启动恢复
+    fn save_session(&mut self) {
+        let docked = self.dock_state.iter_all_tabs().map(|(_, tab)| tab);
+        let open_files: Vec<_> = docked
+            .chain(self.detached_tabs.iter())
+            .filter_map(|tab| tab.instance.file_path().map(|p| p.to_path_buf()))
+            .collect();
+        if open_files != self.session.open_files {
+            self.session.open_files = open_files;
+            self.session.save();
+        }
+    }
+
+    /// 根据配置与电源状态计算本帧的性能参数并写入 Context
+    fn update_power_profile(&mut self, ctx: &egui::Context) {
+        let settings = &self.config.performance;
+        let saver_active = match settings.battery_saver {
+            BatterySaverMode::Off => false,
+            BatterySaverMode::Always => true,
+            BatterySaverMode::Auto => self.power_monitor.on_battery().unwrap_or(false),
+        };
+
+        if saver_active != self.battery_saver_active {
+            self.battery_saver_active = saver_active;
+            // 省电模式下关闭 UI 动画
+            let animation_time = if saver_active { 0.0 } else { egui::Style::default().animation_time };
+            ctx.style_mut(|s| s.animation_time = animation_time);
+        }
+
+        PowerProfile::from_settings(settings, saver_active).store(ctx);
+    }
+}
+
+/// 设置窗口中的自动保存、轮询间隔与省电模式配置
+fn performance_settings_ui(ui: &mut egui::Ui, config: &mut AppConfig, on_battery: Option<bool>, saver_active: bool) {
+    let perf = &mut config.performance;
+    let mut changed = false;
+
+    egui::Grid::new("performance_grid").num_columns(2).show(ui, |ui| {
+        ui.label("Autosave interval (s, 0 = off):");
+        changed |= ui.add(egui::DragValue::new(&mut perf.autosave_interval_secs).range(0..=3600)).changed();
+        ui.end_row();
+
+        ui.label("File watcher interval (ms):");
+        changed |= ui.add(egui::DragValue::new(&mut perf.watcher_interval_ms).range(100..=60_000)).changed();
+        ui.end_row();
+
+        ui.label("Background repaint interval (ms):")
+            .on_hover_text("Minimum delay between repaints requested by background activity such as terminal output. Input still redraws immediately, so this is not a hard frame-rate cap.");
+        changed |= ui.add(egui::DragValue::new(&mut perf.background_repaint_ms).range(0..=5000)).changed();
+        ui.end_row();
+
+        ui.label("Battery saver:");
+        ui.horizontal(|ui| {
+            changed |= ui.radio_value(&mut perf.battery_saver, BatterySaverMode::Off, "Off").changed();
+            changed |= ui.radio_value(&mut perf.battery_saver, BatterySaverMode::Auto, "On battery").changed();
+            changed |= ui.radio_value(&mut perf.battery_saver, BatterySaverMode::Always, "Always").changed();
+        });
+        ui.end_row();
+    });
+
+    let source = match on_battery {
+        Some(true) => "Battery",
+        Some(false) => "AC power",
+        None => "Unknown",
+    };
+    ui.weak(format!(
+        "Power source: {} — battery saver {}",
+        source,
+        if saver_active { "active (watchers paused, background repaints slowed to once a second, no animations)" } else { "inactive" }
+    ));
+
+    if changed {
+        config.save();
+    }
+}
+
+/// 设置窗口中的自动备份配置与手动备份 / 还原入口
+fn backup_settings_ui(
+    ui: &mut egui::Ui,
+    config: &mut AppConfig,
+    scheduler: &mut BackupScheduler,
+    plugins: &[Box<dyn Plugin>],
+    restore_picker: &mut Option<Vec<RestorePoint>>,
+    command_queue: &mut Vec<AppCommand>,
+) {
+    let settings = &mut config.backup;
+    let mut changed = false;
+
+    egui::Grid::new("backup_grid").num_columns(2).show(ui, |ui| {
+        ui.label("Backup interval (h, 0 = off):");
+        changed |= ui.add(egui::DragValue::new(&mut settings.interval_hours).range(0..=24 * 30)).changed();
+        ui.end_row();
+
+        ui.label("Restore points to keep:");
+        changed |= ui.add(egui::DragValue::new(&mut settings.keep).range(1..=100)).changed();
+        ui.end_row();
+    });
+    ui.weak("Settings, plugin configs, the session, the workspace, chat sessions and bookmarks are copied into .verbium_backups in the workspace.");
+
+    ui.horizontal(|ui| {
+        if ui.button("Back Up Now").clicked() {
+            let (message, level) = match backup::create_restore_point(settings.keep, &plugin_backup_files(plugins)) {
+                Ok(point) => {
+                    scheduler.backed_up(&point);
+                    (format!("Backed up {} files", point.files.len()), NotificationLevel::Success)
+                }
+                Err(e) => (format!("Backup failed: {}", e), NotificationLevel::Error),
+            };
+            command_queue.push(AppCommand::Notify { message, level });
+        }
+        if ui.button("Restore from Backup…").clicked() {
+            *restore_picker = Some(backup::list_restore_points());
+        }
+    });
+
+    if changed {
+        config.save();
+    }
+}
+
+/// 各插件提供的需要备份的状态文件
+fn plugin_backup_files(plugins: &[Box<dyn Plugin>]) -> Vec<std::path::PathBuf> {
+    plugins.iter().flat_map(|p| p.backup_files()).collect()
+}
+
+/// 设置窗口中的界面缩放
+fn appearance_settings_ui(ui: &mut egui::Ui, config: &mut AppConfig) {
+    let ctx = ui.ctx().clone();
+    let edit_id = ui.id().with("zoom_edit");
+    let mut zoom = ui.data(|d| d.get_temp::<f32>(edit_id)).unwrap_or_else(|| ctx.zoom_factor());
+
+    ui.horizontal(|ui| {
+        ui.label("UI zoom:");
+        let slider = egui::Slider::new(&mut zoom, MIN_ZOOM..=MAX_ZOOM)
+            .step_by(ZOOM_STEP as f64)
+            .custom_formatter(|v, _| format!("{:.0}%", v * 100.0));
+        let response = ui.add(slider);
+        // 拖动过程中暂不缩放，避免界面在指针下跳动
+        if response.dragged() {
+            ui.data_mut(|d| d.insert_temp(edit_id, zoom));
+        } else {
+            if response.changed() || response.drag_stopped() {
+                ctx.set_zoom_factor(zoom);
+            }
+            ui.data_mut(|d| d.remove::<f32>(edit_id));
+        }
+        if ui.button("Reset").clicked() {
+            ctx.set_zoom_factor(1.0);
+        }
+    });
+    ui.weak("Ctrl + = / Ctrl + - / Ctrl + 0. Applied on top of each monitor's own DPI scale.");
+
+    ui.horizontal(|ui| {
+        ui.label("Status colors:");
+        let before = config.status_palette;
+        egui::ComboBox::from_id_salt("status_palette")
+            .selected_text(config.status_palette.label())
+            .show_ui(ui, |ui| {
+                for preset in theme::PalettePreset::ALL {
+                    ui.selectable_value(&mut config.status_palette, preset, preset.label());
+                }
+            });
+        if config.status_palette != before {
+            theme::install(&ctx, config.status_palette);
+            config.save();
+        }
+    });
+    // 预览当前配色
+    let palette = theme::palette(&ctx);
+    ui.horizontal(|ui| {
+        for (name, color) in [
+            ("Info", palette.info),
+            ("Success", palette.success),
+            ("Warning", palette.warning),
+            ("Error", palette.error),
+            ("Added", palette.added),
+            ("Modified", palette.modified),
+            ("Deleted", palette.deleted),
+        ] {
+            ui.label(egui::RichText::new(name).color(color).strong());
+        }
+    });
+}
+
+/// 设置窗口中的自定义字体列表，修改后立即重新加载字体
+fn font_settings_ui(ui: &mut egui::Ui, config: &mut AppConfig) {
+    ui.weak("Extra font files are used as fallbacks before the system CJK font.");
+    let mut changed = false;
+    let mut to_remove = None;
+
+    for (i, path) in config.custom_fonts.iter().enumerate() {
+        ui.horizontal(|ui| {
+            ui.monospace(path.to_string_lossy());
+            if ui.small_button("🗑").clicked() {
+                to_remove = Some(i);
+            }
+        });
+    }
+    if let Some(i) = to_remove {
+        config.custom_fonts.remove(i);
+        changed = true;
+    }
+
+    if ui.button("Add Font File...").clicked() {
+        if let Some(path) = rfd::FileDialog::new().add_filter("Fonts", &["ttf", "otf", "ttc"]).pick_file() {
+            config.custom_fonts.push(path);
+            changed = true;
+        }
+    }
+
+    if changed {
+        config.save();
+        fonts::setup_fonts(ui.ctx(), &config.custom_fonts);
+    }
+}
+
+/// 设置窗口中的启动行为配置
+fn startup_settings_ui(ui: &mut egui::Ui, config: &mut AppConfig) {
+    let mut changed = false;
+    changed |= ui.radio_value(&mut config.startup, StartupPolicy::RestoreSession, "Restore previously open files").changed();
+    changed |= ui.radio_value(&mut config.startup, StartupPolicy::Welcome, "Show the Welcome tab").changed();
+    changed |= ui.radio_value(&mut config.startup, StartupPolicy::Empty, "Start with an empty layout").changed();
+    if changed {
+        config.save();
+    }
+}
+
+/// 设置窗口中的“扩展名 -> 默认插件”编辑界面
+fn file_associations_ui(ui: &mut egui::Ui, config: &mut AppConfig, new_ext: &mut String, plugin_names: &[String]) {
+    ui.weak("Choose which plugin opens a file extension by default.");
+    let mut changed = false;
+    let mut to_remove = None;
+
+    egui::Grid::new("file_associations_grid").num_columns(3).show(ui, |ui| {
+        for (ext, plugin_name) in config.file_associations.iter_mut() {
+            ui.monospace(format!(".{}", ext));
+            egui::ComboBox::from_id_salt(("assoc", ext.as_str()))
+                .selected_text(plugin_name.as_str())
+                .show_ui(ui, |ui| {
+                    for name in plugin_names {
+                        changed |= ui.selectable_value(plugin_name, name.clone(), name).changed();
+                    }
+                });
+            if ui.small_button("🗑").clicked() {
+                to_remove = Some(ext.clone());
+            }
+            ui.end_row();
+        }
+    });
+
+    if let Some(ext) = to_remove {
+        config.file_associations.remove(&ext);
+        changed = true;
+    }
+
+    ui.horizontal(|ui| {
+        ui.label("Extension:");
+        ui.add(egui::TextEdit::singleline(new_ext).desired_width(60.0));
+        let ext = new_ext.trim().trim_start_matches('.').to_lowercase();
+        if ui.add_enabled(!ext.is_empty(), egui::Button::new("Add")).clicked() {
+            let default_plugin = plugin_names.first().cloned().unwrap_or_default();
+            config.file_associations.entry(ext).or_insert(default_plugin);
+            new_ext.clear();
+            changed = true;
+        }
+    });
+
+    if changed {
+        config.save();
+    }
+}
+
 impl eframe::App for VerbiumApp {
+    /// 先通知所有标签页，再通知插件，不依赖对象的 Drop 顺序
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        let docked = self.dock_state.iter_all_tabs_mut().map(|(_, tab)| tab);
+        for tab in docked.chain(self.detached_tabs.iter_mut()).chain(self.closed_tabs.iter_mut()) {
+            tab.instance.on_app_exit();
+        }
+        for plugin in &mut self.plugins {
+            plugin.on_shutdown();
+        }
+        self.save_session();
+    }
+
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         // 0. 更新通知时间
         let dt = ctx.input(|i| i.stable_dt);
@@ -221,11 +1251,41 @@ impl eframe::App for VerbiumApp {
             n.remaining_time > 0.0
         });
 
+        self.update_power_profile(ctx);
+        self.run_scheduled_backup();
+
+        handle_zoom_shortcuts(ctx);
+        self.sync_zoom(ctx);
+
+        // 全局快捷键: Ctrl + Shift + T 重新打开关闭的标签页
+        if ctx.input_mut(|i| i.consume_key(egui::Modifiers::COMMAND | egui::Modifiers::SHIFT, egui::Key::T)) {
+            self.command_queue.push(AppCommand::ReopenLastClosed);
+        }
+        // Ctrl + Tab 打开 / 关闭快速切换器
+        if ctx.input_mut(|i| i.consume_key(egui::Modifiers::COMMAND, egui::Key::Tab)) {
+            self.tab_switcher = match self.tab_switcher {
+                Some(_) => None,
+                None => Some(TabSwitcher::default()),
+            };
+        }
+
         // 1. 插件逻辑更新
         for plugin in &mut self.plugins {
             plugin.update(&mut self.command_queue);
         }
 
+        // 轮询标签页的注意请求（后台事件如终端响铃、构建完成），并清理已关闭标签的记录
+        let mut live_ids = HashSet::new();
+        let docked = self.dock_state.iter_all_tabs_mut().map(|(_, tab)| tab);
+        for tab in docked.chain(self.detached_tabs.iter_mut()) {
+            live_ids.insert(tab.id);
+            if tab.instance.needs_attention() {
+                self.attention.insert(tab.id);
+            }
+        }
+        self.attention.retain(|id| live_ids.contains(id));
+        self.focus_history.retain(|id| live_ids.contains(id));
+
         // 2. 顶部栏渲染
         egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
             egui::menu::bar(ui, |ui| {
@@ -261,6 +1321,38 @@ impl eframe::App for VerbiumApp {
                 .open(&mut self.show_settings)
                 .show(ctx, |ui| {
                     egui::ScrollArea::vertical().show(ui, |ui| {
+                        ui.push_id("appearance", |ui| {
+                            ui.collapsing("Appearance", |ui| {
+                                appearance_settings_ui(ui, &mut self.config);
+                            });
+                        });
+                        ui.push_id("fonts", |ui| {
+                            ui.collapsing("Fonts", |ui| {
+                                font_settings_ui(ui, &mut self.config);
+                            });
+                        });
+                        ui.push_id("startup", |ui| {
+                            ui.collapsing("Startup", |ui| {
+                                startup_settings_ui(ui, &mut self.config);
+                            });
+                        });
+                        let plugin_names: Vec<String> = self.plugins.iter().map(|p| p.name().to_string()).collect();
+                        ui.push_id("file_associations", |ui| {
+                            ui.collapsing("File Associations", |ui| {
+                                file_associations_ui(ui, &mut self.config, &mut self.new_association_ext, &plugin_names);
+                            });
+                        });
+                        let on_battery = self.power_monitor.on_battery();
+                        ui.push_id("performance", |ui| {
+                            ui.collapsing("Performance & Power", |ui| {
+                                performance_settings_ui(ui, &mut self.config, on_battery, self.battery_saver_active);
+                            });
+                        });
+                        ui.push_id("backups", |ui| {
+                            ui.collapsing("Backups", |ui| {
+                                backup_settings_ui(ui, &mut self.config, &mut self.backups, &self.plugins, &mut self.restore_picker, &mut self.command_queue);
+                            });
+                        });
                         for plugin in &mut self.plugins {
                             let plugin_name = plugin.name().to_string();
                             ui.push_id(&plugin_name, |ui| {
@@ -277,9 +1369,15 @@ impl eframe::App for VerbiumApp {
         self.process_commands(ctx);
 
         // 5. 中心 Dock 区域
+        let mut shown = HashSet::new();
         egui::CentralPanel::default().show(ctx, |ui| {
             let mut viewer = VerbiumTabViewer {
                 command_queue: &mut self.command_queue,
+                attention: &self.attention,
+                closed_tabs: &mut self.closed_tabs,
+                shown: &mut shown,
+                time: ctx.input(|i| i.time),
+                highlight: theme::palette(ctx).warning,
             };
             let style = Style::from_egui(ui.style().as_ref());
 
@@ -291,16 +1389,37 @@ impl eframe::App for VerbiumApp {
                 .show_inside(ui, &mut viewer);
         });
 
+        // 5.1 独立窗口中的标签页（包括刚被拖出停靠区域的）
+        self.detach_floating_tabs();
+        self.show_detached_tabs(ctx, &mut shown);
+        self.update_tab_visibility(shown);
+        self.show_tab_switcher(ctx);
+        self.show_restore_picker(ctx);
+        if self.session_checked.is_none_or(|t| t.elapsed() >= SESSION_CHECK_INTERVAL) {
+            self.session_checked = Some(std::time::Instant::now());
+            self.save_session();
+        }
+
+        // 获得焦点的标签页不再需要提示
+        if let Some((_, tab)) = self.dock_state.find_active_focused() {
+            self.attention.remove(&tab.id);
+            if self.focus_history.last() != Some(&tab.id) {
+                let id = tab.id;
+                self.focus_history.retain(|other| *other != id);
+                self.focus_history.push(id);
+            }
+        }
+        if !self.attention.is_empty() {
+            ctx.request_repaint_after(std::time::Duration::from_millis(500));
+        }
+
         // 6. 渲染通知 (Toast)
         let mut offset = egui::vec2(-10.0, -10.0);
 
+        let palette = theme::palette(ctx);
+        let mut triggered = None;
         for (i, n) in self.notifications.iter().enumerate() {
-            let color = match n.level {
-                NotificationLevel::Info => egui::Color32::from_rgb(100, 150, 255),
-                NotificationLevel::Success => egui::Color32::from_rgb(100, 200, 100),
-                NotificationLevel::Warning => egui::Color32::from_rgb(255, 200, 100),
-                NotificationLevel::Error => egui::Color32::from_rgb(255, 100, 100),
-            };
+            let color = palette.level(&n.level);
 
             // 计算位置：右下角堆叠
             let area_id = egui::Id::new("notification").with(i);
@@ -321,12 +1440,24 @@ impl eframe::App for VerbiumApp {
                                 };
                                 ui.label(egui::RichText::new(icon).color(color).strong());
                                 ui.label(&n.message);
+                                if let Some(action) = &n.action {
+                                    if ui.button(&action.label).clicked() {
+                                        triggered = Some(i);
+                                    }
+                                }
                             });
                         });
                 });
             
             offset.y -= 45.0; // 向上堆叠
         }
+        if let Some(i) = triggered {
+            let n = self.notifications.remove(i);
+            if let Some(action) = n.action {
+                (action.run)(&mut self.command_queue);
+            }
+            ctx.request_repaint();
+        }
 
         if !self.notifications.is_empty() {
             ctx.request_repaint();
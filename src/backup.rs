@@ -0,0 +1,269 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// ----------------------------------------------------------------------------
+// 用户状态备份 / 还原点
+// ----------------------------------------------------------------------------
+
+/// 还原点所在目录（位于工作目录下）
+const BACKUP_DIR: &str = ".verbium_backups";
+/// 还原点目录名前缀，后接 Unix 时间戳（秒）；同一秒内的多个还原点再附加 `-序号`
+const POINT_PREFIX: &str = "restore-";
+/// 还原点中记录各文件原始位置的清单
+const MANIFEST_FILE: &str = "manifest.toml";
+/// 等待下次启动时应用的还原点（内容为还原点目录名）
+const PENDING_FILE: &str = "pending_restore";
+/// 两次检查是否需要备份的最小间隔
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// 保存在 AppConfig 中的备份设置
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct BackupSettings {
+    /// 自动备份间隔（小时），0 表示关闭
+    #[serde(default = "default_interval_hours")]
+    pub interval_hours: u32,
+    /// 最多保留的还原点数量，超出时删除最旧的
+    #[serde(default = "default_keep")]
+    pub keep: usize,
+}
+
+fn default_interval_hours() -> u32 { 24 }
+fn default_keep() -> usize { 10 }
+
+impl Default for BackupSettings {
+    fn default() -> Self {
+        Self {
+            interval_hours: default_interval_hours(),
+            keep: default_keep(),
+        }
+    }
+}
+
+/// 还原点中的一个文件：保存时的文件名与还原时写回的位置
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct BackedUpFile {
+    pub name: String,
+    pub path: PathBuf,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct Manifest {
+    #[serde(default)]
+    files: Vec<BackedUpFile>,
+}
+
+/// 一个还原点：某一时刻全部状态文件的副本
+#[derive(Clone, Debug)]
+pub struct RestorePoint {
+    pub dir: PathBuf,
+    pub created: SystemTime,
+    /// 同一秒内创建的还原点的序号
+    seq: u32,
+    pub files: Vec<BackedUpFile>,
+}
+
+impl RestorePoint {
+    /// 距今多久，例如 "3 h ago"
+    pub fn age_label(&self) -> String {
+        let secs = SystemTime::now().duration_since(self.created).unwrap_or_default().as_secs();
+        match secs {
+            0..=59 => "just now".to_string(),
+            60..=3599 => format!("{} min ago", secs / 60),
+            3600..=86_399 => format!("{} h ago", secs / 3600),
+            _ => format!("{} d ago", secs / 86_400),
+        }
+    }
+}
+
+/// 需要备份的状态文件：宿主的会话与工作区文件、各插件的 `*_config.toml`，
+/// 以及插件通过 `Plugin::backup_files` 提供的其他文件（聊天会话、书签等）
+fn state_files(root: &Path, extra: &[PathBuf]) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = std::fs::read_dir(root)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.is_file())
+        .filter(|p| {
+            let name = p.file_name().and_then(|s| s.to_str()).unwrap_or_default();
+            name == "verbium_session.toml" || name == "verbium_workspace.toml" || name.ends_with("_config.toml")
+        })
+        .collect();
+    files.extend(extra.iter().filter(|p| p.is_file()).map(|p| root.join(p)));
+    files.sort();
+    files.dedup();
+    files
+}
+
+/// 读取还原点的文件清单；没有清单的旧还原点中的文件都写回工作目录
+fn read_manifest(dir: &Path) -> Option<Vec<BackedUpFile>> {
+    if let Ok(content) = std::fs::read_to_string(dir.join(MANIFEST_FILE)) {
+        return toml::from_str::<Manifest>(&content).ok().map(|m| m.files);
+    }
+    let mut files: Vec<BackedUpFile> = std::fs::read_dir(dir)
+        .ok()?
+        .flatten()
+        .filter_map(|f| f.file_name().to_str().map(String::from))
+        .map(|name| BackedUpFile { path: PathBuf::from(&name), name })
+        .collect();
+    files.sort_by(|a, b| a.name.cmp(&b.name));
+    Some(files)
+}
+
+/// 列出全部还原点，最新在前
+pub fn list_restore_points() -> Vec<RestorePoint> {
+    let mut points: Vec<RestorePoint> = std::fs::read_dir(BACKUP_DIR)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_str()?.to_string();
+            let stamp = name.strip_prefix(POINT_PREFIX)?;
+            let (secs, seq) = stamp.split_once('-').unwrap_or((stamp, "0"));
+            let (secs, seq): (u64, u32) = (secs.parse().ok()?, seq.parse().ok()?);
+            let dir = entry.path();
+            let files = read_manifest(&dir)?;
+            Some(RestorePoint { dir, created: UNIX_EPOCH + Duration::from_secs(secs), seq, files })
+        })
+        .collect();
+    points.sort_by_key(|p| std::cmp::Reverse((p.created, p.seq)));
+    points
+}
+
+/// 立即创建还原点，并按 keep 清理旧的还原点。`extra` 为插件提供的状态文件
+pub fn create_restore_point(keep: usize, extra: &[PathBuf]) -> std::io::Result<RestorePoint> {
+    let root = std::env::current_dir()?;
+    let files = state_files(&root, extra);
+    if files.is_empty() {
+        return Err(std::io::Error::new(std::io::ErrorKind::NotFound, "no state files to back up"));
+    }
+    let created = SystemTime::now();
+    let secs = created.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+    // 同一秒内已有还原点（例如还原前的自动备份）时追加序号，不覆盖已有的还原点
+    let mut seq = 0;
+    let dir = loop {
+        let name = match seq {
+            0 => format!("{}{}", POINT_PREFIX, secs),
+            _ => format!("{}{}-{}", POINT_PREFIX, secs, seq),
+        };
+        let dir = Path::new(BACKUP_DIR).join(name);
+        if !dir.exists() {
+            break dir;
+        }
+        seq += 1;
+    };
+    std::fs::create_dir_all(&dir)?;
+    let mut backed_up = Vec::new();
+    for file in &files {
+        // 跳过无法解析的文件，避免把已损坏的配置当作还原点
+        let Ok(content) = std::fs::read_to_string(file) else { continue };
+        if content.parse::<toml::Table>().is_err() {
+            continue;
+        }
+        // 不同目录中可能有同名文件，保存时加上序号
+        let file_name = file.file_name().unwrap_or_default().to_string_lossy();
+        let name = format!("{:03}-{}", backed_up.len(), file_name);
+        std::fs::write(dir.join(&name), content)?;
+        let path = file.strip_prefix(&root).map(Path::to_path_buf).unwrap_or_else(|_| file.clone());
+        backed_up.push(BackedUpFile { name, path });
+    }
+    let manifest = Manifest { files: backed_up };
+    let content = toml::to_string_pretty(&manifest).map_err(std::io::Error::other)?;
+    std::fs::write(dir.join(MANIFEST_FILE), content)?;
+    prune(keep.max(1));
+    Ok(RestorePoint { dir, created, seq, files: manifest.files })
+}
+
+fn prune(keep: usize) {
+    for point in list_restore_points().into_iter().skip(keep) {
+        let _ = std::fs::remove_dir_all(&point.dir);
+    }
+}
+
+// ----------------------------------------------------------------------------
+// 还原：运行中的插件会在退出时保存内存中的配置，覆盖还原的文件，
+// 因此只记录要还原的还原点，在下次启动、插件加载之前应用
+// ----------------------------------------------------------------------------
+
+/// 安排在下次启动时还原该还原点
+pub fn schedule_restore(point: &RestorePoint) -> std::io::Result<()> {
+    let name = point.dir.file_name().unwrap_or_default().to_string_lossy().to_string();
+    std::fs::write(Path::new(BACKUP_DIR).join(PENDING_FILE), name)
+}
+
+/// 等待下次启动时应用的还原点
+pub fn pending_restore() -> Option<RestorePoint> {
+    let name = std::fs::read_to_string(Path::new(BACKUP_DIR).join(PENDING_FILE)).ok()?;
+    list_restore_points().into_iter().find(|p| p.dir.file_name().is_some_and(|n| n == name.trim()))
+}
+
+pub fn cancel_pending_restore() {
+    let _ = std::fs::remove_file(Path::new(BACKUP_DIR).join(PENDING_FILE));
+}
+
+/// 启动时在加载插件之前调用：应用已安排的还原，返回还原的还原点。
+/// 覆盖前先为当前状态创建一个还原点，以便撤销
+pub fn apply_pending_restore(keep: usize) -> Option<std::io::Result<RestorePoint>> {
+    let point = pending_restore();
+    // 无论成败都只尝试一次，避免每次启动重复还原
+    cancel_pending_restore();
+    let point = point?;
+    let current: Vec<PathBuf> = point.files.iter().map(|f| f.path.clone()).collect();
+    // 保留要还原的还原点，不让预先备份把它挤出保留数量
+    let _ = create_restore_point(keep.max(1) + 1, &current);
+    let result = point.files.iter().try_for_each(|file| {
+        if let Some(parent) = file.path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::copy(point.dir.join(&file.name), &file.path).map(|_| ())
+    });
+    Some(result.map(|()| point))
+}
+
+/// 宿主持有的定时备份调度
+pub struct BackupScheduler {
+    last_backup: Option<SystemTime>,
+    last_check: Option<std::time::Instant>,
+}
+
+impl BackupScheduler {
+    pub fn new() -> Self {
+        Self {
+            last_backup: list_restore_points().first().map(|p| p.created),
+            last_check: None,
+        }
+    }
+
+    /// 每帧调用；距上次备份超过设定间隔时创建还原点。`extra` 仅在需要备份时调用
+    pub fn tick(&mut self, settings: &BackupSettings, extra: impl FnOnce() -> Vec<PathBuf>) -> Option<std::io::Result<RestorePoint>> {
+        if settings.interval_hours == 0 {
+            return None;
+        }
+        if self.last_check.is_some_and(|t| t.elapsed() < CHECK_INTERVAL) {
+            return None;
+        }
+        self.last_check = Some(std::time::Instant::now());
+        let interval = Duration::from_secs(settings.interval_hours as u64 * 3600);
+        let due = self.last_backup
+            .map(|t| SystemTime::now().duration_since(t).unwrap_or_default() >= interval)
+            .unwrap_or(true);
+        if !due {
+            return None;
+        }
+        let result = create_restore_point(settings.keep, &extra());
+        // 失败时同样推迟到下一个周期，避免每分钟重复报错
+        self.last_backup = Some(SystemTime::now());
+        Some(result)
+    }
+
+    pub fn backed_up(&mut self, point: &RestorePoint) {
+        self.last_backup = Some(point.created);
+    }
+}
+
+impl Default for BackupScheduler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
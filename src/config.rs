@@ -0,0 +1,130 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use crate::backup::BackupSettings;
+use crate::power::PerformanceSettings;
+use crate::theme::PalettePreset;
+
+const CONFIG_FILE: &str = "verbium_config.toml";
+const SESSION_FILE: &str = "verbium_session.toml";
+/// 最近工作区列表的最大长度
+const MAX_RECENT_WORKSPACES: usize = 10;
+
+/// 启动时的初始标签页
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum StartupPolicy {
+    /// 重新打开上次退出时打开的文件
+    RestoreSession,
+    /// 显示欢迎页
+    #[default]
+    Welcome,
+    /// 空白布局
+    Empty,
+}
+
+/// 宿主程序的全局配置，保存在工作目录下的 verbium_config.toml
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AppConfig {
+    /// 按扩展名（小写、不含点）指定默认打开的插件，例如 "md" -> "markdown"
+    #[serde(default)]
+    pub file_associations: BTreeMap<String, String>,
+    /// 自动保存、文件监视与后台刷新间隔，以及省电模式
+    #[serde(default)]
+    pub performance: PerformanceSettings,
+    #[serde(default)]
+    pub startup: StartupPolicy,
+    /// 最近启动过的工作目录（最新在前）
+    #[serde(default)]
+    pub recent_workspaces: Vec<PathBuf>,
+    /// 界面缩放倍数，叠加在显示器自身的 DPI 缩放之上
+    #[serde(default = "default_zoom")]
+    pub ui_zoom: f32,
+    /// 用户指定的额外字体文件（作为回退字体）
+    #[serde(default)]
+    pub custom_fonts: Vec<PathBuf>,
+    /// 通知 / 版本控制 / 差异显示使用的状态配色
+    #[serde(default)]
+    pub status_palette: PalettePreset,
+    /// 自动备份间隔与还原点保留数量
+    #[serde(default)]
+    pub backup: BackupSettings,
+}
+
+fn default_zoom() -> f32 { 1.0 }
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        Self {
+            file_associations: BTreeMap::new(),
+            performance: PerformanceSettings::default(),
+            startup: StartupPolicy::default(),
+            recent_workspaces: Vec::new(),
+            ui_zoom: default_zoom(),
+            custom_fonts: Vec::new(),
+            status_palette: PalettePreset::default(),
+            backup: BackupSettings::default(),
+        }
+    }
+}
+
+impl AppConfig {
+    pub fn load() -> Self {
+        let path = Path::new(CONFIG_FILE);
+        if path.exists() {
+            if let Ok(content) = std::fs::read_to_string(path) {
+                return toml::from_str(&content).unwrap_or_default();
+            }
+        }
+        Self::default()
+    }
+
+    pub fn save(&self) {
+        if let Ok(content) = toml::to_string_pretty(self) {
+            let _ = std::fs::write(CONFIG_FILE, content);
+        }
+    }
+
+    /// 配置文件不存在即视为首次启动
+    pub fn is_first_launch() -> bool {
+        !Path::new(CONFIG_FILE).exists()
+    }
+
+    /// 将工作目录记录到最近工作区列表的最前面，返回列表是否变化
+    pub fn remember_workspace(&mut self, dir: PathBuf) -> bool {
+        if self.recent_workspaces.first() == Some(&dir) {
+            return false;
+        }
+        self.recent_workspaces.retain(|p| p != &dir);
+        self.recent_workspaces.insert(0, dir);
+        self.recent_workspaces.truncate(MAX_RECENT_WORKSPACES);
+        true
+    }
+
+    /// 查询该文件扩展名关联的插件名
+    pub fn association_for(&self, path: &Path) -> Option<&str> {
+        let ext = path.extension()?.to_str()?.to_lowercase();
+        self.file_associations.get(&ext).map(|s| s.as_str())
+    }
+}
+
+/// 上次会话中打开的文件，用于 `StartupPolicy::RestoreSession`
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct SessionState {
+    #[serde(default)]
+    pub open_files: Vec<PathBuf>,
+}
+
+impl SessionState {
+    pub fn load() -> Self {
+        std::fs::read_to_string(SESSION_FILE)
+            .ok()
+            .and_then(|content| toml::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        if let Ok(content) = toml::to_string_pretty(self) {
+            let _ = std::fs::write(SESSION_FILE, content);
+        }
+    }
+}
@@ -0,0 +1,670 @@
+use egui::text::{LayoutJob, TextFormat};
+use egui::{Color32, RichText, Ui, WidgetText};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use crate::{AppCommand, NotificationLevel, TabIcon, TabInstance};
+
+/// 编辑距离超过该值时放弃逐行比较，剩余部分整体视为删除 + 新增
+const MAX_EDIT_DISTANCE: usize = 4000;
+/// 行内比较的最大字符数，更长的行只标记整行
+const MAX_INLINE_CHARS: usize = 1000;
+
+// ----------------------------------------------------------------------------
+// 差异计算
+// ----------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Edit {
+    /// (旧序号, 新序号)
+    Equal(usize, usize),
+    Delete(usize),
+    Insert(usize),
+}
+
+/// Myers 差异算法；先去掉公共前后缀以缩小比较范围
+fn diff<T: PartialEq>(a: &[T], b: &[T]) -> Vec<Edit> {
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..].iter().rev().zip(b[prefix..].iter().rev()).take_while(|(x, y)| x == y).count();
+    let (mid_a, mid_b) = (&a[prefix..a.len() - suffix], &b[prefix..b.len() - suffix]);
+
+    let mut edits: Vec<Edit> = (0..prefix).map(|i| Edit::Equal(i, i)).collect();
+    edits.extend(myers(mid_a, mid_b).into_iter().map(|edit| match edit {
+        Edit::Equal(i, j) => Edit::Equal(i + prefix, j + prefix),
+        Edit::Delete(i) => Edit::Delete(i + prefix),
+        Edit::Insert(j) => Edit::Insert(j + prefix),
+    }));
+    edits.extend((0..suffix).map(|i| Edit::Equal(a.len() - suffix + i, b.len() - suffix + i)));
+    edits
+}
+
+fn myers<T: PartialEq>(a: &[T], b: &[T]) -> Vec<Edit> {
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    let max = (n + m) as usize;
+    let replace_all = || (0..a.len()).map(Edit::Delete).chain((0..b.len()).map(Edit::Insert)).collect();
+    if max == 0 {
+        return Vec::new();
+    }
+
+    let offset = max as isize;
+    let mut v = vec![0isize; 2 * max + 2];
+    // trace[d] 保存第 d 轮结束时 k ∈ [-d, d] 的最远 x
+    let mut trace: Vec<Vec<isize>> = Vec::new();
+    let mut found = None;
+    'outer: for d in 0..=max.min(MAX_EDIT_DISTANCE) as isize {
+        for k in (-d..=d).step_by(2) {
+            let idx = (k + offset) as usize;
+            let mut x = if k == -d || (k != d && v[idx - 1] < v[idx + 1]) { v[idx + 1] } else { v[idx - 1] + 1 };
+            let mut y = x - k;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[idx] = x;
+            if x >= n && y >= m {
+                trace.push(v[(offset - d) as usize..=(offset + d) as usize].to_vec());
+                found = Some(d);
+                break 'outer;
+            }
+        }
+        trace.push(v[(offset - d) as usize..=(offset + d) as usize].to_vec());
+    }
+    let Some(distance) = found else {
+        return replace_all();
+    };
+
+    let mut edits = Vec::new();
+    let (mut x, mut y) = (n, m);
+    for d in (1..=distance).rev() {
+        let previous = &trace[d as usize - 1];
+        let at = |k: isize| previous[(k + d - 1) as usize];
+        let k = x - y;
+        let prev_k = if k == -d || (k != d && at(k - 1) < at(k + 1)) { k + 1 } else { k - 1 };
+        let prev_x = at(prev_k);
+        let prev_y = prev_x - prev_k;
+        while x > prev_x && y > prev_y {
+            x -= 1;
+            y -= 1;
+            edits.push(Edit::Equal(x as usize, y as usize));
+        }
+        if prev_k == k + 1 {
+            edits.push(Edit::Insert(prev_y as usize));
+        } else {
+            edits.push(Edit::Delete(prev_x as usize));
+        }
+        x = prev_x;
+        y = prev_y;
+    }
+    while x > 0 && y > 0 {
+        x -= 1;
+        y -= 1;
+        edits.push(Edit::Equal(x as usize, y as usize));
+    }
+    edits.reverse();
+    edits
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RowKind {
+    Equal,
+    Changed,
+    Deleted,
+    Inserted,
+}
+
+/// 并排视图中的一行：左右两侧的行号（0 起始）与行内变化的字节范围
+#[derive(Debug, Clone)]
+struct DiffRow {
+    kind: RowKind,
+    left: Option<usize>,
+    right: Option<usize>,
+    left_changes: Vec<Range<usize>>,
+    right_changes: Vec<Range<usize>>,
+}
+
+/// 行内差异：返回两侧变化的字节范围（相邻的合并）
+fn inline_changes(old: &str, new: &str) -> (Vec<Range<usize>>, Vec<Range<usize>>) {
+    let old_chars: Vec<(usize, char)> = old.char_indices().collect();
+    let new_chars: Vec<(usize, char)> = new.char_indices().collect();
+    if old_chars.len() > MAX_INLINE_CHARS || new_chars.len() > MAX_INLINE_CHARS {
+        return (vec![0..old.len()], vec![0..new.len()]);
+    }
+    let a: Vec<char> = old_chars.iter().map(|(_, c)| *c).collect();
+    let b: Vec<char> = new_chars.iter().map(|(_, c)| *c).collect();
+    let push = |ranges: &mut Vec<Range<usize>>, chars: &[(usize, char)], i: usize| {
+        let (start, c) = chars[i];
+        let end = start + c.len_utf8();
+        match ranges.last_mut() {
+            Some(last) if last.end == start => last.end = end,
+            _ => ranges.push(start..end),
+        }
+    };
+    let (mut left, mut right) = (Vec::new(), Vec::new());
+    for edit in diff(&a, &b) {
+        match edit {
+            Edit::Delete(i) => push(&mut left, &old_chars, i),
+            Edit::Insert(j) => push(&mut right, &new_chars, j),
+            Edit::Equal(..) => {}
+        }
+    }
+    (left, right)
+}
+
+/// 把逐行差异排成并排的行：连续的删除与新增按顺序两两配对为修改行
+fn build_rows(old: &[&str], new: &[&str]) -> Vec<DiffRow> {
+    let mut rows = Vec::new();
+    let mut deleted = Vec::new();
+    let mut inserted = Vec::new();
+    let flush = |rows: &mut Vec<DiffRow>, deleted: &mut Vec<usize>, inserted: &mut Vec<usize>| {
+        for i in 0..deleted.len().max(inserted.len()) {
+            let (left, right) = (deleted.get(i).copied(), inserted.get(i).copied());
+            let (kind, (left_changes, right_changes)) = match (left, right) {
+                (Some(l), Some(r)) => (RowKind::Changed, inline_changes(old[l], new[r])),
+                (Some(_), None) => (RowKind::Deleted, Default::default()),
+                _ => (RowKind::Inserted, Default::default()),
+            };
+            rows.push(DiffRow { kind, left, right, left_changes, right_changes });
+        }
+        deleted.clear();
+        inserted.clear();
+    };
+    for edit in diff(old, new) {
+        match edit {
+            Edit::Delete(i) => deleted.push(i),
+            Edit::Insert(j) => inserted.push(j),
+            Edit::Equal(i, j) => {
+                flush(&mut rows, &mut deleted, &mut inserted);
+                rows.push(DiffRow { kind: RowKind::Equal, left: Some(i), right: Some(j), left_changes: Vec::new(), right_changes: Vec::new() });
+            }
+        }
+    }
+    flush(&mut rows, &mut deleted, &mut inserted);
+    rows
+}
+
+/// 相对基准版本的行变化，用于在编辑器行号栏中标注
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineChange {
+    Added,
+    Modified,
+    /// 该行之前有行被删除
+    Deleted,
+}
+
+/// 当前文本中各行（0 起始）相对基准文本的变化，按行号排序
+pub fn line_changes(base: &str, current: &str) -> Vec<(usize, LineChange)> {
+    let old: Vec<&str> = base.lines().collect();
+    let new: Vec<&str> = current.lines().collect();
+    let mut changes = Vec::new();
+    let (mut deleted, mut inserted) = (0, Vec::new());
+    let mut flush = |deleted: &mut usize, inserted: &mut Vec<usize>, next_line: usize| {
+        for (i, &line) in inserted.iter().enumerate() {
+            changes.push((line, if i < *deleted { LineChange::Modified } else { LineChange::Added }));
+        }
+        if inserted.is_empty() && *deleted > 0 {
+            changes.push((next_line.min(new.len().saturating_sub(1)), LineChange::Deleted));
+        }
+        *deleted = 0;
+        inserted.clear();
+    };
+    for edit in diff(&old, &new) {
+        match edit {
+            Edit::Delete(_) => deleted += 1,
+            Edit::Insert(j) => inserted.push(j),
+            Edit::Equal(_, j) => flush(&mut deleted, &mut inserted, j),
+        }
+    }
+    flush(&mut deleted, &mut inserted, new.len());
+    changes
+}
+
+// ----------------------------------------------------------------------------
+// 统一格式补丁
+// ----------------------------------------------------------------------------
+
+#[derive(Debug, Clone, PartialEq)]
+enum HunkLine {
+    Context(String),
+    Remove(String),
+    Add(String),
+}
+
+#[derive(Debug, Clone)]
+struct Hunk {
+    /// `@@ -l,s +l,s @@` 中旧文本的起始行（从 1 开始），缺失时为 0
+    old_start: usize,
+    lines: Vec<HunkLine>,
+}
+
+/// 补丁中针对一个文件的部分
+#[derive(Debug, Clone)]
+pub struct FilePatch {
+    /// `+++` 行中的路径（删除文件时取 `---` 行），已去掉 `a/`、`b/` 前缀；没有文件头时为空
+    pub path: String,
+    /// 这一部分的原始文本，可以单独交给 `apply_patch`
+    pub text: String,
+    hunks: Vec<Hunk>,
+}
+
+fn header_path(header: &str) -> String {
+    let path = header.split('\t').next().unwrap_or_default().trim();
+    path.strip_prefix("a/").or_else(|| path.strip_prefix("b/")).unwrap_or(path).to_string()
+}
+
+fn hunk_start(header: &str) -> usize {
+    header
+        .split_whitespace()
+        .find_map(|part| part.strip_prefix('-'))
+        .and_then(|range| range.split(',').next())
+        .and_then(|start| start.parse().ok())
+        .unwrap_or(0)
+}
+
+/// 解析统一格式补丁。模型生成的补丁行数常常不准确，因此不依赖 `@@` 中的行数，
+/// `diff --git`、`index` 等其它行忽略
+pub fn parse_patch(patch: &str) -> Vec<FilePatch> {
+    let lines: Vec<&str> = patch.lines().collect();
+    let mut files: Vec<FilePatch> = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i];
+        if let (Some(old), Some(new)) = (line.strip_prefix("--- "), lines.get(i + 1).and_then(|l| l.strip_prefix("+++ "))) {
+            let (old, new) = (header_path(old), header_path(new));
+            files.push(FilePatch {
+                path: if new == "/dev/null" { old } else { new },
+                text: format!("{}\n{}\n", line, lines[i + 1]),
+                hunks: Vec::new(),
+            });
+            i += 2;
+            continue;
+        }
+        i += 1;
+        if line.starts_with("@@") {
+            if files.is_empty() {
+                files.push(FilePatch { path: String::new(), text: String::new(), hunks: Vec::new() });
+            }
+            let Some(file) = files.last_mut() else { continue };
+            file.hunks.push(Hunk { old_start: hunk_start(line), lines: Vec::new() });
+            file.text.push_str(line);
+            file.text.push('\n');
+            continue;
+        }
+        let Some(file) = files.last_mut() else { continue };
+        let Some(hunk) = file.hunks.last_mut() else { continue };
+        let parsed = match line.chars().next() {
+            Some('+') => HunkLine::Add(line[1..].to_string()),
+            Some('-') => HunkLine::Remove(line[1..].to_string()),
+            Some(' ') => HunkLine::Context(line[1..].to_string()),
+            // 空行通常是去掉了前导空格的空上下文行
+            None => HunkLine::Context(String::new()),
+            _ => continue,
+        };
+        hunk.lines.push(parsed);
+        file.text.push_str(line);
+        file.text.push('\n');
+    }
+    files
+}
+
+/// 从 `hint` 开始向两侧查找与 `old` 相同的连续行（忽略行尾空白），位置不早于 `min_pos`
+fn find_lines(lines: &[String], old: &[&str], hint: usize, min_pos: usize) -> Option<usize> {
+    if old.is_empty() {
+        return Some(hint.clamp(min_pos.min(lines.len()), lines.len()));
+    }
+    let last = lines.len().checked_sub(old.len())?;
+    if min_pos > last {
+        return None;
+    }
+    let matches = |pos: usize| lines[pos..pos + old.len()].iter().zip(old).all(|(a, b)| a.trim_end() == b.trim_end());
+    let hint = hint.clamp(min_pos, last);
+    (0..=last).find_map(|d| {
+        let after = hint + d;
+        let before = hint.checked_sub(d).filter(|&p| d > 0 && p >= min_pos);
+        [Some(after).filter(|&p| p <= last), before].into_iter().flatten().find(|&p| matches(p))
+    })
+}
+
+/// 把补丁中的所有改动依次应用到 `text`。按上下文定位，允许行号偏移；
+/// 保留原文的换行符风格与末尾换行
+pub fn apply_patch(text: &str, patch: &str) -> Result<String, String> {
+    let files = parse_patch(patch);
+    let hunks: Vec<&Hunk> = files.iter().flat_map(|f| &f.hunks).collect();
+    if hunks.iter().all(|h| h.lines.iter().all(|l| matches!(l, HunkLine::Context(_)))) {
+        return Err("The patch contains no changes".to_string());
+    }
+    let newline = if text.contains("\r\n") { "\r\n" } else { "\n" };
+    let mut lines: Vec<String> = text.lines().map(String::from).collect();
+    let (mut offset, mut min_pos) = (0isize, 0usize);
+    for (n, hunk) in hunks.iter().enumerate() {
+        let old: Vec<&str> = hunk
+            .lines
+            .iter()
+            .filter_map(|l| match l {
+                HunkLine::Context(s) | HunkLine::Remove(s) => Some(s.as_str()),
+                HunkLine::Add(_) => None,
+            })
+            .collect();
+        let new: Vec<String> = hunk
+            .lines
+            .iter()
+            .filter_map(|l| match l {
+                HunkLine::Context(s) | HunkLine::Add(s) => Some(s.clone()),
+                HunkLine::Remove(_) => None,
+            })
+            .collect();
+        let hint = (hunk.old_start.saturating_sub(1) as isize + offset).max(0) as usize;
+        let pos = find_lines(&lines, &old, hint, min_pos).ok_or_else(|| format!("Change {} does not match the current text", n + 1))?;
+        offset += new.len() as isize - old.len() as isize;
+        min_pos = pos + new.len();
+        lines.splice(pos..pos + old.len(), new);
+    }
+    let mut result = lines.join(newline);
+    if text.ends_with('\n') && !result.is_empty() {
+        result.push_str(newline);
+    }
+    Ok(result)
+}
+
+// ----------------------------------------------------------------------------
+// Diff Tab
+// ----------------------------------------------------------------------------
+
+/// 比较的一侧：文件内容或其它来源（例如 git 中的某个版本）的文本
+#[derive(Debug, Clone)]
+pub struct DiffSide {
+    pub label: String,
+    pub text: String,
+    /// 来自磁盘文件时可以刷新
+    pub path: Option<PathBuf>,
+}
+
+impl DiffSide {
+    pub fn new(label: impl Into<String>, text: impl Into<String>) -> Self {
+        Self { label: label.into(), text: text.into(), path: None }
+    }
+
+    /// 读取文件；非 UTF-8 的字节以替换字符显示
+    pub fn from_file(path: &Path) -> std::io::Result<Self> {
+        let bytes = std::fs::read(path)?;
+        Ok(Self {
+            label: path.file_name().unwrap_or_default().to_string_lossy().to_string(),
+            text: String::from_utf8_lossy(&bytes).replace("\r\n", "\n"),
+            path: Some(path.to_path_buf()),
+        })
+    }
+}
+
+/// 两段文本的差异视图，支持并排与内联两种布局
+#[derive(Debug, Clone)]
+pub struct DiffTab {
+    left: DiffSide,
+    right: DiffSide,
+    rows: Vec<DiffRow>,
+    /// 每处变化（连续的非相同行）的首行序号
+    hunks: Vec<usize>,
+    inline: bool,
+    current_hunk: Option<usize>,
+    scroll_to_row: Option<usize>,
+}
+
+impl DiffTab {
+    pub fn new(left: DiffSide, right: DiffSide) -> Self {
+        let mut tab = Self {
+            left,
+            right,
+            rows: Vec::new(),
+            hunks: Vec::new(),
+            inline: false,
+            current_hunk: None,
+            scroll_to_row: None,
+        };
+        tab.recompute();
+        tab
+    }
+
+    /// 比较两个文件
+    pub fn compare_files(left: &Path, right: &Path) -> std::io::Result<Self> {
+        Ok(Self::new(DiffSide::from_file(left)?, DiffSide::from_file(right)?))
+    }
+
+    /// 以内联布局打开（git 的变更视图默认使用）
+    pub fn inline(mut self) -> Self {
+        self.inline = true;
+        self
+    }
+
+    fn recompute(&mut self) {
+        let old: Vec<&str> = self.left.text.lines().collect();
+        let new: Vec<&str> = self.right.text.lines().collect();
+        self.rows = build_rows(&old, &new);
+        self.hunks = self
+            .rows
+            .iter()
+            .enumerate()
+            .filter(|(i, row)| row.kind != RowKind::Equal && (*i == 0 || self.rows[i - 1].kind == RowKind::Equal))
+            .map(|(i, _)| i)
+            .collect();
+        self.current_hunk = None;
+    }
+
+    fn reload(&mut self) -> std::io::Result<()> {
+        for side in [&mut self.left, &mut self.right] {
+            if let Some(path) = side.path.clone() {
+                *side = DiffSide::from_file(&path)?;
+            }
+        }
+        self.recompute();
+        Ok(())
+    }
+
+    fn go_to_hunk(&mut self, forward: bool) {
+        if self.hunks.is_empty() {
+            return;
+        }
+        let last = self.hunks.len() - 1;
+        let next = match (self.current_hunk, forward) {
+            (None, true) => 0,
+            (None, false) => last,
+            (Some(i), true) => if i >= last { 0 } else { i + 1 },
+            (Some(i), false) => if i == 0 { last } else { i - 1 },
+        };
+        self.current_hunk = Some(next);
+        self.scroll_to_row = Some(self.hunks[next]);
+    }
+
+    fn stats(&self) -> (usize, usize) {
+        self.rows.iter().fold((0, 0), |(added, removed), row| match row.kind {
+            RowKind::Equal => (added, removed),
+            RowKind::Changed => (added + 1, removed + 1),
+            RowKind::Deleted => (added, removed + 1),
+            RowKind::Inserted => (added + 1, removed),
+        })
+    }
+
+    fn toolbar_ui(&mut self, ui: &mut Ui, control: &mut Vec<AppCommand>) {
+        let palette = crate::theme::palette(ui.ctx());
+        ui.horizontal(|ui| {
+            ui.label(RichText::new(&self.left.label).color(palette.deleted));
+            ui.label("↔");
+            ui.label(RichText::new(&self.right.label).color(palette.added));
+            ui.separator();
+            let (added, removed) = self.stats();
+            if added + removed == 0 {
+                ui.weak("Identical");
+            } else {
+                ui.label(RichText::new(format!("+{}", added)).color(palette.added));
+                ui.label(RichText::new(format!("−{}", removed)).color(palette.deleted));
+            }
+
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                if (self.left.path.is_some() || self.right.path.is_some()) && ui.button("⟳").on_hover_text("Reload from disk").clicked() {
+                    if let Err(e) = self.reload() {
+                        control.push(AppCommand::Notify { message: format!("Failed to reload: {}", e), level: NotificationLevel::Error });
+                    }
+                }
+                ui.selectable_value(&mut self.inline, true, "Inline");
+                ui.selectable_value(&mut self.inline, false, "Side by Side");
+                ui.separator();
+                let has_hunks = !self.hunks.is_empty();
+                if ui.add_enabled(has_hunks, egui::Button::new("⏷")).on_hover_text("Next change (F7)").clicked() {
+                    self.go_to_hunk(true);
+                }
+                if ui.add_enabled(has_hunks, egui::Button::new("⏶")).on_hover_text("Previous change (Shift + F7)").clicked() {
+                    self.go_to_hunk(false);
+                }
+                if let Some(i) = self.current_hunk {
+                    ui.weak(format!("{} / {}", i + 1, self.hunks.len()));
+                }
+            });
+        });
+    }
+}
+
+/// 一行文字：整行背景色 + 行内变化的高亮
+fn line_job(text: &str, changes: &[Range<usize>], font_id: &egui::FontId, color: Color32, highlight: Color32) -> LayoutJob {
+    let mut job = LayoutJob::default();
+    let mut last = 0;
+    for range in changes {
+        job.append(&text[last..range.start], 0.0, TextFormat::simple(font_id.clone(), color));
+        job.append(&text[range.clone()], 0.0, TextFormat { background: highlight, ..TextFormat::simple(font_id.clone(), color) });
+        last = range.end;
+    }
+    job.append(&text[last..], 0.0, TextFormat::simple(font_id.clone(), color));
+    job
+}
+
+/// 在 rect 中绘制一侧的一行：行号栏、标记与文字
+#[allow(clippy::too_many_arguments)]
+fn paint_line(ui: &Ui, rect: egui::Rect, number: Option<usize>, marker: &str, text: &str, changes: &[Range<usize>], fill: Color32, highlight: Color32, gutter: f32) {
+    let painter = ui.painter().with_clip_rect(rect.intersect(ui.clip_rect()));
+    let font_id = egui::TextStyle::Monospace.resolve(ui.style());
+    let weak = ui.visuals().weak_text_color();
+    if fill != Color32::TRANSPARENT {
+        painter.rect_filled(rect, 0.0, fill);
+    }
+    if let Some(number) = number {
+        painter.text(rect.left_center() + egui::vec2(gutter - 12.0, 0.0), egui::Align2::RIGHT_CENTER, (number + 1).to_string(), font_id.clone(), weak);
+    }
+    painter.text(rect.left_center() + egui::vec2(gutter - 8.0, 0.0), egui::Align2::LEFT_CENTER, marker, font_id.clone(), weak);
+    let galley = ui.fonts(|f| f.layout_job(line_job(text, changes, &font_id, ui.visuals().text_color(), highlight)));
+    painter.galley(egui::pos2(rect.left() + gutter + 4.0, rect.center().y - galley.size().y / 2.0), galley, Color32::TRANSPARENT);
+}
+
+impl TabInstance for DiffTab {
+    fn title(&self) -> WidgetText {
+        format!("{} ↔ {}", self.left.label, self.right.label).into()
+    }
+
+    fn icon(&self) -> Option<TabIcon> {
+        Some(TabIcon::Emoji("🔀".into()))
+    }
+
+    fn ui(&mut self, ui: &mut Ui, control: &mut Vec<AppCommand>) {
+        if ui.rect_contains_pointer(ui.max_rect()) {
+            if ui.input_mut(|i| i.consume_key(egui::Modifiers::SHIFT, egui::Key::F7)) {
+                self.go_to_hunk(false);
+            } else if ui.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::F7)) {
+                self.go_to_hunk(true);
+            }
+        }
+        self.toolbar_ui(ui, control);
+        ui.separator();
+
+        let palette = crate::theme::palette(ui.ctx());
+        let (added_bg, deleted_bg) = (palette.added.gamma_multiply(0.15), palette.deleted.gamma_multiply(0.15));
+        let (added_hl, deleted_hl) = (palette.added.gamma_multiply(0.45), palette.deleted.gamma_multiply(0.45));
+        let font_id = egui::TextStyle::Monospace.resolve(ui.style());
+        let row_height = ui.fonts(|f| f.row_height(&font_id)) + 2.0;
+        let digits = self.left.text.lines().count().max(self.right.text.lines().count()).to_string().len().max(3);
+        let gutter = ui.fonts(|f| f.glyph_width(&font_id, '0')) * digits as f32 + 24.0;
+
+        let old: Vec<&str> = self.left.text.lines().collect();
+        let new: Vec<&str> = self.right.text.lines().collect();
+
+        // 内联布局：修改行拆成删除行与新增行，同一处变化中先列出全部删除再列出全部新增
+        let inline_rows: Vec<(usize, bool)> = if self.inline {
+            let mut rows = Vec::new();
+            let mut pending_new = Vec::new();
+            for (i, row) in self.rows.iter().enumerate() {
+                if row.kind == RowKind::Equal {
+                    rows.append(&mut pending_new);
+                    rows.push((i, false));
+                    continue;
+                }
+                if row.left.is_some() {
+                    rows.push((i, false));
+                }
+                if row.right.is_some() {
+                    pending_new.push((i, true));
+                }
+            }
+            rows.append(&mut pending_new);
+            rows
+        } else {
+            Vec::new()
+        };
+        let total_rows = if self.inline { inline_rows.len() } else { self.rows.len() };
+
+        let mut scroll = egui::ScrollArea::vertical().id_salt("diff_scroll").auto_shrink([false, false]);
+        if let Some(target) = self.scroll_to_row.take() {
+            let display_row = if self.inline { inline_rows.iter().position(|(i, _)| *i >= target).unwrap_or(0) } else { target };
+            let spacing = ui.spacing().item_spacing.y;
+            scroll = scroll.vertical_scroll_offset(display_row.saturating_sub(3) as f32 * (row_height + spacing));
+        }
+        scroll.show_rows(ui, row_height, total_rows, |ui, range| {
+            for display_row in range {
+                let (rect, _) = ui.allocate_exact_size(egui::vec2(ui.available_width(), row_height), egui::Sense::hover());
+                if self.inline {
+                    let (i, is_new) = inline_rows[display_row];
+                    let row = &self.rows[i];
+                    if row.kind == RowKind::Equal {
+                        paint_line(ui, rect, row.right, " ", new[row.right.unwrap_or(0)], &[], Color32::TRANSPARENT, Color32::TRANSPARENT, gutter);
+                    } else if is_new {
+                        let line = row.right.unwrap_or(0);
+                        paint_line(ui, rect, Some(line), "+", new[line], &row.right_changes, added_bg, added_hl, gutter);
+                    } else {
+                        let line = row.left.unwrap_or(0);
+                        paint_line(ui, rect, Some(line), "−", old[line], &row.left_changes, deleted_bg, deleted_hl, gutter);
+                    }
+                } else {
+                    let row = &self.rows[display_row];
+                    let half = rect.width() / 2.0;
+                    let left_rect = egui::Rect::from_min_size(rect.min, egui::vec2(half - 1.0, rect.height()));
+                    let right_rect = egui::Rect::from_min_size(rect.min + egui::vec2(half + 1.0, 0.0), egui::vec2(half - 1.0, rect.height()));
+                    let (left_fill, right_fill) = match row.kind {
+                        RowKind::Equal => (Color32::TRANSPARENT, Color32::TRANSPARENT),
+                        _ => (
+                            if row.left.is_some() { deleted_bg } else { ui.visuals().faint_bg_color },
+                            if row.right.is_some() { added_bg } else { ui.visuals().faint_bg_color },
+                        ),
+                    };
+                    let left_text = row.left.map_or("", |l| old[l]);
+                    let right_text = row.right.map_or("", |r| new[r]);
+                    let (left_marker, right_marker) = if row.kind == RowKind::Equal { (" ", " ") } else { ("−", "+") };
+                    paint_line(ui, left_rect, row.left, if row.left.is_some() { left_marker } else { "" }, left_text, &row.left_changes, left_fill, deleted_hl, gutter);
+                    paint_line(ui, right_rect, row.right, if row.right.is_some() { right_marker } else { "" }, right_text, &row.right_changes, right_fill, added_hl, gutter);
+                    ui.painter().vline(rect.center().x, rect.y_range(), ui.visuals().widgets.noninteractive.bg_stroke);
+                }
+            }
+        });
+    }
+
+    fn on_context_menu(&mut self, ui: &mut Ui, control: &mut Vec<AppCommand>) {
+        for side in [&self.left, &self.right] {
+            if let Some(path) = &side.path {
+                if ui.button(format!("Open {}", side.label)).clicked() {
+                    control.push(AppCommand::OpenFile(path.clone()));
+                    ui.close_menu();
+                }
+            }
+        }
+        if ui.button("Swap Sides").clicked() {
+            std::mem::swap(&mut self.left, &mut self.right);
+            self.recompute();
+            ui.close_menu();
+        }
+    }
+
+    fn box_clone(&self) -> Box<dyn TabInstance> {
+        Box::new(self.clone())
+    }
+}
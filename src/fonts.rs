@@ -0,0 +1,139 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+// ----------------------------------------------------------------------------
+// 字体发现与回退
+// ----------------------------------------------------------------------------
+
+/// 常见的 CJK 系统字体族，按优先级排列（覆盖 Windows / macOS / Linux）
+const CJK_FAMILIES: &[&str] = &[
+    // Windows
+    "Microsoft YaHei UI",
+    "Microsoft YaHei",
+    "SimSun",
+    "Yu Gothic UI",
+    "Malgun Gothic",
+    // macOS
+    "PingFang SC",
+    "Hiragino Sans GB",
+    "Heiti SC",
+    "Apple SD Gothic Neo",
+    // Linux
+    "Noto Sans CJK SC",
+    "Noto Sans SC",
+    "Source Han Sans SC",
+    "WenQuanYi Micro Hei",
+    "Droid Sans Fallback",
+];
+
+fn query_family(db: &fontdb::Database, family: &str) -> Option<egui::FontData> {
+    let query = fontdb::Query {
+        families: &[fontdb::Family::Name(family)],
+        ..Default::default()
+    };
+    let id = db.query(&query)?;
+    db.with_face_data(id, |data, index| egui::FontData {
+        index,
+        ..egui::FontData::from_owned(data.to_vec())
+    })
+}
+
+/// 通过系统字体枚举查找第一个可用的 CJK 字体
+fn discover_system_cjk_font() -> Option<egui::FontData> {
+    let mut db = fontdb::Database::new();
+    db.load_system_fonts();
+
+    CJK_FAMILIES.iter().find_map(|family| query_family(&db, family))
+}
+
+/// 按字体族名称加载系统字体的常规字形
+pub fn load_system_family(family: &str) -> Option<egui::FontData> {
+    let mut db = fontdb::Database::new();
+    db.load_system_fonts();
+    query_family(&db, family)
+}
+
+/// 系统中等宽字体族的名称，按字母排序
+pub fn monospace_families() -> Vec<String> {
+    let mut db = fontdb::Database::new();
+    db.load_system_fonts();
+    let mut names: Vec<String> = db.faces()
+        .filter(|face| face.monospaced)
+        .filter_map(|face| face.families.first().map(|(name, _)| name.clone()))
+        .collect();
+    names.sort();
+    names.dedup();
+    names
+}
+
+/// 插件注册的具名字体族（例如终端字体），重新安装字体时一并保留
+#[derive(Clone, Default)]
+struct NamedFamilies(BTreeMap<String, egui::FontData>);
+
+fn named_families_id() -> egui::Id { egui::Id::new("verbium_named_font_families") }
+fn base_fonts_id() -> egui::Id { egui::Id::new("verbium_base_fonts") }
+
+/// 注册或移除一个具名字体族 `FontFamily::Name(name)`：指定字体优先，缺字时回退到等宽字体链。
+/// 新字体从下一帧起生效，使用前应先用 [`has_family`] 检查
+pub fn set_named_family(ctx: &egui::Context, name: &str, data: Option<egui::FontData>) {
+    let mut named = ctx.data(|d| d.get_temp::<NamedFamilies>(named_families_id())).unwrap_or_default();
+    match data {
+        Some(data) => { named.0.insert(name.to_string(), data); }
+        None => { named.0.remove(name); }
+    }
+    ctx.data_mut(|d| d.insert_temp(named_families_id(), named));
+    if let Some(base) = ctx.data(|d| d.get_temp::<egui::FontDefinitions>(base_fonts_id())) {
+        install(ctx, base);
+    }
+}
+
+/// 当前生效的字体中是否已有该字体族
+pub fn has_family(ctx: &egui::Context, family: &egui::FontFamily) -> bool {
+    ctx.fonts(|f| f.lock().fonts.definitions().families.contains_key(family))
+}
+
+fn install(ctx: &egui::Context, mut fonts: egui::FontDefinitions) {
+    let named = ctx.data(|d| d.get_temp::<NamedFamilies>(named_families_id())).unwrap_or_default();
+    let monospace = fonts.families.get(&egui::FontFamily::Monospace).cloned().unwrap_or_default();
+    for (name, data) in named.0 {
+        let key = format!("named_family_{}", name);
+        fonts.font_data.insert(key.clone(), data);
+        let chain = std::iter::once(key).chain(monospace.iter().cloned()).collect();
+        fonts.families.insert(egui::FontFamily::Name(name.into()), chain);
+    }
+    ctx.set_fonts(fonts);
+}
+
+/// 安装字体：用户指定的字体文件优先，其次是系统 CJK 字体。
+/// 它们都作为 egui 默认字体之后的回退字体，不影响拉丁字符的显示。
+/// 返回是否有可用的回退字体；没有时 CJK 字符会显示为方框
+pub fn setup_fonts(ctx: &egui::Context, user_fonts: &[PathBuf]) -> bool {
+    let mut fonts = egui::FontDefinitions::default();
+    let mut fallbacks = Vec::new();
+
+    for (i, path) in user_fonts.iter().enumerate() {
+        match std::fs::read(path) {
+            Ok(data) => {
+                let key = format!("user_font_{}", i);
+                fonts.font_data.insert(key.clone(), egui::FontData::from_owned(data));
+                fallbacks.push(key);
+            }
+            Err(e) => eprintln!("Failed to load font {}: {}", path.display(), e),
+        }
+    }
+
+    if let Some(data) = discover_system_cjk_font() {
+        fonts.font_data.insert("cjk_fallback".to_owned(), data);
+        fallbacks.push("cjk_fallback".to_owned());
+    }
+
+    for family in [egui::FontFamily::Proportional, egui::FontFamily::Monospace] {
+        if let Some(vec) = fonts.families.get_mut(&family) {
+            vec.extend(fallbacks.iter().cloned());
+        }
+    }
+
+    ctx.data_mut(|d| d.insert_temp(base_fonts_id(), fonts.clone()));
+    install(ctx, fonts);
+    !fallbacks.is_empty()
+}
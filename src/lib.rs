@@ -4,6 +4,16 @@ use std::sync::atomic::{AtomicU64, Ordering};
 
 pub mod plugins;
 pub mod app;
+pub mod backup;
+pub mod config;
+pub mod diff;
+pub mod fonts;
+pub mod markdown;
+pub mod power;
+pub mod secrets;
+pub mod theme;
+pub mod welcome;
+pub mod workspace;
 
 static NEXT_TAB_ID: AtomicU64 = AtomicU64::new(1);
 
@@ -11,12 +21,65 @@ static NEXT_TAB_ID: AtomicU64 = AtomicU64::new(1);
 // Tab 抽象
 // ----------------------------------------------------------------------------
 
+/// 显示在标签标题前的图标
+#[derive(Clone)]
+pub enum TabIcon {
+    Emoji(String),
+    /// egui_dock 的标签栏只能显示文本，纹理图标仅在快速切换器和独立窗口标题栏中显示
+    Texture(egui::TextureHandle),
+}
+
+impl TabIcon {
+    /// 绘制图标，纹理按当前行高缩放
+    pub fn show(&self, ui: &mut Ui) -> egui::Response {
+        match self {
+            TabIcon::Emoji(emoji) => ui.label(emoji),
+            TabIcon::Texture(texture) => {
+                let size = ui.text_style_height(&egui::TextStyle::Body);
+                ui.add(egui::Image::new(egui::load::SizedTexture::new(texture.id(), egui::vec2(size, size))))
+            }
+        }
+    }
+}
+
 /// 插件必须实现这个 Trait 来定义自己的标签页内容
 pub trait TabInstance: Debug + Send + Sync {
     fn title(&self) -> WidgetText;
+    /// 标签图标，用于在标签栏中区分终端、编辑器、浏览器等
+    fn icon(&self) -> Option<TabIcon> { None }
     fn ui(&mut self, ui: &mut Ui, control: &mut Vec<AppCommand>);
     /// 标签页右键菜单钩子
     fn on_context_menu(&mut self, _ui: &mut Ui, _control: &mut Vec<AppCommand>) {}
+    /// 每帧由宿主轮询（包括不可见的标签页），返回 true 表示请求用户注意
+    /// 宿主会在标签标题上闪烁提示，直到该标签页获得焦点
+    fn needs_attention(&mut self) -> bool { false }
+    /// 标签页当前打开的文件，宿主据此复用已打开的标签页
+    fn file_path(&self) -> Option<&std::path::Path> { None }
+    /// 跳转到指定位置（行列号均从 1 开始），由 `AppCommand::OpenFileAt` 调用
+    fn go_to(&mut self, _line: usize, _column: usize) {}
+    /// 在终端中执行命令，由 `AppCommand::RunInTerminal` 调用；返回 false 表示该标签页不是终端
+    fn run_in_terminal(&mut self, _cwd: &std::path::Path, _program: &str, _args: &[String]) -> bool { false }
+    /// 向终端中的进程发送信号，由 `AppCommand::TerminalSignal` 调用；信号没有送达（包括该标签页不是终端）时返回原因
+    fn send_signal(&mut self, _signal: TerminalSignal) -> Result<(), String> { Err("This tab is not a terminal".into()) }
+    /// 在光标处插入文本，由 `AppCommand::InsertText` 调用；返回 false 表示该标签页不接受文本
+    fn insert_text(&mut self, _text: &str) -> bool { false }
+    /// 把统一格式的补丁应用到缓冲区，由 `AppCommand::ApplyPatch` 调用；返回 false 表示该标签页不能编辑
+    fn apply_patch(&mut self, _patch: &str) -> bool { false }
+    /// 全局界面缩放倍数变化后调用（包括不可见的标签页），用于重新计算依赖像素尺寸的布局
+    fn on_zoom_changed(&mut self, _zoom: f32) {}
+    /// 标签页开始或停止被绘制时调用（切换到同一节点的其他标签页、移入独立窗口等），
+    /// 用于显示或隐藏不由 egui 绘制的原生子窗口
+    fn on_visibility_changed(&mut self, _visible: bool) {}
+    /// 标签页被关闭时调用，用于释放原生资源。关闭的标签页会保留一份以便重新打开，
+    /// 之后再次绘制时应能重新创建这些资源
+    fn on_close(&mut self) {}
+    /// 应用退出前调用（包括不可见、独立窗口中以及最近关闭的标签页），用于结束子进程、写回未保存的数据
+    fn on_app_exit(&mut self) {}
+    /// 右键菜单“Duplicate Tab”调用，返回一个新的标签页。默认与 `box_clone` 相同；
+    /// 持有子进程等不能共享的资源时应返回独立的副本
+    fn duplicate(&self) -> Box<dyn TabInstance> {
+        self.box_clone()
+    }
     /// 用于克隆 Trait 对象
     fn box_clone(&self) -> Box<dyn TabInstance>;
 }
@@ -69,14 +132,70 @@ pub enum AppCommand {
     CloseTab(String),
     /// 请求打开指定路径的文件
     OpenFile(std::path::PathBuf),
+    /// 打开文件并跳转到指定行列（均从 1 开始）
+    OpenFileAt { path: std::path::PathBuf, line: usize, column: usize },
+    /// 在系统默认浏览器中打开链接
+    OpenUrl(String),
+    /// 在内置浏览器标签页中打开链接，未启用浏览器插件时回退到系统浏览器
+    OpenInBrowser(String),
     /// 在系统文件管理器中定位
     RevealInShell(std::path::PathBuf),
+    /// 用外部程序打开文件；program 为 None 时使用系统默认程序
+    OpenWith { path: std::path::PathBuf, program: Option<std::path::PathBuf> },
     /// 将字符串拷贝到系统剪贴板
     CopyToClipboard(String),
     /// 发送全局通知
     Notify { message: String, level: NotificationLevel },
+    /// 发送带按钮的通知（例如"撤销删除"），显示时间比普通通知稍长
+    NotifyWithAction { message: String, level: NotificationLevel, action: NotificationAction },
     /// 切换设置窗口
     ToggleSettings,
+    /// 重新打开最近关闭的标签页
+    ReopenLastClosed,
+    /// 将指定 ID 的标签页移出到独立的系统窗口
+    DetachTab(u64),
+    /// 将独立窗口中的标签页重新停靠回主窗口
+    RedockTab(u64),
+    /// 在终端标签页中执行命令（优先复用已打开的终端）；program 为空时只切换到 cwd
+    RunInTerminal { cwd: std::path::PathBuf, program: String, args: Vec<String> },
+    /// 向终端标签页中的进程发送信号；tab 为 None 时发送给当前激活的终端
+    TerminalSignal { tab: Option<u64>, signal: TerminalSignal },
+    /// 以该文件夹作为工作区的唯一根目录
+    SetWorkspaceRoot(std::path::PathBuf),
+    /// 向工作区追加一个根目录
+    AddWorkspaceFolder(std::path::PathBuf),
+    /// 从工作区移除一个根目录（不删除文件）
+    RemoveWorkspaceFolder(std::path::PathBuf),
+    /// 在最近使用的编辑器的光标处插入文本（替换选中内容）
+    InsertText(String),
+    /// 在新的未命名编辑器标签页中打开文本；language 为语言名或扩展名，例如 "rust" 或 "rs"
+    NewDocument { text: String, language: String },
+    /// 把统一格式的补丁应用到该文件的编辑器缓冲区（未打开时先打开）；相对路径在工作区根目录中查找
+    ApplyPatch { path: std::path::PathBuf, patch: String },
+}
+
+/// 可发送给终端进程的信号
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TerminalSignal {
+    /// 中断前台进程（Ctrl + C / SIGINT）
+    Interrupt,
+    /// 请求前台进程结束（SIGTERM）；Windows 上结束 shell 进程
+    Terminate,
+    /// 强制结束 shell 进程（SIGKILL / TerminateProcess）
+    Kill,
+}
+
+/// 通知上的按钮：点击后执行回调并关闭通知，回调可以继续发出命令
+#[derive(Clone)]
+pub struct NotificationAction {
+    pub label: String,
+    pub run: std::sync::Arc<dyn Fn(&mut Vec<AppCommand>) + Send + Sync>,
+}
+
+impl NotificationAction {
+    pub fn new(label: impl Into<String>, run: impl Fn(&mut Vec<AppCommand>) + Send + Sync + 'static) -> Self {
+        Self { label: label.into(), run: std::sync::Arc::new(run) }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -87,6 +206,17 @@ pub enum NotificationLevel {
     Error,
 }
 
+/// 插件对某个文件的打开能力评级，宿主据此在多个插件之间选择打开方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum OpenSupport {
+    /// 不支持该文件
+    None,
+    /// 可以打开，但只是兜底方案（例如把任意文件当作纯文本）
+    Fallback,
+    /// 专门处理该文件类型
+    Exact,
+}
+
 // ----------------------------------------------------------------------------
 // 插件接口
 // ----------------------------------------------------------------------------
@@ -100,12 +230,33 @@ pub trait Plugin {
         Vec::new()
     }
 
+    /// 评估对指定文件的支持程度，得分最高的插件优先调用 try_open_file
+    /// 默认视为兜底处理器，以兼容只实现了 try_open_file 的插件
+    fn open_support(&self, _path: &std::path::Path) -> OpenSupport {
+        OpenSupport::Fallback
+    }
+
     /// 尝试打开文件
     /// 如果插件支持该文件类型，返回一个新的 Tab 实例；否则返回 None
     fn try_open_file(&mut self, _path: &std::path::Path) -> Option<Box<dyn TabInstance>> {
         None
     }
 
+    /// 创建一个工作目录为 cwd 的终端标签页，供 `AppCommand::RunInTerminal` 使用
+    fn create_terminal(&mut self, _ctx: &Context, _cwd: &std::path::Path) -> Option<Box<dyn TabInstance>> {
+        None
+    }
+
+    /// 创建一个打开 url 的浏览器标签页，供 `AppCommand::OpenInBrowser` 使用
+    fn create_browser(&mut self, _url: &str) -> Option<Box<dyn TabInstance>> {
+        None
+    }
+
+    /// 创建内容为 text 的未命名文档标签页，供 `AppCommand::NewDocument` 使用
+    fn create_document(&mut self, _text: &str, _language: &str) -> Option<Box<dyn TabInstance>> {
+        None
+    }
+
     /// 注入到设置窗口的 UI
     fn on_settings_ui(&mut self, _ui: &mut Ui) {}
     
@@ -123,4 +274,285 @@ pub trait Plugin {
 
     /// 每帧逻辑更新
     fn update(&mut self, _control: &mut Vec<AppCommand>) {}
-}
\ No newline at end of file
+
+    /// 应用退出前调用，在所有标签页的 `on_app_exit` 之后执行
+    fn on_shutdown(&mut self) {}
+
+    /// 需要纳入备份还原点的其他状态文件（聊天会话、书签等）。
+    /// 工作目录下的 `*_config.toml` 已由宿主自动备份，无需列出
+    fn backup_files(&self) -> Vec<std::path::PathBuf> {
+        Vec::new()
+    }
+}
+// ----------------------------------------------------------------------------
+// 快捷键归属
+// ----------------------------------------------------------------------------
+
+fn claimed_shortcuts_id() -> egui::Id {
+    egui::Id::new("verbium_claimed_shortcuts")
+}
+
+/// 拥有焦点的标签页声明由自己处理某个快捷键（需每帧调用）。
+/// 全局快捷键在标签页之前处理，应先通过 [`is_shortcut_claimed`] 检查，避免抢走按键
+pub fn claim_shortcut(ctx: &Context, shortcut: egui::KeyboardShortcut) {
+    let pass = ctx.cumulative_pass_nr();
+    ctx.data_mut(|d| {
+        let entry = d.get_temp_mut_or_default::<(u64, Vec<egui::KeyboardShortcut>)>(claimed_shortcuts_id());
+        if entry.0 != pass {
+            *entry = (pass, Vec::new());
+        }
+        entry.1.push(shortcut);
+    });
+}
+
+/// 上一帧或本帧是否有标签页声明了该快捷键
+pub fn is_shortcut_claimed(ctx: &Context, shortcut: egui::KeyboardShortcut) -> bool {
+    let pass = ctx.cumulative_pass_nr();
+    ctx.data(|d| d.get_temp::<(u64, Vec<egui::KeyboardShortcut>)>(claimed_shortcuts_id()))
+        .is_some_and(|(claimed_pass, shortcuts)| claimed_pass + 1 >= pass && shortcuts.contains(&shortcut))
+}
+
+// ----------------------------------------------------------------------------
+// 代码补全
+// ----------------------------------------------------------------------------
+
+/// 补全建议的类别，决定弹窗中显示的图标
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompletionKind {
+    /// 文档中出现过的单词
+    Text,
+    Keyword,
+    Function,
+    Variable,
+    Type,
+    Module,
+    /// 代码片段，插入文本中的 `$0` 标记插入后的光标位置
+    Snippet,
+}
+
+impl CompletionKind {
+    pub fn icon(&self) -> &'static str {
+        match self {
+            CompletionKind::Text => "≡",
+            CompletionKind::Keyword => "◇",
+            CompletionKind::Function => "ƒ",
+            CompletionKind::Variable => "○",
+            CompletionKind::Type => "◆",
+            CompletionKind::Module => "▣",
+            CompletionKind::Snippet => "✂",
+        }
+    }
+}
+
+/// 一条补全建议
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompletionItem {
+    /// 弹窗中显示的文字，同时用于与已输入的前缀匹配
+    pub label: String,
+    /// 替换前缀后插入的文本
+    pub insert_text: String,
+    /// 显示在右侧的说明，例如类型签名
+    pub detail: Option<String>,
+    pub kind: CompletionKind,
+}
+
+/// 一次补全请求，光标与前缀均以字符计
+pub struct CompletionRequest<'a> {
+    pub path: Option<&'a std::path::Path>,
+    /// 编辑器的语言 ID（"rs"、"py" 等）
+    pub language: &'a str,
+    pub text: &'a str,
+    pub cursor: usize,
+    /// 光标前正在输入的单词，可以为空
+    pub prefix: &'a str,
+    /// 是否由 Ctrl + Space 主动触发
+    pub manual: bool,
+}
+
+/// 补全来源。在 UI 线程上同步调用，需要耗时计算的来源（语言服务器、模型）
+/// 应在后台准备结果，这里只返回已有的建议。返回的建议由编辑器统一按前缀过滤
+pub trait CompletionProvider: Send + Sync {
+    /// 来源名称，重复注册同名来源时替换旧的
+    fn name(&self) -> &str;
+    fn complete(&self, request: &CompletionRequest) -> Vec<CompletionItem>;
+}
+
+fn completion_providers_id() -> egui::Id {
+    egui::Id::new("verbium_completion_providers")
+}
+
+/// 注册补全来源，编辑器在内置的单词与片段补全之前查询它们
+pub fn register_completion_provider(ctx: &Context, provider: std::sync::Arc<dyn CompletionProvider>) {
+    ctx.data_mut(|d| {
+        let providers = d.get_temp_mut_or_default::<Vec<std::sync::Arc<dyn CompletionProvider>>>(completion_providers_id());
+        providers.retain(|p| p.name() != provider.name());
+        providers.push(provider);
+    });
+}
+
+/// 已注册的补全来源
+pub fn completion_providers(ctx: &Context) -> Vec<std::sync::Arc<dyn CompletionProvider>> {
+    ctx.data(|d| d.get_temp(completion_providers_id())).unwrap_or_default()
+}
+
+// ----------------------------------------------------------------------------
+// 版本控制
+// ----------------------------------------------------------------------------
+
+/// 文件在版本库中的状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VcsStatus {
+    Modified,
+    Added,
+    Deleted,
+    Renamed,
+    Untracked,
+    /// 合并冲突
+    Conflicted,
+}
+
+impl VcsStatus {
+    /// 文件树与变更列表中显示的单字母标记
+    pub fn letter(&self) -> &'static str {
+        match self {
+            VcsStatus::Modified => "M",
+            VcsStatus::Added => "A",
+            VcsStatus::Deleted => "D",
+            VcsStatus::Renamed => "R",
+            VcsStatus::Untracked => "U",
+            VcsStatus::Conflicted => "!",
+        }
+    }
+
+    /// 悬停提示中的说明
+    pub fn label(&self) -> &'static str {
+        match self {
+            VcsStatus::Modified => "Modified",
+            VcsStatus::Added => "Added",
+            VcsStatus::Deleted => "Deleted",
+            VcsStatus::Renamed => "Renamed",
+            VcsStatus::Untracked => "Untracked",
+            VcsStatus::Conflicted => "Conflicted",
+        }
+    }
+
+    pub fn color(&self, palette: &theme::StatusPalette) -> egui::Color32 {
+        match self {
+            VcsStatus::Modified | VcsStatus::Renamed => palette.modified,
+            VcsStatus::Added => palette.added,
+            VcsStatus::Deleted => palette.deleted,
+            VcsStatus::Untracked => palette.untracked,
+            VcsStatus::Conflicted => palette.error,
+        }
+    }
+}
+
+/// 版本控制信息的来源（git 插件），供编辑器与文件管理器标注修改。
+/// 在 UI 线程上调用，实现应只返回已缓存的结果
+pub trait VcsProvider: Send + Sync {
+    /// 文件的状态；目录在其中有任何变更时返回 `Modified`。路径为绝对路径
+    fn status(&self, path: &std::path::Path) -> Option<VcsStatus>;
+    /// 文件的变更是否都已暂存（工作区中没有未暂存的修改）
+    fn is_staged(&self, _path: &std::path::Path) -> bool {
+        false
+    }
+    /// 文件或目录是否被 .gitignore 等规则忽略
+    fn is_ignored(&self, _path: &std::path::Path) -> bool {
+        false
+    }
+    /// 文件最近一次提交的内容，用于标注编辑器中修改过的行；
+    /// 尚未读取完成或文件不在版本库中时返回 None
+    fn base_text(&self, path: &std::path::Path) -> Option<std::sync::Arc<String>>;
+}
+
+fn vcs_provider_id() -> egui::Id {
+    egui::Id::new("verbium_vcs_provider")
+}
+
+/// 注册版本控制来源，替换已注册的来源
+pub fn register_vcs_provider(ctx: &Context, provider: std::sync::Arc<dyn VcsProvider>) {
+    ctx.data_mut(|d| d.insert_temp(vcs_provider_id(), provider));
+}
+
+/// 已注册的版本控制来源
+pub fn vcs_provider(ctx: &Context) -> Option<std::sync::Arc<dyn VcsProvider>> {
+    ctx.data(|d| d.get_temp(vcs_provider_id()))
+}
+
+// ----------------------------------------------------------------------------
+// Agent 工具
+// ----------------------------------------------------------------------------
+
+/// Agent 可以调用的工具（函数调用），由各插件注册
+pub trait AgentTool: Send + Sync {
+    /// 工具名，重复注册同名工具时替换旧的
+    fn name(&self) -> &str;
+    /// 提供给模型的用途说明
+    fn description(&self) -> &str;
+    /// 参数的 JSON Schema
+    fn parameters(&self) -> &str;
+    /// 会修改文件或执行命令的工具，每次调用前都需要用户确认
+    fn has_side_effects(&self) -> bool {
+        false
+    }
+    /// 执行调用，`arguments` 为符合 `parameters` 的 JSON 文本，返回提供给模型的结果。
+    /// 在后台线程中调用，可以阻塞；相对路径以 `roots` 中的第一个目录为准，
+    /// 需要宿主配合的操作写入 `control`，由调用方转交
+    fn call(&self, arguments: &str, roots: &[std::path::PathBuf], control: &mut Vec<AppCommand>) -> Result<String, String>;
+    /// 可取消的 `call`：用户停止回复时 `cancelled` 被设置，运行较久的工具应据此尽早返回。
+    /// 默认忽略取消请求
+    fn call_cancellable(
+        &self,
+        arguments: &str,
+        roots: &[std::path::PathBuf],
+        control: &mut Vec<AppCommand>,
+        _cancelled: &std::sync::atomic::AtomicBool,
+    ) -> Result<String, String> {
+        self.call(arguments, roots, control)
+    }
+}
+
+fn agent_tools_id() -> egui::Id {
+    egui::Id::new("verbium_agent_tools")
+}
+
+/// 注册 Agent 工具
+pub fn register_agent_tool(ctx: &Context, tool: std::sync::Arc<dyn AgentTool>) {
+    ctx.data_mut(|d| {
+        let tools = d.get_temp_mut_or_default::<Vec<std::sync::Arc<dyn AgentTool>>>(agent_tools_id());
+        tools.retain(|t| t.name() != tool.name());
+        tools.push(tool);
+    });
+}
+
+/// 已注册的 Agent 工具
+pub fn agent_tools(ctx: &Context) -> Vec<std::sync::Arc<dyn AgentTool>> {
+    ctx.data(|d| d.get_temp(agent_tools_id())).unwrap_or_default()
+}
+
+// ----------------------------------------------------------------------------
+// 编辑器上下文
+// ----------------------------------------------------------------------------
+
+/// 最近获得焦点的编辑器的状态，供 Agent 等插件填充提示模板
+#[derive(Debug, Clone, Default)]
+pub struct EditorContext {
+    pub path: Option<std::path::PathBuf>,
+    pub language: String,
+    /// 选中的文本，没有选区时为空
+    pub selection: String,
+}
+
+fn editor_context_id() -> egui::Id {
+    egui::Id::new("verbium_editor_context")
+}
+
+/// 由获得焦点的编辑器每帧发布，焦点移到其他标签页后保留最后一次的内容
+pub fn publish_editor_context(ctx: &Context, context: EditorContext) {
+    ctx.data_mut(|d| d.insert_temp(editor_context_id(), context));
+}
+
+/// 最近一次发布的编辑器上下文
+pub fn editor_context(ctx: &Context) -> Option<EditorContext> {
+    ctx.data(|d| d.get_temp(editor_context_id()))
+}
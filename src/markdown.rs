@@ -0,0 +1,314 @@
+use egui::{Color32, RichText, Ui};
+
+// ----------------------------------------------------------------------------
+// Markdown 解析：常用的块（标题、段落、列表、引用、代码块、表格、分隔线）
+// 与行内语法（强调、删除线、行内代码、链接与图片），供编辑器预览与助手聊天共用
+// ----------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Style {
+    pub strong: bool,
+    pub italic: bool,
+    pub code: bool,
+    pub strike: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Span {
+    Text(String, Style),
+    Link { text: String, url: String, image: bool },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BlockKind {
+    Heading(u8, Vec<Span>),
+    Paragraph(Vec<Span>),
+    /// (缩进层级, 序号或 None, 任务状态, 内容)
+    ListItem { depth: usize, number: Option<u64>, task: Option<bool>, spans: Vec<Span> },
+    Quote(Vec<Span>),
+    Code { language: String, text: String },
+    Table { header: Vec<Vec<Span>>, rows: Vec<Vec<Vec<Span>>> },
+    Rule,
+}
+
+/// 一个块及其在源码中的起始行（0 起始）
+#[derive(Debug, Clone, PartialEq)]
+pub struct Block {
+    pub line: usize,
+    pub kind: BlockKind,
+}
+
+fn parse_inline(text: &str) -> Vec<Span> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut spans = Vec::new();
+    let mut style = Style::default();
+    let mut current = String::new();
+    let flush = |current: &mut String, spans: &mut Vec<Span>, style: Style| {
+        if !current.is_empty() {
+            spans.push(Span::Text(std::mem::take(current), style));
+        }
+    };
+    let find = |from: usize, pattern: &str| -> Option<usize> {
+        let pattern: Vec<char> = pattern.chars().collect();
+        (from..chars.len()).find(|&i| chars[i..].starts_with(&pattern))
+    };
+
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let rest = &chars[i..];
+        if c == '\\' && chars.get(i + 1).is_some_and(|n| n.is_ascii_punctuation()) {
+            current.push(chars[i + 1]);
+            i += 2;
+        } else if c == '`' {
+            match find(i + 1, "`") {
+                Some(end) => {
+                    flush(&mut current, &mut spans, style);
+                    spans.push(Span::Text(chars[i + 1..end].iter().collect(), Style { code: true, ..style }));
+                    i = end + 1;
+                }
+                None => {
+                    current.push(c);
+                    i += 1;
+                }
+            }
+        } else if rest.starts_with(&['*', '*']) || rest.starts_with(&['_', '_']) {
+            flush(&mut current, &mut spans, style);
+            style.strong = !style.strong;
+            i += 2;
+        } else if rest.starts_with(&['~', '~']) {
+            flush(&mut current, &mut spans, style);
+            style.strike = !style.strike;
+            i += 2;
+        } else if c == '*' || (c == '_' && (style.italic || i == 0 || !chars[i - 1].is_alphanumeric())) {
+            flush(&mut current, &mut spans, style);
+            style.italic = !style.italic;
+            i += 1;
+        } else if c == '[' || (c == '!' && chars.get(i + 1) == Some(&'[')) {
+            let image = c == '!';
+            let open = if image { i + 1 } else { i };
+            let link = find(open + 1, "](").and_then(|mid| find(mid + 2, ")").map(|end| (mid, end)));
+            match link {
+                Some((mid, end)) => {
+                    flush(&mut current, &mut spans, style);
+                    let text: String = chars[open + 1..mid].iter().collect();
+                    let url: String = chars[mid + 2..end].iter().collect();
+                    // 去掉可选的标题：[text](url "title")
+                    let url = url.split_whitespace().next().unwrap_or_default().to_string();
+                    spans.push(Span::Link { text, url, image });
+                    i = end + 1;
+                }
+                None => {
+                    current.push(c);
+                    i += 1;
+                }
+            }
+        } else {
+            current.push(c);
+            i += 1;
+        }
+    }
+    flush(&mut current, &mut spans, style);
+    spans
+}
+
+fn heading(line: &str) -> Option<(u8, &str)> {
+    let level = line.chars().take_while(|c| *c == '#').count();
+    let rest = &line[level..];
+    ((1..=6).contains(&level) && (rest.is_empty() || rest.starts_with(' ')))
+        .then(|| (level as u8, rest.trim().trim_end_matches('#').trim_end()))
+}
+
+fn is_rule(line: &str) -> bool {
+    let compact: String = line.chars().filter(|c| !c.is_whitespace()).collect();
+    compact.len() >= 3 && ['-', '*', '_'].iter().any(|m| compact.chars().all(|c| c == *m))
+}
+
+/// 列表项：返回 (缩进层级, 序号, 内容)
+fn list_item(line: &str) -> Option<(usize, Option<u64>, &str)> {
+    let trimmed = line.trim_start();
+    let depth = (line.len() - trimmed.len()) / 2;
+    if let Some(rest) = trimmed.strip_prefix(['-', '*', '+']) {
+        return rest.starts_with(' ').then(|| (depth, None, rest.trim_start()));
+    }
+    let digits = trimmed.chars().take_while(|c| c.is_ascii_digit()).count();
+    let rest = &trimmed[digits..];
+    if digits > 0 && (rest.starts_with(". ") || rest.starts_with(") ")) {
+        return Some((depth, trimmed[..digits].parse().ok(), rest[2..].trim_start()));
+    }
+    None
+}
+
+fn table_cells(line: &str) -> Vec<Vec<Span>> {
+    let line = line.trim().trim_start_matches('|').trim_end_matches('|');
+    line.split('|').map(|cell| parse_inline(cell.trim())).collect()
+}
+
+fn is_table_separator(line: &str) -> bool {
+    let line = line.trim();
+    line.contains('-') && line.chars().all(|c| matches!(c, '|' | '-' | ':' | ' '))
+}
+
+/// 解析 Markdown 文本。代码块没有结束标记（如流式输出尚未完成）时一直延续到末尾
+pub fn parse(text: &str) -> Vec<Block> {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut blocks = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i];
+        let trimmed = line.trim();
+        let start = i;
+        i += 1;
+        if trimmed.is_empty() {
+            continue;
+        }
+
+        if let Some(fence) = ["```", "~~~"].into_iter().find(|f| trimmed.starts_with(f)) {
+            let language = trimmed[fence.len()..].trim().to_string();
+            let mut text = Vec::new();
+            while i < lines.len() && !lines[i].trim_start().starts_with(fence) {
+                text.push(lines[i]);
+                i += 1;
+            }
+            i += 1;
+            blocks.push(Block { line: start, kind: BlockKind::Code { language, text: text.join("\n") } });
+        } else if let Some((level, title)) = heading(trimmed) {
+            blocks.push(Block { line: start, kind: BlockKind::Heading(level, parse_inline(title)) });
+        } else if is_rule(trimmed) {
+            blocks.push(Block { line: start, kind: BlockKind::Rule });
+        } else if trimmed.starts_with('>') {
+            let mut text = vec![trimmed.trim_start_matches('>').trim()];
+            while i < lines.len() && lines[i].trim().starts_with('>') {
+                text.push(lines[i].trim().trim_start_matches('>').trim());
+                i += 1;
+            }
+            blocks.push(Block { line: start, kind: BlockKind::Quote(parse_inline(&text.join(" "))) });
+        } else if let Some((depth, number, content)) = list_item(line) {
+            let (task, content) = match content.get(..4) {
+                Some("[ ] ") => (Some(false), &content[4..]),
+                Some("[x] ") | Some("[X] ") => (Some(true), &content[4..]),
+                _ => (None, content),
+            };
+            // 缩进的后续行属于同一项
+            let mut text = vec![content];
+            while i < lines.len() && lines[i].starts_with("  ") && !lines[i].trim().is_empty() && list_item(lines[i]).is_none() {
+                text.push(lines[i].trim());
+                i += 1;
+            }
+            blocks.push(Block { line: start, kind: BlockKind::ListItem { depth, number, task, spans: parse_inline(&text.join(" ")) } });
+        } else if trimmed.starts_with('|') && lines.get(i).is_some_and(|next| is_table_separator(next)) {
+            let header = table_cells(trimmed);
+            i += 1;
+            let mut rows = Vec::new();
+            while i < lines.len() && lines[i].trim().starts_with('|') {
+                rows.push(table_cells(lines[i]));
+                i += 1;
+            }
+            blocks.push(Block { line: start, kind: BlockKind::Table { header, rows } });
+        } else {
+            let mut text = vec![trimmed];
+            while i < lines.len() {
+                let next = lines[i].trim();
+                if next.is_empty() || heading(next).is_some() || next.starts_with("```") || next.starts_with("~~~") || next.starts_with('>') || list_item(lines[i]).is_some() {
+                    break;
+                }
+                text.push(next);
+                i += 1;
+            }
+            blocks.push(Block { line: start, kind: BlockKind::Paragraph(parse_inline(&text.join(" "))) });
+        }
+    }
+    blocks
+}
+
+// ----------------------------------------------------------------------------
+// 绘制：行内内容、列表项、引用与表格。标题与代码块的样式由调用方决定
+// ----------------------------------------------------------------------------
+
+fn rich_text(text: &str, style: Style, size: Option<f32>, color: Option<Color32>) -> RichText {
+    let mut rich = RichText::new(text);
+    if let Some(color) = color {
+        rich = rich.color(color);
+    }
+    if let Some(size) = size {
+        rich = rich.size(size).strong();
+    }
+    if style.strong {
+        rich = rich.strong();
+    }
+    if style.italic {
+        rich = rich.italics();
+    }
+    if style.code {
+        rich = rich.code();
+    }
+    if style.strike {
+        rich = rich.strikethrough();
+    }
+    rich
+}
+
+/// 绘制一段行内内容；`size` 用于标题，`color` 为 None 时使用默认文字颜色
+pub fn spans_ui(ui: &mut Ui, spans: &[Span], size: Option<f32>, color: Option<Color32>) {
+    ui.horizontal_wrapped(|ui| {
+        ui.spacing_mut().item_spacing.x = 0.0;
+        for span in spans {
+            match span {
+                Span::Text(text, style) => {
+                    ui.label(rich_text(text, *style, size, color));
+                }
+                Span::Link { text, url, image } => {
+                    let label = if *image { format!("🖼 {}", if text.is_empty() { url } else { text }) } else { text.clone() };
+                    ui.hyperlink_to(rich_text(&label, Style::default(), size, Some(ui.visuals().hyperlink_color)), url).on_hover_text(url);
+                }
+            }
+        }
+    });
+}
+
+/// 列表项：按层级缩进，前面是圆点、序号或任务复选框
+pub fn list_item_ui(ui: &mut Ui, depth: usize, number: Option<u64>, task: Option<bool>, spans: &[Span], color: Option<Color32>) {
+    ui.horizontal_top(|ui| {
+        ui.add_space(depth as f32 * 16.0 + 4.0);
+        let marker = match (task, number) {
+            (Some(done), _) => (if done { "☑" } else { "☐" }).to_string(),
+            (None, Some(number)) => format!("{}.", number),
+            (None, None) => "•".to_string(),
+        };
+        ui.label(rich_text(&marker, Style::default(), None, color));
+        spans_ui(ui, spans, None, color);
+    });
+}
+
+/// 引用：左侧一条竖线
+pub fn quote_ui(ui: &mut Ui, spans: &[Span], color: Option<Color32>) {
+    let stroke = egui::Stroke::new(3.0, ui.visuals().weak_text_color());
+    let response = egui::Frame::none()
+        .inner_margin(egui::Margin { left: 10.0, ..Default::default() })
+        .show(ui, |ui| spans_ui(ui, spans, None, color))
+        .response;
+    ui.painter().vline(response.rect.left() + 1.5, response.rect.y_range(), stroke);
+}
+
+/// 表格：表头加粗，行交替着色
+pub fn table_ui(ui: &mut Ui, header: &[Vec<Span>], rows: &[Vec<Vec<Span>>], color: Option<Color32>) {
+    egui::Grid::new(ui.next_auto_id()).striped(true).spacing([16.0, 4.0]).show(ui, |ui| {
+        for cell in header {
+            let cell: Vec<Span> = cell
+                .iter()
+                .map(|span| match span {
+                    Span::Text(text, style) => Span::Text(text.clone(), Style { strong: true, ..*style }),
+                    link => link.clone(),
+                })
+                .collect();
+            spans_ui(ui, &cell, None, color);
+        }
+        ui.end_row();
+        for row in rows {
+            for cell in row {
+                spans_ui(ui, cell, None, color);
+            }
+            ui.end_row();
+        }
+    });
+}
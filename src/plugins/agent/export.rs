@@ -0,0 +1,178 @@
+use anyhow::{anyhow, bail};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use super::models::{ChatMessage, ChatSession, MessageRole};
+
+/// JSON 导出文件的格式标识
+const JSON_FORMAT: &str = "verbium-chat";
+const JSON_VERSION: u32 = 1;
+
+/// Markdown 导出中每条消息前的标记。HTML 注释在渲染时不可见，导入时据此切分消息
+const MESSAGE_MARKER: &str = "<!-- verbium:";
+/// 回复中工具调用列表的开头，与消息标记同一前缀，导入时作为未知的标记跳过
+const TOOL_CALLS_MARKER: &str = "<!-- verbium:tool-calls -->";
+
+// ----------------------------------------------------------------------------
+// 会话的导出与导入
+//
+// JSON 完整保存会话（包括工具调用、分叉版本与用量）及导出信息；
+// Markdown 便于阅读和分享，导入时只恢复消息文本、模式与模型
+// ----------------------------------------------------------------------------
+
+#[derive(Serialize, Deserialize)]
+struct JsonExport {
+    format: String,
+    version: u32,
+    title: String,
+    exported_at: String,
+    session: ChatSession,
+}
+
+impl ChatSession {
+    /// 会话文件名，未保存的会话为 `Chat`
+    pub fn title(&self) -> String {
+        self.path
+            .as_ref()
+            .and_then(|p| p.file_stem())
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_else(|| "Chat".into())
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!("# {}\n\n", self.title());
+        out.push_str(&format!("- Mode: {}\n", self.context_mode));
+        out.push_str(&format!("- Model: {}\n", self.model_name));
+        if let Some(created) = &self.created_at {
+            out.push_str(&format!("- Created: {}\n", created));
+        }
+        out.push_str(&format!("- Exported: {}\n", chrono::Local::now().to_rfc3339()));
+        let usage = self.usage();
+        if usage.total() > 0 {
+            out.push_str(&format!("- Usage: {}\n", usage.summary()));
+        }
+
+        for message in &self.messages {
+            let (key, heading) = match message.role {
+                MessageRole::User => ("user", "🧑 User"),
+                MessageRole::Agent => ("agent", "🤖 Agent"),
+                MessageRole::Tool => ("tool", "🛠 Tool"),
+            };
+            out.push_str(&format!("\n{}{} -->\n## {}\n\n", MESSAGE_MARKER, key, heading));
+            match message.role {
+                // 工具输出按原样放进代码块，围栏比内容中最长的反引号串更长
+                MessageRole::Tool => {
+                    let fence = "`".repeat(longest_backtick_run(&message.content).max(2) + 1);
+                    out.push_str(&format!("{}text\n{}\n{}\n", fence, message.content.trim_end(), fence));
+                }
+                _ => {
+                    out.push_str(message.content.trim_end());
+                    out.push('\n');
+                }
+            }
+            if !message.tool_calls.is_empty() {
+                out.push_str(&format!("\n{}\n", TOOL_CALLS_MARKER));
+                for call in &message.tool_calls {
+                    out.push_str(&format!("- 🛠 `{}` {}\n", call.name, call.arguments));
+                }
+            }
+        }
+        out
+    }
+
+    pub fn to_json(&self) -> anyhow::Result<String> {
+        let export = JsonExport {
+            format: JSON_FORMAT.into(),
+            version: JSON_VERSION,
+            title: self.title(),
+            exported_at: chrono::Local::now().to_rfc3339(),
+            session: self.clone(),
+        };
+        Ok(serde_json::to_string_pretty(&export)?)
+    }
+
+    pub fn from_json(text: &str) -> anyhow::Result<Self> {
+        let export: JsonExport = serde_json::from_str(text)?;
+        if export.format != JSON_FORMAT {
+            bail!("not a Verbium chat export");
+        }
+        if export.version > JSON_VERSION {
+            bail!("exported by a newer version (format version {})", export.version);
+        }
+        Ok(export.session)
+    }
+
+    /// 读取 `to_markdown` 导出的文本
+    pub fn from_markdown(text: &str) -> anyhow::Result<Self> {
+        let mut session = ChatSession::new("Chat".into(), String::new());
+        let mut parts = text.split(MESSAGE_MARKER);
+        for line in parts.next().unwrap_or_default().lines() {
+            let Some((key, value)) = line.strip_prefix("- ").and_then(|l| l.split_once(": ")) else { continue };
+            match key {
+                "Mode" => session.context_mode = value.trim().to_string(),
+                "Model" => session.model_name = value.trim().to_string(),
+                "Created" => session.created_at = Some(value.trim().to_string()),
+                _ => {}
+            }
+        }
+
+        for part in parts {
+            let Some((key, body)) = part.split_once(" -->") else { continue };
+            let role = match key {
+                "user" => MessageRole::User,
+                "agent" => MessageRole::Agent,
+                "tool" => MessageRole::Tool,
+                _ => continue,
+            };
+            // 跳过标题行
+            let body = body.trim_start_matches(['\r', '\n']);
+            let body = body.split_once('\n').map_or("", |(_, rest)| rest).trim();
+            let content = match role {
+                MessageRole::Tool => strip_fence(body),
+                _ => body.to_string(),
+            };
+            session.messages.push(ChatMessage::new(role, content));
+        }
+        if session.messages.is_empty() {
+            bail!("no messages found");
+        }
+        Ok(session)
+    }
+
+    /// 按扩展名导出为 `.md` 或 `.json`
+    pub fn export(&self, path: &Path) -> anyhow::Result<()> {
+        let content = match extension(path).as_str() {
+            "json" => self.to_json()?,
+            _ => self.to_markdown(),
+        };
+        std::fs::write(path, content)?;
+        Ok(())
+    }
+
+    /// 读取导出的文件，返回的会话尚未关联会话文件
+    pub fn import(path: &Path) -> anyhow::Result<Self> {
+        let text = std::fs::read_to_string(path)?;
+        match extension(path).as_str() {
+            "json" => Self::from_json(&text),
+            "md" | "markdown" => Self::from_markdown(&text),
+            other => Err(anyhow!("unsupported file type \".{}\"", other)),
+        }
+    }
+}
+
+fn extension(path: &Path) -> String {
+    path.extension().map(|e| e.to_string_lossy().to_lowercase()).unwrap_or_default()
+}
+
+fn longest_backtick_run(text: &str) -> usize {
+    text.split(|c| c != '`').map(str::len).max().unwrap_or(0)
+}
+
+/// 去掉包住整段内容的代码块围栏
+fn strip_fence(text: &str) -> String {
+    let mut lines: Vec<&str> = text.lines().collect();
+    if lines.len() >= 2 && lines[0].starts_with("```") && lines[lines.len() - 1].starts_with("```") {
+        lines.remove(0);
+        lines.pop();
+    }
+    lines.join("\n")
+}
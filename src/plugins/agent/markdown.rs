@@ -0,0 +1,143 @@
+use egui::Ui;
+use crate::AppCommand;
+use crate::markdown::{self, BlockKind};
+
+// ----------------------------------------------------------------------------
+// 聊天消息的 Markdown 渲染：解析与行内样式共用 crate::markdown，
+// 代码块带语法高亮以及复制 / 插入编辑器 / 在新标签页中打开按钮，
+// 统一格式的补丁显示为可以应用到编辑器的改动
+// ----------------------------------------------------------------------------
+
+/// 补丁中一个文件的改动：文件名与应用 / 拒绝按钮在上，按增删着色的补丁在下。
+/// 应用的结果由编辑器以通知告知，这里只记录已发送或已拒绝；
+/// 处理状态只保存在内存中，重新打开会话后按钮会再次出现
+fn patch_block_ui(ui: &mut Ui, file: &crate::diff::FilePatch, control: &mut Vec<AppCommand>) {
+    let palette = crate::theme::palette(ui.ctx());
+    let id = egui::Id::new(("agent_patch", &file.text));
+    let mut status: Option<bool> = ui.data(|d| d.get_temp(id));
+    egui::Frame::none()
+        .fill(ui.visuals().extreme_bg_color)
+        .rounding(4.0)
+        .inner_margin(8.0)
+        .show(ui, |ui| {
+            ui.set_width(ui.available_width());
+            ui.horizontal(|ui| {
+                let name = if file.path.is_empty() { "Unknown file" } else { file.path.as_str() };
+                ui.label(egui::RichText::new(format!("📝 {}", name)).strong());
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    match status {
+                        Some(true) => {
+                            ui.weak("➜ Sent to editor").on_hover_text("The editor reports whether the patch applied");
+                        }
+                        Some(false) => {
+                            ui.weak("Rejected");
+                        }
+                        None => {
+                            if ui.small_button("Reject").clicked() {
+                                status = Some(false);
+                            }
+                            let apply = ui
+                                .add_enabled(!file.path.is_empty(), egui::Button::new("✔ Apply").small())
+                                .on_hover_text("Apply to the editor buffer (undo with Ctrl + Z)")
+                                .on_disabled_hover_text("The patch does not name a file");
+                            if apply.clicked() {
+                                control.push(AppCommand::ApplyPatch { path: file.path.clone().into(), patch: file.text.clone() });
+                                status = Some(true);
+                            }
+                        }
+                    }
+                    if ui.small_button("📋").on_hover_text("Copy Patch").clicked() {
+                        control.push(AppCommand::CopyToClipboard(file.text.clone()));
+                    }
+                });
+            });
+            ui.data_mut(|d| match status {
+                Some(status) => d.insert_temp(id, status),
+                None => d.remove::<bool>(id),
+            });
+            if status == Some(false) {
+                return;
+            }
+            egui::ScrollArea::horizontal().id_salt(ui.next_auto_id()).show(ui, |ui| {
+                for line in file.text.lines() {
+                    let color = match line.chars().next() {
+                        _ if line.starts_with("+++") || line.starts_with("---") => ui.visuals().weak_text_color(),
+                        Some('+') => palette.added,
+                        Some('-') => palette.deleted,
+                        Some('@') => palette.info,
+                        _ => ui.visuals().text_color(),
+                    };
+                    ui.add(egui::Label::new(egui::RichText::new(line).monospace().color(color)).wrap_mode(egui::TextWrapMode::Extend));
+                }
+            });
+        });
+}
+
+/// 代码块：语言标签与操作按钮在上，带语法高亮的代码在下
+fn code_block_ui(ui: &mut Ui, language: &str, text: &str, control: &mut Vec<AppCommand>) {
+    if matches!(language, "diff" | "patch") {
+        let files = crate::diff::parse_patch(text);
+        if !files.is_empty() {
+            for file in &files {
+                patch_block_ui(ui, file, control);
+            }
+            return;
+        }
+    }
+    egui::Frame::none()
+        .fill(ui.visuals().extreme_bg_color)
+        .rounding(4.0)
+        .inner_margin(8.0)
+        .show(ui, |ui| {
+            ui.set_width(ui.available_width());
+            ui.horizontal(|ui| {
+                ui.label(egui::RichText::new(if language.is_empty() { "text" } else { language }).small().weak());
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    if ui.small_button("🗋").on_hover_text("Open in New Tab").clicked() {
+                        control.push(AppCommand::NewDocument { text: text.to_string(), language: language.to_string() });
+                    }
+                    if ui.small_button("⤵").on_hover_text("Insert into Editor").clicked() {
+                        control.push(AppCommand::InsertText(text.to_string()));
+                    }
+                    if ui.small_button("📋").on_hover_text("Copy Code").clicked() {
+                        control.push(AppCommand::CopyToClipboard(text.to_string()));
+                    }
+                });
+            });
+            let theme = egui_extras::syntax_highlighting::CodeTheme::from_memory(ui.ctx(), ui.style());
+            let job = egui_extras::syntax_highlighting::highlight(ui.ctx(), ui.style(), &theme, text, if language.is_empty() { "txt" } else { language });
+            egui::ScrollArea::horizontal().id_salt(ui.next_auto_id()).show(ui, |ui| {
+                ui.add(egui::Label::new(job).wrap_mode(egui::TextWrapMode::Extend));
+            });
+        });
+}
+
+/// 以 Markdown 绘制消息内容
+pub fn show(ui: &mut Ui, text: &str, color: egui::Color32, control: &mut Vec<AppCommand>) {
+    let body_size = egui::TextStyle::Body.resolve(ui.style()).size;
+    let color = Some(color);
+    for (index, block) in markdown::parse(text).iter().enumerate() {
+        if index > 0 {
+            ui.add_space(4.0);
+        }
+        match &block.kind {
+            BlockKind::Heading(level, spans) => {
+                let scale = match level {
+                    1 => 1.4,
+                    2 => 1.25,
+                    3 => 1.1,
+                    _ => 1.0,
+                };
+                markdown::spans_ui(ui, spans, Some(body_size * scale), color);
+            }
+            BlockKind::Paragraph(spans) => markdown::spans_ui(ui, spans, None, color),
+            BlockKind::ListItem { depth, number, task, spans } => markdown::list_item_ui(ui, *depth, *number, *task, spans, color),
+            BlockKind::Quote(spans) => markdown::quote_ui(ui, spans, color),
+            BlockKind::Code { language, text } => code_block_ui(ui, language, text, control),
+            BlockKind::Table { header, rows } => markdown::table_ui(ui, header, rows, color),
+            BlockKind::Rule => {
+                ui.separator();
+            }
+        }
+    }
+}
@@ -1,5 +1,15 @@
+pub mod export;
+pub mod markdown;
 pub mod models;
+pub mod ollama;
 pub mod tab;
 pub mod plugin;
+pub mod prompts;
+pub mod provider;
+pub mod script;
+pub mod sessions;
+pub mod sidebar;
+pub mod tools;
+pub mod usage;
 
 pub use plugin::create;
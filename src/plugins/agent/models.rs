@@ -1,24 +1,168 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::PathBuf;
+use super::ollama::OllamaConfig;
+use super::provider::ProviderConfig;
+use super::tools::ShellPolicy;
+use super::usage::{self, ModelPrice, TokenUsage};
 
-#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct AgentConfig {
     pub script_directory: Option<PathBuf>,
     pub default_chat_dir: Option<PathBuf>,
+    #[serde(default)]
+    pub shell_policy: ShellPolicy,
+    /// 模型服务的接口地址与密钥
+    #[serde(default)]
+    pub provider: ProviderConfig,
+    /// 通过 Ollama 运行的本地模型
+    #[serde(default)]
+    pub ollama: OllamaConfig,
+    /// 会话标签，键为相对于会话目录、以 / 分隔的路径
+    #[serde(default)]
+    pub session_tags: BTreeMap<String, Vec<String>>,
+    /// 用户为标签指定的颜色（RGB），未指定的标签按名称自动取色
+    #[serde(default)]
+    pub tag_colors: BTreeMap<String, [u8; 3]>,
+    /// 计算费用所用的模型单价
+    #[serde(default = "usage::default_prices")]
+    pub prices: Vec<ModelPrice>,
+    /// 每月费用预算（美元），超出时在 Agent 标签页中提示
+    #[serde(default)]
+    pub monthly_budget: Option<f64>,
+}
+
+impl Default for AgentConfig {
+    fn default() -> Self {
+        Self {
+            script_directory: None,
+            default_chat_dir: None,
+            shell_policy: ShellPolicy::default(),
+            provider: ProviderConfig::default(),
+            ollama: OllamaConfig::default(),
+            session_tags: BTreeMap::new(),
+            tag_colors: BTreeMap::new(),
+            prices: usage::default_prices(),
+            monthly_budget: None,
+        }
+    }
+}
+
+/// Agent 工作模式。脚本模式可在文件开头用 `//!` 注释声明元数据：
+///
+/// ```text
+/// //! description: Break the task into steps before coding
+/// //! model: gpt-4o
+/// //! temperature: 0.2
+/// //! icon: 🧭
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ModeInfo {
+    pub name: String,
+    pub description: String,
+    pub preferred_model: Option<String>,
+    pub temperature: Option<f32>,
+    pub icon: Option<String>,
+    /// 对应的 .rhai 脚本，内置模式为 None
+    pub script: Option<PathBuf>,
+}
+
+impl ModeInfo {
+    pub fn builtin(name: &str, icon: &str, description: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            description: description.to_string(),
+            icon: Some(icon.to_string()),
+            ..Default::default()
+        }
+    }
+
+    /// 读取脚本文件头部的 `//!` 元数据注释
+    pub fn from_script(path: &std::path::Path) -> Option<Self> {
+        let name = path.file_stem()?.to_str()?.to_string();
+        let mut info = Self { name, script: Some(path.to_path_buf()), ..Default::default() };
+
+        let content = std::fs::read_to_string(path).unwrap_or_default();
+        for line in content.lines().map(str::trim).take_while(|l| l.is_empty() || l.starts_with("//")) {
+            let Some((key, value)) = line.strip_prefix("//!").and_then(|l| l.split_once(':')) else { continue };
+            let value = value.trim();
+            match key.trim().to_lowercase().as_str() {
+                "description" => info.description = value.to_string(),
+                "model" => info.preferred_model = Some(value.to_string()),
+                "temperature" => info.temperature = value.parse().ok(),
+                "icon" => info.icon = Some(value.to_string()),
+                _ => {}
+            }
+        }
+        Some(info)
+    }
+
+    pub fn label(&self) -> String {
+        match &self.icon {
+            Some(icon) => format!("{} {}", icon, self.name),
+            None => self.name.clone(),
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub enum MessageRole {
     User,
     Agent,
+    /// 工具执行记录（例如 shell 命令及其输出）
+    Tool,
+}
+
+/// 模型请求的一次工具调用
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ToolCall {
+    pub id: String,
+    pub name: String,
+    /// JSON 文本形式的参数
+    pub arguments: String,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ChatMessage {
     pub role: MessageRole,
     pub content: String,
+    /// Agent 回复中请求的工具调用
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tool_calls: Vec<ToolCall>,
+    /// 工具结果所对应的调用，`/run` 等手动执行的记录为 None
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_call_id: Option<String>,
+    /// 生成这条回复所用的 token 与费用
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage: Option<TokenUsage>,
+    /// 生成这条回复的模型
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+}
+
+impl ChatMessage {
+    pub fn new(role: MessageRole, content: String) -> Self {
+        Self { role, content, tool_calls: Vec::new(), tool_call_id: None, usage: None, model: None }
+    }
+
+    pub fn tool_result(call: &ToolCall, content: String) -> Self {
+        Self { tool_call_id: Some(call.id.clone()), ..Self::new(MessageRole::Tool, content) }
+    }
 }
 
+/// 对话在某一位置之后的另一个版本，编辑消息或重新生成回复时产生
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ChatBranch {
+    /// 分叉位置：该版本替换当前对话中从此处开始的消息
+    pub at: usize,
+    pub messages: Vec<ChatMessage>,
+    /// 该版本内部的分叉，位置相对于本版本的开头
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub branches: Vec<ChatBranch>,
+}
+
+/// 会话以树的形式保存：`messages` 是当前显示的一条路径，
+/// `branches` 是这条路径上各个位置的其他版本
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct ChatSession {
     #[serde(skip)]
@@ -28,6 +172,19 @@ pub struct ChatSession {
     pub messages: Vec<ChatMessage>,
     pub context_mode: String,
     pub model_name: String,
+    /// 采样温度，由模式默认值或用户设置，None 表示使用模型默认值
+    #[serde(default)]
+    pub temperature: Option<f32>,
+    /// 本会话的系统提示，为空时使用由模式生成的默认提示
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub system_prompt: String,
+    /// 单次回复的最大 token 数，None 表示使用模型默认值
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub branches: Vec<ChatBranch>,
 }
 
 impl AgentConfig {
@@ -35,7 +192,11 @@ impl AgentConfig {
         let path = std::path::Path::new("agent_config.toml");
         if path.exists() {
             if let Ok(content) = std::fs::read_to_string(path) {
-                return toml::from_str(&content).unwrap_or_default();
+                let mut config: Self = toml::from_str(&content).unwrap_or_default();
+                if config.provider.migrate_api_key() {
+                    config.save();
+                }
+                return config;
             }
         }
         Self::default()
@@ -57,6 +218,89 @@ impl ChatSession {
             messages: Vec::new(),
             context_mode: mode,
             model_name: model,
+            temperature: None,
+            system_prompt: String::new(),
+            max_tokens: None,
+            top_p: None,
+            branches: Vec::new(),
+        }
+    }
+
+    /// 当前对话中所有回复的用量合计
+    pub fn usage(&self) -> TokenUsage {
+        let mut total = TokenUsage::default();
+        for usage in self.messages.iter().filter_map(|m| m.usage.as_ref()) {
+            total.add(usage);
+        }
+        total
+    }
+
+    /// 把从 `at` 开始的消息（连同其中的分叉）另存为一个版本，当前对话截断到 `at`
+    pub fn fork(&mut self, at: usize) {
+        let at = at.min(self.messages.len());
+        let messages = self.messages.split_off(at);
+        let (inner, outer): (Vec<_>, Vec<_>) = std::mem::take(&mut self.branches).into_iter().partition(|b| b.at > at);
+        self.branches = outer;
+        if messages.is_empty() && inner.is_empty() {
+            return;
+        }
+        let branches = inner.into_iter().map(|b| ChatBranch { at: b.at - at, ..b }).collect();
+        self.branches.push(ChatBranch { at, messages, branches });
+    }
+
+    /// 在 `at` 处可以切换到的其他版本，返回其在 `branches` 中的下标
+    pub fn alternatives(&self, at: usize) -> impl Iterator<Item = (usize, &ChatBranch)> {
+        self.branches.iter().enumerate().filter(move |(_, b)| b.at == at)
+    }
+
+    /// 切换到 `branches[index]`，当前从分叉处开始的消息另存为一个版本
+    pub fn switch_branch(&mut self, index: usize) {
+        if index >= self.branches.len() {
+            return;
+        }
+        let ChatBranch { at, messages, branches } = self.branches.remove(index);
+        self.fork(at);
+        self.messages.extend(messages);
+        self.branches.extend(branches.into_iter().map(|b| ChatBranch { at: b.at + at, ..b }));
+    }
+
+    /// 删除一条消息。工具调用与其结果成对出现，删除一方时同时处理另一方，避免请求被服务拒绝
+    pub fn remove_message(&mut self, index: usize) {
+        let Some(message) = self.messages.get(index) else { return };
+        let mut removed = vec![index];
+        if !message.tool_calls.is_empty() {
+            let ids: Vec<&String> = message.tool_calls.iter().map(|c| &c.id).collect();
+            removed.extend(
+                self.messages
+                    .iter()
+                    .enumerate()
+                    .skip(index + 1)
+                    .filter(|(_, m)| m.tool_call_id.as_ref().is_some_and(|id| ids.contains(&id)))
+                    .map(|(i, _)| i),
+            );
+        }
+        if let Some(id) = message.tool_call_id.clone() {
+            if let Some(call_message) = self.messages[..index].iter_mut().rev().find(|m| m.tool_calls.iter().any(|c| c.id == id)) {
+                call_message.tool_calls.retain(|c| c.id != id);
+            }
+        }
+        removed.sort_unstable();
+        for &i in removed.iter().rev() {
+            self.messages.remove(i);
+            for branch in self.branches.iter_mut().filter(|b| b.at > i) {
+                branch.at -= 1;
+            }
+        }
+    }
+
+    /// 切换模式并应用其声明的默认模型与温度
+    pub fn apply_mode(&mut self, mode: &ModeInfo) {
+        self.context_mode = mode.name.clone();
+        if let Some(model) = &mode.preferred_model {
+            self.model_name = model.clone();
+        }
+        if mode.temperature.is_some() {
+            self.temperature = mode.temperature;
         }
     }
 
@@ -0,0 +1,137 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::sync::atomic::AtomicBool;
+use super::models::ToolCall;
+use super::provider::{self, ChatRequest, Reply};
+use super::usage::TokenUsage;
+
+// ----------------------------------------------------------------------------
+// 本地模型：通过 Ollama 的 HTTP 接口（默认 localhost:11434）离线运行
+// ----------------------------------------------------------------------------
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct OllamaConfig {
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    #[serde(default = "default_base_url")]
+    pub base_url: String,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn default_base_url() -> String {
+    "http://localhost:11434".to_string()
+}
+
+impl Default for OllamaConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_enabled(),
+            base_url: default_base_url(),
+        }
+    }
+}
+
+impl OllamaConfig {
+    fn endpoint(&self, path: &str) -> String {
+        format!("{}/{}", self.base_url.trim().trim_end_matches('/'), path)
+    }
+
+    /// 已安装的模型。会阻塞，应在后台线程中调用
+    pub fn fetch_models(&self) -> Result<Vec<String>, String> {
+        let response = provider::http().get(&self.endpoint("api/tags")).call().map_err(|e| match e {
+            ureq::Error::Transport(_) => format!("Ollama is not running at {}", self.base_url.trim()),
+            e => provider::describe_error(e),
+        })?;
+        let body: Value = response.into_json().map_err(|e| format!("Invalid response: {}", e))?;
+        let mut models: Vec<String> = body["models"]
+            .as_array()
+            .ok_or("Invalid response: missing model list")?
+            .iter()
+            .filter_map(|m| m["name"].as_str().map(String::from))
+            .collect();
+        models.sort();
+        Ok(models)
+    }
+
+    /// 以流式方式发送对话请求。Ollama 每行返回一个 JSON 对象，最后一个带有 `"done": true`
+    pub fn stream(&self, request: &ChatRequest, cancelled: &AtomicBool, mut on_delta: impl FnMut(String) -> bool) -> Result<Reply, String> {
+        let mut body = json!({
+            "model": request.model,
+            "messages": request.messages.iter().map(to_ollama_message).collect::<Vec<_>>(),
+            "stream": true,
+        });
+        // 采样参数放在 options 中，最大长度对应 num_predict
+        let mut options = serde_json::Map::new();
+        if let Some(t) = request.temperature {
+            options.insert("temperature".into(), json!(t));
+        }
+        if let Some(max_tokens) = request.max_tokens {
+            options.insert("num_predict".into(), json!(max_tokens));
+        }
+        if let Some(top_p) = request.top_p {
+            options.insert("top_p".into(), json!(top_p));
+        }
+        if !options.is_empty() {
+            body["options"] = Value::Object(options);
+        }
+        if !request.tools.is_empty() {
+            body["tools"] = json!(request.tools);
+        }
+        let response = provider::http()
+            .post(&self.endpoint("api/chat"))
+            .send_json(body)
+            .map_err(provider::describe_error)?;
+        let mut calls = Vec::new();
+        let mut usage = None;
+        for line in provider::StreamLines::new(response, cancelled) {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let event: Value = serde_json::from_str(&line).map_err(|e| format!("Invalid response: {}", e))?;
+            if let Some(error) = event["error"].as_str() {
+                return Err(error.to_string());
+            }
+            // Ollama 的工具调用没有 ID，参数为 JSON 对象
+            for call in event["message"]["tool_calls"].as_array().into_iter().flatten() {
+                calls.push(ToolCall {
+                    id: format!("call_{}", calls.len()),
+                    name: call["function"]["name"].as_str().unwrap_or_default().to_string(),
+                    arguments: call["function"]["arguments"].to_string(),
+                });
+            }
+            if let Some(delta) = event["message"]["content"].as_str().filter(|d| !d.is_empty()) {
+                if !on_delta(delta.to_string()) {
+                    return Ok(Reply::default());
+                }
+            }
+            if event["done"].as_bool() == Some(true) {
+                // 最后一行带有提示与生成部分的 token 数
+                if let Some(prompt_tokens) = event["prompt_eval_count"].as_u64() {
+                    usage = Some(TokenUsage {
+                        prompt_tokens,
+                        completion_tokens: event["eval_count"].as_u64().unwrap_or(0),
+                        ..Default::default()
+                    });
+                }
+                break;
+            }
+        }
+        Ok(Reply { tool_calls: calls, usage })
+    }
+}
+
+/// OpenAI 格式的消息转为 Ollama 格式：工具调用的参数是 JSON 对象而不是文本
+fn to_ollama_message(message: &Value) -> Value {
+    let mut message = message.clone();
+    for call in message["tool_calls"].as_array_mut().into_iter().flatten() {
+        let arguments = call["function"]["arguments"].as_str().and_then(|a| serde_json::from_str::<Value>(a).ok());
+        if let Some(arguments) = arguments {
+            call["function"]["arguments"] = arguments;
+        }
+    }
+    message
+}
@@ -1,85 +1,315 @@
 use egui::Ui;
 use crate::{Plugin, AppCommand, Tab};
-use super::models::{AgentConfig, ChatSession};
+use super::models::{AgentConfig, ChatSession, ModeInfo};
+use super::provider::ModelCatalog;
+use super::sessions::{self, SessionEntry, SessionMoves};
 use super::tab::AgentTab;
+use super::usage;
 use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+/// 会话列表中的操作，在列表绘制完成后统一执行
+enum SessionAction {
+    Open(PathBuf),
+    Move(PathBuf, Option<String>),
+    ToggleTag(PathBuf, String),
+}
 
 pub struct AgentPlugin {
     config: AgentConfig,
     show_session_creator: bool,
     new_session_name: String,
+    /// Shell 工具白名单 / 黑名单的编辑缓冲（每行一项）
+    allowlist_text: String,
+    denylist_text: String,
+    /// 会话列表只显示带有该标签的会话
+    tag_filter: Option<String>,
+    new_folder_name: String,
+    new_tag_name: String,
+    session_moves: SessionMoves,
+    models: ModelCatalog,
+    /// 启动后是否已请求过模型列表
+    models_requested: bool,
+    /// 正在输入的新 API Key，None 表示未在编辑
+    key_input: Option<String>,
+    show_key: bool,
+    key_error: Option<String>,
 }
 
 impl AgentPlugin {
     pub fn new() -> Self {
+        let config = AgentConfig::load();
+        let allowlist_text = config.shell_policy.allowlist.join("\n");
+        let denylist_text = config.shell_policy.denylist.join("\n");
         Self {
-            config: AgentConfig::load(),
+            config,
             show_session_creator: false,
             new_session_name: "New Chat".to_string(),
+            allowlist_text,
+            denylist_text,
+            tag_filter: None,
+            new_folder_name: String::new(),
+            new_tag_name: String::new(),
+            session_moves: Arc::new(Mutex::new(Vec::new())),
+            models: ModelCatalog::default(),
+            models_requested: false,
+            key_input: None,
+            show_key: false,
+            key_error: None,
         }
     }
 
-    fn get_available_modes(&self) -> Vec<String> {
-        let mut modes = vec!["Chat".to_string(), "Plan".to_string(), "Solo".to_string()];
+    fn get_available_modes(&self) -> Vec<ModeInfo> {
+        let mut modes = vec![
+            ModeInfo::builtin("Chat", "💬", "General conversation."),
+            ModeInfo::builtin("Plan", "🧭", "Discuss and plan before making changes."),
+            ModeInfo::builtin("Solo", "🤖", "Work autonomously on the task."),
+        ];
         
         if let Some(dir) = &self.config.script_directory {
             if let Ok(entries) = std::fs::read_dir(dir) {
                 for entry in entries.flatten() {
                     let path = entry.path();
                     if path.is_file() && path.extension().and_then(|s| s.to_str()) == Some("rhai") {
-                        if let Some(name) = path.file_stem().and_then(|s| s.to_str()) {
-                            let mode_name = name.to_string();
-                            if !modes.contains(&mode_name) {
-                                modes.push(mode_name);
-                            }
+                        if let Some(info) = ModeInfo::from_script(&path) {
+                            // 同名脚本覆盖内置模式
+                            modes.retain(|m| m.name != info.name);
+                            modes.push(info);
                         }
                     }
                 }
             }
         }
-        modes.sort();
+        modes.sort_by(|a, b| a.name.cmp(&b.name));
         modes
     }
 
     fn create_and_open_session(&mut self, path: PathBuf, control: &mut Vec<AppCommand>) {
-        if let Ok(session) = ChatSession::load(&path) {
-             let modes = self.get_available_modes();
-             control.push(AppCommand::OpenTab(Tab::new(Box::new(AgentTab::new(session, modes)))));
-             self.show_session_creator = false;
+        match ChatSession::load(&path) {
+            Ok(session) => {
+                let modes = self.get_available_modes();
+                control.push(AppCommand::OpenTab(Tab::new(Box::new(AgentTab::new(session, modes, self.session_moves.clone(), self.models.clone())))));
+                self.show_session_creator = false;
+            }
+            Err(e) => control.push(AppCommand::Notify {
+                message: format!("Failed to open session {}: {}", path.display(), e),
+                level: crate::NotificationLevel::Error,
+            }),
         }
     }
 
-    fn get_available_sessions(&self) -> Vec<PathBuf> {
-        let mut sessions = Vec::new();
-        let folder = self.config.default_chat_dir.clone().unwrap_or_else(|| {
-            std::env::current_dir().unwrap_or_default()
+    /// 打开最近修改的会话，没有会话时新建一个；之后在标签页的侧边栏中切换
+    fn open_recent_session(&mut self, control: &mut Vec<AppCommand>) {
+        // 会话目录中可能混有其他 toml 文件，跳过无法读取为会话的文件
+        let recent = sessions::list_sessions(&self.config.chat_dir())
+            .into_iter()
+            .find(|e| ChatSession::load(&e.path).is_ok())
+            .map(|e| e.path);
+        let path = match recent {
+            Some(path) => Ok(path),
+            None => sessions::create_session(&self.config, "New Chat", self.get_available_modes().first()),
+        };
+        match path {
+            Ok(path) => self.create_and_open_session(path, control),
+            Err(e) => control.push(AppCommand::Notify {
+                message: format!("Failed to create session: {}", e),
+                level: crate::NotificationLevel::Error,
+            }),
+        }
+    }
+
+    /// 会话列表中的一行：打开按钮、标签与移动 / 标签菜单
+    fn session_row(&mut self, ui: &mut Ui, entry: &SessionEntry, folders: &[String], all_tags: &[String], actions: &mut Vec<SessionAction>) {
+        let filename = entry.path.file_stem().and_then(|s| s.to_str()).unwrap_or("Unknown");
+        let datetime: chrono::DateTime<chrono::Local> = entry.modified.into();
+        let date_str = datetime.format("%Y-%m-%d %H:%M").to_string();
+        let tags = self.config.tags_for(&entry.path).to_vec();
+
+        ui.horizontal(|ui| {
+            if ui.button(format!("💬 {}", filename)).clicked() {
+                actions.push(SessionAction::Open(entry.path.clone()));
+            }
+            for tag in &tags {
+                tag_chip(ui, tag, self.config.tag_color(tag));
+            }
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                ui.menu_button("⋯", |ui| {
+                    ui.menu_button("📁 Move to", |ui| {
+                        if entry.folder.is_some() && ui.button("Session Root").clicked() {
+                            actions.push(SessionAction::Move(entry.path.clone(), None));
+                            ui.close_menu();
+                        }
+                        for folder in folders.iter().filter(|f| entry.folder.as_ref() != Some(*f)) {
+                            if ui.button(format!("📁 {}", folder)).clicked() {
+                                actions.push(SessionAction::Move(entry.path.clone(), Some(folder.clone())));
+                                ui.close_menu();
+                            }
+                        }
+                        ui.separator();
+                        ui.horizontal(|ui| {
+                            ui.add(egui::TextEdit::singleline(&mut self.new_folder_name).hint_text("New folder").desired_width(120.0));
+                            let name = sessions::sanitize_folder_name(&self.new_folder_name);
+                            if ui.add_enabled(!name.is_empty(), egui::Button::new("Move")).clicked() {
+                                actions.push(SessionAction::Move(entry.path.clone(), Some(name)));
+                                self.new_folder_name.clear();
+                                ui.close_menu();
+                            }
+                        });
+                    });
+                    ui.menu_button("🏷 Tags", |ui| {
+                        for tag in all_tags {
+                            let mut on = tags.contains(tag);
+                            if ui.checkbox(&mut on, egui::RichText::new(tag).color(self.config.tag_color(tag))).changed() {
+                                actions.push(SessionAction::ToggleTag(entry.path.clone(), tag.clone()));
+                            }
+                        }
+                        if !all_tags.is_empty() {
+                            ui.separator();
+                        }
+                        ui.horizontal(|ui| {
+                            ui.add(egui::TextEdit::singleline(&mut self.new_tag_name).hint_text("New tag").desired_width(120.0));
+                            let tag = self.new_tag_name.trim().to_string();
+                            if ui.add_enabled(!tag.is_empty() && !tags.contains(&tag), egui::Button::new("Add")).clicked() {
+                                actions.push(SessionAction::ToggleTag(entry.path.clone(), tag));
+                                self.new_tag_name.clear();
+                            }
+                        });
+                    });
+                });
+                ui.weak(date_str);
+            });
         });
+        ui.separator();
+    }
+
+    fn apply_session_action(&mut self, action: SessionAction, control: &mut Vec<AppCommand>) {
+        match action {
+            SessionAction::Open(path) => self.create_and_open_session(path, control),
+            SessionAction::Move(path, folder) => {
+                match sessions::move_session(&mut self.config, &self.session_moves, &path, folder.as_deref()) {
+                    Ok(_) => self.config.save(),
+                    Err(e) => control.push(AppCommand::Notify {
+                        message: format!("Failed to move session: {}", e),
+                        level: crate::NotificationLevel::Error,
+                    }),
+                }
+            }
+            SessionAction::ToggleTag(path, tag) => {
+                self.config.toggle_tag(&path, &tag);
+                self.config.save();
+            }
+        }
+    }
 
-        if let Ok(entries) = std::fs::read_dir(folder) {
-            for entry in entries.flatten() {
-                let path = entry.path();
-                if path.is_file() && path.extension().and_then(|s| s.to_str()) == Some("toml") {
-                    // Check if it's not a config file (simple heuristic: if it contains session data)
-                    // For now, let's just include all .toml except known configs
-                    if let Some(filename) = path.file_name().and_then(|s| s.to_str()) {
-                        if filename != "agent_config.toml" && filename != "launcher_config.toml" {
-                            sessions.push(path);
+    /// API Key 只显示遮盖后的形式，修改后直接写入系统钥匙串
+    fn api_key_ui(&mut self, ui: &mut Ui) {
+        let palette = crate::theme::palette(ui.ctx());
+        let name = self.config.provider.secret_name();
+        ui.vertical(|ui| {
+            if let Some(mut input) = self.key_input.take() {
+                let mut editing = true;
+                ui.horizontal(|ui| {
+                    let response = ui.add(
+                        egui::TextEdit::singleline(&mut input)
+                            .password(!self.show_key)
+                            .hint_text("Paste the API key")
+                            .desired_width(200.0),
+                    );
+                    if ui.selectable_label(self.show_key, "👁").on_hover_text("Show the key while typing").clicked() {
+                        self.show_key = !self.show_key;
+                    }
+                    let submitted = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                    if ui.add_enabled(!input.trim().is_empty(), egui::Button::new("Save")).clicked() || (submitted && !input.trim().is_empty()) {
+                        match crate::secrets::set(&name, input.trim()) {
+                            Ok(()) => {
+                                editing = false;
+                                self.key_error = None;
+                                self.config.provider.api_key.clear();
+                                self.config.save();
+                                self.models.refresh(&self.config, ui.ctx());
+                            }
+                            Err(e) => self.key_error = Some(format!("Could not save to the system keychain: {}", e)),
                         }
                     }
+                    if ui.button("Cancel").clicked() {
+                        editing = false;
+                        self.key_error = None;
+                    }
+                });
+                if editing {
+                    self.key_input = Some(input);
+                } else {
+                    self.show_key = false;
                 }
+            } else {
+                ui.horizontal(|ui| {
+                    let legacy = self.config.provider.api_key.trim().to_string();
+                    let stored = match crate::secrets::get(&name) {
+                        Ok(stored) => stored,
+                        Err(e) => {
+                            ui.colored_label(palette.error, "⚠ Keychain unavailable").on_hover_text(e);
+                            None
+                        }
+                    };
+                    let has_key = stored.is_some() || !legacy.is_empty();
+                    match stored {
+                        Some(key) => {
+                            ui.monospace(crate::secrets::mask(&key)).on_hover_text("Stored in the system keychain");
+                        }
+                        None if !legacy.is_empty() => {
+                            ui.monospace(crate::secrets::mask(&legacy));
+                            ui.colored_label(palette.warning, "⚠ Plain text").on_hover_text("Saved in agent_config.toml because the system keychain could not be used");
+                        }
+                        None if std::env::var("OPENAI_API_KEY").is_ok_and(|k| !k.trim().is_empty()) => {
+                            ui.weak("Using OPENAI_API_KEY");
+                        }
+                        None => {
+                            ui.weak("Not set");
+                        }
+                    }
+                    if ui.button(if has_key { "Change…" } else { "Set…" }).clicked() {
+                        self.key_input = Some(String::new());
+                        self.key_error = None;
+                    }
+                    if has_key && ui.button("Remove").on_hover_text("Delete the key from the system keychain").clicked() {
+                        match crate::secrets::delete(&name) {
+                            Ok(()) => {
+                                self.key_error = None;
+                                self.config.provider.api_key.clear();
+                                self.config.save();
+                            }
+                            Err(e) => self.key_error = Some(format!("Could not remove the key: {}", e)),
+                        }
+                    }
+                });
             }
-        }
-        sessions.sort_by(|a, b| b.metadata().and_then(|m| m.modified()).unwrap_or(std::time::SystemTime::UNIX_EPOCH)
-            .cmp(&a.metadata().and_then(|m| m.modified()).unwrap_or(std::time::SystemTime::UNIX_EPOCH)));
-        sessions
+            if let Some(error) = &self.key_error {
+                ui.colored_label(palette.error, error);
+            }
+        });
     }
 }
 
+/// 带底色的小标签
+fn tag_chip(ui: &mut Ui, tag: &str, color: egui::Color32) {
+    ui.label(egui::RichText::new(format!(" {} ", tag)).small().color(egui::Color32::BLACK).background_color(color));
+}
+
 impl Plugin for AgentPlugin {
     fn name(&self) -> &str {
         crate::plugins::PLUGIN_NAME_AGENT
     }
 
+    fn on_shutdown(&mut self) {
+        self.config.save();
+    }
+
+    fn backup_files(&self) -> Vec<PathBuf> {
+        let mut files: Vec<PathBuf> = sessions::list_sessions(&self.config.chat_dir()).into_iter().map(|e| e.path).collect();
+        files.push(usage::USAGE_FILE.into());
+        files
+    }
+
     fn on_settings_ui(&mut self, ui: &mut Ui) {
         ui.vertical(|ui| {
             ui.heading("Agent Settings");
@@ -103,6 +333,126 @@ impl Plugin for AgentPlugin {
                 });
                 ui.add_space(4.0);
                 ui.weak("Each .rhai file here becomes a selectable Agent mode.");
+                ui.weak("Declare mode metadata with header comments, e.g. `//! model: gpt-4o`, `//! temperature: 0.2`, `//! icon: 🧭`, `//! description: ...`.");
+                ui.weak("Scripts may define `system_prompt(prompt)`, `before_send(text)` and `after_reply(text)` returning strings, and `tools()` returning the names of the tools the mode may use.");
+                ui.weak("Prompt templates live in `prompts.toml` as `[[prompt]]` entries with `name`, `description` and `template`; type `/name` in the input box to use one. Templates may contain {selection}, {file}, {language}, {clipboard} and {input}.");
+            });
+
+            ui.add_space(8.0);
+
+            ui.group(|ui| {
+                ui.label("Model Provider");
+                ui.weak("Any OpenAI-compatible chat completion API. Keys are kept in the system keychain, one per base URL; without a key the OPENAI_API_KEY environment variable is used.");
+                egui::Grid::new("agent_provider_grid").num_columns(2).show(ui, |ui| {
+                    ui.label("Base URL");
+                    ui.add(egui::TextEdit::singleline(&mut self.config.provider.base_url).desired_width(280.0));
+                    ui.end_row();
+                    ui.label("API Key");
+                    self.api_key_ui(ui);
+                    ui.end_row();
+                    ui.label("Default Model");
+                    let models = self.models.models();
+                    if models.is_empty() {
+                        ui.add(egui::TextEdit::singleline(&mut self.config.provider.default_model).desired_width(280.0));
+                    } else {
+                        egui::ComboBox::from_id_salt("agent_default_model")
+                            .selected_text(&self.config.provider.default_model)
+                            .width(280.0)
+                            .show_ui(ui, |ui| {
+                                for model in models {
+                                    ui.selectable_value(&mut self.config.provider.default_model, model.clone(), model);
+                                }
+                            });
+                    }
+                    ui.end_row();
+                });
+                ui.horizontal(|ui| {
+                    if ui.button("Save & Refresh Models").clicked() {
+                        self.config.save();
+                        self.models.refresh(&self.config, ui.ctx());
+                    }
+                    if self.models.is_loading() {
+                        ui.spinner();
+                    } else if let Some(error) = self.models.error() {
+                        ui.colored_label(crate::theme::palette(ui.ctx()).error, error);
+                    } else {
+                        ui.weak(format!("{} models available", self.models.models().len()));
+                    }
+                });
+            });
+
+            ui.add_space(8.0);
+
+            ui.group(|ui| {
+                ui.label("Local Models (Ollama)");
+                ui.weak("Installed Ollama models appear in the model list and run offline.");
+                ui.checkbox(&mut self.config.ollama.enabled, "Use local models");
+                ui.add_enabled_ui(self.config.ollama.enabled, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label("Server");
+                        ui.add(egui::TextEdit::singleline(&mut self.config.ollama.base_url).desired_width(240.0));
+                    });
+                });
+                ui.horizontal(|ui| {
+                    if ui.button("Save & Refresh Models").clicked() {
+                        self.config.save();
+                        self.models.refresh(&self.config, ui.ctx());
+                    }
+                    if !self.config.ollama.enabled {
+                        return;
+                    }
+                    if let Some(error) = self.models.local_error() {
+                        ui.colored_label(crate::theme::palette(ui.ctx()).warning, error);
+                    } else {
+                        ui.weak(format!("{} models installed", self.models.local_models().len()));
+                    }
+                });
+            });
+
+            ui.add_space(8.0);
+
+            ui.group(|ui| {
+                ui.label("Usage & Budget");
+                ui.weak(format!("This month: {}", usage::UsageLog::load().this_month().summary()));
+                let mut changed = false;
+                ui.horizontal(|ui| {
+                    let mut enabled = self.config.monthly_budget.is_some();
+                    changed |= ui.checkbox(&mut enabled, "Monthly budget (USD)").changed();
+                    if enabled != self.config.monthly_budget.is_some() {
+                        self.config.monthly_budget = enabled.then_some(10.0);
+                    }
+                    if let Some(budget) = &mut self.config.monthly_budget {
+                        changed |= ui.add(egui::DragValue::new(budget).range(0.0..=100_000.0).speed(1.0).prefix("$")).changed();
+                    }
+                });
+                ui.add_space(4.0);
+                ui.weak("Prices in USD per million tokens. A model uses the longest matching name prefix; models without a price show token counts only.");
+                let mut removed = None;
+                egui::Grid::new("agent_price_grid").num_columns(4).show(ui, |ui| {
+                    ui.weak("Model");
+                    ui.weak("Prompt");
+                    ui.weak("Completion");
+                    ui.end_row();
+                    for (index, price) in self.config.prices.iter_mut().enumerate() {
+                        changed |= ui.add(egui::TextEdit::singleline(&mut price.model).desired_width(160.0)).lost_focus();
+                        changed |= ui.add(egui::DragValue::new(&mut price.prompt).range(0.0..=1000.0).speed(0.01).prefix("$")).changed();
+                        changed |= ui.add(egui::DragValue::new(&mut price.completion).range(0.0..=1000.0).speed(0.01).prefix("$")).changed();
+                        if ui.small_button("🗑").clicked() {
+                            removed = Some(index);
+                        }
+                        ui.end_row();
+                    }
+                });
+                if let Some(index) = removed {
+                    self.config.prices.remove(index);
+                    changed = true;
+                }
+                if ui.button("➕ Add Price").clicked() {
+                    self.config.prices.push(usage::ModelPrice { model: String::new(), prompt: 0.0, completion: 0.0 });
+                }
+                if changed {
+                    self.config.save();
+                }
             });
 
             ui.add_space(8.0);
@@ -124,10 +474,63 @@ impl Plugin for AgentPlugin {
                     }
                 });
             });
+
+            ui.add_space(8.0);
+
+            ui.group(|ui| {
+                ui.label("Shell Command Tool");
+                ui.weak("Commands must start with an allowlisted prefix and must not contain a denylisted fragment. One entry per line.");
+                ui.horizontal_top(|ui| {
+                    ui.vertical(|ui| {
+                        ui.label("Allowlist");
+                        ui.add(egui::TextEdit::multiline(&mut self.allowlist_text).code_editor().desired_rows(6).desired_width(180.0));
+                    });
+                    ui.vertical(|ui| {
+                        ui.label("Denylist");
+                        ui.add(egui::TextEdit::multiline(&mut self.denylist_text).code_editor().desired_rows(6).desired_width(180.0));
+                    });
+                });
+                if ui.button("Save Command Rules").clicked() {
+                    let parse = |text: &str| -> Vec<String> {
+                        text.lines().map(|l| l.trim()).filter(|l| !l.is_empty()).map(String::from).collect()
+                    };
+                    self.config.shell_policy.allowlist = parse(&self.allowlist_text);
+                    self.config.shell_policy.denylist = parse(&self.denylist_text);
+                    self.config.save();
+                    super::tools::register_builtin_tools(ui.ctx(), &self.config.shell_policy);
+                }
+            });
+
+            let tags = self.config.all_tags();
+            if !tags.is_empty() {
+                ui.add_space(8.0);
+
+                ui.group(|ui| {
+                    ui.label("Session Tags");
+                    ui.weak("Tags are assigned from the session manager. Click a color to change it.");
+                    for tag in &tags {
+                        ui.horizontal(|ui| {
+                            let [r, g, b, _] = self.config.tag_color(tag).to_array();
+                            let mut color = [r, g, b];
+                            if ui.color_edit_button_srgb(&mut color).changed() {
+                                self.config.tag_colors.insert(tag.clone(), color);
+                                self.config.save();
+                            }
+                            tag_chip(ui, tag, self.config.tag_color(tag));
+                        });
+                    }
+                });
+            }
         });
     }
 
     fn on_global_ui(&mut self, ctx: &egui::Context, control: &mut Vec<AppCommand>) {
+        if !self.models_requested {
+            self.models_requested = true;
+            self.models.refresh(&self.config, ctx);
+            super::tools::register_builtin_tools(ctx, &self.config.shell_policy);
+        }
+
         if self.show_session_creator {
             let mut open = true;
             egui::Window::new("Agent Session Manager")
@@ -149,25 +552,12 @@ impl Plugin for AgentPlugin {
                             ui.label("Name:");
                             ui.text_edit_singleline(&mut self.new_session_name);
                             if ui.button("🚀 Create").clicked() {
-                                let folder = self.config.default_chat_dir.clone().unwrap_or_else(|| {
-                                    std::env::current_dir().unwrap_or_default()
-                                });
-                                
-                                let safe_name = self.new_session_name.replace(|c: char| !c.is_alphanumeric() && c != '_' && c != '-', "_");
-                                let filename = format!("{}.toml", safe_name);
-                                let full_path = folder.join(filename);
-
-                                let mode = self.get_available_modes().get(0).cloned().unwrap_or("Chat".into());
-                                let mut session = ChatSession::new(mode, "Gemini Pro".into());
-                                session.path = Some(full_path.clone());
-
-                                if let Err(e) = session.save() {
-                                     control.push(AppCommand::Notify { 
-                                         message: format!("Failed to create session: {}", e), 
-                                         level: crate::NotificationLevel::Error 
-                                     });
-                                } else {
-                                    self.create_and_open_session(full_path, control);
+                                match sessions::create_session(&self.config, &self.new_session_name, self.get_available_modes().first()) {
+                                    Ok(full_path) => self.create_and_open_session(full_path, control),
+                                    Err(e) => control.push(AppCommand::Notify {
+                                        message: format!("Failed to create session: {}", e),
+                                        level: crate::NotificationLevel::Error,
+                                    }),
                                 }
                             }
                         });
@@ -178,32 +568,65 @@ impl Plugin for AgentPlugin {
                     // 2. Existing Sessions List
                     ui.label(egui::RichText::new("Open Existing Session").strong());
                     ui.add_space(4.0);
-                    
-                    let sessions = self.get_available_sessions();
-                    if sessions.is_empty() {
+
+                    let root = self.config.chat_dir();
+                    let all_tags = self.config.all_tags();
+                    if self.tag_filter.as_ref().is_some_and(|t| !all_tags.contains(t)) {
+                        self.tag_filter = None;
+                    }
+                    if !all_tags.is_empty() {
+                        ui.horizontal_wrapped(|ui| {
+                            ui.weak("Filter:");
+                            if ui.selectable_label(self.tag_filter.is_none(), "All").clicked() {
+                                self.tag_filter = None;
+                            }
+                            for tag in &all_tags {
+                                let selected = self.tag_filter.as_ref() == Some(tag);
+                                let label = egui::RichText::new(format!("● {}", tag)).color(self.config.tag_color(tag));
+                                if ui.selectable_label(selected, label).clicked() {
+                                    self.tag_filter = if selected { None } else { Some(tag.clone()) };
+                                }
+                            }
+                        });
+                        ui.add_space(4.0);
+                    }
+
+                    let sessions: Vec<SessionEntry> = sessions::list_sessions(&root)
+                        .into_iter()
+                        .filter(|s| self.tag_filter.as_ref().is_none_or(|t| self.config.tags_for(&s.path).contains(t)))
+                        .collect();
+                    let folders = sessions::list_folders(&root);
+                    let mut actions = Vec::new();
+                    if sessions.is_empty() && folders.is_empty() {
                         ui.weak("No sessions found in storage directory.");
                     } else {
                         egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
-                            for path in sessions {
-                                let filename = path.file_stem().and_then(|s| s.to_str()).unwrap_or("Unknown");
-                                let date_str = path.metadata().and_then(|m| m.modified()).ok()
-                                    .map(|t| {
-                                        let datetime: chrono::DateTime<chrono::Local> = t.into();
-                                        datetime.format("%Y-%m-%d %H:%M").to_string()
-                                    }).unwrap_or_default();
-
-                                ui.horizontal(|ui| {
-                                    if ui.button(format!("💬 {}", filename)).clicked() {
-                                        self.create_and_open_session(path.clone(), control);
-                                    }
-                                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                                        ui.weak(date_str);
+                            for entry in sessions.iter().filter(|s| s.folder.is_none()) {
+                                self.session_row(ui, entry, &folders, &all_tags, &mut actions);
+                            }
+                            for folder in &folders {
+                                let entries: Vec<&SessionEntry> = sessions.iter().filter(|s| s.folder.as_ref() == Some(folder)).collect();
+                                // 按标签筛选时隐藏没有匹配会话的文件夹
+                                if entries.is_empty() && self.tag_filter.is_some() {
+                                    continue;
+                                }
+                                egui::CollapsingHeader::new(format!("📁 {} ({})", folder, entries.len()))
+                                    .id_salt(("agent_session_folder", folder))
+                                    .default_open(true)
+                                    .show(ui, |ui| {
+                                        if entries.is_empty() {
+                                            ui.weak("Empty folder");
+                                        }
+                                        for entry in entries {
+                                            self.session_row(ui, entry, &folders, &all_tags, &mut actions);
+                                        }
                                     });
-                                });
-                                ui.separator();
                             }
                         });
                     }
+                    for action in actions {
+                        self.apply_session_action(action, control);
+                    }
 
                     ui.add_space(8.0);
                     if ui.button("📂 Browse Files...").clicked() {
@@ -217,8 +640,14 @@ impl Plugin for AgentPlugin {
         }
     }
 
-    fn on_tab_menu(&mut self, ui: &mut Ui, _control: &mut Vec<AppCommand>) {
+    fn on_tab_menu(&mut self, ui: &mut Ui, control: &mut Vec<AppCommand>) {
         if ui.button("Agent Tab").clicked() {
+            self.open_recent_session(control);
+            ui.close_menu();
+        }
+        if ui.button("Agent Sessions...").clicked() {
+            // 标签页的侧边栏可能改过标签索引，先重新读取
+            self.config = AgentConfig::load();
             self.show_session_creator = true;
             ui.close_menu();
         }
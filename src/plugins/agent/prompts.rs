@@ -0,0 +1,124 @@
+use serde::Deserialize;
+use std::time::{Duration, Instant};
+use super::models::AgentConfig;
+
+/// 提示模板文件，位于脚本目录下
+pub const PROMPTS_FILE: &str = "prompts.toml";
+
+/// 模板列表的刷新间隔，修改文件后无需重启
+const REFRESH_INTERVAL: Duration = Duration::from_secs(2);
+
+// ----------------------------------------------------------------------------
+// 提示模板：在输入框中以 `/名称` 调用，模板中的占位符按当前上下文填充
+//
+//   [[prompt]]
+//   name = "explain"
+//   description = "Explain the selected code"
+//   template = "Explain this {language} code from {file}:\n\n{selection}"
+//
+// 占位符：{selection} 编辑器选中的文本，{file} 文件路径，{language} 语言，
+// {clipboard} 剪贴板文本，{input} 命令名之后输入的内容
+// ----------------------------------------------------------------------------
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct PromptTemplate {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub template: String,
+}
+
+#[derive(Deserialize, Default)]
+struct PromptFile {
+    #[serde(default)]
+    prompt: Vec<PromptTemplate>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PromptLibrary {
+    prompts: Vec<PromptTemplate>,
+    loaded_at: Option<Instant>,
+    error: Option<String>,
+}
+
+impl PromptLibrary {
+    /// 按间隔重新读取脚本目录下的模板文件
+    pub fn refresh(&mut self) {
+        if self.loaded_at.is_some_and(|t| t.elapsed() < REFRESH_INTERVAL) {
+            return;
+        }
+        self.loaded_at = Some(Instant::now());
+        self.error = None;
+        self.prompts = match AgentConfig::load().script_directory.map(|d| d.join(PROMPTS_FILE)) {
+            Some(path) if path.is_file() => match std::fs::read_to_string(&path).map_err(|e| e.to_string()).and_then(|content| {
+                toml::from_str::<PromptFile>(&content).map_err(|e| e.to_string())
+            }) {
+                Ok(file) => file.prompt,
+                Err(e) => {
+                    self.error = Some(format!("{}: {}", PROMPTS_FILE, e));
+                    Vec::new()
+                }
+            },
+            _ => Vec::new(),
+        };
+    }
+
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.prompts.is_empty()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&PromptTemplate> {
+        self.prompts.iter().find(|p| p.name == name)
+    }
+
+    /// 名称以 `query` 开头的模板在前，其余包含 `query` 的在后
+    pub fn matches(&self, query: &str) -> Vec<&PromptTemplate> {
+        let query = query.to_lowercase();
+        let (mut prefixed, mut contained): (Vec<_>, Vec<_>) = self
+            .prompts
+            .iter()
+            .filter(|p| p.name.to_lowercase().contains(&query))
+            .partition(|p| p.name.to_lowercase().starts_with(&query));
+        prefixed.append(&mut contained);
+        prefixed
+    }
+}
+
+/// 解析 `/名称 内容` 形式的输入，返回模板名与其后的内容
+pub fn parse_command(text: &str) -> Option<(&str, &str)> {
+    let rest = text.strip_prefix('/')?;
+    let (name, input) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+    Some((name, input.trim()))
+}
+
+/// 填充模板中的占位符，未知的占位符原样保留。逐个替换，填入的内容不会再被当作占位符
+pub fn expand(template: &str, context: Option<&crate::EditorContext>, input: &str) -> String {
+    let mut text = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        text.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let Some(end) = rest.find('}') else { break };
+        let value = match &rest[1..end] {
+            "selection" => context.map(|c| c.selection.clone()),
+            "file" => context.and_then(|c| c.path.as_ref()).map(|p| p.to_string_lossy().to_string()),
+            "language" => context.map(|c| c.language.clone()),
+            "input" => Some(input.to_string()),
+            // 只在模板用到时读取剪贴板
+            "clipboard" => arboard::Clipboard::new().and_then(|mut c| c.get_text()).ok(),
+            _ => {
+                text.push('{');
+                rest = &rest[1..];
+                continue;
+            }
+        };
+        text.push_str(&value.unwrap_or_default());
+        rest = &rest[end + 1..];
+    }
+    text.push_str(rest);
+    text
+}
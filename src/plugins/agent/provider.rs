@@ -0,0 +1,562 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::io::BufRead;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use super::models::{AgentConfig, ChatSession, MessageRole, ModeInfo, ToolCall};
+use super::ollama::OllamaConfig;
+use super::usage::{self, TokenUsage};
+
+/// 未配置 API Key 时读取的环境变量
+const API_KEY_ENV: &str = "OPENAI_API_KEY";
+
+/// 旧会话中使用的本地模型占位名，发送时换成已安装的 Ollama 模型
+pub const LOCAL_PLACEHOLDER: &str = "Local Llama";
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+/// 两段数据之间的最长等待，本地模型首次加载可能较慢
+const READ_TIMEOUT: Duration = Duration::from_secs(300);
+/// 读取回复时检查取消标记的间隔
+const CANCEL_POLL: Duration = Duration::from_millis(100);
+
+/// 所有模型服务共用的 HTTP 客户端设置。只限制连接与单次读取的等待时间，
+/// 不限制整个请求的时长，以免截断较长的流式回复
+pub(super) fn http() -> ureq::Agent {
+    ureq::AgentBuilder::new()
+        .timeout_connect(CONNECT_TIMEOUT)
+        .timeout_read(READ_TIMEOUT)
+        .build()
+}
+
+/// 逐行读取流式回复。阻塞中的读取无法从外部中断，因此由单独的线程读取并通过通道送回，
+/// 调用方在两行之间按 `CANCEL_POLL` 检查取消标记，停止后立即返回；
+/// 读取线程在当前的读取返回时发现无人接收而退出，连接随之关闭
+pub(super) struct StreamLines<'a> {
+    lines: Receiver<std::io::Result<String>>,
+    cancelled: &'a AtomicBool,
+}
+
+impl<'a> StreamLines<'a> {
+    pub(super) fn new(response: ureq::Response, cancelled: &'a AtomicBool) -> Self {
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            for line in std::io::BufReader::new(response.into_reader()).lines() {
+                if tx.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        Self { lines: rx, cancelled }
+    }
+}
+
+impl Iterator for StreamLines<'_> {
+    type Item = Result<String, String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.cancelled.load(Ordering::Relaxed) {
+                return Some(Err("Cancelled".to_string()));
+            }
+            match self.lines.recv_timeout(CANCEL_POLL) {
+                Ok(line) => return Some(line.map_err(|e| e.to_string())),
+                Err(mpsc::RecvTimeoutError::Timeout) => continue,
+                Err(mpsc::RecvTimeoutError::Disconnected) => return None,
+            }
+        }
+    }
+}
+
+// ----------------------------------------------------------------------------
+// 模型服务：OpenAI 兼容的 Chat Completions 接口（OpenAI、DeepSeek、vLLM、LM Studio 等）
+// ----------------------------------------------------------------------------
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ProviderConfig {
+    /// 接口地址，例如 `https://api.openai.com/v1`，其后接 `/models` 与 `/chat/completions`
+    #[serde(default = "default_base_url")]
+    pub base_url: String,
+    /// 旧版本以明文保存的密钥，加载配置时移入系统钥匙串；钥匙串不可用时仍从这里读取
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub api_key: String,
+    /// 新建会话使用的模型
+    #[serde(default = "default_model")]
+    pub default_model: String,
+}
+
+fn default_base_url() -> String {
+    "https://api.openai.com/v1".to_string()
+}
+
+fn default_model() -> String {
+    "gpt-4o-mini".to_string()
+}
+
+impl Default for ProviderConfig {
+    fn default() -> Self {
+        Self {
+            base_url: default_base_url(),
+            api_key: String::new(),
+            default_model: default_model(),
+        }
+    }
+}
+
+impl ProviderConfig {
+    fn endpoint(&self, path: &str) -> String {
+        format!("{}/{}", self.base_url.trim().trim_end_matches('/'), path)
+    }
+
+    /// 钥匙串中保存本服务密钥的条目名，按接口地址区分，切换服务后各用各的密钥
+    pub fn secret_name(&self) -> String {
+        format!("agent:{}", self.base_url.trim().trim_end_matches('/'))
+    }
+
+    /// 依次使用钥匙串中的密钥、旧配置中的明文密钥和环境变量 OPENAI_API_KEY
+    fn api_key(&self) -> Option<String> {
+        let stored = crate::secrets::get(&self.secret_name()).ok().flatten();
+        [stored, Some(self.api_key.clone()), std::env::var(API_KEY_ENV).ok()]
+            .into_iter()
+            .flatten()
+            .map(|k| k.trim().to_string())
+            .find(|k| !k.is_empty())
+    }
+
+    /// 把明文密钥移入钥匙串，成功后返回 true，调用方应保存配置
+    pub fn migrate_api_key(&mut self) -> bool {
+        let key = self.api_key.trim();
+        if key.is_empty() || crate::secrets::set(&self.secret_name(), key).is_err() {
+            return false;
+        }
+        self.api_key.clear();
+        true
+    }
+
+    fn authorize(&self, request: ureq::Request) -> ureq::Request {
+        match self.api_key() {
+            Some(key) => request.set("Authorization", &format!("Bearer {}", key)),
+            None => request,
+        }
+    }
+
+    /// 获取服务器提供的模型列表。会阻塞，应在后台线程中调用
+    pub fn fetch_models(&self) -> Result<Vec<String>, String> {
+        let response = self
+            .authorize(http().get(&self.endpoint("models")))
+            .call()
+            .map_err(describe_error)?;
+        let body: Value = response.into_json().map_err(|e| format!("Invalid response: {}", e))?;
+        let mut models: Vec<String> = body["data"]
+            .as_array()
+            .ok_or("Invalid response: missing model list")?
+            .iter()
+            .filter_map(|m| m["id"].as_str().map(String::from))
+            .collect();
+        models.sort();
+        Ok(models)
+    }
+
+    /// 以流式方式发送对话补全请求，每收到一段回复调用一次 `on_delta`，
+    /// 其返回 false 或 `cancelled` 置位时停止读取；结束后返回模型请求的工具调用与用量。会阻塞，应在后台线程中调用
+    pub fn stream(&self, request: &ChatRequest, cancelled: &AtomicBool, mut on_delta: impl FnMut(String) -> bool) -> Result<Reply, String> {
+        let mut body = json!({
+            "model": request.model,
+            "messages": request.messages,
+            "stream": true,
+            // 流式输出默认不含用量，需要服务在最后一个事件中附上
+            "stream_options": { "include_usage": true },
+        });
+        if let Some(t) = request.temperature {
+            body["temperature"] = json!(t);
+        }
+        if let Some(max_tokens) = request.max_tokens {
+            body["max_tokens"] = json!(max_tokens);
+        }
+        if let Some(top_p) = request.top_p {
+            body["top_p"] = json!(top_p);
+        }
+        if !request.tools.is_empty() {
+            body["tools"] = json!(request.tools);
+        }
+        let response = self
+            .authorize(http().post(&self.endpoint("chat/completions")))
+            .send_json(body)
+            .map_err(describe_error)?;
+
+        // Server-Sent Events：每个事件为一行 `data: {...}`，以 `data: [DONE]` 结束。
+        // 不支持流式输出的服务会直接返回完整的 JSON，此时整体解析
+        let (mut plain, mut streamed) = (String::new(), false);
+        // 工具调用分多段到达，按 index 拼接
+        let mut calls: Vec<ToolCall> = Vec::new();
+        let mut token_usage = None;
+        for line in StreamLines::new(response, cancelled) {
+            let line = line?;
+            let Some(data) = line.strip_prefix("data:").map(str::trim) else {
+                // 注释（`:` 开头）与 event / id 等字段不影响内容
+                if !streamed && !line.starts_with(':') && !line.starts_with("event:") && !line.starts_with("id:") {
+                    plain.push_str(&line);
+                }
+                continue;
+            };
+            streamed = true;
+            if data == "[DONE]" {
+                break;
+            }
+            let event: Value = serde_json::from_str(data).map_err(|e| format!("Invalid response: {}", e))?;
+            if let Some(message) = event["error"]["message"].as_str() {
+                return Err(message.to_string());
+            }
+            token_usage = usage::from_openai(&event["usage"]).or(token_usage);
+            let delta = &event["choices"][0]["delta"];
+            for part in delta["tool_calls"].as_array().into_iter().flatten() {
+                let index = part["index"].as_u64().unwrap_or(0) as usize;
+                while calls.len() <= index {
+                    calls.push(ToolCall { id: format!("call_{}", calls.len()), name: String::new(), arguments: String::new() });
+                }
+                let call = &mut calls[index];
+                if let Some(id) = part["id"].as_str() {
+                    call.id = id.to_string();
+                }
+                call.name.push_str(part["function"]["name"].as_str().unwrap_or_default());
+                call.arguments.push_str(part["function"]["arguments"].as_str().unwrap_or_default());
+            }
+            if let Some(text) = delta["content"].as_str().filter(|d| !d.is_empty()) {
+                if !on_delta(text.to_string()) {
+                    return Ok(Reply::default());
+                }
+            }
+        }
+        if streamed || plain.trim().is_empty() {
+            return Ok(Reply { tool_calls: calls, usage: token_usage });
+        }
+        let body: Value = serde_json::from_str(&plain).map_err(|e| format!("Invalid response: {}", e))?;
+        let message = &body["choices"][0]["message"];
+        let calls = message["tool_calls"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .enumerate()
+            .map(|(i, call)| ToolCall {
+                id: call["id"].as_str().map_or_else(|| format!("call_{}", i), String::from),
+                name: call["function"]["name"].as_str().unwrap_or_default().to_string(),
+                arguments: call["function"]["arguments"].as_str().unwrap_or("{}").to_string(),
+            })
+            .collect::<Vec<_>>();
+        match message["content"].as_str() {
+            Some(content) => {
+                on_delta(content.to_string());
+            }
+            None if calls.is_empty() => return Err("Invalid response: missing reply content".to_string()),
+            None => {}
+        }
+        Ok(Reply { tool_calls: calls, usage: usage::from_openai(&body["usage"]) })
+    }
+}
+
+/// 把 HTTP 错误整理成给用户看的说明，尽量取出服务器返回的 `error.message`（Ollama 为 `error`）
+pub(super) fn describe_error(error: ureq::Error) -> String {
+    match error {
+        ureq::Error::Status(code, response) => {
+            let body = response.into_string().unwrap_or_default();
+            let message = serde_json::from_str::<Value>(&body)
+                .ok()
+                .and_then(|v| v["error"]["message"].as_str().or(v["error"].as_str()).map(String::from))
+                .unwrap_or(body);
+            if message.trim().is_empty() {
+                format!("HTTP {}", code)
+            } else {
+                format!("HTTP {}: {}", code, message.trim())
+            }
+        }
+        ureq::Error::Transport(transport) => transport.to_string(),
+    }
+}
+
+// ----------------------------------------------------------------------------
+// 对话请求
+// ----------------------------------------------------------------------------
+
+#[derive(Debug, Clone)]
+pub struct ChatRequest {
+    pub model: String,
+    pub temperature: Option<f32>,
+    pub max_tokens: Option<u32>,
+    pub top_p: Option<f32>,
+    /// OpenAI 格式的消息列表
+    pub messages: Vec<Value>,
+    /// OpenAI 格式的工具定义
+    pub tools: Vec<Value>,
+}
+
+/// 系统提示：会话中设置的提示，未设置时为说明当前模式的默认提示。模式脚本可以在此基础上修改
+pub fn system_prompt(session: &ChatSession, mode: Option<&ModeInfo>) -> String {
+    if !session.system_prompt.trim().is_empty() {
+        return session.system_prompt.clone();
+    }
+    default_system_prompt(session, mode)
+}
+
+pub fn default_system_prompt(session: &ChatSession, mode: Option<&ModeInfo>) -> String {
+    let mut system = format!("You are the coding agent of the Verbium editor, working in {} mode.", session.context_mode);
+    if let Some(description) = mode.map(|m| m.description.trim()).filter(|d| !d.is_empty()) {
+        system.push(' ');
+        system.push_str(description);
+    }
+    // 补丁代码块在聊天中显示为可以直接应用到编辑器的改动
+    system.push_str(" To propose changes to a file, reply with a unified diff in a ```diff block with --- a/<path> and +++ b/<path> headers relative to the workspace root.");
+    system
+}
+
+impl ChatRequest {
+    /// 由会话生成请求，`/run` 等手动执行的工具记录以用户消息的形式提供给模型
+    pub fn from_session(session: &ChatSession, system: &str) -> Self {
+        let mut messages = vec![json!({ "role": "system", "content": system })];
+        for message in &session.messages {
+            messages.push(match (&message.role, &message.tool_call_id) {
+                (MessageRole::User, _) => json!({ "role": "user", "content": message.content }),
+                (MessageRole::Agent, _) if !message.tool_calls.is_empty() => {
+                    let calls: Vec<Value> = message
+                        .tool_calls
+                        .iter()
+                        .map(|call| json!({
+                            "id": call.id,
+                            "type": "function",
+                            "function": { "name": call.name, "arguments": call.arguments },
+                        }))
+                        .collect();
+                    json!({ "role": "assistant", "content": message.content, "tool_calls": calls })
+                }
+                (MessageRole::Agent, _) => json!({ "role": "assistant", "content": message.content }),
+                (MessageRole::Tool, Some(id)) => json!({ "role": "tool", "tool_call_id": id, "content": message.content }),
+                (MessageRole::Tool, None) => json!({ "role": "user", "content": format!("Tool output:\n{}", message.content) }),
+            });
+        }
+        Self {
+            model: session.model_name.clone(),
+            temperature: session.temperature,
+            max_tokens: session.max_tokens,
+            top_p: session.top_p,
+            messages,
+            tools: Vec::new(),
+        }
+    }
+
+    /// 附上可供调用的工具
+    pub fn with_tools(mut self, tools: &[Arc<dyn crate::AgentTool>]) -> Self {
+        self.tools = tools
+            .iter()
+            .map(|tool| {
+                let parameters: Value = serde_json::from_str(tool.parameters()).unwrap_or_else(|_| json!({ "type": "object" }));
+                json!({
+                    "type": "function",
+                    "function": { "name": tool.name(), "description": tool.description(), "parameters": parameters },
+                })
+            })
+            .collect();
+        self
+    }
+}
+
+/// 一次回复结束后除文本外的结果
+#[derive(Debug, Clone, Default)]
+pub struct Reply {
+    pub tool_calls: Vec<ToolCall>,
+    /// 服务返回的 token 用量，未返回时为 None
+    pub usage: Option<TokenUsage>,
+}
+
+#[derive(Debug)]
+pub enum StreamEvent {
+    /// 新到达的一段回复
+    Delta(String),
+    /// 回复结束时模型请求的工具调用
+    ToolCalls(Vec<ToolCall>),
+    /// 服务返回的 token 用量
+    Usage(TokenUsage),
+    Done,
+    Failed(String),
+}
+
+/// 后台请求的句柄：回复以事件形式通过通道送回标签页，可随时取消
+#[derive(Debug, Clone)]
+pub struct ReplyStream {
+    events: Arc<Mutex<Receiver<StreamEvent>>>,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl ReplyStream {
+    /// 取出目前已到达的事件
+    pub fn drain(&self) -> Vec<StreamEvent> {
+        self.events.lock().map(|rx| rx.try_iter().collect()).unwrap_or_default()
+    }
+
+    /// 停止接收；后台线程在 `CANCEL_POLL` 之内停止读取并退出
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+}
+
+/// 处理请求的模型服务
+#[derive(Debug, Clone)]
+pub enum Backend {
+    OpenAi(ProviderConfig),
+    Ollama(OllamaConfig),
+}
+
+impl Backend {
+    fn stream(&self, request: &ChatRequest, cancelled: &AtomicBool, on_delta: impl FnMut(String) -> bool) -> Result<Reply, String> {
+        match self {
+            Backend::OpenAi(config) => config.stream(request, cancelled, on_delta),
+            Backend::Ollama(config) => config.stream(request, cancelled, on_delta),
+        }
+    }
+}
+
+/// 在后台线程中以流式方式发送请求
+pub fn spawn_chat(backend: Backend, request: ChatRequest, ctx: &egui::Context) -> ReplyStream {
+    let (tx, rx) = mpsc::channel();
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let (flag, ctx) = (cancelled.clone(), ctx.clone());
+    std::thread::spawn(move || {
+        let result = backend.stream(&request, &flag, |delta| {
+            if flag.load(Ordering::Relaxed) || tx.send(StreamEvent::Delta(delta)).is_err() {
+                return false;
+            }
+            ctx.request_repaint();
+            true
+        });
+        match result {
+            Ok(reply) => {
+                if !reply.tool_calls.is_empty() && !flag.load(Ordering::Relaxed) {
+                    let _ = tx.send(StreamEvent::ToolCalls(reply.tool_calls));
+                }
+                if let Some(usage) = reply.usage {
+                    let _ = tx.send(StreamEvent::Usage(usage));
+                }
+                let _ = tx.send(StreamEvent::Done);
+            }
+            Err(e) => {
+                let _ = tx.send(StreamEvent::Failed(e));
+            }
+        }
+        ctx.request_repaint();
+    });
+    ReplyStream { events: Arc::new(Mutex::new(rx)), cancelled }
+}
+
+// ----------------------------------------------------------------------------
+// 模型列表：插件与所有 Agent 标签页共享，在后台刷新
+// ----------------------------------------------------------------------------
+
+/// 一个模型服务的模型列表及其刷新状态
+#[derive(Debug, Default)]
+struct Source {
+    models: Vec<String>,
+    loading: bool,
+    error: Option<String>,
+}
+
+#[derive(Debug, Default)]
+struct CatalogState {
+    /// OpenAI 兼容服务
+    remote: Source,
+    /// Ollama 本地模型
+    local: Source,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ModelCatalog {
+    inner: Arc<Mutex<CatalogState>>,
+}
+
+impl ModelCatalog {
+    pub fn models(&self) -> Vec<String> {
+        self.inner.lock().map(|s| s.remote.models.clone()).unwrap_or_default()
+    }
+
+    pub fn local_models(&self) -> Vec<String> {
+        self.inner.lock().map(|s| s.local.models.clone()).unwrap_or_default()
+    }
+
+    pub fn is_loading(&self) -> bool {
+        self.inner.lock().is_ok_and(|s| s.remote.loading || s.local.loading)
+    }
+
+    /// 上次刷新失败的原因
+    pub fn error(&self) -> Option<String> {
+        self.inner.lock().ok().and_then(|s| s.remote.error.clone())
+    }
+
+    pub fn local_error(&self) -> Option<String> {
+        self.inner.lock().ok().and_then(|s| s.local.error.clone())
+    }
+
+    /// 重新获取两个服务的模型列表；正在刷新的服务跳过
+    pub fn refresh(&self, config: &AgentConfig, ctx: &egui::Context) {
+        let remote = config.provider.clone();
+        self.refresh_source(|s| &mut s.remote, move || remote.fetch_models(), ctx);
+        if config.ollama.enabled {
+            let local = config.ollama.clone();
+            self.refresh_source(|s| &mut s.local, move || local.fetch_models(), ctx);
+        } else if let Ok(mut state) = self.inner.lock() {
+            state.local = Source::default();
+        }
+    }
+
+    fn refresh_source(
+        &self,
+        source: fn(&mut CatalogState) -> &mut Source,
+        fetch: impl FnOnce() -> Result<Vec<String>, String> + Send + 'static,
+        ctx: &egui::Context,
+    ) {
+        let Ok(mut state) = self.inner.lock() else { return };
+        if source(&mut state).loading {
+            return;
+        }
+        source(&mut state).loading = true;
+        let (inner, ctx) = (self.inner.clone(), ctx.clone());
+        std::thread::spawn(move || {
+            let result = fetch();
+            if let Ok(mut state) = inner.lock() {
+                let source = source(&mut state);
+                source.loading = false;
+                match result {
+                    Ok(models) => {
+                        source.models = models;
+                        source.error = None;
+                    }
+                    Err(e) => {
+                        source.models.clear();
+                        source.error = Some(e);
+                    }
+                }
+            }
+            ctx.request_repaint();
+        });
+    }
+
+    /// 选出处理该模型的服务及实际发送的模型名：已安装的本地模型与占位名交给 Ollama，
+    /// 其余交给 OpenAI 兼容服务
+    pub fn resolve(&self, model: &str, config: &AgentConfig) -> Result<(Backend, String), String> {
+        let local = self.local_models();
+        if model == LOCAL_PLACEHOLDER {
+            if !config.ollama.enabled {
+                return Err("Local models are disabled in the Agent settings".to_string());
+            }
+            // 优先选择 Llama 系列模型
+            let chosen = local
+                .iter()
+                .find(|m| m.to_lowercase().contains("llama"))
+                .or(local.first())
+                .cloned()
+                .ok_or_else(|| self.local_error().unwrap_or_else(|| "No local models installed in Ollama".to_string()))?;
+            return Ok((Backend::Ollama(config.ollama.clone()), chosen));
+        }
+        if config.ollama.enabled && local.iter().any(|m| m == model) {
+            return Ok((Backend::Ollama(config.ollama.clone()), model.to_string()));
+        }
+        Ok((Backend::OpenAi(config.provider.clone()), model.to_string()))
+    }
+}
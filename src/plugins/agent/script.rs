@@ -0,0 +1,83 @@
+use rhai::{Dynamic, Engine, Scope, AST};
+use std::path::{Path, PathBuf};
+
+/// 单次调用最多执行的操作数，防止脚本死循环卡住界面
+const MAX_OPERATIONS: u64 = 1_000_000;
+
+// ----------------------------------------------------------------------------
+// 模式脚本：每个 .rhai 文件可以定义以下函数，未定义的使用默认行为
+//
+//   fn system_prompt(prompt) { ... }  返回发给模型的系统提示，参数为默认提示
+//   fn before_send(text) { ... }      发送前处理用户消息
+//   fn after_reply(text) { ... }      处理模型的完整回复
+//   fn tools() { ... }                返回允许调用的工具名数组
+// ----------------------------------------------------------------------------
+
+pub struct ModeScript {
+    path: PathBuf,
+    engine: Engine,
+    ast: AST,
+}
+
+impl ModeScript {
+    /// 读取并编译脚本。每次使用时重新加载，修改脚本后无需重启
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let source = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", file_name(path), e))?;
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+        let ast = engine.compile(source).map_err(|e| format!("{}: {}", file_name(path), e))?;
+        Ok(Self { path: path.to_path_buf(), engine, ast })
+    }
+
+    fn defines(&self, name: &str, params: usize) -> bool {
+        self.ast.iter_functions().any(|f| f.name == name && f.params.len() == params)
+    }
+
+    fn call(&self, name: &str, args: impl rhai::FuncArgs) -> Result<Dynamic, String> {
+        self.engine
+            .call_fn::<Dynamic>(&mut Scope::new(), &self.ast, name, args)
+            .map_err(|e| format!("{} ({}): {}", file_name(&self.path), name, e))
+    }
+
+    /// 以脚本中同名函数处理文本，未定义该函数时原样返回
+    fn transform(&self, name: &str, text: &str) -> Result<String, String> {
+        if !self.defines(name, 1) {
+            return Ok(text.to_string());
+        }
+        let value = self.call(name, (text.to_string(),))?;
+        let type_name = value.type_name();
+        value
+            .into_string()
+            .map_err(|_| format!("{} ({}): expected a string, got {}", file_name(&self.path), name, type_name))
+    }
+
+    pub fn system_prompt(&self, default: &str) -> Result<String, String> {
+        self.transform("system_prompt", default)
+    }
+
+    pub fn before_send(&self, text: &str) -> Result<String, String> {
+        self.transform("before_send", text)
+    }
+
+    pub fn after_reply(&self, text: &str) -> Result<String, String> {
+        self.transform("after_reply", text)
+    }
+
+    /// 允许调用的工具名，None 表示不限制
+    pub fn allowed_tools(&self) -> Result<Option<Vec<String>>, String> {
+        if !self.defines("tools", 0) {
+            return Ok(None);
+        }
+        let error = || format!("{} (tools): expected an array of tool names", file_name(&self.path));
+        let names = self.call("tools", ())?.into_array().map_err(|_| error())?;
+        names
+            .into_iter()
+            .map(|name| name.into_string().map_err(|_| error()))
+            .collect::<Result<Vec<_>, _>>()
+            .map(Some)
+    }
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default()
+}
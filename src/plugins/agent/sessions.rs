@@ -0,0 +1,256 @@
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use egui::Color32;
+use super::models::{AgentConfig, ChatSession, ModeInfo};
+
+/// 会话目录中不属于会话的配置文件
+const KNOWN_CONFIGS: [&str; 11] = [
+    "agent_config.toml",
+    "agent_usage.toml",
+    "browser_config.toml",
+    "code_editor_bookmarks.toml",
+    "code_editor_config.toml",
+    "file_manager_config.toml",
+    "launcher_config.toml",
+    "terminal_config.toml",
+    "verbium_config.toml",
+    "verbium_session.toml",
+    "verbium_workspace.toml",
+];
+
+/// 未指定颜色的标签按名称从这组颜色中取色
+const TAG_COLORS: [[u8; 3]; 8] = [
+    [86, 180, 233],
+    [0, 158, 115],
+    [230, 159, 0],
+    [204, 121, 167],
+    [240, 228, 66],
+    [0, 114, 178],
+    [213, 94, 0],
+    [170, 170, 170],
+];
+
+/// 会话文件的移动记录（旧路径，新路径），按发生顺序追加。
+/// 已打开的标签页据此更新自己的保存路径，避免移动后又在原位置重新写出文件
+pub type SessionMoves = Arc<Mutex<Vec<(PathBuf, PathBuf)>>>;
+
+/// 会话目录中的一个会话文件
+#[derive(Debug, Clone)]
+pub struct SessionEntry {
+    pub path: PathBuf,
+    /// 所在的子文件夹，None 表示位于会话目录根部
+    pub folder: Option<String>,
+    pub modified: std::time::SystemTime,
+}
+
+impl AgentConfig {
+    /// 会话存储目录，未设置时使用当前工作目录
+    pub fn chat_dir(&self) -> PathBuf {
+        self.default_chat_dir.clone().unwrap_or_else(|| std::env::current_dir().unwrap_or_default())
+    }
+
+    /// 标签索引的键：相对于会话目录、以 / 分隔的路径
+    pub fn session_key(&self, path: &Path) -> String {
+        let root = self.chat_dir();
+        let relative = path.strip_prefix(&root).unwrap_or(path);
+        relative.components().map(|c| c.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/")
+    }
+
+    pub fn tags_for(&self, path: &Path) -> &[String] {
+        self.session_tags.get(&self.session_key(path)).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// 添加或移除标签；移除后没有会话使用的标签颜色一并清理
+    pub fn toggle_tag(&mut self, path: &Path, tag: &str) {
+        let key = self.session_key(path);
+        let tags = self.session_tags.entry(key.clone()).or_default();
+        if let Some(pos) = tags.iter().position(|t| t == tag) {
+            tags.remove(pos);
+        } else {
+            tags.push(tag.to_string());
+            tags.sort();
+        }
+        if tags.is_empty() {
+            self.session_tags.remove(&key);
+        }
+        let used: Vec<String> = self.all_tags();
+        self.tag_colors.retain(|tag, _| used.contains(tag));
+    }
+
+    /// 所有会话用到的标签，按名称排序
+    pub fn all_tags(&self) -> Vec<String> {
+        let mut tags: Vec<String> = self.session_tags.values().flatten().cloned().collect();
+        tags.sort();
+        tags.dedup();
+        tags
+    }
+
+    pub fn tag_color(&self, tag: &str) -> Color32 {
+        let [r, g, b] = self.tag_colors.get(tag).copied().unwrap_or_else(|| {
+            let hash = tag.bytes().fold(0usize, |h, b| h.wrapping_mul(31).wrapping_add(b as usize));
+            TAG_COLORS[hash % TAG_COLORS.len()]
+        });
+        Color32::from_rgb(r, g, b)
+    }
+}
+
+/// 列出会话目录根部及其一级子文件夹中的会话，按修改时间倒序
+pub fn list_sessions(root: &Path) -> Vec<SessionEntry> {
+    let mut sessions = Vec::new();
+    collect_sessions(root, None, &mut sessions);
+    for folder in list_folders(root) {
+        collect_sessions(&root.join(&folder), Some(folder), &mut sessions);
+    }
+    sessions.sort_by(|a, b| b.modified.cmp(&a.modified));
+    sessions
+}
+
+fn collect_sessions(dir: &Path, folder: Option<String>, out: &mut Vec<SessionEntry>) {
+    let Ok(entries) = std::fs::read_dir(dir) else { return };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if !path.is_file() || path.extension().and_then(|s| s.to_str()) != Some("toml") {
+            continue;
+        }
+        // For now, let's just include all .toml except known configs
+        let filename = path.file_name().and_then(|s| s.to_str()).unwrap_or_default();
+        if KNOWN_CONFIGS.contains(&filename) {
+            continue;
+        }
+        let modified = path.metadata().and_then(|m| m.modified()).unwrap_or(std::time::SystemTime::UNIX_EPOCH);
+        out.push(SessionEntry { path, folder: folder.clone(), modified });
+    }
+}
+
+/// 会话目录下的子文件夹（不含隐藏目录），按名称排序
+pub fn list_folders(root: &Path) -> Vec<String> {
+    let mut folders: Vec<String> = std::fs::read_dir(root)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|e| e.path().is_dir())
+        .filter_map(|e| e.file_name().to_str().map(String::from))
+        .filter(|name| !name.starts_with('.'))
+        .collect();
+    folders.sort();
+    folders
+}
+
+/// 将会话文件移动到 folder（None 表示根目录），同步标签索引并通知已打开的标签页
+pub fn move_session(config: &mut AgentConfig, moves: &SessionMoves, path: &Path, folder: Option<&str>) -> std::io::Result<PathBuf> {
+    let root = config.chat_dir();
+    let dir = match folder {
+        Some(folder) => root.join(folder),
+        None => root,
+    };
+    std::fs::create_dir_all(&dir)?;
+    let file_name = path.file_name().ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidInput, "invalid session path"))?;
+    let target = dir.join(file_name);
+    if target == path {
+        return Ok(target);
+    }
+    if target.exists() {
+        return Err(std::io::Error::new(std::io::ErrorKind::AlreadyExists, format!("{} already exists", target.display())));
+    }
+    std::fs::rename(path, &target)?;
+
+    if let Some(tags) = config.session_tags.remove(&config.session_key(path)) {
+        config.session_tags.insert(config.session_key(&target), tags);
+    }
+    if let Ok(mut moves) = moves.lock() {
+        moves.push((path.to_path_buf(), target.clone()));
+    }
+    Ok(target)
+}
+
+/// `dir` 中名为 `stem` 的会话文件路径，重名时追加序号
+fn unique_session_path(dir: &Path, stem: &str) -> PathBuf {
+    let mut path = dir.join(format!("{}.toml", stem));
+    let mut n = 2;
+    while path.exists() {
+        path = dir.join(format!("{} {}.toml", stem, n));
+        n += 1;
+    }
+    path
+}
+
+/// 在会话目录根部新建会话文件并应用模式，返回文件路径
+pub fn create_session(config: &AgentConfig, name: &str, mode: Option<&ModeInfo>) -> anyhow::Result<PathBuf> {
+    let root = config.chat_dir();
+    std::fs::create_dir_all(&root)?;
+    let safe_name = name.replace(|c: char| !c.is_alphanumeric() && c != '_' && c != '-', "_");
+    let path = unique_session_path(&root, &safe_name);
+    let mut session = ChatSession::new("Chat".into(), config.provider.default_model.clone());
+    if let Some(mode) = mode {
+        session.apply_mode(mode);
+    }
+    session.path = Some(path.clone());
+    session.save()?;
+    Ok(path)
+}
+
+/// 把导出的 Markdown / JSON 文件导入为会话目录根部的新会话，返回文件路径
+pub fn import_session(config: &AgentConfig, source: &Path) -> anyhow::Result<PathBuf> {
+    let mut session = ChatSession::import(source)?;
+    if session.model_name.is_empty() {
+        session.model_name = config.provider.default_model.clone();
+    }
+    let root = config.chat_dir();
+    std::fs::create_dir_all(&root)?;
+    let name = source.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_else(|| "Imported".into());
+    let safe_name = name.replace(|c: char| !c.is_alphanumeric() && c != '_' && c != '-', "_");
+    let path = unique_session_path(&root, &safe_name);
+    session.path = Some(path.clone());
+    session.save()?;
+    Ok(path)
+}
+
+/// 在原文件夹中重命名会话，同步标签索引并通知已打开的标签页
+pub fn rename_session(config: &mut AgentConfig, moves: &SessionMoves, path: &Path, name: &str) -> std::io::Result<PathBuf> {
+    let name = sanitize_folder_name(name);
+    if name.is_empty() {
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, "Enter a name"));
+    }
+    let target = path.with_file_name(format!("{}.toml", name));
+    if target == path {
+        return Ok(target);
+    }
+    if target.exists() {
+        return Err(std::io::Error::new(std::io::ErrorKind::AlreadyExists, format!("\"{}\" already exists", name)));
+    }
+    std::fs::rename(path, &target)?;
+
+    if let Some(tags) = config.session_tags.remove(&config.session_key(path)) {
+        config.session_tags.insert(config.session_key(&target), tags);
+    }
+    if let Ok(mut moves) = moves.lock() {
+        moves.push((path.to_path_buf(), target.clone()));
+    }
+    Ok(target)
+}
+
+/// 在同一文件夹中复制会话（连同标签），返回副本路径
+pub fn duplicate_session(config: &mut AgentConfig, path: &Path) -> std::io::Result<PathBuf> {
+    let stem = path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    let dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+    let target = unique_session_path(&dir, &format!("{} copy", stem));
+    std::fs::copy(path, &target)?;
+    if let Some(tags) = config.session_tags.get(&config.session_key(path)).cloned() {
+        config.session_tags.insert(config.session_key(&target), tags);
+    }
+    Ok(target)
+}
+
+/// 删除会话文件及其标签
+pub fn delete_session(config: &mut AgentConfig, path: &Path) -> std::io::Result<()> {
+    std::fs::remove_file(path)?;
+    config.session_tags.remove(&config.session_key(path));
+    let used = config.all_tags();
+    config.tag_colors.retain(|tag, _| used.contains(tag));
+    Ok(())
+}
+
+/// 把文件夹名称限制为单层、安全的目录名
+pub fn sanitize_folder_name(name: &str) -> String {
+    name.trim().replace(|c: char| !c.is_alphanumeric() && c != '_' && c != '-' && c != ' ', "_")
+}
@@ -0,0 +1,195 @@
+use egui::Ui;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use super::models::AgentConfig;
+use super::sessions::{self, SessionEntry, SessionMoves};
+
+/// 会话列表的刷新间隔，期间其他标签页或会话管理器做出的修改随后出现
+const REFRESH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// 侧边栏中需要标签页处理的操作
+pub enum SidebarAction {
+    Open(PathBuf),
+    New,
+    /// 会话文件已被删除
+    Deleted(PathBuf),
+}
+
+// ----------------------------------------------------------------------------
+// Agent 标签页左侧的会话列表：切换、重命名、复制与删除
+// ----------------------------------------------------------------------------
+
+#[derive(Debug, Clone)]
+pub struct SessionSidebar {
+    pub open: bool,
+    entries: Vec<SessionEntry>,
+    listed_at: Option<Instant>,
+    filter: String,
+    /// 正在重命名的会话及输入的新名称
+    renaming: Option<(PathBuf, String)>,
+    /// 开始重命名后的第一帧让输入框获得焦点
+    focus_rename: bool,
+    /// 等待确认删除的会话
+    confirm_delete: Option<PathBuf>,
+    error: Option<String>,
+}
+
+impl Default for SessionSidebar {
+    fn default() -> Self {
+        Self {
+            open: true,
+            entries: Vec::new(),
+            listed_at: None,
+            filter: String::new(),
+            renaming: None,
+            focus_rename: false,
+            confirm_delete: None,
+            error: None,
+        }
+    }
+}
+
+impl SessionSidebar {
+    /// 下一帧重新读取会话列表
+    pub fn invalidate(&mut self) {
+        self.listed_at = None;
+    }
+
+    fn refresh(&mut self) {
+        if self.listed_at.is_some_and(|t| t.elapsed() < REFRESH_INTERVAL) {
+            return;
+        }
+        self.entries = sessions::list_sessions(&AgentConfig::load().chat_dir());
+        self.listed_at = Some(Instant::now());
+    }
+
+    /// 绘制侧边栏。`current` 为标签页当前的会话，`can_switch` 为 false 时（回复进行中）不能切换
+    pub fn ui(&mut self, ui: &mut Ui, current: Option<&Path>, can_switch: bool, moves: &SessionMoves) -> Option<SidebarAction> {
+        self.refresh();
+        let mut action = None;
+
+        ui.horizontal(|ui| {
+            ui.strong("Sessions");
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                if ui.add_enabled(can_switch, egui::Button::new("➕").small()).on_hover_text("New Chat").clicked() {
+                    action = Some(SidebarAction::New);
+                }
+            });
+        });
+        ui.add(egui::TextEdit::singleline(&mut self.filter).hint_text("🔍 Filter").desired_width(f32::INFINITY));
+        if let Some(error) = &self.error {
+            ui.colored_label(crate::theme::palette(ui.ctx()).error, error);
+        }
+        ui.add_space(4.0);
+
+        let filter = self.filter.trim().to_lowercase();
+        let entries: Vec<SessionEntry> = self
+            .entries
+            .iter()
+            .filter(|e| filter.is_empty() || session_name(&e.path).to_lowercase().contains(&filter))
+            .cloned()
+            .collect();
+        egui::ScrollArea::vertical().auto_shrink([false, false]).show(ui, |ui| {
+            if entries.is_empty() {
+                ui.weak(if filter.is_empty() { "No sessions yet." } else { "No matching sessions." });
+            }
+            for entry in &entries {
+                if let Some(a) = self.row_ui(ui, entry, current, can_switch, moves) {
+                    action = Some(a);
+                }
+            }
+        });
+        action
+    }
+
+    fn row_ui(&mut self, ui: &mut Ui, entry: &SessionEntry, current: Option<&Path>, can_switch: bool, moves: &SessionMoves) -> Option<SidebarAction> {
+        let mut action = None;
+        let path = &entry.path;
+
+        if let Some((_, name)) = self.renaming.as_mut().filter(|(p, _)| p == path) {
+            let response = ui.add(egui::TextEdit::singleline(name).desired_width(f32::INFINITY));
+            if std::mem::take(&mut self.focus_rename) {
+                response.request_focus();
+            }
+            if ui.input(|i| i.key_pressed(egui::Key::Escape)) {
+                self.renaming = None;
+            } else if response.lost_focus() {
+                let name = name.clone();
+                self.renaming = None;
+                let mut config = AgentConfig::load();
+                match sessions::rename_session(&mut config, moves, path, &name) {
+                    Ok(_) => {
+                        config.save();
+                        self.error = None;
+                    }
+                    Err(e) => self.error = Some(format!("Rename failed: {}", e)),
+                }
+                self.invalidate();
+            }
+            return None;
+        }
+
+        let selected = current == Some(path.as_path());
+        let datetime: chrono::DateTime<chrono::Local> = entry.modified.into();
+        let mut hover = datetime.format("%Y-%m-%d %H:%M").to_string();
+        if let Some(folder) = &entry.folder {
+            hover = format!("📁 {}\n{}", folder, hover);
+        }
+        let response = ui
+            .add_enabled(can_switch || selected, egui::SelectableLabel::new(selected, format!("💬 {}", session_name(path))))
+            .on_hover_text(hover);
+        if response.clicked() && !selected {
+            action = Some(SidebarAction::Open(path.clone()));
+        }
+        response.context_menu(|ui| {
+            if ui.button("✏ Rename").clicked() {
+                self.renaming = Some((path.clone(), session_name(path)));
+                self.focus_rename = true;
+                ui.close_menu();
+            }
+            if ui.button("⧉ Duplicate").clicked() {
+                let mut config = AgentConfig::load();
+                match sessions::duplicate_session(&mut config, path) {
+                    Ok(_) => {
+                        config.save();
+                        self.error = None;
+                    }
+                    Err(e) => self.error = Some(format!("Duplicate failed: {}", e)),
+                }
+                self.invalidate();
+                ui.close_menu();
+            }
+            if ui.button("🗑 Delete").clicked() {
+                self.confirm_delete = Some(path.clone());
+                ui.close_menu();
+            }
+        });
+
+        if self.confirm_delete.as_ref() == Some(path) {
+            ui.horizontal(|ui| {
+                ui.weak("Delete this session?");
+                if ui.small_button("Delete").clicked() {
+                    self.confirm_delete = None;
+                    let mut config = AgentConfig::load();
+                    match sessions::delete_session(&mut config, path) {
+                        Ok(()) => {
+                            config.save();
+                            self.error = None;
+                            action = Some(SidebarAction::Deleted(path.clone()));
+                        }
+                        Err(e) => self.error = Some(format!("Delete failed: {}", e)),
+                    }
+                    self.invalidate();
+                }
+                if ui.small_button("Cancel").clicked() {
+                    self.confirm_delete = None;
+                }
+            });
+        }
+        action
+    }
+}
+
+fn session_name(path: &Path) -> String {
+    path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default()
+}
@@ -1,58 +1,970 @@
 use egui::{Ui, WidgetText};
 use crate::{AppCommand, TabInstance};
-use super::models::{ChatSession, ChatMessage, MessageRole};
+use super::markdown;
+use super::models::{AgentConfig, ChatSession, ChatMessage, MessageRole, ModeInfo, ToolCall};
+use super::prompts::{self, PromptLibrary};
+use super::provider::{self, ChatRequest, ModelCatalog, ReplyStream, StreamEvent};
+use super::script::ModeScript;
+use super::sessions::{self, SessionMoves};
+use super::sidebar::{SessionSidebar, SidebarAction};
+use super::tools::{self, Running, ShellOutcome, ShellRequest, ToolOutcome};
+use super::usage::{self, TokenUsage, UsageLog};
+use std::collections::VecDeque;
+
+/// 一条用户消息之后最多连续执行工具的回合数，防止模型反复调用工具
+const MAX_TOOL_ROUNDS: usize = 8;
 
 #[derive(Debug, Clone, Default)]
 struct InputState {
     text: String,
+    /// 提示模板候选列表中高亮的一项
+    prompt_index: usize,
     // Future: attachments, focus state, etc.
 }
 
+/// 对会话中单条消息的操作
+enum MessageAction {
+    Edit(usize),
+    Regenerate(usize),
+    /// 用另一个模型重新生成回复，并与原回复并排比较
+    RetryWith(usize, String),
+    /// 并排比较从此处开始的各个回复版本
+    Compare(usize),
+    Delete(usize),
+    /// 切换到 `ChatSession::branches` 中的某个版本
+    SwitchBranch(usize),
+}
+
+/// 用户对待执行命令的选择
+enum ShellDecision {
+    Run,
+    RunInTerminal,
+    Cancel,
+}
+
 #[derive(Debug, Clone)]
 pub struct AgentTab {
     session: ChatSession,
     input: InputState,
-    available_modes: Vec<String>,
-    available_models: Vec<String>,
+    available_modes: Vec<ModeInfo>,
+    /// 从模型服务获取的模型列表
+    models: ModelCatalog,
+    /// 正在接收的模型回复，以及已收到的部分
+    pending_reply: Option<ReplyStream>,
+    partial_reply: String,
+    /// 上次请求失败的原因，可以重试
+    reply_error: Option<String>,
+    /// 有新的回复尚未被用户看到
+    reply_ready: bool,
+    /// 等待用户确认的 shell 命令
+    pending_shell: Option<ShellRequest>,
+    /// 正在后台执行的 shell 命令
+    running_shell: Option<(ShellRequest, Running<ShellOutcome>)>,
+    /// 本次回复中模型请求的工具调用
+    partial_calls: Vec<ToolCall>,
+    /// 尚未执行的工具调用
+    tool_queue: VecDeque<ToolCall>,
+    /// 等待用户确认的有副作用的工具调用
+    pending_tool: Option<ToolCall>,
+    /// 正在后台执行的工具调用
+    running_tool: Option<(ToolCall, Running<ToolOutcome>)>,
+    /// 工具调用全部完成后把结果发回模型
+    awaiting_tools: bool,
+    /// 当前用户消息之后已执行的工具回合数
+    tool_rounds: usize,
+    /// 模式脚本限定的工具，None 表示可以使用全部工具
+    allowed_tools: Option<Vec<String>>,
+    /// 模式脚本的最近一次错误，显示在对话末尾
+    script_error: Option<String>,
+    /// 正在编辑的用户消息及其新内容
+    editing: Option<(usize, String)>,
+    /// 本轮回复改用的模型（Retry with…），None 表示使用会话的模型
+    retry_model: Option<String>,
+    /// 并排比较各个回复版本的分叉位置
+    comparing: Option<usize>,
+    /// 本次请求实际使用的模型、估算的提示 token 数，以及服务返回的用量
+    reply_model: String,
+    prompt_estimate: u64,
+    reply_usage: Option<TokenUsage>,
+    /// 本月累计用量与预算，用于标题栏的提示
+    month_usage: TokenUsage,
+    monthly_budget: Option<f64>,
+    /// 左侧的会话列表
+    sidebar: SessionSidebar,
+    /// 输入框中 `/` 调用的提示模板
+    prompts: PromptLibrary,
+    /// 会话管理器中的文件移动记录，以及已处理到的位置
+    session_moves: SessionMoves,
+    moves_seen: usize,
 }
 
 impl AgentTab {
-    pub fn new(session: ChatSession, available_modes: Vec<String>) -> Self {
+    pub fn new(session: ChatSession, available_modes: Vec<ModeInfo>, session_moves: SessionMoves, models: ModelCatalog) -> Self {
         Self {
             session,
             input: InputState::default(),
             available_modes,
-            available_models: vec!["Gemini Pro".into(), "GPT-4".into(), "Local Llama".into()],
+            models,
+            pending_reply: None,
+            partial_reply: String::new(),
+            reply_error: None,
+            reply_ready: false,
+            pending_shell: None,
+            running_shell: None,
+            partial_calls: Vec::new(),
+            tool_queue: VecDeque::new(),
+            pending_tool: None,
+            running_tool: None,
+            awaiting_tools: false,
+            tool_rounds: 0,
+            allowed_tools: None,
+            script_error: None,
+            editing: None,
+            retry_model: None,
+            comparing: None,
+            reply_model: String::new(),
+            prompt_estimate: 0,
+            reply_usage: None,
+            month_usage: UsageLog::load().this_month(),
+            monthly_budget: AgentConfig::load().monthly_budget,
+            sidebar: SessionSidebar::default(),
+            prompts: PromptLibrary::default(),
+            moves_seen: session_moves.lock().map(|m| m.len()).unwrap_or(0),
+            session_moves,
         }
     }
 
-    fn send_message(&mut self) {
-        let text = self.input.text.trim().to_string();
-        if text.is_empty() {
-            return;
+    /// 模型下拉框的选项：(服务器提供的模型，加上模式声明的首选模型与会话当前的模型; 本地模型)
+    fn model_choices(&self) -> (Vec<String>, Vec<String>) {
+        let mut remote = self.models.models();
+        let local = self.models.local_models();
+        for model in self.available_modes.iter().filter_map(|m| m.preferred_model.clone()).chain([self.session.model_name.clone()]) {
+            if !model.is_empty() && model != provider::LOCAL_PLACEHOLDER && !remote.contains(&model) && !local.contains(&model) {
+                remote.push(model);
+            }
+        }
+        (remote, local)
+    }
+
+    /// 会话文件在会话管理器中被移动后，改为保存到新位置
+    fn follow_session_moves(&mut self) {
+        let Ok(moves) = self.session_moves.lock() else { return };
+        for (from, to) in &moves[self.moves_seen.min(moves.len())..] {
+            if self.session.path.as_ref() == Some(from) {
+                self.session.path = Some(to.clone());
+            }
+        }
+        self.moves_seen = moves.len();
+    }
+
+    /// 在本标签页中切换到另一个会话
+    fn open_session(&mut self, path: &std::path::Path) {
+        match ChatSession::load(path) {
+            Ok(session) => {
+                self.session = session;
+                self.reply_error = None;
+                self.script_error = None;
+                self.editing = None;
+                self.retry_model = None;
+                self.comparing = None;
+                self.tool_rounds = 0;
+            }
+            Err(e) => self.reply_error = Some(format!("Failed to open session: {}", e)),
+        }
+    }
+
+    /// 把当前会话导出为 Markdown（`ext` 为 "md"）或 JSON 文件
+    fn export_session(&mut self, ext: &str, control: &mut Vec<AppCommand>) {
+        let Some(path) = rfd::FileDialog::new()
+            .set_file_name(format!("{}.{}", self.session.title(), ext))
+            .add_filter(ext, &[ext])
+            .save_file()
+        else { return };
+        let (message, level) = match self.session.export(&path) {
+            Ok(()) => (format!("Chat exported to {}", path.display()), crate::NotificationLevel::Info),
+            Err(e) => (format!("Failed to export chat: {}", e), crate::NotificationLevel::Error),
+        };
+        control.push(AppCommand::Notify { message, level });
+    }
+
+    /// 导入之前导出的文件，另存为新会话并切换过去
+    fn import_session(&mut self, control: &mut Vec<AppCommand>) {
+        let Some(source) = rfd::FileDialog::new().add_filter("Chat export", &["md", "markdown", "json"]).pick_file() else { return };
+        match sessions::import_session(&AgentConfig::load(), &source) {
+            Ok(path) => {
+                self.open_session(&path);
+                self.sidebar.invalidate();
+            }
+            Err(e) => control.push(AppCommand::Notify {
+                message: format!("Failed to import {}: {}", source.display(), e),
+                level: crate::NotificationLevel::Error,
+            }),
+        }
+    }
+
+    fn sidebar_ui(&mut self, ui: &mut Ui) {
+        let can_switch = self.is_idle();
+        let current = self.session.path.clone();
+        let Some(action) = self.sidebar.ui(ui, current.as_deref(), can_switch, &self.session_moves) else { return };
+        self.follow_session_moves();
+        match action {
+            SidebarAction::Open(path) => self.open_session(&path),
+            // 删除的是其他会话时无需处理；当前会话被删除则换成一个新会话
+            SidebarAction::Deleted(path) if Some(&path) != self.session.path.as_ref() => {}
+            SidebarAction::New | SidebarAction::Deleted(_) => {
+                match sessions::create_session(&AgentConfig::load(), "New Chat", self.available_modes.first()) {
+                    Ok(path) => self.open_session(&path),
+                    Err(e) => self.reply_error = Some(format!("Failed to create session: {}", e)),
+                }
+                self.sidebar.invalidate();
+            }
+        }
+    }
+
+    /// 将工具执行记录写入会话并保存
+    fn log_tool(&mut self, content: String) {
+        self.session.messages.push(ChatMessage::new(MessageRole::Tool, content));
+        self.reply_ready = true;
+        if let Err(e) = self.session.save() {
+            eprintln!("Failed to save session: {}", e);
+        }
+    }
+
+    /// 处理 `/run <command>`：先经过白名单 / 黑名单检查，再等待用户确认
+    fn request_shell(&mut self, command: &str) {
+        let request = ShellRequest {
+            command: command.trim().to_string(),
+            cwd: std::env::current_dir().unwrap_or_default(),
+        };
+        match AgentConfig::load().shell_policy.check(&request.command) {
+            Ok(()) => self.pending_shell = Some(request),
+            Err(reason) => self.log_tool(format!("$ {}\nRejected: {}", request.command, reason)),
+        }
+    }
+
+    fn shell_ui(&mut self, ui: &mut Ui, control: &mut Vec<AppCommand>) {
+        // 轮询后台执行结果
+        if let Some((request, running)) = &self.running_shell {
+            if let Some(outcome) = running.take() {
+                let log = tools::format_log(request, &outcome);
+                self.running_shell = None;
+                self.log_tool(log);
+            } else {
+                ui.ctx().request_repaint_after(std::time::Duration::from_millis(200));
+            }
+        }
+
+        let Some(request) = self.pending_shell.clone() else { return };
+        let mut decision = None;
+        egui::Window::new("Run Command?")
+            .id(ui.id().with("shell_confirm"))
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+            .show(ui.ctx(), |ui| {
+                ui.label("The agent wants to run the following command:");
+                ui.add_space(4.0);
+                egui::Frame::group(ui.style())
+                    .fill(ui.visuals().extreme_bg_color)
+                    .show(ui, |ui| {
+                        ui.label(egui::RichText::new(&request.command).monospace().strong());
+                    });
+                ui.label(format!("Working directory: {}", request.cwd.display()));
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    if ui.button("▶ Run").clicked() {
+                        decision = Some(ShellDecision::Run);
+                    }
+                    if ui.button("⌨ Run in Terminal").on_hover_text("Hand the command to a terminal tab; its output is not logged here").clicked() {
+                        decision = Some(ShellDecision::RunInTerminal);
+                    }
+                    if ui.button("Cancel").clicked() {
+                        decision = Some(ShellDecision::Cancel);
+                    }
+                });
+            });
+
+        match decision {
+            Some(ShellDecision::Run) => {
+                self.pending_shell = None;
+                let running = tools::spawn_shell(&request, ui.ctx());
+                self.running_shell = Some((request, running));
+            }
+            Some(ShellDecision::RunInTerminal) => {
+                self.pending_shell = None;
+                // 白名单已禁止 shell 元字符，按空白拆分即可得到程序与参数
+                let mut words = request.command.split_whitespace().map(String::from);
+                let program = words.next().unwrap_or_default();
+                control.push(AppCommand::RunInTerminal { cwd: request.cwd.clone(), program, args: words.collect() });
+                self.log_tool(format!("$ {}\nSent to terminal", request.command));
+            }
+            Some(ShellDecision::Cancel) => {
+                self.pending_shell = None;
+                self.log_tool(format!("$ {}\nCancelled by user", request.command));
+            }
+            None => {}
+        }
+    }
+
+    fn send_message(&mut self, ctx: &egui::Context) {
+        let mut text = self.input.text.trim().to_string();
+        // `/模板名 内容` 展开为模板后发送
+        self.prompts.refresh();
+        let expanded = prompts::parse_command(&text)
+            .and_then(|(name, input)| self.prompts.get(name).map(|p| prompts::expand(&p.template, crate::editor_context(ctx).as_ref(), input)));
+        if let Some(expanded) = expanded {
+            text = expanded.trim().to_string();
+        }
+        if self.submit(text, ctx) {
+            self.input.text.clear();
+        }
+    }
+
+    /// 把用户消息加入会话并请求回复。模式脚本出错时不发送，返回 false
+    fn submit(&mut self, text: String, ctx: &egui::Context) -> bool {
+        if text.is_empty() || !self.is_idle() {
+            return false;
+        }
+        self.tool_rounds = 0;
+        self.script_error = None;
+        self.retry_model = None;
+        self.comparing = None;
+
+        if let Some(command) = text.strip_prefix("/run ") {
+            self.session.messages.push(ChatMessage::new(MessageRole::User, text.clone()));
+            self.request_shell(command);
+            return true;
         }
 
+        // 脚本出错时保留输入的内容，修改脚本后可以直接重新发送
+        let text = match self.mode_script().and_then(|script| script.map_or(Ok(text.clone()), |s| s.before_send(&text))) {
+            Ok(text) => text,
+            Err(e) => {
+                self.script_error = Some(e);
+                return false;
+            }
+        };
+
         // Add user message to session
-        self.session.messages.push(ChatMessage {
-            role: MessageRole::User,
-            content: text.clone(),
+        self.session.messages.push(ChatMessage::new(MessageRole::User, text));
+
+        // Auto-save logic
+        if let Err(e) = self.session.save() {
+            eprintln!("Failed to save session: {}", e);
+        }
+
+        self.request_reply(ctx);
+        true
+    }
+
+    /// 没有进行中的回复、工具调用或命令，可以修改会话
+    fn is_idle(&self) -> bool {
+        !self.is_busy() && self.pending_shell.is_none() && self.running_shell.is_none()
+    }
+
+    fn apply_message_action(&mut self, action: MessageAction, ctx: &egui::Context) {
+        if !self.is_idle() {
+            return;
+        }
+        self.editing = None;
+        match action {
+            MessageAction::Edit(index) => {
+                if let Some(message) = self.session.messages.get(index) {
+                    self.editing = Some((index, message.content.clone()));
+                }
+            }
+            MessageAction::Regenerate(index) | MessageAction::RetryWith(index, _) => {
+                // 从这条回复所答复的用户消息之后重新生成，原来的回复保留为另一个版本
+                let start = self.session.messages[..index].iter().rposition(|m| m.role == MessageRole::User).map_or(0, |i| i + 1);
+                self.session.fork(start);
+                self.tool_rounds = 0;
+                self.script_error = None;
+                self.comparing = None;
+                self.retry_model = None;
+                if let MessageAction::RetryWith(_, model) = action {
+                    self.retry_model = Some(model);
+                    self.comparing = Some(start);
+                }
+                self.request_reply(ctx);
+            }
+            MessageAction::Compare(index) => self.comparing = Some(index),
+            MessageAction::Delete(index) => {
+                self.comparing = None;
+                self.session.remove_message(index);
+                if let Err(e) = self.session.save() {
+                    eprintln!("Failed to save session: {}", e);
+                }
+            }
+            MessageAction::SwitchBranch(index) => {
+                self.comparing = None;
+                self.session.switch_branch(index);
+                self.reply_error = None;
+                if let Err(e) = self.session.save() {
+                    eprintln!("Failed to save session: {}", e);
+                }
+            }
+        }
+    }
+
+    /// 提交编辑后的用户消息：原消息及其后的对话保留为另一个版本
+    fn submit_edit(&mut self, ctx: &egui::Context) {
+        let Some((index, text)) = self.editing.take() else { return };
+        let text = text.trim().to_string();
+        let mut session = self.session.clone();
+        session.fork(index);
+        let previous = std::mem::replace(&mut self.session, session);
+        if !self.submit(text.clone(), ctx) {
+            self.session = previous;
+            self.editing = Some((index, text));
+        }
+    }
+
+    /// 消息下方的操作按钮，以及在此处分叉的其他版本
+    fn message_actions_ui(&self, ui: &mut Ui, index: usize, msg: &ChatMessage, control: &mut Vec<AppCommand>) -> Option<MessageAction> {
+        let mut action = None;
+        let align = if msg.role == MessageRole::User { egui::Align::Max } else { egui::Align::Min };
+        ui.with_layout(egui::Layout::top_down(align), |ui| {
+            ui.horizontal(|ui| {
+                ui.spacing_mut().item_spacing.x = 2.0;
+                let alternatives: Vec<(usize, String)> = self
+                    .session
+                    .alternatives(index)
+                    .map(|(i, branch)| (i, branch.messages.first().map(|m| preview(&m.content)).unwrap_or_else(|| "(empty)".into())))
+                    .collect();
+                if !alternatives.is_empty() {
+                    ui.menu_button(egui::RichText::new(format!("⎇ {}", alternatives.len() + 1)).small(), |ui| {
+                        ui.add_enabled(false, egui::Button::new(format!("● {}", preview(&msg.content))));
+                        for (i, text) in &alternatives {
+                            if ui.button(format!("○ {}", text)).clicked() {
+                                action = Some(MessageAction::SwitchBranch(*i));
+                                ui.close_menu();
+                            }
+                        }
+                        if msg.role == MessageRole::Agent && self.comparable(index) {
+                            ui.separator();
+                            if ui.button("⇆ Compare Side by Side").clicked() {
+                                action = Some(MessageAction::Compare(index));
+                                ui.close_menu();
+                            }
+                        }
+                    })
+                    .response
+                    .on_hover_text("Other versions of the conversation from here");
+                }
+                ui.add_enabled_ui(self.is_idle(), |ui| {
+                    if ui.small_button("📋").on_hover_text("Copy").clicked() {
+                        control.push(AppCommand::CopyToClipboard(msg.content.clone()));
+                    }
+                    match msg.role {
+                        MessageRole::User => {
+                            if ui.small_button("✏").on_hover_text("Edit and resend").clicked() {
+                                action = Some(MessageAction::Edit(index));
+                            }
+                        }
+                        MessageRole::Agent => {
+                            if ui.small_button("⟳").on_hover_text("Regenerate").clicked() {
+                                action = Some(MessageAction::Regenerate(index));
+                            }
+                            ui.menu_button(egui::RichText::new("⇄").small(), |ui| {
+                                let (remote, local) = self.model_choices();
+                                let local = local.into_iter().map(|m| (format!("🖥 {}", m), m));
+                                for (label, model) in remote.into_iter().map(|m| (m.clone(), m)).chain(local) {
+                                    if ui.button(label).clicked() {
+                                        action = Some(MessageAction::RetryWith(index, model));
+                                        ui.close_menu();
+                                    }
+                                }
+                            })
+                            .response
+                            .on_hover_text("Retry with another model and compare the answers");
+                        }
+                        MessageRole::Tool => {}
+                    }
+                    if ui.small_button("🗑").on_hover_text("Delete").clicked() {
+                        action = Some(MessageAction::Delete(index));
+                    }
+                });
+                if let Some(usage) = &msg.usage {
+                    let prefix = if usage.estimated { "~" } else { "" };
+                    ui.label(egui::RichText::new(format!("{}{}↑ {}↓", prefix, usage::format_count(usage.prompt_tokens), usage::format_count(usage.completion_tokens))).small().weak())
+                        .on_hover_text(usage.summary());
+                }
+            });
+        });
+        action
+    }
+
+    /// `at` 处是回复，且有其他以回复开头的版本（重新生成或换模型重试产生），可以并排比较
+    fn comparable(&self, at: usize) -> bool {
+        self.session.messages.get(at).is_some_and(|m| m.role == MessageRole::Agent)
+            && self.session.alternatives(at).any(|(_, b)| b.messages.first().is_some_and(|m| m.role == MessageRole::Agent))
+    }
+
+    /// 把 `at` 处的当前回复与其他回复版本分列显示，可以选用其中一个
+    fn comparison_ui(&mut self, ui: &mut Ui, at: usize, max_width: f32, control: &mut Vec<AppCommand>) -> Option<MessageAction> {
+        let mut action = None;
+        let messages = &self.session.messages[at..];
+        let mut versions: Vec<(Option<usize>, &[ChatMessage])> = vec![(None, &messages[..reply_len(messages)])];
+        versions.extend(
+            self.session
+                .alternatives(at)
+                .filter(|(_, b)| b.messages.first().is_some_and(|m| m.role == MessageRole::Agent))
+                .map(|(i, b)| (Some(i), &b.messages[..reply_len(&b.messages)])),
+        );
+
+        let mut close = false;
+        ui.horizontal(|ui| {
+            ui.set_max_width(max_width);
+            ui.strong(format!("⇆ Comparing {} answers", versions.len()));
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                close = ui.small_button("✖").on_hover_text("Close comparison").clicked();
+            });
+        });
+        let idle = self.is_idle();
+        ui.columns(versions.len(), |columns| {
+            for (ui, (branch, replies)) in columns.iter_mut().zip(&versions) {
+                let model = replies.iter().find_map(|m| m.model.as_deref()).unwrap_or("Unknown model");
+                ui.horizontal_wrapped(|ui| {
+                    ui.label(egui::RichText::new(format!("🤖 {}", model)).strong());
+                    let mut usage = TokenUsage::default();
+                    for u in replies.iter().filter_map(|m| m.usage.as_ref()) {
+                        usage.add(u);
+                    }
+                    if usage.total() > 0 {
+                        ui.weak(usage.summary());
+                    }
+                });
+                match branch {
+                    Some(i) => {
+                        if ui.add_enabled(idle, egui::Button::new("✔ Use This").small()).clicked() {
+                            action = Some(MessageAction::SwitchBranch(*i));
+                        }
+                    }
+                    None => {
+                        ui.weak("Current");
+                    }
+                }
+                ui.add_space(4.0);
+                let width = ui.available_width();
+                for message in replies.iter() {
+                    render_message(ui, message, width, control);
+                    ui.add_space(4.0);
+                }
+            }
+        });
+        if close {
+            self.comparing = None;
+        }
+        action
+    }
+
+    /// 输入以 `/` 开头时在输入框上方列出匹配的提示模板。方向键选择，Enter 或 Tab 把模板展开到输入框
+    fn prompt_suggestions_ui(&mut self, ui: &mut Ui, input_id: egui::Id) {
+        let Some(query) = self.input.text.strip_prefix('/') else { return };
+        if query.contains(char::is_whitespace) {
+            return;
+        }
+        self.prompts.refresh();
+        let matches: Vec<(String, String, String)> = self
+            .prompts
+            .matches(query)
+            .into_iter()
+            .map(|p| (p.name.clone(), p.description.clone(), p.template.clone()))
+            .collect();
+        // 例如正在输入 `/run`
+        if matches.is_empty() && !query.is_empty() && self.prompts.error().is_none() {
+            return;
+        }
+        self.input.prompt_index = self.input.prompt_index.min(matches.len().saturating_sub(1));
+
+        let mut picked = None;
+        if ui.memory(|m| m.has_focus(input_id)) && !matches.is_empty() {
+            ui.input_mut(|i| {
+                if i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown) {
+                    self.input.prompt_index = (self.input.prompt_index + 1) % matches.len();
+                }
+                if i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp) {
+                    self.input.prompt_index = (self.input.prompt_index + matches.len() - 1) % matches.len();
+                }
+                if i.consume_key(egui::Modifiers::NONE, egui::Key::Enter) || i.consume_key(egui::Modifiers::NONE, egui::Key::Tab) {
+                    picked = Some(self.input.prompt_index);
+                }
+            });
+        }
+
+        egui::Frame::group(ui.style()).show(ui, |ui| {
+            ui.set_width(ui.available_width());
+            if let Some(error) = self.prompts.error() {
+                ui.colored_label(crate::theme::palette(ui.ctx()).error, error);
+            } else if self.prompts.is_empty() {
+                ui.weak(format!("No prompt templates. Add {} to the script directory.", prompts::PROMPTS_FILE));
+            }
+            egui::ScrollArea::vertical().max_height(160.0).show(ui, |ui| {
+                for (i, (name, description, template)) in matches.iter().enumerate() {
+                    let selected = i == self.input.prompt_index;
+                    let response = ui.horizontal(|ui| {
+                        let response = ui.selectable_label(selected, egui::RichText::new(format!("/{}", name)).strong());
+                        ui.weak(description);
+                        response
+                    });
+                    let response = response.inner.on_hover_text(template);
+                    if selected && picked.is_some() {
+                        response.scroll_to_me(None);
+                    }
+                    if response.clicked() {
+                        picked = Some(i);
+                    }
+                }
+            });
+        });
+        ui.add_space(4.0);
+
+        if let Some((_, _, template)) = picked.and_then(|i| matches.get(i)) {
+            self.input.text = prompts::expand(template, crate::editor_context(ui.ctx()).as_ref(), "");
+            self.input.prompt_index = 0;
+            // 光标移到展开后的末尾，继续输入
+            let mut state = egui::TextEdit::load_state(ui.ctx(), input_id).unwrap_or_default();
+            let end = egui::text::CCursor::new(self.input.text.chars().count());
+            state.cursor.set_char_range(Some(egui::text::CCursorRange::one(end)));
+            state.store(ui.ctx(), input_id);
+            ui.memory_mut(|m| m.request_focus(input_id));
+        }
+    }
+
+    /// 输入卡片中的齿轮菜单：本会话的系统提示与采样参数
+    fn session_settings_ui(&mut self, ui: &mut Ui) {
+        let mode = self.available_modes.iter().find(|m| m.name == self.session.context_mode).cloned();
+        let default_prompt = provider::default_system_prompt(&self.session, mode.as_ref());
+        let session = &mut self.session;
+        let mut changed = false;
+        ui.menu_button("⚙", |ui| {
+            ui.set_min_width(320.0);
+            ui.label("System Prompt");
+            changed |= ui
+                .add(egui::TextEdit::multiline(&mut session.system_prompt).hint_text(&default_prompt).desired_rows(4).desired_width(f32::INFINITY))
+                .changed();
+            ui.add_space(4.0);
+
+            egui::Grid::new("agent_session_params").num_columns(2).show(ui, |ui| {
+                let mut enabled = session.temperature.is_some();
+                changed |= ui.checkbox(&mut enabled, "Temperature").changed();
+                session.temperature = enabled.then(|| session.temperature.unwrap_or(1.0));
+                if let Some(t) = &mut session.temperature {
+                    changed |= ui.add(egui::Slider::new(t, 0.0..=2.0).step_by(0.1)).changed();
+                }
+                ui.end_row();
+
+                let mut enabled = session.top_p.is_some();
+                changed |= ui.checkbox(&mut enabled, "Top-p").changed();
+                session.top_p = enabled.then(|| session.top_p.unwrap_or(1.0));
+                if let Some(p) = &mut session.top_p {
+                    changed |= ui.add(egui::Slider::new(p, 0.0..=1.0).step_by(0.05)).changed();
+                }
+                ui.end_row();
+
+                let mut enabled = session.max_tokens.is_some();
+                changed |= ui.checkbox(&mut enabled, "Max tokens").changed();
+                session.max_tokens = enabled.then(|| session.max_tokens.unwrap_or(1024));
+                if let Some(n) = &mut session.max_tokens {
+                    changed |= ui.add(egui::DragValue::new(n).range(1..=1_000_000).speed(16.0)).changed();
+                }
+                ui.end_row();
+            });
+            ui.weak("Unchecked parameters use the model's defaults.");
+            ui.add_space(4.0);
+            if ui.button("Reset to Mode Defaults").clicked() {
+                session.system_prompt.clear();
+                session.temperature = mode.as_ref().and_then(|m| m.temperature);
+                session.top_p = None;
+                session.max_tokens = None;
+                changed = true;
+            }
+        })
+        .response
+        .on_hover_text("Session Settings");
+        if changed {
+            if let Err(e) = self.session.save() {
+                eprintln!("Failed to save session: {}", e);
+            }
+        }
+    }
+
+    /// 就地编辑用户消息
+    fn edit_message_ui(&mut self, ui: &mut Ui, max_width: f32) -> Option<bool> {
+        let (_, text) = self.editing.as_mut()?;
+        let mut submitted = None;
+        ui.with_layout(egui::Layout::top_down(egui::Align::Max), |ui| {
+            egui::Frame::group(ui.style()).show(ui, |ui| {
+                ui.set_max_width(max_width * 0.85);
+                ui.add(egui::TextEdit::multiline(text).desired_rows(2).desired_width(f32::INFINITY));
+                ui.horizontal(|ui| {
+                    if ui.button("Send").on_hover_text("The original message is kept as another version").clicked() {
+                        submitted = Some(true);
+                    }
+                    if ui.button("Cancel").clicked() {
+                        submitted = Some(false);
+                    }
+                });
+            });
         });
+        ui.add_space(8.0);
+        submitted
+    }
+
+    /// 当前模式的脚本，内置模式为 None
+    fn mode_script(&self) -> Result<Option<ModeScript>, String> {
+        let mode = self.available_modes.iter().find(|m| m.name == self.session.context_mode);
+        mode.and_then(|m| m.script.as_deref()).map(ModeScript::load).transpose()
+    }
+
+    /// 生成请求：系统提示与可用工具由模式脚本决定
+    fn build_request(&mut self, ctx: &egui::Context) -> Result<ChatRequest, String> {
+        let mode = self.available_modes.iter().find(|m| m.name == self.session.context_mode);
+        let mut system = provider::system_prompt(&self.session, mode);
+        self.allowed_tools = None;
+        if let Some(script) = self.mode_script()? {
+            system = script.system_prompt(&system)?;
+            self.allowed_tools = script.allowed_tools()?;
+        }
+        let tools: Vec<_> = crate::agent_tools(ctx).into_iter().filter(|t| self.tool_allowed(t.name())).collect();
+        Ok(ChatRequest::from_session(&self.session, &system).with_tools(&tools))
+    }
+
+    fn tool_allowed(&self, name: &str) -> bool {
+        self.allowed_tools.as_ref().is_none_or(|allowed| allowed.iter().any(|n| n == name))
+    }
+
+    /// 把整个会话发给当前选择的模型，回复在后台返回
+    fn request_reply(&mut self, ctx: &egui::Context) {
+        let mut request = match self.build_request(ctx) {
+            Ok(request) => request,
+            Err(e) => {
+                self.script_error = Some(e);
+                return;
+            }
+        };
+        self.reply_error = None;
+        self.partial_reply.clear();
+        self.partial_calls.clear();
+        self.reply_usage = None;
+        let model = self.retry_model.as_ref().unwrap_or(&self.session.model_name);
+        match self.models.resolve(model, &AgentConfig::load()) {
+            Ok((backend, model)) => {
+                request.model = model;
+                self.reply_model = request.model.clone();
+                self.prompt_estimate = usage::estimate_prompt(&request);
+                self.pending_reply = Some(provider::spawn_chat(backend, request, ctx));
+            }
+            Err(e) => self.reply_error = Some(e),
+        }
+    }
+
+    /// 接收后台请求送回的回复片段
+    fn poll_reply(&mut self) {
+        let Some(stream) = &self.pending_reply else { return };
+        for event in stream.drain() {
+            match event {
+                StreamEvent::Delta(text) => self.partial_reply.push_str(&text),
+                StreamEvent::ToolCalls(calls) => self.partial_calls = calls,
+                StreamEvent::Usage(usage) => self.reply_usage = Some(usage),
+                StreamEvent::Done => return self.finish_reply(None),
+                StreamEvent::Failed(e) => return self.finish_reply(Some(e)),
+            }
+        }
+    }
+
+    /// 正在等待回复或执行工具调用
+    fn is_busy(&self) -> bool {
+        self.pending_reply.is_some() || self.awaiting_tools
+    }
 
-        // Mock response (Phase 1)
-        let mode = self.session.context_mode.clone();
-        let model = self.session.model_name.clone();
-        
+    /// 停止接收回复并取消尚未执行的工具调用，已收到的部分仍记入会话
+    fn stop_reply(&mut self) {
+        if let Some(stream) = &self.pending_reply {
+            stream.cancel();
+            self.poll_reply();
+        }
+        if self.pending_reply.is_some() {
+            self.finish_reply(None);
+        }
+        self.awaiting_tools = false;
+        // 正在执行的工具结束后照常记录结果
+        if let Some((_, running)) = &self.running_tool {
+            running.cancel();
+        }
+        // 每个工具调用都要有结果，否则之后的请求会被服务拒绝
+        let cancelled: Vec<ToolCall> = self.pending_tool.take().into_iter().chain(self.tool_queue.drain(..)).collect();
+        for call in cancelled {
+            self.log_tool_result(&call, "Cancelled by the user".into());
+        }
+    }
+
+    fn finish_reply(&mut self, error: Option<String>) {
+        self.pending_reply = None;
+        self.reply_error = error;
+        let mut content = std::mem::take(&mut self.partial_reply);
+        let calls = std::mem::take(&mut self.partial_calls);
+        let usage = self.record_usage(&content, &calls);
+        if content.is_empty() && calls.is_empty() {
+            return;
+        }
+        if !content.is_empty() {
+            // 脚本出错时保留原始回复
+            match self.mode_script().and_then(|script| script.map_or(Ok(content.clone()), |s| s.after_reply(&content))) {
+                Ok(processed) => content = processed,
+                Err(e) => self.script_error = Some(e),
+            }
+        }
         self.session.messages.push(ChatMessage {
-            role: MessageRole::Agent,
-            content: format!("(Mock Response in [{}] mode using [{}])\nReceived: {}", mode, model, text),
+            tool_calls: calls.clone(),
+            usage,
+            model: Some(self.reply_model.clone()),
+            ..ChatMessage::new(MessageRole::Agent, content)
         });
+        self.reply_ready = true;
+        if let Err(e) = self.session.save() {
+            eprintln!("Failed to save session: {}", e);
+        }
+        if !calls.is_empty() {
+            self.tool_queue.extend(calls);
+            self.awaiting_tools = true;
+        }
+    }
 
-        // Auto-save logic
+    /// 记入本次回复的用量；服务未返回用量（或回复被中途停止）时按文本估算
+    fn record_usage(&mut self, content: &str, calls: &[ToolCall]) -> Option<TokenUsage> {
+        let mut usage = self.reply_usage.take().or_else(|| {
+            (!content.is_empty() || !calls.is_empty()).then(|| TokenUsage {
+                prompt_tokens: self.prompt_estimate,
+                completion_tokens: usage::estimate_tokens(content) + calls.iter().map(|c| usage::estimate_tokens(&c.arguments)).sum::<u64>(),
+                estimated: true,
+                cost: None,
+            })
+        })?;
+        let config = AgentConfig::load();
+        usage.cost = usage::cost(&config.prices, &self.reply_model, &usage);
+        self.month_usage = UsageLog::record(&usage);
+        self.monthly_budget = config.monthly_budget;
+        Some(usage)
+    }
+
+    /// 标题栏右侧：本会话的用量，以及本月超出预算时的提示
+    fn usage_ui(&self, ui: &mut Ui) {
+        let palette = crate::theme::palette(ui.ctx());
+        let session = self.session.usage();
+        let month = &self.month_usage;
+        let mut details = format!(
+            "This conversation\n  Prompt: {} tokens\n  Completion: {} tokens\n\nThis month: {}",
+            session.prompt_tokens,
+            session.completion_tokens,
+            month.summary()
+        );
+        if session.estimated || month.estimated {
+            details.push_str("\n\n~ Some counts are estimated because the service did not report usage.");
+        }
+        if let Some(budget) = self.monthly_budget.filter(|b| *b > 0.0) {
+            details.push_str(&format!("\nMonthly budget: {}", usage::format_cost(budget)));
+            let spent = month.cost.unwrap_or(0.0);
+            if spent >= budget {
+                ui.colored_label(palette.error, "⚠ Budget exceeded").on_hover_text(&details);
+            } else if spent >= budget * 0.8 {
+                ui.colored_label(palette.warning, format!("⚠ {:.0}% of budget", spent / budget * 100.0)).on_hover_text(&details);
+            }
+        }
+        if session.total() > 0 {
+            ui.weak(session.summary()).on_hover_text(details);
+        }
+    }
+
+    fn log_tool_result(&mut self, call: &ToolCall, content: String) {
+        self.session.messages.push(ChatMessage::tool_result(call, content));
         if let Err(e) = self.session.save() {
             eprintln!("Failed to save session: {}", e);
         }
+    }
+
+    /// 依次执行模型请求的工具调用：有副作用的先等待确认，全部完成后把结果发回模型
+    fn tool_calls_ui(&mut self, ui: &mut Ui, control: &mut Vec<AppCommand>) {
+        if let Some((call, running)) = &self.running_tool {
+            let Some(outcome) = running.take() else { return };
+            let call = call.clone();
+            self.running_tool = None;
+            self.log_tool_result(&call, outcome.log());
+            control.extend(outcome.commands);
+        }
+
+        if self.pending_tool.is_none() {
+            if let Some(call) = self.tool_queue.pop_front() {
+                match crate::agent_tools(ui.ctx()).into_iter().find(|t| t.name() == call.name) {
+                    _ if !self.tool_allowed(&call.name) => self.log_tool_result(&call, format!("Error: tool \"{}\" is not available in this mode", call.name)),
+                    None => self.log_tool_result(&call, format!("Error: unknown tool \"{}\"", call.name)),
+                    Some(tool) if tool.has_side_effects() => self.pending_tool = Some(call),
+                    Some(tool) => {
+                        let roots = crate::workspace::current(ui.ctx()).roots.clone();
+                        let running = tools::spawn_tool(tool, &call, roots, ui.ctx());
+                        self.running_tool = Some((call, running));
+                        return;
+                    }
+                }
+            }
+        }
 
-        self.input.text.clear();
+        if let Some(call) = self.pending_tool.clone() {
+            let mut allowed = None;
+            egui::Window::new("Allow Tool Call?")
+                .id(ui.id().with("tool_confirm"))
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+                .show(ui.ctx(), |ui| {
+                    ui.label(format!("The agent wants to call {}:", call.name));
+                    ui.add_space(4.0);
+                    egui::Frame::group(ui.style())
+                        .fill(ui.visuals().extreme_bg_color)
+                        .show(ui, |ui| {
+                            ui.label(egui::RichText::new(tools::pretty_arguments(&call.arguments)).monospace());
+                        });
+                    ui.add_space(8.0);
+                    ui.horizontal(|ui| {
+                        if ui.button("✔ Allow").clicked() {
+                            allowed = Some(true);
+                        }
+                        if ui.button("Deny").clicked() {
+                            allowed = Some(false);
+                        }
+                    });
+                });
+            match allowed {
+                Some(true) => {
+                    self.pending_tool = None;
+                    // 确认期间工具可能已被注销，仍要给出结果，否则之后的请求会被服务拒绝
+                    match crate::agent_tools(ui.ctx()).into_iter().find(|t| t.name() == call.name) {
+                        Some(tool) => {
+                            let roots = crate::workspace::current(ui.ctx()).roots.clone();
+                            let running = tools::spawn_tool(tool, &call, roots, ui.ctx());
+                            self.running_tool = Some((call, running));
+                        }
+                        None => self.log_tool_result(&call, format!("Error: unknown tool \"{}\"", call.name)),
+                    }
+                }
+                Some(false) => {
+                    self.pending_tool = None;
+                    self.log_tool_result(&call, "Denied by the user".into());
+                }
+                None => {}
+            }
+            return;
+        }
+
+        if self.awaiting_tools && self.tool_queue.is_empty() && self.running_tool.is_none() {
+            self.awaiting_tools = false;
+            if self.tool_rounds < MAX_TOOL_ROUNDS {
+                self.tool_rounds += 1;
+                self.request_reply(ui.ctx());
+            } else {
+                self.reply_error = Some(format!("Stopped after {} rounds of tool calls", MAX_TOOL_ROUNDS));
+            }
+        }
     }
 }
 
@@ -63,7 +975,66 @@ impl TabInstance for AgentTab {
         "Agent".into()
     }
 
-    fn ui(&mut self, ui: &mut Ui, _control: &mut Vec<AppCommand>) {
+    fn icon(&self) -> Option<crate::TabIcon> {
+        Some(crate::TabIcon::Emoji("🤖".into()))
+    }
+
+    fn on_context_menu(&mut self, ui: &mut Ui, control: &mut Vec<AppCommand>) {
+        let has_messages = !self.session.messages.is_empty();
+        if ui.add_enabled(has_messages, egui::Button::new("📝 Export as Markdown…")).clicked() {
+            self.export_session("md", control);
+            ui.close_menu();
+        }
+        if ui.add_enabled(has_messages, egui::Button::new("📦 Export as JSON…")).clicked() {
+            self.export_session("json", control);
+            ui.close_menu();
+        }
+        ui.separator();
+        if ui.add_enabled(self.is_idle(), egui::Button::new("📥 Import Chat…")).clicked() {
+            self.import_session(control);
+            ui.close_menu();
+        }
+    }
+
+    fn on_app_exit(&mut self) {
+        self.follow_session_moves();
+        self.stop_reply();
+        // 把仍在进行中的工具调用记入会话，避免重新打开后丢失上下文
+        if let Some((request, running)) = self.running_shell.take() {
+            running.cancel();
+            match running.take() {
+                Some(outcome) => self.log_tool(tools::format_log(&request, &outcome)),
+                None => self.log_tool(format!("$ {}\nInterrupted: application exited", request.command)),
+            }
+        }
+        if let Some(request) = self.pending_shell.take() {
+            self.log_tool(format!("$ {}\nNot run: application exited", request.command));
+        }
+        if let Some((call, running)) = self.running_tool.take() {
+            match running.take() {
+                Some(outcome) => self.log_tool_result(&call, outcome.log()),
+                None => self.log_tool_result(&call, "Interrupted: application exited".into()),
+            }
+        }
+        if let Err(e) = self.session.save() {
+            eprintln!("Failed to save session: {}", e);
+        }
+    }
+
+    fn ui(&mut self, ui: &mut Ui, control: &mut Vec<AppCommand>) {
+        self.follow_session_moves();
+        self.poll_reply();
+        self.tool_calls_ui(ui, control);
+        self.shell_ui(ui, control);
+
+        if self.sidebar.open {
+            egui::SidePanel::left(ui.make_persistent_id("agent_session_sidebar"))
+                .resizable(true)
+                .default_width(200.0)
+                .width_range(140.0..=400.0)
+                .show_inside(ui, |ui| self.sidebar_ui(ui));
+        }
+
         // 1. Input Area (Bottom) with Top Row Controls
         let mut sent_text = None;
 
@@ -89,25 +1060,59 @@ impl TabInstance for AgentTab {
                             
                             // Mode Selector
                             ui.label("Mode:");
+                            let current_mode = self.available_modes.iter().find(|m| m.name == self.session.context_mode);
+                            let selected_text = current_mode.map(|m| m.label()).unwrap_or_else(|| self.session.context_mode.clone());
+                            let mut chosen_mode = None;
                             egui::ComboBox::from_id_salt("mode_select_input")
-                                .selected_text(&self.session.context_mode)
+                                .selected_text(selected_text)
+                                .width(160.0)
                                 .show_ui(ui, |ui| {
+                                    ui.set_min_width(260.0);
                                     for mode in &self.available_modes {
-                                        ui.selectable_value(&mut self.session.context_mode, mode.clone(), mode);
+                                        let selected = mode.name == self.session.context_mode;
+                                        let response = ui.selectable_label(selected, mode_entry_text(ui, mode));
+                                        if response.clicked() {
+                                            chosen_mode = Some(mode.clone());
+                                        }
                                     }
                                 });
+                            if let Some(mode) = chosen_mode {
+                                self.session.apply_mode(&mode);
+                            }
 
                             ui.add_space(8.0);
 
                             // Model Selector
                             ui.label("Model:");
+                            let (remote, local) = self.model_choices();
                             egui::ComboBox::from_id_salt("model_select_input")
                                 .selected_text(&self.session.model_name)
                                 .show_ui(ui, |ui| {
-                                    for model in &self.available_models {
+                                    for model in remote {
                                         ui.selectable_value(&mut self.session.model_name, model.clone(), model);
                                     }
+                                    ui.separator();
+                                    ui.weak("Local (Ollama)");
+                                    let placeholder = provider::LOCAL_PLACEHOLDER.to_string();
+                                    ui.selectable_value(&mut self.session.model_name, placeholder.clone(), format!("🖥 {}", placeholder))
+                                        .on_hover_text("The first installed Llama model, or any installed model");
+                                    for model in local {
+                                        ui.selectable_value(&mut self.session.model_name, model.clone(), format!("🖥 {}", model));
+                                    }
+                                    if let Some(error) = self.models.local_error() {
+                                        ui.weak(error);
+                                    }
                                 });
+                            if self.models.is_loading() {
+                                ui.spinner();
+                            } else if ui.small_button("⟳").on_hover_text(self.models.error().unwrap_or_else(|| "Refresh model list".into())).clicked() {
+                                self.models.refresh(&AgentConfig::load(), ui.ctx());
+                            }
+                            self.session_settings_ui(ui);
+                            if let Some(t) = self.session.temperature {
+                                ui.label(egui::RichText::new(format!("T={:.1}", t)).weak())
+                                    .on_hover_text("Sampling temperature");
+                            }
 
                             ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                                 // Context attachment indicator (Mock)
@@ -119,10 +1124,23 @@ impl TabInstance for AgentTab {
                         ui.separator();
                         ui.add_space(4.0);
 
+                        if let Some(error) = self.reply_error.clone() {
+                            ui.horizontal(|ui| {
+                                ui.colored_label(crate::theme::palette(ui.ctx()).error, format!("⚠ {}", error));
+                                if ui.small_button("Retry").clicked() {
+                                    self.request_reply(ui.ctx());
+                                }
+                            });
+                            ui.add_space(4.0);
+                        }
+
                         // B. Input Field (Frameless)
+                        let input_id = ui.make_persistent_id("agent_input");
+                        self.prompt_suggestions_ui(ui, input_id);
                         let text_area = egui::TextEdit::multiline(&mut self.input.text)
+                            .id(input_id)
                             .frame(false)
-                            .hint_text("Ask me anything...")
+                            .hint_text("Ask me anything... (/ for prompt templates, /run <command> to execute a shell command)")
                             .desired_rows(2)
                             .desired_width(f32::INFINITY)
                             .lock_focus(true);
@@ -135,8 +1153,11 @@ impl TabInstance for AgentTab {
                              if ui.button("📎").on_hover_text("Attach File").clicked() { /* TODO */ }
                              
                              ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                                let send_btn = egui::Button::new("  🚀 Send  ").rounding(8.0);
-                                if ui.add(send_btn).clicked() {
+                                if self.is_busy() {
+                                    if ui.add(egui::Button::new("  ⏹ Stop  ").rounding(8.0)).on_hover_text("Stop generating").clicked() {
+                                        self.stop_reply();
+                                    }
+                                } else if ui.add(egui::Button::new("  🚀 Send  ").rounding(8.0)).clicked() {
                                     sent_text = Some(self.input.text.clone());
                                 }
                                 if response.has_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter) && i.modifiers.command) {
@@ -149,7 +1170,7 @@ impl TabInstance for AgentTab {
 
         // Handle sending
         if let Some(_) = sent_text {
-            self.send_message();
+            self.send_message(ui.ctx());
         }
 
         // 2. Chat Area (Fill Rest)
@@ -158,7 +1179,24 @@ impl TabInstance for AgentTab {
             ui.add_space(8.0);
             ui.horizontal(|ui| {
                 ui.add_space(8.0);
+                if ui.selectable_label(self.sidebar.open, "☰").on_hover_text("Sessions").clicked() {
+                    self.sidebar.open = !self.sidebar.open;
+                }
                 ui.heading("Agent");
+                if let Some((request, _)) = &self.running_shell {
+                    ui.spinner();
+                    ui.weak(format!("Running: {}", request.command));
+                } else if let Some((call, _)) = &self.running_tool {
+                    ui.spinner();
+                    ui.weak(format!("Running tool: {}", call.name));
+                } else if self.pending_reply.is_some() {
+                    ui.spinner();
+                    ui.weak(format!("{} is typing…", self.reply_model));
+                }
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    ui.add_space(8.0);
+                    self.usage_ui(ui);
+                });
             });
             ui.separator();
 
@@ -169,20 +1207,134 @@ impl TabInstance for AgentTab {
                 .show(ui, |ui| {
                     ui.add_space(8.0);
                     let inner_w = ui.available_width() - 16.0; 
-                    for msg in &self.session.messages {
-                        render_message(ui, msg, inner_w);
+                    let mut action = None;
+                    // 并排比较时，当前版本的回复已显示在比较视图中
+                    let mut skip_to = 0;
+                    for index in 0..self.session.messages.len() {
+                        if index < skip_to {
+                            continue;
+                        }
+                        if self.comparing == Some(index) && self.comparable(index) {
+                            if let Some(a) = self.comparison_ui(ui, index, inner_w, control) {
+                                action = Some(a);
+                            }
+                            skip_to = index + reply_len(&self.session.messages[index..]);
+                            ui.add_space(8.0);
+                            continue;
+                        }
+                        if self.editing.as_ref().is_some_and(|(i, _)| *i == index) {
+                            match self.edit_message_ui(ui, inner_w) {
+                                Some(true) => {
+                                    self.submit_edit(ui.ctx());
+                                    break;
+                                }
+                                Some(false) => self.editing = None,
+                                None => {}
+                            }
+                            continue;
+                        }
+                        let msg = &self.session.messages[index];
+                        render_message(ui, msg, inner_w, control);
+                        if let Some(a) = self.message_actions_ui(ui, index, msg, control) {
+                            action = Some(a);
+                        }
+                        ui.add_space(8.0);
+                    }
+                    if let Some(action) = action {
+                        self.apply_message_action(action, ui.ctx());
+                    }
+                    if self.pending_reply.is_some() {
+                        render_typing(ui, &self.partial_reply, inner_w, control);
+                    }
+                    if let Some(error) = &self.script_error {
+                        let color = crate::theme::palette(ui.ctx()).error;
+                        let mut dismissed = false;
+                        egui::Frame::none()
+                            .stroke(egui::Stroke::new(1.0, color))
+                            .rounding(4.0)
+                            .inner_margin(8.0)
+                            .show(ui, |ui| {
+                                ui.set_max_width(inner_w);
+                                ui.horizontal(|ui| {
+                                    ui.colored_label(color, "⚠ Mode script error");
+                                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                        dismissed = ui.small_button("✖").on_hover_text("Dismiss").clicked();
+                                    });
+                                });
+                                ui.label(egui::RichText::new(error).monospace());
+                            });
+                        if dismissed {
+                            self.script_error = None;
+                        }
                     }
                     ui.add_space(8.0);
                 });
         });
     }
 
+    fn needs_attention(&mut self) -> bool {
+        std::mem::take(&mut self.reply_ready)
+    }
+
     fn box_clone(&self) -> Box<dyn TabInstance> {
         Box::new(self.clone())
     }
 }
 
-fn render_message(ui: &mut Ui, msg: &ChatMessage, max_width: f32) {
+/// 模式下拉框中的富文本条目：图标、名称、描述以及声明的默认参数
+fn mode_entry_text(ui: &Ui, mode: &ModeInfo) -> egui::text::LayoutJob {
+    let mut job = egui::text::LayoutJob::default();
+    let body = egui::TextStyle::Body.resolve(ui.style());
+    let small = egui::TextStyle::Small.resolve(ui.style());
+    job.append(&mode.label(), 0.0, egui::TextFormat::simple(body, ui.visuals().strong_text_color()));
+
+    let mut details = Vec::new();
+    if let Some(model) = &mode.preferred_model {
+        details.push(model.clone());
+    }
+    if let Some(t) = mode.temperature {
+        details.push(format!("T={:.1}", t));
+    }
+    if !details.is_empty() {
+        job.append(&format!("  {}", details.join(" · ")), 0.0, egui::TextFormat::simple(small.clone(), ui.visuals().weak_text_color()));
+    }
+    if !mode.description.is_empty() {
+        job.append(&format!("\n{}", mode.description), 0.0, egui::TextFormat::simple(small, ui.visuals().weak_text_color()));
+    }
+    job
+}
+
+/// 正在生成的回复：已收到的部分加光标，尚未收到内容时显示等待动画
+fn render_typing(ui: &mut Ui, partial: &str, max_width: f32, control: &mut Vec<AppCommand>) {
+    if partial.is_empty() {
+        ui.horizontal(|ui| {
+            ui.add_space(4.0);
+            ui.spinner();
+            ui.weak("Thinking…");
+        });
+        ui.add_space(8.0);
+        return;
+    }
+    let message = ChatMessage::new(MessageRole::Agent, format!("{}▌", partial));
+    render_message(ui, &message, max_width, control);
+    ui.add_space(8.0);
+}
+
+/// 版本菜单中显示的消息摘要：第一行的前 40 个字符
+/// 一次回复（包括其中的工具调用与结果）的消息数，到下一条用户消息为止
+fn reply_len(messages: &[ChatMessage]) -> usize {
+    messages.iter().position(|m| m.role == MessageRole::User).unwrap_or(messages.len())
+}
+
+fn preview(text: &str) -> String {
+    let line = text.lines().find(|l| !l.trim().is_empty()).unwrap_or_default().trim();
+    match line.char_indices().nth(40) {
+        Some((end, _)) => format!("{}…", &line[..end]),
+        None => line.to_string(),
+    }
+}
+
+fn render_message(ui: &mut Ui, msg: &ChatMessage, max_width: f32, control: &mut Vec<AppCommand>) {
     let (align, fill_color, stroke_color, label_color) = match msg.role {
         MessageRole::User => (
             egui::Align::RIGHT,
@@ -190,7 +1342,7 @@ fn render_message(ui: &mut Ui, msg: &ChatMessage, max_width: f32) {
             egui::Stroke::new(1.0, ui.visuals().selection.bg_fill.gamma_multiply(0.5)),
             ui.visuals().strong_text_color(),
         ),
-        MessageRole::Agent => (
+        MessageRole::Agent | MessageRole::Tool => (
             egui::Align::LEFT,
             ui.visuals().widgets.active.bg_fill.gamma_multiply(0.1),
             egui::Stroke::new(1.0, ui.visuals().widgets.active.bg_fill.gamma_multiply(0.3)),
@@ -198,6 +1350,29 @@ fn render_message(ui: &mut Ui, msg: &ChatMessage, max_width: f32) {
         ),
     };
 
+    if msg.role == MessageRole::Tool {
+        egui::Frame::none()
+            .fill(ui.visuals().extreme_bg_color)
+            .stroke(ui.visuals().widgets.noninteractive.bg_stroke)
+            .rounding(4.0)
+            .inner_margin(8.0)
+            .show(ui, |ui| {
+                ui.set_max_width(max_width);
+                if msg.tool_call_id.is_some() {
+                    // 模型调用工具的结果可能很长，默认折叠
+                    egui::CollapsingHeader::new(egui::RichText::new("🛠 Tool result").small().weak())
+                        .id_salt(ui.next_auto_id())
+                        .show(ui, |ui| {
+                            ui.label(egui::RichText::new(&msg.content).monospace());
+                        });
+                } else {
+                    ui.label(egui::RichText::new("🛠 Tool").small().weak());
+                    ui.label(egui::RichText::new(&msg.content).monospace());
+                }
+            });
+        return;
+    }
+
     ui.with_layout(egui::Layout::top_down(align), |ui| {
         let max_bubble_w = max_width * 0.85;
         egui::Frame::none()
@@ -207,8 +1382,14 @@ fn render_message(ui: &mut Ui, msg: &ChatMessage, max_width: f32) {
             .inner_margin(10.0)
             .show(ui, |ui| {
                 ui.set_max_width(max_bubble_w);
-                ui.label(egui::RichText::new(&msg.content).color(label_color));
+                if msg.role == MessageRole::Agent {
+                    markdown::show(ui, &msg.content, label_color, control);
+                    for call in &msg.tool_calls {
+                        ui.label(egui::RichText::new(format!("🛠 {}({})", call.name, call.arguments)).small().monospace().weak());
+                    }
+                } else {
+                    ui.label(egui::RichText::new(&msg.content).color(label_color));
+                }
             });
     });
-    ui.add_space(8.0);
 }
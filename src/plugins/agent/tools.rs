@@ -0,0 +1,413 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use crate::{AgentTool, AppCommand};
+use super::models::ToolCall;
+
+/// 记录到会话中的输出最大长度（字符）
+const MAX_LOGGED_OUTPUT: usize = 4000;
+/// read_file 工具返回的最大长度（字符）
+const MAX_READ_CHARS: usize = 20000;
+/// list_directory 工具最多列出的项数
+const MAX_LISTED_ENTRIES: usize = 500;
+/// 命令的最长运行时间，超时后结束整个进程组
+const COMMAND_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// 即使命中白名单也拒绝的 shell 元字符，防止通过管道 / 串联绕过限制
+const FORBIDDEN_CHARS: &[char] = &[';', '&', '|', '`', '$', '<', '>', '\n', '\r'];
+
+// ----------------------------------------------------------------------------
+// Shell 命令工具的安全策略
+// ----------------------------------------------------------------------------
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ShellPolicy {
+    /// 允许的命令前缀（按单词匹配），例如 `cargo` 或 `git status`
+    #[serde(default = "default_allowlist")]
+    pub allowlist: Vec<String>,
+    /// 禁止出现的片段（不区分大小写），优先于白名单
+    #[serde(default = "default_denylist")]
+    pub denylist: Vec<String>,
+}
+
+fn default_allowlist() -> Vec<String> {
+    ["cargo", "git status", "git diff", "git log", "ls", "dir", "echo", "rustc --version"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect()
+}
+
+fn default_denylist() -> Vec<String> {
+    ["rm -rf", "sudo", "del /", "format ", "shutdown", "mkfs", "reg delete"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect()
+}
+
+impl Default for ShellPolicy {
+    fn default() -> Self {
+        Self {
+            allowlist: default_allowlist(),
+            denylist: default_denylist(),
+        }
+    }
+}
+
+impl ShellPolicy {
+    /// 检查命令是否允许执行，拒绝时返回原因
+    pub fn check(&self, command: &str) -> Result<(), String> {
+        let command = command.trim();
+        if command.is_empty() {
+            return Err("Empty command".into());
+        }
+        if let Some(c) = command.chars().find(|c| FORBIDDEN_CHARS.contains(c)) {
+            return Err(format!("Shell operator '{}' is not allowed", c.escape_default()));
+        }
+
+        let lower = command.to_lowercase();
+        if let Some(denied) = self.denylist.iter().find(|d| !d.trim().is_empty() && lower.contains(&d.to_lowercase())) {
+            return Err(format!("Command matches denylist entry '{}'", denied));
+        }
+
+        let words: Vec<&str> = command.split_whitespace().collect();
+        let allowed = self.allowlist.iter().any(|entry| {
+            let prefix: Vec<&str> = entry.split_whitespace().collect();
+            !prefix.is_empty() && words.starts_with(&prefix)
+        });
+        if allowed {
+            Ok(())
+        } else {
+            Err("Command is not in the allowlist".into())
+        }
+    }
+}
+
+// ----------------------------------------------------------------------------
+// 命令执行
+// ----------------------------------------------------------------------------
+
+/// 等待用户确认的命令请求
+#[derive(Debug, Clone)]
+pub struct ShellRequest {
+    pub command: String,
+    pub cwd: PathBuf,
+}
+
+#[derive(Debug, Clone)]
+pub struct ShellOutcome {
+    pub exit_code: Option<i32>,
+    pub output: String,
+}
+
+/// 后台执行的命令或工具调用：结果写入后由界面取走，`cancel` 请求提前结束
+#[derive(Debug)]
+pub struct Running<T> {
+    result: Arc<Mutex<Option<T>>>,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl<T> Clone for Running<T> {
+    fn clone(&self) -> Self {
+        Self { result: self.result.clone(), cancelled: self.cancelled.clone() }
+    }
+}
+
+impl<T: Send + 'static> Running<T> {
+    fn spawn(ctx: &egui::Context, job: impl FnOnce(&AtomicBool) -> T + Send + 'static) -> Self {
+        let running = Self { result: Arc::new(Mutex::new(None)), cancelled: Arc::new(AtomicBool::new(false)) };
+        let (result, cancelled, ctx) = (running.result.clone(), running.cancelled.clone(), ctx.clone());
+        std::thread::spawn(move || {
+            let value = job(&cancelled);
+            if let Ok(mut r) = result.lock() {
+                *r = Some(value);
+            }
+            ctx.request_repaint();
+        });
+        running
+    }
+
+    /// 已完成时取走结果
+    pub fn take(&self) -> Option<T> {
+        self.result.lock().ok().and_then(|mut r| r.take())
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+}
+
+/// 通过系统 shell 执行命令，超过 `COMMAND_TIMEOUT` 或 `cancelled` 被设置时结束进程。会阻塞。
+/// 不经过终端的 `RunInTerminal`：终端的输出无法取回交给模型，命令与输出改为记录在会话中
+fn run_shell(request: &ShellRequest, cancelled: &AtomicBool) -> ShellOutcome {
+    #[cfg(windows)]
+    let mut cmd = {
+        let mut c = Command::new("cmd");
+        c.arg("/C").arg(&request.command);
+        c
+    };
+    #[cfg(not(windows))]
+    let mut cmd = {
+        use std::os::unix::process::CommandExt;
+        let mut c = Command::new("sh");
+        // 单独的进程组，结束时连同子进程一起结束
+        c.arg("-c").arg(&request.command).process_group(0);
+        c
+    };
+    cmd.current_dir(&request.cwd).stdin(Stdio::null()).stdout(Stdio::piped()).stderr(Stdio::piped());
+
+    let mut child = match cmd.spawn() {
+        Ok(child) => child,
+        Err(e) => return ShellOutcome { exit_code: None, output: format!("Failed to start: {}", e) },
+    };
+    // 在单独的线程中读取输出，避免管道写满后进程阻塞
+    let stdout = child.stdout.take().map(read_to_end);
+    let stderr = child.stderr.take().map(read_to_end);
+    let started = Instant::now();
+    let (exit_code, interrupted) = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break (status.code(), None),
+            Ok(None) if cancelled.load(Ordering::Relaxed) => break (None, Some("Cancelled by the user".to_string())),
+            Ok(None) if started.elapsed() >= COMMAND_TIMEOUT => {
+                break (None, Some(format!("Timed out after {} seconds", COMMAND_TIMEOUT.as_secs())))
+            }
+            Ok(None) => std::thread::sleep(Duration::from_millis(50)),
+            Err(e) => break (None, Some(format!("Failed to wait for the command: {}", e))),
+        }
+    };
+    if interrupted.is_some() {
+        kill_tree(&mut child);
+    }
+
+    let mut output = String::new();
+    for reader in [stdout, stderr].into_iter().flatten() {
+        output.push_str(&String::from_utf8_lossy(&reader.join().unwrap_or_default()));
+    }
+    if let Some(reason) = interrupted {
+        output.push('\n');
+        output.push_str(&reason);
+    }
+    ShellOutcome { exit_code, output }
+}
+
+fn read_to_end(mut pipe: impl std::io::Read + Send + 'static) -> std::thread::JoinHandle<Vec<u8>> {
+    std::thread::spawn(move || {
+        let mut buffer = Vec::new();
+        let _ = pipe.read_to_end(&mut buffer);
+        buffer
+    })
+}
+
+fn kill_tree(child: &mut Child) {
+    #[cfg(unix)]
+    let _ = Command::new("kill").args(["-KILL", "--", &format!("-{}", child.id())]).status();
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+/// 在后台线程中通过系统 shell 执行命令
+pub fn spawn_shell(request: &ShellRequest, ctx: &egui::Context) -> Running<ShellOutcome> {
+    let request = request.clone();
+    Running::spawn(ctx, move |cancelled| run_shell(&request, cancelled))
+}
+
+/// 生成写入会话的执行记录
+pub fn format_log(request: &ShellRequest, outcome: &ShellOutcome) -> String {
+    let mut output = outcome.output.trim_end().to_string();
+    if output.chars().count() > MAX_LOGGED_OUTPUT {
+        output = output.chars().take(MAX_LOGGED_OUTPUT).collect();
+        output.push_str("\n... (output truncated)");
+    }
+    let status = outcome.exit_code.map(|c| c.to_string()).unwrap_or_else(|| "none".into());
+    format!(
+        "$ {}\ncwd: {}\nexit code: {}\n{}",
+        request.command,
+        request.cwd.display(),
+        status,
+        output
+    )
+}
+
+// ----------------------------------------------------------------------------
+// 工具调用的执行
+// ----------------------------------------------------------------------------
+
+/// 工具调用的结果，以及工具请求宿主执行的命令
+pub struct ToolOutcome {
+    pub result: Result<String, String>,
+    pub commands: Vec<AppCommand>,
+}
+
+impl std::fmt::Debug for ToolOutcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ToolOutcome").field("result", &self.result).field("commands", &self.commands.len()).finish()
+    }
+}
+
+impl ToolOutcome {
+    /// 写入会话、提供给模型的结果文本
+    pub fn log(&self) -> String {
+        let text = match &self.result {
+            Ok(output) => output.clone(),
+            Err(e) => format!("Error: {}", e),
+        };
+        if text.chars().count() <= MAX_READ_CHARS {
+            return text;
+        }
+        let mut truncated: String = text.chars().take(MAX_READ_CHARS).collect();
+        truncated.push_str("\n... (output truncated)");
+        truncated
+    }
+}
+
+/// 在后台线程中执行工具调用
+pub fn spawn_tool(tool: Arc<dyn AgentTool>, call: &ToolCall, roots: Vec<PathBuf>, ctx: &egui::Context) -> Running<ToolOutcome> {
+    let arguments = call.arguments.clone();
+    Running::spawn(ctx, move |cancelled| {
+        let mut commands = Vec::new();
+        let result = tool.call_cancellable(&arguments, &roots, &mut commands, cancelled);
+        ToolOutcome { result, commands }
+    })
+}
+
+/// 确认窗口与会话记录中显示的参数：能解析时格式化为多行 JSON
+pub fn pretty_arguments(arguments: &str) -> String {
+    serde_json::from_str::<Value>(arguments)
+        .ok()
+        .and_then(|v| serde_json::to_string_pretty(&v).ok())
+        .unwrap_or_else(|| arguments.to_string())
+}
+
+// ----------------------------------------------------------------------------
+// 内置的 Agent 工具：读取文件、列出目录、执行命令。只能访问工作区中的路径
+// ----------------------------------------------------------------------------
+
+/// 注册内置工具；命令规则修改后再次调用，替换使用旧规则的 run_command
+pub fn register_builtin_tools(ctx: &egui::Context, policy: &ShellPolicy) {
+    crate::register_agent_tool(ctx, Arc::new(ReadFileTool));
+    crate::register_agent_tool(ctx, Arc::new(ListDirectoryTool));
+    crate::register_agent_tool(ctx, Arc::new(RunCommandTool { policy: policy.clone() }));
+}
+
+/// 读取字符串参数
+fn string_arg(arguments: &str, key: &str) -> Result<Option<String>, String> {
+    let value: Value = serde_json::from_str(arguments).map_err(|e| format!("Invalid arguments: {}", e))?;
+    Ok(value[key].as_str().map(String::from))
+}
+
+/// 把工具参数中的路径解析为工作区内的绝对路径，拒绝工作区以外的路径
+fn resolve_path(path: &str, roots: &[PathBuf]) -> Result<PathBuf, String> {
+    let root = roots.first().ok_or("No workspace folder is open")?;
+    let path = Path::new(path.trim());
+    let joined = if path.is_absolute() { path.to_path_buf() } else { root.join(path) };
+    let resolved = joined.canonicalize().map_err(|e| format!("{}: {}", joined.display(), e))?;
+    let inside = roots.iter().filter_map(|r| r.canonicalize().ok()).any(|r| resolved.starts_with(r));
+    if inside {
+        Ok(resolved)
+    } else {
+        Err(format!("{} is outside the workspace", joined.display()))
+    }
+}
+
+struct ReadFileTool;
+
+impl AgentTool for ReadFileTool {
+    fn name(&self) -> &str {
+        "read_file"
+    }
+
+    fn description(&self) -> &str {
+        "Read a text file from the workspace. Paths are relative to the workspace root."
+    }
+
+    fn parameters(&self) -> &str {
+        r#"{"type":"object","properties":{"path":{"type":"string","description":"File path"}},"required":["path"]}"#
+    }
+
+    fn call(&self, arguments: &str, roots: &[PathBuf], _control: &mut Vec<AppCommand>) -> Result<String, String> {
+        let path = resolve_path(&string_arg(arguments, "path")?.ok_or("Missing \"path\"")?, roots)?;
+        let content = std::fs::read_to_string(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        if content.chars().count() <= MAX_READ_CHARS {
+            return Ok(content);
+        }
+        let mut truncated: String = content.chars().take(MAX_READ_CHARS).collect();
+        truncated.push_str("\n... (file truncated)");
+        Ok(truncated)
+    }
+}
+
+struct ListDirectoryTool;
+
+impl AgentTool for ListDirectoryTool {
+    fn name(&self) -> &str {
+        "list_directory"
+    }
+
+    fn description(&self) -> &str {
+        "List the entries of a workspace folder. Folders end with '/'. Omit the path for the workspace root."
+    }
+
+    fn parameters(&self) -> &str {
+        r#"{"type":"object","properties":{"path":{"type":"string","description":"Folder path"}}}"#
+    }
+
+    fn call(&self, arguments: &str, roots: &[PathBuf], _control: &mut Vec<AppCommand>) -> Result<String, String> {
+        let path = resolve_path(&string_arg(arguments, "path")?.unwrap_or_else(|| ".".into()), roots)?;
+        let mut entries: Vec<(bool, String)> = std::fs::read_dir(&path)
+            .map_err(|e| format!("{}: {}", path.display(), e))?
+            .flatten()
+            .map(|entry| (entry.path().is_dir(), entry.file_name().to_string_lossy().to_string()))
+            .collect();
+        entries.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+        let total = entries.len();
+        let mut listing: Vec<String> = entries
+            .into_iter()
+            .take(MAX_LISTED_ENTRIES)
+            .map(|(is_dir, name)| if is_dir { format!("{}/", name) } else { name })
+            .collect();
+        if total > MAX_LISTED_ENTRIES {
+            listing.push(format!("... ({} more)", total - MAX_LISTED_ENTRIES));
+        }
+        Ok(if listing.is_empty() { "(empty folder)".to_string() } else { listing.join("\n") })
+    }
+}
+
+struct RunCommandTool {
+    policy: ShellPolicy,
+}
+
+impl AgentTool for RunCommandTool {
+    fn name(&self) -> &str {
+        "run_command"
+    }
+
+    fn description(&self) -> &str {
+        "Run a shell command in the workspace root and return its output. Only allowlisted commands are permitted; shell operators are rejected."
+    }
+
+    fn parameters(&self) -> &str {
+        r#"{"type":"object","properties":{"command":{"type":"string","description":"Command line"}},"required":["command"]}"#
+    }
+
+    fn has_side_effects(&self) -> bool {
+        true
+    }
+
+    fn call(&self, arguments: &str, roots: &[PathBuf], control: &mut Vec<AppCommand>) -> Result<String, String> {
+        self.call_cancellable(arguments, roots, control, &AtomicBool::new(false))
+    }
+
+    fn call_cancellable(&self, arguments: &str, roots: &[PathBuf], _control: &mut Vec<AppCommand>, cancelled: &AtomicBool) -> Result<String, String> {
+        let command = string_arg(arguments, "command")?.ok_or("Missing \"command\"")?;
+        self.policy.check(&command)?;
+        let request = ShellRequest {
+            command: command.trim().to_string(),
+            cwd: roots.first().cloned().ok_or("No workspace folder is open")?,
+        };
+        let outcome = run_shell(&request, cancelled);
+        Ok(format_log(&request, &outcome))
+    }
+}
@@ -0,0 +1,173 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use super::provider::ChatRequest;
+
+/// 按月累计的用量，与 agent_config.toml 分开保存，避免设置页保存配置时覆盖
+pub const USAGE_FILE: &str = "agent_usage.toml";
+
+// ----------------------------------------------------------------------------
+// Token 用量与费用
+// ----------------------------------------------------------------------------
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+pub struct TokenUsage {
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    /// 服务未返回用量时按文本长度估算
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub estimated: bool,
+    /// 按设置中的价格计算的费用（美元），价格未知时为 None
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost: Option<f64>,
+}
+
+impl TokenUsage {
+    pub fn total(&self) -> u64 {
+        self.prompt_tokens + self.completion_tokens
+    }
+
+    pub fn add(&mut self, other: &TokenUsage) {
+        self.prompt_tokens += other.prompt_tokens;
+        self.completion_tokens += other.completion_tokens;
+        self.estimated |= other.estimated;
+        if let Some(cost) = other.cost {
+            self.cost = Some(self.cost.unwrap_or(0.0) + cost);
+        }
+    }
+
+    /// 标签页标题栏等处的简短说明，例如 `~1.2k tokens · $0.0031`
+    pub fn summary(&self) -> String {
+        let mut text = format!("{}{} tokens", if self.estimated { "~" } else { "" }, format_count(self.total()));
+        if let Some(cost) = self.cost {
+            text.push_str(&format!(" · {}", format_cost(cost)));
+        }
+        text
+    }
+}
+
+/// 模型单价，单位为美元 / 百万 token
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ModelPrice {
+    /// 模型名或其前缀，多个前缀匹配时取最长的
+    pub model: String,
+    pub prompt: f64,
+    pub completion: f64,
+}
+
+pub fn default_prices() -> Vec<ModelPrice> {
+    vec![
+        ModelPrice { model: "gpt-4o".into(), prompt: 2.5, completion: 10.0 },
+        ModelPrice { model: "gpt-4o-mini".into(), prompt: 0.15, completion: 0.6 },
+    ]
+}
+
+/// 按价格表计算费用，没有匹配的价格时返回 None
+pub fn cost(prices: &[ModelPrice], model: &str, usage: &TokenUsage) -> Option<f64> {
+    let price = prices
+        .iter()
+        .filter(|p| !p.model.trim().is_empty() && model.starts_with(p.model.trim()))
+        .max_by_key(|p| p.model.trim().len())?;
+    Some((usage.prompt_tokens as f64 * price.prompt + usage.completion_tokens as f64 * price.completion) / 1_000_000.0)
+}
+
+/// 估算文本的 token 数：英文等约 4 个字符一个 token，中日韩字符大约各占一个
+pub fn estimate_tokens(text: &str) -> u64 {
+    let (wide, narrow) = text.chars().fold((0u64, 0u64), |(wide, narrow), c| {
+        if ('\u{2E80}'..='\u{9FFF}').contains(&c) || ('\u{AC00}'..='\u{D7AF}').contains(&c) || ('\u{F900}'..='\u{FAFF}').contains(&c) {
+            (wide + 1, narrow)
+        } else {
+            (wide, narrow + 1)
+        }
+    });
+    wide + narrow.div_ceil(4)
+}
+
+/// 估算请求的提示部分：消息内容、工具调用参数以及工具定义
+pub fn estimate_prompt(request: &ChatRequest) -> u64 {
+    let messages: u64 = request
+        .messages
+        .iter()
+        .map(|m| {
+            let calls: u64 = m["tool_calls"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .map(|c| estimate_tokens(c["function"]["arguments"].as_str().unwrap_or_default()))
+                .sum();
+            // 每条消息的角色等格式开销约几个 token
+            4 + estimate_tokens(m["content"].as_str().unwrap_or_default()) + calls
+        })
+        .sum();
+    let tools: u64 = request.tools.iter().map(|t| estimate_tokens(&t.to_string())).sum();
+    messages + tools
+}
+
+/// 从 OpenAI 格式的 `usage` 字段读取用量
+pub fn from_openai(usage: &Value) -> Option<TokenUsage> {
+    Some(TokenUsage {
+        prompt_tokens: usage["prompt_tokens"].as_u64()?,
+        completion_tokens: usage["completion_tokens"].as_u64().unwrap_or(0),
+        ..Default::default()
+    })
+}
+
+pub fn format_count(count: u64) -> String {
+    match count {
+        0..=999 => count.to_string(),
+        1_000..=999_999 => format!("{:.1}k", count as f64 / 1_000.0),
+        _ => format!("{:.2}M", count as f64 / 1_000_000.0),
+    }
+}
+
+pub fn format_cost(cost: f64) -> String {
+    if cost < 0.01 {
+        format!("${:.4}", cost)
+    } else {
+        format!("${:.2}", cost)
+    }
+}
+
+// ----------------------------------------------------------------------------
+// 按月累计
+// ----------------------------------------------------------------------------
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct UsageLog {
+    /// 键为 `YYYY-MM`
+    #[serde(default)]
+    pub months: BTreeMap<String, TokenUsage>,
+}
+
+impl UsageLog {
+    pub fn load() -> Self {
+        std::fs::read_to_string(USAGE_FILE)
+            .ok()
+            .and_then(|content| toml::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self) {
+        if let Ok(content) = toml::to_string_pretty(self) {
+            let _ = std::fs::write(USAGE_FILE, content);
+        }
+    }
+
+    fn month_key() -> String {
+        chrono::Local::now().format("%Y-%m").to_string()
+    }
+
+    pub fn this_month(&self) -> TokenUsage {
+        self.months.get(&Self::month_key()).copied().unwrap_or_default()
+    }
+
+    /// 把一次回复的用量记入本月，返回本月累计
+    pub fn record(usage: &TokenUsage) -> TokenUsage {
+        let mut log = Self::load();
+        let month = log.months.entry(Self::month_key()).or_default();
+        month.add(usage);
+        let total = *month;
+        log.save();
+        total
+    }
+}
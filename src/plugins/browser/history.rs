@@ -0,0 +1,130 @@
+use std::sync::Arc;
+use parking_lot::Mutex;
+use eframe::egui;
+use crate::{TabInstance, AppCommand};
+use super::library::BrowserLibrary;
+
+// ----------------------------------------------------------------------------
+// 书签与历史记录页：搜索、打开与删除
+// ----------------------------------------------------------------------------
+
+#[derive(Clone)]
+pub struct HistoryTab {
+    library: Arc<Mutex<BrowserLibrary>>,
+    search: String,
+}
+
+impl std::fmt::Debug for HistoryTab {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HistoryTab").field("search", &self.search).finish()
+    }
+}
+
+impl HistoryTab {
+    pub fn new(library: Arc<Mutex<BrowserLibrary>>) -> Self {
+        Self { library, search: String::new() }
+    }
+}
+
+impl TabInstance for HistoryTab {
+    fn title(&self) -> egui::WidgetText {
+        "History".into()
+    }
+
+    fn icon(&self) -> Option<crate::TabIcon> {
+        Some(crate::TabIcon::Emoji("🕘".into()))
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui, control: &mut Vec<AppCommand>) {
+        let mut library = self.library.lock();
+        let mut clear_history = false;
+
+        ui.horizontal(|ui| {
+            ui.label("🔍");
+            ui.add(egui::TextEdit::singleline(&mut self.search).hint_text("Search bookmarks and history").desired_width(320.0));
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                if ui.add_enabled(!library.history().is_empty(), egui::Button::new("🗑 Clear History")).clicked() {
+                    clear_history = true;
+                }
+            });
+        });
+        ui.weak(format!("Stored in {}", library.location().display()));
+        ui.separator();
+
+        let query = self.search.trim().to_lowercase();
+        let mut remove_bookmark = None;
+        let mut remove_history = None;
+
+        egui::ScrollArea::vertical().auto_shrink([false, false]).show(ui, |ui| {
+            let bookmarks: Vec<_> = library
+                .bookmarks()
+                .iter()
+                .enumerate()
+                .filter(|(_, b)| b.title.to_lowercase().contains(&query) || b.url.to_lowercase().contains(&query))
+                .collect();
+            egui::CollapsingHeader::new(format!("★ Bookmarks ({})", bookmarks.len()))
+                .id_salt("browser_bookmarks")
+                .default_open(true)
+                .show(ui, |ui| {
+                    if bookmarks.is_empty() {
+                        ui.weak("No bookmarks. Use ☆ next to the address bar to add one.");
+                    }
+                    for (index, bookmark) in bookmarks {
+                        ui.horizontal(|ui| {
+                            if ui.small_button("🗑").on_hover_text("Remove bookmark").clicked() {
+                                remove_bookmark = Some(index);
+                            }
+                            if ui.link(&bookmark.title).clicked() {
+                                control.push(AppCommand::OpenInBrowser(bookmark.url.clone()));
+                            }
+                            ui.weak(&bookmark.url);
+                        });
+                    }
+                });
+
+            let entries = library.search_history(&query);
+            egui::CollapsingHeader::new(format!("🕘 History ({})", entries.len()))
+                .id_salt("browser_history")
+                .default_open(true)
+                .show(ui, |ui| {
+                    if entries.is_empty() {
+                        ui.weak("No history.");
+                    }
+                    // 按访问日期分组，最新的在前
+                    let mut date = "";
+                    for (index, entry) in entries {
+                        if entry.date() != date {
+                            date = entry.date();
+                            ui.add_space(4.0);
+                            ui.strong(date);
+                        }
+                        ui.horizontal(|ui| {
+                            if ui.small_button("🗑").on_hover_text("Remove from history").clicked() {
+                                remove_history = Some(index);
+                            }
+                            ui.weak(entry.time());
+                            let title = if entry.title.is_empty() { &entry.url } else { &entry.title };
+                            if ui.link(title).clicked() {
+                                control.push(AppCommand::OpenInBrowser(entry.url.clone()));
+                            }
+                            ui.weak(&entry.url);
+                        });
+                    }
+                });
+        });
+
+        if let Some(index) = remove_bookmark {
+            library.remove_bookmark(index);
+        }
+        if let Some(index) = remove_history {
+            library.remove_history(index);
+        }
+        if clear_history {
+            library.clear_history();
+        }
+    }
+
+    fn box_clone(&self) -> Box<dyn TabInstance> {
+        Box::new(self.clone())
+    }
+}
@@ -0,0 +1,299 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use eframe::egui;
+
+/// 全局配置文件，同时保存全局的书签与历史记录
+const CONFIG_FILE: &str = "browser_config.toml";
+/// 按工作区存储时，书签与历史记录保存在工作区根目录下的该文件中
+const WORKSPACE_FILE: &str = ".verbium_browser.toml";
+/// 历史记录的最大条数，超出时丢弃最早的记录
+const MAX_HISTORY: usize = 1000;
+
+// ----------------------------------------------------------------------------
+// 书签与历史记录
+// ----------------------------------------------------------------------------
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Storage {
+    /// 所有工作区共用
+    #[default]
+    Global,
+    /// 每个工作区各自保存，未打开工作区时使用全局数据
+    Workspace,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Bookmark {
+    pub title: String,
+    pub url: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct HistoryEntry {
+    pub url: String,
+    #[serde(default)]
+    pub title: String,
+    /// RFC 3339 格式的访问时间
+    pub visited_at: String,
+}
+
+impl HistoryEntry {
+    /// 访问日期，历史记录按此分组
+    pub fn date(&self) -> &str {
+        self.visited_at.get(..10).unwrap_or(&self.visited_at)
+    }
+
+    /// 访问时刻（时:分）
+    pub fn time(&self) -> &str {
+        self.visited_at.get(11..16).unwrap_or_default()
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Library {
+    #[serde(default)]
+    pub bookmarks: Vec<Bookmark>,
+    /// 按访问时间排列，最新的在最后
+    #[serde(default)]
+    pub history: Vec<HistoryEntry>,
+}
+
+impl Library {
+    fn load(path: &Path) -> Self {
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|content| toml::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self, path: &Path) {
+        if let Ok(content) = toml::to_string_pretty(self) {
+            let _ = std::fs::write(path, content);
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct BrowserConfig {
+    #[serde(default)]
+    storage: Storage,
+    #[serde(default)]
+    library: Library,
+}
+
+impl BrowserConfig {
+    fn load() -> Self {
+        std::fs::read_to_string(CONFIG_FILE)
+            .ok()
+            .and_then(|content| toml::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self) {
+        if let Ok(content) = toml::to_string_pretty(self) {
+            let _ = std::fs::write(CONFIG_FILE, content);
+        }
+    }
+}
+
+/// 地址栏的补全建议
+#[derive(Clone, Debug)]
+pub struct Suggestion {
+    pub url: String,
+    pub title: String,
+    pub bookmarked: bool,
+}
+
+// ----------------------------------------------------------------------------
+// 书签与历史记录的存储：由插件创建，所有浏览器标签页与历史记录页共享
+// ----------------------------------------------------------------------------
+
+pub struct BrowserLibrary {
+    config: BrowserConfig,
+    /// 当前工作区的根目录及其中保存的数据
+    root: Option<PathBuf>,
+    workspace: Library,
+}
+
+impl BrowserLibrary {
+    pub fn load() -> Self {
+        Self {
+            config: BrowserConfig::load(),
+            root: None,
+            workspace: Library::default(),
+        }
+    }
+
+    /// 工作区变化时重新读取该工作区的数据
+    pub fn sync(&mut self, ctx: &egui::Context) {
+        let root = crate::workspace::current(ctx).primary_root().map(Path::to_path_buf);
+        if root != self.root {
+            self.workspace = root.as_ref().map(|r| Library::load(&r.join(WORKSPACE_FILE))).unwrap_or_default();
+            self.root = root;
+        }
+    }
+
+    pub fn storage(&self) -> Storage {
+        self.config.storage
+    }
+
+    pub fn set_storage(&mut self, storage: Storage) {
+        self.config.storage = storage;
+        self.config.save();
+    }
+
+    /// 当前使用的数据所在的文件
+    pub fn location(&self) -> PathBuf {
+        match self.workspace_root() {
+            Some(root) => root.join(WORKSPACE_FILE),
+            None => PathBuf::from(CONFIG_FILE),
+        }
+    }
+
+    fn workspace_root(&self) -> Option<&Path> {
+        match self.config.storage {
+            Storage::Workspace => self.root.as_deref(),
+            Storage::Global => None,
+        }
+    }
+
+    fn library(&self) -> &Library {
+        match self.workspace_root() {
+            Some(_) => &self.workspace,
+            None => &self.config.library,
+        }
+    }
+
+    /// 修改当前使用的数据并立即保存
+    fn modify(&mut self, f: impl FnOnce(&mut Library)) {
+        match self.workspace_root().map(|r| r.join(WORKSPACE_FILE)) {
+            Some(path) => {
+                f(&mut self.workspace);
+                self.workspace.save(&path);
+            }
+            None => {
+                f(&mut self.config.library);
+                self.config.save();
+            }
+        }
+    }
+
+    pub fn bookmarks(&self) -> &[Bookmark] {
+        &self.library().bookmarks
+    }
+
+    pub fn history(&self) -> &[HistoryEntry] {
+        &self.library().history
+    }
+
+    pub fn is_bookmarked(&self, url: &str) -> bool {
+        self.bookmarks().iter().any(|b| b.url == url)
+    }
+
+    /// 添加或移除书签，返回之后是否已加入书签
+    pub fn toggle_bookmark(&mut self, url: &str, title: &str) -> bool {
+        let bookmarked = !self.is_bookmarked(url);
+        self.modify(|library| {
+            if bookmarked {
+                let title = if title.trim().is_empty() { url } else { title.trim() };
+                library.bookmarks.push(Bookmark { title: title.to_string(), url: url.to_string() });
+            } else {
+                library.bookmarks.retain(|b| b.url != url);
+            }
+        });
+        bookmarked
+    }
+
+    pub fn remove_bookmark(&mut self, index: usize) {
+        self.modify(|library| {
+            if index < library.bookmarks.len() {
+                library.bookmarks.remove(index);
+            }
+        });
+    }
+
+    /// 页面加载完成时记录访问；连续访问同一地址只更新最后一条
+    pub fn record_visit(&mut self, url: &str, title: &str) {
+        if url.is_empty() || url.starts_with("about:") || url.starts_with("data:") {
+            return;
+        }
+        let visited_at = chrono::Local::now().to_rfc3339();
+        self.modify(|library| {
+            match library.history.last_mut() {
+                Some(last) if last.url == url => {
+                    last.visited_at = visited_at;
+                    if !title.is_empty() {
+                        last.title = title.to_string();
+                    }
+                }
+                _ => library.history.push(HistoryEntry { url: url.to_string(), title: title.to_string(), visited_at }),
+            }
+            let excess = library.history.len().saturating_sub(MAX_HISTORY);
+            library.history.drain(..excess);
+        });
+    }
+
+    /// 页面标题在加载完成后才变化时，补上最近一次访问的标题
+    pub fn set_title(&mut self, url: &str, title: &str) {
+        let unchanged = self.history().iter().rev().find(|e| e.url == url).is_none_or(|e| e.title == title);
+        if title.is_empty() || unchanged {
+            return;
+        }
+        self.modify(|library| {
+            if let Some(entry) = library.history.iter_mut().rev().find(|e| e.url == url) {
+                entry.title = title.to_string();
+            }
+        });
+    }
+
+    pub fn remove_history(&mut self, index: usize) {
+        self.modify(|library| {
+            if index < library.history.len() {
+                library.history.remove(index);
+            }
+        });
+    }
+
+    pub fn clear_history(&mut self) {
+        self.modify(|library| library.history.clear());
+    }
+
+    /// 标题或地址包含 `query` 的历史记录及其下标，最新的在前
+    pub fn search_history(&self, query: &str) -> Vec<(usize, &HistoryEntry)> {
+        let query = query.to_lowercase();
+        self.history()
+            .iter()
+            .enumerate()
+            .rev()
+            .filter(|(_, e)| matches(&query, &e.title, &e.url))
+            .collect()
+    }
+
+    /// 地址栏的补全建议：先列出匹配的书签，再按最近访问列出历史记录，同一地址只出现一次
+    pub fn suggestions(&self, query: &str, limit: usize) -> Vec<Suggestion> {
+        let query = query.trim().to_lowercase();
+        if query.is_empty() {
+            return Vec::new();
+        }
+        let mut suggestions: Vec<Suggestion> = self
+            .bookmarks()
+            .iter()
+            .filter(|b| matches(&query, &b.title, &b.url))
+            .map(|b| Suggestion { url: b.url.clone(), title: b.title.clone(), bookmarked: true })
+            .take(limit)
+            .collect();
+        for entry in self.history().iter().rev() {
+            if suggestions.len() >= limit {
+                break;
+            }
+            if matches(&query, &entry.title, &entry.url) && !suggestions.iter().any(|s| s.url == entry.url) {
+                suggestions.push(Suggestion { url: entry.url.clone(), title: entry.title.clone(), bookmarked: false });
+            }
+        }
+        suggestions
+    }
+}
+
+fn matches(query: &str, title: &str, url: &str) -> bool {
+    title.to_lowercase().contains(query) || url.to_lowercase().contains(query)
+}
@@ -1,9 +1,12 @@
 use std::sync::Arc;
 use std::sync::mpsc::{channel, Receiver, Sender};
 use parking_lot::Mutex;
-use egui::Ui;
-use crate::{Plugin, AppCommand, Tab};
+use egui::{Context, Ui};
+use crate::{Plugin, AppCommand, Tab, TabInstance};
 
+pub mod history;
+pub mod library;
+pub mod navigation;
 pub mod tab;
 pub mod webview;
 pub mod widgets;
@@ -11,6 +14,8 @@ pub mod widgets;
 pub struct BrowserPlugin {
     new_tab_tx: Arc<Sender<String>>,
     new_tab_rx: Receiver<String>,
+    /// 书签与历史记录，所有浏览器标签页共享
+    library: Arc<Mutex<library::BrowserLibrary>>,
 }
 
 impl BrowserPlugin {
@@ -19,8 +24,13 @@ impl BrowserPlugin {
         Self {
             new_tab_tx: Arc::new(tx),
             new_tab_rx: rx,
+            library: Arc::new(Mutex::new(library::BrowserLibrary::load())),
         }
     }
+
+    fn new_tab(&self, url: String) -> tab::BrowserTab {
+        tab::BrowserTab::new(url, self.new_tab_tx.clone(), self.library.clone())
+    }
 }
 
 impl Plugin for BrowserPlugin {
@@ -31,18 +41,56 @@ impl Plugin for BrowserPlugin {
     fn update(&mut self, control: &mut Vec<AppCommand>) {
         // 在每帧开始时处理新标签页请求，确保指令在同一帧被 process_commands 处理
         while let Ok(url) = self.new_tab_rx.try_recv() {
-            let tab = tab::BrowserTab::new(url, self.new_tab_tx.clone());
+            let tab = self.new_tab(url);
             control.push(AppCommand::OpenTab(Tab::new(Box::new(tab))));
         }
     }
 
+    fn create_browser(&mut self, url: &str) -> Option<Box<dyn TabInstance>> {
+        Some(Box::new(self.new_tab(url.to_string())))
+    }
+
     fn on_tab_menu(&mut self, ui: &mut Ui, control: &mut Vec<AppCommand>) {
         if ui.button("🌐 New Browser").clicked() {
-            let tab = tab::BrowserTab::new("https://www.google.com".to_string(), self.new_tab_tx.clone());
+            let tab = self.new_tab("https://www.google.com".to_string());
+            control.push(AppCommand::OpenTab(Tab::new(Box::new(tab))));
+            ui.close_menu();
+        }
+        if ui.button("🕘 Bookmarks & History").clicked() {
+            let tab = history::HistoryTab::new(self.library.clone());
             control.push(AppCommand::OpenTab(Tab::new(Box::new(tab))));
             ui.close_menu();
         }
     }
+
+    fn on_global_ui(&mut self, ctx: &Context, _control: &mut Vec<AppCommand>) {
+        // 按工作区存储时，切换工作区后改用新工作区的书签与历史记录
+        self.library.lock().sync(ctx);
+    }
+
+    fn backup_files(&self) -> Vec<std::path::PathBuf> {
+        vec![self.library.lock().location()]
+    }
+
+    fn on_settings_ui(&mut self, ui: &mut Ui) {
+        ui.vertical(|ui| {
+            ui.heading("Browser Settings");
+            ui.add_space(4.0);
+            ui.group(|ui| {
+                ui.label("Bookmarks & History");
+                let mut library = self.library.lock();
+                let mut storage = library.storage();
+                ui.horizontal(|ui| {
+                    ui.radio_value(&mut storage, library::Storage::Global, "Shared by all workspaces");
+                    ui.radio_value(&mut storage, library::Storage::Workspace, "Per workspace");
+                });
+                if storage != library.storage() {
+                    library.set_storage(storage);
+                }
+                ui.weak(format!("Stored in {}", library.location().display()));
+            });
+        });
+    }
 }
 
 pub fn create() -> BrowserPlugin {
@@ -0,0 +1,122 @@
+use eframe::egui;
+
+/// 图标的边长（像素）
+const FAVICON_SIZE: usize = 32;
+
+/// 在页面加载完成后把网站图标缩放到 32x32，以 `favicon:宽:高:RGBA 字节` 的形式发回。
+/// 跨域图标会使画布被污染而无法读取，这时不显示图标
+pub const FAVICON_SCRIPT: &str = r#"
+window.addEventListener('load', function () {
+    if (window.top !== window) return;
+    var link = document.querySelector('link[rel~="icon"]');
+    var img = new Image();
+    img.onload = function () {
+        try {
+            var canvas = document.createElement('canvas');
+            canvas.width = 32;
+            canvas.height = 32;
+            var context = canvas.getContext('2d');
+            context.drawImage(img, 0, 0, 32, 32);
+            var data = context.getImageData(0, 0, 32, 32).data;
+            window.ipc.postMessage('favicon:32:32:' + Array.prototype.join.call(data, ','));
+        } catch (e) {}
+    };
+    img.src = link ? link.href : '/favicon.ico';
+});
+"#;
+
+// ----------------------------------------------------------------------------
+// 导航状态：由 webview 的回调更新，标签页据此显示标题、图标与前进后退按钮
+// ----------------------------------------------------------------------------
+
+#[derive(Default)]
+pub struct Navigation {
+    /// 访问过的地址，`index` 为当前页面
+    history: Vec<String>,
+    index: usize,
+    /// 由后退 / 前进发起、尚未开始加载的目标位置
+    pending: Option<usize>,
+    pub title: String,
+    pub loading: bool,
+    /// 当前地址有变化，地址栏未在编辑时应同步
+    pub url_changed: bool,
+    /// 页面发回、尚未上传为纹理的图标
+    favicon_pixels: Option<egui::ColorImage>,
+    pub favicon: Option<egui::TextureHandle>,
+}
+
+impl Navigation {
+    pub fn current(&self) -> Option<&str> {
+        self.history.get(self.index).map(String::as_str)
+    }
+
+    pub fn can_go_back(&self) -> bool {
+        self.index > 0
+    }
+
+    pub fn can_go_forward(&self) -> bool {
+        self.index + 1 < self.history.len()
+    }
+
+    /// 后退（`delta` 为 -1）或前进（1）的目标地址，开始加载后才移动当前位置
+    pub fn go(&mut self, delta: isize) -> Option<String> {
+        let target = self.index.checked_add_signed(delta)?;
+        let url = self.history.get(target)?.clone();
+        self.pending = Some(target);
+        Some(url)
+    }
+
+    /// 页面开始加载：后退 / 前进时移动到目标位置，其他导航丢弃前进记录并追加
+    pub fn started(&mut self, url: String) {
+        self.loading = true;
+        self.url_changed = true;
+        if let Some(target) = self.pending.take() {
+            self.index = target;
+        } else if self.current() != Some(url.as_str()) {
+            self.history.truncate(self.index + 1);
+            self.history.push(url);
+            self.index = self.history.len() - 1;
+        }
+        self.favicon = None;
+        self.favicon_pixels = None;
+    }
+
+    /// 页面加载完成，`url` 为重定向之后的最终地址
+    pub fn finished(&mut self, url: String) {
+        self.loading = false;
+        if let Some(current) = self.history.get_mut(self.index) {
+            if *current != url {
+                *current = url;
+                self.url_changed = true;
+            }
+        }
+    }
+
+    /// 处理页面通过 `window.ipc.postMessage` 发回的消息
+    pub fn handle_message(&mut self, message: &str) {
+        if let Some(image) = message.strip_prefix("favicon:").and_then(parse_favicon) {
+            self.favicon_pixels = Some(image);
+        }
+    }
+
+    /// 把新收到的图标上传为纹理
+    pub fn load_favicon(&mut self, ctx: &egui::Context) {
+        if let Some(image) = self.favicon_pixels.take() {
+            self.favicon = Some(ctx.load_texture("browser_favicon", image, egui::TextureOptions::LINEAR));
+        }
+    }
+}
+
+fn parse_favicon(message: &str) -> Option<egui::ColorImage> {
+    let mut parts = message.splitn(3, ':');
+    let width: usize = parts.next()?.parse().ok()?;
+    let height: usize = parts.next()?.parse().ok()?;
+    if width > FAVICON_SIZE || height > FAVICON_SIZE {
+        return None;
+    }
+    let rgba = parts.next()?.split(',').map(|b| b.parse::<u8>().ok()).collect::<Option<Vec<u8>>>()?;
+    if rgba.len() != width * height * 4 || rgba.chunks(4).all(|p| p[3] == 0) {
+        return None;
+    }
+    Some(egui::ColorImage::from_rgba_unmultiplied([width, height], &rgba))
+}
@@ -2,9 +2,19 @@ use std::sync::Arc;
 use std::sync::mpsc::Sender;
 use parking_lot::Mutex;
 use eframe::egui;
-use crate::{TabInstance, AppCommand};
+use crate::{TabInstance, AppCommand, Tab};
+use super::history::HistoryTab;
+use super::library::{BrowserLibrary, Suggestion};
+use super::navigation::{Navigation, FAVICON_SCRIPT};
 use super::widgets::NavButton;
-use super::webview::{create_webview, steal_focus_from_webview};
+use super::webview::{create_webview, steal_focus_from_webview, WebViewHandlers};
+
+/// 标签栏中标题的最大字符数
+const MAX_TITLE_CHARS: usize = 30;
+/// 书签栏中每个书签显示的最大字符数
+const MAX_BOOKMARK_CHARS: usize = 20;
+/// 地址栏补全建议的最大条数
+const MAX_SUGGESTIONS: usize = 8;
 
 /// Wrapper to make WebView Send + Sync
 pub struct SafeWebView(pub wry::WebView);
@@ -18,53 +28,156 @@ pub struct BrowserTab {
     last_rect: Arc<Mutex<egui::Rect>>,
     last_ppp: Arc<Mutex<f32>>,
     new_tab_tx: Arc<Sender<String>>,
+    /// 标题、图标与前进后退记录，由 webview 的回调更新
+    nav: Arc<Mutex<Navigation>>,
+    /// 书签与历史记录，所有浏览器标签页共享
+    library: Arc<Mutex<BrowserLibrary>>,
+    /// 编辑地址栏时的补全建议及键盘选中的一项
+    suggestions: Vec<Suggestion>,
+    suggestion_index: Option<usize>,
 }
 
 impl std::fmt::Debug for BrowserTab {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("BrowserTab").field("url", &self.url).finish()
+        f.debug_struct("BrowserTab").field("url", &self.url).field("title", &self.nav.lock().title).finish()
     }
 }
 
 impl BrowserTab {
-    pub fn new(url: String, new_tab_tx: Arc<Sender<String>>) -> Self {
+    pub fn new(url: String, new_tab_tx: Arc<Sender<String>>, library: Arc<Mutex<BrowserLibrary>>) -> Self {
         Self {
             url,
             webview: Arc::new(Mutex::new(None)),
             last_rect: Arc::new(Mutex::new(egui::Rect::NOTHING)),
             last_ppp: Arc::new(Mutex::new(0.0)),
             new_tab_tx,
+            nav: Arc::new(Mutex::new(Navigation::default())),
+            library,
+            suggestions: Vec::new(),
+            suggestion_index: None,
+        }
+    }
+
+    /// 加载地址栏中输入或从书签、补全建议中选择的地址，未写协议时补上 https
+    fn load(&mut self, url: &str) {
+        self.url = if url.contains("://") { url.to_string() } else { format!("https://{}", url) };
+        self.suggestions.clear();
+        self.suggestion_index = None;
+        if let Some(safe_webview) = self.webview.lock().as_ref() {
+            let _ = safe_webview.0.load_url(&self.url);
+        }
+    }
+
+    /// 后退（-1）或前进（1），按记录的地址重新加载
+    fn go(&mut self, delta: isize) {
+        let Some(url) = self.nav.lock().go(delta) else { return };
+        if let Some(safe_webview) = self.webview.lock().as_ref() {
+            let _ = safe_webview.0.load_url(&url);
+        }
+    }
+
+    fn handlers(&self, ctx: &egui::Context) -> WebViewHandlers {
+        let tx = self.new_tab_tx.clone();
+        let new_tab_ctx = ctx.clone();
+        let (title_nav, title_library, title_ctx) = (self.nav.clone(), self.library.clone(), ctx.clone());
+        let (load_nav, load_library, load_ctx) = (self.nav.clone(), self.library.clone(), ctx.clone());
+        let (message_nav, message_ctx) = (self.nav.clone(), ctx.clone());
+        WebViewHandlers {
+            new_window: Box::new(move |url: String, _| {
+                let _ = tx.send(url);
+                new_tab_ctx.request_repaint(); // 立即通知主线程创建标签页
+                wry::NewWindowResponse::Deny
+            }),
+            title_changed: Box::new(move |title| {
+                let url = {
+                    let mut nav = title_nav.lock();
+                    nav.title = title.clone();
+                    nav.current().map(str::to_string)
+                };
+                if let Some(url) = url {
+                    title_library.lock().set_title(&url, &title);
+                }
+                title_ctx.request_repaint();
+            }),
+            page_load: Box::new(move |event, url| {
+                match event {
+                    wry::PageLoadEvent::Started => load_nav.lock().started(url),
+                    wry::PageLoadEvent::Finished => {
+                        let title = {
+                            let mut nav = load_nav.lock();
+                            nav.finished(url.clone());
+                            nav.title.clone()
+                        };
+                        load_library.lock().record_visit(&url, &title);
+                    }
+                }
+                load_ctx.request_repaint();
+            }),
+            message: Box::new(move |message| {
+                message_nav.lock().handle_message(&message);
+                message_ctx.request_repaint();
+            }),
+            script: FAVICON_SCRIPT,
         }
     }
 }
 
 impl TabInstance for BrowserTab {
     fn title(&self) -> egui::WidgetText {
-        "Browser".into()
+        let nav = self.nav.lock();
+        let title = nav.title.trim();
+        if title.is_empty() {
+            return "Browser".into();
+        }
+        truncate(title, MAX_TITLE_CHARS).into()
+    }
+
+    fn icon(&self) -> Option<crate::TabIcon> {
+        match &self.nav.lock().favicon {
+            Some(texture) => Some(crate::TabIcon::Texture(texture.clone())),
+            None => Some(crate::TabIcon::Emoji("🌐".into())),
+        }
     }
 
-    fn ui(&mut self, ui: &mut egui::Ui, _control: &mut Vec<AppCommand>) {
+    fn ui(&mut self, ui: &mut egui::Ui, control: &mut Vec<AppCommand>) {
         let ctx = ui.ctx().clone();
-        
+        let (can_go_back, can_go_forward, loading, current_url) = {
+            let mut nav = self.nav.lock();
+            nav.load_favicon(&ctx);
+            (nav.can_go_back(), nav.can_go_forward(), nav.loading, nav.current().map(str::to_string))
+        };
+        let bookmarked = current_url.as_deref().is_some_and(|url| self.library.lock().is_bookmarked(url));
+        let address_bar_id = ui.make_persistent_id("browser_address_bar");
+        let mut address_bar_focused = false;
+
         // 1. Top Bar
         ui.horizontal(|ui| {
-            if ui.add(NavButton::new("⬅")).clicked() {
-                if let Some(safe_webview) = self.webview.lock().as_ref() {
-                    let _ = safe_webview.0.evaluate_script("history.back()");
-                }
+            if ui.add_enabled(can_go_back, NavButton::new("⬅")).clicked() {
+                self.go(-1);
             }
-            if ui.add(NavButton::new("➡")).clicked() {
-                if let Some(safe_webview) = self.webview.lock().as_ref() {
-                    let _ = safe_webview.0.evaluate_script("history.forward()");
-                }
+            if ui.add_enabled(can_go_forward, NavButton::new("➡")).clicked() {
+                self.go(1);
             }
             if ui.add(NavButton::new("🔄")).clicked() {
                 if let Some(safe_webview) = self.webview.lock().as_ref() {
                     let _ = safe_webview.0.reload();
                 }
             }
+            if ui.add_enabled(current_url.is_some(), NavButton::new(if bookmarked { "★" } else { "☆" })).clicked() {
+                if let Some(url) = &current_url {
+                    let title = self.nav.lock().title.clone();
+                    self.library.lock().toggle_bookmark(url, &title);
+                }
+            }
+            if ui.add(NavButton::new("🕘")).clicked() {
+                let tab = HistoryTab::new(self.library.clone());
+                control.push(AppCommand::OpenTab(Tab::new(Box::new(tab))));
+            }
             
             ui.add_space(8.0);
+            if loading {
+                ui.spinner();
+            }
 
             let address_bar_frame = egui::Frame::group(ui.style())
                 .fill(ui.visuals().extreme_bg_color)
@@ -73,11 +186,40 @@ impl TabInstance for BrowserTab {
                 .inner_margin(egui::Margin::symmetric(10.0, 5.0));
 
             address_bar_frame.show(ui, |ui| {
+                // 上下方向键在补全建议间移动，Esc 关闭建议
+                if ui.memory(|m| m.has_focus(address_bar_id)) && !self.suggestions.is_empty() {
+                    let len = self.suggestions.len();
+                    ui.input_mut(|i| {
+                        if i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown) {
+                            self.suggestion_index = Some(self.suggestion_index.map_or(0, |s| (s + 1) % len));
+                        }
+                        if i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp) {
+                            self.suggestion_index = Some(self.suggestion_index.map_or(len - 1, |s| (s + len - 1) % len));
+                        }
+                        if i.consume_key(egui::Modifiers::NONE, egui::Key::Escape) {
+                            self.suggestions.clear();
+                            self.suggestion_index = None;
+                        }
+                    });
+                }
+
                 let text_edit = egui::TextEdit::singleline(&mut self.url)
+                    .id(address_bar_id)
                     .frame(false)
                     .desired_width(ui.available_width());
                     
                 let response = ui.add(text_edit);
+                address_bar_focused = response.has_focus();
+
+                // 页面跳转后地址栏显示新地址，正在编辑时不覆盖
+                let mut nav = self.nav.lock();
+                if nav.url_changed && !response.has_focus() {
+                    nav.url_changed = false;
+                    if let Some(url) = nav.current() {
+                        self.url = url.to_string();
+                    }
+                }
+                drop(nav);
                 
                 if response.clicked() || response.has_focus() {
                     steal_focus_from_webview();
@@ -91,19 +233,69 @@ impl TabInstance for BrowserTab {
                     }
                 }
 
+                if response.changed() {
+                    self.suggestions = self.library.lock().suggestions(&self.url, MAX_SUGGESTIONS);
+                    self.suggestion_index = None;
+                }
+
                 if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
-                    if let Some(safe_webview) = self.webview.lock().as_ref() {
-                        let url = if self.url.contains("://") {
-                            self.url.clone()
-                        } else {
-                            format!("https://{}", self.url)
-                        };
-                        let _ = safe_webview.0.load_url(&url);
-                    }
+                    let url = match self.suggestion_index.and_then(|i| self.suggestions.get(i)) {
+                        Some(suggestion) => suggestion.url.clone(),
+                        None => self.url.clone(),
+                    };
+                    self.load(&url);
                 }
             });
         });
 
+        // 补全建议直接排在地址栏下方而不是弹出层：webview 是原生子窗口，会盖住弹出的内容
+        if !self.suggestions.is_empty() {
+            let mut selected = None;
+            let list = ui.vertical(|ui| {
+                for (i, suggestion) in self.suggestions.iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        let icon = if suggestion.bookmarked { "★" } else { "🕘" };
+                        let title = if suggestion.title.is_empty() { &suggestion.url } else { &suggestion.title };
+                        let label = format!("{} {}", icon, truncate(title, MAX_TITLE_CHARS * 2));
+                        if ui.selectable_label(self.suggestion_index == Some(i), label).clicked() {
+                            selected = Some(suggestion.url.clone());
+                        }
+                        ui.weak(&suggestion.url);
+                    });
+                }
+            });
+            if let Some(url) = selected {
+                self.load(&url);
+            } else if !address_bar_focused && !ui.rect_contains_pointer(list.response.rect) {
+                // 点击建议时地址栏会先失去焦点，鼠标仍在建议列表上时保留列表
+                self.suggestions.clear();
+                self.suggestion_index = None;
+            }
+            ui.separator();
+        }
+
+        // 书签栏：单击在当前标签页打开，中键在新标签页打开
+        let bookmarks = self.library.lock().bookmarks().to_vec();
+        if !bookmarks.is_empty() {
+            let mut open = None;
+            egui::ScrollArea::horizontal().id_salt("browser_bookmarks_bar").show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    for bookmark in &bookmarks {
+                        let response = ui.add(egui::Button::new(format!("★ {}", truncate(&bookmark.title, MAX_BOOKMARK_CHARS))).frame(false));
+                        if response.clicked() {
+                            open = Some(bookmark.url.clone());
+                        }
+                        if response.middle_clicked() {
+                            let _ = self.new_tab_tx.send(bookmark.url.clone());
+                        }
+                    }
+                });
+            });
+            if let Some(url) = open {
+                self.load(&url);
+            }
+        }
+
         // 2. WebView Area
         egui::CentralPanel::default()
             .frame(egui::Frame::none())
@@ -113,16 +305,9 @@ impl TabInstance for BrowserTab {
 
                 let mut webview_lock = self.webview.lock();
                 if webview_lock.is_none() {
-                    let tx = self.new_tab_tx.clone();
-                    let ctx_clone = ctx.clone();
-                    
-                    let handler = Box::new(move |url: String, _| {
-                        let _ = tx.send(url);
-                        ctx_clone.request_repaint(); // 立即通知主线程创建标签页
-                        wry::NewWindowResponse::Deny
-                    });
-
-                    if let Some(webview) = create_webview(&self.url, Some(handler)) {
+                    if let Some(webview) = create_webview(&self.url, self.handlers(&ctx)) {
+                        // 网页内容跟随全局界面缩放
+                        let _ = webview.zoom(ctx.zoom_factor() as f64);
                         *webview_lock = Some(SafeWebView(webview));
                     }
                 }
@@ -151,8 +336,6 @@ impl TabInstance for BrowserTab {
                             ).into(),
                         });
                     }
-                    
-                    let _ = safe_webview.0.set_visible(true);
                 }
 
                 ui.centered_and_justified(|ui| {
@@ -164,7 +347,45 @@ impl TabInstance for BrowserTab {
         });
     }
 
+    fn on_zoom_changed(&mut self, zoom: f32) {
+        if let Some(safe_webview) = self.webview.lock().as_ref() {
+            let _ = safe_webview.0.zoom(zoom as f64);
+        }
+        // 强制下一帧重新计算 webview 的物理像素边界
+        *self.last_rect.lock() = egui::Rect::NOTHING;
+    }
+
+    fn on_visibility_changed(&mut self, visible: bool) {
+        // webview 是覆盖在主窗口上的原生子窗口，标签页不可见时必须隐藏，否则会盖住其他内容
+        if let Some(safe_webview) = self.webview.lock().as_ref() {
+            let _ = safe_webview.0.set_visible(visible);
+        }
+        if visible {
+            *self.last_rect.lock() = egui::Rect::NOTHING;
+        }
+    }
+
+    fn on_close(&mut self) {
+        // 销毁 webview；重新打开标签页时再按当前地址创建
+        self.webview.lock().take();
+        *self.last_rect.lock() = egui::Rect::NOTHING;
+    }
+
     fn box_clone(&self) -> Box<dyn TabInstance> {
         Box::new(self.clone())
     }
+
+    fn duplicate(&self) -> Box<dyn TabInstance> {
+        // 副本不与原标签页共用 webview，在当前地址打开新的页面
+        let url = self.nav.lock().current().map_or_else(|| self.url.clone(), str::to_string);
+        Box::new(Self::new(url, self.new_tab_tx.clone(), self.library.clone()))
+    }
+}
+
+/// 截断过长的文本，末尾加省略号
+fn truncate(text: &str, max_chars: usize) -> String {
+    match text.char_indices().nth(max_chars) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text.to_string(),
+    }
 }
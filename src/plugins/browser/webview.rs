@@ -1,5 +1,5 @@
 use std::num::NonZeroIsize;
-use wry::{WebView, NewWindowFeatures, NewWindowResponse};
+use wry::{WebView, NewWindowFeatures, NewWindowResponse, PageLoadEvent};
 use raw_window_handle::{HasWindowHandle, WindowHandle, RawWindowHandle, Win32WindowHandle, HandleError};
 
 #[cfg(target_os = "windows")]
@@ -25,23 +25,34 @@ impl HasWindowHandle for WindowWrapper {
     }
 }
 
-pub fn create_webview(
-    url: &str, 
-    new_window_handler: Option<Box<dyn Fn(String, NewWindowFeatures) -> NewWindowResponse + Send + Sync + 'static>>
-) -> Option<WebView> {
+/// webview 的事件回调，均在主线程中调用
+pub struct WebViewHandlers {
+    /// 页面请求打开新窗口
+    pub new_window: Box<dyn Fn(String, NewWindowFeatures) -> NewWindowResponse + Send + Sync + 'static>,
+    pub title_changed: Box<dyn Fn(String) + 'static>,
+    pub page_load: Box<dyn Fn(PageLoadEvent, String) + 'static>,
+    /// 页面通过 `window.ipc.postMessage` 发来的消息
+    pub message: Box<dyn Fn(String) + 'static>,
+    /// 每个页面加载时执行的脚本
+    pub script: &'static str,
+}
+
+pub fn create_webview(url: &str, handlers: WebViewHandlers) -> Option<WebView> {
     #[cfg(target_os = "windows")]
     {
         let hwnd = find_my_hwnd()?;
         let wrapper = WindowWrapper(hwnd);
-        
-        let mut builder = wry::WebViewBuilder::new()
-            .with_url(url);
-
-        if let Some(handler) = new_window_handler {
-            builder = builder.with_new_window_req_handler(handler);
-        }
+        let WebViewHandlers { new_window, title_changed, page_load, message, script } = handlers;
 
-        builder.build_as_child(&wrapper).ok()
+        wry::WebViewBuilder::new()
+            .with_url(url)
+            .with_initialization_script(script)
+            .with_new_window_req_handler(new_window)
+            .with_document_title_changed_handler(title_changed)
+            .with_on_page_load_handler(page_load)
+            .with_ipc_handler(move |request| message(request.into_body()))
+            .build_as_child(&wrapper)
+            .ok()
     }
     #[cfg(not(target_os = "windows"))]
     {
@@ -0,0 +1,167 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use egui::{Ui, WidgetText};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use crate::{AppCommand, TabInstance};
+
+// ----------------------------------------------------------------------------
+// 书签：按文件记录的行号。保存在工作目录的 code_editor_bookmarks.toml 中，
+// 因此每个工作区各有一份
+// ----------------------------------------------------------------------------
+
+pub const BOOKMARKS_FILE: &str = "code_editor_bookmarks.toml";
+
+/// 所有文件的书签，由插件、编辑器标签页与书签列表共享
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Bookmarks {
+    /// 文件路径 -> 书签所在的行（0 起始）
+    #[serde(default)]
+    files: BTreeMap<PathBuf, BTreeSet<usize>>,
+    /// 内存中的书签与文件中的不一致
+    #[serde(skip)]
+    dirty: bool,
+}
+
+pub type SharedBookmarks = Arc<Mutex<Bookmarks>>;
+
+impl Bookmarks {
+    pub fn load() -> Self {
+        std::fs::read_to_string(BOOKMARKS_FILE)
+            .ok()
+            .and_then(|content| toml::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&mut self) {
+        if !self.dirty {
+            return;
+        }
+        if let Ok(content) = toml::to_string_pretty(self) {
+            if std::fs::write(BOOKMARKS_FILE, content).is_ok() {
+                self.dirty = false;
+            }
+        }
+    }
+
+    pub fn lines(&self, path: &Path) -> BTreeSet<usize> {
+        self.files.get(path).cloned().unwrap_or_default()
+    }
+
+    /// 替换一个文件的书签（没有书签时移除该文件）；只修改内存，需要时调用 [`Bookmarks::save`]
+    pub fn set(&mut self, path: &Path, lines: &BTreeSet<usize>) {
+        if self.files.get(path) == Some(lines) || (lines.is_empty() && !self.files.contains_key(path)) {
+            return;
+        }
+        if lines.is_empty() {
+            self.files.remove(path);
+        } else {
+            self.files.insert(path.to_path_buf(), lines.clone());
+        }
+        self.dirty = true;
+    }
+
+    fn clear(&mut self) {
+        self.dirty |= !self.files.is_empty();
+        self.files.clear();
+    }
+}
+
+/// 书签列表：按文件分组列出所有书签，点击跳转
+#[derive(Debug, Clone)]
+pub struct BookmarksTab {
+    bookmarks: SharedBookmarks,
+    /// 书签所在行的文字，按文件缓存；书签变化或点击刷新后重新读取
+    previews: HashMap<PathBuf, BTreeMap<usize, String>>,
+}
+
+impl BookmarksTab {
+    pub fn new(bookmarks: SharedBookmarks) -> Self {
+        Self { bookmarks, previews: HashMap::new() }
+    }
+
+    fn previews_for(&mut self, path: &Path, lines: &BTreeSet<usize>) -> &BTreeMap<usize, String> {
+        let stale = self.previews.get(path).is_none_or(|cached| !cached.keys().eq(lines.iter()));
+        if stale {
+            let content = super::encoding::read(path).map(|(content, _)| content).unwrap_or_default();
+            let text: Vec<&str> = content.lines().collect();
+            let previews = lines
+                .iter()
+                .map(|&line| (line, text.get(line).map_or(String::new(), |l| l.trim().chars().take(120).collect())))
+                .collect();
+            self.previews.insert(path.to_path_buf(), previews);
+        }
+        &self.previews[path]
+    }
+}
+
+impl TabInstance for BookmarksTab {
+    fn title(&self) -> WidgetText {
+        "Bookmarks".into()
+    }
+
+    fn icon(&self) -> Option<crate::TabIcon> {
+        Some(crate::TabIcon::Emoji("🔖".into()))
+    }
+
+    fn ui(&mut self, ui: &mut Ui, control: &mut Vec<AppCommand>) {
+        let files = self.bookmarks.lock().files.clone();
+        let total: usize = files.values().map(BTreeSet::len).sum();
+        ui.horizontal(|ui| {
+            ui.label(format!("{} bookmark(s) in {} file(s)", total, files.len()));
+            if ui.button("⟳ Refresh").on_hover_text("Reload the line previews").clicked() {
+                self.previews.clear();
+            }
+            if ui.add_enabled(total > 0, egui::Button::new("🗑 Clear All")).clicked() {
+                let mut bookmarks = self.bookmarks.lock();
+                bookmarks.clear();
+                bookmarks.save();
+            }
+        });
+        ui.separator();
+        if files.is_empty() {
+            ui.weak("No bookmarks. Press Ctrl + F2 in the editor to bookmark the current line.");
+            return;
+        }
+
+        let mut removed = None;
+        egui::ScrollArea::vertical().auto_shrink([false; 2]).show(ui, |ui| {
+            for (path, lines) in &files {
+                let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+                let previews = self.previews_for(path, lines).clone();
+                egui::CollapsingHeader::new(format!("📄 {}  ({})", name, lines.len()))
+                    .id_salt(path)
+                    .default_open(true)
+                    .show(ui, |ui| {
+                        ui.weak(path.parent().map(|p| p.display().to_string()).unwrap_or_default());
+                        for &line in lines {
+                            let preview = previews.get(&line).map(String::as_str).unwrap_or_default();
+                            let response = ui.selectable_label(false, format!("{:>5}  {}", line + 1, preview));
+                            if response.clicked() {
+                                control.push(AppCommand::OpenFileAt { path: path.clone(), line: line + 1, column: 1 });
+                            }
+                            response.context_menu(|ui| {
+                                if ui.button("Remove Bookmark").clicked() {
+                                    removed = Some((path.clone(), line));
+                                    ui.close_menu();
+                                }
+                            });
+                        }
+                    });
+            }
+        });
+        // 打开的标签页在下一帧从共享的书签中同步
+        if let Some((path, line)) = removed {
+            let mut bookmarks = self.bookmarks.lock();
+            let mut lines = bookmarks.lines(&path);
+            lines.remove(&line);
+            bookmarks.set(&path, &lines);
+            bookmarks.save();
+        }
+    }
+
+    fn box_clone(&self) -> Box<dyn TabInstance> {
+        Box::new(self.clone())
+    }
+}
@@ -0,0 +1,212 @@
+use crate::{CompletionItem, CompletionKind, CompletionProvider, CompletionRequest};
+
+// ----------------------------------------------------------------------------
+// 补全弹窗：内置的文档单词与代码片段来源，以及前缀匹配与排序
+// ----------------------------------------------------------------------------
+
+/// 自动弹出补全所需的最少前缀长度；Ctrl + Space 不受限制
+pub const MIN_AUTO_PREFIX: usize = 2;
+/// 弹窗中最多保留的建议数量
+const MAX_ITEMS: usize = 100;
+/// 文档单词的最短长度，更短的单词补全意义不大
+const MIN_WORD_LEN: usize = 3;
+
+pub fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// 光标所在单词的起始字符索引
+pub fn word_start(code: &str, cursor: usize) -> usize {
+    let before: Vec<char> = code.chars().take(cursor).collect();
+    let len = before.iter().rev().take_while(|c| is_word_char(**c)).count();
+    cursor - len
+}
+
+fn byte_of(code: &str, char_idx: usize) -> usize {
+    code.char_indices().nth(char_idx).map(|(i, _)| i).unwrap_or(code.len())
+}
+
+/// 打开的补全弹窗
+#[derive(Debug, Clone)]
+pub struct CompletionPopup {
+    /// 被替换的前缀的起始字符索引
+    pub start: usize,
+    pub prefix: String,
+    pub items: Vec<CompletionItem>,
+    pub selected: usize,
+    /// 由 Ctrl + Space 打开，前缀为空时也保持打开
+    pub manual: bool,
+    /// 选中项变化后滚动到可见区域
+    pub scroll_pending: bool,
+}
+
+impl CompletionPopup {
+    pub fn new(start: usize, manual: bool) -> Self {
+        Self { start, prefix: String::new(), items: Vec::new(), selected: 0, manual, scroll_pending: false }
+    }
+
+    pub fn step(&mut self, forward: bool) {
+        let count = self.items.len();
+        if count > 0 {
+            self.selected = if forward { (self.selected + 1) % count } else { (self.selected + count - 1) % count };
+            self.scroll_pending = true;
+        }
+    }
+}
+
+/// 依次查询各来源，按前缀过滤、去重并排序。越靠前的来源在同名时优先
+pub fn collect(providers: &[&dyn CompletionProvider], request: &CompletionRequest) -> Vec<CompletionItem> {
+    let mut ranked: Vec<(u8, CompletionItem)> = Vec::new();
+    for provider in providers {
+        for item in provider.complete(request) {
+            if item.insert_text == request.prefix || ranked.iter().any(|(_, i)| i.label == item.label) {
+                continue;
+            }
+            if let Some(rank) = rank(request.prefix, &item.label) {
+                ranked.push((rank, item));
+            }
+        }
+    }
+    ranked.sort_by_key(|(rank, _)| *rank);
+    ranked.into_iter().take(MAX_ITEMS).map(|(_, item)| item).collect()
+}
+
+/// 匹配程度：0 区分大小写的前缀，1 不区分大小写的前缀，2 按顺序包含前缀的全部字符
+fn rank(prefix: &str, label: &str) -> Option<u8> {
+    if label.starts_with(prefix) {
+        return Some(0);
+    }
+    let lower = label.to_lowercase();
+    let prefix = prefix.to_lowercase();
+    if lower.starts_with(&prefix) {
+        return Some(1);
+    }
+    let mut chars = lower.chars();
+    prefix.chars().all(|p| chars.any(|c| c == p)).then_some(2)
+}
+
+/// 在光标处展开插入文本：后续行沿用当前行的缩进，`$0` 处为光标。
+/// 返回 (插入的文本, 光标相对插入位置的字符偏移)
+pub fn expand(insert_text: &str, code: &str, start: usize) -> (String, usize) {
+    let byte = byte_of(code, start);
+    let line_start = code[..byte].rfind('\n').map_or(0, |i| i + 1);
+    let line = &code[line_start..];
+    let indent = &line[..line.len() - line.trim_start_matches([' ', '\t']).len()];
+    let text = insert_text.replace('\n', &format!("\n{}", indent));
+    match text.find("$0") {
+        Some(marker) => (text.replacen("$0", "", 1), text[..marker].chars().count()),
+        None => {
+            let len = text.chars().count();
+            (text, len)
+        }
+    }
+}
+
+// ----------------------------------------------------------------------------
+// 内置来源
+// ----------------------------------------------------------------------------
+
+/// 当前文档中出现过的单词
+pub struct BufferWords;
+
+impl CompletionProvider for BufferWords {
+    fn name(&self) -> &str { "buffer_words" }
+
+    fn complete(&self, request: &CompletionRequest) -> Vec<CompletionItem> {
+        let Some(first) = request.prefix.chars().next() else { return Vec::new() };
+        let first = first.to_lowercase().next().unwrap_or(first);
+        // 光标处正在输入的单词本身不算
+        let cursor_word_start = byte_of(request.text, request.cursor - request.prefix.chars().count());
+        let mut words: Vec<&str> = Vec::new();
+        let mut seen = std::collections::HashSet::new();
+        let mut start = None;
+        for (i, c) in request.text.char_indices().chain(std::iter::once((request.text.len(), ' '))) {
+            match (is_word_char(c), start) {
+                (true, None) => start = Some(i),
+                (false, Some(s)) => {
+                    start = None;
+                    let word = &request.text[s..i];
+                    if s == cursor_word_start || word.chars().count() < MIN_WORD_LEN {
+                        continue;
+                    }
+                    let leading = word.chars().next().unwrap_or(' ');
+                    if leading.is_ascii_digit() || leading.to_lowercase().next() != Some(first) {
+                        continue;
+                    }
+                    if seen.insert(word) {
+                        words.push(word);
+                    }
+                }
+                _ => {}
+            }
+        }
+        words
+            .into_iter()
+            .map(|word| CompletionItem {
+                label: word.to_string(),
+                insert_text: word.to_string(),
+                detail: None,
+                kind: CompletionKind::Text,
+            })
+            .collect()
+    }
+}
+
+/// 常用语法结构的代码片段
+pub struct Snippets;
+
+impl CompletionProvider for Snippets {
+    fn name(&self) -> &str { "snippets" }
+
+    fn complete(&self, request: &CompletionRequest) -> Vec<CompletionItem> {
+        snippets(request.language)
+            .iter()
+            .map(|(label, detail, body)| CompletionItem {
+                label: label.to_string(),
+                insert_text: body.to_string(),
+                detail: Some(detail.to_string()),
+                kind: CompletionKind::Snippet,
+            })
+            .collect()
+    }
+}
+
+/// (触发词, 说明, 片段)
+fn snippets(language: &str) -> &'static [(&'static str, &'static str, &'static str)] {
+    match language {
+        "rs" => &[
+            ("fn", "fn name() { … }", "fn $0() {\n    \n}"),
+            ("impl", "impl Type { … }", "impl $0 {\n    \n}"),
+            ("struct", "struct Name { … }", "struct $0 {\n    \n}"),
+            ("enum", "enum Name { … }", "enum $0 {\n    \n}"),
+            ("match", "match value { … }", "match $0 {\n    _ => {}\n}"),
+            ("iflet", "if let Some(x) = … { … }", "if let Some($0) =  {\n    \n}"),
+            ("for", "for x in … { … }", "for $0 in  {\n    \n}"),
+            ("test", "#[test] fn …", "#[test]\nfn $0() {\n    \n}"),
+            ("println", "println!(\"…\")", "println!(\"$0\");"),
+        ],
+        "py" => &[
+            ("def", "def name(): …", "def $0():\n    pass"),
+            ("class", "class Name: …", "class $0:\n    def __init__(self):\n        pass"),
+            ("for", "for x in …: …", "for $0 in :\n    pass"),
+            ("with", "with … as f: …", "with $0 as f:\n    pass"),
+            ("ifmain", "if __name__ == \"__main__\": …", "if __name__ == \"__main__\":\n    $0"),
+        ],
+        "js" => &[
+            ("function", "function name() { … }", "function $0() {\n    \n}"),
+            ("arrow", "(…) => { … }", "($0) => {\n    \n}"),
+            ("for", "for (let i = 0; …) { … }", "for (let i = 0; i < $0; i++) {\n    \n}"),
+            ("log", "console.log(…)", "console.log($0);"),
+        ],
+        "c" | "cpp" => &[
+            ("main", "int main() { … }", "int main(int argc, char **argv) {\n    $0\n    return 0;\n}"),
+            ("for", "for (int i = 0; …) { … }", "for (int i = 0; i < $0; i++) {\n    \n}"),
+            ("include", "#include <…>", "#include <$0>"),
+        ],
+        "md" => &[
+            ("link", "[text](url)", "[$0]()"),
+            ("code", "```…```", "```$0\n\n```"),
+        ],
+        _ => &[],
+    }
+}
@@ -0,0 +1,179 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+const CONFIG_FILE: &str = "code_editor_config.toml";
+
+// ----------------------------------------------------------------------------
+// 编辑器设置
+// ----------------------------------------------------------------------------
+
+fn default_true() -> bool { true }
+
+/// 默认的格式化命令；`{file}` 为文件路径，prettier 与 clang-format 据此判断语法
+fn default_formatters() -> BTreeMap<String, String> {
+    let prettier = "prettier --stdin-filepath {file}";
+    [
+        ("rs", "rustfmt --edition 2021"),
+        ("py", "black --quiet -"),
+        ("js", prettier),
+        ("json", prettier),
+        ("html", prettier),
+        ("css", prettier),
+        ("md", prettier),
+        ("c", "clang-format --assume-filename={file}"),
+        ("cpp", "clang-format --assume-filename={file}"),
+    ]
+    .into_iter()
+    .map(|(language, command)| (language.to_string(), command.to_string()))
+    .collect()
+}
+
+/// 一种语言的缩进方式
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Indentation {
+    /// 用制表符缩进，否则用空格
+    pub tabs: bool,
+    /// 每级缩进的空格数
+    pub width: usize,
+}
+
+impl Default for Indentation {
+    fn default() -> Self {
+        Self { tabs: false, width: 4 }
+    }
+}
+
+impl Indentation {
+    /// 一级缩进插入的文本
+    pub fn unit(&self) -> String {
+        if self.tabs { "\t".to_string() } else { " ".repeat(self.width) }
+    }
+
+    pub fn label(&self) -> String {
+        if self.tabs { format!("Tab Size: {}", self.width) } else { format!("Spaces: {}", self.width) }
+    }
+}
+
+/// Web 相关语言默认两个空格，其余语言使用 `default_indentation`
+fn default_language_indentation() -> BTreeMap<String, Indentation> {
+    ["js", "json", "html", "css"]
+        .into_iter()
+        .map(|language| (language.to_string(), Indentation { tabs: false, width: 2 }))
+        .collect()
+}
+
+/// 保存在 code_editor_config.toml 中的编辑器设置；定时自动保存的间隔在宿主的性能设置中
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct EditorConfig {
+    /// 编辑器失去焦点（切到其它面板或其它程序）时自动保存
+    #[serde(default)]
+    pub autosave_on_focus_loss: bool,
+    /// 先写入同目录下的临时文件再替换原文件，写入中途崩溃不会截断原文件
+    #[serde(default = "default_true")]
+    pub safe_write: bool,
+    /// 保存前把原文件复制为 `<文件名>.bak`
+    #[serde(default)]
+    pub keep_backup: bool,
+    /// 打开 Markdown 文件时在右侧显示预览
+    #[serde(default = "default_true")]
+    pub markdown_preview: bool,
+    /// 保存时去掉行尾空白（Markdown 中行尾的两个空格表示换行，不处理）
+    #[serde(default)]
+    pub trim_trailing_whitespace: bool,
+    /// 保存时确保文件以换行结尾
+    #[serde(default)]
+    pub insert_final_newline: bool,
+    /// 用淡色的 · 与 → 标出空格和制表符
+    #[serde(default)]
+    pub render_whitespace: bool,
+    /// 没有单独配置的语言使用的缩进
+    #[serde(default)]
+    pub default_indentation: Indentation,
+    /// 各语言（键为语言 ID）的缩进
+    #[serde(default = "default_language_indentation")]
+    pub indentation: BTreeMap<String, Indentation>,
+    /// 按文件中已有的缩进推断缩进方式，文件还没有缩进时才使用上面的设置
+    #[serde(default = "default_true")]
+    pub detect_indentation: bool,
+    /// 读取文件所在目录及上级目录中的 .editorconfig，优先于以上设置
+    #[serde(default = "default_true")]
+    pub use_editorconfig: bool,
+    /// 保存前用该语言的格式化命令格式化
+    #[serde(default)]
+    pub format_on_save: bool,
+    /// 各语言（键为语言 ID）的格式化命令，从标准输入读取代码并把结果写到标准输出
+    #[serde(default = "default_formatters")]
+    pub formatters: BTreeMap<String, String>,
+}
+
+impl Default for EditorConfig {
+    fn default() -> Self {
+        Self {
+            autosave_on_focus_loss: false,
+            safe_write: true,
+            keep_backup: false,
+            markdown_preview: true,
+            trim_trailing_whitespace: false,
+            insert_final_newline: false,
+            render_whitespace: false,
+            default_indentation: Indentation::default(),
+            indentation: default_language_indentation(),
+            detect_indentation: true,
+            use_editorconfig: true,
+            format_on_save: false,
+            formatters: default_formatters(),
+        }
+    }
+}
+
+impl EditorConfig {
+    pub fn load() -> Self {
+        std::fs::read_to_string(CONFIG_FILE)
+            .ok()
+            .and_then(|content| toml::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        if let Ok(content) = toml::to_string_pretty(self) {
+            let _ = std::fs::write(CONFIG_FILE, content);
+        }
+    }
+
+    /// 按设置写入文件：可选地保留备份，并通过临时文件 + 重命名替换原文件
+    pub fn write(&self, path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+        // 符号链接写入其指向的文件，而不是把链接替换成普通文件
+        let target = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        let original = std::fs::metadata(&target).ok();
+        if self.keep_backup && original.is_some() {
+            std::fs::copy(&target, with_suffix(&target, ".bak"))?;
+        }
+        if !self.safe_write {
+            return std::fs::write(&target, bytes);
+        }
+
+        let file_name = target.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        let temp = target.with_file_name(format!(".{}.verbium-tmp", file_name));
+        let result = (|| {
+            let mut file = std::fs::File::create(&temp)?;
+            file.write_all(bytes)?;
+            file.sync_all()?;
+            if let Some(original) = &original {
+                std::fs::set_permissions(&temp, original.permissions())?;
+            }
+            std::fs::rename(&temp, &target)
+        })();
+        if result.is_err() {
+            let _ = std::fs::remove_file(&temp);
+        }
+        result
+    }
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(suffix);
+    path.with_file_name(name)
+}
@@ -0,0 +1,142 @@
+use std::ops::Range;
+use egui::{Galley, Pos2};
+use super::history::Selection;
+use super::{char_of_position, line_of_char};
+
+// ----------------------------------------------------------------------------
+// 多光标：主光标仍由 TextEdit 管理，其余光标保存在标签页中；存在多个光标时
+// 编辑与移动按键由这里统一应用到全部光标。选区均为 (主光标, 另一端) 的字符索引
+// ----------------------------------------------------------------------------
+
+fn byte_of(code: &str, char_idx: usize) -> usize {
+    code.char_indices().nth(char_idx).map(|(i, _)| i).unwrap_or(code.len())
+}
+
+pub fn sorted(selection: Selection) -> Range<usize> {
+    selection.0.min(selection.1)..selection.0.max(selection.1)
+}
+
+/// 对每个光标执行同一编辑，`edit` 返回编辑后的光标位置。
+/// 从后往前处理，已处理的（位于后面的）光标按文本长度的变化平移
+pub fn edit_each(code: &mut String, cursors: &mut [Selection], mut edit: impl FnMut(&mut String, Range<usize>, usize) -> usize) {
+    let mut order: Vec<usize> = (0..cursors.len()).collect();
+    order.sort_by_key(|&i| std::cmp::Reverse(sorted(cursors[i]).start));
+    let mut done: Vec<usize> = Vec::new();
+    for i in order {
+        let before = code.chars().count() as isize;
+        let cursor = edit(code, sorted(cursors[i]), i);
+        let delta = code.chars().count() as isize - before;
+        for &j in &done {
+            let (a, b) = cursors[j];
+            cursors[j] = ((a as isize + delta).max(0) as usize, (b as isize + delta).max(0) as usize);
+        }
+        cursors[i] = (cursor, cursor);
+        done.push(i);
+    }
+}
+
+/// 用 `text` 替换选区，返回插入文本之后的位置
+pub fn replace(code: &mut String, range: Range<usize>, text: &str) -> usize {
+    let bytes = byte_of(code, range.start)..byte_of(code, range.end);
+    code.replace_range(bytes, text);
+    range.start + text.chars().count()
+}
+
+/// Backspace：删除选区或光标前的一个字符
+pub fn backspace(code: &mut String, range: Range<usize>) -> usize {
+    if range.is_empty() && range.start > 0 {
+        replace(code, range.start - 1..range.start, "")
+    } else {
+        replace(code, range, "")
+    }
+}
+
+/// Delete：删除选区或光标后的一个字符
+pub fn delete(code: &mut String, range: Range<usize>) -> usize {
+    let len = code.chars().count();
+    if range.is_empty() && range.end < len {
+        replace(code, range.start..range.end + 1, "")
+    } else {
+        replace(code, range, "")
+    }
+}
+
+/// 方向键、Home、End 按逻辑行移动光标；`extend` 为按住 Shift 扩展选区
+pub fn move_cursor(code: &str, selection: Selection, key: egui::Key, extend: bool) -> Selection {
+    let (primary, secondary) = selection;
+    let range = sorted(selection);
+    let line = line_of_char(code, primary);
+    let line_start = char_of_position(code, line, 0);
+    let column = primary - line_start;
+    let target = match key {
+        // 有选区时不扩展，光标落在选区的对应端
+        egui::Key::ArrowLeft if !extend && !range.is_empty() => range.start,
+        egui::Key::ArrowRight if !extend && !range.is_empty() => range.end,
+        egui::Key::ArrowLeft => primary.saturating_sub(1),
+        egui::Key::ArrowRight => (primary + 1).min(code.chars().count()),
+        egui::Key::ArrowUp if line == 0 => 0,
+        egui::Key::ArrowUp => char_of_position(code, line - 1, column),
+        egui::Key::ArrowDown => char_of_position(code, line + 1, column),
+        egui::Key::Home => line_start,
+        egui::Key::End => char_of_position(code, line, usize::MAX),
+        _ => primary,
+    };
+    if extend { (target, secondary) } else { (target, target) }
+}
+
+/// 去掉重叠的光标；保留较早加入（索引较小）的一个
+pub fn merge(cursors: &mut Vec<Selection>) {
+    let mut kept: Vec<Selection> = Vec::with_capacity(cursors.len());
+    for &cursor in cursors.iter() {
+        let range = sorted(cursor);
+        let overlaps = kept.iter().any(|other| {
+            let other = sorted(*other);
+            range == other || (range.start < other.end && other.start < range.end)
+        });
+        if !overlaps {
+            kept.push(cursor);
+        }
+    }
+    *cursors = kept;
+}
+
+/// Ctrl + D：主光标没有选区时选中光标处的单词；否则在全部光标之后（到末尾后从头）
+/// 查找所选文本的下一处出现。返回新的主选区，没有找到时返回 None
+pub fn next_occurrence(code: &str, primary: Selection, cursors: &[Selection]) -> Option<Selection> {
+    let range = sorted(primary);
+    if range.is_empty() {
+        let is_word = |c: char| c.is_alphanumeric() || c == '_';
+        let chars: Vec<char> = code.chars().collect();
+        let start = range.start - chars[..range.start].iter().rev().take_while(|c| is_word(**c)).count();
+        let end = range.start + chars[range.start..].iter().take_while(|c| is_word(**c)).count();
+        return (start < end).then_some((end, start));
+    }
+    let needle: String = code.chars().skip(range.start).take(range.len()).collect();
+    let from = cursors.iter().chain(std::iter::once(&primary)).map(|c| sorted(*c).end).max().unwrap_or(range.end);
+    let from_byte = byte_of(code, from);
+    let found = code[from_byte..]
+        .find(&needle)
+        .map(|i| from_byte + i)
+        .or_else(|| code.find(&needle))?;
+    let start = code[..found].chars().count();
+    let selection = (start + range.len(), start);
+    let taken = cursors.iter().chain(std::iter::once(&primary)).any(|c| sorted(*c) == sorted(selection));
+    (!taken).then_some(selection)
+}
+
+/// Alt + 拖动的列选择：起止点之间每个显示行上相同横坐标范围内的文字，
+/// 坐标相对排版原点。最后一项（拖动终点所在行）作为主光标
+pub fn column_selection(galley: &Galley, from: Pos2, to: Pos2) -> Vec<Selection> {
+    let (top, bottom) = (from.y.min(to.y), from.y.max(to.y));
+    let mut selections = Vec::new();
+    for row in galley.rows.iter().filter(|row| row.rect.bottom() > top && row.rect.top() <= bottom) {
+        let y = row.rect.center().y;
+        let anchor = galley.cursor_from_pos(egui::vec2(from.x, y)).ccursor.index;
+        let head = galley.cursor_from_pos(egui::vec2(to.x, y)).ccursor.index;
+        selections.push((head, anchor));
+    }
+    if from.y > to.y {
+        selections.reverse();
+    }
+    selections
+}
@@ -0,0 +1,227 @@
+use std::path::Path;
+use super::config::Indentation;
+
+// ----------------------------------------------------------------------------
+// .editorconfig：从文件所在目录向上查找，直到 `root = true` 的文件为止；
+// 离文件越近的配置、同一文件中越靠后的段落优先。只读取编辑器用到的几项属性
+// ----------------------------------------------------------------------------
+
+/// 对某个文件生效的 .editorconfig 属性，未设置的为 None
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Properties {
+    /// indent_style：true 为制表符
+    pub tabs: Option<bool>,
+    /// indent_size（为 `tab` 时取 tab_width）
+    pub indent_size: Option<usize>,
+    pub tab_width: Option<usize>,
+    pub trim_trailing_whitespace: Option<bool>,
+    pub insert_final_newline: Option<bool>,
+}
+
+impl Properties {
+    /// 设置了缩进方式或宽度时，与 `fallback` 合并得到的缩进
+    pub fn indentation(&self, fallback: Indentation) -> Option<Indentation> {
+        let width = self.indent_size.or(self.tab_width);
+        if self.tabs.is_none() && width.is_none() {
+            return None;
+        }
+        Some(Indentation {
+            tabs: self.tabs.unwrap_or(fallback.tabs),
+            width: width.unwrap_or(fallback.width),
+        })
+    }
+
+    /// 只填入尚未设置的属性，因此应从最近的配置开始合并
+    fn merge_missing(&mut self, other: Properties) {
+        self.tabs = self.tabs.or(other.tabs);
+        self.indent_size = self.indent_size.or(other.indent_size);
+        self.tab_width = self.tab_width.or(other.tab_width);
+        self.trim_trailing_whitespace = self.trim_trailing_whitespace.or(other.trim_trailing_whitespace);
+        self.insert_final_newline = self.insert_final_newline.or(other.insert_final_newline);
+    }
+}
+
+/// 查找并合并对 `path` 生效的 .editorconfig 属性
+pub fn properties(path: &Path) -> Properties {
+    let mut result = Properties::default();
+    let path = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    for dir in path.ancestors().skip(1) {
+        let Ok(content) = std::fs::read_to_string(dir.join(".editorconfig")) else { continue };
+        let Ok(relative) = path.strip_prefix(dir) else { continue };
+        let relative = relative.to_string_lossy().replace('\\', "/");
+        let (properties, root) = parse(&content, &relative);
+        result.merge_missing(properties);
+        if root {
+            break;
+        }
+    }
+    // indent_size = tab 表示与 tab_width 相同
+    if result.indent_size.is_none() && result.tabs == Some(true) {
+        result.indent_size = result.tab_width;
+    }
+    result
+}
+
+/// 解析一个 .editorconfig 文件中对 `relative`（相对该文件所在目录）生效的属性，
+/// 以及文件是否声明了 `root = true`
+fn parse(content: &str, relative: &str) -> (Properties, bool) {
+    let mut properties = Properties::default();
+    let mut root = false;
+    // None 表示第一个段落之前（只允许 root）
+    let mut matches: Option<bool> = None;
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+        if let Some(section) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            matches = Some(section_matches(section, relative));
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else { continue };
+        let key = key.trim().to_ascii_lowercase();
+        let value = value.trim().to_ascii_lowercase();
+        match matches {
+            None if key == "root" => root = value == "true",
+            Some(true) => match key.as_str() {
+                "indent_style" => properties.tabs = match value.as_str() {
+                    "tab" => Some(true),
+                    "space" => Some(false),
+                    _ => None,
+                },
+                // 段落中后出现的属性覆盖先出现的，`unset` 清除
+                "indent_size" if value == "tab" => properties.indent_size = None,
+                "indent_size" => properties.indent_size = value.parse().ok().filter(|w| (1..=16).contains(w)),
+                "tab_width" => properties.tab_width = value.parse().ok().filter(|w| (1..=16).contains(w)),
+                "trim_trailing_whitespace" => properties.trim_trailing_whitespace = parse_bool(&value),
+                "insert_final_newline" => properties.insert_final_newline = parse_bool(&value),
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+    (properties, root)
+}
+
+fn parse_bool(value: &str) -> Option<bool> {
+    match value {
+        "true" => Some(true),
+        "false" => Some(false),
+        _ => None,
+    }
+}
+
+/// 段落名是 glob：不含 `/` 时匹配任意目录下的文件名，否则相对 .editorconfig 所在目录匹配
+fn section_matches(section: &str, relative: &str) -> bool {
+    let pattern = match section.strip_prefix('/') {
+        Some(anchored) => anchored.to_string(),
+        None if section.contains('/') => section.to_string(),
+        None => format!("**/{}", section),
+    };
+    let text: Vec<char> = relative.chars().collect();
+    expand_braces(&pattern).iter().any(|alternative| {
+        let pattern: Vec<char> = alternative.chars().collect();
+        // `**/` 也可以匹配零层目录
+        glob_match(&pattern, &text) || alternative.strip_prefix("**/").is_some_and(|rest| {
+            glob_match(&rest.chars().collect::<Vec<_>>(), &text)
+        })
+    })
+}
+
+/// 展开 `{a,b}` 与 `{1..3}`，返回所有候选模式
+fn expand_braces(pattern: &str) -> Vec<String> {
+    let Some(open) = pattern.find('{') else { return vec![pattern.to_string()] };
+    let mut depth = 0;
+    let mut close = None;
+    for (i, c) in pattern[open..].char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    close = Some(open + i);
+                    break;
+                }
+            }
+            _ => {}
+        }
+    }
+    let Some(close) = close else { return vec![pattern.to_string()] };
+    let (prefix, body, suffix) = (&pattern[..open], &pattern[open + 1..close], &pattern[close + 1..]);
+
+    let mut options = Vec::new();
+    if let Some((from, to)) = body.split_once("..").and_then(|(a, b)| Some((a.parse::<i64>().ok()?, b.parse::<i64>().ok()?))) {
+        // 数字范围过大时不展开
+        if (to - from).abs() <= 1000 {
+            let range: Vec<i64> = if from <= to { (from..=to).collect() } else { (to..=from).rev().collect() };
+            options.extend(range.iter().map(|n| n.to_string()));
+        }
+    } else {
+        let mut depth = 0;
+        let mut start = 0;
+        for (i, c) in body.char_indices() {
+            match c {
+                '{' => depth += 1,
+                '}' => depth -= 1,
+                ',' if depth == 0 => {
+                    options.push(body[start..i].to_string());
+                    start = i + 1;
+                }
+                _ => {}
+            }
+        }
+        options.push(body[start..].to_string());
+    }
+    options
+        .iter()
+        .flat_map(|option| expand_braces(&format!("{}{}{}", prefix, option, suffix)))
+        .collect()
+}
+
+/// `*` 匹配除 `/` 外的任意字符，`**` 匹配任意字符，`?` 匹配单个字符，`[abc]` / `[!abc]` 匹配字符集
+fn glob_match(pattern: &[char], text: &[char]) -> bool {
+    match pattern.first() {
+        None => text.is_empty(),
+        Some('*') if pattern.get(1) == Some(&'*') => {
+            let rest = &pattern[2..];
+            (0..=text.len()).any(|i| glob_match(rest, &text[i..]))
+        }
+        Some('*') => {
+            let rest = &pattern[1..];
+            for i in 0..=text.len() {
+                if glob_match(rest, &text[i..]) {
+                    return true;
+                }
+                if text.get(i) == Some(&'/') {
+                    break;
+                }
+            }
+            false
+        }
+        Some('?') => text.first().is_some_and(|c| *c != '/') && glob_match(&pattern[1..], &text[1..]),
+        Some('[') => {
+            let Some(end) = pattern.iter().skip(1).position(|c| *c == ']').map(|i| i + 1) else {
+                return text.first() == Some(&'[') && glob_match(&pattern[1..], &text[1..]);
+            };
+            let Some(c) = text.first() else { return false };
+            let (negated, set) = match pattern[1..end].split_first() {
+                Some(('!', rest)) => (true, rest),
+                _ => (false, &pattern[1..end]),
+            };
+            let mut found = false;
+            let mut i = 0;
+            while i < set.len() {
+                if i + 2 < set.len() && set[i + 1] == '-' {
+                    found |= (set[i]..=set[i + 2]).contains(c);
+                    i += 3;
+                } else {
+                    found |= set[i] == *c;
+                    i += 1;
+                }
+            }
+            found != negated && glob_match(&pattern[end + 1..], &text[1..])
+        }
+        Some('\\') if pattern.len() > 1 => text.first() == Some(&pattern[1]) && glob_match(&pattern[2..], &text[1..]),
+        Some(p) => text.first() == Some(p) && glob_match(&pattern[1..], &text[1..]),
+    }
+}
@@ -0,0 +1,152 @@
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::OnceLock;
+
+// ----------------------------------------------------------------------------
+// 没有写权限的文件：打开时检测，保存时先写入临时文件，再以管理员身份复制过去
+// （Windows 为 UAC，macOS 为系统授权对话框，Linux 为 pkexec 或 sudo）
+// ----------------------------------------------------------------------------
+
+/// 获取管理员权限的方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Elevation {
+    /// 由系统弹出授权对话框
+    SystemPrompt,
+    /// 在提示栏中输入密码，交给 `sudo -S`
+    SudoPassword,
+}
+
+impl Elevation {
+    pub fn current() -> Self {
+        static CURRENT: OnceLock<Elevation> = OnceLock::new();
+        *CURRENT.get_or_init(|| {
+            if cfg!(any(target_os = "windows", target_os = "macos")) {
+                return Elevation::SystemPrompt;
+            }
+            // 有 pkexec 时由 polkit 弹出对话框，否则在提示栏中输入 sudo 密码
+            let has_pkexec = std::env::var_os("PATH")
+                .is_some_and(|paths| std::env::split_paths(&paths).any(|dir| dir.join("pkexec").is_file()));
+            if has_pkexec { Elevation::SystemPrompt } else { Elevation::SudoPassword }
+        })
+    }
+
+    pub fn button_label(self) -> &'static str {
+        match self {
+            Elevation::SudoPassword => "🛡 Save with sudo",
+            Elevation::SystemPrompt if cfg!(any(target_os = "windows", target_os = "macos")) => "🛡 Save as Administrator",
+            Elevation::SystemPrompt => "🛡 Save as Root",
+        }
+    }
+}
+
+/// 当前进程能否写入该文件（以追加方式打开，不会修改内容）；文件不存在等其它错误视为可写
+pub fn is_writable(path: &Path) -> bool {
+    match std::fs::OpenOptions::new().append(true).open(path) {
+        Ok(_) => true,
+        Err(e) => e.kind() != std::io::ErrorKind::PermissionDenied,
+    }
+}
+
+/// 以管理员身份把 `bytes` 写入 `path`。会阻塞到用户完成授权，应在后台线程中调用
+pub fn write(path: &Path, bytes: &[u8], elevation: Elevation, password: Option<&str>) -> Result<(), String> {
+    // 符号链接写入其指向的文件；Windows 上 canonicalize 得到的 \\?\ 路径 cmd 无法识别
+    let target = if cfg!(target_os = "windows") {
+        path.to_path_buf()
+    } else {
+        std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+    };
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.subsec_nanos());
+    let temp = std::env::temp_dir().join(format!("verbium-save-{}-{}.tmp", std::process::id(), nanos));
+    write_private(&temp, bytes).map_err(|e| format!("Failed to write a temporary file: {}", e))?;
+    let result = copy_elevated(&temp, &target, elevation, password);
+    let _ = std::fs::remove_file(&temp);
+    result?;
+
+    // 取消授权时部分系统仍返回成功，读回内容确认确实写入
+    match std::fs::read(&target) {
+        Ok(written) if written == bytes => Ok(()),
+        Ok(_) => Err("The file was not changed (was the authorization cancelled?)".to_string()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// 新建只有当前用户可读写的临时文件；文件已存在（可能是他人预先放置的符号链接）时失败
+fn write_private(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    use std::io::Write;
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options.open(path)?.write_all(bytes)
+}
+
+#[cfg(target_os = "windows")]
+fn copy_elevated(temp: &Path, target: &Path, _elevation: Elevation, _password: Option<&str>) -> Result<(), String> {
+    use std::os::windows::process::CommandExt;
+    // PowerShell 单引号字符串中的单引号需要写两次
+    let quote = |p: &Path| p.to_string_lossy().replace('\'', "''");
+    let script = format!(
+        "Start-Process -FilePath cmd.exe -ArgumentList '/c copy /y \"{}\" \"{}\"' -Verb RunAs -Wait -WindowStyle Hidden",
+        quote(temp),
+        quote(target)
+    );
+    let mut command = Command::new("powershell");
+    command.args(["-NoProfile", "-NonInteractive", "-Command", &script]);
+    command.creation_flags(0x0800_0000); // CREATE_NO_WINDOW
+    run(&mut command, None)
+}
+
+#[cfg(target_os = "macos")]
+fn copy_elevated(temp: &Path, target: &Path, _elevation: Elevation, _password: Option<&str>) -> Result<(), String> {
+    let quote = |p: &Path| format!("\"{}\"", p.to_string_lossy().replace('\\', "\\\\").replace('"', "\\\""));
+    let script = format!(
+        "do shell script \"cp \" & quoted form of {} & \" \" & quoted form of {} with administrator privileges",
+        quote(temp),
+        quote(target)
+    );
+    run(Command::new("osascript").args(["-e", &script]), None)
+}
+
+/// cp 覆盖已有文件时保留其所有者与权限
+#[cfg(all(unix, not(target_os = "macos")))]
+fn copy_elevated(temp: &Path, target: &Path, elevation: Elevation, password: Option<&str>) -> Result<(), String> {
+    match elevation {
+        Elevation::SystemPrompt => run(Command::new("pkexec").args(["cp", "--"]).arg(temp).arg(target), None),
+        Elevation::SudoPassword => run(
+            // -k 忽略缓存的凭据，-p "" 不输出密码提示
+            Command::new("sudo").args(["-S", "-k", "-p", "", "cp", "--"]).arg(temp).arg(target),
+            Some(format!("{}\n", password.unwrap_or_default())),
+        ),
+    }
+}
+
+#[cfg(not(any(unix, target_os = "windows")))]
+fn copy_elevated(_temp: &Path, _target: &Path, _elevation: Elevation, _password: Option<&str>) -> Result<(), String> {
+    Err("Saving with elevated permissions is not supported on this platform".to_string())
+}
+
+fn run(command: &mut Command, input: Option<String>) -> Result<(), String> {
+    let program = command.get_program().to_string_lossy().to_string();
+    let mut child = command
+        .stdin(if input.is_some() { Stdio::piped() } else { Stdio::null() })
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run {}: {}", program, e))?;
+    if let (Some(input), Some(mut stdin)) = (input, child.stdin.take()) {
+        let _ = stdin.write_all(input.as_bytes());
+    }
+    let output = child.wait_with_output().map_err(|e| e.to_string())?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        Err(if stderr.is_empty() { format!("Authorization failed ({})", output.status) } else { stderr })
+    }
+}
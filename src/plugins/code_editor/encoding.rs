@@ -0,0 +1,173 @@
+use std::path::Path;
+use encoding_rs::Encoding;
+
+// ----------------------------------------------------------------------------
+// 文件编码与换行符：打开时识别，编辑时统一使用 LF，保存时按原格式写回
+// ----------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextEncoding {
+    Utf8,
+    Utf8Bom,
+    Utf16Le,
+    Utf16Be,
+    Gbk,
+    ShiftJis,
+    Windows1252,
+}
+
+impl TextEncoding {
+    pub const ALL: [TextEncoding; 7] = [
+        TextEncoding::Utf8,
+        TextEncoding::Utf8Bom,
+        TextEncoding::Utf16Le,
+        TextEncoding::Utf16Be,
+        TextEncoding::Gbk,
+        TextEncoding::ShiftJis,
+        TextEncoding::Windows1252,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            TextEncoding::Utf8 => "UTF-8",
+            TextEncoding::Utf8Bom => "UTF-8 with BOM",
+            TextEncoding::Utf16Le => "UTF-16 LE",
+            TextEncoding::Utf16Be => "UTF-16 BE",
+            TextEncoding::Gbk => "GBK",
+            TextEncoding::ShiftJis => "Shift-JIS",
+            TextEncoding::Windows1252 => "Windows-1252",
+        }
+    }
+
+    fn encoding(&self) -> &'static Encoding {
+        match self {
+            TextEncoding::Utf8 | TextEncoding::Utf8Bom => encoding_rs::UTF_8,
+            TextEncoding::Utf16Le => encoding_rs::UTF_16LE,
+            TextEncoding::Utf16Be => encoding_rs::UTF_16BE,
+            TextEncoding::Gbk => encoding_rs::GBK,
+            TextEncoding::ShiftJis => encoding_rs::SHIFT_JIS,
+            TextEncoding::Windows1252 => encoding_rs::WINDOWS_1252,
+        }
+    }
+
+    fn bom(&self) -> &'static [u8] {
+        match self {
+            TextEncoding::Utf8Bom => b"\xEF\xBB\xBF",
+            TextEncoding::Utf16Le => b"\xFF\xFE",
+            TextEncoding::Utf16Be => b"\xFE\xFF",
+            _ => b"",
+        }
+    }
+
+    /// 按该编码解码，不识别 BOM；返回是否出现了无法解码的字节
+    pub fn decode(&self, bytes: &[u8]) -> (String, bool) {
+        let bytes = bytes.strip_prefix(self.bom()).unwrap_or(bytes);
+        let (text, had_errors) = self.encoding().decode_without_bom_handling(bytes);
+        (text.into_owned(), had_errors)
+    }
+
+    /// 编码为字节（含 BOM）；返回是否有字符无法用该编码表示（被替换为 HTML 字符引用）
+    pub fn encode(&self, text: &str) -> (Vec<u8>, bool) {
+        let mut bytes = self.bom().to_vec();
+        match self {
+            // encoding_rs 只能解码 UTF-16，编码需要自己完成
+            TextEncoding::Utf16Le => bytes.extend(text.encode_utf16().flat_map(u16::to_le_bytes)),
+            TextEncoding::Utf16Be => bytes.extend(text.encode_utf16().flat_map(u16::to_be_bytes)),
+            TextEncoding::Utf8 | TextEncoding::Utf8Bom => bytes.extend_from_slice(text.as_bytes()),
+            _ => {
+                let (encoded, _, had_errors) = self.encoding().encode(text);
+                bytes.extend_from_slice(&encoded);
+                return (bytes, had_errors);
+            }
+        }
+        (bytes, false)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineEnding {
+    Lf,
+    CrLf,
+}
+
+impl LineEnding {
+    pub fn label(&self) -> &'static str {
+        match self {
+            LineEnding::Lf => "LF",
+            LineEnding::CrLf => "CRLF",
+        }
+    }
+}
+
+/// 文件在磁盘上的格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileFormat {
+    pub encoding: TextEncoding,
+    pub line_ending: LineEnding,
+}
+
+impl Default for FileFormat {
+    fn default() -> Self {
+        Self { encoding: TextEncoding::Utf8, line_ending: LineEnding::Lf }
+    }
+}
+
+/// 识别编码：BOM 优先，其次是合法的 UTF-8、无 BOM 的 UTF-16，
+/// 最后尝试 GBK 与 Shift-JIS（出现假名时倾向后者），都不合适时按 Windows-1252 解码
+pub fn detect(bytes: &[u8]) -> TextEncoding {
+    if let Some((encoding, _)) = Encoding::for_bom(bytes) {
+        if encoding == encoding_rs::UTF_16LE {
+            return TextEncoding::Utf16Le;
+        } else if encoding == encoding_rs::UTF_16BE {
+            return TextEncoding::Utf16Be;
+        }
+        return TextEncoding::Utf8Bom;
+    }
+    if std::str::from_utf8(bytes).is_ok() {
+        return TextEncoding::Utf8;
+    }
+    // ASCII 为主的 UTF-16 文本在奇数或偶数位置上几乎全是零字节
+    let sample = &bytes[..bytes.len().min(4096) & !1];
+    if sample.len() >= 4 {
+        let zeros_at = |parity: usize| sample.iter().skip(parity).step_by(2).filter(|b| **b == 0).count();
+        let half = sample.len() / 2;
+        if zeros_at(1) * 10 > half * 7 {
+            return TextEncoding::Utf16Le;
+        }
+        if zeros_at(0) * 10 > half * 7 {
+            return TextEncoding::Utf16Be;
+        }
+    }
+    let (shift_jis, sjis_errors) = TextEncoding::ShiftJis.decode(bytes);
+    let (_, gbk_errors) = TextEncoding::Gbk.decode(bytes);
+    let has_kana = shift_jis.chars().any(|c| matches!(c, '\u{3040}'..='\u{30FF}'));
+    match (gbk_errors, sjis_errors) {
+        (false, false) if has_kana => TextEncoding::ShiftJis,
+        (false, _) => TextEncoding::Gbk,
+        (true, false) => TextEncoding::ShiftJis,
+        (true, true) => TextEncoding::Windows1252,
+    }
+}
+
+/// 读取并解码文件，换行符统一为 LF；以多数行的换行符作为文件的换行符。
+/// 含有零字节的非 UTF-16 文件视为二进制文件
+pub fn read(path: &Path) -> std::io::Result<(String, FileFormat)> {
+    let bytes = std::fs::read(path)?;
+    let encoding = detect(&bytes);
+    if !matches!(encoding, TextEncoding::Utf16Le | TextEncoding::Utf16Be) && bytes.contains(&0) {
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "binary file"));
+    }
+    let (text, _) = encoding.decode(&bytes);
+    let crlf = text.matches("\r\n").count();
+    let line_ending = if crlf > 0 && crlf * 2 >= text.matches('\n').count() { LineEnding::CrLf } else { LineEnding::Lf };
+    let text = if crlf > 0 { text.replace("\r\n", "\n") } else { text };
+    Ok((text, FileFormat { encoding, line_ending }))
+}
+
+/// 按文件格式编码编辑器中的文本；返回是否有字符无法用该编码表示
+pub fn encode(text: &str, format: FileFormat) -> (Vec<u8>, bool) {
+    match format.line_ending {
+        LineEnding::Lf => format.encoding.encode(text),
+        LineEnding::CrLf => format.encoding.encode(&text.replace('\n', "\r\n")),
+    }
+}
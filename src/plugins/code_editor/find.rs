@@ -0,0 +1,184 @@
+use std::ops::Range;
+use regex::{Regex, RegexBuilder};
+
+/// 最多记录的匹配数量，避免在超大文件中搜索单个字符时卡顿
+const MAX_MATCHES: usize = 10_000;
+
+/// 查找选项；大小写不敏感时沿用智能大小写：查询词含大写字母时仍精确匹配
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FindOptions {
+    pub case_sensitive: bool,
+    pub whole_word: bool,
+    pub regex: bool,
+}
+
+/// 编辑器的查找 / 替换状态（"/"、Ctrl + F 或 Ctrl + H 打开）
+#[derive(Debug, Clone, Default)]
+pub struct EditorFind {
+    pub query: String,
+    pub replacement: String,
+    pub options: FindOptions,
+    /// 是否显示替换栏
+    pub replace_open: bool,
+    /// 匹配的字符范围，按位置排序
+    pub matches: Vec<Range<usize>>,
+    pub current: usize,
+    /// 正则表达式无效时的错误信息
+    pub error: Option<String>,
+    /// 打开查找栏时的光标位置，输入过程中从这里开始寻找第一个匹配
+    anchor: usize,
+    searched: Option<(String, FindOptions)>,
+    compiled: Option<Regex>,
+    pub focus_pending: bool,
+    /// 下一帧选中当前匹配并滚动到可见区域
+    pub reveal_pending: bool,
+}
+
+impl EditorFind {
+    pub fn new(anchor: usize) -> Self {
+        Self {
+            anchor,
+            focus_pending: true,
+            ..Default::default()
+        }
+    }
+
+    /// 查询词、选项变化或 `code_changed` 时重新搜索
+    pub fn refresh(&mut self, code: &str, code_changed: bool) {
+        let query_changed = self.searched.as_ref() != Some(&(self.query.clone(), self.options));
+        if !query_changed && !code_changed {
+            return;
+        }
+        if query_changed {
+            self.searched = Some((self.query.clone(), self.options));
+            match build_regex(&self.query, self.options) {
+                Ok(regex) => {
+                    self.compiled = regex;
+                    self.error = None;
+                }
+                Err(e) => {
+                    self.compiled = None;
+                    self.error = Some(e);
+                }
+            }
+        }
+        self.matches = self.compiled.as_ref().map(|re| find_matches(code, re)).unwrap_or_default();
+
+        if query_changed {
+            // 边输入边跳转：选中锚点之后的第一个匹配
+            self.current = self.matches.iter().position(|m| m.start >= self.anchor).unwrap_or(0);
+            self.reveal_pending = !self.matches.is_empty();
+        } else {
+            self.current = self.current.min(self.matches.len().saturating_sub(1));
+        }
+    }
+
+    /// 跳到下一处 / 上一处匹配，首尾循环
+    pub fn step(&mut self, forward: bool) {
+        let count = self.matches.len();
+        if count == 0 {
+            return;
+        }
+        self.current = if forward { (self.current + 1) % count } else { (self.current + count - 1) % count };
+        self.anchor = self.matches[self.current].start;
+        self.reveal_pending = true;
+    }
+
+    pub fn current_match(&self) -> Option<Range<usize>> {
+        self.matches.get(self.current).cloned()
+    }
+
+    /// 落在字符范围 `visible` 内的匹配
+    pub fn visible_matches(&self, visible: Range<usize>) -> &[Range<usize>] {
+        let first = self.matches.partition_point(|m| m.end <= visible.start);
+        let last = self.matches.partition_point(|m| m.start < visible.end);
+        &self.matches[first..last.max(first)]
+    }
+
+    /// 替换当前匹配并重新搜索，之后停在原位置的下一处匹配。
+    /// 正则模式下替换文本中的 `$1`、`${name}` 引用捕获组
+    pub fn replace_current(&mut self, code: &mut String) -> bool {
+        let (Some(re), Some(range)) = (self.compiled.clone(), self.current_match()) else { return false };
+        let start = byte_of_char(code, range.start);
+        let Some(caps) = re.captures_at(code, start).filter(|c| c.get(0).is_some_and(|m| m.start() == start)) else {
+            return false;
+        };
+        let whole = caps.get(0).map(|m| m.range()).unwrap_or(start..start);
+        let mut replacement = String::new();
+        if self.options.regex {
+            caps.expand(&self.replacement, &mut replacement);
+        } else {
+            replacement.push_str(&self.replacement);
+        }
+        code.replace_range(whole, &replacement);
+
+        let next = range.start + replacement.chars().count();
+        self.matches = find_matches(code, &re);
+        self.current = self.matches.iter().position(|m| m.start >= next).unwrap_or(0);
+        self.anchor = next;
+        self.reveal_pending = !self.matches.is_empty();
+        true
+    }
+
+    /// 替换全部匹配，返回替换的数量
+    pub fn replace_all(&mut self, code: &mut String) -> usize {
+        let Some(re) = self.compiled.clone() else { return 0 };
+        let count = re.find_iter(code).filter(|m| !m.is_empty()).count();
+        if count == 0 {
+            return 0;
+        }
+        let replaced = if self.options.regex {
+            re.replace_all(code, self.replacement.as_str())
+        } else {
+            re.replace_all(code, regex::NoExpand(&self.replacement))
+        };
+        *code = replaced.into_owned();
+        self.matches = find_matches(code, &re);
+        self.current = 0;
+        count
+    }
+}
+
+/// 按选项构造正则；查询词为空时返回 None
+fn build_regex(query: &str, options: FindOptions) -> Result<Option<Regex>, String> {
+    if query.is_empty() {
+        return Ok(None);
+    }
+    let mut pattern = if options.regex { query.to_string() } else { regex::escape(query) };
+    if options.whole_word {
+        pattern = format!(r"\b(?:{})\b", pattern);
+    }
+    let ignore_case = !options.case_sensitive && !query.chars().any(char::is_uppercase);
+    RegexBuilder::new(&pattern)
+        .case_insensitive(ignore_case)
+        .multi_line(true)
+        .build()
+        .map(Some)
+        .map_err(|e| e.to_string())
+}
+
+/// 搜索全部匹配并换算为字符范围；忽略空匹配
+fn find_matches(code: &str, re: &Regex) -> Vec<Range<usize>> {
+    let mut matches = Vec::new();
+    let mut chars = code.char_indices().map(|(i, _)| i).enumerate().peekable();
+    let mut char_at = |byte: usize| {
+        while let Some((_, b)) = chars.peek() {
+            if *b >= byte {
+                break;
+            }
+            chars.next();
+        }
+        chars.peek().map(|(c, _)| *c)
+    };
+    let total_chars = code.chars().count();
+    for m in re.find_iter(code).filter(|m| !m.is_empty()).take(MAX_MATCHES) {
+        let start = char_at(m.start()).unwrap_or(total_chars);
+        let end = char_at(m.end()).unwrap_or(total_chars);
+        matches.push(start..end);
+    }
+    matches
+}
+
+fn byte_of_char(code: &str, char_idx: usize) -> usize {
+    code.char_indices().nth(char_idx).map(|(i, _)| i).unwrap_or(code.len())
+}
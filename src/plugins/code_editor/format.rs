@@ -0,0 +1,104 @@
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+// ----------------------------------------------------------------------------
+// 外部格式化工具：按语言配置的命令从标准输入读取代码，把格式化结果写到标准输出
+// ----------------------------------------------------------------------------
+
+/// 按空白拆分命令行，单引号或双引号包裹的部分作为一个参数
+fn split_command(line: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_arg = false;
+    let mut quote = None;
+    for c in line.chars() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => current.push(c),
+            None if c == '"' || c == '\'' => {
+                quote = Some(c);
+                in_arg = true;
+            }
+            None if c.is_whitespace() => {
+                if in_arg {
+                    args.push(std::mem::take(&mut current));
+                    in_arg = false;
+                }
+            }
+            None => {
+                current.push(c);
+                in_arg = true;
+            }
+        }
+    }
+    if in_arg {
+        args.push(current);
+    }
+    args
+}
+
+/// 运行格式化命令并返回格式化后的代码。`{file}` 替换为文件路径（未保存的文件为
+/// `untitled.<语言>`），命令在文件所在目录中运行，以便找到项目的格式化配置
+pub fn run(command_line: &str, code: &str, path: Option<&Path>, language: &str) -> Result<String, String> {
+    let file = path.map_or_else(|| format!("untitled.{}", language), |p| p.to_string_lossy().to_string());
+    let mut args = split_command(command_line).into_iter().map(|arg| arg.replace("{file}", &file));
+    let program = args.next().ok_or_else(|| "No formatter command configured".to_string())?;
+
+    let mut command = Command::new(&program);
+    command.args(args).stdin(Stdio::piped()).stdout(Stdio::piped()).stderr(Stdio::piped());
+    if let Some(dir) = path.and_then(Path::parent).filter(|dir| !dir.as_os_str().is_empty()) {
+        command.current_dir(dir);
+    }
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        command.creation_flags(0x0800_0000); // CREATE_NO_WINDOW
+    }
+    let mut child = command.spawn().map_err(|e| format!("Failed to run {}: {}", program, e))?;
+
+    // 在单独的线程中写入，避免输出填满管道缓冲区时两边互相等待
+    let stdin = child.stdin.take();
+    let input = code.to_string();
+    let writer = std::thread::spawn(move || {
+        if let Some(mut stdin) = stdin {
+            let _ = stdin.write_all(input.as_bytes());
+        }
+    });
+    let output = child.wait_with_output().map_err(|e| e.to_string())?;
+    let _ = writer.join();
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let message = stderr.lines().find(|line| !line.trim().is_empty()).unwrap_or_default().trim();
+        return Err(if message.is_empty() { format!("{} exited with {}", program, output.status) } else { message.to_string() });
+    }
+    let formatted = String::from_utf8(output.stdout).map_err(|_| format!("{} printed invalid UTF-8", program))?;
+    // 缓冲区内部统一使用 \n，保存时再按文件原有的换行符写回
+    Ok(formatted.replace("\r\n", "\n"))
+}
+
+/// 把格式化前的字符位置映射到格式化后的文本。格式化通常只改动空白，
+/// 因此按光标之前的非空白字符数找到新位置：光标紧挨在某个字符之前时仍停在该字符之前，
+/// 否则停在前一个非空白字符之后
+pub fn map_cursor(old: &str, new: &str, char_idx: usize) -> usize {
+    let significant = old.chars().take(char_idx).filter(|c| !c.is_whitespace()).count();
+    let before_token = old.chars().nth(char_idx).is_some_and(|c| !c.is_whitespace());
+    if !before_token && significant == 0 {
+        return 0;
+    }
+    let mut seen = 0;
+    for (i, c) in new.chars().enumerate() {
+        if c.is_whitespace() {
+            continue;
+        }
+        if before_token && seen == significant {
+            return i;
+        }
+        seen += 1;
+        if !before_token && seen == significant {
+            return i + 1;
+        }
+    }
+    new.chars().count()
+}
@@ -0,0 +1,139 @@
+use super::outline::Symbol;
+
+// ----------------------------------------------------------------------------
+// 跳转弹窗：Ctrl + G 跳到行，Ctrl + Shift + O 跳到符号
+// ----------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GotoMode {
+    Line,
+    Symbol,
+}
+
+#[derive(Debug, Clone)]
+pub struct GotoPopup {
+    pub mode: GotoMode,
+    pub query: String,
+    selected: usize,
+    focus_pending: bool,
+}
+
+pub enum GotoAction {
+    None,
+    Close,
+    /// 跳到 (行, 列)，均从 0 开始
+    Jump(usize, usize),
+}
+
+impl GotoPopup {
+    pub fn new(mode: GotoMode) -> Self {
+        Self { mode, query: String::new(), selected: 0, focus_pending: true }
+    }
+}
+
+/// 解析 "行" 或 "行:列"（从 1 开始），超出范围时停在最后一行
+fn parse_position(query: &str, line_count: usize) -> Option<(usize, usize)> {
+    let mut parts = query.trim().splitn(2, [':', ',']);
+    let line: usize = parts.next()?.trim().parse().ok()?;
+    let column: usize = parts.next().and_then(|c| c.trim().parse().ok()).unwrap_or(1);
+    Some((line.clamp(1, line_count.max(1)) - 1, column.max(1) - 1))
+}
+
+/// 符号名按顺序包含查询词的全部字符（不区分大小写）；前缀匹配排在前面
+fn filter_symbols<'a>(symbols: &'a [Symbol], query: &str) -> Vec<&'a Symbol> {
+    let query = query.trim().to_lowercase();
+    let mut matches: Vec<(bool, &Symbol)> = symbols
+        .iter()
+        .filter_map(|symbol| {
+            let name = symbol.name.to_lowercase();
+            let mut chars = name.chars();
+            query.chars().all(|q| chars.any(|c| c == q)).then(|| (!name.starts_with(&query), symbol))
+        })
+        .collect();
+    matches.sort_by_key(|(not_prefix, _)| *not_prefix);
+    matches.into_iter().map(|(_, symbol)| symbol).collect()
+}
+
+/// 在 `anchor`（编辑区顶部中央）下方绘制弹窗。符号模式下输入以 ":" 开头时切换为跳到行
+pub fn show(ui: &mut egui::Ui, popup: &mut GotoPopup, anchor: egui::Pos2, line_count: usize, current_line: usize, symbols: &[Symbol]) -> GotoAction {
+    if popup.mode == GotoMode::Symbol && popup.query.starts_with(':') {
+        popup.mode = GotoMode::Line;
+        popup.query.remove(0);
+    }
+    let entries = match popup.mode {
+        GotoMode::Symbol => filter_symbols(symbols, &popup.query),
+        GotoMode::Line => Vec::new(),
+    };
+    let (up, down, enter, escape) = ui.input_mut(|i| (
+        i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp),
+        i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown),
+        i.consume_key(egui::Modifiers::NONE, egui::Key::Enter),
+        i.consume_key(egui::Modifiers::NONE, egui::Key::Escape),
+    ));
+    if up {
+        popup.selected = popup.selected.saturating_sub(1);
+    }
+    if down {
+        popup.selected += 1;
+    }
+    popup.selected = popup.selected.min(entries.len().saturating_sub(1));
+
+    let mut action = if escape { GotoAction::Close } else { GotoAction::None };
+    if enter {
+        let target = match popup.mode {
+            GotoMode::Line => parse_position(&popup.query, line_count),
+            GotoMode::Symbol => entries.get(popup.selected).map(|symbol| (symbol.line, 0)),
+        };
+        action = target.map_or(GotoAction::Close, |(line, column)| GotoAction::Jump(line, column));
+    }
+
+    let area = egui::Area::new(ui.id().with("goto_popup"))
+        .order(egui::Order::Foreground)
+        .pivot(egui::Align2::CENTER_TOP)
+        .fixed_pos(anchor)
+        .show(ui.ctx(), |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.set_width(360.0);
+                let hint = match popup.mode {
+                    GotoMode::Line => format!("Line[:column], 1 – {}", line_count),
+                    GotoMode::Symbol => "Go to symbol (\":\" for line)".to_string(),
+                };
+                let response = ui.add(egui::TextEdit::singleline(&mut popup.query).hint_text(hint).desired_width(f32::INFINITY));
+                if std::mem::take(&mut popup.focus_pending) {
+                    response.request_focus();
+                }
+                match popup.mode {
+                    GotoMode::Line => {
+                        ui.weak(format!("Current line {} of {}. Press Enter to jump.", current_line + 1, line_count));
+                    }
+                    GotoMode::Symbol => {
+                        ui.separator();
+                        if entries.is_empty() {
+                            ui.weak(if symbols.is_empty() { "No symbols in this file." } else { "No matching symbols." });
+                        }
+                        egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                            for (i, symbol) in entries.iter().enumerate() {
+                                let response = ui.horizontal(|ui| {
+                                    ui.add_space(symbol.level.saturating_sub(1) as f32 * 12.0);
+                                    ui.label(egui::RichText::new(symbol.kind.icon()).weak());
+                                    let label = ui.selectable_label(i == popup.selected, &symbol.name);
+                                    ui.weak(format!("{}", symbol.line + 1));
+                                    label
+                                }).inner;
+                                if i == popup.selected && (up || down) {
+                                    response.scroll_to_me(None);
+                                }
+                                if response.clicked() {
+                                    action = GotoAction::Jump(symbol.line, 0);
+                                }
+                            }
+                        });
+                    }
+                }
+            });
+        });
+    if area.response.clicked_elsewhere() {
+        action = GotoAction::Close;
+    }
+    action
+}
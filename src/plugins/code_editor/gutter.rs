@@ -0,0 +1,166 @@
+use std::collections::BTreeSet;
+use std::ops::Range;
+use egui::{Align2, FontId, Galley, Pos2, Rangef, Rect, Sense, Ui};
+use crate::diff::LineChange;
+
+// ----------------------------------------------------------------------------
+// 行号栏：断点与书签标记列 + 行号，按编辑器排版结果逐行绘制
+// ----------------------------------------------------------------------------
+
+/// 断点标记列的宽度
+const MARKER_WIDTH: f32 = 14.0;
+/// 行号左右的留白
+const PADDING: f32 = 6.0;
+
+/// 一个逻辑行在排版结果中的位置（相对排版原点）
+pub struct LineRow {
+    /// 行内字符范围，包含行尾换行符
+    pub chars: Range<usize>,
+    /// 第一个显示行，行号与断点画在这里
+    pub first: Rect,
+    /// 自动换行后该逻辑行占据的全部高度
+    pub span: Rangef,
+}
+
+/// 把排版结果中的显示行按换行符归并为逻辑行
+pub fn line_rows(galley: &Galley) -> Vec<LineRow> {
+    let mut lines: Vec<LineRow> = Vec::new();
+    let mut char_idx = 0;
+    let mut line_open = false;
+    for row in &galley.rows {
+        let chars = char_idx..char_idx + row.char_count_including_newline();
+        char_idx = chars.end;
+        match lines.last_mut().filter(|_| line_open) {
+            Some(line) => {
+                line.chars.end = chars.end;
+                line.span.max = row.rect.bottom();
+            }
+            None => lines.push(LineRow { chars, first: row.rect, span: row.rect.y_range() }),
+        }
+        line_open = !row.ends_with_newline;
+    }
+    lines
+}
+
+/// 行号栏宽度：至少容纳两位数字
+pub fn width(ui: &Ui, font_id: &FontId, line_count: usize) -> f32 {
+    let digits = line_count.max(1).to_string().len().max(2);
+    let digit_width = ui.fonts(|f| f.glyph_width(font_id, '0'));
+    MARKER_WIDTH + digits as f32 * digit_width + PADDING * 2.0
+}
+
+/// 指定高度（相对排版原点）所在的逻辑行
+fn line_at(lines: &[LineRow], y: f32) -> Option<usize> {
+    let idx = lines.partition_point(|line| line.span.max <= y);
+    (idx < lines.len()).then_some(idx).or(lines.len().checked_sub(1))
+}
+
+/// 绘制行号栏并处理点击：点击标记列切换断点，点击或拖过行号选中整行。
+/// 返回需要选中的字符范围
+pub fn show(
+    ui: &mut Ui,
+    rect: Rect,
+    galley_pos: Pos2,
+    lines: &[LineRow],
+    current_line: usize,
+    breakpoints: &mut BTreeSet<usize>,
+    bookmarks: &BTreeSet<usize>,
+    font_id: &FontId,
+) -> Option<Range<usize>> {
+    let response = ui.interact(rect, ui.id().with("gutter"), Sense::click_and_drag());
+    let in_markers = |pos: Pos2| pos.x < rect.left() + MARKER_WIDTH;
+    let line_of = |pos: Pos2| line_at(lines, pos.y - galley_pos.y);
+
+    let mut selection = None;
+    if let Some(pos) = response.interact_pointer_pos() {
+        let origin = ui.input(|i| i.pointer.press_origin()).unwrap_or(pos);
+        if in_markers(origin) {
+            if response.clicked() {
+                if let Some(line) = line_of(pos) {
+                    if !breakpoints.remove(&line) {
+                        breakpoints.insert(line);
+                    }
+                }
+            }
+        } else if response.clicked() || response.dragged() {
+            if let (Some(anchor), Some(line)) = (line_of(origin), line_of(pos)) {
+                let (first, last) = (anchor.min(line), anchor.max(line));
+                selection = Some(lines[first].chars.start..lines[last].chars.end);
+            }
+        }
+    }
+    let hovered_marker = response.hover_pos().filter(|pos| in_markers(*pos)).and_then(line_of);
+
+    let painter = ui.painter_at(rect);
+    let visuals = ui.visuals();
+    let palette = crate::theme::palette(ui.ctx());
+    let clip = ui.clip_rect();
+    painter.vline(rect.right() - 0.5, rect.y_range(), visuals.widgets.noninteractive.bg_stroke);
+    let first_visible = line_at(lines, clip.top() - galley_pos.y).unwrap_or(0);
+    for (line, row) in lines.iter().enumerate().skip(first_visible) {
+        let top = galley_pos.y + row.first.top();
+        if top > clip.bottom() {
+            break;
+        }
+        let center_y = galley_pos.y + row.first.center().y;
+        let color = if line == current_line { visuals.strong_text_color() } else { visuals.weak_text_color() };
+        painter.text(
+            egui::pos2(rect.right() - PADDING, center_y),
+            Align2::RIGHT_CENTER,
+            (line + 1).to_string(),
+            font_id.clone(),
+            color,
+        );
+
+        let marker_center = egui::pos2(rect.left() + MARKER_WIDTH / 2.0, center_y);
+        let radius = (row.first.height() / 2.0 - 2.0).clamp(2.0, 5.0);
+        // 书签画成缎带形状，与断点同时存在时断点叠在上面
+        if bookmarks.contains(&line) {
+            let (left, right) = (marker_center.x - radius, marker_center.x + radius);
+            let (top, bottom) = (center_y - radius - 1.0, center_y + radius + 1.0);
+            let points = vec![
+                egui::pos2(left, top),
+                egui::pos2(right, top),
+                egui::pos2(right, bottom),
+                egui::pos2(marker_center.x, bottom - radius * 0.6),
+                egui::pos2(left, bottom),
+            ];
+            painter.add(egui::Shape::convex_polygon(points, palette.info, egui::Stroke::NONE));
+        }
+        if breakpoints.contains(&line) {
+            painter.circle_filled(marker_center, radius, palette.error);
+        } else if hovered_marker == Some(line) {
+            painter.circle_filled(marker_center, radius, palette.error.gamma_multiply(0.35));
+        }
+    }
+    if hovered_marker.is_some() {
+        ui.ctx().set_cursor_icon(egui::CursorIcon::PointingHand);
+    }
+    selection
+}
+
+/// 在行号栏右缘标注相对最近一次提交的修改：新增与修改的行画竖条，删除处画三角形
+pub fn paint_changes(ui: &Ui, rect: Rect, galley_pos: Pos2, lines: &[LineRow], changes: &[(usize, LineChange)]) {
+    let painter = ui.painter_at(rect);
+    let palette = crate::theme::palette(ui.ctx());
+    let clip = ui.clip_rect();
+    let x = rect.right() - 3.0;
+    for &(line, change) in changes {
+        let Some(row) = lines.get(line) else { continue };
+        let y_range = Rangef::new(galley_pos.y + row.span.min, galley_pos.y + row.span.max);
+        if y_range.max < clip.top() || y_range.min > clip.bottom() {
+            continue;
+        }
+        match change {
+            LineChange::Added | LineChange::Modified => {
+                let color = if change == LineChange::Added { palette.added } else { palette.modified };
+                painter.rect_filled(Rect::from_x_y_ranges(x..=x + 2.0, y_range), 0.0, color);
+            }
+            LineChange::Deleted => {
+                let top = y_range.min;
+                let points = vec![egui::pos2(x - 2.0, top - 4.0), egui::pos2(x + 3.0, top), egui::pos2(x - 2.0, top + 4.0)];
+                painter.add(egui::Shape::convex_polygon(points, palette.deleted, egui::Stroke::NONE));
+            }
+        }
+    }
+}
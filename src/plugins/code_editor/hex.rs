@@ -0,0 +1,561 @@
+use std::collections::BTreeSet;
+use std::io::Read;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use egui::{Ui, WidgetText};
+use parking_lot::{Mutex, RwLock};
+use crate::{AppCommand, TabInstance};
+use super::config::EditorConfig;
+use super::encoding::{self, TextEncoding};
+
+// ----------------------------------------------------------------------------
+// 十六进制编辑器：不是文本的文件以 偏移 / 十六进制 / ASCII 三栏显示，
+// 支持原位修改字节（不改变文件长度）与按十六进制序列查找
+// ----------------------------------------------------------------------------
+
+const BYTES_PER_ROW: usize = 16;
+/// 超过该大小的文件不读入内存
+const MAX_BYTES: u64 = 256 * 1024 * 1024;
+/// 偏移栏的字符数（含与十六进制栏之间的空隙）
+const OFFSET_CHARS: usize = 10;
+/// 十六进制栏的字符数：每字节两位加一个空格，第 8 个字节后多一个空格，再与 ASCII 栏隔两格
+const HEX_CHARS: usize = BYTES_PER_ROW * 3 + 3;
+
+/// 按开头的 8 KB 判断是否为二进制文件：含有零字节且不是 UTF-16 文本
+pub fn is_binary(path: &Path) -> bool {
+    let mut sample = Vec::with_capacity(8192);
+    let Ok(file) = std::fs::File::open(path) else { return false };
+    if file.take(8192).read_to_end(&mut sample).is_err() {
+        return false;
+    }
+    sample.contains(&0) && !matches!(encoding::detect(&sample), TextEncoding::Utf16Le | TextEncoding::Utf16Be)
+}
+
+/// 解析查找内容：以空白分隔或连写的十六进制字节，`??` 匹配任意字节
+fn parse_pattern(text: &str) -> Result<Vec<Option<u8>>, String> {
+    let digits: String = text.split_whitespace().collect();
+    if digits.is_empty() || digits.len() % 2 != 0 {
+        return Err("Enter whole bytes, e.g. 4D 5A ?? 00".to_string());
+    }
+    digits
+        .as_bytes()
+        .chunks(2)
+        .map(|pair| {
+            let pair = std::str::from_utf8(pair).unwrap_or_default();
+            if pair == "??" {
+                Ok(None)
+            } else {
+                u8::from_str_radix(pair, 16).map(Some).map_err(|_| format!("\"{}\" is not a hex byte", pair))
+            }
+        })
+        .collect()
+}
+
+fn matches_at(data: &[u8], at: usize, pattern: &[Option<u8>]) -> bool {
+    data.get(at..at + pattern.len())
+        .is_some_and(|window| window.iter().zip(pattern).all(|(b, p)| p.is_none_or(|p| p == *b)))
+}
+
+/// 光标所在的栏：十六进制栏按半字节输入，ASCII 栏按字符输入
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Column {
+    Hex,
+    Ascii,
+}
+
+#[derive(Debug, Clone)]
+pub struct HexTab {
+    name: String,
+    path: PathBuf,
+    /// 后台读取的结果，取走后为 None
+    loading: Option<Arc<RwLock<Option<Result<Vec<u8>, String>>>>>,
+    error: Option<String>,
+    data: Vec<u8>,
+    /// 保存后被修改过的字节
+    modified: BTreeSet<usize>,
+    /// 撤销 / 重做：(偏移, 原值, 新值)
+    undo: Vec<(usize, u8, u8)>,
+    redo: Vec<(usize, u8, u8)>,
+    is_dirty: bool,
+    cursor: usize,
+    column: Column,
+    /// 十六进制栏中已输入了高半字节，下一次输入低半字节
+    low_nibble: bool,
+    scroll_to_cursor: bool,
+    /// 查找栏：十六进制序列与当前匹配
+    find_open: bool,
+    find_focus: bool,
+    query: String,
+    find_error: Option<String>,
+    found: Option<Range<usize>>,
+    goto_text: String,
+    settings: Arc<Mutex<EditorConfig>>,
+}
+
+impl HexTab {
+    pub fn open(path: &Path, settings: Arc<Mutex<EditorConfig>>) -> Self {
+        let result_store = Arc::new(RwLock::new(None));
+        let result_store_clone = result_store.clone();
+        let path_owned = path.to_path_buf();
+        std::thread::spawn(move || {
+            let result = match std::fs::metadata(&path_owned) {
+                Ok(meta) if meta.len() > MAX_BYTES => {
+                    Err(format!("The file is too large for the hex editor ({} MB max)", MAX_BYTES / (1024 * 1024)))
+                }
+                _ => std::fs::read(&path_owned).map_err(|e| e.to_string()),
+            };
+            *result_store_clone.write() = Some(result);
+        });
+        Self {
+            name: path.file_name().unwrap_or_default().to_string_lossy().to_string(),
+            path: path.to_path_buf(),
+            loading: Some(result_store),
+            error: None,
+            data: Vec::new(),
+            modified: BTreeSet::new(),
+            undo: Vec::new(),
+            redo: Vec::new(),
+            is_dirty: false,
+            cursor: 0,
+            column: Column::Hex,
+            low_nibble: false,
+            scroll_to_cursor: false,
+            find_open: false,
+            find_focus: false,
+            query: String::new(),
+            find_error: None,
+            found: None,
+            goto_text: String::new(),
+            settings,
+        }
+    }
+
+    fn set_byte(&mut self, index: usize, value: u8) {
+        let Some(byte) = self.data.get_mut(index) else { return };
+        if *byte == value {
+            return;
+        }
+        self.undo.push((index, *byte, value));
+        self.redo.clear();
+        *byte = value;
+        self.modified.insert(index);
+        self.is_dirty = true;
+    }
+
+    fn undo_redo(&mut self, redo: bool) {
+        let step = if redo { self.redo.pop() } else { self.undo.pop() };
+        let Some((index, old, new)) = step else { return };
+        self.data[index] = if redo { new } else { old };
+        self.modified.insert(index);
+        if redo { self.undo.push((index, old, new)) } else { self.redo.push((index, old, new)) }
+        self.is_dirty = true;
+        self.move_cursor(index);
+    }
+
+    fn move_cursor(&mut self, index: usize) {
+        self.cursor = index.min(self.data.len().saturating_sub(1));
+        self.low_nibble = false;
+        self.scroll_to_cursor = true;
+    }
+
+    fn save(&mut self, control: &mut Vec<AppCommand>) {
+        match self.settings.lock().write(&self.path, &self.data) {
+            Ok(()) => {
+                self.is_dirty = false;
+                self.modified.clear();
+                control.push(AppCommand::Notify {
+                    message: format!("Saved {}", self.name),
+                    level: crate::NotificationLevel::Success,
+                });
+            }
+            Err(e) => control.push(AppCommand::Notify {
+                message: format!("Save failed: {}", e),
+                level: crate::NotificationLevel::Error,
+            }),
+        }
+    }
+
+    /// 从光标之后（或之前）查找，到头后从另一端继续
+    fn find(&mut self, forward: bool) {
+        let pattern = match parse_pattern(&self.query) {
+            Ok(pattern) => pattern,
+            Err(e) => {
+                self.find_error = Some(e);
+                return;
+            }
+        };
+        let len = self.data.len();
+        let last_start = match len.checked_sub(pattern.len()) {
+            Some(last) => last,
+            None => {
+                self.find_error = Some("No match".to_string());
+                return;
+            }
+        };
+        let start = self.found.as_ref().map_or(self.cursor, |found| found.start);
+        let mut order: Box<dyn Iterator<Item = usize>> = if forward {
+            let from = if self.found.is_some() { start + 1 } else { start };
+            Box::new((from..=last_start).chain(0..from.min(last_start + 1)))
+        } else {
+            let from = start.min(last_start + 1);
+            Box::new((0..from).rev().chain((from..=last_start).rev()))
+        };
+        let data = &self.data;
+        match order.find(|&at| matches_at(data, at, &pattern)) {
+            Some(at) => {
+                self.found = Some(at..at + pattern.len());
+                self.find_error = None;
+                self.move_cursor(at);
+            }
+            None => {
+                self.found = None;
+                self.find_error = Some("No match".to_string());
+            }
+        }
+    }
+
+    fn find_bar_ui(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            let response = ui.add(
+                egui::TextEdit::singleline(&mut self.query)
+                    .hint_text("Hex bytes, e.g. 4D 5A ?? 00")
+                    .font(egui::TextStyle::Monospace)
+                    .desired_width(240.0),
+            );
+            if std::mem::take(&mut self.find_focus) {
+                response.request_focus();
+            }
+            if response.changed() {
+                self.found = None;
+                self.find_error = None;
+            }
+            if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                let forward = !ui.input(|i| i.modifiers.shift);
+                self.find(forward);
+                response.request_focus();
+            }
+            if ui.small_button("⬆").on_hover_text("Previous (Shift + Enter)").clicked() {
+                self.find(false);
+            }
+            if ui.small_button("⬇").on_hover_text("Next (Enter)").clicked() {
+                self.find(true);
+            }
+            if let Some(error) = &self.find_error {
+                ui.colored_label(crate::theme::palette(ui.ctx()).warning, error);
+            }
+            if ui.small_button("✖").on_hover_text("Close (Esc)").clicked()
+                || (response.has_focus() && ui.input(|i| i.key_pressed(egui::Key::Escape)))
+            {
+                self.find_open = false;
+                self.found = None;
+            }
+        });
+        ui.separator();
+    }
+
+    /// 编辑区拥有焦点时处理方向键与输入
+    fn handle_keys(&mut self, ui: &mut Ui, control: &mut Vec<AppCommand>, page_rows: usize) {
+        let len = self.data.len();
+        if len == 0 {
+            return;
+        }
+        let events = ui.input(|i| i.events.clone());
+        for event in events {
+            match event {
+                egui::Event::Text(text) => {
+                    for c in text.chars() {
+                        match self.column {
+                            Column::Hex => {
+                                let Some(nibble) = c.to_digit(16) else { continue };
+                                let old = self.data[self.cursor];
+                                let value = if self.low_nibble { (old & 0xF0) | nibble as u8 } else { (old & 0x0F) | ((nibble as u8) << 4) };
+                                self.set_byte(self.cursor, value);
+                                if self.low_nibble {
+                                    self.move_cursor(self.cursor + 1);
+                                } else {
+                                    self.low_nibble = true;
+                                }
+                            }
+                            Column::Ascii if c.is_ascii() && !c.is_ascii_control() => {
+                                self.set_byte(self.cursor, c as u8);
+                                self.move_cursor(self.cursor + 1);
+                            }
+                            Column::Ascii => {}
+                        }
+                    }
+                }
+                egui::Event::Key { key, pressed: true, modifiers, .. } => {
+                    let row = BYTES_PER_ROW;
+                    let target = match key {
+                        egui::Key::ArrowLeft => Some(self.cursor.saturating_sub(1)),
+                        egui::Key::ArrowRight => Some(self.cursor + 1),
+                        egui::Key::ArrowUp => Some(self.cursor.saturating_sub(row)),
+                        egui::Key::ArrowDown => Some(self.cursor + row),
+                        egui::Key::PageUp => Some(self.cursor.saturating_sub(row * page_rows)),
+                        egui::Key::PageDown => Some(self.cursor + row * page_rows),
+                        egui::Key::Home if modifiers.command => Some(0),
+                        egui::Key::End if modifiers.command => Some(len - 1),
+                        egui::Key::Home => Some(self.cursor - self.cursor % row),
+                        egui::Key::End => Some(self.cursor - self.cursor % row + row - 1),
+                        egui::Key::Tab => {
+                            self.column = if self.column == Column::Hex { Column::Ascii } else { Column::Hex };
+                            self.low_nibble = false;
+                            None
+                        }
+                        egui::Key::Z if modifiers.command => {
+                            self.undo_redo(modifiers.shift);
+                            None
+                        }
+                        egui::Key::Y if modifiers.command => {
+                            self.undo_redo(true);
+                            None
+                        }
+                        egui::Key::S if modifiers.command => {
+                            self.save(control);
+                            None
+                        }
+                        _ => None,
+                    };
+                    if let Some(target) = target {
+                        self.move_cursor(target);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn status_bar_ui(&self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.spacing_mut().item_spacing.x = 12.0;
+            if let Some(&byte) = self.data.get(self.cursor) {
+                ui.label(format!("Offset 0x{:08X} ({})", self.cursor, self.cursor));
+                let shown = if byte.is_ascii_graphic() { format!(" '{}'", byte as char) } else { String::new() };
+                ui.label(format!("Byte 0x{:02X} = {} / {}{}", byte, byte, byte as i8, shown));
+                let read = |n: usize| self.data.get(self.cursor..self.cursor + n);
+                if let Some(bytes) = read(2) {
+                    ui.label(egui::RichText::new(format!("u16 LE {}", u16::from_le_bytes([bytes[0], bytes[1]]))).weak());
+                }
+                if let Some(bytes) = read(4) {
+                    ui.label(egui::RichText::new(format!("u32 LE {}", u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))).weak());
+                }
+            }
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                ui.label(egui::RichText::new(format!("{} bytes", self.data.len())).weak());
+                if !self.modified.is_empty() {
+                    ui.label(egui::RichText::new(format!("{} modified", self.modified.len())).color(crate::theme::palette(ui.ctx()).modified));
+                }
+            });
+        });
+    }
+}
+
+impl TabInstance for HexTab {
+    fn title(&self) -> WidgetText {
+        if self.is_dirty { format!("{}*", self.name).into() } else { self.name.clone().into() }
+    }
+
+    fn icon(&self) -> Option<crate::TabIcon> {
+        Some(crate::TabIcon::Emoji("🔢".into()))
+    }
+
+    fn ui(&mut self, ui: &mut Ui, control: &mut Vec<AppCommand>) {
+        if let Some(store) = &self.loading {
+            let result = store.read().clone();
+            match result {
+                Some(Ok(data)) => {
+                    self.data = data;
+                    self.loading = None;
+                }
+                Some(Err(e)) => {
+                    self.error = Some(e);
+                    self.loading = None;
+                }
+                None => {
+                    ui.centered_and_justified(|ui| {
+                        ui.spinner();
+                    });
+                    ui.ctx().request_repaint();
+                    return;
+                }
+            }
+        }
+        if let Some(e) = &self.error {
+            ui.centered_and_justified(|ui| {
+                let color = crate::theme::palette(ui.ctx()).error;
+                ui.label(egui::RichText::new(format!("Failed to load file:\n{}", e)).color(color));
+            });
+            return;
+        }
+
+        let area_id = ui.id().with("hex_area");
+        let focused = ui.memory(|m| m.has_focus(area_id));
+        if (focused || ui.rect_contains_pointer(ui.max_rect())) && ui.input_mut(|i| i.consume_key(egui::Modifiers::COMMAND, egui::Key::F)) {
+            self.find_open = true;
+            self.find_focus = true;
+        }
+
+        ui.horizontal(|ui| {
+            ui.label("Go to offset:");
+            let response = ui.add(egui::TextEdit::singleline(&mut self.goto_text).hint_text("0x1F0 or 496").desired_width(120.0));
+            if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                let text = self.goto_text.trim();
+                let offset = match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+                    Some(hex) => usize::from_str_radix(hex, 16).ok(),
+                    None => text.parse().ok(),
+                };
+                if let Some(offset) = offset {
+                    self.move_cursor(offset);
+                    ui.memory_mut(|m| m.request_focus(area_id));
+                }
+            }
+            ui.separator();
+            if ui.button("🔍 Find").on_hover_text("Find a byte sequence (Ctrl + F)").clicked() {
+                self.find_open = true;
+                self.find_focus = true;
+            }
+            if ui.add_enabled(self.is_dirty, egui::Button::new("💾 Save")).clicked() {
+                self.save(control);
+            }
+        });
+        ui.separator();
+        if self.find_open {
+            self.find_bar_ui(ui);
+        }
+        egui::TopBottomPanel::bottom(ui.id().with("hex_status_bar")).show_inside(ui, |ui| self.status_bar_ui(ui));
+
+        let font_id = egui::TextStyle::Monospace.resolve(ui.style());
+        let row_height = ui.fonts(|f| f.row_height(&font_id));
+        let char_width = ui.fonts(|f| f.glyph_width(&font_id, '0'));
+        let row_count = self.data.len().div_ceil(BYTES_PER_ROW).max(1);
+        let spacing = ui.spacing().item_spacing.y;
+        let page_rows = ((ui.available_height() / (row_height + spacing)) as usize).max(1);
+
+        // 整个编辑区作为一个可获得焦点的控件，方向键与 Tab 不用于切换焦点
+        let background = ui.interact(ui.available_rect_before_wrap(), area_id, egui::Sense::click());
+        if background.clicked() {
+            ui.memory_mut(|m| m.request_focus(area_id));
+        }
+        if focused {
+            ui.memory_mut(|m| {
+                m.set_focus_lock_filter(area_id, egui::EventFilter { tab: true, horizontal_arrows: true, vertical_arrows: true, escape: false })
+            });
+            self.handle_keys(ui, control, page_rows);
+        }
+
+        let mut scroll = egui::ScrollArea::both().id_salt("hex_scroll").auto_shrink([false, false]);
+        if std::mem::take(&mut self.scroll_to_cursor) {
+            let row = self.cursor / BYTES_PER_ROW;
+            let offset_rows = ui.ctx().memory(|m| m.data.get_temp::<(usize, usize)>(area_id.with("visible"))).unwrap_or((0, page_rows));
+            // 光标移出视口时才滚动
+            if row < offset_rows.0 || row >= offset_rows.1 {
+                let top = row.saturating_sub(page_rows / 2);
+                scroll = scroll.vertical_scroll_offset(top as f32 * (row_height + spacing));
+            }
+        }
+
+        let palette = crate::theme::palette(ui.ctx());
+        let visuals = ui.visuals().clone();
+        let mut clicked = None;
+        scroll.show_rows(ui, row_height, row_count, |ui, rows| {
+            ui.ctx().memory_mut(|m| m.data.insert_temp(area_id.with("visible"), (rows.start, rows.end.saturating_sub(1))));
+            let width = (OFFSET_CHARS + HEX_CHARS + BYTES_PER_ROW) as f32 * char_width;
+            for row in rows {
+                let (rect, response) = ui.allocate_exact_size(egui::vec2(width, row_height), egui::Sense::click());
+                let painter = ui.painter_at(rect);
+                let start = row * BYTES_PER_ROW;
+                let bytes = &self.data[start.min(self.data.len())..(start + BYTES_PER_ROW).min(self.data.len())];
+                let hex_x = |i: usize| rect.left() + (OFFSET_CHARS + i * 3 + usize::from(i >= 8)) as f32 * char_width;
+                let ascii_x = |i: usize| rect.left() + (OFFSET_CHARS + HEX_CHARS + i) as f32 * char_width;
+                let text_at = |x: f32, text: String, color: egui::Color32| {
+                    painter.text(egui::pos2(x, rect.center().y), egui::Align2::LEFT_CENTER, text, font_id.clone(), color);
+                };
+
+                text_at(rect.left(), format!("{:08X}", start), visuals.weak_text_color());
+                for (i, &byte) in bytes.iter().enumerate() {
+                    let index = start + i;
+                    let in_match = self.found.as_ref().is_some_and(|found| found.contains(&index));
+                    if in_match {
+                        let highlight = palette.warning.gamma_multiply(0.3);
+                        painter.rect_filled(egui::Rect::from_min_size(egui::pos2(hex_x(i), rect.top()), egui::vec2(char_width * 2.0, row_height)), 1.0, highlight);
+                        painter.rect_filled(egui::Rect::from_min_size(egui::pos2(ascii_x(i), rect.top()), egui::vec2(char_width, row_height)), 1.0, highlight);
+                    }
+                    if index == self.cursor {
+                        // 当前栏画实心底色，另一栏画边框
+                        let hex_rect = egui::Rect::from_min_size(egui::pos2(hex_x(i), rect.top()), egui::vec2(char_width * 2.0, row_height));
+                        let ascii_rect = egui::Rect::from_min_size(egui::pos2(ascii_x(i), rect.top()), egui::vec2(char_width, row_height));
+                        let (active, other) = if self.column == Column::Hex { (hex_rect, ascii_rect) } else { (ascii_rect, hex_rect) };
+                        let color = visuals.selection.bg_fill;
+                        painter.rect_filled(active, 1.0, if focused { color } else { color.gamma_multiply(0.5) });
+                        painter.rect_stroke(other, 1.0, egui::Stroke::new(1.0, color));
+                        if focused && self.column == Column::Hex && self.low_nibble {
+                            let x = hex_x(i) + char_width;
+                            painter.vline(x, rect.y_range(), egui::Stroke::new(1.5, visuals.text_color()));
+                        }
+                    }
+                    let color = if self.modified.contains(&index) {
+                        palette.modified
+                    } else if byte == 0 {
+                        visuals.weak_text_color()
+                    } else {
+                        visuals.text_color()
+                    };
+                    text_at(hex_x(i), format!("{:02X}", byte), color);
+                    let shown = if byte.is_ascii_graphic() || byte == b' ' { byte as char } else { '.' };
+                    text_at(ascii_x(i), shown.to_string(), if shown == '.' && byte != b'.' { visuals.weak_text_color() } else { color });
+                }
+
+                // 点击十六进制栏或 ASCII 栏中的字节移动光标
+                if let Some(pos) = response.interact_pointer_pos().filter(|_| response.clicked()) {
+                    let column_of = |x: f32| ((x - rect.left()) / char_width) as usize;
+                    let chars = column_of(pos.x);
+                    if (OFFSET_CHARS..OFFSET_CHARS + HEX_CHARS - 2).contains(&chars) {
+                        let offset = chars - OFFSET_CHARS;
+                        let i = if offset >= 8 * 3 + 1 { (offset - 1) / 3 } else { offset / 3 };
+                        clicked = Some((start + i.min(BYTES_PER_ROW - 1), Column::Hex));
+                    } else if chars >= OFFSET_CHARS + HEX_CHARS {
+                        clicked = Some((start + (chars - OFFSET_CHARS - HEX_CHARS).min(BYTES_PER_ROW - 1), Column::Ascii));
+                    }
+                }
+            }
+        });
+        if let Some((index, column)) = clicked {
+            if index < self.data.len() {
+                self.cursor = index;
+                self.column = column;
+                self.low_nibble = false;
+            }
+            ui.memory_mut(|m| m.request_focus(area_id));
+        }
+    }
+
+    fn on_context_menu(&mut self, ui: &mut Ui, control: &mut Vec<AppCommand>) {
+        if ui.add_enabled(self.is_dirty, egui::Button::new("💾 Save").shortcut_text("Ctrl+S")).clicked() {
+            self.save(control);
+            ui.close_menu();
+        }
+        if ui.add_enabled(!self.undo.is_empty(), egui::Button::new("↶ Undo").shortcut_text("Ctrl+Z")).clicked() {
+            self.undo_redo(false);
+            ui.close_menu();
+        }
+        if ui.add_enabled(!self.redo.is_empty(), egui::Button::new("↷ Redo").shortcut_text("Ctrl+Y")).clicked() {
+            self.undo_redo(true);
+            ui.close_menu();
+        }
+        ui.separator();
+        if ui.add(egui::Button::new("🔍 Find Bytes…").shortcut_text("Ctrl+F")).clicked() {
+            self.find_open = true;
+            self.find_focus = true;
+            ui.close_menu();
+        }
+    }
+
+    fn file_path(&self) -> Option<&Path> {
+        Some(&self.path)
+    }
+
+    fn box_clone(&self) -> Box<dyn TabInstance> {
+        Box::new(self.clone())
+    }
+}
@@ -0,0 +1,389 @@
+use std::collections::HashMap;
+use std::ops::Range;
+use std::sync::{Arc, OnceLock};
+use egui::text::{LayoutJob, LayoutSection, TextFormat};
+use egui::{Color32, FontId};
+use parking_lot::Mutex;
+use tree_sitter::{InputEdit, Language, Node, Parser, Point, Query, QueryCursor, StreamingIterator, Tree};
+use crate::theme::StatusPalette;
+
+// ----------------------------------------------------------------------------
+// tree-sitter 语法高亮：每个标签页保留一棵语法树，文本变化时先 `Tree::edit` 再增量解析，
+// 只对变化的区域重新执行高亮查询；支持语言注入（Markdown 代码块、HTML 中的脚本与样式）。
+// 颜色取自当前 egui 主题与状态配色；没有对应语法的语言由调用方回落到 syntect
+// ----------------------------------------------------------------------------
+
+/// 高亮查询中识别的作用域。捕获名选择最具体的匹配，例如 `function.method` 回落到 `function`
+const SCOPES: &[&str] = &[
+    "attribute",
+    "comment",
+    "constant",
+    "constant.builtin",
+    "constructor",
+    "embedded",
+    "escape",
+    "function",
+    "function.builtin",
+    "keyword",
+    "label",
+    "number",
+    "operator",
+    "property",
+    "punctuation",
+    "punctuation.special",
+    "string",
+    "string.special",
+    "tag",
+    "type",
+    "type.builtin",
+    "variable.builtin",
+    "variable.parameter",
+    "text.title",
+    "text.literal",
+    "text.uri",
+    "text.reference",
+    "text.emphasis",
+    "text.strong",
+];
+/// 不属于任何作用域的字节
+const NO_SCOPE: u8 = u8::MAX;
+/// 注入的最大嵌套层数（Markdown → 行内 Markdown → 代码块语言）
+const MAX_INJECTION_DEPTH: usize = 3;
+
+/// 当前主题下各作用域的颜色
+#[derive(Clone, PartialEq)]
+struct Theme {
+    dark: bool,
+    text: Color32,
+    weak: Color32,
+    strong: Color32,
+    palette: StatusPalette,
+}
+
+impl Theme {
+    fn new(ctx: &egui::Context, visuals: &egui::Visuals) -> Self {
+        Self {
+            dark: visuals.dark_mode,
+            text: visuals.text_color(),
+            weak: visuals.weak_text_color(),
+            strong: visuals.strong_text_color(),
+            palette: crate::theme::palette(ctx),
+        }
+    }
+
+    /// 作用域颜色；None 表示使用普通文字颜色
+    fn color(&self, scope: &str) -> Option<Color32> {
+        let palette = &self.palette;
+        let color = match scope {
+            "comment" | "punctuation.special" => return Some(self.weak),
+            "function" | "function.builtin" => return Some(self.strong),
+            "property" | "variable.parameter" => return Some(self.text.lerp_to_gamma(palette.info, 0.4)),
+            "keyword" | "text.title" | "tag" | "variable.builtin" => palette.info,
+            "string" | "text.literal" => palette.success,
+            "string.special" | "escape" | "text.uri" => palette.warning,
+            "number" | "constant" | "constant.builtin" => palette.untracked,
+            "type" | "type.builtin" | "constructor" => palette.success.lerp_to_gamma(palette.info, 0.5),
+            "attribute" | "label" | "text.reference" => palette.error.lerp_to_gamma(palette.info, 0.5),
+            _ => return None,
+        };
+        // 状态配色按深色背景设计，浅色主题下压暗以保持对比度
+        Some(if self.dark { color } else { color.lerp_to_gamma(Color32::BLACK, 0.45) })
+    }
+}
+
+/// 编辑器语言 ID 或注入的语言名（代码块的信息字符串）对应的语法
+fn grammar_name(language: &str) -> Option<&'static str> {
+    Some(match language.trim().to_ascii_lowercase().as_str() {
+        "rs" | "rust" => "rust",
+        "py" | "python" => "python",
+        "js" | "javascript" | "jsx" | "ts" | "typescript" => "javascript",
+        "json" => "json",
+        "c" | "h" => "c",
+        "html" | "htm" => "html",
+        "css" => "css",
+        "md" | "markdown" => "markdown",
+        "markdown_inline" => "markdown_inline",
+        _ => return None,
+    })
+}
+
+struct Grammar {
+    language: Language,
+    highlights: Query,
+    injections: Option<Query>,
+    /// 高亮查询中各捕获对应的作用域下标
+    capture_scopes: Vec<u8>,
+}
+
+/// 捕获名对应的作用域：逐级去掉最后一段，直到与某个识别的作用域相同
+fn capture_scope(name: &str) -> u8 {
+    let mut name = name;
+    loop {
+        if let Some(index) = SCOPES.iter().position(|scope| *scope == name) {
+            return index as u8;
+        }
+        match name.rfind('.') {
+            Some(dot) => name = &name[..dot],
+            None => return NO_SCOPE,
+        }
+    }
+}
+
+fn grammars() -> &'static HashMap<&'static str, Grammar> {
+    static GRAMMARS: OnceLock<HashMap<&'static str, Grammar>> = OnceLock::new();
+    GRAMMARS.get_or_init(|| {
+        let mut grammars = HashMap::new();
+        let mut add = |name: &'static str, language: Language, highlights: &str, injections: &str| {
+            // 查询与语法不兼容时跳过该语言，交给 syntect 着色
+            let Ok(highlights) = Query::new(&language, highlights) else { return };
+            let injections = Some(injections).filter(|q| !q.is_empty()).and_then(|q| Query::new(&language, q).ok());
+            let capture_scopes = highlights.capture_names().iter().map(|name| capture_scope(name)).collect();
+            grammars.insert(name, Grammar { language, highlights, injections, capture_scopes });
+        };
+        add("rust", tree_sitter_rust::LANGUAGE.into(), tree_sitter_rust::HIGHLIGHTS_QUERY, tree_sitter_rust::INJECTIONS_QUERY);
+        add("python", tree_sitter_python::LANGUAGE.into(), tree_sitter_python::HIGHLIGHTS_QUERY, "");
+        add("javascript", tree_sitter_javascript::LANGUAGE.into(), tree_sitter_javascript::HIGHLIGHT_QUERY, tree_sitter_javascript::INJECTIONS_QUERY);
+        add("json", tree_sitter_json::LANGUAGE.into(), tree_sitter_json::HIGHLIGHTS_QUERY, "");
+        add("c", tree_sitter_c::LANGUAGE.into(), tree_sitter_c::HIGHLIGHT_QUERY, "");
+        add("html", tree_sitter_html::LANGUAGE.into(), tree_sitter_html::HIGHLIGHTS_QUERY, tree_sitter_html::INJECTIONS_QUERY);
+        add("css", tree_sitter_css::LANGUAGE.into(), tree_sitter_css::HIGHLIGHTS_QUERY, "");
+        add("markdown", tree_sitter_md::LANGUAGE.into(), tree_sitter_md::HIGHLIGHT_QUERY_BLOCK, tree_sitter_md::INJECTION_QUERY_BLOCK);
+        add("markdown_inline", tree_sitter_md::INLINE_LANGUAGE.into(), tree_sitter_md::HIGHLIGHT_QUERY_INLINE, tree_sitter_md::INJECTION_QUERY_INLINE);
+        grammars
+    })
+}
+
+// ----------------------------------------------------------------------------
+// 每个标签页的增量高亮状态
+// ----------------------------------------------------------------------------
+
+struct SyntaxState {
+    grammar: &'static str,
+    parser: Parser,
+    /// 上次解析的文本及其语法树
+    source: String,
+    tree: Option<Tree>,
+    /// 每个字节所属的作用域（`SCOPES` 的下标）
+    scopes: Vec<u8>,
+    /// 按当前文本、字体与主题生成的排版任务
+    job: Option<(FontId, Theme, LayoutJob)>,
+}
+
+impl SyntaxState {
+    fn new(grammar: &'static str) -> Self {
+        let mut parser = Parser::new();
+        let _ = parser.set_language(&grammars()[grammar].language);
+        Self { grammar, parser, source: String::new(), tree: None, scopes: Vec::new(), job: None }
+    }
+
+    /// 把语法树同步到新文本，重新着色变化的区域
+    fn update(&mut self, code: &str) {
+        if self.tree.is_some() && self.source == code {
+            return;
+        }
+        self.job = None;
+        let edit = self.tree.as_ref().and_then(|_| input_edit(&self.source, code));
+        let old_tree = self.tree.take().zip(edit).map(|(mut tree, edit)| {
+            tree.edit(&edit);
+            tree
+        });
+        self.source = code.to_owned();
+        let Some(tree) = self.parser.parse(code, old_tree.as_ref()) else {
+            self.scopes = vec![NO_SCOPE; code.len()];
+            return;
+        };
+
+        let dirty = match (&old_tree, edit) {
+            (Some(old_tree), Some(edit)) => {
+                let inserted = edit.new_end_byte - edit.start_byte;
+                self.scopes.splice(edit.start_byte..edit.old_end_byte, std::iter::repeat_n(NO_SCOPE, inserted));
+                let mut ranges: Vec<Range<usize>> = old_tree.changed_ranges(&tree).map(|r| r.start_byte..r.end_byte).collect();
+                ranges.push(edit.start_byte..edit.new_end_byte);
+                merge_lines(code, ranges)
+            }
+            _ => {
+                self.scopes = vec![NO_SCOPE; code.len()];
+                let whole = 0..code.len();
+                vec![whole]
+            }
+        };
+        let mut injection_parser = Parser::new();
+        for range in dirty {
+            self.scopes[range.clone()].fill(NO_SCOPE);
+            paint(&grammars()[self.grammar], tree.root_node(), code, range, &mut self.scopes, &mut injection_parser, 0);
+        }
+        self.tree = Some(tree);
+    }
+
+    fn layout_job(&mut self, font_id: &FontId, theme: &Theme) -> LayoutJob {
+        if let Some((cached_font, cached_theme, job)) = &self.job {
+            if cached_font == font_id && cached_theme == theme {
+                return job.clone();
+            }
+        }
+        let code = &self.source;
+        let mut job = LayoutJob { text: code.clone(), ..Default::default() };
+        let mut start = 0;
+        while start < code.len() {
+            let scope = self.scopes[start];
+            let end = self.scopes[start..].iter().position(|s| *s != scope).map_or(code.len(), |n| start + n);
+            let name = SCOPES.get(scope as usize).copied();
+            let color = name.and_then(|name| theme.color(name)).unwrap_or(theme.text);
+            let mut format = TextFormat::simple(font_id.clone(), color);
+            format.italics = matches!(name, Some("comment" | "text.emphasis"));
+            job.sections.push(LayoutSection { leading_space: 0.0, byte_range: start..end, format });
+            start = end;
+        }
+        self.job = Some((font_id.clone(), theme.clone(), job.clone()));
+        job
+    }
+}
+
+/// 标签页持有的高亮缓存。克隆出的标签页（复制、移入独立窗口）重新解析，不共用语法树
+#[derive(Default)]
+pub struct SyntaxCache(Arc<Mutex<Option<SyntaxState>>>);
+
+impl Clone for SyntaxCache {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl std::fmt::Debug for SyntaxCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SyntaxCache").finish_non_exhaustive()
+    }
+}
+
+impl SyntaxCache {
+    /// 与本缓存共用状态的句柄，供排版回调使用
+    pub fn share(&self) -> Self {
+        Self(self.0.clone())
+    }
+
+    /// 高亮代码；该语言没有 tree-sitter 语法时返回 None
+    pub fn highlight(&self, ui: &egui::Ui, code: &str, language: &str) -> Option<LayoutJob> {
+        let grammar = grammar_name(language).filter(|name| grammars().contains_key(name))?;
+        let font_id = egui::TextStyle::Monospace.resolve(ui.style());
+        let theme = Theme::new(ui.ctx(), ui.visuals());
+        let mut state = self.0.lock();
+        let state = match &mut *state {
+            Some(state) if state.grammar == grammar => state,
+            slot => slot.insert(SyntaxState::new(grammar)),
+        };
+        state.update(code);
+        Some(state.layout_job(&font_id, &theme))
+    }
+}
+
+/// 旧文本到新文本的编辑：去掉相同的前缀与后缀后剩下的区间；文本相同时返回 None
+fn input_edit(old: &str, new: &str) -> Option<InputEdit> {
+    if old == new {
+        return None;
+    }
+    let mut prefix = old.bytes().zip(new.bytes()).take_while(|(a, b)| a == b).count();
+    while !new.is_char_boundary(prefix) || !old.is_char_boundary(prefix) {
+        prefix -= 1;
+    }
+    let max_suffix = old.len().min(new.len()) - prefix;
+    let mut suffix = old.bytes().rev().zip(new.bytes().rev()).take(max_suffix).take_while(|(a, b)| a == b).count();
+    while !old.is_char_boundary(old.len() - suffix) || !new.is_char_boundary(new.len() - suffix) {
+        suffix -= 1;
+    }
+    let start_position = point_at(new, prefix);
+    Some(InputEdit {
+        start_byte: prefix,
+        old_end_byte: old.len() - suffix,
+        new_end_byte: new.len() - suffix,
+        start_position,
+        old_end_position: advance(start_position, &old[prefix..old.len() - suffix]),
+        new_end_position: advance(start_position, &new[prefix..new.len() - suffix]),
+    })
+}
+
+fn point_at(text: &str, byte: usize) -> Point {
+    advance(Point::new(0, 0), &text[..byte])
+}
+
+/// 从 `point` 开始经过 `text` 之后的位置（列以字节计）
+fn advance(point: Point, text: &str) -> Point {
+    match text.rfind('\n') {
+        Some(last) => Point::new(point.row + text.matches('\n').count(), text.len() - last - 1),
+        None => Point::new(point.row, point.column + text.len()),
+    }
+}
+
+/// 把区间扩展到整行并合并重叠的区间
+fn merge_lines(code: &str, mut ranges: Vec<Range<usize>>) -> Vec<Range<usize>> {
+    for range in &mut ranges {
+        range.start = code[..range.start].rfind('\n').map_or(0, |i| i + 1);
+        range.end = code[range.end..].find('\n').map_or(code.len(), |i| range.end + i + 1);
+    }
+    ranges.sort_by_key(|r| r.start);
+    let mut merged: Vec<Range<usize>> = Vec::new();
+    for range in ranges {
+        match merged.last_mut() {
+            Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+            _ => merged.push(range),
+        }
+    }
+    merged
+}
+
+/// 在 `range` 内执行高亮查询并写入作用域，注入的语言覆盖在外层语言之上
+fn paint(grammar: &Grammar, root: Node, code: &str, range: Range<usize>, scopes: &mut [u8], parser: &mut Parser, depth: usize) {
+    let mut cursor = QueryCursor::new();
+    cursor.set_byte_range(range.clone());
+    let mut captures = cursor.captures(&grammar.highlights, root, code.as_bytes());
+    // 同一节点匹配多个模式时取查询中靠前的一个
+    let mut last_node = None;
+    while let Some((m, index)) = captures.next() {
+        let capture = m.captures[*index];
+        let scope = grammar.capture_scopes[capture.index as usize];
+        if scope == NO_SCOPE || last_node == Some(capture.node.id()) {
+            continue;
+        }
+        last_node = Some(capture.node.id());
+        let start = capture.node.start_byte().max(range.start);
+        let end = capture.node.end_byte().min(range.end);
+        if start < end {
+            scopes[start..end].fill(scope);
+        }
+    }
+
+    let Some(injections) = grammar.injections.as_ref().filter(|_| depth < MAX_INJECTION_DEPTH) else { return };
+    let mut layers = Vec::new();
+    let mut cursor = QueryCursor::new();
+    cursor.set_byte_range(range.clone());
+    let mut matches = cursor.matches(injections, root, code.as_bytes());
+    while let Some(m) = matches.next() {
+        let mut language = injections
+            .property_settings(m.pattern_index)
+            .iter()
+            .find(|p| &*p.key == "injection.language")
+            .and_then(|p| p.value.as_deref());
+        let mut content = None;
+        for capture in m.captures {
+            match injections.capture_names()[capture.index as usize] {
+                "injection.language" => language = capture.node.utf8_text(code.as_bytes()).ok(),
+                "injection.content" => content = Some(capture.node.range()),
+                _ => {}
+            }
+        }
+        if let (Some(name), Some(content)) = (language.and_then(grammar_name), content) {
+            layers.push((name, content));
+        }
+    }
+    for (name, content) in layers {
+        let Some(injected) = grammars().get(name) else { continue };
+        if parser.set_language(&injected.language).is_err() || parser.set_included_ranges(&[content]).is_err() {
+            continue;
+        }
+        let Some(tree) = parser.parse(code, None) else { continue };
+        let start = content.start_byte.max(range.start);
+        let end = content.end_byte.min(range.end);
+        if start < end {
+            paint(injected, tree.root_node(), code, start..end, scopes, parser, depth + 1);
+        }
+    }
+}
@@ -0,0 +1,214 @@
+// ----------------------------------------------------------------------------
+// 撤销 / 重做历史
+// ----------------------------------------------------------------------------
+
+/// 最多保留的撤销步数
+const MAX_UNDO: usize = 500;
+/// 间隔不超过该时长（秒）的连续输入合并为一步
+const COALESCE_SECS: f64 = 1.0;
+
+/// 光标与选区：(主光标, 另一端) 的字符索引
+pub type Selection = (usize, usize);
+
+/// 一次编辑带来的行号变化：从第 `line` 行（0 起始）开始，`removed` 个换行符被替换为 `inserted` 个
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineShift {
+    pub line: usize,
+    pub removed: usize,
+    pub inserted: usize,
+}
+
+impl LineShift {
+    fn new(text: &str, start: usize, removed: &str, inserted: &str) -> Self {
+        Self {
+            line: text[..start].matches('\n').count(),
+            removed: removed.matches('\n').count(),
+            inserted: inserted.matches('\n').count(),
+        }
+    }
+
+    /// 编辑前的行号在编辑后的位置；被删除的行并入编辑所在的范围
+    pub fn apply(&self, line: usize) -> usize {
+        if line <= self.line {
+            line
+        } else if line > self.line + self.removed {
+            line + self.inserted - self.removed
+        } else {
+            line.min(self.line + self.inserted)
+        }
+    }
+}
+
+/// 一次编辑：在字节偏移 `start` 处把 `removed` 替换为 `inserted`
+#[derive(Debug, Clone)]
+struct Edit {
+    id: u64,
+    start: usize,
+    removed: String,
+    inserted: String,
+    before: Option<Selection>,
+    after: Option<Selection>,
+    time: f64,
+}
+
+/// 标签页自己的编辑历史，不依赖 TextEdit 的内部状态，因此在布局变化、保存之后依然有效
+#[derive(Debug, Clone, Default)]
+pub struct EditHistory {
+    undo: Vec<Edit>,
+    redo: Vec<Edit>,
+    /// 最近一次记录时的文本，与新文本比较得出编辑内容
+    baseline: String,
+    next_id: u64,
+    /// 保存时栈顶编辑的 id，0 表示原始内容
+    saved: u64,
+    /// 禁止下一次编辑与栈顶合并（保存、撤销之后）
+    sealed: bool,
+    /// 尚未取走的行号变化，书签据此跟随编辑移动
+    line_shifts: Vec<LineShift>,
+}
+
+impl EditHistory {
+    pub fn new(text: &str) -> Self {
+        Self {
+            baseline: text.to_string(),
+            next_id: 1,
+            ..Default::default()
+        }
+    }
+
+    /// 内容被整体替换（加载、同步模式重新读取）时清空历史
+    pub fn reset(&mut self, text: &str) {
+        *self = Self::new(text);
+    }
+
+    /// 与上次记录的文本比较，记录一次编辑；连续输入或连续退格合并为一步
+    pub fn record(&mut self, text: &str, before: Option<Selection>, after: Option<Selection>, time: f64) {
+        let Some((start, removed, inserted)) = diff(&self.baseline, text) else { return };
+        self.line_shifts.push(LineShift::new(&self.baseline, start, &removed, &inserted));
+        self.baseline.replace_range(start..start + removed.len(), &inserted);
+        self.redo.clear();
+
+        if !self.sealed {
+            if let Some(top) = self.undo.last_mut().filter(|top| time - top.time <= COALESCE_SECS) {
+                if coalesce(top, start, &removed, &inserted) {
+                    top.after = after;
+                    top.time = time;
+                    return;
+                }
+            }
+        }
+        self.sealed = false;
+        let id = self.next_id;
+        self.next_id += 1;
+        self.undo.push(Edit { id, start, removed, inserted, before, after, time });
+        if self.undo.len() > MAX_UNDO {
+            self.undo.remove(0);
+        }
+    }
+
+    /// 撤销一步，返回撤销后应恢复的选区
+    pub fn undo(&mut self, text: &mut String) -> Option<Option<Selection>> {
+        let edit = self.undo.pop()?;
+        self.line_shifts.push(LineShift::new(text, edit.start, &edit.inserted, &edit.removed));
+        text.replace_range(edit.start..edit.start + edit.inserted.len(), &edit.removed);
+        self.baseline = text.clone();
+        self.sealed = true;
+        let selection = edit.before.or_else(|| {
+            let start = text[..edit.start].chars().count();
+            Some((start + edit.removed.chars().count(), start))
+        });
+        self.redo.push(edit);
+        Some(selection)
+    }
+
+    /// 重做一步，返回重做后的选区
+    pub fn redo(&mut self, text: &mut String) -> Option<Option<Selection>> {
+        let edit = self.redo.pop()?;
+        self.line_shifts.push(LineShift::new(text, edit.start, &edit.removed, &edit.inserted));
+        text.replace_range(edit.start..edit.start + edit.removed.len(), &edit.inserted);
+        self.baseline = text.clone();
+        self.sealed = true;
+        let selection = edit.after.or_else(|| {
+            let end = text[..edit.start + edit.inserted.len()].chars().count();
+            Some((end, end))
+        });
+        self.undo.push(edit);
+        Some(selection)
+    }
+
+    /// 取走上次调用以来的行号变化，按发生顺序排列
+    pub fn take_line_shifts(&mut self) -> Vec<LineShift> {
+        std::mem::take(&mut self.line_shifts)
+    }
+
+    pub fn can_undo(&self) -> bool { !self.undo.is_empty() }
+
+    pub fn can_redo(&self) -> bool { !self.redo.is_empty() }
+
+    /// 下一次编辑单独成为一步，不与之前的输入合并（例如保存时整理空白）
+    pub fn seal(&mut self) {
+        self.sealed = true;
+    }
+
+    /// 保存后调用；之后撤销 / 重做回到这一状态时标签页不再显示为已修改
+    pub fn mark_saved(&mut self) {
+        self.saved = self.current();
+        self.sealed = true;
+    }
+
+    pub fn is_saved(&self) -> bool {
+        self.saved == self.current()
+    }
+
+    fn current(&self) -> u64 {
+        self.undo.last().map_or(0, |edit| edit.id)
+    }
+}
+
+/// 连续输入（不跨单词边界、不含换行）或连续退格时把新编辑并入栈顶
+fn coalesce(top: &mut Edit, start: usize, removed: &str, inserted: &str) -> bool {
+    let single = |s: &str| s.chars().count() == 1 && !s.contains('\n');
+    if top.removed.is_empty() && removed.is_empty() && single(inserted) && start == top.start + top.inserted.len() {
+        // 输入空白时结束上一个单词
+        let word_break = inserted.chars().all(char::is_whitespace) && !top.inserted.ends_with(char::is_whitespace);
+        if word_break {
+            return false;
+        }
+        top.inserted.push_str(inserted);
+        return true;
+    }
+    if top.inserted.is_empty() && inserted.is_empty() && single(removed) && start + removed.len() == top.start {
+        top.start = start;
+        top.removed.insert_str(0, removed);
+        return true;
+    }
+    false
+}
+
+/// 比较新旧文本的公共前缀与后缀，返回 (字节偏移, 删除的文本, 插入的文本)；相同时返回 None
+fn diff(old: &str, new: &str) -> Option<(usize, String, String)> {
+    if old == new {
+        return None;
+    }
+    let prefix = old.char_indices()
+        .zip(new.chars())
+        .find(|((_, a), b)| a != b)
+        .map_or(old.len().min(new.len()), |((i, _), _)| i);
+    let max_suffix = old.len().min(new.len()) - prefix;
+    let suffix = old[prefix..].chars().rev()
+        .zip(new[prefix..].chars().rev())
+        .take_while(|(a, b)| a == b)
+        .map(|(a, _)| a.len_utf8())
+        .scan(0, |total, len| {
+            *total += len;
+            Some(*total)
+        })
+        .take_while(|total| *total <= max_suffix)
+        .last()
+        .unwrap_or(0);
+    Some((
+        prefix,
+        old[prefix..old.len() - suffix].to_string(),
+        new[prefix..new.len() - suffix].to_string(),
+    ))
+}
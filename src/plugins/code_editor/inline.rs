@@ -0,0 +1,135 @@
+use std::ops::Range;
+use egui::Color32;
+
+/// 颜色色块在文本中预留的宽度
+pub const SWATCH_WIDTH: f32 = 14.0;
+
+/// 编辑器内联装饰：可点击的链接或颜色字面量
+#[derive(Debug, Clone, PartialEq)]
+pub struct InlineItem {
+    /// 在源码中的字节范围（用于替换文本）
+    pub bytes: Range<usize>,
+    /// 字符范围（用于定位 galley 中的光标）
+    pub chars: Range<usize>,
+    pub kind: InlineKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum InlineKind {
+    Link(String),
+    Color(Color32),
+}
+
+/// 仅在样式 / 配置类文件中识别十六进制颜色
+pub fn supports_colors(language: &str) -> bool {
+    matches!(language, "css" | "html" | "toml" | "json")
+}
+
+/// 扫描链接与颜色字面量。链接在注释和字符串里最常见，这里不区分上下文直接识别
+pub fn scan(code: &str, language: &str) -> Vec<InlineItem> {
+    let with_colors = supports_colors(language);
+    let mut items = Vec::new();
+    let mut char_idx = 0;
+    let mut iter = code.char_indices().peekable();
+
+    while let Some((byte, c)) = iter.next() {
+        let rest = &code[byte..];
+        if c == 'h' && (rest.starts_with("https://") || rest.starts_with("http://")) {
+            let len = link_len(rest);
+            if len > "https://".len() {
+                let text = &rest[..len];
+                let char_len = text.chars().count();
+                items.push(InlineItem {
+                    bytes: byte..byte + len,
+                    chars: char_idx..char_idx + char_len,
+                    kind: InlineKind::Link(text.to_string()),
+                });
+                // 跳过链接剩余部分
+                for _ in 1..char_len {
+                    iter.next();
+                }
+                char_idx += char_len;
+                continue;
+            }
+        } else if with_colors && c == '#' {
+            let prev_is_word = code[..byte].chars().next_back().is_some_and(|p| p.is_alphanumeric() || p == '&');
+            if !prev_is_word {
+                if let Some((len, color)) = color_literal(rest) {
+                    items.push(InlineItem {
+                        bytes: byte..byte + len,
+                        chars: char_idx..char_idx + len,
+                        kind: InlineKind::Color(color),
+                    });
+                }
+            }
+        }
+        char_idx += 1;
+    }
+    items
+}
+
+/// 链接结束于空白或常见的包裹字符，末尾的标点不算在内
+fn link_len(s: &str) -> usize {
+    let end = s
+        .find(|c: char| c.is_whitespace() || matches!(c, '"' | '\'' | '`' | '<' | '>' | ')' | ']' | '}'))
+        .unwrap_or(s.len());
+    s[..end].trim_end_matches(['.', ',', ';', ':', '!', '?']).len()
+}
+
+/// 解析 `#rgb` / `#rgba` / `#rrggbb` / `#rrggbbaa`，返回字面量字节长度
+fn color_literal(s: &str) -> Option<(usize, Color32)> {
+    let digits = s[1..].chars().take_while(|c| c.is_ascii_alphanumeric()).count();
+    if !matches!(digits, 3 | 4 | 6 | 8) {
+        return None;
+    }
+    parse_hex_color(&s[..digits + 1]).map(|color| (digits + 1, color))
+}
+
+pub fn parse_hex_color(literal: &str) -> Option<Color32> {
+    let hex = literal.strip_prefix('#')?;
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let expand = |i: usize| u8::from_str_radix(&hex[i..i + 1], 16).ok().map(|v| v * 17);
+    let pair = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    match hex.len() {
+        3 => Some(Color32::from_rgb(expand(0)?, expand(1)?, expand(2)?)),
+        4 => Some(Color32::from_rgba_unmultiplied(expand(0)?, expand(1)?, expand(2)?, expand(3)?)),
+        6 => Some(Color32::from_rgb(pair(0)?, pair(2)?, pair(4)?)),
+        8 => Some(Color32::from_rgba_unmultiplied(pair(0)?, pair(2)?, pair(4)?, pair(6)?)),
+        _ => None,
+    }
+}
+
+/// 按原字面量的风格（大小写、是否带 alpha）格式化新颜色
+pub fn format_hex_color(color: Color32, original: &str) -> String {
+    let [r, g, b, a] = color.to_srgba_unmultiplied();
+    let with_alpha = matches!(original.len(), 5 | 9);
+    let mut hex = if with_alpha {
+        format!("#{:02x}{:02x}{:02x}{:02x}", r, g, b, a)
+    } else {
+        format!("#{:02x}{:02x}{:02x}", r, g, b)
+    };
+    if original.chars().any(|c| c.is_ascii_uppercase()) {
+        hex = hex.to_uppercase();
+    }
+    hex
+}
+
+/// 在颜色字面量前插入留白，供绘制色块使用（文本内容不变，光标映射不受影响）
+pub fn reserve_swatch_space(job: &mut egui::text::LayoutJob, items: &[InlineItem]) {
+    for item in items.iter().filter(|i| matches!(i.kind, InlineKind::Color(_))) {
+        let start = item.bytes.start;
+        let Some(idx) = job.sections.iter().position(|s| s.byte_range.contains(&start)) else { continue };
+        let section = &mut job.sections[idx];
+        if section.byte_range.start == start {
+            section.leading_space += SWATCH_WIDTH;
+        } else {
+            let mut tail = section.clone();
+            section.byte_range.end = start;
+            tail.byte_range.start = start;
+            tail.leading_space = SWATCH_WIDTH;
+            job.sections.insert(idx + 1, tail);
+        }
+    }
+}
@@ -0,0 +1,164 @@
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use parking_lot::{Mutex, RwLock};
+
+// ----------------------------------------------------------------------------
+// 大文件只读视图：后台建立行索引，只读取并绘制视口内的行
+// ----------------------------------------------------------------------------
+
+/// 超过该大小的文件以只读视图打开，不再整体读入内存
+pub const READ_ONLY_BYTES: u64 = 64 * 1024 * 1024;
+/// 超过该大小的文件在 rope 编辑器中打开，只排版可见的行，不再交给 TextEdit
+pub const ROPE_BYTES: usize = 1024 * 1024;
+/// 超过该大小的文件在编辑器中关闭语法高亮与内联装饰，避免每帧重新着色
+pub const HIGHLIGHT_LIMIT_BYTES: usize = 1024 * 1024;
+/// 单行最多显示的字节数，超长的行（压缩过的 JSON、日志）截断显示
+const MAX_LINE_BYTES: usize = 4096;
+
+struct Inner {
+    path: PathBuf,
+    len: u64,
+    file: Mutex<File>,
+    /// 各行的起始字节偏移，由索引线程逐步追加
+    line_starts: RwLock<Vec<u64>>,
+    indexed: AtomicU64,
+    done: AtomicBool,
+}
+
+/// 以只读方式打开的大文件，克隆的标签页共用同一份索引
+#[derive(Clone)]
+pub struct LargeFile {
+    inner: Arc<Inner>,
+}
+
+impl std::fmt::Debug for LargeFile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LargeFile")
+            .field("path", &self.inner.path)
+            .field("len", &self.inner.len)
+            .finish()
+    }
+}
+
+impl LargeFile {
+    /// 打开文件并在后台建立行索引；索引完成前已经可以浏览已索引的部分
+    pub fn open(path: &Path) -> std::io::Result<Self> {
+        let file = File::open(path)?;
+        let len = file.metadata()?.len();
+        let inner = Arc::new(Inner {
+            path: path.to_path_buf(),
+            len,
+            file: Mutex::new(file),
+            line_starts: RwLock::new(vec![0]),
+            indexed: AtomicU64::new(0),
+            done: AtomicBool::new(false),
+        });
+
+        let indexer = inner.clone();
+        let reader = File::open(path)?;
+        std::thread::spawn(move || {
+            let mut reader = BufReader::with_capacity(1 << 20, reader);
+            let mut buf = vec![0u8; 1 << 20];
+            let mut offset = 0u64;
+            let mut batch = Vec::new();
+            loop {
+                let n = match reader.read(&mut buf) {
+                    Ok(0) | Err(_) => break,
+                    Ok(n) => n,
+                };
+                batch.extend(buf[..n].iter().enumerate().filter(|(_, b)| **b == b'\n').map(|(i, _)| offset + i as u64 + 1));
+                offset += n as u64;
+                indexer.line_starts.write().append(&mut batch);
+                indexer.indexed.store(offset, Ordering::Relaxed);
+                // 所有标签页都已关闭
+                if Arc::strong_count(&indexer) == 1 {
+                    return;
+                }
+            }
+            // 文件以换行结尾时最后一个"行首"后面没有内容
+            let mut starts = indexer.line_starts.write();
+            if starts.len() > 1 && starts.last() == Some(&offset) {
+                starts.pop();
+            }
+            drop(starts);
+            indexer.done.store(true, Ordering::Relaxed);
+        });
+        Ok(Self { inner })
+    }
+
+    pub fn len(&self) -> u64 {
+        self.inner.len
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.inner.line_starts.read().len()
+    }
+
+    /// 索引进度（0..1），完成后为 None
+    pub fn progress(&self) -> Option<f32> {
+        if self.inner.done.load(Ordering::Relaxed) {
+            return None;
+        }
+        Some(self.inner.indexed.load(Ordering::Relaxed) as f32 / self.inner.len.max(1) as f32)
+    }
+
+    /// 读取若干行的文本；无效的 UTF-8 以替换字符显示，超长的行被截断
+    pub fn lines(&self, range: Range<usize>) -> Vec<String> {
+        let (spans, end) = {
+            let starts = self.inner.line_starts.read();
+            let range = range.start.min(starts.len())..range.end.min(starts.len());
+            let end = starts.get(range.end).copied().unwrap_or_else(|| {
+                if self.inner.done.load(Ordering::Relaxed) { self.inner.len } else { self.inner.indexed.load(Ordering::Relaxed) }
+            });
+            (starts[range].to_vec(), end)
+        };
+        let mut file = self.inner.file.lock();
+        spans
+            .iter()
+            .enumerate()
+            .map(|(i, &start)| {
+                let line_end = spans.get(i + 1).copied().unwrap_or(end);
+                // 超长的行只读取需要显示的部分
+                let mut bytes = vec![0u8; (line_end - start).min(MAX_LINE_BYTES as u64 + 2) as usize];
+                if file.seek(SeekFrom::Start(start)).and_then(|_| file.read_exact(&mut bytes)).is_err() {
+                    return String::new();
+                }
+                let line = bytes.strip_suffix(b"\n").unwrap_or(&bytes);
+                let line = line.strip_suffix(b"\r").unwrap_or(line);
+                if line.len() > MAX_LINE_BYTES {
+                    format!("{} …", String::from_utf8_lossy(&line[..MAX_LINE_BYTES]))
+                } else {
+                    String::from_utf8_lossy(line).into_owned()
+                }
+            })
+            .collect()
+    }
+}
+
+/// 绘制只读视图：只排版视口内的行。`jump_to` 为需要滚动到的行（0 起始）
+pub fn show(ui: &mut egui::Ui, file: &LargeFile, jump_to: Option<usize>) {
+    let font_id = egui::TextStyle::Monospace.resolve(ui.style());
+    let row_height = ui.fonts(|f| f.row_height(&font_id));
+    let line_count = file.line_count();
+    let digits = line_count.to_string().len().max(2);
+    let weak = ui.visuals().weak_text_color();
+
+    let mut scroll = egui::ScrollArea::both().id_salt("large_file_scroll").auto_shrink([false, false]);
+    if let Some(line) = jump_to {
+        let offset = line.saturating_sub(3) as f32 * (row_height + ui.spacing().item_spacing.y);
+        scroll = scroll.vertical_scroll_offset(offset);
+    }
+    scroll.show_rows(ui, row_height, line_count, |ui, rows| {
+        let first = rows.start;
+        for (i, text) in file.lines(rows).into_iter().enumerate() {
+            ui.horizontal(|ui| {
+                ui.label(egui::RichText::new(format!("{:>width$}", first + i + 1, width = digits)).font(font_id.clone()).color(weak));
+                ui.add(egui::Label::new(egui::RichText::new(text).font(font_id.clone())).wrap_mode(egui::TextWrapMode::Extend));
+            });
+        }
+    });
+}
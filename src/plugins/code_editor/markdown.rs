@@ -0,0 +1,117 @@
+use std::hash::{Hash, Hasher};
+use crate::markdown::{self, Block, BlockKind};
+
+// ----------------------------------------------------------------------------
+// Markdown 预览：用共享的解析结果以 egui 富文本绘制在编辑器右侧，
+// 并与源码的滚动位置同步
+// ----------------------------------------------------------------------------
+
+/// 编辑器标签页中的预览状态
+#[derive(Debug, Clone, Default)]
+pub struct MarkdownPreview {
+    source_hash: u64,
+    blocks: Vec<Block>,
+    /// 上一帧各块顶部相对内容起点的纵坐标：(源码行, y)
+    block_tops: Vec<(usize, f32)>,
+    /// 最近一次同步时两侧对齐的源码行
+    synced_line: usize,
+    /// 下一帧预览需要滚动到的源码行
+    scroll_to_line: Option<usize>,
+}
+
+impl MarkdownPreview {
+    /// 编辑器滚动到新的首行时让预览跟随
+    pub fn follow_editor(&mut self, top_line: usize) {
+        if top_line != self.synced_line {
+            self.synced_line = top_line;
+            self.scroll_to_line = Some(top_line);
+        }
+    }
+}
+
+// ----------------------------------------------------------------------------
+// 绘制
+// ----------------------------------------------------------------------------
+
+fn block_ui(ui: &mut egui::Ui, block: &BlockKind) {
+    let body_size = egui::TextStyle::Body.resolve(ui.style()).size;
+    match block {
+        BlockKind::Heading(level, spans) => {
+            ui.add_space(4.0);
+            let scale = match level {
+                1 => 1.8,
+                2 => 1.5,
+                3 => 1.25,
+                4 => 1.1,
+                _ => 1.0,
+            };
+            markdown::spans_ui(ui, spans, Some(body_size * scale), None);
+            if *level <= 2 {
+                ui.separator();
+            }
+        }
+        BlockKind::Paragraph(spans) => markdown::spans_ui(ui, spans, None, None),
+        BlockKind::ListItem { depth, number, task, spans } => markdown::list_item_ui(ui, *depth, *number, *task, spans, None),
+        BlockKind::Quote(spans) => markdown::quote_ui(ui, spans, None),
+        BlockKind::Code { language, text } => {
+            egui::Frame::none()
+                .fill(ui.visuals().extreme_bg_color)
+                .rounding(4.0)
+                .inner_margin(8.0)
+                .show(ui, |ui| {
+                    ui.set_width(ui.available_width());
+                    if !language.is_empty() {
+                        ui.label(egui::RichText::new(language).small().weak());
+                    }
+                    ui.add(egui::Label::new(egui::RichText::new(text).monospace()).wrap_mode(egui::TextWrapMode::Extend));
+                });
+        }
+        BlockKind::Table { header, rows } => markdown::table_ui(ui, header, rows, None),
+        BlockKind::Rule => {
+            ui.separator();
+        }
+    }
+}
+
+/// 绘制预览。源码变化时重新解析；预览被滚动（指针在预览上）时返回其首个可见块的
+/// 源码行，供编辑器跟随
+pub fn show(ui: &mut egui::Ui, preview: &mut MarkdownPreview, code: &str) -> Option<usize> {
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    code.hash(&mut hasher);
+    let hash = hasher.finish();
+    if hash != preview.source_hash || preview.blocks.is_empty() {
+        preview.source_hash = hash;
+        preview.blocks = markdown::parse(code);
+    }
+
+    let mut scroll = egui::ScrollArea::vertical().id_salt("markdown_preview_scroll").auto_shrink([false, false]);
+    if let Some(line) = preview.scroll_to_line.take() {
+        let top = preview.block_tops.iter().rev().find(|(l, _)| *l <= line).map_or(0.0, |(_, y)| *y);
+        scroll = scroll.vertical_scroll_offset(top);
+    }
+    let output = scroll.show(ui, |ui| {
+        let origin = ui.min_rect().top();
+        let mut tops = Vec::with_capacity(preview.blocks.len());
+        for block in &preview.blocks {
+            tops.push((block.line, ui.cursor().top() - origin));
+            block_ui(ui, &block.kind);
+            ui.add_space(6.0);
+        }
+        if preview.blocks.is_empty() {
+            ui.weak("Nothing to preview.");
+        }
+        tops
+    });
+    preview.block_tops = output.inner;
+
+    if !ui.rect_contains_pointer(output.inner_rect) {
+        return None;
+    }
+    let offset = output.state.offset.y;
+    let line = preview.block_tops.iter().rev().find(|(_, y)| *y <= offset + 1.0).map_or(0, |(line, _)| *line);
+    if line == preview.synced_line {
+        return None;
+    }
+    preview.synced_line = line;
+    Some(line)
+}
@@ -1,50 +1,481 @@
 use egui::{Ui, WidgetText};
-use crate::{Tab, Plugin, AppCommand, TabInstance};
+use crate::{Tab, Plugin, AppCommand, TabInstance, OpenSupport};
+use crate::diff::LineChange;
+use crate::power::PowerProfile;
+use std::collections::BTreeSet;
 use std::sync::Arc;
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
+
+mod bookmarks;
+mod complete;
+mod config;
+mod cursors;
+mod editorconfig;
+mod elevated;
+mod encoding;
+mod find;
+mod format;
+mod goto;
+mod gutter;
+mod hex;
+mod highlight;
+mod history;
+mod inline;
+mod large;
+mod markdown;
+mod outline;
+mod rope;
+mod smart;
+mod watcher;
+mod whitespace;
+use bookmarks::{Bookmarks, BookmarksTab, SharedBookmarks};
+use complete::CompletionPopup;
+use config::{EditorConfig, Indentation};
+use elevated::Elevation;
+use encoding::{FileFormat, LineEnding, TextEncoding};
+use find::EditorFind;
+use goto::{GotoAction, GotoMode, GotoPopup};
+use hex::HexTab;
+use history::{EditHistory, Selection};
+use inline::{InlineItem, InlineKind};
+use large::LargeFile;
+use markdown::MarkdownPreview;
+use outline::Symbol;
+use rope::RopeEditor;
+use watcher::FileWatch;
+
+/// 映射扩展名到语法高亮 ID
+fn language_for_extension(ext: &str) -> &'static str {
+    match ext {
+        "rs" => "rs",
+        "py" => "py",
+        "js" | "ts" => "js",
+        "html" => "html",
+        "css" => "css",
+        "json" => "json",
+        "md" => "md",
+        "toml" => "toml",
+        "c" | "h" => "c",
+        "cpp" | "hpp" | "cc" | "cxx" => "cpp",
+        _ => "txt",
+    }
+}
+
+/// 代码块等处使用的语言名（如 "rust"、"python"）对应的语言，也接受扩展名
+fn language_for_name(name: &str) -> &'static str {
+    let name = name.trim().to_lowercase();
+    match name.as_str() {
+        "rust" => "rs",
+        "python" => "py",
+        "javascript" | "typescript" | "jsx" | "tsx" => "js",
+        "markdown" => "md",
+        "c++" => "cpp",
+        _ => language_for_extension(&name),
+    }
+}
+
+/// 跳转后目标行高亮的持续时间（秒）
+const JUMP_HIGHLIGHT_SECS: f32 = 1.5;
+
+/// 字符索引所在的行号（0 起始）
+fn line_of_char(text: &str, char_idx: usize) -> usize {
+    text.chars().take(char_idx).filter(|c| *c == '\n').count()
+}
+
+/// 指定行列对应的字符索引（列超出行尾时停在行尾）
+fn char_of_position(text: &str, line: usize, column: usize) -> usize {
+    let mut current_line = 0;
+    let mut line_start = 0;
+    for (i, c) in text.chars().enumerate() {
+        if current_line == line && (i - line_start == column || c == '\n') {
+            return i;
+        }
+        if c == '\n' {
+            current_line += 1;
+            line_start = i + 1;
+        }
+    }
+    text.chars().count()
+}
 
 #[derive(Debug, Clone)]
 enum EditorState {
-    Loading(Arc<RwLock<Option<Result<String, String>>>>),
+    Loading(Arc<RwLock<Option<Result<(String, FileFormat), String>>>>),
     Ready,
+    /// 超过 `large::READ_ONLY_BYTES` 的文件，以只读视图显示
+    ReadOnly(LargeFile),
+    /// 超过 `large::ROPE_BYTES` 的文件，文本保存在 rope 中，只排版可见的行
+    Large(RopeEditor),
     Error(String),
 }
 
+/// 文件在编辑器之外被修改，而标签页有未保存的修改时，等待用户选择
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ExternalChange {
+    Modified,
+    Deleted,
+}
+
+/// 当前进程对文件的写权限
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Protection {
+    Writable,
+    /// 没有写权限，以只读方式打开
+    Locked,
+    /// 没有写权限，但用户选择继续编辑，保存时需要提权
+    Unlocked,
+}
+
+/// 标签页缩进方式的来源，显示在状态栏的提示中
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum IndentSource {
+    Settings,
+    Detected,
+    EditorConfig,
+    /// 在状态栏中手动修改
+    Manual,
+}
+
+impl IndentSource {
+    fn description(self) -> &'static str {
+        match self {
+            IndentSource::Settings => "from the language settings",
+            IndentSource::Detected => "detected from the file content",
+            IndentSource::EditorConfig => "from .editorconfig",
+            IndentSource::Manual => "set for this tab",
+        }
+    }
+}
+
+/// 在后台运行的格式化命令
+#[derive(Debug, Clone)]
+struct Formatting {
+    /// 开始时的文本；完成前内容被修改则放弃结果
+    original: String,
+    result: Arc<RwLock<Option<Result<String, String>>>>,
+    /// 由保存触发，完成后写入文件
+    then_save: bool,
+}
+
+/// 正在通过取色器编辑的颜色字面量
+#[derive(Debug, Clone)]
+struct ColorEdit {
+    bytes: std::ops::Range<usize>,
+    color: egui::Color32,
+    anchor: egui::Pos2,
+}
+
 #[derive(Debug, Clone)]
 pub struct CodeEditorTab {
     pub name: String,
     pub path: Option<std::path::PathBuf>,
     pub code: String,
     pub language: String,
+    /// 磁盘上的编码与换行符，保存时按此写回
+    format: FileFormat,
     pub is_dirty: bool,
     pub sync_mode: bool,
-    pub last_sync_time: f64,
+    last_autosave_time: f64,
+    /// 上一帧电源配置是否启用了自动保存，退出时据此决定是否写回
+    autosave_enabled: bool,
+    /// 与插件设置共享的编辑器设置
+    settings: Arc<Mutex<EditorConfig>>,
+    /// 换行与 Tab 使用的缩进，及其来源
+    indentation: Indentation,
+    indent_source: IndentSource,
+    /// 对该文件生效的 .editorconfig 属性
+    editorconfig: editorconfig::Properties,
+    /// 上一帧编辑器是否拥有焦点（且窗口处于前台），用于失去焦点时自动保存
+    had_focus: bool,
     state: EditorState,
+    protection: Protection,
+    /// 正在进行的提权保存，完成后写入结果（成功时为是否有字符无法用原编码表示）
+    elevated_save: Option<Arc<RwLock<Option<Result<bool, String>>>>>,
+    /// sudo 方式下在提示栏中输入的密码，使用后立即清空
+    sudo_password: String,
+    /// 大纲索引与内联装饰（链接、颜色）缓存，内容变化后置 stale 重新计算
+    outline: Vec<Symbol>,
+    inline_items: Vec<InlineItem>,
+    color_edit: Option<ColorEdit>,
+    outline_stale: bool,
+    /// 待跳转的位置 (行, 列)，从 0 开始，下一帧应用到光标
+    pending_jump: Option<(usize, usize)>,
+    /// 跳转后短暂高亮目标行：(字符索引, 开始时间)
+    jump_highlight: Option<(usize, f64)>,
+    /// 即时查找栏
+    find: Option<EditorFind>,
+    /// 跳到行 / 跳到符号弹窗
+    goto: Option<GotoPopup>,
+    /// 相对最近一次提交的行变化（由版本控制插件提供基准内容），及计算时使用的基准
+    line_changes: Vec<(usize, LineChange)>,
+    vcs_base: Option<Arc<String>>,
+    /// 设有断点的行（0 起始），供后续的调试器使用
+    breakpoints: BTreeSet<usize>,
+    /// 设有书签的行（0 起始）；有路径的文件与共享的书签同步并保存到工作区
+    bookmarks: BTreeSet<usize>,
+    bookmark_store: SharedBookmarks,
+    /// 上一帧主光标所在的行，供标签页右键菜单使用
+    cursor_line: usize,
+    /// 撤销 / 重做历史
+    history: EditHistory,
+    /// 右键菜单请求的撤销（false）或重做（true），在下一帧执行
+    pending_history: Option<bool>,
+    /// 由 `AppCommand::InsertText` 送来、在下一帧插入光标处的文本
+    pending_insert: Option<String>,
+    /// 由 `AppCommand::ApplyPatch` 送来的补丁，文件加载完成后依次应用
+    pending_patches: Vec<String>,
+    /// 补全弹窗
+    completion: Option<CompletionPopup>,
+    /// 正在运行的外部格式化
+    formatting: Option<Formatting>,
+    /// Markdown 文件的并排预览，关闭时为 None
+    preview: Option<MarkdownPreview>,
+    /// 增量语法高亮使用的语法树
+    syntax: highlight::SyntaxCache,
+    /// 保存时删除的行尾空白（字符范围），下一帧据此把光标移到整理后的位置
+    tidied: Vec<std::ops::Range<usize>>,
+    /// 主光标之外的光标（Ctrl + 点击、Ctrl + D、Alt + 拖动列选择）
+    extra_cursors: Vec<Selection>,
+    /// 外部修改监听；系统通知器不可用时按 `watcher_interval` 轮询修改时间
+    watch: Option<FileWatch>,
+    last_poll_time: f64,
+    disk_mtime: Option<std::time::SystemTime>,
+    external_change: Option<ExternalChange>,
 }
 
 impl CodeEditorTab {
-    fn new(name: String, path: Option<std::path::PathBuf>, code: String, language: String) -> Self {
-        Self {
+    fn new(
+        name: String,
+        path: Option<std::path::PathBuf>,
+        code: String,
+        language: String,
+        settings: Arc<Mutex<EditorConfig>>,
+        bookmark_store: SharedBookmarks,
+    ) -> Self {
+        let history = EditHistory::new(&code);
+        let preview = (language == "md" && settings.lock().markdown_preview).then(MarkdownPreview::default);
+        let bookmarks = path.as_deref().map(|p| bookmark_store.lock().lines(p)).unwrap_or_default();
+        let mut tab = Self {
             name,
             path,
             code,
             language,
+            format: FileFormat::default(),
             is_dirty: false,
             sync_mode: false,
-            last_sync_time: 0.0,
+            last_autosave_time: 0.0,
+            autosave_enabled: false,
+            settings,
+            indentation: Indentation::default(),
+            indent_source: IndentSource::Settings,
+            editorconfig: editorconfig::Properties::default(),
+            had_focus: false,
             state: EditorState::Ready,
+            protection: Protection::Writable,
+            elevated_save: None,
+            sudo_password: String::new(),
+            outline: Vec::new(),
+            inline_items: Vec::new(),
+            color_edit: None,
+            outline_stale: true,
+            pending_jump: None,
+            jump_highlight: None,
+            find: None,
+            goto: None,
+            line_changes: Vec::new(),
+            vcs_base: None,
+            bookmarks,
+            bookmark_store,
+            cursor_line: 0,
+            breakpoints: BTreeSet::new(),
+            history,
+            pending_history: None,
+            pending_insert: None,
+            pending_patches: Vec::new(),
+            completion: None,
+            formatting: None,
+            preview,
+            syntax: highlight::SyntaxCache::default(),
+            tidied: Vec::new(),
+            extra_cursors: Vec::new(),
+            watch: None,
+            last_poll_time: 0.0,
+            disk_mtime: None,
+            external_change: None,
+        };
+        tab.resolve_indentation();
+        tab
+    }
+
+    /// 确定缩进方式：.editorconfig 优先，其次是文件中已有的缩进，最后是语言设置
+    fn resolve_indentation(&mut self) {
+        let settings = self.settings.lock().clone();
+        self.editorconfig = match &self.path {
+            Some(path) if settings.use_editorconfig => editorconfig::properties(path),
+            _ => editorconfig::Properties::default(),
+        };
+        let configured = settings.indentation.get(&self.language).copied().unwrap_or(settings.default_indentation);
+        let detected = settings.detect_indentation.then(|| smart::detect_indentation(&self.code, configured.width)).flatten();
+        (self.indentation, self.indent_source) = match (self.editorconfig.indentation(configured), detected) {
+            (Some(indentation), _) => (indentation, IndentSource::EditorConfig),
+            (None, Some(indentation)) => (indentation, IndentSource::Detected),
+            (None, None) => (configured, IndentSource::Settings),
+        };
+    }
+
+    /// 按文件原有的编码与换行符写入；返回是否有字符无法用该编码表示
+    fn write_file(&self, path: &std::path::Path) -> std::io::Result<bool> {
+        let (bytes, lossy) = encoding::encode(&self.text(), self.format);
+        self.settings.lock().write(path, &bytes)?;
+        Ok(lossy)
+    }
+
+    /// 编辑器中的全部文本
+    fn text(&self) -> std::borrow::Cow<'_, str> {
+        match &self.state {
+            EditorState::Large(editor) => editor.text().into(),
+            _ => self.code.as_str().into(),
+        }
+    }
+
+    /// 内容已写入磁盘
+    fn mark_saved(&mut self) {
+        self.is_dirty = false;
+        self.history.mark_saved();
+        if let EditorState::Large(editor) = &mut self.state {
+            editor.mark_saved();
         }
     }
 
+    /// 同步模式、没有写权限或提权保存进行中时禁止编辑
+    fn editable(&self) -> bool {
+        !self.sync_mode && self.protection != Protection::Locked && self.elevated_save.is_none()
+    }
+
+    /// 静默保存（定时或失去焦点时），只在失败时提示；没有写权限的文件不自动保存
+    fn autosave(&mut self, control: &mut Vec<AppCommand>) {
+        if self.protection != Protection::Writable {
+            return;
+        }
+        let Some(path) = &self.path else { return };
+        match self.write_file(path) {
+            Ok(_) => self.mark_saved(),
+            Err(e) => control.push(AppCommand::Notify {
+                message: format!("Autosave failed: {}", e),
+                level: crate::NotificationLevel::Error,
+            }),
+        }
+    }
+
+    /// 保存成功后的提示，编码无法表示部分字符时改为警告
+    fn saved_notice(&self, message: String, lossy: bool, control: &mut Vec<AppCommand>) {
+        control.push(if lossy {
+            AppCommand::Notify {
+                message: format!("{} (some characters cannot be represented in {} and were replaced)", message, self.format.encoding.label()),
+                level: crate::NotificationLevel::Warning,
+            }
+        } else {
+            AppCommand::Notify { message, level: crate::NotificationLevel::Success }
+        });
+    }
+
+    /// 从共享的书签同步（书签列表中可能删除了书签），再让书签跟随编辑带来的行号变化移动
+    fn sync_bookmarks(&mut self) {
+        let shifts = self.history.take_line_shifts();
+        let mut store = self.bookmark_store.lock();
+        if let Some(path) = &self.path {
+            self.bookmarks = store.lines(path);
+        }
+        if shifts.is_empty() || self.bookmarks.is_empty() {
+            return;
+        }
+        for shift in shifts {
+            self.bookmarks = self.bookmarks.iter().map(|&line| shift.apply(line)).collect();
+        }
+        // 只更新内存，保存文件时再写入，使书签与磁盘上的内容一致
+        if let Some(path) = &self.path {
+            store.set(path, &self.bookmarks);
+        }
+    }
+
+    fn toggle_bookmark(&mut self, line: usize) {
+        if !self.bookmarks.remove(&line) {
+            self.bookmarks.insert(line);
+        }
+        if let Some(path) = &self.path {
+            let mut store = self.bookmark_store.lock();
+            store.set(path, &self.bookmarks);
+            store.save();
+        }
+    }
+
+    /// F2 / Shift + F2：跳到下一个 / 上一个书签，到头后从另一端继续
+    fn jump_to_bookmark(&mut self, current_line: usize, forward: bool) {
+        let target = if forward {
+            self.bookmarks.range(current_line + 1..).next().or(self.bookmarks.first())
+        } else {
+            self.bookmarks.range(..current_line).next_back().or(self.bookmarks.last())
+        };
+        if let Some(&line) = target {
+            self.pending_jump = Some((line, 0));
+        }
+    }
+
+    /// 保存前按设置整理空白，作为单独的一步记入历史
+    fn tidy_whitespace(&mut self) {
+        if !matches!(self.state, EditorState::Ready) {
+            return;
+        }
+        // .editorconfig 中的设置优先
+        let (trim, final_newline) = {
+            let settings = self.settings.lock();
+            (
+                self.editorconfig.trim_trailing_whitespace.unwrap_or(settings.trim_trailing_whitespace) && self.language != "md",
+                self.editorconfig.insert_final_newline.unwrap_or(settings.insert_final_newline),
+            )
+        };
+        let Some((tidied, removed)) = whitespace::tidy(&self.code, trim, final_newline) else { return };
+        self.code = tidied;
+        self.history.seal();
+        self.history.record(&self.code, None, None, 0.0);
+        self.is_dirty = true;
+        self.outline_stale = true;
+        for (primary, secondary) in &mut self.extra_cursors {
+            *primary = whitespace::map_position(&removed, *primary);
+            *secondary = whitespace::map_position(&removed, *secondary);
+        }
+        self.tidied = removed;
+    }
+
     fn save(&mut self, control: &mut Vec<AppCommand>) {
-        if let EditorState::Ready = self.state {
+        if let EditorState::Ready | EditorState::Large(_) = self.state {
+            if self.path.is_some() && !self.sync_mode {
+                self.tidy_whitespace();
+            }
             if let Some(path) = &self.path {
-                match std::fs::write(path, &self.code) {
-                    Ok(_) => {
-                        self.is_dirty = false;
+                // 没有写权限时改为提权保存；权限可能已在外部修改，先重新检查
+                if self.protection != Protection::Writable {
+                    if !elevated::is_writable(path) {
+                        self.save_elevated(control);
+                        return;
+                    }
+                    self.protection = Protection::Writable;
+                }
+                match self.write_file(path) {
+                    Ok(lossy) => {
+                        self.mark_saved();
+                        self.bookmark_store.lock().save();
+                        self.saved_notice(format!("Saved {}", self.name), lossy, control);
+                    }
+                    Err(e) if e.kind() == std::io::ErrorKind::PermissionDenied => {
+                        self.protection = Protection::Unlocked;
                         control.push(AppCommand::Notify {
-                            message: format!("Saved {}", self.name),
-                            level: crate::NotificationLevel::Success,
+                            message: format!(
+                                "Permission denied: {}. Use \"{}\" above the editor to save it.",
+                                self.name,
+                                Elevation::current().button_label().trim_start_matches("🛡 ")
+                            ),
+                            level: crate::NotificationLevel::Warning,
                         });
                     }
                     Err(e) => {
@@ -54,69 +485,1019 @@ impl CodeEditorTab {
                         });
                     }
                 }
-            } else {
-                self.save_as(control);
+            } else {
+                self.save_as(control);
+            }
+        }
+    }
+
+    fn save_as(&mut self, control: &mut Vec<AppCommand>) {
+        if let EditorState::Ready | EditorState::Large(_) = self.state {
+            if let Some(path) = rfd::FileDialog::new()
+                .set_file_name(&self.name)
+                .save_file() 
+            {
+                self.tidy_whitespace();
+                match self.write_file(&path) {
+                    Ok(lossy) => {
+                        self.path = Some(path.clone());
+                        self.protection = Protection::Writable;
+                        // 书签随内容带到新文件，原文件的书签保持不变
+                        {
+                            let mut store = self.bookmark_store.lock();
+                            store.set(&path, &self.bookmarks);
+                            store.save();
+                        }
+                        self.name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+                        self.mark_saved();
+                        
+                        // 根据新扩展名更新语言
+                        let ext = path.extension().and_then(|s| s.to_str()).unwrap_or("");
+                        self.language = language_for_extension(ext).to_string();
+                        self.outline_stale = true;
+                        self.resolve_indentation();
+                        self.saved_notice(format!("Saved as {}", self.name), lossy, control);
+                    }
+                    Err(e) => {
+                        control.push(AppCommand::Notify {
+                            message: format!("Save As failed: {}", e),
+                            level: crate::NotificationLevel::Error,
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    /// Ctrl + S 与菜单中的保存：开启了保存时格式化且配置了格式化命令时，先格式化再写入
+    fn request_save(&mut self, control: &mut Vec<AppCommand>) {
+        let format_on_save = self.settings.lock().format_on_save;
+        if format_on_save && self.path.is_some() && self.editable() && self.start_format(true) {
+            return;
+        }
+        self.save(control);
+    }
+
+    /// 在后台线程中运行该语言的格式化命令；没有配置命令时返回 false
+    fn start_format(&mut self, then_save: bool) -> bool {
+        let command_line = self.settings.lock().formatters.get(&self.language).cloned();
+        let Some(command_line) = command_line.filter(|c| !c.trim().is_empty()) else { return false };
+        if let Some(formatting) = &mut self.formatting {
+            formatting.then_save |= then_save;
+            return true;
+        }
+        let (code, path, language) = (self.code.clone(), self.path.clone(), self.language.clone());
+        let result = Arc::new(RwLock::new(None));
+        let result_clone = result.clone();
+        std::thread::spawn(move || {
+            let formatted = format::run(&command_line, &code, path.as_deref(), &language);
+            *result_clone.write() = Some(formatted);
+        });
+        self.formatting = Some(Formatting { original: self.code.clone(), result, then_save });
+        true
+    }
+
+    /// Shift + Alt + F 与菜单中的格式化
+    fn format_document(&mut self, control: &mut Vec<AppCommand>) {
+        if self.editable() && !self.start_format(false) {
+            control.push(AppCommand::Notify {
+                message: format!("No formatter configured for \"{}\" files (see the Code Editor settings).", self.language),
+                level: crate::NotificationLevel::Info,
+            });
+        }
+    }
+
+    /// 应用完成的格式化结果，光标按非空白字符映射到新文本中的对应位置
+    fn finish_format(&mut self, ctx: &egui::Context, editor_id: egui::Id, control: &mut Vec<AppCommand>) {
+        let Some(formatting) = self.formatting.take() else { return };
+        let result = formatting.result.read().clone();
+        let Some(result) = result else {
+            self.formatting = Some(formatting);
+            ctx.request_repaint_after(std::time::Duration::from_millis(100));
+            return;
+        };
+        match result {
+            Ok(_) if self.code != formatting.original => control.push(AppCommand::Notify {
+                message: "Formatting discarded: the file was edited while the formatter was running.".to_string(),
+                level: crate::NotificationLevel::Warning,
+            }),
+            Ok(formatted) if formatted != self.code => {
+                let mut state = egui::text_edit::TextEditState::load(ctx, editor_id).unwrap_or_default();
+                let before = state.cursor.char_range().map(|range| (range.primary.index, range.secondary.index));
+                let after = before.map(|(primary, secondary)| {
+                    (format::map_cursor(&self.code, &formatted, primary), format::map_cursor(&self.code, &formatted, secondary))
+                });
+                self.code = formatted;
+                self.extra_cursors.clear();
+                if let Some((primary, secondary)) = after {
+                    state.cursor.set_char_range(Some(egui::text::CCursorRange::two(
+                        egui::text::CCursor::new(secondary),
+                        egui::text::CCursor::new(primary),
+                    )));
+                    state.store(ctx, editor_id);
+                }
+                self.mark_edited(ctx.input(|i| i.time), before, after);
+            }
+            Ok(_) => {}
+            Err(e) => control.push(AppCommand::Notify {
+                message: format!("Format failed: {}", e),
+                level: crate::NotificationLevel::Error,
+            }),
+        }
+        // 格式化失败时仍然保存
+        if formatting.then_save {
+            self.save(control);
+        }
+    }
+
+    /// 以管理员身份保存，在后台线程中等待用户授权；sudo 方式需要先在提示栏中输入密码
+    fn save_elevated(&mut self, control: &mut Vec<AppCommand>) {
+        let Some(path) = self.path.clone() else { return };
+        if self.elevated_save.is_some() || !self.is_dirty {
+            return;
+        }
+        self.protection = Protection::Unlocked;
+        let elevation = Elevation::current();
+        if elevation == Elevation::SudoPassword && self.sudo_password.is_empty() {
+            control.push(AppCommand::Notify {
+                message: "Enter your password above the editor to save with sudo.".to_string(),
+                level: crate::NotificationLevel::Info,
+            });
+            return;
+        }
+        let (bytes, lossy) = encoding::encode(&self.text(), self.format);
+        let password = std::mem::take(&mut self.sudo_password);
+        let result_store = Arc::new(RwLock::new(None));
+        let result_store_clone = result_store.clone();
+        std::thread::spawn(move || {
+            let password = Some(password.as_str()).filter(|p| !p.is_empty());
+            let result = elevated::write(&path, &bytes, elevation, password).map(|_| lossy);
+            *result_store_clone.write() = Some(result);
+        });
+        self.elevated_save = Some(result_store);
+    }
+
+    /// 没有写权限时的提示栏：继续编辑、提权保存或另存为
+    fn protection_ui(&mut self, ui: &mut Ui, control: &mut Vec<AppCommand>) {
+        if self.protection == Protection::Writable {
+            return;
+        }
+        let elevation = Elevation::current();
+        ui.horizontal(|ui| {
+            let warning = crate::theme::palette(ui.ctx()).warning;
+            if self.protection == Protection::Locked {
+                ui.colored_label(warning, "🔒 You don't have permission to write this file, so it is opened read-only.");
+                if ui.button("Edit Anyway").on_hover_text("Saving will ask for elevated permissions").clicked() {
+                    self.protection = Protection::Unlocked;
+                }
+            } else {
+                ui.colored_label(warning, "🔒 You don't have permission to write this file; saving needs elevated permissions.");
+                if self.elevated_save.is_some() {
+                    ui.spinner();
+                    ui.label("Waiting for authorization…");
+                } else {
+                    let mut submit = false;
+                    if elevation == Elevation::SudoPassword {
+                        let response = ui.add(
+                            egui::TextEdit::singleline(&mut self.sudo_password)
+                                .password(true)
+                                .hint_text("sudo password")
+                                .desired_width(140.0),
+                        );
+                        submit = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                    }
+                    let ready = self.is_dirty && (elevation == Elevation::SystemPrompt || !self.sudo_password.is_empty());
+                    submit |= ui.add_enabled(ready, egui::Button::new(elevation.button_label())).clicked();
+                    if submit && ready {
+                        self.save_elevated(control);
+                    }
+                }
+            }
+            if ui.button("Save As…").clicked() {
+                self.save_as(control);
+            }
+        });
+        ui.separator();
+    }
+
+    /// 查找栏：Enter / Shift + Enter 在匹配间循环跳转，Esc 关闭并回到编辑器
+    fn find_bar_ui(&mut self, ui: &mut Ui, editor_id: egui::Id, control: &mut Vec<AppCommand>) {
+        let editable = self.editable();
+        let Some(find) = &mut self.find else { return };
+        let mut close = false;
+        let mut edited = false;
+        ui.horizontal(|ui| {
+            let toggle_icon = if find.replace_open { "⏷" } else { "⏵" };
+            if ui.small_button(toggle_icon).on_hover_text("Toggle Replace (Ctrl + H)").clicked() {
+                find.replace_open = !find.replace_open;
+            }
+            let response = ui.add(
+                egui::TextEdit::singleline(&mut find.query)
+                    .hint_text("Find")
+                    .desired_width(220.0),
+            );
+            if std::mem::take(&mut find.focus_pending) {
+                response.request_focus();
+            }
+            if response.lost_focus() {
+                if ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                    let backward = ui.input(|i| i.modifiers.shift);
+                    find.step(!backward);
+                    response.request_focus();
+                } else if ui.input(|i| i.key_pressed(egui::Key::Escape)) {
+                    close = true;
+                }
+            }
+
+            let options = &mut find.options;
+            ui.toggle_value(&mut options.case_sensitive, "Aa").on_hover_text("Match case");
+            ui.toggle_value(&mut options.whole_word, "ab").on_hover_text("Match whole word");
+            ui.toggle_value(&mut options.regex, ".*").on_hover_text("Use regular expression");
+
+            if let Some(error) = &find.error {
+                ui.colored_label(crate::theme::palette(ui.ctx()).error, "Invalid pattern").on_hover_text(error);
+            } else if !find.query.is_empty() {
+                if find.matches.is_empty() {
+                    ui.colored_label(crate::theme::palette(ui.ctx()).error, "No results");
+                } else {
+                    ui.label(format!("{} / {}", find.current + 1, find.matches.len()));
+                }
+            }
+            if ui.small_button("⬆").on_hover_text("Previous (Shift + Enter)").clicked() {
+                find.step(false);
+            }
+            if ui.small_button("⬇").on_hover_text("Next (Enter)").clicked() {
+                find.step(true);
+            }
+            if ui.small_button("✖").on_hover_text("Close (Esc)").clicked() {
+                close = true;
+            }
+        });
+
+        if find.replace_open {
+            ui.horizontal(|ui| {
+                // 与查找框对齐
+                ui.add_space(ui.spacing().interact_size.y + ui.spacing().item_spacing.x);
+                ui.add_enabled_ui(editable, |ui| {
+                    let hint = if find.options.regex { "Replace ($1 for groups)" } else { "Replace" };
+                    let response = ui.add(
+                        egui::TextEdit::singleline(&mut find.replacement)
+                            .hint_text(hint)
+                            .desired_width(220.0),
+                    );
+                    let submitted = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                    if submitted {
+                        response.request_focus();
+                    }
+                    if submitted || ui.button("Replace").on_hover_text("Replace current match (Enter)").clicked() {
+                        edited |= find.replace_current(&mut self.code);
+                    }
+                    if ui.button("Replace All").clicked() {
+                        let count = find.replace_all(&mut self.code);
+                        edited |= count > 0;
+                        control.push(AppCommand::Notify {
+                            message: format!("Replaced {} occurrence{}", count, if count == 1 { "" } else { "s" }),
+                            level: crate::NotificationLevel::Info,
+                        });
+                    }
+                });
+            });
+        }
+        ui.separator();
+
+        if edited {
+            self.mark_edited(ui.input(|i| i.time), None, None);
+        }
+        if close {
+            self.find = None;
+            ui.memory_mut(|m| m.request_focus(editor_id));
+        }
+    }
+
+    /// 收到外部修改通知后读取磁盘内容：与缓冲区相同（例如自己刚保存）时忽略，
+    /// 标签页没有未保存的修改或处于同步模式时直接重新加载，否则提示用户
+    fn check_disk(&mut self, time: f64) {
+        let Some(path) = &self.path else { return };
+        match encoding::read(path) {
+            Ok((content, _)) if content == self.text() => self.external_change = None,
+            Ok((content, format)) if !self.is_dirty || self.sync_mode => {
+                self.format = format;
+                self.reload(content, time);
+            }
+            Ok(_) => self.external_change = Some(ExternalChange::Modified),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => self.external_change = Some(ExternalChange::Deleted),
+            // 其它程序可能正在写入，等下一次通知
+            Err(_) => {}
+        }
+    }
+
+    /// 用磁盘内容替换缓冲区；作为一次编辑记入历史，可以撤销回重新加载之前的内容
+    fn reload(&mut self, content: String, time: f64) {
+        if let EditorState::Large(editor) = &mut self.state {
+            editor.replace_all(&content, time);
+            editor.mark_saved();
+            self.is_dirty = false;
+            self.external_change = None;
+            return;
+        }
+        self.code = content;
+        self.history.record(&self.code, None, None, time);
+        self.history.mark_saved();
+        self.is_dirty = false;
+        self.outline_stale = true;
+        self.external_change = None;
+    }
+
+    /// 定时自动保存（仅针对已有路径的文件，静默执行），并处理文件在磁盘上的修改
+    fn autosave_and_watch(&mut self, ui: &Ui, control: &mut Vec<AppCommand>) {
+        let profile = PowerProfile::get(ui.ctx());
+        self.autosave_enabled = profile.autosave_interval.is_some();
+        if let Some(interval) = profile.autosave_interval {
+            let current_time = ui.input(|i| i.time);
+            if !self.sync_mode && self.is_dirty && current_time - self.last_autosave_time >= interval.as_secs_f64() {
+                self.last_autosave_time = current_time;
+                self.autosave(control);
+            }
+            if self.is_dirty {
+                ui.ctx().request_repaint_after(interval);
+            }
+        }
+
+        // 外部修改：干净的标签页与同步模式自动重新加载，有未保存修改时询问（省电模式下暂停处理）
+        if self.watch.as_ref().map(|w| w.path()) != self.path.as_deref() {
+            self.watch = self.path.as_deref().map(|path| FileWatch::new(ui.ctx(), path));
+        }
+        if !profile.watchers_paused {
+            let current_time = ui.input(|i| i.time);
+            let changed = match &self.watch {
+                Some(watch) if watch.is_active() => watch.take_changed(),
+                Some(watch) => {
+                    let due = current_time - self.last_poll_time >= profile.watcher_interval.as_secs_f64();
+                    ui.ctx().request_repaint_after(profile.watcher_interval);
+                    due && {
+                        self.last_poll_time = current_time;
+                        let mtime = std::fs::metadata(watch.path()).and_then(|m| m.modified()).ok();
+                        std::mem::replace(&mut self.disk_mtime, mtime) != mtime
+                    }
+                }
+                None => false,
+            };
+            if changed {
+                self.check_disk(current_time);
+            }
+        }
+    }
+
+    /// 较大的文件：在 rope 中编辑，只排版可见的行；不提供高亮、大纲、查找与补全
+    fn large_editor_ui(&mut self, ui: &mut Ui, control: &mut Vec<AppCommand>) {
+        self.autosave_and_watch(ui, control);
+        let editable = self.editable();
+        let indent = self.indentation.unit();
+        let time = ui.input(|i| i.time);
+        let EditorState::Large(editor) = &mut self.state else { return };
+
+        if !self.sync_mode && ui.input_mut(|i| i.consume_key(egui::Modifiers::COMMAND, egui::Key::S)) {
+            self.save(control);
+            return;
+        }
+        if ui.rect_contains_pointer(ui.max_rect()) && ui.input_mut(|i| i.consume_key(egui::Modifiers::COMMAND, egui::Key::G)) {
+            self.goto = Some(GotoPopup::new(GotoMode::Line));
+        }
+        let (line, column) = editor.cursor_position();
+        if let Some(popup) = &mut self.goto {
+            let anchor = ui.max_rect().center_top() + egui::vec2(0.0, 8.0);
+            match goto::show(ui, popup, anchor, editor.line_count(), line, &[]) {
+                GotoAction::Jump(line, column) => {
+                    self.pending_jump = Some((line, column));
+                    self.goto = None;
+                }
+                GotoAction::Close => self.goto = None,
+                GotoAction::None => {}
+            }
+        }
+        if let Some((line, column)) = self.pending_jump.take() {
+            editor.jump(line, column);
+        }
+        if let Some(text) = self.pending_insert.take().filter(|_| editable) {
+            editor.insert(&text, time);
+            self.is_dirty = editor.is_modified();
+        }
+
+        egui::TopBottomPanel::bottom(ui.id().with("editor_status_bar"))
+            .show_inside(ui, |ui| self.status_bar_ui(ui, Some((line, column))));
+        let EditorState::Large(editor) = &mut self.state else { return };
+        ui.horizontal(|ui| {
+            let size_mb = editor.len_bytes() as f64 / (1024.0 * 1024.0);
+            ui.label(egui::RichText::new(format!(
+                "Large file ({:.1} MB, {} lines): syntax highlighting, outline, find and completion are disabled.",
+                size_mb,
+                editor.line_count()
+            )).weak());
+        });
+        ui.separator();
+        self.protection_ui(ui, control);
+        self.external_change_ui(ui);
+        let EditorState::Large(editor) = &mut self.state else { return };
+        if editor.show(ui, editable, &indent) {
+            self.is_dirty = editor.is_modified();
+        }
+    }
+
+    /// 外部修改提示栏
+    fn external_change_ui(&mut self, ui: &mut Ui) {
+        let Some(change) = self.external_change else { return };
+        ui.horizontal(|ui| {
+            let warning = crate::theme::palette(ui.ctx()).warning;
+            match change {
+                ExternalChange::Modified => {
+                    ui.colored_label(warning, "⚠ The file was changed on disk, and you have unsaved changes.");
+                    if ui.button("Reload").on_hover_text("Discard your changes (Ctrl + Z restores them)").clicked() {
+                        if let Some((content, format)) = self.path.as_ref().and_then(|p| encoding::read(p).ok()) {
+                            self.format = format;
+                            self.reload(content, ui.input(|i| i.time));
+                        }
+                    }
+                }
+                ExternalChange::Deleted => {
+                    ui.colored_label(warning, "⚠ The file was deleted on disk.");
+                }
+            }
+            if ui.button("Keep Mine").on_hover_text("Saving will overwrite the file on disk").clicked() {
+                self.external_change = None;
+                self.is_dirty = true;
+            }
+        });
+        ui.separator();
+    }
+
+    fn toggle_preview(&mut self) {
+        self.preview = match self.preview {
+            Some(_) => None,
+            None => Some(MarkdownPreview::default()),
+        };
+    }
+
+    /// 底部状态栏：光标位置、语言、编码与换行符；点击编码或换行符切换保存格式
+    fn status_bar_ui(&mut self, ui: &mut Ui, cursor: Option<(usize, usize)>) {
+        ui.horizontal(|ui| {
+            ui.spacing_mut().item_spacing.x = 12.0;
+            if let Some((line, column)) = cursor {
+                ui.label(format!("Ln {}, Col {}", line + 1, column + 1));
+            }
+            if self.formatting.is_some() {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label(egui::RichText::new("Formatting…").weak());
+                });
+            }
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                ui.label(egui::RichText::new(&self.language).weak());
+                if self.language == "md" {
+                    let mut shown = self.preview.is_some();
+                    if ui.toggle_value(&mut shown, "👁 Preview").on_hover_text("Toggle Markdown preview (Ctrl + Shift + V)").changed() {
+                        self.toggle_preview();
+                    }
+                }
+
+                ui.menu_button(self.indentation.label(), |ui| {
+                    ui.label(egui::RichText::new(format!("Indentation {}", self.indent_source.description())).weak());
+                    let mut indentation = self.indentation;
+                    ui.radio_value(&mut indentation.tabs, false, "Indent Using Spaces");
+                    ui.radio_value(&mut indentation.tabs, true, "Indent Using Tabs");
+                    ui.separator();
+                    for width in [2, 4, 8] {
+                        ui.radio_value(&mut indentation.width, width, format!("Width: {}", width));
+                    }
+                    if indentation != self.indentation {
+                        self.indentation = indentation;
+                        self.indent_source = IndentSource::Manual;
+                    }
+                }).response.on_hover_text(format!("Indentation {}", self.indent_source.description()));
+
+                let line_ending = self.format.line_ending;
+                ui.menu_button(line_ending.label(), |ui| {
+                    for option in [LineEnding::Lf, LineEnding::CrLf] {
+                        if ui.radio(line_ending == option, option.label()).clicked() && option != line_ending {
+                            self.format.line_ending = option;
+                            self.is_dirty = true;
+                            ui.close_menu();
+                        }
+                    }
+                }).response.on_hover_text("Line endings used when saving");
+
+                let current = self.format.encoding;
+                ui.menu_button(current.label(), |ui| {
+                    ui.label(egui::RichText::new("Save with Encoding").weak());
+                    for option in TextEncoding::ALL {
+                        if ui.radio(current == option, option.label()).clicked() && option != current {
+                            self.format.encoding = option;
+                            self.is_dirty = true;
+                            ui.close_menu();
+                        }
+                    }
+                    ui.separator();
+                    ui.add_enabled_ui(!self.is_dirty && self.path.is_some(), |ui| {
+                        ui.menu_button("Reopen with Encoding", |ui| {
+                            for option in TextEncoding::ALL {
+                                if ui.button(option.label()).clicked() {
+                                    self.reopen_with(option, ui.input(|i| i.time));
+                                    ui.close_menu();
+                                }
+                            }
+                        });
+                    });
+                }).response.on_hover_text("File encoding");
+            });
+        });
+    }
+
+    /// 用指定编码重新解码磁盘上的文件（编码识别错误时使用）
+    fn reopen_with(&mut self, encoding: TextEncoding, time: f64) {
+        let Some(bytes) = self.path.as_ref().and_then(|p| std::fs::read(p).ok()) else { return };
+        let (content, _) = encoding.decode(&bytes);
+        self.format.encoding = encoding;
+        self.reload(content.replace("\r\n", "\n"), time);
+    }
+
+    /// 内容被编辑后调用：记录撤销历史、标记为已修改并刷新大纲
+    fn mark_edited(&mut self, time: f64, before: Option<Selection>, after: Option<Selection>) {
+        self.history.record(&self.code, before, after, time);
+        if !self.is_dirty {
+            // 自动保存间隔从首次修改开始计时
+            self.last_autosave_time = time;
+        }
+        self.is_dirty = true;
+        self.outline_stale = true;
+    }
+
+    /// 在 TextEdit 处理输入之前执行编辑辅助（换行缩进、括号补全、Ctrl + / 注释），
+    /// 处理过的事件从输入队列中移除
+    fn smart_edit(&mut self, ui: &mut Ui, editor_id: egui::Id) {
+        if !self.editable() || !ui.memory(|m| m.has_focus(editor_id)) {
+            return;
+        }
+        let Some(mut state) = egui::text_edit::TextEditState::load(ui.ctx(), editor_id) else { return };
+        let Some(range) = state.cursor.char_range() else { return };
+        let events = ui.input(|i| i.events.clone());
+        // 输入法组字期间按键交给 TextEdit
+        if events.iter().any(|e| matches!(e, egui::Event::Ime(_))) {
+            return;
+        }
+
+        let before = (range.primary.index, range.secondary.index);
+        let mut selection = before;
+        let mut handled = Vec::new();
+        let mut edited = false;
+        let comment = smart::comment_syntax(&self.language);
+        for (idx, event) in events.iter().enumerate() {
+            let sorted = selection.0.min(selection.1)..selection.0.max(selection.1);
+            let len = self.code.len();
+            let (result, edits_text) = match event {
+                egui::Event::Text(text) => {
+                    let mut chars = text.chars();
+                    let result = match (chars.next(), chars.next()) {
+                        (Some(c), None) => smart::type_pair(&mut self.code, sorted, c),
+                        _ => None,
+                    };
+                    (result, true)
+                }
+                egui::Event::Key { key: egui::Key::Enter, pressed: true, modifiers, .. } if !modifiers.command && !modifiers.alt => {
+                    let cursor = smart::newline(&mut self.code, sorted, &self.language, &self.indentation.unit());
+                    (Some((cursor, cursor)), true)
+                }
+                egui::Event::Key { key: egui::Key::Tab, pressed: true, modifiers, .. } if modifiers.is_none() || modifiers.shift_only() => {
+                    (Some(smart::indent(&mut self.code, sorted, self.indentation, modifiers.shift)), true)
+                }
+                egui::Event::Key { key: egui::Key::Backspace, pressed: true, modifiers, .. } if modifiers.is_none() && sorted.is_empty() => {
+                    (smart::delete_pair(&mut self.code, sorted.start).map(|c| (c, c)), true)
+                }
+                egui::Event::Key { key: egui::Key::Slash, pressed: true, modifiers, .. } if modifiers.command_only() => {
+                    (comment.map(|syntax| smart::toggle_comment(&mut self.code, sorted, syntax)), true)
+                }
+                egui::Event::Key { key: egui::Key::Enter | egui::Key::Backspace | egui::Key::Delete | egui::Key::Tab, pressed: true, .. }
+                | egui::Event::Paste(_)
+                | egui::Event::Cut => (None, true),
+                _ => (None, false),
+            };
+            match result {
+                Some(new_selection) => {
+                    selection = new_selection;
+                    edited |= self.code.len() != len;
+                    handled.push(idx);
+                }
+                // 之后的事件必须在 TextEdit 处理完这一个之后才能处理，保持输入顺序
+                None if edits_text => break,
+                None => {}
+            }
+        }
+        if handled.is_empty() {
+            return;
+        }
+
+        let mut idx = 0;
+        ui.input_mut(|i| {
+            i.events.retain(|_| {
+                let keep = !handled.contains(&idx);
+                idx += 1;
+                keep
+            })
+        });
+        state.cursor.set_char_range(Some(egui::text::CCursorRange::two(
+            egui::text::CCursor::new(selection.1),
+            egui::text::CCursor::new(selection.0),
+        )));
+        state.store(ui.ctx(), editor_id);
+        if edited {
+            self.mark_edited(ui.input(|i| i.time), Some(before), Some(selection));
+        }
+    }
+
+    /// 补全弹窗的按键：Ctrl + Space 打开，弹窗打开时 Up / Down 选择、Enter / Tab 接受、Esc 关闭。
+    /// 需在 TextEdit 与编辑辅助之前消费
+    fn completion_keys(&mut self, ui: &mut Ui, editor_id: egui::Id) {
+        if !self.editable() || !ui.memory(|m| m.has_focus(editor_id)) {
+            return;
+        }
+        if ui.input_mut(|i| i.consume_key(egui::Modifiers::COMMAND, egui::Key::Space)) {
+            let cursor = egui::text_edit::TextEditState::load(ui.ctx(), editor_id)
+                .and_then(|state| state.cursor.char_range())
+                .map_or(0, |range| range.primary.index);
+            self.completion = Some(CompletionPopup::new(complete::word_start(&self.code, cursor), true));
+            self.refresh_completion(ui.ctx(), Some(cursor), false);
+        }
+        let Some(popup) = &mut self.completion else { return };
+        let (up, down, accept, close) = ui.input_mut(|i| (
+            i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp),
+            i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown),
+            i.consume_key(egui::Modifiers::NONE, egui::Key::Enter) || i.consume_key(egui::Modifiers::NONE, egui::Key::Tab),
+            i.consume_key(egui::Modifiers::NONE, egui::Key::Escape),
+        ));
+        if up || down {
+            popup.step(down);
+        }
+        if accept {
+            let selected = popup.selected;
+            self.accept_completion(ui, editor_id, selected);
+        } else if close {
+            self.completion = None;
+        }
+    }
+
+    /// 光标移动或内容变化后更新补全弹窗：输入单词字符且前缀足够长时自动打开，
+    /// 光标离开正在补全的单词或没有匹配的建议时关闭
+    fn refresh_completion(&mut self, ctx: &egui::Context, cursor: Option<usize>, typed: bool) {
+        let Some(cursor) = cursor else { return };
+        let start = complete::word_start(&self.code, cursor);
+        let prefix: String = self.code.chars().skip(start).take(cursor - start).collect();
+        let popup = match self.completion.take() {
+            Some(popup) if popup.start == start && (popup.manual || !prefix.is_empty()) => popup,
+            Some(_) => return,
+            None if typed && prefix.chars().count() >= complete::MIN_AUTO_PREFIX => CompletionPopup::new(start, false),
+            None => return,
+        };
+        if popup.prefix == prefix && !popup.items.is_empty() {
+            self.completion = Some(popup);
+            return;
+        }
+
+        let registered = crate::completion_providers(ctx);
+        let mut providers: Vec<&dyn crate::CompletionProvider> = registered.iter().map(|p| p.as_ref()).collect();
+        providers.push(&complete::Snippets);
+        providers.push(&complete::BufferWords);
+        let request = crate::CompletionRequest {
+            path: self.path.as_deref(),
+            language: &self.language,
+            text: &self.code,
+            cursor,
+            prefix: &prefix,
+            manual: popup.manual,
+        };
+        let items = complete::collect(&providers, &request);
+        if !items.is_empty() {
+            self.completion = Some(CompletionPopup { prefix, items, selected: 0, scroll_pending: true, ..popup });
+        }
+    }
+
+    /// 用选中的建议替换光标前的前缀
+    fn accept_completion(&mut self, ui: &mut Ui, editor_id: egui::Id, index: usize) {
+        let Some(popup) = self.completion.take() else { return };
+        let Some(item) = popup.items.get(index) else { return };
+        let mut state = egui::text_edit::TextEditState::load(ui.ctx(), editor_id).unwrap_or_default();
+        let Some(range) = state.cursor.char_range() else { return };
+        let cursor = range.primary.index.max(popup.start);
+
+        let (text, offset) = complete::expand(&item.insert_text, &self.code, popup.start);
+        let byte_of = |idx: usize| self.code.char_indices().nth(idx).map_or(self.code.len(), |(i, _)| i);
+        let bytes = byte_of(popup.start)..byte_of(cursor);
+        self.code.replace_range(bytes, &text);
+
+        let new_cursor = popup.start + offset;
+        state.cursor.set_char_range(Some(egui::text::CCursorRange::one(egui::text::CCursor::new(new_cursor))));
+        state.store(ui.ctx(), editor_id);
+        ui.memory_mut(|m| m.request_focus(editor_id));
+        self.mark_edited(ui.input(|i| i.time), Some((range.primary.index, range.secondary.index)), Some((new_cursor, new_cursor)));
+    }
+
+    /// 用 text 替换选中内容（没有选中时插入光标处），光标移到插入内容之后
+    fn insert_at_cursor(&mut self, ui: &mut Ui, editor_id: egui::Id, text: &str) {
+        let mut state = egui::text_edit::TextEditState::load(ui.ctx(), editor_id).unwrap_or_default();
+        let end = self.code.chars().count();
+        let (primary, secondary) = state.cursor.char_range().map_or((end, end), |r| (r.primary.index, r.secondary.index));
+        let (start, stop) = (primary.min(secondary), primary.max(secondary));
+        let byte_of = |idx: usize| self.code.char_indices().nth(idx).map_or(self.code.len(), |(i, _)| i);
+        let bytes = byte_of(start)..byte_of(stop);
+        self.code.replace_range(bytes, text);
+
+        let new_cursor = start + text.chars().count();
+        state.cursor.set_char_range(Some(egui::text::CCursorRange::one(egui::text::CCursor::new(new_cursor))));
+        state.store(ui.ctx(), editor_id);
+        ui.memory_mut(|m| m.request_focus(editor_id));
+        self.mark_edited(ui.input(|i| i.time), Some((primary, secondary)), Some((new_cursor, new_cursor)));
+    }
+
+    /// 应用补丁，作为一步可撤销的编辑；与当前内容不符时提示失败原因
+    fn apply_pending_patch(&mut self, ui: &Ui, patch: &str, control: &mut Vec<AppCommand>) {
+        let name = self.path.as_ref().and_then(|p| p.file_name()).map_or("untitled".into(), |n| n.to_string_lossy().to_string());
+        match crate::diff::apply_patch(&self.code, patch) {
+            Ok(code) => {
+                self.code = code;
+                self.mark_edited(ui.input(|i| i.time), None, None);
+                control.push(AppCommand::Notify { message: format!("Applied changes to {}", name), level: crate::NotificationLevel::Success });
+            }
+            Err(e) => control.push(AppCommand::Notify { message: format!("Can't apply changes to {}: {}", name, e), level: crate::NotificationLevel::Error }),
+        }
+    }
+
+    /// 在前缀起点下方绘制补全弹窗，点击建议即接受
+    fn completion_ui(&mut self, ui: &mut Ui, editor_id: egui::Id, anchor: egui::Pos2) {
+        let Some(popup) = &mut self.completion else { return };
+        let mut clicked = None;
+        let area = egui::Area::new(editor_id.with("completion"))
+            .order(egui::Order::Foreground)
+            .fixed_pos(anchor)
+            .show(ui.ctx(), |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    egui::ScrollArea::vertical().max_height(240.0).show(ui, |ui| {
+                        ui.set_min_width(220.0);
+                        for (i, item) in popup.items.iter().enumerate() {
+                            let response = ui.horizontal(|ui| {
+                                ui.label(egui::RichText::new(item.kind.icon()).monospace().weak());
+                                let response = ui.selectable_label(i == popup.selected, &item.label);
+                                if let Some(detail) = &item.detail {
+                                    ui.label(egui::RichText::new(detail).small().weak());
+                                }
+                                response
+                            }).inner;
+                            if response.clicked() {
+                                clicked = Some(i);
+                            }
+                            if i == popup.selected && std::mem::take(&mut popup.scroll_pending) {
+                                response.scroll_to_me(None);
+                            }
+                        }
+                    });
+                });
+            });
+
+        if let Some(index) = clicked {
+            self.accept_completion(ui, editor_id, index);
+        } else if area.response.clicked_elsewhere() && !ui.memory(|m| m.has_focus(editor_id)) {
+            self.completion = None;
+        }
+    }
+
+    /// Ctrl + D 选中下一处相同的文本；存在多个光标时把输入、删除、粘贴与移动按键
+    /// 应用到全部光标，Esc 回到单个光标。需在 TextEdit 与编辑辅助之前处理
+    fn multi_cursor_input(&mut self, ui: &mut Ui, editor_id: egui::Id) {
+        if !self.editable() || !ui.memory(|m| m.has_focus(editor_id)) {
+            return;
+        }
+        let Some(mut state) = egui::text_edit::TextEditState::load(ui.ctx(), editor_id) else { return };
+        let Some(range) = state.cursor.char_range() else { return };
+        let before = (range.primary.index, range.secondary.index);
+        let mut primary = before;
+        let mut moved = false;
+        let mut edited = false;
+
+        if ui.input_mut(|i| i.consume_key(egui::Modifiers::COMMAND, egui::Key::D)) {
+            if let Some(next) = cursors::next_occurrence(&self.code, primary, &self.extra_cursors) {
+                if primary.0 != primary.1 {
+                    self.extra_cursors.push(primary);
+                }
+                primary = next;
+                moved = true;
+            }
+        }
+        if !self.extra_cursors.is_empty() && ui.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::Escape)) {
+            self.extra_cursors.clear();
+        }
+
+        let events = ui.input(|i| i.events.clone());
+        let composing = events.iter().any(|e| matches!(e, egui::Event::Ime(_)));
+        if !self.extra_cursors.is_empty() && !composing {
+            let mut all = vec![primary];
+            all.append(&mut self.extra_cursors);
+            let mut handled = Vec::new();
+            for (idx, event) in events.iter().enumerate() {
+                match event {
+                    egui::Event::Text(text) => {
+                        cursors::edit_each(&mut self.code, &mut all, |code, range, _| cursors::replace(code, range, text));
+                    }
+                    egui::Event::Key { key: egui::Key::Enter, pressed: true, modifiers, .. } if !modifiers.command && !modifiers.alt => {
+                        let (language, unit) = (self.language.clone(), self.indentation.unit());
+                        cursors::edit_each(&mut self.code, &mut all, |code, range, _| smart::newline(code, range, &language, &unit));
+                    }
+                    egui::Event::Key { key: egui::Key::Backspace, pressed: true, modifiers, .. } if modifiers.is_none() => {
+                        cursors::edit_each(&mut self.code, &mut all, |code, range, _| cursors::backspace(code, range));
+                    }
+                    egui::Event::Key { key: egui::Key::Delete, pressed: true, modifiers, .. } if modifiers.is_none() => {
+                        cursors::edit_each(&mut self.code, &mut all, |code, range, _| cursors::delete(code, range));
+                    }
+                    egui::Event::Key { key: egui::Key::Tab, pressed: true, modifiers, .. } if modifiers.is_none() => {
+                        let indentation = self.indentation;
+                        cursors::edit_each(&mut self.code, &mut all, |code, range, _| smart::indent(code, range, indentation, false).0);
+                    }
+                    egui::Event::Key {
+                        key: key @ (egui::Key::ArrowLeft | egui::Key::ArrowRight | egui::Key::ArrowUp | egui::Key::ArrowDown | egui::Key::Home | egui::Key::End),
+                        pressed: true,
+                        modifiers,
+                        ..
+                    } if !modifiers.command && !modifiers.alt => {
+                        for cursor in all.iter_mut() {
+                            *cursor = cursors::move_cursor(&self.code, *cursor, *key, modifiers.shift);
+                        }
+                        handled.push(idx);
+                        continue;
+                    }
+                    // 行数与光标数相同时每个光标粘贴一行
+                    egui::Event::Paste(text) => {
+                        let mut order: Vec<usize> = (0..all.len()).collect();
+                        order.sort_by_key(|&i| cursors::sorted(all[i]).start);
+                        let lines: Vec<&str> = text.lines().collect();
+                        cursors::edit_each(&mut self.code, &mut all, |code, range, i| {
+                            let piece = if lines.len() == order.len() {
+                                lines[order.iter().position(|&o| o == i).unwrap_or(0)]
+                            } else {
+                                text.as_str()
+                            };
+                            cursors::replace(code, range, piece)
+                        });
+                    }
+                    egui::Event::Copy | egui::Event::Cut => {
+                        let mut ranges: Vec<_> = all.iter().map(|c| cursors::sorted(*c)).collect();
+                        ranges.sort_by_key(|r| r.start);
+                        let copied: Vec<String> = ranges
+                            .into_iter()
+                            .map(|r| self.code.chars().skip(r.start).take(r.len()).collect())
+                            .collect();
+                        ui.ctx().copy_text(copied.join("\n"));
+                        handled.push(idx);
+                        if matches!(event, egui::Event::Copy) {
+                            continue;
+                        }
+                        cursors::edit_each(&mut self.code, &mut all, |code, range, _| cursors::replace(code, range, ""));
+                    }
+                    _ => continue,
+                }
+                if handled.last() != Some(&idx) {
+                    handled.push(idx);
+                }
+                edited = true;
+            }
+            cursors::merge(&mut all);
+            primary = all.remove(0);
+            self.extra_cursors = all;
+            moved |= !handled.is_empty();
+
+            let mut idx = 0;
+            ui.input_mut(|i| {
+                i.events.retain(|_| {
+                    let keep = !handled.contains(&idx);
+                    idx += 1;
+                    keep
+                })
+            });
+        }
+
+        if moved {
+            state.cursor.set_char_range(Some(egui::text::CCursorRange::two(
+                egui::text::CCursor::new(primary.1),
+                egui::text::CCursor::new(primary.0),
+            )));
+            state.store(ui.ctx(), editor_id);
+        }
+        if edited {
+            self.mark_edited(ui.input(|i| i.time), Some(before), Some(primary));
+        }
+    }
+
+    /// 绘制主光标之外的光标与选区
+    fn paint_extra_cursors(&self, ui: &Ui, galley: &egui::Galley, galley_pos: egui::Pos2, right: f32) {
+        let offset = galley_pos.to_vec2();
+        let visuals = ui.visuals();
+        for &(primary, secondary) in &self.extra_cursors {
+            let range = cursors::sorted((primary, secondary));
+            if !range.is_empty() {
+                let start = galley.pos_from_ccursor(egui::text::CCursor::new(range.start)).translate(offset);
+                let end = galley.pos_from_ccursor(egui::text::CCursor::new(range.end)).translate(offset);
+                // 跨行的选区逐行填充
+                let mut top = start;
+                while top.top() < end.top() - 1.0 {
+                    let rect = egui::Rect::from_min_max(top.min, egui::pos2(right, top.max.y));
+                    ui.painter().rect_filled(rect, 0.0, visuals.selection.bg_fill);
+                    top = egui::Rect::from_min_max(egui::pos2(galley_pos.x, top.max.y), egui::pos2(galley_pos.x, top.max.y + top.height().max(1.0)));
+                }
+                let rect = egui::Rect::from_min_max(top.min, egui::pos2(end.min.x, top.max.y));
+                ui.painter().rect_filled(rect, 0.0, visuals.selection.bg_fill);
             }
+            let caret = galley.pos_from_ccursor(egui::text::CCursor::new(primary)).translate(offset);
+            ui.painter().vline(caret.min.x, caret.y_range(), visuals.text_cursor.stroke);
         }
     }
 
-    fn save_as(&mut self, control: &mut Vec<AppCommand>) {
-        if let EditorState::Ready = self.state {
-            if let Some(path) = rfd::FileDialog::new()
-                .set_file_name(&self.name)
-                .save_file() 
-            {
-                match std::fs::write(&path, &self.code) {
-                    Ok(_) => {
-                        self.path = Some(path.clone());
-                        self.name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
-                        self.is_dirty = false;
-                        
-                        // 根据新扩展名更新语言
-                        let ext = path.extension().and_then(|s| s.to_str()).unwrap_or("");
-                        self.language = match ext {
-                            "rs" => "rs",
-                            "py" => "py",
-                            "js" | "ts" => "js",
-                            "html" => "html",
-                            "css" => "css",
-                            "json" => "json",
-                            "md" => "md",
-                            "toml" => "toml",
-                            "c" | "h" => "c",
-                            "cpp" | "hpp" | "cc" | "cxx" => "cpp",
-                            _ => "txt",
-                        }.to_string();
+    /// 撤销或重做一步并恢复当时的选区
+    fn undo_redo(&mut self, ctx: &egui::Context, editor_id: egui::Id, redo: bool) {
+        let result = if redo { self.history.redo(&mut self.code) } else { self.history.undo(&mut self.code) };
+        let Some(selection) = result else { return };
+        self.extra_cursors.clear();
+        // 撤销回保存时的内容后不再显示为已修改
+        self.is_dirty = !self.history.is_saved();
+        self.outline_stale = true;
+        if let Some((primary, secondary)) = selection {
+            let mut state = egui::text_edit::TextEditState::load(ctx, editor_id).unwrap_or_default();
+            state.cursor.set_char_range(Some(egui::text::CCursorRange::two(
+                egui::text::CCursor::new(secondary),
+                egui::text::CCursor::new(primary),
+            )));
+            state.store(ctx, editor_id);
+        }
+    }
 
-                        control.push(AppCommand::Notify {
-                            message: format!("Saved as {}", self.name),
-                            level: crate::NotificationLevel::Success,
-                        });
-                    }
-                    Err(e) => {
-                        control.push(AppCommand::Notify {
-                            message: format!("Save As failed: {}", e),
-                            level: crate::NotificationLevel::Error,
-                        });
-                    }
-                }
-            }
+    /// 颜色字面量的取色弹窗，修改实时写回源码
+    fn show_color_picker(&mut self, ui: &mut Ui, editor_id: egui::Id) {
+        let Some(edit) = &mut self.color_edit else { return };
+        let Some(original) = self.code.get(edit.bytes.clone()).filter(|s| inline::parse_hex_color(s).is_some()) else {
+            // 字面量已被其它编辑改变
+            self.color_edit = None;
+            return;
+        };
+        let original = original.to_string();
+
+        let mut changed = false;
+        let area = egui::Area::new(editor_id.with("color_picker"))
+            .order(egui::Order::Foreground)
+            .fixed_pos(edit.anchor)
+            .show(ui.ctx(), |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    changed = egui::color_picker::color_picker_color32(ui, &mut edit.color, egui::color_picker::Alpha::OnlyBlend);
+                });
+            });
+
+        if changed {
+            let replacement = inline::format_hex_color(edit.color, &original);
+            self.code.replace_range(edit.bytes.clone(), &replacement);
+            edit.bytes.end = edit.bytes.start + replacement.len();
+            self.mark_edited(ui.input(|i| i.time), None, None);
+        }
+
+        if ui.input(|i| i.key_pressed(egui::Key::Escape)) || area.response.clicked_elsewhere() {
+            self.color_edit = None;
         }
     }
 }
 
 impl TabInstance for CodeEditorTab {
     fn title(&self) -> WidgetText {
-        let mut title = match self.state {
-            EditorState::Loading(_) => format!("⏳ {}", self.name),
-            EditorState::Error(_) => format!("❌ {}", self.name),
-            EditorState::Ready => format!("{} {}", if self.is_dirty { "📝" } else { "" }, self.name),
-        };
-        
         if self.is_dirty {
-            title.push('*');
+            format!("{}*", self.name).into()
+        } else {
+            self.name.clone().into()
+        }
+    }
+
+    fn on_app_exit(&mut self) {
+        // 启用自动保存时，退出前写回尚未到达保存间隔的修改
+        if !self.autosave_enabled || self.sync_mode || !self.is_dirty || self.protection != Protection::Writable {
+            return;
+        }
+        if let (EditorState::Ready | EditorState::Large(_), Some(path)) = (&self.state, &self.path) {
+            match self.write_file(path) {
+                Ok(_) => self.mark_saved(),
+                Err(e) => eprintln!("Failed to flush {} on exit: {}", path.display(), e),
+            }
         }
-        title.into()
+    }
+
+    fn icon(&self) -> Option<crate::TabIcon> {
+        let icon = match self.state {
+            EditorState::Loading(_) => "⏳",
+            EditorState::Error(_) => "❌",
+            EditorState::ReadOnly(_) => "🔒",
+            EditorState::Ready | EditorState::Large(_) if self.protection == Protection::Locked => "🔒",
+            EditorState::Ready | EditorState::Large(_) if self.is_dirty => "📝",
+            EditorState::Ready | EditorState::Large(_) => "📄",
+        };
+        Some(crate::TabIcon::Emoji(icon.into()))
     }
 
     fn ui(&mut self, ui: &mut Ui, control: &mut Vec<AppCommand>) {
@@ -131,14 +1512,48 @@ impl TabInstance for CodeEditorTab {
 
         if let Some(res) = loaded_content {
             match res {
-                Ok(content) => {
+                Ok((content, format)) => {
                     self.code = content;
-                    self.state = EditorState::Ready;
+                    self.format = format;
+                    self.resolve_indentation();
+                    if self.code.len() > large::ROPE_BYTES {
+                        self.state = EditorState::Large(RopeEditor::new(&std::mem::take(&mut self.code)));
+                    } else {
+                        self.history.reset(&self.code);
+                        self.state = EditorState::Ready;
+                        self.outline_stale = true;
+                    }
                 }
                 Err(e) => {
                     self.state = EditorState::Error(e);
                 }
             }
+            // 加载期间收到的补丁只能在 Ready 状态下应用
+            if !matches!(self.state, EditorState::Ready) && !self.pending_patches.is_empty() {
+                self.pending_patches.clear();
+                control.push(AppCommand::Notify {
+                    message: format!("Can't apply changes to {}: the file can't be edited in this view", self.name),
+                    level: crate::NotificationLevel::Error,
+                });
+            }
+        }
+
+        // 提权保存的结果
+        let elevated_result = self.elevated_save.as_ref().and_then(|store| store.read().clone());
+        if let Some(result) = elevated_result {
+            self.elevated_save = None;
+            match result {
+                Ok(lossy) => {
+                    self.mark_saved();
+                    self.saved_notice(format!("Saved {} with elevated permissions", self.name), lossy, control);
+                }
+                Err(e) => control.push(AppCommand::Notify {
+                    message: format!("Elevated save failed: {}", e),
+                    level: crate::NotificationLevel::Error,
+                }),
+            }
+        } else if self.elevated_save.is_some() {
+            ui.ctx().request_repaint_after(std::time::Duration::from_millis(100));
         }
 
         if let EditorState::Loading(_) = self.state {
@@ -152,86 +1567,553 @@ impl TabInstance for CodeEditorTab {
         }
 
         if let EditorState::Error(ref e) = self.state {
-            ui.centered_and_justified(|ui| {
-                ui.label(format!("Failed to load file:\n{}", e));
+            let binary = e == "binary file";
+            ui.vertical_centered(|ui| {
+                ui.add_space(ui.available_height() / 3.0);
+                let color = crate::theme::palette(ui.ctx()).error;
+                ui.label(egui::RichText::new(format!("Failed to load file:\n{}", e)).color(color));
+                // 不是文本的文件可以改用十六进制编辑器打开
+                if let Some(path) = self.path.as_ref().filter(|_| binary) {
+                    ui.add_space(8.0);
+                    if ui.button("🔢 Open in Hex Editor").clicked() {
+                        let tab = HexTab::open(path, self.settings.clone());
+                        control.push(AppCommand::OpenTab(Tab::new(Box::new(tab))));
+                    }
+                }
+            });
+            return;
+        }
+
+        if let EditorState::Large(_) = self.state {
+            self.large_editor_ui(ui, control);
+            return;
+        }
+
+        if let EditorState::ReadOnly(ref file) = self.state {
+            ui.horizontal(|ui| {
+                let warning = crate::theme::palette(ui.ctx()).warning;
+                let size_mb = file.len() as f64 / (1024.0 * 1024.0);
+                ui.colored_label(warning, format!("🔒 Large file ({:.1} MB) opened read-only.", size_mb));
+                match file.progress() {
+                    Some(progress) => {
+                        ui.add(egui::ProgressBar::new(progress).desired_width(160.0).text("Indexing lines…"));
+                        ui.ctx().request_repaint_after(std::time::Duration::from_millis(100));
+                    }
+                    None => {
+                        ui.label(egui::RichText::new(format!("{} lines", file.line_count())).weak());
+                    }
+                }
             });
+            ui.separator();
+            // 只读视图没有编辑框焦点，指针在视图上时响应 Ctrl + G
+            if ui.rect_contains_pointer(ui.max_rect()) && ui.input_mut(|i| i.consume_key(egui::Modifiers::COMMAND, egui::Key::G)) {
+                self.goto = Some(GotoPopup::new(GotoMode::Line));
+            }
+            if let Some(popup) = &mut self.goto {
+                let anchor = ui.max_rect().center_top() + egui::vec2(0.0, 8.0);
+                match goto::show(ui, popup, anchor, file.line_count(), 0, &[]) {
+                    GotoAction::Jump(line, column) => {
+                        self.pending_jump = Some((line, column));
+                        self.goto = None;
+                    }
+                    GotoAction::Close => self.goto = None,
+                    GotoAction::None => {}
+                }
+            }
+            let jump_to = self.pending_jump.take().map(|(line, _)| line);
+            large::show(ui, file, jump_to);
             return;
         }
 
         // 只有 Ready 状态才执行后续逻辑
         let language = self.language.clone();
+        let syntax = self.syntax.share();
         let mut layouter = move |ui: &egui::Ui, string: &str, wrap_width: f32| {
-            let theme = egui_extras::syntax_highlighting::CodeTheme::from_memory(ui.ctx(), ui.style());
-            let mut layout_job = egui_extras::syntax_highlighting::highlight(
-                ui.ctx(),
-                ui.style(),
-                &theme,
-                string,
-                &language,
-            );
+            // 较大的文件不着色，每帧重新高亮整个文件会让输入明显卡顿
+            if string.len() > large::HIGHLIGHT_LIMIT_BYTES {
+                let font_id = egui::TextStyle::Monospace.resolve(ui.style());
+                let color = ui.visuals().text_color();
+                return ui.fonts(|f| f.layout_job(egui::text::LayoutJob::simple(string.to_owned(), font_id, color, wrap_width)));
+            }
+            // 优先使用 tree-sitter，没有对应语法的语言交给 syntect
+            let mut layout_job = syntax.highlight(ui, string, &language).unwrap_or_else(|| {
+                let theme = egui_extras::syntax_highlighting::CodeTheme::from_memory(ui.ctx(), ui.style());
+                egui_extras::syntax_highlighting::highlight(ui.ctx(), ui.style(), &theme, string, &language)
+            });
+            if inline::supports_colors(&language) {
+                inline::reserve_swatch_space(&mut layout_job, &inline::scan(string, &language));
+            }
             layout_job.wrap.max_width = wrap_width;
             ui.fonts(|f| f.layout_job(layout_job))
         };
 
-        // 处理同步模式逻辑
-        if self.sync_mode {
-            let current_time = ui.input(|i| i.time);
-            if current_time - self.last_sync_time > 1.0 {
-                if let Some(path) = &self.path {
-                    if let Ok(content) = std::fs::read_to_string(path) {
-                        if content != self.code {
-                            self.code = content;
-                            self.is_dirty = false;
-                        }
-                    }
+        self.autosave_and_watch(ui, control);
+
+        self.sync_bookmarks();
+        let code_changed = self.outline_stale;
+        if self.outline_stale {
+            self.outline = outline::build_outline(&self.code, &self.language);
+            self.inline_items = if self.code.len() > large::HIGHLIGHT_LIMIT_BYTES {
+                Vec::new()
+            } else {
+                inline::scan(&self.code, &self.language)
+            };
+            self.outline_stale = false;
+        }
+        if let Some(find) = &mut self.find {
+            find.refresh(&self.code, code_changed);
+        }
+
+        // 版本控制：内容或基准版本变化后重新比较，标注在行号栏中
+        let vcs_base = crate::vcs_provider(ui.ctx())
+            .zip(self.path.as_deref())
+            .and_then(|(provider, path)| provider.base_text(path))
+            .filter(|_| self.code.len() <= large::HIGHLIGHT_LIMIT_BYTES);
+        let base_changed = match (&vcs_base, &self.vcs_base) {
+            (Some(new), Some(old)) => !Arc::ptr_eq(new, old),
+            (None, None) => false,
+            _ => true,
+        };
+        if code_changed || base_changed {
+            self.line_changes = vcs_base.as_deref().map_or_else(Vec::new, |base| crate::diff::line_changes(base, &self.code));
+            self.vcs_base = vcs_base;
+        }
+
+        let editor_id = ui.make_persistent_id("code_editor_text");
+        self.finish_format(ui.ctx(), editor_id, control);
+        let cursor_range = egui::text_edit::TextEditState::load(ui.ctx(), editor_id).and_then(|state| state.cursor.char_range());
+        let current_line = cursor_range
+            .map(|range| line_of_char(&self.code, range.primary.index))
+            .unwrap_or(0);
+        self.cursor_line = current_line;
+
+        // Ctrl + F（编辑器获得焦点时）或 "/"（指针在编辑器上且没有输入框获得焦点时）打开查找栏，
+        // Ctrl + H 同时展开替换栏
+        let editor_focused = ui.memory(|m| m.has_focus(editor_id));
+        let open_replace = editor_focused && ui.input_mut(|i| i.consume_key(egui::Modifiers::COMMAND, egui::Key::H));
+        let open_find = open_replace
+            || (editor_focused && ui.input_mut(|i| i.consume_key(egui::Modifiers::COMMAND, egui::Key::F)))
+            || (!ui.ctx().wants_keyboard_input()
+                && ui.rect_contains_pointer(ui.max_rect())
+                && ui.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::Slash)));
+        // Ctrl + G 跳到行，Ctrl + Shift + O 按大纲跳到符号
+        if editor_focused {
+            let (goto_line, goto_symbol) = ui.input_mut(|i| (
+                i.consume_key(egui::Modifiers::COMMAND, egui::Key::G),
+                i.consume_key(egui::Modifiers::COMMAND | egui::Modifiers::SHIFT, egui::Key::O),
+            ));
+            if self.language == "md" && ui.input_mut(|i| i.consume_key(egui::Modifiers::COMMAND | egui::Modifiers::SHIFT, egui::Key::V)) {
+                self.toggle_preview();
+            }
+            // Ctrl + F2 切换书签，F2 / Shift + F2 跳到下一个 / 上一个书签
+            let (toggle_bookmark, next_bookmark, previous_bookmark) = ui.input_mut(|i| (
+                i.consume_key(egui::Modifiers::COMMAND, egui::Key::F2),
+                i.consume_key(egui::Modifiers::NONE, egui::Key::F2),
+                i.consume_key(egui::Modifiers::SHIFT, egui::Key::F2),
+            ));
+            if toggle_bookmark {
+                self.toggle_bookmark(current_line);
+            }
+            if next_bookmark || previous_bookmark {
+                self.jump_to_bookmark(current_line, next_bookmark);
+            }
+            // Shift + Alt + F 格式化文档
+            if ui.input_mut(|i| i.consume_key(egui::Modifiers::SHIFT | egui::Modifiers::ALT, egui::Key::F)) {
+                self.format_document(control);
+            }
+            if goto_line {
+                self.goto = Some(GotoPopup::new(GotoMode::Line));
+            } else if goto_symbol {
+                self.goto = Some(GotoPopup::new(GotoMode::Symbol));
+            }
+        }
+        if open_find {
+            let anchor = cursor_range.map_or(0, |r| r.primary.index.min(r.secondary.index));
+            let find = self.find.get_or_insert_with(|| EditorFind::new(anchor));
+            find.focus_pending = true;
+            find.replace_open |= open_replace;
+            // 单行选区作为初始查询词
+            if let Some(range) = cursor_range.filter(|r| r.primary != r.secondary) {
+                let [start, end] = range.sorted();
+                let selected: String = self.code.chars().skip(start.index).take(end.index - start.index).collect();
+                if !selected.contains('\n') {
+                    find.query = selected;
                 }
-                self.last_sync_time = current_time;
             }
-            // 确保 UI 持续刷新以检查同步
-            ui.ctx().request_repaint_after(std::time::Duration::from_millis(500));
         }
 
+        let cursor_position = cursor_range.map(|range| {
+            let line_start = char_of_position(&self.code, current_line, 0);
+            (current_line, range.primary.index.saturating_sub(line_start))
+        });
+        egui::TopBottomPanel::bottom(ui.id().with("editor_status_bar"))
+            .show_inside(ui, |ui| self.status_bar_ui(ui, cursor_position));
+
         ui.vertical(|ui| {
             // 快捷键监听: Ctrl + S 保存 (同步模式下禁用)
             if !self.sync_mode && ui.input_mut(|i| i.consume_key(egui::Modifiers::COMMAND, egui::Key::S)) {
-                self.save(control);
+                self.request_save(control);
+            }
+
+            // Ctrl + Z 撤销，Ctrl + Y / Ctrl + Shift + Z 重做；在编辑器之前消费，不交给 TextEdit 自带的撤销
+            if self.editable() && ui.memory(|m| m.has_focus(editor_id)) {
+                let (undo, redo) = ui.input_mut(|i| {
+                    let redo = i.consume_key(egui::Modifiers::COMMAND | egui::Modifiers::SHIFT, egui::Key::Z)
+                        || i.consume_key(egui::Modifiers::COMMAND, egui::Key::Y);
+                    (i.consume_key(egui::Modifiers::COMMAND, egui::Key::Z), redo)
+                });
+                if undo || redo {
+                    self.undo_redo(ui.ctx(), editor_id, redo);
+                }
+            }
+            if let Some(redo) = self.pending_history.take() {
+                self.undo_redo(ui.ctx(), editor_id, redo);
+            }
+            if let Some(text) = self.pending_insert.take() {
+                self.insert_at_cursor(ui, editor_id, &text);
+            }
+            for patch in std::mem::take(&mut self.pending_patches) {
+                self.apply_pending_patch(ui, &patch, control);
+            }
+
+            // Ctrl + Up / Down 在标题、函数等结构元素间跳转（需在编辑器之前消费，否则会跳到文首/文末）
+            if ui.memory(|m| m.has_focus(editor_id)) {
+                if ui.input_mut(|i| i.consume_key(egui::Modifiers::COMMAND, egui::Key::ArrowUp)) {
+                    if let Some(symbol) = self.outline.iter().rev().find(|s| s.line < current_line) {
+                        self.pending_jump = Some((symbol.line, 0));
+                    }
+                }
+                if ui.input_mut(|i| i.consume_key(egui::Modifiers::COMMAND, egui::Key::ArrowDown)) {
+                    if let Some(symbol) = self.outline.iter().find(|s| s.line > current_line) {
+                        self.pending_jump = Some((symbol.line, 0));
+                    }
+                }
+            }
+
+            // 面包屑：显示光标所在的符号链，点击跳转
+            if !self.outline.is_empty() {
+                ui.horizontal(|ui| {
+                    ui.spacing_mut().item_spacing.x = 4.0;
+                    ui.label(egui::RichText::new(&self.name).weak());
+                    for symbol in outline::enclosing_symbols(&self.outline, current_line) {
+                        ui.label(egui::RichText::new("›").weak());
+                        let text = format!("{} {}", symbol.kind.icon(), symbol.name);
+                        if ui.add(egui::Label::new(text).sense(egui::Sense::click())).clicked() {
+                            self.pending_jump = Some((symbol.line, 0));
+                        }
+                    }
+                });
+                ui.separator();
+            }
+
+            if let Some(popup) = &mut self.goto {
+                let anchor = ui.max_rect().center_top() + egui::vec2(0.0, 8.0);
+                let line_count = self.code.matches('\n').count() + 1;
+                match goto::show(ui, popup, anchor, line_count, current_line, &self.outline) {
+                    GotoAction::Jump(line, column) => {
+                        self.pending_jump = Some((line, column));
+                        self.goto = None;
+                    }
+                    GotoAction::Close => {
+                        self.goto = None;
+                        ui.memory_mut(|m| m.request_focus(editor_id));
+                    }
+                    GotoAction::None => {}
+                }
+            }
+
+            if !self.tidied.is_empty() {
+                let removed = std::mem::take(&mut self.tidied);
+                if let Some(mut state) = egui::text_edit::TextEditState::load(ui.ctx(), editor_id) {
+                    if let Some(range) = state.cursor.char_range() {
+                        state.cursor.set_char_range(Some(egui::text::CCursorRange::two(
+                            egui::text::CCursor::new(whitespace::map_position(&removed, range.secondary.index)),
+                            egui::text::CCursor::new(whitespace::map_position(&removed, range.primary.index)),
+                        )));
+                        state.store(ui.ctx(), editor_id);
+                    }
+                }
+            }
+
+            let jump_to = self.pending_jump.take().map(|(line, column)| char_of_position(&self.code, line, column));
+            if let Some(idx) = jump_to {
+                let mut state = egui::text_edit::TextEditState::load(ui.ctx(), editor_id).unwrap_or_default();
+                let ccursor = egui::text::CCursor::new(idx);
+                state.cursor.set_char_range(Some(egui::text::CCursorRange::one(ccursor)));
+                state.store(ui.ctx(), editor_id);
+                ui.memory_mut(|m| m.request_focus(editor_id));
+                self.jump_highlight = Some((idx, ui.input(|i| i.time)));
+            }
+
+            self.show_color_picker(ui, editor_id);
+            self.protection_ui(ui, control);
+            self.external_change_ui(ui);
+            self.find_bar_ui(ui, editor_id, control);
+            if !self.extra_cursors.is_empty() {
+                self.completion = None;
+            }
+            self.completion_keys(ui, editor_id);
+            self.multi_cursor_input(ui, editor_id);
+            if self.extra_cursors.is_empty() {
+                self.smart_edit(ui, editor_id);
+            }
+
+            // 选中当前查找结果（不抢走查找栏的焦点）
+            let reveal_match = self.find.as_mut()
+                .filter(|f| f.reveal_pending)
+                .and_then(|f| {
+                    f.reveal_pending = false;
+                    f.current_match()
+                });
+            if let Some(range) = &reveal_match {
+                let mut state = egui::text_edit::TextEditState::load(ui.ctx(), editor_id).unwrap_or_default();
+                state.cursor.set_char_range(Some(egui::text::CCursorRange::two(
+                    egui::text::CCursor::new(range.start),
+                    egui::text::CCursor::new(range.end),
+                )));
+                state.store(ui.ctx(), editor_id);
+            }
+
+            // Markdown 预览：指针在哪一侧，哪一侧就带动另一侧滚动
+            let mut preview_hovered = false;
+            let mut scroll_editor_to = None;
+            if self.language != "md" {
+                self.preview = None;
+            }
+            if let Some(preview) = &mut self.preview {
+                let code = &self.code;
+                egui::SidePanel::right(ui.id().with("markdown_preview"))
+                    .resizable(true)
+                    .default_width(ui.available_width() / 2.0)
+                    .show_inside(ui, |ui| {
+                        preview_hovered = ui.rect_contains_pointer(ui.max_rect());
+                        scroll_editor_to = markdown::show(ui, preview, code);
+                    });
             }
 
             egui::ScrollArea::both()
                 .id_salt("code_editor_scroll")
                 .show(ui, |ui| {
                     ui.horizontal_top(|ui| {
-                        // 1. 优化的行号显示
+                        // 1. 行号栏：先占位，编辑器排版完成后按显示行绘制
                         let text_style = egui::TextStyle::Monospace;
-                        let line_count = self.code.lines().count().max(1);
-                        
-                        let mut line_numbers_str = String::new();
-                        for i in 1..=line_count {
-                            line_numbers_str.push_str(&format!("{}\n", i));
-                        }
-
-                        ui.add(
-                            egui::Label::new(
-                                egui::RichText::new(line_numbers_str)
-                                    .font(egui::FontId::monospace(12.0))
-                                    .color(ui.visuals().weak_text_color())
-                            )
-                        );
-
-                        ui.separator();
+                        let font_id = text_style.resolve(ui.style());
+                        let line_count = self.code.split('\n').count();
+                        let gutter_width = gutter::width(ui, &font_id, line_count);
+                        let (gutter_rect, _) = ui.allocate_exact_size(egui::vec2(gutter_width, 0.0), egui::Sense::hover());
+                        // 当前行底色需要画在文字之下，先占一个位置
+                        let line_highlight = ui.painter().add(egui::Shape::Noop);
 
                         // 2. 编辑器主体
-                        ui.add_enabled_ui(!self.sync_mode, |ui| {
+                        ui.add_enabled_ui(self.editable(), |ui| {
                             let editor = egui::TextEdit::multiline(&mut self.code)
+                                .id(editor_id)
                                 .font(text_style)
                                 .code_editor()
                                 .lock_focus(true)
                                 .desired_width(f32::INFINITY)
                                 .layouter(&mut layouter);
 
-                            let response = ui.add_sized(ui.available_size(), editor);
-                            if response.changed() {
-                                self.is_dirty = true;
+                            let output = ui
+                                .allocate_ui_with_layout(
+                                    ui.available_size(),
+                                    egui::Layout::centered_and_justified(egui::Direction::TopDown),
+                                    |ui| editor.show(ui),
+                                )
+                                .inner;
+
+                            let lines = gutter::line_rows(&output.galley);
+                            if let Some(row) = lines.get(current_line) {
+                                let y_range = egui::Rangef::new(row.span.min + output.galley_pos.y, row.span.max + output.galley_pos.y);
+                                let rect = egui::Rect::from_x_y_ranges(output.response.rect.x_range(), y_range);
+                                let color = ui.visuals().faint_bg_color;
+                                ui.painter().set(line_highlight, egui::Shape::rect_filled(rect, 0.0, color));
+                            }
+                            let gutter_rect = egui::Rect::from_x_y_ranges(gutter_rect.x_range(), output.response.rect.y_range());
+                            gutter::paint_changes(ui, gutter_rect, output.galley_pos, &lines, &self.line_changes);
+                            if let Some(chars) = gutter::show(ui, gutter_rect, output.galley_pos, &lines, current_line, &mut self.breakpoints, &self.bookmarks, &font_id) {
+                                let mut state = output.state.clone();
+                                state.cursor.set_char_range(Some(egui::text::CCursorRange::two(
+                                    egui::text::CCursor::new(chars.start),
+                                    egui::text::CCursor::new(chars.end),
+                                )));
+                                state.store(ui.ctx(), editor_id);
+                                ui.memory_mut(|m| m.request_focus(editor_id));
+                            }
+                            if let Some(row) = scroll_editor_to.and_then(|line| lines.get(line)) {
+                                let y = row.span.min + output.galley_pos.y;
+                                let rect = egui::Rect::from_x_y_ranges(output.response.rect.x_range(), y..=y + 1.0);
+                                ui.scroll_to_rect(rect, Some(egui::Align::TOP));
+                            }
+                            if let Some(preview) = self.preview.as_mut().filter(|_| !preview_hovered) {
+                                let top = ui.clip_rect().top() - output.galley_pos.y;
+                                preview.follow_editor(lines.iter().position(|row| row.span.max > top).unwrap_or(0));
+                            }
+                            if let Some(idx) = jump_to.or(reveal_match.as_ref().map(|r| r.start)) {
+                                let cursor_rect = output.galley.pos_from_ccursor(egui::text::CCursor::new(idx));
+                                ui.scroll_to_rect(cursor_rect.translate(output.galley_pos.to_vec2()), Some(egui::Align::Center));
+                            }
+
+                            if self.settings.lock().render_whitespace && self.code.len() <= large::HIGHLIGHT_LIMIT_BYTES {
+                                let clip = ui.clip_rect();
+                                let visible_start = output.galley.cursor_from_pos(clip.min - output.galley_pos).ccursor.index;
+                                let visible_end = output.galley.cursor_from_pos(clip.max - output.galley_pos).ccursor.index + 1;
+                                whitespace::paint_markers(ui, &output.galley, output.galley_pos, &self.code, visible_start..visible_end);
+                            }
+
+                            // 查找结果高亮：只绘制视口内的匹配
+                            if let Some(find) = &self.find {
+                                let clip = ui.clip_rect();
+                                let offset = output.galley_pos.to_vec2();
+                                let visible_start = output.galley.cursor_from_pos(clip.min - output.galley_pos).ccursor.index;
+                                let visible_end = output.galley.cursor_from_pos(clip.max - output.galley_pos).ccursor.index + 1;
+                                let color = crate::theme::palette(ui.ctx()).warning;
+                                let current = find.current_match();
+                                for range in find.visible_matches(visible_start..visible_end) {
+                                    let start = output.galley.pos_from_ccursor(egui::text::CCursor::new(range.start)).translate(offset);
+                                    let end = output.galley.pos_from_ccursor(egui::text::CCursor::new(range.end)).translate(offset);
+                                    let right = if (end.min.y - start.min.y).abs() < 1.0 { end.min.x } else { output.response.rect.right() };
+                                    let rect = egui::Rect::from_min_max(start.min, egui::pos2(right, start.max.y));
+                                    ui.painter().rect_filled(rect, 2.0, color.gamma_multiply(0.25));
+                                    if current.as_ref() == Some(range) {
+                                        ui.painter().rect_stroke(rect, 2.0, egui::Stroke::new(1.5, color));
+                                    }
+                                }
+                            }
+
+                            // 内联装饰：链接下划线（Ctrl + 点击打开）与颜色色块（点击取色）
+                            let galley_offset = output.galley_pos.to_vec2();
+                            let link_modifier = ui.input(|i| i.modifiers.command);
+                            let mut on_link = false;
+                            let pointer = ui.input(|i| i.pointer.hover_pos());
+                            let clip = ui.clip_rect();
+                            for (i, item) in self.inline_items.iter().enumerate() {
+                                let start = output.galley.pos_from_ccursor(egui::text::CCursor::new(item.chars.start))
+                                    .translate(galley_offset);
+                                if !clip.intersects(start) {
+                                    continue;
+                                }
+                                match &item.kind {
+                                    InlineKind::Link(url) => {
+                                        let end = output.galley.pos_from_ccursor(egui::text::CCursor::new(item.chars.end))
+                                            .translate(galley_offset);
+                                        // 跨行的链接只标注第一行
+                                        let right = if (end.min.y - start.min.y).abs() < 1.0 { end.min.x } else { output.response.rect.right() };
+                                        let rect = egui::Rect::from_min_max(start.min, egui::pos2(right, start.max.y));
+                                        let active = link_modifier && pointer.is_some_and(|p| rect.contains(p));
+                                        let color = ui.visuals().hyperlink_color.gamma_multiply(if active { 1.0 } else { 0.5 });
+                                        ui.painter().hline(rect.x_range(), rect.bottom() - 1.0, egui::Stroke::new(1.0, color));
+                                        if active {
+                                            on_link = true;
+                                            ui.ctx().set_cursor_icon(egui::CursorIcon::PointingHand);
+                                            if output.response.clicked() {
+                                                control.push(AppCommand::OpenUrl(url.clone()));
+                                            }
+                                        }
+                                    }
+                                    InlineKind::Color(color) => {
+                                        let size = (start.height() - 4.0).min(inline::SWATCH_WIDTH - 4.0);
+                                        let center = egui::pos2(start.min.x - inline::SWATCH_WIDTH / 2.0, start.center().y);
+                                        let swatch = egui::Rect::from_center_size(center, egui::vec2(size, size));
+                                        ui.painter().rect_filled(swatch, 2.0, *color);
+                                        ui.painter().rect_stroke(swatch, 2.0, egui::Stroke::new(1.0, ui.visuals().weak_text_color()));
+                                        let swatch_response = ui
+                                            .interact(swatch, editor_id.with(("color_swatch", i)), egui::Sense::click())
+                                            .on_hover_cursor(egui::CursorIcon::PointingHand);
+                                        if swatch_response.clicked() {
+                                            self.color_edit = Some(ColorEdit {
+                                                bytes: item.bytes.clone(),
+                                                color: *color,
+                                                anchor: swatch.left_bottom() + egui::vec2(0.0, 4.0),
+                                            });
+                                        }
+                                    }
+                                }
+                            }
+
+                            // 多光标：Ctrl + 点击在原光标处保留一个光标，Alt + 拖动按列选择，普通点击回到单个光标
+                            if self.editable() {
+                                let modifiers = ui.input(|i| i.modifiers);
+                                if output.response.clicked() && modifiers.command && !on_link {
+                                    if let Some(previous) = cursor_range {
+                                        self.extra_cursors.push((previous.primary.index, previous.secondary.index));
+                                    }
+                                } else if output.response.dragged() && modifiers.alt {
+                                    let origin = ui.input(|i| i.pointer.press_origin());
+                                    if let (Some(from), Some(to)) = (origin, output.response.interact_pointer_pos()) {
+                                        let mut columns = cursors::column_selection(&output.galley, from - galley_offset, to - galley_offset);
+                                        if let Some((primary, secondary)) = columns.pop() {
+                                            self.extra_cursors = columns;
+                                            let mut state = output.state.clone();
+                                            state.cursor.set_char_range(Some(egui::text::CCursorRange::two(
+                                                egui::text::CCursor::new(secondary),
+                                                egui::text::CCursor::new(primary),
+                                            )));
+                                            state.store(ui.ctx(), editor_id);
+                                        }
+                                    }
+                                } else if output.response.clicked() || output.response.drag_started() {
+                                    self.extra_cursors.clear();
+                                }
+                                if !self.extra_cursors.is_empty() {
+                                    let primary = output.cursor_range.map(|r| (r.primary.ccursor.index, r.secondary.ccursor.index));
+                                    self.extra_cursors.retain(|c| Some(*c) != primary);
+                                    self.paint_extra_cursors(ui, &output.galley, output.galley_pos, output.response.rect.right());
+                                }
+                            }
+
+                            // 跳转目标行的淡出高亮
+                            if let Some((idx, start)) = self.jump_highlight {
+                                let elapsed = (ui.input(|i| i.time) - start) as f32;
+                                if elapsed < JUMP_HIGHLIGHT_SECS {
+                                    let row = output.galley.pos_from_ccursor(egui::text::CCursor::new(idx))
+                                        .translate(output.galley_pos.to_vec2());
+                                    let rect = egui::Rect::from_x_y_ranges(output.response.rect.x_range(), row.y_range());
+                                    let alpha = 0.4 * (1.0 - elapsed / JUMP_HIGHLIGHT_SECS);
+                                    ui.painter().rect_filled(rect, 0.0, ui.visuals().selection.bg_fill.gamma_multiply(alpha));
+                                    ui.ctx().request_repaint();
+                                } else {
+                                    self.jump_highlight = None;
+                                }
+                            }
+                            if output.response.changed() {
+                                let before = cursor_range.map(|r| (r.primary.index, r.secondary.index));
+                                let after = output.cursor_range.map(|r| (r.primary.ccursor.index, r.secondary.ccursor.index));
+                                self.mark_edited(ui.input(|i| i.time), before, after);
+                            }
+
+                            // 编辑器失去焦点（包括窗口切到后台）时自动保存
+                            let focused = ui.memory(|m| m.has_focus(editor_id)) && ui.input(|i| i.focused);
+                            if self.had_focus && !focused && self.is_dirty && !self.sync_mode && self.settings.lock().autosave_on_focus_loss {
+                                self.autosave(control);
+                            }
+                            self.had_focus = focused;
+                            // 发布当前文件与选区，供 Agent 的提示模板使用
+                            if focused {
+                                let selection = output.cursor_range.map_or(String::new(), |r| {
+                                    let [start, end] = r.sorted_cursors();
+                                    self.code.chars().skip(start.ccursor.index).take(end.ccursor.index - start.ccursor.index).collect()
+                                });
+                                crate::publish_editor_context(ui.ctx(), crate::EditorContext {
+                                    path: self.path.clone(),
+                                    language: self.language.clone(),
+                                    selection,
+                                });
+                            }
+
+                            // 补全弹窗跟随输入更新，显示在前缀起点的下方
+                            let typed = output.response.changed() && ui.input(|i| i.events.iter().any(|e| {
+                                matches!(e, egui::Event::Text(text) if text.chars().all(complete::is_word_char))
+                            }));
+                            if self.editable() && (typed || self.completion.is_some()) {
+                                let cursor = output.cursor_range.map(|r| r.primary.ccursor.index);
+                                self.refresh_completion(ui.ctx(), cursor, typed);
+                            }
+                            if let Some(start) = self.completion.as_ref().map(|p| p.start) {
+                                let anchor = output.galley.pos_from_ccursor(egui::text::CCursor::new(start))
+                                    .translate(output.galley_pos.to_vec2())
+                                    .left_bottom();
+                                self.completion_ui(ui, editor_id, anchor);
                             }
                         });
                     });
@@ -243,20 +2125,91 @@ impl TabInstance for CodeEditorTab {
         // 加载或错误时不显示完整菜单
         if let EditorState::Ready = self.state {
             if ui.add_enabled(!self.sync_mode, egui::Button::new("💾 Save")).clicked() {
-                self.save(control);
+                self.request_save(control);
                 ui.close_menu();
             }
+            if self.protection != Protection::Writable {
+                let label = Elevation::current().button_label();
+                if ui.add_enabled(self.is_dirty && self.elevated_save.is_none(), egui::Button::new(label)).clicked() {
+                    self.save_elevated(control);
+                    ui.close_menu();
+                }
+            }
             if ui.button("📂 Save As...").clicked() {
                 self.save_as(control);
                 ui.close_menu();
             }
             ui.separator();
+            if ui.add_enabled(self.editable() && self.history.can_undo(), egui::Button::new("↶ Undo").shortcut_text("Ctrl+Z")).clicked() {
+                self.pending_history = Some(false);
+                ui.close_menu();
+            }
+            if ui.add_enabled(self.editable() && self.history.can_redo(), egui::Button::new("↷ Redo").shortcut_text("Ctrl+Y")).clicked() {
+                self.pending_history = Some(true);
+                ui.close_menu();
+            }
+            let can_format = self.editable() && self.formatting.is_none();
+            if ui.add_enabled(can_format, egui::Button::new("✨ Format Document").shortcut_text("Shift+Alt+F")).clicked() {
+                self.format_document(control);
+                ui.close_menu();
+            }
+            ui.separator();
+            if ui.add(egui::Button::new("↪ Go to Line…").shortcut_text("Ctrl+G")).clicked() {
+                self.goto = Some(GotoPopup::new(GotoMode::Line));
+                ui.close_menu();
+            }
+            let has_bookmarks = !self.bookmarks.is_empty();
+            if ui.add_enabled(has_bookmarks, egui::Button::new("🔖 Next Bookmark").shortcut_text("F2")).clicked() {
+                self.jump_to_bookmark(self.cursor_line, true);
+                ui.close_menu();
+            }
+            if ui.add_enabled(has_bookmarks, egui::Button::new("🔖 Previous Bookmark").shortcut_text("Shift+F2")).clicked() {
+                self.jump_to_bookmark(self.cursor_line, false);
+                ui.close_menu();
+            }
+            if ui.add(egui::Button::new("Toggle Bookmark").shortcut_text("Ctrl+F2")).clicked() {
+                self.toggle_bookmark(self.cursor_line);
+                ui.close_menu();
+            }
+            if ui.add(egui::Button::new("☰ Go to Symbol…").shortcut_text("Ctrl+Shift+O")).clicked() {
+                self.goto = Some(GotoPopup::new(GotoMode::Symbol));
+                ui.close_menu();
+            }
+            ui.separator();
             
             let sync_text = if self.sync_mode { "🔄 Sync Mode: ON" } else { "🔄 Sync Mode: OFF" };
             if ui.checkbox(&mut self.sync_mode, sync_text).clicked() {
+                // 打开时立即与磁盘同步，之后跟随外部修改通知
                 if self.sync_mode {
-                    self.last_sync_time = ui.input(|i| i.time);
+                    self.check_disk(ui.input(|i| i.time));
+                }
+                ui.close_menu();
+            }
+        } else if let EditorState::Large(_) = self.state {
+            if ui.add_enabled(!self.sync_mode, egui::Button::new("💾 Save")).clicked() {
+                self.save(control);
+                ui.close_menu();
+            }
+            if self.protection != Protection::Writable {
+                let label = Elevation::current().button_label();
+                if ui.add_enabled(self.is_dirty && self.elevated_save.is_none(), egui::Button::new(label)).clicked() {
+                    self.save_elevated(control);
+                    ui.close_menu();
                 }
+            }
+            if ui.button("📂 Save As...").clicked() {
+                self.save_as(control);
+                ui.close_menu();
+            }
+            ui.separator();
+            if ui.add(egui::Button::new("↪ Go to Line…").shortcut_text("Ctrl+G")).clicked() {
+                self.goto = Some(GotoPopup::new(GotoMode::Line));
+                ui.close_menu();
+            }
+        } else if let EditorState::ReadOnly(_) = self.state {
+            ui.label("Read-only: the file is too large to edit.");
+            if ui.add(egui::Button::new("↪ Go to Line…").shortcut_text("Ctrl+G")).clicked() {
+                self.goto = Some(GotoPopup::new(GotoMode::Line));
                 ui.close_menu();
             }
         } else {
@@ -264,12 +2217,46 @@ impl TabInstance for CodeEditorTab {
         }
     }
 
+    fn file_path(&self) -> Option<&std::path::Path> {
+        self.path.as_deref()
+    }
+
+    fn go_to(&mut self, line: usize, column: usize) {
+        // 文件可能仍在后台加载，跳转会在内容就绪后的第一帧执行
+        self.pending_jump = Some((line.saturating_sub(1), column.saturating_sub(1)));
+    }
+
+    fn insert_text(&mut self, text: &str) -> bool {
+        if !self.editable() {
+            return false;
+        }
+        self.pending_insert.get_or_insert_with(String::new).push_str(text);
+        true
+    }
+
+    fn apply_patch(&mut self, patch: &str) -> bool {
+        // 补丁在 Ready 状态下应用；大文件、只读视图与加载失败的标签页不接受
+        if !self.editable() || !matches!(self.state, EditorState::Loading(_) | EditorState::Ready) {
+            return false;
+        }
+        self.pending_patches.push(patch.to_string());
+        true
+    }
+
     fn box_clone(&self) -> Box<dyn TabInstance> {
         Box::new(self.clone())
     }
 }
 
-pub struct CodeEditorPlugin;
+pub struct CodeEditorPlugin {
+    config: EditorConfig,
+    /// 与所有编辑器标签页共享的设置，修改后立即生效
+    settings: Arc<Mutex<EditorConfig>>,
+    /// 设置页中待添加格式化命令的语言 ID
+    new_formatter_language: String,
+    /// 当前工作区的书签，与所有编辑器标签页共享
+    bookmarks: SharedBookmarks,
+}
 
 impl Plugin for CodeEditorPlugin {
     fn name(&self) -> &str { crate::plugins::PLUGIN_NAME_CODE_EDITOR }
@@ -278,45 +2265,75 @@ impl Plugin for CodeEditorPlugin {
         vec!["core".to_string()]
     }
 
+    fn open_support(&self, path: &std::path::Path) -> OpenSupport {
+        let ext = path.extension().and_then(|s| s.to_str()).unwrap_or("");
+        // 已知语言或纯文本为精确匹配，其余文件仅作为文本兜底
+        if language_for_extension(ext) != "txt" || matches!(ext, "txt" | "log" | "") {
+            OpenSupport::Exact
+        } else {
+            OpenSupport::Fallback
+        }
+    }
+
+    fn create_document(&mut self, text: &str, language: &str) -> Option<Box<dyn TabInstance>> {
+        let mut tab = CodeEditorTab::new(
+            "untitled".into(),
+            None,
+            text.to_string(),
+            language_for_name(language).into(),
+            self.settings.clone(),
+            self.bookmarks.clone(),
+        );
+        tab.is_dirty = !text.is_empty();
+        Some(Box::new(tab))
+    }
+
     fn try_open_file(&mut self, path: &std::path::Path) -> Option<Box<dyn TabInstance>> {
         let ext = path.extension().and_then(|s| s.to_str()).unwrap_or("");
         
-        // 映射扩展名到语法高亮 ID
-        let language = match ext {
-            "rs" => "rs",
-            "py" => "py",
-            "js" | "ts" => "js",
-            "html" => "html",
-            "css" => "css",
-            "json" => "json",
-            "md" => "md",
-            "toml" => "toml",
-            "c" | "h" => "c",
-            "cpp" | "hpp" | "cc" | "cxx" => "cpp",
-            _ => "txt",
-        };
+        let language = language_for_extension(ext);
+
+        // 不是文本的文件用十六进制编辑器打开
+        if hex::is_binary(path) {
+            return Some(Box::new(HexTab::open(path, self.settings.clone())));
+        }
 
         // 如果是已知文本格式或没有扩展名（可能是 README 等）
         if !language.is_empty() || ext.is_empty() {
+            let size = std::fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+            let large_file = (size > large::READ_ONLY_BYTES).then(|| LargeFile::open(path).ok()).flatten();
             let path_owned = path.to_path_buf();
             let result_store = Arc::new(RwLock::new(None));
             let result_store_clone = result_store.clone();
 
-            std::thread::spawn(move || {
-                let res = std::fs::read_to_string(&path_owned).map_err(|e| e.to_string());
-                *result_store_clone.write() = Some(res);
-            });
+            let state = match large_file {
+                Some(file) => EditorState::ReadOnly(file),
+                None => {
+                    std::thread::spawn(move || {
+                        let res = encoding::read(&path_owned).map_err(|e| e.to_string());
+                        *result_store_clone.write() = Some(res);
+                    });
+                    EditorState::Loading(result_store)
+                }
+            };
 
-            return Some(Box::new(CodeEditorTab {
-                name: path.file_name().unwrap_or_default().to_string_lossy().to_string(),
-                path: Some(path.to_path_buf()),
-                code: String::new(),
-                language: language.to_string(),
-                is_dirty: false,
-                sync_mode: false,
-                last_sync_time: 0.0,
-                state: EditorState::Loading(result_store),
-            }));
+            let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+            let mut tab = CodeEditorTab::new(
+                name,
+                Some(path.to_path_buf()),
+                String::new(),
+                language.to_string(),
+                self.settings.clone(),
+                self.bookmarks.clone(),
+            );
+            // 没有写权限的文件以只读方式打开，提示栏中可以选择继续编辑并提权保存
+            if let EditorState::Loading(_) = state {
+                if !elevated::is_writable(path) {
+                    tab.protection = Protection::Locked;
+                }
+            }
+            tab.state = state;
+            return Some(Box::new(tab));
         }
         None
     }
@@ -324,8 +2341,123 @@ impl Plugin for CodeEditorPlugin {
     fn on_settings_ui(&mut self, ui: &mut Ui) {
         ui.label("Editor Settings");
         ui.label("• Ctrl + S to save current file.");
-        ui.label("• Syntax highlighting is automatically applied based on extension.");
+        ui.label("• Trailing whitespace, final newlines and whitespace markers are configured under Whitespace below.");
+        ui.label("• Shift + Alt + F formats the file with the external formatter configured for its language.");
+        ui.label("• Enter keeps indentation, brackets and quotes close automatically, Ctrl + / toggles comments.");
+        ui.label("• Tab / Shift + Tab indent and outdent; indentation follows .editorconfig, the file content or the language settings.");
+        ui.label("• Suggestions pop up while typing; Ctrl + Space to show them, Enter or Tab to accept.");
+        ui.label("• Ctrl + Click adds a cursor, Ctrl + D selects the next occurrence, Alt + Drag selects a column; Esc returns to one cursor.");
+        ui.label("• Ctrl + Z to undo, Ctrl + Y or Ctrl + Shift + Z to redo; history survives saving.");
+        ui.label("• Ctrl + F to find, Ctrl + H to replace (case, whole word and regex modes).");
+        ui.label("• Syntax highlighting is automatically applied based on extension, using tree-sitter for Rust, Python, JavaScript, JSON, C, HTML, CSS and Markdown (files over 1 MB are shown plain).");
+        ui.label("• Files over 1 MB are edited in a lightweight editor that only lays out the visible lines, without highlighting, outline, find or completion.");
+        ui.label("• Files over 64 MB open in a read-only viewer that only loads the visible lines.");
+        ui.label("• Binary files open in a hex editor: type over bytes in the hex or ASCII column (Tab switches), Ctrl + F finds a byte sequence.");
+        ui.label("• Files you can't write open read-only; choose Edit Anyway, then save as administrator (or with sudo).");
+        ui.label("• GBK, Shift-JIS and UTF-16 files are detected; change encoding or line endings from the status bar.");
+        ui.label("• Files changed on disk reload automatically; you are asked first if you have unsaved changes.");
         ui.label("• Right-click tab for Sync Mode (Read-only follow file).");
+        ui.label("• Ctrl + Up / Down to jump between headings / functions.");
+        ui.label("• Markdown files open with a live preview beside the source; Ctrl + Shift + V toggles it.");
+        ui.label("• Ctrl + F2 toggles a bookmark, F2 / Shift + F2 jump between bookmarks; Tabs → Bookmarks lists them all.");
+        ui.label("• Ctrl + G to go to a line (line:column), Ctrl + Shift + O to go to a symbol.");
+        ui.label("• Ctrl + Click a link to open it; click a color swatch to edit the color.");
+        ui.label("• Click or drag over line numbers to select lines; click left of them to toggle a breakpoint.");
+
+        ui.add_space(8.0);
+        ui.group(|ui| {
+            ui.label("Saving");
+            let mut changed = false;
+            changed |= ui.checkbox(&mut self.config.autosave_on_focus_loss, "Autosave when the editor loses focus").changed();
+            changed |= ui.checkbox(&mut self.config.safe_write, "Safe save (write a temporary file, then replace the original)").changed();
+            changed |= ui.checkbox(&mut self.config.keep_backup, "Keep a .bak copy of the previous version").changed();
+            ui.weak("Timed autosave is configured under Performance.");
+            if changed {
+                *self.settings.lock() = self.config.clone();
+                self.config.save();
+            }
+        });
+        ui.group(|ui| {
+            ui.label("Indentation");
+            let mut changed = false;
+            changed |= ui.checkbox(&mut self.config.detect_indentation, "Detect indentation from the file content").changed();
+            changed |= ui.checkbox(&mut self.config.use_editorconfig, "Read .editorconfig files (takes precedence)").changed();
+            ui.weak("Applies to files opened afterwards; click the indentation in the status bar to change it for one tab.");
+            egui::Grid::new("indentation_settings").num_columns(3).striped(true).show(ui, |ui| {
+                ui.label("Default");
+                changed |= indentation_ui(ui, &mut self.config.default_indentation);
+                ui.end_row();
+                for language in ["rs", "py", "js", "json", "html", "css", "md", "toml", "c", "cpp", "txt"] {
+                    let current = self.config.indentation.get(language).copied().unwrap_or(self.config.default_indentation);
+                    let mut indentation = current;
+                    ui.label(language);
+                    indentation_ui(ui, &mut indentation);
+                    if indentation != current {
+                        self.config.indentation.insert(language.to_string(), indentation);
+                        changed = true;
+                    }
+                    ui.end_row();
+                }
+            });
+            if changed {
+                *self.settings.lock() = self.config.clone();
+                self.config.save();
+            }
+        });
+        ui.group(|ui| {
+            ui.label("Whitespace");
+            let mut changed = false;
+            changed |= ui.checkbox(&mut self.config.trim_trailing_whitespace, "Trim trailing whitespace when saving").changed();
+            changed |= ui.checkbox(&mut self.config.insert_final_newline, "Make sure files end with a newline").changed();
+            changed |= ui.checkbox(&mut self.config.render_whitespace, "Show spaces and tabs").changed();
+            if changed {
+                *self.settings.lock() = self.config.clone();
+                self.config.save();
+            }
+        });
+        ui.group(|ui| {
+            ui.label("Formatting");
+            let mut changed = ui.checkbox(&mut self.config.format_on_save, "Format on save").changed();
+            ui.weak("Commands read the code from stdin and print the formatted code; {file} is replaced with the file path.");
+            let mut removed = None;
+            egui::Grid::new("formatter_commands").num_columns(3).striped(true).show(ui, |ui| {
+                for (language, command) in self.config.formatters.iter_mut() {
+                    ui.label(language.as_str());
+                    changed |= ui
+                        .add(egui::TextEdit::singleline(command).font(egui::TextStyle::Monospace).desired_width(280.0))
+                        .changed();
+                    if ui.small_button("🗑").on_hover_text("Remove").clicked() {
+                        removed = Some(language.clone());
+                    }
+                    ui.end_row();
+                }
+            });
+            if let Some(language) = removed {
+                self.config.formatters.remove(&language);
+                changed = true;
+            }
+            ui.horizontal(|ui| {
+                ui.add(egui::TextEdit::singleline(&mut self.new_formatter_language).hint_text("Language (e.g. toml)").desired_width(140.0));
+                let language = self.new_formatter_language.trim().to_string();
+                let valid = !language.is_empty() && !self.config.formatters.contains_key(&language);
+                if ui.add_enabled(valid, egui::Button::new("➕ Add Formatter")).clicked() {
+                    self.config.formatters.insert(language, String::new());
+                    self.new_formatter_language.clear();
+                    changed = true;
+                }
+            });
+            if changed {
+                *self.settings.lock() = self.config.clone();
+                self.config.save();
+            }
+        });
+        ui.group(|ui| {
+            ui.label("Markdown");
+            if ui.checkbox(&mut self.config.markdown_preview, "Show the preview when opening Markdown files").changed() {
+                *self.settings.lock() = self.config.clone();
+                self.config.save();
+            }
+        });
     }
 
     fn on_tab_menu(&mut self, ui: &mut Ui, control: &mut Vec<AppCommand>) {
@@ -335,12 +2467,47 @@ impl Plugin for CodeEditorPlugin {
                 None,
                 String::new(),
                 "rs".into(),
+                self.settings.clone(),
+                self.bookmarks.clone(),
             )))));
             ui.close_menu();
         }
+        if ui.button("🔖 Bookmarks").clicked() {
+            control.push(AppCommand::OpenTab(Tab::new(Box::new(BookmarksTab::new(self.bookmarks.clone())))));
+            ui.close_menu();
+        }
+    }
+
+    fn on_shutdown(&mut self) {
+        // 书签跟随未保存的编辑移动后只更新了内存
+        self.bookmarks.lock().save();
+    }
+
+    fn backup_files(&self) -> Vec<std::path::PathBuf> {
+        vec![bookmarks::BOOKMARKS_FILE.into()]
     }
 }
 
+/// 设置页中的一行缩进设置：缩进字符与宽度，返回是否修改
+fn indentation_ui(ui: &mut Ui, indentation: &mut Indentation) -> bool {
+    let mut changed = false;
+    egui::ComboBox::from_id_salt(ui.next_auto_id())
+        .selected_text(if indentation.tabs { "Tabs" } else { "Spaces" })
+        .width(80.0)
+        .show_ui(ui, |ui| {
+            changed |= ui.selectable_value(&mut indentation.tabs, false, "Spaces").changed();
+            changed |= ui.selectable_value(&mut indentation.tabs, true, "Tabs").changed();
+        });
+    changed |= ui.add(egui::DragValue::new(&mut indentation.width).range(1..=16).suffix(" wide")).changed();
+    changed
+}
+
 pub fn create() -> CodeEditorPlugin {
-    CodeEditorPlugin
+    let config = EditorConfig::load();
+    CodeEditorPlugin {
+        settings: Arc::new(Mutex::new(config.clone())),
+        config,
+        new_formatter_language: String::new(),
+        bookmarks: Arc::new(Mutex::new(Bookmarks::load())),
+    }
 }
\ No newline at end of file
@@ -0,0 +1,199 @@
+/// 轻量级大纲索引：按语言用简单的行规则提取标题 / 函数等结构元素
+#[derive(Debug, Clone, PartialEq)]
+pub struct Symbol {
+    pub name: String,
+    pub kind: SymbolKind,
+    /// 0 起始的行号
+    pub line: usize,
+    /// 嵌套层级（Markdown 为标题级别，代码为缩进层级）
+    pub level: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymbolKind {
+    Heading,
+    Function,
+    Type,
+    Module,
+    Section,
+}
+
+impl SymbolKind {
+    pub fn icon(&self) -> &'static str {
+        match self {
+            SymbolKind::Heading => "#",
+            SymbolKind::Function => "ƒ",
+            SymbolKind::Type => "◆",
+            SymbolKind::Module => "▣",
+            SymbolKind::Section => "§",
+        }
+    }
+}
+
+pub fn build_outline(code: &str, language: &str) -> Vec<Symbol> {
+    match language {
+        "md" => markdown_outline(code),
+        "toml" => toml_outline(code),
+        "rs" | "py" | "js" | "c" | "cpp" => code_outline(code, language),
+        _ => Vec::new(),
+    }
+}
+
+fn markdown_outline(code: &str) -> Vec<Symbol> {
+    let mut symbols = Vec::new();
+    let mut in_fence = false;
+    for (line, text) in code.lines().enumerate() {
+        let trimmed = text.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+        let level = trimmed.chars().take_while(|c| *c == '#').count();
+        if (1..=6).contains(&level) && trimmed[level..].starts_with(' ') {
+            symbols.push(Symbol {
+                name: trimmed[level..].trim().trim_end_matches('#').trim().to_string(),
+                kind: SymbolKind::Heading,
+                line,
+                level,
+            });
+        }
+    }
+    symbols
+}
+
+fn toml_outline(code: &str) -> Vec<Symbol> {
+    code.lines()
+        .enumerate()
+        .filter_map(|(line, text)| {
+            let trimmed = text.trim();
+            if trimmed.starts_with('[') && trimmed.ends_with(']') {
+                let name = trimmed.trim_matches(|c| c == '[' || c == ']').trim().to_string();
+                Some(Symbol { name, kind: SymbolKind::Section, line, level: 0 })
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// 读取标识符（字母、数字、下划线）
+fn take_ident(s: &str) -> Option<String> {
+    let ident: String = s.trim_start().chars().take_while(|c| c.is_alphanumeric() || *c == '_').collect();
+    if ident.is_empty() { None } else { Some(ident) }
+}
+
+/// 去掉开头的修饰关键字（可见性、async 等）
+fn strip_modifiers<'a>(mut s: &'a str, modifiers: &[&str]) -> &'a str {
+    loop {
+        let before = s;
+        if s.starts_with("pub(") {
+            if let Some(end) = s.find(')') {
+                s = s[end + 1..].trim_start();
+            }
+        }
+        for m in modifiers {
+            if let Some(rest) = s.strip_prefix(m) {
+                if rest.starts_with(' ') {
+                    s = rest.trim_start();
+                }
+            }
+        }
+        if s == before {
+            return s;
+        }
+    }
+}
+
+fn code_outline(code: &str, language: &str) -> Vec<Symbol> {
+    let mut symbols = Vec::new();
+    for (line, text) in code.lines().enumerate() {
+        let trimmed = text.trim_start();
+        if trimmed.is_empty() || trimmed.starts_with("//") || trimmed.starts_with('#') {
+            continue;
+        }
+        let indent = text.len() - trimmed.len();
+        let level = indent / 4;
+
+        let found = match language {
+            "rs" => {
+                let s = strip_modifiers(trimmed, &["pub", "async", "unsafe", "const", "extern", "default"]);
+                if let Some(rest) = s.strip_prefix("fn ") {
+                    take_ident(rest).map(|n| (n, SymbolKind::Function))
+                } else if let Some(rest) = s.strip_prefix("struct ").or_else(|| s.strip_prefix("enum ")).or_else(|| s.strip_prefix("trait ")) {
+                    take_ident(rest).map(|n| (n, SymbolKind::Type))
+                } else if let Some(rest) = s.strip_prefix("mod ") {
+                    take_ident(rest).map(|n| (n, SymbolKind::Module))
+                } else if s.starts_with("impl") && (s.starts_with("impl ") || s.starts_with("impl<")) {
+                    let header = s.trim_end_matches('{').trim().to_string();
+                    Some((header, SymbolKind::Type))
+                } else {
+                    None
+                }
+            }
+            "py" => {
+                let s = strip_modifiers(trimmed, &["async"]);
+                if let Some(rest) = s.strip_prefix("def ") {
+                    take_ident(rest).map(|n| (n, SymbolKind::Function))
+                } else if let Some(rest) = s.strip_prefix("class ") {
+                    take_ident(rest).map(|n| (n, SymbolKind::Type))
+                } else {
+                    None
+                }
+            }
+            "js" => {
+                let s = strip_modifiers(trimmed, &["export", "default", "async"]);
+                if let Some(rest) = s.strip_prefix("function ").or_else(|| s.strip_prefix("function* ")) {
+                    take_ident(rest).map(|n| (n, SymbolKind::Function))
+                } else if let Some(rest) = s.strip_prefix("class ") {
+                    take_ident(rest).map(|n| (n, SymbolKind::Type))
+                } else if let Some(rest) = s.strip_prefix("const ").or_else(|| s.strip_prefix("let ")) {
+                    // const foo = (...) => / const foo = function
+                    if s.contains("=>") || s.contains("= function") {
+                        take_ident(rest).map(|n| (n, SymbolKind::Function))
+                    } else {
+                        None
+                    }
+                } else {
+                    None
+                }
+            }
+            // C / C++：顶格且形如 `type name(...)` 的行视为函数定义
+            _ => {
+                if indent == 0 && trimmed.contains('(') && !trimmed.ends_with(';') {
+                    let before_paren = &trimmed[..trimmed.find('(').unwrap_or(0)];
+                    let name = before_paren.split_whitespace().last().unwrap_or("").trim_start_matches(['*', '&']);
+                    let is_keyword = matches!(name, "if" | "for" | "while" | "switch" | "return" | "sizeof");
+                    if !name.is_empty() && !is_keyword && before_paren.split_whitespace().count() >= 2 {
+                        Some((name.to_string(), SymbolKind::Function))
+                    } else {
+                        None
+                    }
+                } else if let Some(rest) = trimmed.strip_prefix("struct ").or_else(|| trimmed.strip_prefix("class ")) {
+                    if trimmed.ends_with(';') { None } else { take_ident(rest).map(|n| (n, SymbolKind::Type)) }
+                } else {
+                    None
+                }
+            }
+        };
+
+        if let Some((name, kind)) = found {
+            symbols.push(Symbol { name, kind, line, level });
+        }
+    }
+    symbols
+}
+
+/// 返回包含指定行的符号链（从外到内），用于面包屑显示
+pub fn enclosing_symbols(symbols: &[Symbol], line: usize) -> Vec<&Symbol> {
+    let mut stack: Vec<&Symbol> = Vec::new();
+    for symbol in symbols.iter().take_while(|s| s.line <= line) {
+        while stack.last().is_some_and(|top| top.level >= symbol.level) {
+            stack.pop();
+        }
+        stack.push(symbol);
+    }
+    stack
+}
@@ -0,0 +1,498 @@
+use std::ops::Range;
+use ropey::Rope;
+
+/// 单行最多排版的字符数，超长的行（压缩过的 JSON、日志）只显示开头
+const MAX_LINE_CHARS: usize = 10_000;
+/// 间隔不超过该秒数的连续输入合并为一步撤销
+const MERGE_SECS: f64 = 1.0;
+
+// ----------------------------------------------------------------------------
+// 大文件编辑器：文本保存在 rope 中，编辑只修改局部；每帧只排版并绘制视口内的行。
+// 语法高亮、补全、多光标等依赖整段文本的功能在此模式下不可用
+// ----------------------------------------------------------------------------
+
+/// 一次编辑：在 `at` 处删除 `removed` 并插入 `inserted`，撤销时反向执行
+#[derive(Clone)]
+struct Edit {
+    at: usize,
+    removed: String,
+    inserted: String,
+    /// 编辑前的 (光标, 选区起点)
+    before: (usize, usize),
+    time: f64,
+}
+
+#[derive(Clone)]
+pub struct RopeEditor {
+    rope: Rope,
+    /// 光标与选区起点（字符索引），两者相等时没有选区
+    cursor: usize,
+    anchor: usize,
+    /// 上下移动时保持的列
+    preferred_column: Option<usize>,
+    undo: Vec<Edit>,
+    redo: Vec<Edit>,
+    /// 与磁盘内容一致时撤销栈的深度，之后的编辑无法再回到该状态时为 None
+    saved_depth: Option<usize>,
+    /// 光标移动后需要把所在行滚入视口
+    scroll_to_cursor: bool,
+    /// 上一帧视口的第一行与可见行数
+    viewport: (usize, usize),
+}
+
+impl std::fmt::Debug for RopeEditor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RopeEditor")
+            .field("chars", &self.rope.len_chars())
+            .field("lines", &self.rope.len_lines())
+            .field("cursor", &self.cursor)
+            .finish()
+    }
+}
+
+impl RopeEditor {
+    pub fn new(text: &str) -> Self {
+        Self {
+            rope: Rope::from_str(text),
+            cursor: 0,
+            anchor: 0,
+            preferred_column: None,
+            undo: Vec::new(),
+            redo: Vec::new(),
+            saved_depth: Some(0),
+            scroll_to_cursor: false,
+            viewport: (0, 0),
+        }
+    }
+
+    pub fn text(&self) -> String {
+        self.rope.to_string()
+    }
+
+    pub fn len_bytes(&self) -> usize {
+        self.rope.len_bytes()
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.rope.len_lines()
+    }
+
+    /// 内容与上次保存时不同
+    pub fn is_modified(&self) -> bool {
+        self.saved_depth != Some(self.undo.len())
+    }
+
+    pub fn mark_saved(&mut self) {
+        self.saved_depth = Some(self.undo.len());
+    }
+
+    /// 光标所在的 (行, 列)，从 0 开始
+    pub fn cursor_position(&self) -> (usize, usize) {
+        let line = self.rope.char_to_line(self.cursor);
+        (line, self.cursor - self.rope.line_to_char(line))
+    }
+
+    /// 把光标移到 (行, 列)，超出范围时停在最后一行或行尾
+    pub fn jump(&mut self, line: usize, column: usize) {
+        let line = line.min(self.line_count() - 1);
+        let pos = self.rope.line_to_char(line) + column.min(self.line_len(line));
+        self.move_to(pos, false);
+    }
+
+    /// 在光标处插入文本，替换选中的内容
+    pub fn insert(&mut self, text: &str, time: f64) {
+        self.apply(self.selection(), text, time);
+    }
+
+    /// 用新内容替换全部文本（例如从磁盘重新加载），作为一次编辑记入历史
+    pub fn replace_all(&mut self, text: &str, time: f64) {
+        self.apply(0..self.rope.len_chars(), text, time);
+        self.move_to(0, false);
+    }
+
+    fn selection(&self) -> Range<usize> {
+        self.cursor.min(self.anchor)..self.cursor.max(self.anchor)
+    }
+
+    fn selected_text(&self) -> Option<String> {
+        let range = self.selection();
+        (!range.is_empty()).then(|| self.rope.slice(range).to_string())
+    }
+
+    /// 行的字符数，不含换行符
+    fn line_len(&self, line: usize) -> usize {
+        let slice = self.rope.line(line);
+        let mut len = slice.len_chars();
+        if len > 0 && slice.char(len - 1) == '\n' {
+            len -= 1;
+            if len > 0 && slice.char(len - 1) == '\r' {
+                len -= 1;
+            }
+        }
+        len
+    }
+
+    fn line_text(&self, line: usize) -> String {
+        let len = self.line_len(line);
+        let slice = self.rope.line(line).slice(..len.min(MAX_LINE_CHARS));
+        let mut text = slice.to_string();
+        if len > MAX_LINE_CHARS {
+            text.push_str(" …");
+        }
+        text
+    }
+
+    fn move_to(&mut self, pos: usize, select: bool) {
+        self.cursor = pos.min(self.rope.len_chars());
+        if !select {
+            self.anchor = self.cursor;
+        }
+        self.scroll_to_cursor = true;
+    }
+
+    fn apply(&mut self, range: Range<usize>, text: &str, time: f64) {
+        let removed = self.rope.slice(range.clone()).to_string();
+        if removed.is_empty() && text.is_empty() {
+            return;
+        }
+        let before = (self.cursor, self.anchor);
+        self.rope.remove(range.clone());
+        self.rope.insert(range.start, text);
+        self.move_to(range.start + text.chars().count(), false);
+        self.preferred_column = None;
+        self.redo.clear();
+        if self.saved_depth.is_some_and(|depth| depth > self.undo.len()) {
+            self.saved_depth = None;
+        }
+
+        // 连续输入同一处的文字合并为一步撤销，换行后另起一步
+        let typing = removed.is_empty() && !text.contains('\n');
+        let mergeable = self.saved_depth != Some(self.undo.len());
+        if let Some(last) = self.undo.last_mut().filter(|_| typing && mergeable) {
+            if last.removed.is_empty()
+                && !last.inserted.ends_with('\n')
+                && time - last.time < MERGE_SECS
+                && last.at + last.inserted.chars().count() == range.start
+            {
+                last.inserted.push_str(text);
+                last.time = time;
+                return;
+            }
+        }
+        self.undo.push(Edit { at: range.start, removed, inserted: text.to_string(), before, time });
+    }
+
+    fn undo(&mut self) -> bool {
+        let Some(edit) = self.undo.pop() else { return false };
+        let end = edit.at + edit.inserted.chars().count();
+        self.rope.remove(edit.at..end);
+        self.rope.insert(edit.at, &edit.removed);
+        (self.cursor, self.anchor) = edit.before;
+        self.scroll_to_cursor = true;
+        self.redo.push(edit);
+        true
+    }
+
+    fn redo(&mut self) -> bool {
+        let Some(edit) = self.redo.pop() else { return false };
+        let end = edit.at + edit.removed.chars().count();
+        self.rope.remove(edit.at..end);
+        self.rope.insert(edit.at, &edit.inserted);
+        self.move_to(edit.at + edit.inserted.chars().count(), false);
+        self.undo.push(edit);
+        true
+    }
+
+    /// 向左跳过空白，再跳过一段同类字符（单词或标点）
+    fn word_left(&self, mut pos: usize) -> usize {
+        while pos > 0 && self.rope.char(pos - 1).is_whitespace() {
+            pos -= 1;
+        }
+        let word = pos > 0 && is_word(self.rope.char(pos - 1));
+        while pos > 0 && !self.rope.char(pos - 1).is_whitespace() && is_word(self.rope.char(pos - 1)) == word {
+            pos -= 1;
+        }
+        pos
+    }
+
+    fn word_right(&self, mut pos: usize) -> usize {
+        let len = self.rope.len_chars();
+        while pos < len && self.rope.char(pos).is_whitespace() {
+            pos += 1;
+        }
+        let word = pos < len && is_word(self.rope.char(pos));
+        while pos < len && !self.rope.char(pos).is_whitespace() && is_word(self.rope.char(pos)) == word {
+            pos += 1;
+        }
+        pos
+    }
+
+    /// 双击选中光标处的单词
+    fn select_word(&mut self, pos: usize) {
+        let len = self.rope.len_chars();
+        if pos >= len || !is_word(self.rope.char(pos)) {
+            return;
+        }
+        let mut start = pos;
+        while start > 0 && is_word(self.rope.char(start - 1)) {
+            start -= 1;
+        }
+        let mut end = pos;
+        while end < len && is_word(self.rope.char(end)) {
+            end += 1;
+        }
+        self.anchor = start;
+        self.cursor = end;
+    }
+
+    /// 上下移动 `delta` 行，保持移动前的列
+    fn move_lines(&mut self, delta: isize, select: bool) {
+        let (line, column) = self.cursor_position();
+        let column = *self.preferred_column.get_or_insert(column);
+        let target = line.saturating_add_signed(delta).min(self.line_count() - 1);
+        let pos = self.rope.line_to_char(target) + column.min(self.line_len(target));
+        self.move_to(pos, select);
+    }
+
+    fn delete(&mut self, range: Range<usize>, time: f64) {
+        let range = range.start..range.end.min(self.rope.len_chars());
+        if !range.is_empty() {
+            self.apply(range, "", time);
+        }
+    }
+
+    /// 处理一次按键，返回内容是否被修改
+    fn key(&mut self, key: egui::Key, modifiers: egui::Modifiers, editable: bool, indent: &str, time: f64) -> bool {
+        use egui::Key;
+        let select = modifiers.shift;
+        let word = modifiers.command || modifiers.alt;
+        let selection = self.selection();
+        let (line, _) = self.cursor_position();
+        if !matches!(key, Key::ArrowUp | Key::ArrowDown | Key::PageUp | Key::PageDown) {
+            self.preferred_column = None;
+        }
+        match key {
+            Key::ArrowLeft if !select && !selection.is_empty() => self.move_to(selection.start, false),
+            Key::ArrowRight if !select && !selection.is_empty() => self.move_to(selection.end, false),
+            Key::ArrowLeft if word => self.move_to(self.word_left(self.cursor), select),
+            Key::ArrowRight if word => self.move_to(self.word_right(self.cursor), select),
+            Key::ArrowLeft => self.move_to(self.cursor.saturating_sub(1), select),
+            Key::ArrowRight => self.move_to(self.cursor + 1, select),
+            Key::ArrowUp => self.move_lines(-1, select),
+            Key::ArrowDown => self.move_lines(1, select),
+            Key::PageUp => self.move_lines(-(self.viewport.1.max(2) as isize - 1), select),
+            Key::PageDown => self.move_lines(self.viewport.1.max(2) as isize - 1, select),
+            Key::Home if modifiers.command => self.move_to(0, select),
+            Key::End if modifiers.command => self.move_to(self.rope.len_chars(), select),
+            Key::Home => {
+                // 先跳到缩进之后，已在该处时跳到行首
+                let start = self.rope.line_to_char(line);
+                let indent_len = self.rope.line(line).chars().take_while(|c| *c == ' ' || *c == '\t').count();
+                let target = if self.cursor == start + indent_len { start } else { start + indent_len };
+                self.move_to(target, select);
+            }
+            Key::End => self.move_to(self.rope.line_to_char(line) + self.line_len(line), select),
+            Key::A if modifiers.command => {
+                self.anchor = 0;
+                self.cursor = self.rope.len_chars();
+            }
+            Key::Z if modifiers.command && editable => {
+                return if modifiers.shift { self.redo() } else { self.undo() };
+            }
+            Key::Y if modifiers.command && editable => return self.redo(),
+            Key::Backspace if editable => {
+                let start = match (selection.is_empty(), word) {
+                    (false, _) => selection.start,
+                    (true, true) => self.word_left(self.cursor),
+                    (true, false) => self.cursor.saturating_sub(1),
+                };
+                self.delete(start..selection.end, time);
+                return true;
+            }
+            Key::Delete if editable => {
+                let end = match (selection.is_empty(), word) {
+                    (false, _) => selection.end,
+                    (true, true) => self.word_right(self.cursor),
+                    (true, false) => self.cursor + 1,
+                };
+                self.delete(selection.start..end, time);
+                return true;
+            }
+            Key::Enter if editable => {
+                // 新行沿用当前行的缩进
+                let leading: String = self.rope.line(line).chars().take_while(|c| *c == ' ' || *c == '\t').collect();
+                self.insert(&format!("\n{}", leading), time);
+                return true;
+            }
+            Key::Tab if editable && !modifiers.shift => {
+                self.insert(indent, time);
+                return true;
+            }
+            _ => {}
+        }
+        false
+    }
+
+    /// 处理键盘与剪贴板事件，返回内容是否被修改
+    fn handle_input(&mut self, ui: &egui::Ui, editable: bool, indent: &str) -> bool {
+        let (events, time) = ui.input(|i| (i.events.clone(), i.time));
+        let mut changed = false;
+        for event in events {
+            match event {
+                egui::Event::Text(text) if editable => {
+                    self.insert(&text, time);
+                    changed = true;
+                }
+                egui::Event::Paste(text) if editable => {
+                    self.insert(&text.replace("\r\n", "\n"), time);
+                    changed = true;
+                }
+                egui::Event::Copy => {
+                    if let Some(text) = self.selected_text() {
+                        ui.ctx().copy_text(text);
+                    }
+                }
+                egui::Event::Cut => {
+                    if let Some(text) = self.selected_text() {
+                        ui.ctx().copy_text(text);
+                        if editable {
+                            self.delete(self.selection(), time);
+                            changed = true;
+                        }
+                    }
+                }
+                egui::Event::Key { key, pressed: true, modifiers, .. } => {
+                    changed |= self.key(key, modifiers, editable, indent, time);
+                }
+                _ => {}
+            }
+        }
+        changed
+    }
+
+    /// 第 `line` 行中距文本左边缘 `x` 处的字符索引
+    fn pos_at(&self, ui: &egui::Ui, line: usize, x: f32, font_id: &egui::FontId) -> usize {
+        let galley = ui.fonts(|f| f.layout_no_wrap(self.line_text(line), font_id.clone(), egui::Color32::PLACEHOLDER));
+        let column = galley.cursor_from_pos(egui::vec2(x, 0.0)).ccursor.index;
+        self.rope.line_to_char(line) + column.min(self.line_len(line))
+    }
+
+    /// 绘制编辑器，返回内容是否被修改。`indent` 为 Tab 键插入的文本
+    pub fn show(&mut self, ui: &mut egui::Ui, editable: bool, indent: &str) -> bool {
+        let id = ui.make_persistent_id("rope_editor");
+        let font_id = egui::TextStyle::Monospace.resolve(ui.style());
+        let row_height = ui.fonts(|f| f.row_height(&font_id));
+        let digit_width = ui.fonts(|f| f.glyph_width(&font_id, '0'));
+        let has_focus = ui.memory(|m| m.has_focus(id));
+
+        let mut changed = false;
+        if has_focus {
+            // 方向键与 Tab 留给编辑器，不用于切换焦点
+            ui.memory_mut(|m| {
+                m.set_focus_lock_filter(id, egui::EventFilter { tab: true, horizontal_arrows: true, vertical_arrows: true, escape: false })
+            });
+            changed = self.handle_input(ui, editable, indent);
+        }
+
+        let line_count = self.line_count();
+        let gutter_width = (line_count.to_string().len().max(3) as f32 + 1.5) * digit_width;
+        let mut scroll = egui::ScrollArea::both().id_salt(id).auto_shrink([false, false]);
+        if std::mem::take(&mut self.scroll_to_cursor) {
+            let (line, _) = self.cursor_position();
+            let (first, visible) = self.viewport;
+            if line < first || line + 1 >= first + visible {
+                let target = line.saturating_sub(visible / 2);
+                scroll = scroll.vertical_scroll_offset(target as f32 * row_height);
+            }
+        }
+
+        let visuals = ui.visuals().clone();
+        let output = ui.scope(|ui| {
+            ui.spacing_mut().item_spacing.y = 0.0;
+            scroll.show_rows(ui, row_height, line_count, |ui, rows| {
+                let galleys: Vec<_> = rows
+                    .clone()
+                    .map(|line| ui.fonts(|f| f.layout_no_wrap(self.line_text(line), font_id.clone(), visuals.text_color())))
+                    .collect();
+                let text_width = galleys.iter().map(|g| g.size().x).fold(0.0, f32::max);
+                let width = ui.available_width().max(gutter_width + text_width + digit_width * 2.0);
+                let (rect, response) = ui.allocate_exact_size(egui::vec2(width, rows.len() as f32 * row_height), egui::Sense::click_and_drag());
+                let text_left = rect.left() + gutter_width;
+
+                // 指针：单击定位光标（Shift 扩展选区），拖动选择，双击选中单词
+                if let Some(pointer) = response.interact_pointer_pos() {
+                    // 拖动到视口之外时停在首行或末行
+                    let offset = ((pointer.y - rect.top()) / row_height).floor() as isize;
+                    let line = rows.start.saturating_add_signed(offset).min(line_count - 1);
+                    let pos = self.pos_at(ui, line, pointer.x - text_left, &font_id);
+                    if response.double_clicked() {
+                        self.select_word(pos);
+                    } else if response.drag_started() || response.clicked() {
+                        let extend = ui.input(|i| i.modifiers.shift);
+                        self.move_to(pos, extend);
+                        self.scroll_to_cursor = false;
+                        ui.memory_mut(|m| m.request_focus(id));
+                    } else if response.dragged() {
+                        self.cursor = pos;
+                    }
+                    self.preferred_column = None;
+                }
+                if response.hovered() {
+                    ui.ctx().set_cursor_icon(egui::CursorIcon::Text);
+                }
+
+                let painter = ui.painter_at(ui.clip_rect());
+                let selection = self.selection();
+                let cursor = self.cursor_position();
+                for (i, galley) in galleys.into_iter().enumerate() {
+                    let line = rows.start + i;
+                    let top = rect.top() + i as f32 * row_height;
+                    let line_start = self.rope.line_to_char(line);
+                    let line_len = self.line_len(line);
+
+                    let number_color = if line == cursor.0 { visuals.strong_text_color() } else { visuals.weak_text_color() };
+                    painter.text(
+                        egui::pos2(text_left - digit_width, top),
+                        egui::Align2::RIGHT_TOP,
+                        (line + 1).to_string(),
+                        font_id.clone(),
+                        number_color,
+                    );
+
+                    // 选区与本行的交集；选区跨过行尾时多画一个字符宽度表示换行符
+                    let start = selection.start.max(line_start);
+                    let end = selection.end.min(line_start + line_len);
+                    if !selection.is_empty() && start <= end && selection.end > line_start && selection.start <= line_start + line_len {
+                        let x = |column: usize| galley.pos_from_ccursor(egui::text::CCursor::new(column)).min.x;
+                        let newline = if selection.end > line_start + line_len { digit_width } else { 0.0 };
+                        let sel_rect = egui::Rect::from_min_max(
+                            egui::pos2(text_left + x(start - line_start), top),
+                            egui::pos2(text_left + x(end - line_start) + newline, top + row_height),
+                        );
+                        painter.rect_filled(sel_rect, 0.0, visuals.selection.bg_fill);
+                    }
+
+                    painter.galley(egui::pos2(text_left, top), galley.clone(), visuals.text_color());
+
+                    if has_focus && line == cursor.0 {
+                        let x = text_left + galley.pos_from_ccursor(egui::text::CCursor::new(cursor.1)).min.x;
+                        painter.line_segment(
+                            [egui::pos2(x, top), egui::pos2(x, top + row_height)],
+                            visuals.text_cursor.stroke,
+                        );
+                    }
+                }
+            })
+        });
+
+        let state = output.inner;
+        let first = (state.state.offset.y / row_height).floor() as usize;
+        let visible = (state.inner_rect.height() / row_height).floor() as usize;
+        self.viewport = (first, visible);
+        changed
+    }
+}
+
+fn is_word(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
@@ -0,0 +1,266 @@
+use std::ops::Range;
+use super::config::Indentation;
+
+// ----------------------------------------------------------------------------
+// 编辑辅助：换行保持缩进、Tab 缩进、括号 / 引号自动补全、切换行注释
+// 选区与返回的光标均为字符索引
+// ----------------------------------------------------------------------------
+
+const PAIRS: [(char, char); 6] = [('(', ')'), ('[', ']'), ('{', '}'), ('"', '"'), ('\'', '\''), ('`', '`')];
+
+/// 语言的注释语法
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommentSyntax {
+    Line(&'static str),
+    Block(&'static str, &'static str),
+}
+
+/// 按 `language_for_extension` 给出的语言 ID 选择注释语法；JSON 与纯文本没有注释
+pub fn comment_syntax(language: &str) -> Option<CommentSyntax> {
+    match language {
+        "rs" | "js" | "c" | "cpp" => Some(CommentSyntax::Line("//")),
+        "py" | "toml" => Some(CommentSyntax::Line("#")),
+        "html" | "md" => Some(CommentSyntax::Block("<!--", "-->")),
+        "css" => Some(CommentSyntax::Block("/*", "*/")),
+        _ => None,
+    }
+}
+
+fn byte_of(code: &str, char_idx: usize) -> usize {
+    code.char_indices().nth(char_idx).map(|(i, _)| i).unwrap_or(code.len())
+}
+
+fn char_of(code: &str, byte: usize) -> usize {
+    code[..byte].chars().count()
+}
+
+/// 字节偏移所在行的起止字节（不含换行符）
+fn line_bounds(code: &str, byte: usize) -> Range<usize> {
+    let start = code[..byte].rfind('\n').map_or(0, |i| i + 1);
+    let end = code[byte..].find('\n').map_or(code.len(), |i| byte + i);
+    start..end
+}
+
+fn indentation(line: &str) -> &str {
+    &line[..line.len() - line.trim_start_matches([' ', '\t']).len()]
+}
+
+/// 按文件中已有的缩进推断缩进方式：制表符缩进的行更多时用制表符，否则取最小的空格缩进
+/// （2 到 8 个）为宽度，不在此范围时沿用 `fallback_width`。文件还没有缩进的行时返回 None
+pub fn detect_indentation(code: &str, fallback_width: usize) -> Option<Indentation> {
+    let tabs = code.lines().filter(|l| l.starts_with('\t')).count();
+    // 单个空格多半是块注释的续行，不计入
+    let space_widths: Vec<usize> = code
+        .lines()
+        .filter(|l| l.starts_with("  ") && !l.trim().is_empty())
+        .map(|l| l.len() - l.trim_start_matches(' ').len())
+        .collect();
+    if tabs == 0 && space_widths.is_empty() {
+        return None;
+    }
+    if tabs > space_widths.len() {
+        return Some(Indentation { tabs: true, width: fallback_width });
+    }
+    let width = space_widths.iter().copied().min().filter(|w| (2..=8).contains(w)).unwrap_or(fallback_width);
+    Some(Indentation { tabs: false, width })
+}
+
+/// Enter：换行并沿用当前行的缩进；光标前是左括号（Python 中为冒号）时多缩进一级（`unit`），
+/// 光标后紧跟对应的右括号时把它移到单独的一行。返回新的光标位置
+pub fn newline(code: &mut String, selection: Range<usize>, language: &str, unit: &str) -> usize {
+    let start = byte_of(code, selection.start);
+    let end = byte_of(code, selection.end);
+    let line = line_bounds(code, start);
+    let indent = indentation(&code[line.start..start]).to_string();
+    let before = code[line.start..start].trim_end().chars().last();
+    let after = code[end..].chars().next();
+
+    let opens_block = matches!(before, Some('{' | '[' | '(')) || (language == "py" && before == Some(':'));
+    let mut inserted = format!("\n{}", indent);
+    let mut cursor = selection.start + inserted.chars().count();
+    if opens_block {
+        inserted.push_str(unit);
+        cursor += unit.chars().count();
+        let closes = PAIRS.iter().any(|(open, close)| Some(*open) == before && Some(*close) == after);
+        if closes {
+            inserted.push('\n');
+            inserted.push_str(&indent);
+        }
+    }
+    code.replace_range(start..end, &inserted);
+    cursor
+}
+
+/// Tab：选区跨多行时给各行增加一级缩进，否则插入缩进（空格缩进时补齐到下一个缩进位置）；
+/// Shift + Tab（`outdent`）减少选区所在各行的一级缩进。返回新的选区 (主光标, 另一端)
+pub fn indent(code: &mut String, selection: Range<usize>, indentation: Indentation, outdent: bool) -> (usize, usize) {
+    let start = byte_of(code, selection.start);
+    let mut end = byte_of(code, selection.end);
+    if !outdent && !code[start..end].contains('\n') {
+        let unit = if indentation.tabs {
+            "\t".to_string()
+        } else {
+            let column = code[line_bounds(code, start).start..start].chars().count();
+            " ".repeat(indentation.width - column % indentation.width.max(1))
+        };
+        code.replace_range(start..end, &unit);
+        let cursor = selection.start + unit.chars().count();
+        return (cursor, cursor);
+    }
+
+    // 选区结束于下一行行首时不包括那一行
+    if end > start && code[..end].ends_with('\n') {
+        end -= 1;
+    }
+    let first = line_bounds(code, start).start;
+    let last = line_bounds(code, end).end;
+    let block = code[first..last].to_string();
+    let unit = indentation.unit();
+    let mut first_line_delta = 0isize;
+    let replaced = block
+        .split('\n')
+        .enumerate()
+        .map(|(i, line)| {
+            let new_line = if outdent {
+                let remove = if line.starts_with('\t') {
+                    1
+                } else {
+                    line.chars().take(indentation.width).take_while(|c| *c == ' ').count()
+                };
+                line[remove..].to_string()
+            } else if line.trim().is_empty() {
+                line.to_string()
+            } else {
+                format!("{}{}", unit, line)
+            };
+            if i == 0 {
+                first_line_delta = new_line.chars().count() as isize - line.chars().count() as isize;
+            }
+            new_line
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    code.replace_range(first..last, &replaced);
+
+    let first_char = char_of(code, first);
+    if selection.is_empty() {
+        // 单行且没有选区时保持光标在原来的文字上
+        let cursor = (selection.start as isize + first_line_delta).max(first_char as isize) as usize;
+        return (cursor, cursor);
+    }
+    (first_char + replaced.chars().count(), first_char)
+}
+
+/// 输入括号或引号：有选区时用括号包围选区，光标后为空白或右括号时成对插入，
+/// 输入的右括号与光标后的字符相同时直接跳过。返回新的选区 (主光标, 另一端)，None 表示不处理
+pub fn type_pair(code: &mut String, selection: Range<usize>, typed: char) -> Option<(usize, usize)> {
+    let start = byte_of(code, selection.start);
+    let end = byte_of(code, selection.end);
+    let before = code[..start].chars().last();
+    let after = code[end..].chars().next();
+    let is_quote = matches!(typed, '"' | '\'' | '`');
+
+    if selection.is_empty() && after == Some(typed) && PAIRS.iter().any(|(_, close)| *close == typed) {
+        return Some((selection.start + 1, selection.start + 1));
+    }
+    let close = PAIRS.iter().find(|(open, _)| *open == typed).map(|(_, close)| *close)?;
+    if !selection.is_empty() {
+        code.insert(end, close);
+        code.insert(start, typed);
+        return Some((selection.end + 1, selection.start + 1));
+    }
+    let free_after = after.is_none_or(|c| c.is_whitespace() || ")]};,".contains(c));
+    // 单词后的引号多半是撇号或生命周期，不补全
+    let free_before = !is_quote || before.is_none_or(|c| !c.is_alphanumeric() && c != typed);
+    if !free_after || !free_before {
+        return None;
+    }
+    code.insert_str(start, &format!("{}{}", typed, close));
+    Some((selection.start + 1, selection.start + 1))
+}
+
+/// Backspace：光标位于一对空括号之间时一并删除右括号。返回新的光标位置
+pub fn delete_pair(code: &mut String, cursor: usize) -> Option<usize> {
+    let byte = byte_of(code, cursor);
+    let before = code[..byte].chars().last()?;
+    let after = code[byte..].chars().next()?;
+    if !PAIRS.contains(&(before, after)) {
+        return None;
+    }
+    code.replace_range(byte - before.len_utf8()..byte + after.len_utf8(), "");
+    Some(cursor - 1)
+}
+
+/// 切换选区所在各行的注释。行注释在最小缩进处添加前缀，全部已注释时移除；
+/// 块注释包围或拆除整段。返回新的选区 (主光标, 另一端)
+pub fn toggle_comment(code: &mut String, selection: Range<usize>, syntax: CommentSyntax) -> (usize, usize) {
+    let start = byte_of(code, selection.start);
+    let mut end = byte_of(code, selection.end);
+    // 选区结束于下一行行首时不包括那一行
+    if end > start && code[..end].ends_with('\n') {
+        end -= 1;
+    }
+    let first = line_bounds(code, start).start;
+    let last = line_bounds(code, end).end;
+    let block = code[first..last].to_string();
+
+    let replaced = match syntax {
+        CommentSyntax::Line(prefix) => toggle_line_comments(&block, prefix),
+        CommentSyntax::Block(open, close) => toggle_block_comment(&block, open, close),
+    };
+    code.replace_range(first..last, &replaced);
+
+    let first_char = char_of(code, first);
+    if selection.is_empty() && !replaced.contains('\n') {
+        // 单行且没有选区时保持光标在原来的文字上
+        let delta = replaced.chars().count() as isize - block.chars().count() as isize;
+        let cursor = (selection.start as isize + delta).max(first_char as isize) as usize;
+        return (cursor, cursor);
+    }
+    (first_char + replaced.chars().count(), first_char)
+}
+
+fn toggle_line_comments(block: &str, prefix: &str) -> String {
+    let lines: Vec<&str> = block.split('\n').collect();
+    let content = || lines.iter().filter(|l| !l.trim().is_empty());
+    let commented = content().count() > 0 && content().all(|l| l.trim_start().starts_with(prefix));
+    if commented {
+        return lines
+            .iter()
+            .map(|line| {
+                let indent = indentation(line);
+                match line[indent.len()..].strip_prefix(prefix) {
+                    Some(rest) => format!("{}{}", indent, rest.strip_prefix(' ').unwrap_or(rest)),
+                    None => line.to_string(),
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+    }
+    let column = content().map(|l| indentation(l).len()).min().unwrap_or(0);
+    lines
+        .iter()
+        .map(|line| {
+            if line.trim().is_empty() && content().count() > 0 {
+                line.to_string()
+            } else {
+                let column = column.min(line.len());
+                format!("{}{} {}", &line[..column], prefix, &line[column..])
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn toggle_block_comment(block: &str, open: &str, close: &str) -> String {
+    let indent = indentation(block);
+    let body = block[indent.len()..].trim_end();
+    match body.strip_prefix(open).and_then(|b| b.strip_suffix(close)) {
+        Some(inner) => {
+            let inner = inner.strip_prefix(' ').unwrap_or(inner);
+            let inner = inner.strip_suffix(' ').unwrap_or(inner);
+            format!("{}{}", indent, inner)
+        }
+        None => format!("{}{} {} {}", indent, open, body, close),
+    }
+}
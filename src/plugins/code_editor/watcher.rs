@@ -0,0 +1,128 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use parking_lot::Mutex;
+
+// ----------------------------------------------------------------------------
+// 打开文件的外部修改监听
+// 所有标签页共用一个系统通知器。监听的是文件所在的目录而不是文件本身，
+// 这样其它程序以"写入临时文件再重命名"的方式保存时也能收到通知
+// ----------------------------------------------------------------------------
+
+struct Shared {
+    /// 创建失败（例如超出 inotify 数量限制）时为 None，标签页退回定时轮询
+    watcher: Option<RecommendedWatcher>,
+    /// 被监听的目录及其引用计数
+    dirs: HashMap<PathBuf, usize>,
+    /// 收到修改通知、尚未被标签页取走的文件
+    changed: Arc<Mutex<HashSet<PathBuf>>>,
+    /// 收到通知时唤醒界面
+    ctx: Arc<Mutex<Option<egui::Context>>>,
+}
+
+static SHARED: OnceLock<Mutex<Shared>> = OnceLock::new();
+
+fn shared() -> &'static Mutex<Shared> {
+    SHARED.get_or_init(|| {
+        let changed: Arc<Mutex<HashSet<PathBuf>>> = Arc::default();
+        let ctx: Arc<Mutex<Option<egui::Context>>> = Arc::default();
+        let (changed_clone, ctx_clone) = (changed.clone(), ctx.clone());
+        let watcher = notify::recommended_watcher(move |result: notify::Result<notify::Event>| {
+            let Ok(event) = result else { return };
+            if matches!(event.kind, EventKind::Access(_)) {
+                return;
+            }
+            changed_clone.lock().extend(event.paths);
+            if let Some(ctx) = ctx_clone.lock().as_ref() {
+                ctx.request_repaint();
+            }
+        });
+        if let Err(e) = &watcher {
+            eprintln!("File watcher unavailable, falling back to polling: {}", e);
+        }
+        Mutex::new(Shared { watcher: watcher.ok(), dirs: HashMap::new(), changed, ctx })
+    })
+}
+
+fn parent_dir(path: &Path) -> PathBuf {
+    match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+        _ => PathBuf::from("."),
+    }
+}
+
+/// 对一个文件的监听，随标签页克隆与销毁增减目录的引用计数
+#[derive(Debug)]
+pub struct FileWatch {
+    path: PathBuf,
+    active: bool,
+}
+
+impl FileWatch {
+    pub fn new(ctx: &egui::Context, path: &Path) -> Self {
+        shared().lock().ctx.lock().get_or_insert_with(|| ctx.clone());
+        Self::watch(path)
+    }
+
+    fn watch(path: &Path) -> Self {
+        let mut shared = shared().lock();
+        let dir = parent_dir(path);
+        let active = match shared.dirs.get_mut(&dir) {
+            Some(count) => {
+                *count += 1;
+                true
+            }
+            None => {
+                let ok = shared.watcher.as_mut().is_some_and(|w| w.watch(&dir, RecursiveMode::NonRecursive).is_ok());
+                if ok {
+                    shared.dirs.insert(dir, 1);
+                }
+                ok
+            }
+        };
+        Self { path: path.to_path_buf(), active }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 是否由系统通知器监听；否则调用方需要自行轮询
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// 自上次调用以来文件是否收到过修改通知
+    pub fn take_changed(&self) -> bool {
+        let changed = shared().lock().changed.clone();
+        let mut changed = changed.lock();
+        changed.remove(&self.path)
+    }
+}
+
+impl Clone for FileWatch {
+    fn clone(&self) -> Self {
+        Self::watch(&self.path)
+    }
+}
+
+impl Drop for FileWatch {
+    fn drop(&mut self) {
+        if !self.active {
+            return;
+        }
+        let mut shared = shared().lock();
+        let dir = parent_dir(&self.path);
+        let Some(count) = shared.dirs.get_mut(&dir) else { return };
+        *count -= 1;
+        if *count == 0 {
+            shared.dirs.remove(&dir);
+            if let Some(watcher) = shared.watcher.as_mut() {
+                let _ = watcher.unwatch(&dir);
+            }
+            // 同一目录下其它文件的通知已无人领取
+            shared.changed.lock().retain(|p| parent_dir(p) != dir);
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use std::ops::Range;
+
+// ----------------------------------------------------------------------------
+// 空白处理：保存时去掉行尾空白、补上末尾换行，以及在编辑器中标出空格与制表符
+// ----------------------------------------------------------------------------
+
+/// 整理空白；内容没有变化时返回 None。另外返回被删除的字符范围（按字符索引，升序），
+/// 用来把光标映射到整理后的位置
+pub fn tidy(text: &str, trim_trailing: bool, final_newline: bool) -> Option<(String, Vec<Range<usize>>)> {
+    let mut result = String::with_capacity(text.len() + 1);
+    let mut removed = Vec::new();
+    let mut char_offset = 0;
+    let line_count = text.split('\n').count();
+    for (i, line) in text.split('\n').enumerate() {
+        let kept = if trim_trailing { line.trim_end_matches([' ', '\t']) } else { line };
+        result.push_str(kept);
+        let kept_chars = kept.chars().count();
+        let line_chars = line.chars().count();
+        if kept_chars < line_chars {
+            removed.push(char_offset + kept_chars..char_offset + line_chars);
+        }
+        char_offset += line_chars + 1;
+        if i + 1 < line_count {
+            result.push('\n');
+        }
+    }
+    if final_newline && !result.is_empty() && !result.ends_with('\n') {
+        result.push('\n');
+    }
+    (result != text).then_some((result, removed))
+}
+
+/// 整理前的字符位置在整理后的位置：减去之前被删除的字符，落在删除范围内时移到范围起点
+pub fn map_position(removed: &[Range<usize>], index: usize) -> usize {
+    let mut shift = 0;
+    for range in removed {
+        if range.start >= index {
+            break;
+        }
+        shift += range.end.min(index) - range.start;
+    }
+    index - shift
+}
+
+/// 在可见范围 `visible`（字符索引）内用淡色的 · 与 → 标出空格和制表符
+pub fn paint_markers(ui: &egui::Ui, galley: &egui::Galley, galley_pos: egui::Pos2, text: &str, visible: Range<usize>) {
+    let color = ui.visuals().weak_text_color().gamma_multiply(0.6);
+    let painter = ui.painter();
+    let offset = galley_pos.to_vec2();
+    let cell = |index: usize| galley.pos_from_ccursor(egui::text::CCursor::new(index)).translate(offset);
+    for (i, c) in text.chars().enumerate().skip(visible.start).take(visible.end.saturating_sub(visible.start)) {
+        if c != ' ' && c != '\t' {
+            continue;
+        }
+        let (start, end) = (cell(i), cell(i + 1));
+        // 自动换行处的空白没有宽度可画
+        if (end.min.y - start.min.y).abs() > 1.0 || end.min.x <= start.min.x {
+            continue;
+        }
+        let y = start.center().y;
+        if c == ' ' {
+            painter.circle_filled(egui::pos2((start.min.x + end.min.x) / 2.0, y), 1.0, color);
+        } else {
+            let (left, right) = (start.min.x + 2.0, end.min.x - 2.0);
+            let stroke = egui::Stroke::new(1.0, color);
+            painter.hline(left..=right, y, stroke);
+            painter.line_segment([egui::pos2(right - 3.0, y - 3.0), egui::pos2(right, y)], stroke);
+            painter.line_segment([egui::pos2(right - 3.0, y + 3.0), egui::pos2(right, y)], stroke);
+        }
+    }
+}
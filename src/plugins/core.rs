@@ -38,6 +38,10 @@ impl Plugin for CorePlugin {
             control.push(AppCommand::ResetLayout);
             ui.close_menu();
         }
+        if ui.button("Reopen Closed Tab (Ctrl+Shift+T)").clicked() {
+            control.push(AppCommand::ReopenLastClosed);
+            ui.close_menu();
+        }
     }
 
     fn on_menu_bar(&mut self, ui: &mut Ui, control: &mut Vec<AppCommand>) {
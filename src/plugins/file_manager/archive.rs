@@ -0,0 +1,209 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// 可以在资源管理器中展开的压缩包扩展名
+const EXTENSIONS: &[&str] = &["zip", "verbium"];
+
+// ----------------------------------------------------------------------------
+// 压缩包浏览：把 zip（以及同为 zip 格式的 .verbium 插件包）当作只读的虚拟文件夹展开，
+// 支持解压整个压缩包或其中的一部分，以及在只读标签页中查看其中的文件
+// ----------------------------------------------------------------------------
+
+pub fn is_archive(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| EXTENSIONS.iter().any(|known| ext.eq_ignore_ascii_case(known)))
+}
+
+/// 压缩包中的一项
+#[derive(Debug, Clone)]
+pub struct ArchiveEntry {
+    /// 压缩包内以 `/` 分隔的路径，不含末尾的 `/`
+    pub name: String,
+    pub is_dir: bool,
+    /// 解压后的大小
+    pub size: u64,
+}
+
+impl ArchiveEntry {
+    pub fn file_name(&self) -> &str {
+        self.name.rsplit('/').next().unwrap_or(&self.name)
+    }
+}
+
+/// 压缩包的目录结构
+#[derive(Debug, Default)]
+pub struct ArchiveTree {
+    /// 目录（根目录为空字符串）-> 其中的项，文件夹在前
+    children: HashMap<String, Vec<ArchiveEntry>>,
+}
+
+impl ArchiveTree {
+    fn read(path: &Path) -> std::io::Result<Self> {
+        let mut archive = zip::ZipArchive::new(File::open(path)?)?;
+        let mut entries: BTreeMap<String, ArchiveEntry> = BTreeMap::new();
+        for i in 0..archive.len() {
+            let Ok(file) = archive.by_index_raw(i) else { continue };
+            // 跳过包含 `..` 或绝对路径的项，解压时同样会跳过它们
+            let Some(name) = file.enclosed_name().map(inner_name) else { continue };
+            if name.is_empty() {
+                continue;
+            }
+            // 有些压缩包不单独记录目录，补上所有上级目录
+            let mut child = name.as_str();
+            while let Some((dir, _)) = child.rsplit_once('/') {
+                entries.entry(dir.to_string()).or_insert_with(|| ArchiveEntry { name: dir.to_string(), is_dir: true, size: 0 });
+                child = dir;
+            }
+            entries.insert(name.clone(), ArchiveEntry { name, is_dir: file.is_dir(), size: file.size() });
+        }
+
+        let mut tree = Self::default();
+        for (name, entry) in entries {
+            let parent = name.rsplit_once('/').map_or("", |(dir, _)| dir).to_string();
+            tree.children.entry(parent).or_default().push(entry);
+        }
+        for children in tree.children.values_mut() {
+            children.sort_by(|a, b| b.is_dir.cmp(&a.is_dir).then_with(|| a.name.cmp(&b.name)));
+        }
+        Ok(tree)
+    }
+
+    pub fn children(&self, dir: &str) -> &[ArchiveEntry] {
+        self.children.get(dir).map_or(&[], Vec::as_slice)
+    }
+}
+
+fn inner_name(path: &Path) -> String {
+    path.components().map(|c| c.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/")
+}
+
+/// 一个压缩包的读取结果
+#[derive(Debug, Clone)]
+pub enum ArchiveListing {
+    Loading,
+    Ready(Arc<ArchiveTree>),
+    Failed(String),
+}
+
+/// 已展开的压缩包的内容，在后台线程中读取，克隆后共享同一份数据
+#[derive(Clone, Default)]
+pub struct ArchiveCache {
+    inner: Arc<Mutex<HashMap<PathBuf, ArchiveListing>>>,
+}
+
+impl ArchiveCache {
+    pub fn listing(&self, path: &Path, ctx: &egui::Context) -> ArchiveListing {
+        let Ok(mut inner) = self.inner.lock() else { return ArchiveListing::Loading };
+        if let Some(listing) = inner.get(path) {
+            return listing.clone();
+        }
+        inner.insert(path.to_path_buf(), ArchiveListing::Loading);
+        let (cache, path, ctx) = (Arc::downgrade(&self.inner), path.to_path_buf(), ctx.clone());
+        std::thread::spawn(move || {
+            let listing = match ArchiveTree::read(&path) {
+                Ok(tree) => ArchiveListing::Ready(Arc::new(tree)),
+                Err(e) => ArchiveListing::Failed(e.to_string()),
+            };
+            let Some(cache) = cache.upgrade() else { return };
+            if let Ok(mut cache) = cache.lock() {
+                // 读取期间被折叠时丢弃结果
+                if let Some(slot) = cache.get_mut(&path) {
+                    *slot = listing;
+                }
+            }
+            ctx.request_repaint();
+        });
+        ArchiveListing::Loading
+    }
+
+    /// 压缩包被折叠后丢弃其内容，下次展开时重新读取
+    pub fn forget(&self, path: &Path) {
+        if let Ok(mut inner) = self.inner.lock() {
+            inner.remove(path);
+        }
+    }
+
+    /// 手动刷新：所有压缩包都重新读取
+    pub fn clear(&self) {
+        if let Ok(mut inner) = self.inner.lock() {
+            inner.clear();
+        }
+    }
+}
+
+/// 把压缩包中的 `prefix`（文件或目录；为空时表示整个压缩包）解压到 `target` 下，
+/// 保留所选项本身的名称。返回解压出的文件数。会阻塞，应在后台线程中调用
+pub fn extract(archive: &Path, prefix: &str, target: &Path) -> std::io::Result<usize> {
+    let mut zip = zip::ZipArchive::new(File::open(archive)?)?;
+    let base = prefix.rsplit_once('/').map_or("", |(dir, _)| dir);
+    let mut count = 0;
+    for i in 0..zip.len() {
+        let mut file = zip.by_index(i)?;
+        let Some(name) = file.enclosed_name().map(Path::to_path_buf) else { continue };
+        if !name.starts_with(prefix) {
+            continue;
+        }
+        let out = target.join(name.strip_prefix(base).unwrap_or(&name));
+        if file.is_dir() {
+            std::fs::create_dir_all(&out)?;
+            continue;
+        }
+        if let Some(parent) = out.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::io::copy(&mut file, &mut File::create(&out)?)?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+/// "解压到此处"的目标文件夹：压缩包旁边以其名称命名、尚不存在的文件夹
+pub fn extract_here_target(archive: &Path) -> PathBuf {
+    let parent = archive.parent().unwrap_or(Path::new("."));
+    let stem = archive.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_else(|| "archive".to_string());
+    let mut target = parent.join(&stem);
+    let mut n = 2;
+    while target.exists() {
+        target = parent.join(format!("{} ({})", stem, n));
+        n += 1;
+    }
+    target
+}
+
+/// 把压缩包中的一个文件解压到临时目录并设为只读，供编辑器以只读方式打开。
+/// 会阻塞，应在后台线程中调用
+pub fn extract_for_viewing(archive: &Path, name: &str) -> std::io::Result<PathBuf> {
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    archive.hash(&mut hasher);
+    let stem = archive.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    let dir = std::env::temp_dir().join("verbium-archives").join(format!("{}-{:016x}", stem, hasher.finish()));
+    let out = dir.join(name);
+    if let Ok(metadata) = std::fs::metadata(&out) {
+        // 上次解压的副本是只读的，恢复写权限后再覆盖
+        set_readonly(&out, metadata.permissions(), false)?;
+    }
+    let target = out.parent().unwrap_or(&dir).to_path_buf();
+    if extract(archive, name, &target)? == 0 {
+        return Err(std::io::Error::new(std::io::ErrorKind::NotFound, format!("{} is not in the archive", name)));
+    }
+    let permissions = std::fs::metadata(&out)?.permissions();
+    set_readonly(&out, permissions, true)?;
+    Ok(out)
+}
+
+fn set_readonly(path: &Path, mut permissions: std::fs::Permissions, readonly: bool) -> std::io::Result<()> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = permissions.mode();
+        permissions.set_mode(if readonly { mode & !0o222 } else { mode | 0o200 });
+    }
+    #[cfg(not(unix))]
+    #[allow(clippy::permissions_set_readonly_false)]
+    permissions.set_readonly(readonly);
+    std::fs::set_permissions(path, permissions)
+}
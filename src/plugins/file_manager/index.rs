@@ -0,0 +1,164 @@
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// 建立索引时跳过的目录
+const IGNORED_DIRS: &[&str] = &[".git", "target", "node_modules", ".svn", ".hg"];
+/// 索引条目上限，防止误把整个磁盘当作工作区时占满内存
+const MAX_ENTRIES: usize = 200_000;
+/// 后台线程每收集这么多条目就发布一次，界面可以先用部分结果
+const BATCH: usize = 2_000;
+
+// ----------------------------------------------------------------------------
+// 文件索引：在后台遍历一个目录下的所有文件与文件夹，供文件名过滤与快速打开使用
+// ----------------------------------------------------------------------------
+
+#[derive(Debug, Clone)]
+pub struct IndexEntry {
+    pub path: PathBuf,
+    /// 相对索引根目录、以 `/` 分隔的路径，用于匹配与显示
+    pub relative: String,
+    pub is_dir: bool,
+}
+
+impl IndexEntry {
+    /// 最后一段（文件名）在 `relative` 中的起始字节位置
+    pub fn name_start(&self) -> usize {
+        self.relative.rfind('/').map_or(0, |i| i + 1)
+    }
+}
+
+#[derive(Clone)]
+pub struct FileIndex {
+    root: PathBuf,
+    entries: Arc<Mutex<Vec<IndexEntry>>>,
+    finished: Arc<AtomicBool>,
+    cancel: Arc<AtomicBool>,
+}
+
+impl FileIndex {
+    pub fn build(root: PathBuf, ctx: &egui::Context) -> Self {
+        let index = Self {
+            root,
+            entries: Arc::default(),
+            finished: Arc::new(AtomicBool::new(false)),
+            cancel: Arc::new(AtomicBool::new(false)),
+        };
+        let worker = index.clone();
+        let ctx = ctx.clone();
+        std::thread::spawn(move || {
+            let walker = walkdir::WalkDir::new(&worker.root).into_iter().filter_entry(|e| {
+                !(e.file_type().is_dir() && e.depth() > 0 && IGNORED_DIRS.contains(&e.file_name().to_string_lossy().as_ref()))
+            });
+            let mut batch = Vec::with_capacity(BATCH);
+            let mut total = 0;
+            for entry in walker.flatten().filter(|e| e.depth() > 0) {
+                if worker.cancel.load(Ordering::Relaxed) || total >= MAX_ENTRIES {
+                    break;
+                }
+                let relative = entry.path().strip_prefix(&worker.root).unwrap_or(entry.path()).to_string_lossy().replace('\\', "/");
+                batch.push(IndexEntry { is_dir: entry.file_type().is_dir(), path: entry.into_path(), relative });
+                total += 1;
+                if batch.len() >= BATCH {
+                    if let Ok(mut entries) = worker.entries.lock() {
+                        entries.append(&mut batch);
+                    }
+                    ctx.request_repaint();
+                }
+            }
+            if let Ok(mut entries) = worker.entries.lock() {
+                entries.append(&mut batch);
+            }
+            worker.finished.store(true, Ordering::Relaxed);
+            ctx.request_repaint();
+        });
+        index
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished.load(Ordering::Relaxed)
+    }
+
+    /// 目前已收集的条目数，结果缓存据此判断是否需要重新计算
+    pub fn len(&self) -> usize {
+        self.entries.lock().map_or(0, |entries| entries.len())
+    }
+
+    pub fn with_entries<R>(&self, f: impl FnOnce(&[IndexEntry]) -> R) -> R {
+        match self.entries.lock() {
+            Ok(entries) => f(&entries),
+            Err(_) => f(&[]),
+        }
+    }
+}
+
+impl Drop for FileIndex {
+    fn drop(&mut self) {
+        // 最后一个持有者（后台线程之外）释放时停止遍历
+        if Arc::strong_count(&self.entries) <= 2 && !self.is_finished() {
+            self.cancel.store(true, Ordering::Relaxed);
+        }
+    }
+}
+
+/// 模糊匹配：`query` 中的字符（忽略大小写与空格）按顺序出现在 `candidate` 中即匹配。
+/// 连续匹配、单词开头与文件名中的匹配得分更高。返回分数与匹配字符的字节位置
+pub fn fuzzy_match(query: &str, candidate: &str, name_start: usize) -> Option<(i64, Vec<usize>)> {
+    let needle: Vec<char> = query.chars().filter(|c| !c.is_whitespace()).flat_map(char::to_lowercase).collect();
+    if needle.is_empty() {
+        return Some((0, Vec::new()));
+    }
+    let chars: Vec<(usize, char)> = candidate.char_indices().collect();
+    let lower: Vec<char> = chars.iter().map(|(_, c)| c.to_lowercase().next().unwrap_or(*c)).collect();
+    let name_char = chars.iter().position(|(b, _)| *b >= name_start).unwrap_or(chars.len());
+
+    let is_boundary = |i: usize| {
+        i == 0 || matches!(chars[i - 1].1, '/' | '\\' | '_' | '-' | '.' | ' ') || (chars[i - 1].1.is_lowercase() && chars[i].1.is_uppercase())
+    };
+    let score_of = |positions: &[usize]| {
+        let mut score = 0i64;
+        for (k, &i) in positions.iter().enumerate() {
+            score += 16;
+            if k > 0 {
+                let gap = i - positions[k - 1] - 1;
+                score += if gap == 0 { 12 } else { -(gap as i64).min(12) };
+            }
+            if is_boundary(i) {
+                score += 10;
+            }
+            if i >= name_char {
+                score += 6;
+            }
+        }
+        score
+    };
+
+    // 从首字符的每个出现位置向后贪心匹配，取得分最高的一种
+    let mut best: Option<(i64, Vec<usize>)> = None;
+    for start in (0..lower.len()).filter(|&i| lower[i] == needle[0]) {
+        let mut positions = vec![start];
+        let mut i = start + 1;
+        while positions.len() < needle.len() && i < lower.len() {
+            if lower[i] == needle[positions.len()] {
+                positions.push(i);
+            }
+            i += 1;
+        }
+        if positions.len() < needle.len() {
+            // 更靠后的起点同样无法完成匹配
+            break;
+        }
+        let score = score_of(&positions);
+        if best.as_ref().is_none_or(|(b, _)| score > *b) {
+            best = Some((score, positions));
+        }
+    }
+    let (score, positions) = best?;
+    // 同等条件下较短的路径排在前面
+    let score = score - (chars.len() / 8) as i64;
+    Some((score, positions.iter().map(|&i| chars[i].0).collect()))
+}
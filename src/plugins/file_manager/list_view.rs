@@ -0,0 +1,163 @@
+use std::path::{Path, PathBuf};
+use egui::{Button, RichText, Ui};
+use super::tree::Entry;
+
+// ----------------------------------------------------------------------------
+// 列表视图：一次只显示一个文件夹，带面包屑路径、前进 / 后退历史与可排序的列
+// ----------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortColumn {
+    Name,
+    Size,
+    Modified,
+}
+
+#[derive(Debug, Clone)]
+pub struct ListView {
+    /// 当前显示的文件夹
+    dir: Option<PathBuf>,
+    back: Vec<PathBuf>,
+    forward: Vec<PathBuf>,
+    sort: SortColumn,
+    ascending: bool,
+}
+
+impl Default for ListView {
+    fn default() -> Self {
+        Self { dir: None, back: Vec::new(), forward: Vec::new(), sort: SortColumn::Name, ascending: true }
+    }
+}
+
+impl ListView {
+    pub fn dir(&self) -> Option<&Path> {
+        self.dir.as_deref()
+    }
+
+    /// 进入文件夹，记入后退历史
+    pub fn navigate(&mut self, dir: PathBuf) {
+        if self.dir.as_ref() == Some(&dir) {
+            return;
+        }
+        if let Some(current) = self.dir.replace(dir) {
+            self.back.push(current);
+        }
+        self.forward.clear();
+    }
+
+    pub fn go_back(&mut self) {
+        if let Some(dir) = self.back.pop() {
+            if let Some(current) = self.dir.replace(dir) {
+                self.forward.push(current);
+            }
+        }
+    }
+
+    pub fn go_forward(&mut self) {
+        if let Some(dir) = self.forward.pop() {
+            if let Some(current) = self.dir.replace(dir) {
+                self.back.push(current);
+            }
+        }
+    }
+
+    /// 工作区变化后丢弃不在任何根目录下的历史；当前文件夹不在其中时回到第一个根目录
+    pub fn follow_roots(&mut self, roots: &[PathBuf]) {
+        let inside = |path: &Path| roots.iter().any(|root| path.starts_with(root));
+        self.back.retain(|p| inside(p));
+        self.forward.retain(|p| inside(p));
+        if !self.dir.as_deref().is_some_and(inside) {
+            self.dir = roots.first().cloned();
+        }
+    }
+
+    /// 导航栏：后退、前进、上一级与从所在根目录开始的面包屑
+    pub fn nav_bar(&mut self, ui: &mut Ui, roots: &[PathBuf]) {
+        let Some(dir) = self.dir.clone() else { return };
+        let root = roots
+            .iter()
+            .filter(|root| dir.starts_with(root))
+            .max_by_key(|root| root.as_os_str().len())
+            .cloned()
+            .unwrap_or_else(|| dir.clone());
+        let mut target = None;
+        ui.horizontal_wrapped(|ui| {
+            if ui.add_enabled(!self.back.is_empty(), Button::new("⬅").small()).on_hover_text("Back").clicked() {
+                self.go_back();
+            }
+            if ui.add_enabled(!self.forward.is_empty(), Button::new("➡").small()).on_hover_text("Forward").clicked() {
+                self.go_forward();
+            }
+            // 不离开工作区的根目录
+            let parent = dir.parent().filter(|_| dir != root).map(PathBuf::from);
+            if ui.add_enabled(parent.is_some(), Button::new("⬆").small()).on_hover_text("Up").clicked() {
+                target = parent;
+            }
+            ui.separator();
+
+            if roots.len() > 1 {
+                ui.menu_button(display_name(&root), |ui| {
+                    for other in roots {
+                        if ui.button(display_name(other)).on_hover_text(other.display().to_string()).clicked() {
+                            target = Some(other.clone());
+                            ui.close_menu();
+                        }
+                    }
+                });
+            } else if ui.small_button(display_name(&root)).clicked() {
+                target = Some(root.clone());
+            }
+            let mut crumb = root.clone();
+            for part in dir.strip_prefix(&root).map(|rest| rest.components().collect::<Vec<_>>()).unwrap_or_default() {
+                crumb.push(part);
+                ui.weak("›");
+                if ui.small_button(part.as_os_str().to_string_lossy()).clicked() {
+                    target = Some(crumb.clone());
+                }
+            }
+        });
+        if let Some(target) = target {
+            self.navigate(target);
+        }
+    }
+
+    /// 列标题：点击切换排序列，再次点击切换升降序
+    pub fn sort_header(&mut self, ui: &mut Ui) {
+        for (column, label) in [(SortColumn::Name, "Name"), (SortColumn::Size, "Size"), (SortColumn::Modified, "Modified")] {
+            let arrow = match (self.sort == column, self.ascending) {
+                (false, _) => "",
+                (true, true) => " ⏶",
+                (true, false) => " ⏷",
+            };
+            if ui.add(Button::new(RichText::new(format!("{}{}", label, arrow)).strong()).frame(false)).clicked() {
+                if self.sort == column {
+                    self.ascending = !self.ascending;
+                } else {
+                    self.sort = column;
+                    self.ascending = true;
+                }
+            }
+        }
+    }
+
+    /// 文件夹总在前面，同类按所选列排序，大小或时间相同时按名称
+    pub fn sort<'a>(&self, rows: Vec<&'a Entry>) -> Vec<&'a Entry> {
+        let mut keyed: Vec<(String, &Entry)> = rows
+            .into_iter()
+            .map(|entry| (entry.path.file_name().map(|n| n.to_string_lossy().to_lowercase()).unwrap_or_default(), entry))
+            .collect();
+        keyed.sort_by(|(a_name, a), (b_name, b)| {
+            let order = match self.sort {
+                SortColumn::Name => a_name.cmp(b_name),
+                SortColumn::Size => a.size.cmp(&b.size).then_with(|| a_name.cmp(b_name)),
+                SortColumn::Modified => a.modified.cmp(&b.modified).then_with(|| a_name.cmp(b_name)),
+            };
+            b.is_dir.cmp(&a.is_dir).then(if self.ascending { order } else { order.reverse() })
+        });
+        keyed.into_iter().map(|(_, entry)| entry).collect()
+    }
+}
+
+fn display_name(path: &Path) -> String {
+    path.file_name().unwrap_or(path.as_os_str()).to_string_lossy().to_string()
+}
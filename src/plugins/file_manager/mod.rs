@@ -2,7 +2,56 @@ use std::path::{Path, PathBuf};
 use std::collections::HashSet;
 use std::sync::{Arc, Mutex};
 use egui::{Ui, WidgetText, Id, Color32, RichText};
-use crate::{Plugin, AppCommand, TabInstance, Tab, NotificationLevel};
+use egui::text::{CCursor, CCursorRange};
+use crate::{Plugin, AppCommand, TabInstance, Tab, NotificationAction, NotificationLevel};
+
+mod archive;
+mod index;
+mod list_view;
+mod naming;
+mod open_with;
+mod properties;
+mod quick_open;
+mod recycle;
+mod run;
+mod tree;
+use archive::{ArchiveCache, ArchiveEntry, ArchiveListing, ArchiveTree};
+use index::FileIndex;
+use list_view::ListView;
+use open_with::{Association, OpenWithEntry, OpenWithRegistry};
+use properties::PropertiesDialog;
+use quick_open::QuickOpen;
+use tree::{Entry, Listing, TreeCache};
+
+/// "Reveal" names the system file manager
+#[cfg(target_os = "windows")]
+const REVEAL_LABEL: &str = "Reveal in Explorer";
+#[cfg(target_os = "macos")]
+const REVEAL_LABEL: &str = "Reveal in Finder";
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+const REVEAL_LABEL: &str = "Reveal in File Manager";
+
+/// The name filter stops after this many matches to keep the tree manageable
+const MAX_FILTER_MATCHES: usize = 1000;
+
+/// Result of the inline name field for one frame
+enum InlineEdit {
+    Editing,
+    Commit(PathBuf),
+    Cancel,
+}
+
+/// What the tree shows while a name filter is active
+#[derive(Clone)]
+struct FilterView {
+    /// (filter text, number of indexed entries) this view was computed for
+    key: (String, usize),
+    /// Matching items and the folders leading to them
+    visible: HashSet<PathBuf>,
+    /// Folders shown expanded because they contain matches
+    open: HashSet<PathBuf>,
+    matches: usize,
+}
 
 // ----------------------------------------------------------------------------
 // Tab Instance
@@ -10,37 +59,140 @@ use crate::{Plugin, AppCommand, TabInstance, Tab, NotificationLevel};
 
 #[derive(Clone)]
 pub struct FileExplorerTab {
-    root_path: Option<PathBuf>,
+    /// Workspace root folders shown at the top level, kept in sync with the host's workspace
+    roots: Vec<PathBuf>,
     expanded_nodes: HashSet<PathBuf>,
     rename_path: Option<PathBuf>,
     new_item_parent: Option<(PathBuf, bool)>, // (parent_path, is_dir)
     input_text: String,
+    /// Focus the inline name field and select the name when it first appears
+    inline_focus: bool,
+    /// Items waiting for the user to confirm moving them to the trash
+    pending_delete: Option<Vec<PathBuf>>,
+    /// Open "Properties" windows, one per item
+    properties: Vec<PropertiesDialog>,
     
     // New state for interaction
     selected_items: HashSet<PathBuf>,
+    /// Where a Shift-click range starts: the last item clicked without Shift
+    selection_anchor: Option<PathBuf>,
+    /// Items in the order they were drawn last frame, for Shift-click ranges
+    visible_order: Vec<PathBuf>,
+    drawn_order: Vec<PathBuf>,
     /// Queue for commands generated by background threads (e.g., move finished)
     pending_commands: Arc<Mutex<Vec<AppCommand>>>,
+    /// Programs offered in the "Open With…" submenu, shared with the plugin settings
+    open_with: Arc<Mutex<OpenWithRegistry>>,
+    /// Directory listings read in the background, so expanded folders don't hit the disk every frame
+    tree: TreeCache,
+    /// Contents of archives expanded in the tree
+    archives: ArchiveCache,
+    /// Show one folder at a time as a sortable list instead of the tree
+    list_mode: bool,
+    list: ListView,
+    /// Narrows the tree to names containing this text
+    filter: String,
+    /// Every file under each root, built in the background once a filter is typed
+    filter_indexes: Vec<FileIndex>,
+    filter_view: Option<FilterView>,
 }
 
 impl std::fmt::Debug for FileExplorerTab {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("FileExplorerTab")
-            .field("root_path", &self.root_path)
+            .field("roots", &self.roots)
             .finish()
     }
 }
 
 impl FileExplorerTab {
-    fn new() -> Self {
+    fn new(open_with: Arc<Mutex<OpenWithRegistry>>) -> Self {
         Self {
-            root_path: None,
+            roots: Vec::new(),
             expanded_nodes: HashSet::new(),
             rename_path: None,
             new_item_parent: None,
             input_text: String::new(),
+            inline_focus: false,
+            pending_delete: None,
+            properties: Vec::new(),
             selected_items: HashSet::new(),
+            selection_anchor: None,
+            visible_order: Vec::new(),
+            drawn_order: Vec::new(),
             pending_commands: Arc::new(Mutex::new(Vec::new())),
+            open_with,
+            tree: TreeCache::new(),
+            archives: ArchiveCache::default(),
+            list_mode: false,
+            list: ListView::default(),
+            filter: String::new(),
+            filter_indexes: Vec::new(),
+            filter_view: None,
+        }
+    }
+
+    /// Follows the host's workspace roots, dropping state that belonged to removed folders
+    fn sync_workspace(&mut self, ctx: &egui::Context) {
+        let workspace = crate::workspace::current(ctx);
+        if workspace.roots == self.roots {
+            return;
+        }
+        for removed in self.roots.iter().filter(|root| !workspace.roots.contains(root)) {
+            self.tree.forget(removed);
+        }
+        for added in workspace.roots.iter().filter(|root| !self.roots.contains(root)) {
+            // New roots open expanded
+            self.expanded_nodes.insert(added.clone());
+        }
+        self.roots = workspace.roots.clone();
+        let roots = &self.roots;
+        let inside = |path: &PathBuf| roots.iter().any(|root| path.starts_with(root));
+        self.expanded_nodes.retain(inside);
+        self.selected_items.retain(inside);
+        self.list.follow_roots(&self.roots);
+        self.filter_indexes.clear();
+        self.filter_view = None;
+    }
+
+    /// Recomputes the filtered view when the filter text changes or the index grows
+    fn update_filter_view(&mut self, ctx: &egui::Context) {
+        let needle = self.filter.trim().to_lowercase();
+        if needle.is_empty() {
+            self.filter_view = None;
+            return;
+        }
+        if self.filter_indexes.len() != self.roots.len() || self.filter_indexes.iter().zip(&self.roots).any(|(index, root)| index.root() != root) {
+            self.filter_indexes = self.roots.iter().map(|root| FileIndex::build(root.clone(), ctx)).collect();
+        }
+        let key = (needle.clone(), self.filter_indexes.iter().map(FileIndex::len).sum());
+        if self.filter_view.as_ref().is_some_and(|view| view.key == key) {
+            return;
+        }
+
+        let mut visible = HashSet::new();
+        let mut open: HashSet<PathBuf> = self.roots.iter().cloned().collect();
+        let mut matches = 0;
+        for index in &self.filter_indexes {
+            let root = index.root();
+            index.with_entries(|entries| {
+                for entry in entries {
+                    if !entry.relative[entry.name_start()..].to_lowercase().contains(&needle) {
+                        continue;
+                    }
+                    matches += 1;
+                    if matches > MAX_FILTER_MATCHES {
+                        break;
+                    }
+                    visible.insert(entry.path.clone());
+                    for ancestor in entry.path.ancestors().skip(1).take_while(|a| a.starts_with(root) && *a != root) {
+                        visible.insert(ancestor.to_path_buf());
+                        open.insert(ancestor.to_path_buf());
+                    }
+                }
+            });
         }
+        self.filter_view = Some(FilterView { key, visible, open, matches });
     }
 
     /// Helper to process commands from background threads
@@ -52,27 +204,54 @@ impl FileExplorerTab {
         }
     }
 
-    fn render_tree(&mut self, ui: &mut Ui, path: PathBuf, control: &mut Vec<AppCommand>) {
+    fn render_tree(&mut self, ui: &mut Ui, path: PathBuf, item_is_dir: bool, control: &mut Vec<AppCommand>) {
         let name = path.file_name()
             .map(|n| n.to_string_lossy().to_string())
             .unwrap_or_else(|| "/".to_string());
 
         let is_selected = self.selected_items.contains(&path);
+        self.drawn_order.push(path.clone());
+        if self.rename_path.as_ref() == Some(&path) {
+            let dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+            let outcome = self.inline_name_field(ui, item_icon(&path, item_is_dir), &dir, Some(&path), item_is_dir);
+            self.finish_rename(&path, outcome, control);
+            return;
+        }
         let id = Id::new(&path);
-        let item_is_dir = path.is_dir();
+        // Version control status from the git plugin, if enabled
+        let vcs = crate::vcs_provider(ui.ctx());
+        let vcs_color = vcs.as_ref()
+            .and_then(|provider| provider.status(&path))
+            .map(|status| (status, status.color(&crate::theme::palette(ui.ctx()))));
+        let ignored = vcs.as_ref().is_some_and(|provider| provider.is_ignored(&path));
 
         if item_is_dir {
             let is_expanded = self.expanded_nodes.contains(&path);
+            // While filtering, folders with matches open under a separate state so the normal expansion comes back afterwards
+            let filtered = self.filter_view.as_ref().map(|view| view.open.contains(&path));
             
-            let state = egui::collapsing_header::CollapsingState::load_with_default_open(
-                ui.ctx(), 
-                id, 
-                is_expanded
-            );
+            let mut state = match filtered {
+                Some(open) => egui::collapsing_header::CollapsingState::load_with_default_open(ui.ctx(), id.with(&self.filter), open),
+                None => egui::collapsing_header::CollapsingState::load_with_default_open(
+                    ui.ctx(), 
+                    id, 
+                    is_expanded
+                ),
+            };
+
+            let creating_here = self.new_item_parent.as_ref().is_some_and(|(parent, _)| *parent == path);
+            if creating_here {
+                state.set_open(true);
+            }
 
             let header_response = state.show_header(ui, |ui| {
                 // Text styling
                 let mut text = RichText::new(format!("📁 {}", name));
+                if let Some((_, color)) = vcs_color {
+                    text = text.color(color);
+                } else if ignored {
+                    text = text.color(ui.visuals().weak_text_color());
+                }
                 if is_selected {
                     text = text.color(ui.visuals().selection.stroke.color);
                 }
@@ -120,43 +299,113 @@ impl FileExplorerTab {
                     self.handle_click(&path, ui);
                 }
 
+                if response.secondary_clicked() {
+                    self.select_for_menu(&path);
+                }
                 response.context_menu(|ui| {
                     self.context_menu_items(ui, &path, control, name.clone());
                 });
             });
 
             // Sync expanded state using header_response
-            if header_response.is_open() {
+            if filtered.is_some() {
+                // Leave the unfiltered expansion untouched
+            } else if header_response.is_open() {
                 self.expanded_nodes.insert(path.clone());
-            } else {
-                self.expanded_nodes.remove(&path);
+            } else if self.expanded_nodes.remove(&path) {
+                // Collapsed folders stop being watched until they are opened again
+                self.tree.forget(&path);
             }
 
             header_response.body(|ui| {
-                if let Ok(entries) = std::fs::read_dir(&path) {
-                    let mut paths: Vec<_> = entries.flatten().map(|e| e.path()).collect();
-                    paths.sort_by(|a, b| {
-                        let a_is_dir = a.is_dir();
-                        let b_is_dir = b.is_dir();
-                        if a_is_dir != b_is_dir {
-                            b_is_dir.cmp(&a_is_dir)
-                        } else {
-                            a.cmp(b)
+                if creating_here {
+                    self.render_new_item(ui, control);
+                }
+                match self.tree.listing(&path, ui.ctx()) {
+                    Listing::Ready(entries) => {
+                        let (hide_ignored, hide_dotfiles) = self.open_with.lock()
+                            .map(|registry| (registry.config.hide_ignored, registry.config.hide_dotfiles))
+                            .unwrap_or_default();
+                        for entry in entries.iter() {
+                            let hidden = entry.path.file_name().is_some_and(|n| n.to_string_lossy().starts_with('.'));
+                            if (hide_dotfiles && hidden) || (hide_ignored && vcs.as_ref().is_some_and(|p| p.is_ignored(&entry.path))) {
+                                continue;
+                            }
+                            if self.filter_view.as_ref().is_some_and(|view| !view.visible.contains(&entry.path)) {
+                                continue;
+                            }
+                            self.render_tree(ui, entry.path.clone(), entry.is_dir, control);
                         }
-                    });
-
-                    for child_path in paths {
-                        self.render_tree(ui, child_path, control);
+                    }
+                    Listing::Loading => {
+                        ui.horizontal(|ui| {
+                            ui.spinner();
+                            ui.weak("Loading…");
+                        });
+                    }
+                    Listing::Failed(e) => {
+                        ui.colored_label(crate::theme::palette(ui.ctx()).error, format!("⚠ {}", e));
                     }
                 }
             });
 
+        } else if archive::is_archive(&path) {
+            // Archives expand in place into read-only virtual folders
+            let mut text = RichText::new(format!("🗜 {}", name));
+            if let Some((_, color)) = vcs_color {
+                text = text.color(color);
+            } else if ignored {
+                text = text.color(ui.visuals().weak_text_color());
+            }
+            if is_selected {
+                text = text.color(ui.visuals().selection.stroke.color);
+            }
+
+            let state = egui::collapsing_header::CollapsingState::load_with_default_open(ui.ctx(), id, false);
+            let header_response = state.show_header(ui, |ui| {
+                let response = ui.add(egui::SelectableLabel::new(is_selected, text)).interact(egui::Sense::drag());
+                if response.dragged() {
+                    response.dnd_set_drag_payload(path.clone());
+                    ui.ctx().set_cursor_icon(egui::CursorIcon::Grabbing);
+                }
+                if response.clicked() {
+                    self.handle_click(&path, ui);
+                }
+                if response.secondary_clicked() {
+                    self.select_for_menu(&path);
+                }
+                response.context_menu(|ui| {
+                    self.context_menu_items(ui, &path, control, name.clone());
+                });
+            });
+            if !header_response.is_open() {
+                self.archives.forget(&path);
+            }
+            header_response.body(|ui| {
+                match self.archives.listing(&path, ui.ctx()) {
+                    ArchiveListing::Ready(tree) => self.render_archive_dir(ui, &path, &tree, "", control),
+                    ArchiveListing::Loading => {
+                        ui.horizontal(|ui| {
+                            ui.spinner();
+                            ui.weak("Reading archive…");
+                        });
+                    }
+                    ArchiveListing::Failed(e) => {
+                        ui.colored_label(crate::theme::palette(ui.ctx()).error, format!("⚠ {}", e));
+                    }
+                }
+            });
         } else {
             // File display
             ui.horizontal(|ui| {
                 ui.add_space(16.0); // Indentation
                 
                 let mut text = RichText::new(format!("📄 {}", name));
+                if let Some((_, color)) = vcs_color {
+                    text = text.color(color);
+                } else if ignored {
+                    text = text.color(ui.visuals().weak_text_color());
+                }
                 if is_selected {
                     text = text.color(ui.visuals().selection.stroke.color);
                     text = text.background_color(ui.visuals().selection.bg_fill.gamma_multiply(0.3));
@@ -193,15 +442,350 @@ impl FileExplorerTab {
                     control.push(AppCommand::OpenFile(path.clone()));
                 }
 
+                if response.secondary_clicked() {
+                    self.select_for_menu(&path);
+                }
                 response.context_menu(|ui| {
                     self.context_menu_items(ui, &path, control, name.clone());
                 });
+
+                if let Some((status, color)) = vcs_color {
+                    // Fully staged changes get a filled badge
+                    let staged = vcs.as_ref().is_some_and(|provider| provider.is_staged(&path));
+                    let mut badge = RichText::new(status.letter()).small().color(color);
+                    let mut hint = status.label().to_string();
+                    if staged {
+                        badge = badge.strong().background_color(color.gamma_multiply(0.25));
+                        hint.push_str(" (staged)");
+                    }
+                    ui.label(badge).on_hover_text(hint);
+                }
             });
         }
     }
 
+    /// Name field drawn in place of an item while renaming or creating it.
+    /// Enter commits a valid name; Esc or clicking elsewhere cancels
+    fn inline_name_field(&mut self, ui: &mut Ui, icon: &str, dir: &Path, renaming: Option<&Path>, is_dir: bool) -> InlineEdit {
+        let id = Id::new("explorer_inline_name");
+        let mut outcome = InlineEdit::Editing;
+        ui.horizontal(|ui| {
+            ui.add_space(16.0);
+            ui.label(icon);
+            let mut output = egui::TextEdit::singleline(&mut self.input_text)
+                .id(id)
+                .desired_width(ui.available_width().min(240.0))
+                .show(ui);
+            if std::mem::take(&mut self.inline_focus) {
+                // Select the name without its extension so typing replaces just that
+                let end = match self.input_text.rfind('.') {
+                    Some(dot) if dot > 0 && !is_dir => self.input_text[..dot].chars().count(),
+                    _ => self.input_text.chars().count(),
+                };
+                output.state.cursor.set_char_range(Some(CCursorRange::two(CCursor::new(0), CCursor::new(end))));
+                output.state.store(ui.ctx(), id);
+                output.response.request_focus();
+            }
+            let error = naming::validate(&self.input_text, dir, renaming).err();
+            if output.response.lost_focus() {
+                if !ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                    outcome = InlineEdit::Cancel;
+                } else if error.is_none() {
+                    outcome = InlineEdit::Commit(dir.join(&self.input_text));
+                } else {
+                    output.response.request_focus();
+                }
+            }
+            if let Some(error) = error {
+                ui.colored_label(crate::theme::palette(ui.ctx()).error, RichText::new(error).small());
+            }
+        });
+        outcome
+    }
+
+    fn finish_rename(&mut self, path: &Path, outcome: InlineEdit, control: &mut Vec<AppCommand>) {
+        let new_path = match outcome {
+            InlineEdit::Editing => return,
+            InlineEdit::Cancel => {
+                self.rename_path = None;
+                return;
+            }
+            InlineEdit::Commit(new_path) => new_path,
+        };
+        self.rename_path = None;
+        if new_path == path {
+            return;
+        }
+        match std::fs::rename(path, &new_path) {
+            Ok(_) => {
+                self.tree.forget(path);
+                if let Some(parent) = path.parent() {
+                    self.tree.invalidate(parent);
+                }
+                if self.selected_items.remove(path) {
+                    self.selected_items.insert(new_path);
+                }
+                control.push(AppCommand::Notify {
+                    message: "Renamed successfully".into(),
+                    level: NotificationLevel::Success,
+                });
+            }
+            Err(e) => {
+                control.push(AppCommand::Notify {
+                    message: format!("Rename failed: {}", e),
+                    level: NotificationLevel::Error,
+                });
+            }
+        }
+    }
+
+    /// Inline field for the item being created, drawn at the top of its folder
+    fn render_new_item(&mut self, ui: &mut Ui, control: &mut Vec<AppCommand>) {
+        let Some((parent, is_dir)) = self.new_item_parent.clone() else { return };
+        let new_path = match self.inline_name_field(ui, if is_dir { "📁" } else { "📄" }, &parent, None, is_dir) {
+            InlineEdit::Editing => return,
+            InlineEdit::Cancel => {
+                self.new_item_parent = None;
+                return;
+            }
+            InlineEdit::Commit(new_path) => new_path,
+        };
+        self.new_item_parent = None;
+        let res = if is_dir {
+            std::fs::create_dir_all(&new_path)
+        } else {
+            std::fs::File::create(&new_path).map(|_| ())
+        };
+        match res {
+            Ok(_) => {
+                self.tree.invalidate(&parent);
+                self.expanded_nodes.insert(parent);
+                self.selected_items.clear();
+                self.selected_items.insert(new_path);
+                control.push(AppCommand::Notify {
+                    message: format!("Created {}", if is_dir { "folder" } else { "file" }),
+                    level: NotificationLevel::Success,
+                });
+            }
+            Err(e) => {
+                control.push(AppCommand::Notify {
+                    message: format!("Creation failed: {}", e),
+                    level: NotificationLevel::Error,
+                });
+            }
+        }
+    }
+
+    /// List mode: the current folder's entries in sortable columns
+    fn render_list(&mut self, ui: &mut Ui, control: &mut Vec<AppCommand>) {
+        // New items are created inline in the folder they go into
+        if let Some((parent, _)) = &self.new_item_parent {
+            if self.list.dir() != Some(parent.as_path()) {
+                self.list.navigate(parent.clone());
+            }
+        }
+        let Some(dir) = self.list.dir().map(Path::to_path_buf) else { return };
+        let entries = match self.tree.listing(&dir, ui.ctx()) {
+            Listing::Ready(entries) => entries,
+            Listing::Loading => {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.weak("Loading…");
+                });
+                return;
+            }
+            Listing::Failed(e) => {
+                ui.colored_label(crate::theme::palette(ui.ctx()).error, format!("⚠ {}", e));
+                return;
+            }
+        };
+        let vcs = crate::vcs_provider(ui.ctx());
+        let (hide_ignored, hide_dotfiles) = self.open_with.lock()
+            .map(|registry| (registry.config.hide_ignored, registry.config.hide_dotfiles))
+            .unwrap_or_default();
+        let needle = self.filter.trim().to_lowercase();
+        let rows: Vec<&Entry> = entries
+            .iter()
+            .filter(|entry| {
+                let name = entry.path.file_name().map(|n| n.to_string_lossy().to_lowercase()).unwrap_or_default();
+                !(hide_dotfiles && name.starts_with('.'))
+                    && !(hide_ignored && vcs.as_ref().is_some_and(|p| p.is_ignored(&entry.path)))
+                    && name.contains(&needle)
+            })
+            .collect();
+        let rows = self.list.sort(rows);
+
+        let mut enter = None;
+        egui::Grid::new("explorer_list").num_columns(3).striped(true).spacing([16.0, 2.0]).show(ui, |ui| {
+            self.list.sort_header(ui);
+            ui.end_row();
+            if self.new_item_parent.is_some() {
+                self.render_new_item(ui, control);
+                ui.end_row();
+            }
+            for entry in rows {
+                let path = &entry.path;
+                let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+                let is_selected = self.selected_items.contains(path);
+                self.drawn_order.push(path.clone());
+
+                if self.rename_path.as_ref() == Some(path) {
+                    let outcome = self.inline_name_field(ui, item_icon(path, entry.is_dir), &dir, Some(path), entry.is_dir);
+                    self.finish_rename(path, outcome, control);
+                    ui.end_row();
+                    continue;
+                }
+                let mut text = RichText::new(format!("{} {}", item_icon(path, entry.is_dir), name));
+                if let Some(status) = vcs.as_ref().and_then(|provider| provider.status(path)) {
+                    text = text.color(status.color(&crate::theme::palette(ui.ctx())));
+                } else if vcs.as_ref().is_some_and(|provider| provider.is_ignored(path)) {
+                    text = text.color(ui.visuals().weak_text_color());
+                }
+                let response = ui.add(egui::SelectableLabel::new(is_selected, text)).interact(egui::Sense::drag());
+                if response.dragged() {
+                    response.dnd_set_drag_payload(path.clone());
+                    ui.ctx().set_cursor_icon(egui::CursorIcon::Grabbing);
+                }
+                if entry.is_dir {
+                    if let Some(source_path) = response.dnd_release_payload::<PathBuf>() {
+                        self.handle_drop(source_path.as_ref(), path, ui.ctx());
+                    }
+                    if response.dnd_hover_payload::<PathBuf>().is_some() {
+                        ui.painter().rect_stroke(response.rect, 2.0, egui::Stroke::new(2.0, Color32::LIGHT_GRAY));
+                    }
+                }
+                if response.clicked() {
+                    self.handle_click(path, ui);
+                }
+                if response.double_clicked() {
+                    if entry.is_dir {
+                        enter = Some(path.clone());
+                    } else {
+                        control.push(AppCommand::OpenFile(path.clone()));
+                    }
+                }
+                if response.secondary_clicked() {
+                    self.select_for_menu(path);
+                }
+                response.context_menu(|ui| {
+                    self.context_menu_items(ui, path, control, name.clone());
+                });
+
+                ui.weak(if entry.is_dir { String::new() } else { properties::format_size(entry.size) });
+                ui.weak(entry.modified.map(properties::format_time).unwrap_or_default());
+                ui.end_row();
+            }
+        });
+        if let Some(dir) = enter {
+            self.selected_items.clear();
+            self.list.navigate(dir);
+        }
+    }
+
+    /// Draws the entries of one folder inside an archive. They are not real paths, so they
+    /// can't be selected or dragged; double-clicking a file opens a read-only copy
+    fn render_archive_dir(&mut self, ui: &mut Ui, archive: &Path, tree: &ArchiveTree, dir: &str, control: &mut Vec<AppCommand>) {
+        for entry in tree.children(dir) {
+            let id = Id::new(archive).with(&entry.name);
+            if entry.is_dir {
+                egui::collapsing_header::CollapsingState::load_with_default_open(ui.ctx(), id, false)
+                    .show_header(ui, |ui| {
+                        let response = ui.add(egui::Label::new(format!("📁 {}", entry.file_name())).sense(egui::Sense::click()));
+                        response.context_menu(|ui| self.archive_entry_menu(ui, archive, entry, control));
+                    })
+                    .body(|ui| self.render_archive_dir(ui, archive, tree, &entry.name, control));
+            } else {
+                ui.horizontal(|ui| {
+                    ui.add_space(16.0);
+                    let response = ui.add(egui::Label::new(format!("📄 {}", entry.file_name())).sense(egui::Sense::click()))
+                        .on_hover_text(format!("{} bytes", entry.size));
+                    if response.double_clicked() {
+                        self.open_archive_entry(archive, &entry.name, ui.ctx());
+                    }
+                    response.context_menu(|ui| self.archive_entry_menu(ui, archive, entry, control));
+                });
+            }
+        }
+    }
+
+    fn archive_entry_menu(&mut self, ui: &mut Ui, archive: &Path, entry: &ArchiveEntry, control: &mut Vec<AppCommand>) {
+        if !entry.is_dir && ui.button("Open (Read-Only)").clicked() {
+            self.open_archive_entry(archive, &entry.name, ui.ctx());
+            ui.close_menu();
+        }
+        if ui.button("Extract To…").clicked() {
+            if let Some(target) = rfd::FileDialog::new().pick_folder() {
+                self.spawn_extract(archive.to_path_buf(), entry.name.clone(), target, ui.ctx());
+            }
+            ui.close_menu();
+        }
+        if ui.button("Copy Path in Archive").clicked() {
+            control.push(AppCommand::CopyToClipboard(entry.name.clone()));
+            ui.close_menu();
+        }
+    }
+
+    /// Extracts a single file to a temporary read-only copy and opens it
+    fn open_archive_entry(&self, archive: &Path, name: &str, ctx: &egui::Context) {
+        let pending = self.pending_commands.clone();
+        let (archive, name, ctx) = (archive.to_path_buf(), name.to_string(), ctx.clone());
+        std::thread::spawn(move || {
+            let command = match archive::extract_for_viewing(&archive, &name) {
+                Ok(path) => AppCommand::OpenFile(path),
+                Err(e) => AppCommand::Notify {
+                    message: format!("Failed to open {}: {}", name, e),
+                    level: NotificationLevel::Error,
+                },
+            };
+            if let Ok(mut pending) = pending.lock() {
+                pending.push(command);
+            }
+            ctx.request_repaint();
+        });
+    }
+
+    /// Extracts `prefix` (empty for the whole archive) into `target` in the background
+    fn spawn_extract(&self, archive: PathBuf, prefix: String, target: PathBuf, ctx: &egui::Context) {
+        let pending = self.pending_commands.clone();
+        let tree = self.tree.clone();
+        let ctx = ctx.clone();
+        std::thread::spawn(move || {
+            let command = match archive::extract(&archive, &prefix, &target) {
+                Ok(count) => AppCommand::Notify {
+                    message: format!("Extracted {} file(s) to {}", count, target.display()),
+                    level: NotificationLevel::Success,
+                },
+                Err(e) => AppCommand::Notify {
+                    message: format!("Extraction failed: {}", e),
+                    level: NotificationLevel::Error,
+                },
+            };
+            tree.invalidate(&target);
+            if let Some(parent) = target.parent() {
+                tree.invalidate(parent);
+            }
+            if let Ok(mut pending) = pending.lock() {
+                pending.push(command);
+            }
+            ctx.request_repaint();
+        });
+    }
+
     fn handle_click(&mut self, path: &PathBuf, ui: &Ui) {
-        if ui.input(|i| i.modifiers.ctrl) {
+        let modifiers = ui.input(|i| i.modifiers);
+        if modifiers.shift {
+            // Select everything drawn between the anchor and this item; Ctrl + Shift adds to the selection
+            let anchor = self.selection_anchor.as_ref().and_then(|a| self.visible_order.iter().position(|p| p == a));
+            if let (Some(from), Some(to)) = (anchor, self.visible_order.iter().position(|p| p == path)) {
+                if !modifiers.command {
+                    self.selected_items.clear();
+                }
+                self.selected_items.extend(self.visible_order[from.min(to)..=from.max(to)].iter().cloned());
+                return;
+            }
+        }
+        self.selection_anchor = Some(path.clone());
+        if modifiers.command {
             if self.selected_items.contains(path) {
                 self.selected_items.remove(path);
             } else {
@@ -216,15 +800,35 @@ impl FileExplorerTab {
         }
     }
 
-    fn handle_drop(&mut self, primary_source: &Path, target_dir: &Path, ctx: &egui::Context) {
-        // Collect what to move
-        let mut sources = Vec::new();
-        if self.selected_items.contains(primary_source) {
-            sources.extend(self.selected_items.iter().cloned());
-        } else {
-            sources.push(primary_source.to_path_buf());
+    /// Right-clicking outside the selection acts on that item alone
+    fn select_for_menu(&mut self, path: &PathBuf) {
+        if !self.selected_items.contains(path) {
+            self.selected_items.clear();
+            self.selected_items.insert(path.clone());
+            self.selection_anchor = Some(path.clone());
         }
+    }
 
+    /// The whole selection when `path` is part of it, otherwise just `path`, in tree order
+    fn targets_for(&self, path: &Path) -> Vec<PathBuf> {
+        if !self.selected_items.contains(path) {
+            return vec![path.to_path_buf()];
+        }
+        let mut targets: Vec<PathBuf> = self.selected_items.iter().cloned().collect();
+        targets.sort_by_key(|p| self.visible_order.iter().position(|v| v == p).unwrap_or(usize::MAX));
+        // Moving or deleting a folder already covers anything selected inside it
+        let folders: Vec<PathBuf> = targets.iter().filter(|p| p.is_dir()).cloned().collect();
+        targets.retain(|p| !folders.iter().any(|f| f != p && p.starts_with(f)));
+        targets
+    }
+
+    fn handle_drop(&mut self, primary_source: &Path, target_dir: &Path, ctx: &egui::Context) {
+        let sources = self.targets_for(primary_source);
+        self.move_items(sources, target_dir, ctx);
+    }
+
+    /// Moves items into `target_dir` on a background thread
+    fn move_items(&mut self, sources: Vec<PathBuf>, target_dir: &Path, ctx: &egui::Context) {
         // Filter valid moves
         let target_dir = target_dir.to_path_buf();
         let final_sources: Vec<PathBuf> = sources.into_iter()
@@ -242,6 +846,7 @@ impl FileExplorerTab {
         if final_sources.is_empty() { return; }
 
         let pending = self.pending_commands.clone();
+        let tree = self.tree.clone();
         let ctx_clone = ctx.clone();
 
         // Spawn background thread
@@ -262,10 +867,16 @@ impl FileExplorerTab {
                 }
 
                 match std::fs::rename(&src, &dest) {
-                    Ok(_) => success_count += 1,
+                    Ok(_) => {
+                        success_count += 1;
+                        if let Some(parent) = src.parent() {
+                            tree.invalidate(parent);
+                        }
+                    }
                     Err(e) => errors.push(format!("{}: {}", file_name.to_string_lossy(), e)),
                 }
             }
+            tree.invalidate(&target_dir);
 
             let mut cmds = pending.lock().unwrap();
             if success_count > 0 {
@@ -285,33 +896,156 @@ impl FileExplorerTab {
         });
     }
 
+    fn open_with_menu(&mut self, ui: &mut Ui, path: &PathBuf, control: &mut Vec<AppCommand>) {
+        let (associated, detected) = match self.open_with.lock() {
+            Ok(registry) => registry.entries_for(path),
+            Err(_) => (Vec::new(), Vec::new()),
+        };
+
+        let mut chosen = None;
+        if ui.button("System Default").clicked() {
+            chosen = Some(None);
+        }
+        for group in [&associated, &detected] {
+            if group.is_empty() { continue; }
+            ui.separator();
+            for OpenWithEntry { name, program } in group.iter() {
+                if ui.button(name).on_hover_text(program.to_string_lossy()).clicked() {
+                    chosen = Some(Some(program.clone()));
+                }
+            }
+        }
+        ui.separator();
+        if ui.button("Configure Associations…").clicked() {
+            control.push(AppCommand::ToggleSettings);
+            ui.close_menu();
+        }
+
+        if let Some(program) = chosen {
+            control.push(AppCommand::OpenWith { path: path.clone(), program });
+            ui.close_menu();
+        }
+    }
+
+    /// "Compare with…" to pick the other file; two selected files are compared from the batch menu
+    fn compare_menu_items(&mut self, ui: &mut Ui, path: &PathBuf, control: &mut Vec<AppCommand>) {
+        if ui.button("Compare with…").clicked() {
+            let dir = path.parent().map(PathBuf::from).unwrap_or_default();
+            if let Some(other) = rfd::FileDialog::new().set_directory(dir).pick_file() {
+                open_diff(path, &other, control);
+            }
+            ui.close_menu();
+        }
+    }
+
+    /// Actions on several selected items at once
+    fn batch_menu_items(&mut self, ui: &mut Ui, targets: Vec<PathBuf>, control: &mut Vec<AppCommand>) {
+        ui.weak(format!("{} items selected", targets.len()));
+        ui.separator();
+        let files: Vec<&PathBuf> = targets.iter().filter(|p| p.is_file()).collect();
+        if ui.add_enabled(!files.is_empty(), egui::Button::new(format!("Open {} Files", files.len()))).clicked() {
+            control.extend(files.iter().map(|p| AppCommand::OpenFile((*p).clone())));
+            ui.close_menu();
+        }
+        if files.len() == 2 && targets.len() == 2 && ui.button("Compare Selected").clicked() {
+            open_diff(files[0], files[1], control);
+            ui.close_menu();
+        }
+        if ui.button("Move To…").clicked() {
+            let start = targets[0].parent().map(PathBuf::from).unwrap_or_default();
+            if let Some(dir) = rfd::FileDialog::new().set_directory(start).pick_folder() {
+                self.move_items(targets.clone(), &dir, ui.ctx());
+            }
+            ui.close_menu();
+        }
+        if ui.button("Copy Paths").clicked() {
+            let paths: Vec<String> = targets.iter().map(|p| p.to_string_lossy().to_string()).collect();
+            control.push(AppCommand::CopyToClipboard(paths.join("\n")));
+            ui.close_menu();
+        }
+        ui.separator();
+        if ui.button(format!("Move {} Items to Trash", targets.len())).clicked() {
+            self.request_delete(targets, control);
+            ui.close_menu();
+        }
+    }
+
+    fn request_delete(&mut self, targets: Vec<PathBuf>, control: &mut Vec<AppCommand>) {
+        let confirm = self.open_with.lock().map(|registry| registry.config.confirm_delete).unwrap_or(true);
+        if confirm {
+            self.pending_delete = Some(targets);
+        } else {
+            self.delete_items(targets, control);
+        }
+    }
+
     fn context_menu_items(&mut self, ui: &mut Ui, path: &PathBuf, control: &mut Vec<AppCommand>, name: String) {
+        let targets = self.targets_for(path);
+        if targets.len() > 1 {
+            self.batch_menu_items(ui, targets, control);
+            return;
+        }
         if ui.button("Open").clicked() {
             if path.is_file() {
                 control.push(AppCommand::OpenFile(path.clone()));
             }
             ui.close_menu();
         }
+        if path.is_file() {
+            ui.menu_button("Open With…", |ui| self.open_with_menu(ui, path, control));
+            self.compare_menu_items(ui, path, control);
+        }
+        if archive::is_archive(path) {
+            if ui.button("Extract Here").on_hover_text("Extract into a new folder next to the archive").clicked() {
+                self.spawn_extract(path.clone(), String::new(), archive::extract_here_target(path), ui.ctx());
+                ui.close_menu();
+            }
+            if ui.button("Extract To…").clicked() {
+                if let Some(target) = rfd::FileDialog::new().pick_folder() {
+                    self.spawn_extract(path.clone(), String::new(), target, ui.ctx());
+                }
+                ui.close_menu();
+            }
+        }
         if path.is_dir() {
             if ui.button("New File").clicked() {
                 self.new_item_parent = Some((path.clone(), false));
                 self.input_text = "new_file.txt".to_string();
+                self.inline_focus = true;
                 ui.close_menu();
             }
             if ui.button("New Folder").clicked() {
                 self.new_item_parent = Some((path.clone(), true));
                 self.input_text = "new_folder".to_string();
+                self.inline_focus = true;
                 ui.close_menu();
             }
             ui.separator();
+            if self.roots.contains(path) {
+                if ui.button("Remove Folder from Workspace").clicked() {
+                    control.push(AppCommand::RemoveWorkspaceFolder(path.clone()));
+                    ui.close_menu();
+                }
+            } else {
+                if ui.button("Set as Workspace Root").clicked() {
+                    control.push(AppCommand::SetWorkspaceRoot(path.clone()));
+                    ui.close_menu();
+                }
+                if ui.button("Add Folder to Workspace").clicked() {
+                    control.push(AppCommand::AddWorkspaceFolder(path.clone()));
+                    ui.close_menu();
+                }
+            }
+            ui.separator();
         }
         
         if ui.button("Rename").clicked() {
             self.rename_path = Some(path.clone());
             self.input_text = name.clone();
+            self.inline_focus = true;
             ui.close_menu();
         }
-        if ui.button("Reveal in Explorer").clicked() {
+        if ui.button(REVEAL_LABEL).clicked() {
             control.push(AppCommand::RevealInShell(path.clone()));
             ui.close_menu();
         }
@@ -319,74 +1053,244 @@ impl FileExplorerTab {
             control.push(AppCommand::CopyToClipboard(path.to_string_lossy().to_string()));
             ui.close_menu();
         }
+        if ui.button("Open Terminal Here").clicked() {
+            let dir = if path.is_dir() { path.clone() } else { path.parent().map(PathBuf::from).unwrap_or_default() };
+            control.push(AppCommand::RunInTerminal { cwd: dir, program: String::new(), args: Vec::new() });
+            ui.close_menu();
+        }
+        if let Some((program, args)) = run::command_for(path) {
+            let command = std::iter::once(program.as_str()).chain(args.iter().map(String::as_str)).collect::<Vec<_>>().join(" ");
+            if ui.button("▶ Run File").on_hover_text(format!("Run `{}` in a terminal", command)).clicked() {
+                let cwd = path.parent().map(PathBuf::from).unwrap_or_default();
+                control.push(AppCommand::RunInTerminal { cwd, program, args });
+                ui.close_menu();
+            }
+        }
         ui.separator();
-        if ui.button("Delete").clicked() {
-            // Support deleting multiple if selected
-            let targets = if self.selected_items.contains(path) {
-                self.selected_items.iter().cloned().collect::<Vec<_>>()
-            } else {
-                vec![path.clone()]
-            };
+        if ui.button("Move to Trash").clicked() {
+            self.request_delete(targets, control);
+            ui.close_menu();
+        }
+        ui.separator();
+        if ui.button("Properties").clicked() {
+            if !self.properties.iter().any(|dialog| dialog.path() == path.as_path()) {
+                self.properties.push(PropertiesDialog::new(path, ui.ctx()));
+            }
+            ui.close_menu();
+        }
+    }
 
-            let mut success = 0;
-            for p in targets {
-                let res = if p.is_dir() { std::fs::remove_dir_all(&p) } else { std::fs::remove_file(&p) };
-                if res.is_ok() { 
-                    success += 1; 
-                    self.expanded_nodes.remove(&p);
-                    self.selected_items.remove(&p);
+    /// Moves items to the system trash and offers to put them back
+    fn delete_items(&mut self, targets: Vec<PathBuf>, control: &mut Vec<AppCommand>) {
+        let deleted_at = recycle::now();
+        if let Err(e) = recycle::move_to_trash(&targets) {
+            control.push(AppCommand::Notify {
+                message: format!("Failed to move to trash: {}", e),
+                level: NotificationLevel::Error,
+            });
+            // Some items may have been moved before the failure
+            for p in &targets {
+                if let Some(parent) = p.parent() {
+                    self.tree.invalidate(parent);
                 }
             }
-            
-            if success > 0 {
-                control.push(AppCommand::Notify { 
-                    message: format!("Deleted {} items", success), 
-                    level: NotificationLevel::Success 
-                });
+            return;
+        }
+
+        for p in &targets {
+            self.expanded_nodes.remove(p);
+            self.selected_items.remove(p);
+            self.tree.forget(p);
+            if let Some(parent) = p.parent() {
+                self.tree.invalidate(parent);
             }
-            ui.close_menu();
+        }
+
+        let message = match targets.as_slice() {
+            [single] => format!("Moved {} to trash", single.file_name().unwrap_or_default().to_string_lossy()),
+            _ => format!("Moved {} items to trash", targets.len()),
+        };
+        if !recycle::CAN_RESTORE {
+            control.push(AppCommand::Notify { message, level: NotificationLevel::Success });
+            return;
+        }
+        let tree = self.tree.clone();
+        let undo = NotificationAction::new("Undo", move |control| {
+            let message = match recycle::restore(&targets, deleted_at) {
+                Ok(count) => {
+                    for p in &targets {
+                        if let Some(parent) = p.parent() {
+                            tree.invalidate(parent);
+                        }
+                    }
+                    AppCommand::Notify { message: format!("Restored {} item(s)", count), level: NotificationLevel::Success }
+                }
+                Err(e) => AppCommand::Notify { message: format!("Undo failed: {}", e), level: NotificationLevel::Error },
+            };
+            control.push(message);
+        });
+        control.push(AppCommand::NotifyWithAction { message, level: NotificationLevel::Success, action: undo });
+    }
+
+    fn delete_confirmation(&mut self, ui: &mut Ui, control: &mut Vec<AppCommand>) {
+        let Some(targets) = self.pending_delete.clone() else { return };
+        let focus_id = ui.id().with("delete_confirmation_focus");
+        let mut open = true;
+        let mut confirmed = false;
+        egui::Window::new("Move to Trash")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ui.ctx(), |ui| {
+                match targets.as_slice() {
+                    [single] => ui.label(format!("Move \"{}\" to the trash?", single.file_name().unwrap_or_default().to_string_lossy())),
+                    _ => ui.label(format!("Move these {} items to the trash?", targets.len())),
+                };
+                if targets.len() > 1 {
+                    for p in targets.iter().take(8) {
+                        ui.weak(p.file_name().unwrap_or_default().to_string_lossy());
+                    }
+                    if targets.len() > 8 {
+                        ui.weak(format!("…and {} more", targets.len() - 8));
+                    }
+                }
+                ui.add_space(4.0);
+                if let Ok(mut registry) = self.open_with.lock() {
+                    let mut ask = registry.config.confirm_delete;
+                    if ui.checkbox(&mut ask, "Always ask before deleting").changed() {
+                        registry.config.confirm_delete = ask;
+                        registry.config.save();
+                    }
+                }
+                // 打开时让确认按钮获得焦点：Enter 由获得焦点的按钮响应，Escape 也只在焦点位于窗口按钮上时生效，
+                // 避免在编辑器或终端中按键误删文件。egui 在按下 Escape 的那一帧开始前就清除焦点，
+                // 因此记录上一帧焦点是否在按钮上
+                ui.horizontal(|ui| {
+                    let confirm = ui.button("Move to Trash");
+                    let cancel = ui.button("Cancel");
+                    let had_focus = ui.data(|d| d.get_temp::<bool>(focus_id));
+                    if had_focus.is_none() {
+                        confirm.request_focus();
+                    }
+                    ui.data_mut(|d| d.insert_temp(focus_id, confirm.has_focus() || cancel.has_focus()));
+                    if confirm.clicked() {
+                        confirmed = true;
+                    }
+                    let escape = had_focus == Some(true) && ui.input(|i| i.key_pressed(egui::Key::Escape));
+                    if cancel.clicked() || escape {
+                        self.pending_delete = None;
+                    }
+                });
+            });
+        if confirmed {
+            self.pending_delete = None;
+            self.delete_items(targets, control);
+        } else if !open {
+            self.pending_delete = None;
+        }
+        if self.pending_delete.is_none() {
+            ui.data_mut(|d| d.remove::<bool>(focus_id));
         }
     }
 }
 
 impl TabInstance for FileExplorerTab {
     fn title(&self) -> WidgetText {
-        if let Some(path) = &self.root_path {
-            format!("📂 {}", path.file_name().map(|n| n.to_string_lossy()).unwrap_or_default()).into()
-        } else {
-            "📁 Explorer".into()
+        match self.roots.as_slice() {
+            [] => "Explorer".into(),
+            [root] => root.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default().into(),
+            _ => "Workspace".into(),
         }
     }
 
+    fn icon(&self) -> Option<crate::TabIcon> {
+        Some(crate::TabIcon::Emoji(if !self.roots.is_empty() { "📂" } else { "📁" }.into()))
+    }
+
     fn ui(&mut self, ui: &mut Ui, control: &mut Vec<AppCommand>) {
         // Poll background commands
         self.process_pending_commands(control);
+        self.sync_workspace(ui.ctx());
 
         ui.vertical(|ui| {
             // Toolbar
             ui.horizontal(|ui| {
-                if ui.button("Open Folder...").clicked() {
+                if ui.button("Open Folder...").on_hover_text("Open a folder as the workspace root").clicked() {
                     if let Some(path) = rfd::FileDialog::new().pick_folder() {
-                        self.root_path = Some(path);
-                        self.selected_items.clear();
+                        control.push(AppCommand::SetWorkspaceRoot(path));
                     }
                 }
-                if self.root_path.is_some() {
-                    if ui.button("Refresh").clicked() {
+                if !self.roots.is_empty() {
+                    if ui.button("Add Folder...").on_hover_text("Add another root folder to the workspace").clicked() {
+                        if let Some(path) = rfd::FileDialog::new().pick_folder() {
+                            control.push(AppCommand::AddWorkspaceFolder(path));
+                        }
+                    }
+                    if ui.selectable_label(self.list_mode, "≡").on_hover_text("List view with sortable columns").clicked() {
+                        self.list_mode = !self.list_mode;
+                        self.filter_view = None;
+                    }
+                    ui.menu_button("👁", |ui| {
+                        if let Ok(mut registry) = self.open_with.lock() {
+                            let config = &mut registry.config;
+                            let changed = ui.checkbox(&mut config.hide_ignored, "Hide Git-Ignored Files").changed()
+                                | ui.checkbox(&mut config.hide_dotfiles, "Hide Dotfiles").changed();
+                            if changed {
+                                config.save();
+                            }
+                        }
+                    }).response.on_hover_text("Visible files");
+                    if ui.button("Refresh").on_hover_text("Re-read all expanded folders").clicked() {
                         self.expanded_nodes.retain(|p| p.exists());
+                        self.tree.refresh();
+                        self.archives.clear();
+                        self.filter_indexes.clear();
                     }
-                    if ui.button("Close").clicked() {
-                        self.root_path = None;
-                        self.expanded_nodes.clear();
-                        self.selected_items.clear();
+                    if ui.button("Close").on_hover_text("Remove all folders from the workspace").clicked() {
+                        control.extend(self.roots.iter().cloned().map(AppCommand::RemoveWorkspaceFolder));
                     }
                 }
             });
 
+            if !self.roots.is_empty() {
+                ui.horizontal(|ui| {
+                    let response = ui.add(
+                        egui::TextEdit::singleline(&mut self.filter)
+                            .hint_text(if self.list_mode { "🔍 Filter this folder" } else { "🔍 Filter by name" })
+                            .desired_width(ui.available_width() - 28.0),
+                    );
+                    if response.has_focus() && ui.input(|i| i.key_pressed(egui::Key::Escape)) {
+                        self.filter.clear();
+                    }
+                    if ui.add_enabled(!self.filter.is_empty(), egui::Button::new("✖").small()).on_hover_text("Clear filter").clicked() {
+                        self.filter.clear();
+                    }
+                });
+                if self.list_mode {
+                    self.list.nav_bar(ui, &self.roots);
+                } else {
+                    self.update_filter_view(ui.ctx());
+                }
+                if let Some(view) = &self.filter_view {
+                    ui.horizontal(|ui| {
+                        if !self.filter_indexes.iter().all(FileIndex::is_finished) {
+                            ui.spinner();
+                            ui.weak("Indexing…");
+                        } else if view.matches == 0 {
+                            ui.weak("No matching files");
+                        } else if view.matches > MAX_FILTER_MATCHES {
+                            ui.weak(format!("Showing the first {} matches", MAX_FILTER_MATCHES));
+                        } else {
+                            ui.weak(format!("{} match(es)", view.matches));
+                        }
+                    });
+                }
+            }
+
             ui.separator();
 
             // Content
-            if let Some(root) = self.root_path.clone() {
+            if !self.roots.is_empty() {
                 egui::ScrollArea::vertical()
                     .auto_shrink([false, false])
                     .show(ui, |ui| {
@@ -397,95 +1301,35 @@ impl TabInstance for FileExplorerTab {
                             self.selected_items.clear();
                         }
 
-                        self.render_tree(ui, root, control);
+                        if self.list_mode {
+                            self.render_list(ui, control);
+                        } else {
+                            for root in self.roots.clone() {
+                                self.render_tree(ui, root, true, control);
+                            }
+                        }
+                        self.visible_order = std::mem::take(&mut self.drawn_order);
                     });
+                // Mouse back / forward buttons
+                if self.list_mode && ui.ui_contains_pointer() {
+                    let (back, forward) = ui.input(|i| {
+                        (i.pointer.button_clicked(egui::PointerButton::Extra1), i.pointer.button_clicked(egui::PointerButton::Extra2))
+                    });
+                    if back {
+                        self.list.go_back();
+                    } else if forward {
+                        self.list.go_forward();
+                    }
+                }
             } else {
                 ui.centered_and_justified(|ui| {
-                    ui.label("No directory selected.\nClick the button above to start exploring.");
+                    ui.label("No folder in the workspace.\nClick \"Open Folder...\" to start exploring.");
                 });
             }
         });
 
-        // Dialogs (Rename / New Item)
-        if let Some(path) = self.rename_path.clone() {
-            let mut open = true;
-            egui::Window::new("Rename")
-                .open(&mut open)
-                .collapsible(false)
-                .resizable(false)
-                .show(ui.ctx(), |ui| {
-                    ui.label(format!("Old name: {}", path.file_name().unwrap_or_default().to_string_lossy()));
-                    ui.text_edit_singleline(&mut self.input_text);
-                    ui.horizontal(|ui| {
-                        if ui.button("Rename").clicked() {
-                            let new_path = path.parent().unwrap().join(&self.input_text);
-                            match std::fs::rename(&path, new_path) {
-                                Ok(_) => {
-                                    self.rename_path = None;
-                                    control.push(AppCommand::Notify { 
-                                        message: "Renamed successfully".into(), 
-                                        level: NotificationLevel::Success 
-                                    });
-                                }
-                                Err(e) => {
-                                    control.push(AppCommand::Notify { 
-                                        message: format!("Rename failed: {}", e), 
-                                        level: NotificationLevel::Error 
-                                    });
-                                }
-                            }
-                        }
-                        if ui.button("Cancel").clicked() {
-                            self.rename_path = None;
-                        }
-                    });
-                });
-            if !open { self.rename_path = None; }
-        }
-
-        if let Some((parent, is_dir)) = self.new_item_parent.clone() {
-            let mut open = true;
-            let title = if is_dir { "New Folder" } else { "New File" };
-            egui::Window::new(title)
-                .open(&mut open)
-                .collapsible(false)
-                .resizable(false)
-                .show(ui.ctx(), |ui| {
-                    ui.label(format!("Parent: {}", parent.to_string_lossy()));
-                    ui.text_edit_singleline(&mut self.input_text);
-                    ui.horizontal(|ui| {
-                        if ui.button("Create").clicked() {
-                            let new_path = parent.join(&self.input_text);
-                            let res = if is_dir {
-                                std::fs::create_dir_all(&new_path)
-                            } else {
-                                std::fs::File::create(&new_path).map(|_| ())
-                            };
-
-                            match res {
-                                Ok(_) => {
-                                    self.new_item_parent = None;
-                                    self.expanded_nodes.insert(parent);
-                                    control.push(AppCommand::Notify { 
-                                        message: format!("Created {}", if is_dir { "folder" } else { "file" }), 
-                                        level: NotificationLevel::Success 
-                                    });
-                                }
-                                Err(e) => {
-                                    control.push(AppCommand::Notify { 
-                                        message: format!("Creation failed: {}", e), 
-                                        level: NotificationLevel::Error 
-                                    });
-                                }
-                            }
-                        }
-                        if ui.button("Cancel").clicked() {
-                            self.new_item_parent = None;
-                        }
-                    });
-                });
-            if !open { self.new_item_parent = None; }
-        }
+        self.delete_confirmation(ui, control);
+        self.properties.retain_mut(|dialog| dialog.show(ui.ctx(), control));
     }
 
     fn box_clone(&self) -> Box<dyn TabInstance> {
@@ -493,11 +1337,34 @@ impl TabInstance for FileExplorerTab {
     }
 }
 
+fn item_icon(path: &Path, is_dir: bool) -> &'static str {
+    if is_dir {
+        "📁"
+    } else if archive::is_archive(path) {
+        "🗜"
+    } else {
+        "📄"
+    }
+}
+
+fn open_diff(left: &Path, right: &Path, control: &mut Vec<AppCommand>) {
+    match crate::diff::DiffTab::compare_files(left, right) {
+        Ok(tab) => control.push(AppCommand::OpenTab(Tab::new(Box::new(tab)))),
+        Err(e) => control.push(AppCommand::Notify {
+            message: format!("Failed to compare files: {}", e),
+            level: NotificationLevel::Error,
+        }),
+    }
+}
+
 // ----------------------------------------------------------------------------
 // Plugin Implementation
 // ----------------------------------------------------------------------------
 
-pub struct FileManagerPlugin;
+pub struct FileManagerPlugin {
+    open_with: Arc<Mutex<OpenWithRegistry>>,
+    quick_open: QuickOpen,
+}
 
 impl Plugin for FileManagerPlugin {
     fn name(&self) -> &str {
@@ -506,12 +1373,108 @@ impl Plugin for FileManagerPlugin {
 
     fn on_tab_menu(&mut self, ui: &mut Ui, control: &mut Vec<AppCommand>) {
         if ui.button("File Explorer").clicked() {
-            control.push(AppCommand::OpenTab(Tab::new(Box::new(FileExplorerTab::new()))));
+            control.push(AppCommand::OpenTab(Tab::new(Box::new(FileExplorerTab::new(self.open_with.clone())))));
             ui.close_menu();
         }
     }
+
+    fn on_global_ui(&mut self, ctx: &egui::Context, control: &mut Vec<AppCommand>) {
+        self.quick_open.show(ctx, control);
+    }
+
+    fn on_settings_ui(&mut self, ui: &mut Ui) {
+        let Ok(mut registry) = self.open_with.lock() else { return };
+        ui.vertical(|ui| {
+            ui.heading("File Manager Settings");
+            ui.add_space(4.0);
+
+            ui.label("• Ctrl + Click adds to the selection, Shift + Click selects a range; right-click for actions on all selected items.");
+            ui.label("• Ctrl + P opens any file in the workspace by fuzzy name; add :line to jump to a line.");
+            ui.label("• Type in the explorer's filter box to narrow the tree to matching names.");
+            ui.label("• ≡ switches the explorer to a list of one folder with sortable columns and back / forward navigation.");
+            ui.label("• Right-click a script or executable and choose Run File to run it in a terminal tab.");
+            ui.label("• Expand .zip and .verbium archives in the tree to browse them; files inside open read-only.");
+            ui.label("• The explorer follows the workspace folders; right-click a folder to make it the root or add it as another root.");
+            ui.label("• Deleted files go to the system trash; use Undo on the notification to put them back.");
+            if ui.checkbox(&mut registry.config.confirm_delete, "Ask before moving files to the trash").changed() {
+                registry.config.save();
+            }
+            ui.label("• Git status letters follow file names (filled when staged); ignored files are dimmed.");
+            if ui.checkbox(&mut registry.config.hide_ignored, "Hide files ignored by Git").changed() {
+                registry.config.save();
+            }
+            if ui.checkbox(&mut registry.config.hide_dotfiles, "Hide dotfiles").changed() {
+                registry.config.save();
+            }
+            ui.add_space(4.0);
+
+            ui.group(|ui| {
+                ui.label("Open With Associations");
+                ui.weak("Programs listed in the \"Open With…\" menu for files with the given extension. Use * to match every file.");
+                ui.add_space(4.0);
+
+                let mut changed = false;
+                let mut remove = None;
+                egui::Grid::new("file_manager_associations").num_columns(4).spacing([8.0, 4.0]).show(ui, |ui| {
+                    ui.strong("Extension");
+                    ui.strong("Name");
+                    ui.strong("Program");
+                    ui.end_row();
+                    for (i, association) in registry.config.associations.iter_mut().enumerate() {
+                        changed |= ui.add(egui::TextEdit::singleline(&mut association.extension).desired_width(60.0).hint_text("rs")).changed();
+                        changed |= ui.add(egui::TextEdit::singleline(&mut association.name).desired_width(120.0)).changed();
+                        ui.horizontal(|ui| {
+                            let program = if association.program.as_os_str().is_empty() {
+                                "No program selected".to_string()
+                            } else {
+                                association.program.to_string_lossy().to_string()
+                            };
+                            ui.label(program);
+                            if ui.button("Select...").clicked() {
+                                if let Some(program) = rfd::FileDialog::new().pick_file() {
+                                    association.program = program;
+                                    changed = true;
+                                }
+                            }
+                        });
+                        if ui.small_button("🗑").clicked() {
+                            remove = Some(i);
+                        }
+                        ui.end_row();
+                    }
+                });
+                if let Some(i) = remove {
+                    registry.config.associations.remove(i);
+                    changed = true;
+                }
+                if ui.button("➕ Add Association").clicked() {
+                    registry.config.associations.push(Association::default());
+                    changed = true;
+                }
+                if changed {
+                    registry.config.save();
+                }
+            });
+
+            if !registry.detected.is_empty() {
+                ui.add_space(8.0);
+                ui.group(|ui| {
+                    ui.label("Detected Applications");
+                    for entry in &registry.detected {
+                        ui.horizontal(|ui| {
+                            ui.label(&entry.name);
+                            ui.weak(entry.program.to_string_lossy());
+                        });
+                    }
+                });
+            }
+        });
+    }
 }
 
 pub fn create() -> FileManagerPlugin {
-    FileManagerPlugin
+    FileManagerPlugin {
+        open_with: Arc::new(Mutex::new(OpenWithRegistry::new())),
+        quick_open: QuickOpen::default(),
+    }
 }
\ No newline at end of file
@@ -0,0 +1,54 @@
+use std::path::Path;
+
+/// 文件名中不允许出现的字符
+#[cfg(windows)]
+const ILLEGAL_CHARS: &[char] = &['<', '>', ':', '"', '/', '\\', '|', '?', '*'];
+#[cfg(not(windows))]
+const ILLEGAL_CHARS: &[char] = &['/'];
+
+/// Windows 保留的设备名，带扩展名也不能使用
+#[cfg(windows)]
+const RESERVED_NAMES: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8", "COM9",
+    "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+// ----------------------------------------------------------------------------
+// 重命名与新建时的名称检查
+// ----------------------------------------------------------------------------
+
+/// 检查 `name` 能否作为 `dir` 中的新名称；`renaming` 为正在重命名的项，
+/// 它自己的名称（包括只改大小写）不算重复。返回给用户看的错误说明
+pub fn validate(name: &str, dir: &Path, renaming: Option<&Path>) -> Result<(), String> {
+    if name.is_empty() {
+        return Err("Enter a name".to_string());
+    }
+    if name == "." || name == ".." {
+        return Err(format!("\"{}\" is not a valid name", name));
+    }
+    if let Some(c) = name.chars().find(|c| ILLEGAL_CHARS.contains(c) || c.is_control()) {
+        return Err(if c.is_control() {
+            "Names can't contain control characters".to_string()
+        } else {
+            format!("Names can't contain \"{}\"", c)
+        });
+    }
+    #[cfg(windows)]
+    {
+        if name.ends_with('.') || name.ends_with(' ') {
+            return Err("Names can't end with a dot or a space".to_string());
+        }
+        let stem = name.split('.').next().unwrap_or(name).trim_end();
+        if RESERVED_NAMES.iter().any(|reserved| stem.eq_ignore_ascii_case(reserved)) {
+            return Err(format!("\"{}\" is reserved by Windows", stem));
+        }
+    }
+    let current = renaming.and_then(Path::file_name).map(|n| n.to_string_lossy());
+    if current.is_some_and(|current| current.to_lowercase() == name.to_lowercase()) {
+        return Ok(());
+    }
+    if dir.join(name).symlink_metadata().is_ok() {
+        return Err(format!("\"{}\" already exists here", name));
+    }
+    Ok(())
+}
@@ -0,0 +1,165 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+const CONFIG_FILE: &str = "file_manager_config.toml";
+
+// ----------------------------------------------------------------------------
+// "Open With" 程序
+// ----------------------------------------------------------------------------
+
+/// 用户为某个扩展名指定的外部程序
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct Association {
+    /// 不含点号的扩展名，`*` 匹配所有文件
+    pub extension: String,
+    pub name: String,
+    pub program: PathBuf,
+}
+
+impl Association {
+    fn matches(&self, extension: &str) -> bool {
+        let ext = self.extension.trim().trim_start_matches('.');
+        ext == "*" || ext.eq_ignore_ascii_case(extension)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct FileManagerConfig {
+    #[serde(default)]
+    pub associations: Vec<Association>,
+    /// 移到回收站之前先确认
+    #[serde(default = "default_true")]
+    pub confirm_delete: bool,
+    /// 资源管理器中隐藏被版本控制忽略的文件
+    #[serde(default)]
+    pub hide_ignored: bool,
+    /// 资源管理器中隐藏以 `.` 开头的文件与文件夹
+    #[serde(default)]
+    pub hide_dotfiles: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for FileManagerConfig {
+    fn default() -> Self {
+        Self { associations: Vec::new(), confirm_delete: true, hide_ignored: false, hide_dotfiles: false }
+    }
+}
+
+impl FileManagerConfig {
+    pub fn load() -> Self {
+        std::fs::read_to_string(CONFIG_FILE)
+            .ok()
+            .and_then(|content| toml::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        if let Ok(content) = toml::to_string_pretty(self) {
+            let _ = std::fs::write(CONFIG_FILE, content);
+        }
+    }
+}
+
+/// 菜单中的一个候选程序
+#[derive(Clone, Debug, PartialEq)]
+pub struct OpenWithEntry {
+    pub name: String,
+    pub program: PathBuf,
+}
+
+/// 插件与所有资源管理器标签页共享的程序列表
+#[derive(Clone, Debug, Default)]
+pub struct OpenWithRegistry {
+    pub config: FileManagerConfig,
+    /// 启动时在 PATH 中找到的常用程序
+    pub detected: Vec<OpenWithEntry>,
+}
+
+impl OpenWithRegistry {
+    pub fn new() -> Self {
+        Self {
+            config: FileManagerConfig::load(),
+            detected: detect_applications(),
+        }
+    }
+
+    /// 适用于 path 的程序：先列出用户关联，再列出检测到的程序（去重）
+    pub fn entries_for(&self, path: &Path) -> (Vec<OpenWithEntry>, Vec<OpenWithEntry>) {
+        let extension = path.extension().map(|e| e.to_string_lossy().to_string()).unwrap_or_default();
+        let associated: Vec<OpenWithEntry> = self.config.associations
+            .iter()
+            .filter(|a| a.matches(&extension) && !a.program.as_os_str().is_empty())
+            .map(|a| OpenWithEntry {
+                name: if a.name.trim().is_empty() { program_label(&a.program) } else { a.name.clone() },
+                program: a.program.clone(),
+            })
+            .collect();
+        let detected = self.detected
+            .iter()
+            .filter(|d| !associated.iter().any(|a| a.program == d.program))
+            .cloned()
+            .collect();
+        (associated, detected)
+    }
+}
+
+fn program_label(program: &Path) -> String {
+    program.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_else(|| program.to_string_lossy().to_string())
+}
+
+/// 按平台列出的常见程序（显示名称，可执行文件名）
+fn candidates() -> &'static [(&'static str, &'static str)] {
+    if cfg!(windows) {
+        &[
+            ("Visual Studio Code", "code"),
+            ("Sublime Text", "subl"),
+            ("Notepad++", "notepad++"),
+            ("Notepad", "notepad"),
+            ("Paint", "mspaint"),
+            ("VLC", "vlc"),
+            ("GIMP", "gimp"),
+        ]
+    } else if cfg!(target_os = "macos") {
+        &[
+            ("Visual Studio Code", "code"),
+            ("Sublime Text", "subl"),
+            ("Zed", "zed"),
+            ("VLC", "vlc"),
+        ]
+    } else {
+        &[
+            ("Visual Studio Code", "code"),
+            ("Sublime Text", "subl"),
+            ("Zed", "zed"),
+            ("Kate", "kate"),
+            ("gedit", "gedit"),
+            ("GIMP", "gimp"),
+            ("VLC", "vlc"),
+            ("LibreOffice", "libreoffice"),
+        ]
+    }
+}
+
+fn detect_applications() -> Vec<OpenWithEntry> {
+    candidates()
+        .iter()
+        .filter_map(|(name, exe)| {
+            find_in_path(exe).map(|program| OpenWithEntry { name: name.to_string(), program })
+        })
+        .collect()
+}
+
+/// 在 PATH 中查找可执行文件，Windows 下依次尝试常见扩展名
+fn find_in_path(exe: &str) -> Option<PathBuf> {
+    let extensions: &[&str] = if cfg!(windows) { &["exe", "cmd", "bat"] } else { &[""] };
+    let paths = std::env::var_os("PATH")?;
+    std::env::split_paths(&paths).find_map(|dir| {
+        extensions.iter().find_map(|ext| {
+            let candidate = if ext.is_empty() { dir.join(exe) } else { dir.join(format!("{}.{}", exe, ext)) };
+            candidate.is_file().then_some(candidate)
+        })
+    })
+}
@@ -0,0 +1,235 @@
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+use egui::{RichText, Ui};
+use crate::{AppCommand, NotificationLevel};
+
+// ----------------------------------------------------------------------------
+// 属性窗口：大小（文件夹在后台递归统计）、时间、只读与权限
+// ----------------------------------------------------------------------------
+
+/// 文件夹大小的统计进度
+#[derive(Debug, Clone, Copy, Default)]
+struct FolderSize {
+    bytes: u64,
+    files: u64,
+    folders: u64,
+    /// 无法读取的项
+    skipped: u64,
+    done: bool,
+}
+
+#[derive(Clone)]
+pub struct PropertiesDialog {
+    path: PathBuf,
+    metadata: Result<std::fs::Metadata, String>,
+    /// 符号链接本身（而不是其目标）
+    is_symlink: bool,
+    folder_size: Option<Arc<Mutex<FolderSize>>>,
+    cancel: Arc<AtomicBool>,
+}
+
+impl PropertiesDialog {
+    pub fn new(path: &Path, ctx: &egui::Context) -> Self {
+        let metadata = std::fs::metadata(path).map_err(|e| e.to_string());
+        let is_symlink = std::fs::symlink_metadata(path).is_ok_and(|m| m.file_type().is_symlink());
+        let cancel = Arc::new(AtomicBool::new(false));
+        let folder_size = metadata.as_ref().ok().filter(|m| m.is_dir()).map(|_| {
+            let size = Arc::new(Mutex::new(FolderSize::default()));
+            spawn_folder_size(path.to_path_buf(), size.clone(), cancel.clone(), ctx.clone());
+            size
+        });
+        Self { path: path.to_path_buf(), metadata, is_symlink, folder_size, cancel }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 显示窗口；关闭时返回 false
+    pub fn show(&mut self, ctx: &egui::Context, control: &mut Vec<AppCommand>) -> bool {
+        let mut open = true;
+        let name = self.path.file_name().unwrap_or(self.path.as_os_str()).to_string_lossy().to_string();
+        egui::Window::new(format!("Properties: {}", name))
+            .id(egui::Id::new("file_properties").with(&self.path))
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| self.ui(ui, control));
+        if !open {
+            self.cancel.store(true, Ordering::Relaxed);
+        }
+        open
+    }
+
+    fn ui(&mut self, ui: &mut Ui, control: &mut Vec<AppCommand>) {
+        let metadata = match &self.metadata {
+            Ok(metadata) => metadata.clone(),
+            Err(e) => {
+                ui.colored_label(crate::theme::palette(ui.ctx()).error, format!("Cannot read properties: {}", e));
+                return;
+            }
+        };
+        egui::Grid::new("file_properties_grid").num_columns(2).spacing([16.0, 6.0]).show(ui, |ui| {
+            ui.weak("Location");
+            ui.horizontal(|ui| {
+                ui.label(self.path.parent().map(|p| p.display().to_string()).unwrap_or_default());
+                if ui.small_button("📋").on_hover_text("Copy full path").clicked() {
+                    control.push(AppCommand::CopyToClipboard(self.path.to_string_lossy().to_string()));
+                }
+            });
+            ui.end_row();
+
+            ui.weak("Type");
+            let kind = if metadata.is_dir() {
+                "Folder".to_string()
+            } else {
+                match self.path.extension() {
+                    Some(ext) => format!("{} file", ext.to_string_lossy().to_uppercase()),
+                    None => "File".to_string(),
+                }
+            };
+            ui.label(if self.is_symlink { format!("{} (symbolic link)", kind) } else { kind });
+            ui.end_row();
+
+            ui.weak("Size");
+            match &self.folder_size {
+                Some(size) => {
+                    let size = size.lock().map(|s| *s).unwrap_or_default();
+                    ui.horizontal(|ui| {
+                        ui.label(format!("{} ({} bytes)", format_size(size.bytes), size.bytes));
+                        if !size.done {
+                            ui.spinner();
+                        }
+                    });
+                    ui.end_row();
+                    ui.weak("Contains");
+                    let mut contains = format!("{} files, {} folders", size.files, size.folders);
+                    if size.skipped > 0 {
+                        contains.push_str(&format!(" ({} unreadable)", size.skipped));
+                    }
+                    ui.label(contains);
+                }
+                None => {
+                    ui.label(format!("{} ({} bytes)", format_size(metadata.len()), metadata.len()));
+                }
+            }
+            ui.end_row();
+
+            for (label, time) in [("Created", metadata.created()), ("Modified", metadata.modified()), ("Accessed", metadata.accessed())] {
+                ui.weak(label);
+                ui.label(time.map(format_time).unwrap_or_else(|_| "Unavailable".to_string()));
+                ui.end_row();
+            }
+
+            ui.weak("Attributes");
+            ui.horizontal(|ui| {
+                let mut readonly = metadata.permissions().readonly();
+                if ui.checkbox(&mut readonly, "Read-only").changed() {
+                    match set_readonly(&self.path, &metadata, readonly) {
+                        Ok(()) => self.metadata = std::fs::metadata(&self.path).map_err(|e| e.to_string()),
+                        Err(e) => control.push(AppCommand::Notify {
+                            message: format!("Failed to change permissions: {}", e),
+                            level: NotificationLevel::Error,
+                        }),
+                    }
+                }
+                if let Some(mode) = unix_mode(&metadata) {
+                    ui.label(RichText::new(mode).monospace());
+                }
+            });
+            ui.end_row();
+        });
+    }
+}
+
+fn spawn_folder_size(root: PathBuf, size: Arc<Mutex<FolderSize>>, cancel: Arc<AtomicBool>, ctx: egui::Context) {
+    std::thread::spawn(move || {
+        let mut total = FolderSize::default();
+        // 不跟随符号链接，避免重复统计或陷入循环
+        for (i, entry) in walkdir::WalkDir::new(&root).min_depth(1).into_iter().enumerate() {
+            if cancel.load(Ordering::Relaxed) {
+                return;
+            }
+            match entry {
+                Ok(entry) if entry.file_type().is_dir() => total.folders += 1,
+                Ok(entry) => match entry.metadata() {
+                    Ok(metadata) => {
+                        total.files += 1;
+                        total.bytes += metadata.len();
+                    }
+                    Err(_) => total.skipped += 1,
+                },
+                Err(_) => total.skipped += 1,
+            }
+            if i % 1000 == 999 {
+                if let Ok(mut size) = size.lock() {
+                    *size = total;
+                }
+                ctx.request_repaint();
+            }
+        }
+        total.done = true;
+        if let Ok(mut size) = size.lock() {
+            *size = total;
+        }
+        ctx.request_repaint();
+    });
+}
+
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 { format!("{} B", bytes) } else { format!("{:.1} {}", size, UNITS[unit]) }
+}
+
+pub fn format_time(time: SystemTime) -> String {
+    let local: chrono::DateTime<chrono::Local> = time.into();
+    local.format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+/// Unix 下只增减所有者的写权限，不像 `set_readonly(false)` 那样让所有人可写
+fn set_readonly(path: &Path, metadata: &std::fs::Metadata, readonly: bool) -> std::io::Result<()> {
+    let mut permissions = metadata.permissions();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = permissions.mode();
+        permissions.set_mode(if readonly { mode & !0o222 } else { mode | 0o200 });
+    }
+    #[cfg(not(unix))]
+    permissions.set_readonly(readonly);
+    std::fs::set_permissions(path, permissions)
+}
+
+/// `rwxr-xr-x` 形式的权限位，非 Unix 平台为 None
+fn unix_mode(metadata: &std::fs::Metadata) -> Option<String> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = metadata.permissions().mode();
+        let bits: String = (0..9)
+            .map(|i| {
+                let set = mode & (0o400 >> i) != 0;
+                match (set, i % 3) {
+                    (false, _) => '-',
+                    (true, 0) => 'r',
+                    (true, 1) => 'w',
+                    (true, _) => 'x',
+                }
+            })
+            .collect();
+        Some(format!("{} ({:o})", bits, mode & 0o777))
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = metadata;
+        None
+    }
+}
@@ -0,0 +1,226 @@
+use std::path::PathBuf;
+use egui::text::{LayoutJob, TextFormat};
+use crate::AppCommand;
+use super::index::{fuzzy_match, FileIndex};
+
+/// 最多列出的结果数
+const MAX_RESULTS: usize = 50;
+
+// ----------------------------------------------------------------------------
+// 快速打开（Ctrl + P）：对工作区的文件做模糊匹配，回车打开。
+// 输入 `名称:行号` 可以直接跳到指定行
+// ----------------------------------------------------------------------------
+
+struct Hit {
+    index: usize,
+    positions: Vec<usize>,
+}
+
+#[derive(Default)]
+pub struct QuickOpen {
+    open: bool,
+    query: String,
+    selected: usize,
+    index: Option<FileIndex>,
+    /// 重新建立中的索引，完成后替换 `index`
+    rebuilding: Option<FileIndex>,
+    /// 针对 (查询, 条目数) 计算的结果；索引仍在增长时条目数变化会触发重新计算
+    hits: Vec<Hit>,
+    hits_for: Option<(String, usize)>,
+    file_count: usize,
+    focus: bool,
+}
+
+impl QuickOpen {
+    pub fn show(&mut self, ctx: &egui::Context, control: &mut Vec<AppCommand>) {
+        let shortcut = egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::P);
+        // 终端获得焦点时 Ctrl + P 交给 shell
+        if !crate::is_shortcut_claimed(ctx, shortcut) && ctx.input_mut(|i| i.consume_shortcut(&shortcut)) {
+            self.toggle(ctx);
+        }
+        if !self.open {
+            return;
+        }
+        if self.rebuilding.as_ref().is_some_and(FileIndex::is_finished) {
+            self.index = self.rebuilding.take();
+            self.hits_for = None;
+        }
+        let Some(index) = self.index.clone() else { return };
+
+        let (query, line) = split_line_suffix(&self.query);
+        let query = query.to_string();
+        let key = (query.clone(), index.len());
+        if self.hits_for.as_ref() != Some(&key) {
+            self.hits = search(&index, &query);
+            self.file_count = index.with_entries(|entries| entries.iter().filter(|e| !e.is_dir).count());
+            self.hits_for = Some(key);
+            self.selected = self.selected.min(self.hits.len().saturating_sub(1));
+        }
+
+        let mut chosen = None;
+        let window = egui::Window::new("Go to File")
+            .title_bar(false)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 60.0))
+            .fixed_size(egui::vec2(560.0, 0.0))
+            .show(ctx, |ui| {
+                let response = ui.add(
+                    egui::TextEdit::singleline(&mut self.query)
+                        .hint_text("Search files by name (append :line to jump to a line)")
+                        .desired_width(f32::INFINITY),
+                );
+                if std::mem::take(&mut self.focus) {
+                    response.request_focus();
+                }
+                if response.changed() {
+                    self.selected = 0;
+                }
+                let (up, down, enter, escape) = ui.input_mut(|i| {
+                    (
+                        i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp),
+                        i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown),
+                        i.key_pressed(egui::Key::Enter),
+                        i.key_pressed(egui::Key::Escape),
+                    )
+                });
+                if up {
+                    self.selected = self.selected.saturating_sub(1);
+                }
+                if down && self.selected + 1 < self.hits.len() {
+                    self.selected += 1;
+                }
+                if enter && !self.hits.is_empty() {
+                    chosen = Some(self.selected);
+                }
+                if escape {
+                    self.open = false;
+                }
+
+                ui.add_space(4.0);
+                let weak = ui.visuals().weak_text_color();
+                let text_color = ui.visuals().text_color();
+                let highlight = ui.visuals().selection.stroke.color;
+                let font_id = egui::TextStyle::Body.resolve(ui.style());
+                index.with_entries(|entries| {
+                    egui::ScrollArea::vertical().max_height(360.0).show(ui, |ui| {
+                        for (row, hit) in self.hits.iter().enumerate() {
+                            let entry = &entries[hit.index];
+                            let name_start = entry.name_start();
+                            let mut job = LayoutJob::default();
+                            // 文件名在前，所在目录以淡色跟在后面；匹配的字符高亮
+                            let append = |job: &mut LayoutJob, range: std::ops::Range<usize>, base: egui::Color32| {
+                                for (offset, c) in entry.relative[range.clone()].char_indices() {
+                                    let at = range.start + offset;
+                                    let color = if hit.positions.contains(&at) { highlight } else { base };
+                                    job.append(&c.to_string(), 0.0, TextFormat::simple(font_id.clone(), color));
+                                }
+                            };
+                            append(&mut job, name_start..entry.relative.len(), text_color);
+                            if name_start > 0 {
+                                job.append("  ", 0.0, TextFormat::simple(font_id.clone(), weak));
+                                append(&mut job, 0..name_start - 1, weak);
+                            }
+                            let response = ui.selectable_label(row == self.selected, job);
+                            if row == self.selected && (up || down) {
+                                response.scroll_to_me(None);
+                            }
+                            if response.clicked() {
+                                chosen = Some(row);
+                            }
+                        }
+                    });
+                });
+                let status = if self.hits.is_empty() && !query.is_empty() {
+                    "No matching files".to_string()
+                } else {
+                    format!("{} files indexed", self.file_count)
+                };
+                ui.horizontal(|ui| {
+                    ui.weak(status);
+                    if !index.is_finished() {
+                        ui.spinner();
+                    }
+                });
+            });
+        // 在窗口外点击时关闭
+        if let Some(window) = window {
+            let clicked_outside = ctx.input(|i| {
+                i.pointer.any_pressed() && i.pointer.interact_pos().is_some_and(|pos| !window.response.rect.contains(pos))
+            });
+            if clicked_outside {
+                self.open = false;
+            }
+        }
+
+        if let Some(row) = chosen {
+            let path: Option<PathBuf> = self.hits.get(row).map(|hit| index.with_entries(|entries| entries[hit.index].path.clone()));
+            if let Some(path) = path {
+                control.push(match line {
+                    Some(line) => AppCommand::OpenFileAt { path, line, column: 1 },
+                    None => AppCommand::OpenFile(path),
+                });
+            }
+            self.open = false;
+        }
+    }
+
+    /// 打开时在后台重新建立工作区（主根目录）索引，建好之前先沿用上一次的结果
+    fn toggle(&mut self, ctx: &egui::Context) {
+        self.open = !self.open;
+        if !self.open {
+            return;
+        }
+        self.query.clear();
+        self.selected = 0;
+        self.focus = true;
+        self.hits_for = None;
+        let root = crate::workspace::current(ctx)
+            .primary_root()
+            .map(PathBuf::from)
+            .unwrap_or_else(|| std::env::current_dir().unwrap_or_default());
+        match &self.index {
+            Some(index) if index.root() == root => {
+                if index.is_finished() && self.rebuilding.is_none() {
+                    self.rebuilding = Some(FileIndex::build(root, ctx));
+                }
+            }
+            _ => {
+                self.index = Some(FileIndex::build(root, ctx));
+                self.rebuilding = None;
+            }
+        }
+    }
+}
+
+/// 拆出末尾的 `:行号`
+fn split_line_suffix(query: &str) -> (&str, Option<usize>) {
+    match query.rsplit_once(':') {
+        Some((name, line)) if !line.is_empty() && line.chars().all(|c| c.is_ascii_digit()) => (name, line.parse().ok()),
+        _ => (query, None),
+    }
+}
+
+fn search(index: &FileIndex, query: &str) -> Vec<Hit> {
+    index.with_entries(|entries| {
+        if query.trim().is_empty() {
+            return entries
+                .iter()
+                .enumerate()
+                .filter(|(_, e)| !e.is_dir)
+                .take(MAX_RESULTS)
+                .map(|(index, _)| Hit { index, positions: Vec::new() })
+                .collect();
+        }
+        let mut scored: Vec<(i64, Hit)> = entries
+            .iter()
+            .enumerate()
+            .filter(|(_, e)| !e.is_dir)
+            .filter_map(|(index, e)| {
+                fuzzy_match(query, &e.relative, e.name_start()).map(|(score, positions)| (score, Hit { index, positions }))
+            })
+            .collect();
+        scored.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| entries[a.1.index].relative.cmp(&entries[b.1.index].relative)));
+        scored.into_iter().take(MAX_RESULTS).map(|(_, hit)| hit).collect()
+    })
+}
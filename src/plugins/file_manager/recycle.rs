@@ -0,0 +1,53 @@
+use std::path::PathBuf;
+
+// ----------------------------------------------------------------------------
+// 回收站：删除的文件移到系统回收站，Windows 与 Linux 上可以从回收站还原
+// ----------------------------------------------------------------------------
+
+/// 当前平台能否从回收站还原（macOS 的废纸篓没有公开的还原接口）
+pub const CAN_RESTORE: bool = cfg!(any(
+    target_os = "windows",
+    all(unix, not(target_os = "macos"), not(target_os = "ios"), not(target_os = "android"))
+));
+
+/// 当前时间（Unix 秒），与回收站记录的删除时间比较
+pub fn now() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs() as i64)
+}
+
+pub fn move_to_trash(paths: &[PathBuf]) -> Result<(), String> {
+    trash::delete_all(paths).map_err(|e| e.to_string())
+}
+
+/// 还原 `since` 之后删除的 `paths`（同一路径删除过多次时取最近的一次），返回还原的数量。
+/// 原位置已被占用时不还原
+#[cfg(any(target_os = "windows", all(unix, not(target_os = "macos"), not(target_os = "ios"), not(target_os = "android"))))]
+pub fn restore(paths: &[PathBuf], since: i64) -> Result<usize, String> {
+    if let Some(taken) = paths.iter().find(|p| p.exists()) {
+        return Err(format!("{} already exists", taken.display()));
+    }
+    let mut latest: std::collections::HashMap<PathBuf, trash::TrashItem> = Default::default();
+    for item in trash::os_limited::list().map_err(|e| e.to_string())? {
+        // 回收站记录的时间精度为秒，留出一秒余量
+        let original = item.original_path();
+        if item.time_deleted + 1 < since || !paths.contains(&original) {
+            continue;
+        }
+        if latest.get(&original).is_none_or(|newest| newest.time_deleted < item.time_deleted) {
+            latest.insert(original, item);
+        }
+    }
+    if latest.is_empty() {
+        return Err("The items are no longer in the trash".to_string());
+    }
+    let count = latest.len();
+    trash::os_limited::restore_all(latest.into_values()).map_err(|e| e.to_string())?;
+    Ok(count)
+}
+
+#[cfg(not(any(target_os = "windows", all(unix, not(target_os = "macos"), not(target_os = "ios"), not(target_os = "android")))))]
+pub fn restore(_paths: &[PathBuf], _since: i64) -> Result<usize, String> {
+    Err("Restoring from the trash is not supported on this platform".to_string())
+}
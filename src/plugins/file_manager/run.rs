@@ -0,0 +1,68 @@
+use std::io::Read;
+use std::path::Path;
+
+// ----------------------------------------------------------------------------
+// "运行文件"：决定脚本或可执行文件在终端中的运行方式
+// ----------------------------------------------------------------------------
+
+/// 按扩展名（小写）选择解释器及其在文件路径之前的参数
+fn interpreter_for(ext: &str) -> Option<(&'static str, &'static [&'static str])> {
+    Some(match ext {
+        "sh" => ("sh", &[]),
+        "bash" => ("bash", &[]),
+        "zsh" => ("zsh", &[]),
+        "fish" => ("fish", &[]),
+        "py" | "pyw" => (if cfg!(windows) { "python" } else { "python3" }, &[]),
+        "js" | "mjs" | "cjs" => ("node", &[]),
+        "rb" => ("ruby", &[]),
+        "pl" => ("perl", &[]),
+        "php" => ("php", &[]),
+        "lua" => ("lua", &[]),
+        "ps1" => (if cfg!(windows) { "powershell" } else { "pwsh" }, &["-File"]),
+        "bat" | "cmd" if cfg!(windows) => ("cmd", &["/C"]),
+        _ => return None,
+    })
+}
+
+/// 文件的运行命令：(程序, 参数)。可执行文件直接运行，脚本交给对应的解释器，
+/// 没有已知扩展名的脚本按首行的 `#!` 选择解释器。无法运行时返回 None
+pub fn command_for(path: &Path) -> Option<(String, Vec<String>)> {
+    if !path.is_file() {
+        return None;
+    }
+    let file = path.to_string_lossy().to_string();
+    if is_executable(path) {
+        return Some((file, Vec::new()));
+    }
+    let ext = path.extension().map(|e| e.to_string_lossy().to_lowercase()).unwrap_or_default();
+    if let Some((program, args)) = interpreter_for(&ext) {
+        let mut args: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+        args.push(file);
+        return Some((program.to_string(), args));
+    }
+    let mut words = shebang(path)?.split_whitespace().map(str::to_string).collect::<Vec<_>>().into_iter();
+    let program = words.next()?;
+    let mut args: Vec<String> = words.collect();
+    args.push(file);
+    Some((program, args))
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::metadata(path).is_ok_and(|m| m.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("exe") || ext.eq_ignore_ascii_case("com"))
+}
+
+/// 首行 `#!` 之后的解释器命令
+fn shebang(path: &Path) -> Option<String> {
+    let mut head = [0u8; 256];
+    let len = std::fs::File::open(path).and_then(|mut f| f.read(&mut head)).ok()?;
+    let line = head[..len].split(|b| *b == b'\n').next()?;
+    let command = std::str::from_utf8(line.strip_prefix(b"#!")?).ok()?.trim();
+    (!command.is_empty()).then(|| command.to_string())
+}
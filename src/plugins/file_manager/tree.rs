@@ -0,0 +1,204 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+
+// ----------------------------------------------------------------------------
+// 目录树缓存：展开的目录在后台线程中读取，结果缓存起来供每一帧绘制，
+// 由文件系统通知（不可用时定时轮询）或手动刷新触发重新读取
+// ----------------------------------------------------------------------------
+
+/// 没有文件系统通知时，已展开目录的重新读取间隔
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// 目录中的一项
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub path: PathBuf,
+    pub is_dir: bool,
+    /// 文件大小（目录为 0），供列表视图显示与排序
+    pub size: u64,
+    pub modified: Option<SystemTime>,
+}
+
+/// 一个目录的读取结果
+#[derive(Debug, Clone)]
+pub enum Listing {
+    Loading,
+    Ready(Arc<Vec<Entry>>),
+    Failed(String),
+}
+
+struct CachedDir {
+    /// 最近一次读取的结果；重新读取期间保留旧结果，避免界面闪烁
+    listing: Listing,
+    loaded_at: Instant,
+    /// 收到修改通知或手动刷新后置位，下次绘制时重新读取
+    stale: bool,
+    loading: bool,
+}
+
+struct Inner {
+    dirs: HashMap<PathBuf, CachedDir>,
+    /// 创建失败（例如超出 inotify 数量限制）时为 None，退回定时轮询
+    watcher: Option<RecommendedWatcher>,
+    watched: HashSet<PathBuf>,
+    /// 通知器回调收到的路径，下次绘制时处理。回调不直接锁定缓存，
+    /// 以免与持有缓存锁时调用 watch / unwatch 互相等待
+    changed: Arc<Mutex<Vec<PathBuf>>>,
+    /// 收到通知时唤醒界面
+    ctx: Arc<Mutex<Option<egui::Context>>>,
+}
+
+/// 资源管理器标签页的目录缓存，克隆后共享同一份数据
+#[derive(Clone)]
+pub struct TreeCache {
+    inner: Arc<Mutex<Inner>>,
+}
+
+impl TreeCache {
+    pub fn new() -> Self {
+        let changed: Arc<Mutex<Vec<PathBuf>>> = Arc::default();
+        let ctx: Arc<Mutex<Option<egui::Context>>> = Arc::default();
+        let (changed_clone, ctx_clone) = (changed.clone(), ctx.clone());
+        let watcher = notify::recommended_watcher(move |result: notify::Result<notify::Event>| {
+            let Ok(event) = result else { return };
+            if matches!(event.kind, EventKind::Access(_)) {
+                return;
+            }
+            if let Ok(mut changed) = changed_clone.lock() {
+                changed.extend(event.paths);
+            }
+            if let Some(ctx) = ctx_clone.lock().ok().and_then(|ctx| ctx.clone()) {
+                ctx.request_repaint();
+            }
+        });
+        if let Err(e) = &watcher {
+            eprintln!("Explorer watcher unavailable, falling back to polling: {}", e);
+        }
+        let inner = Inner {
+            dirs: HashMap::new(),
+            watcher: watcher.ok(),
+            watched: HashSet::new(),
+            changed,
+            ctx,
+        };
+        Self { inner: Arc::new(Mutex::new(inner)) }
+    }
+
+    /// 返回目录的缓存内容；尚未读取、已过期或收到修改通知时在后台重新读取
+    pub fn listing(&self, dir: &Path, ctx: &egui::Context) -> Listing {
+        let Ok(mut inner) = self.inner.lock() else { return Listing::Loading };
+        if let Ok(mut stored) = inner.ctx.lock() {
+            stored.get_or_insert_with(|| ctx.clone());
+        }
+        let changed = inner.changed.lock().map(|mut changed| std::mem::take(&mut *changed)).unwrap_or_default();
+        for path in changed {
+            // 目录内容变化时通知的是其中的项，因此标记其父目录；目录本身被删除时也标记它自己
+            for dir in [path.parent(), Some(path.as_path())].into_iter().flatten() {
+                if let Some(cached) = inner.dirs.get_mut(dir) {
+                    cached.stale = true;
+                }
+            }
+        }
+        let polling = inner.watcher.is_none();
+        let cached = inner.dirs.entry(dir.to_path_buf()).or_insert_with(|| CachedDir {
+            listing: Listing::Loading,
+            loaded_at: Instant::now(),
+            stale: true,
+            loading: false,
+        });
+        if polling && !cached.loading && cached.loaded_at.elapsed() >= POLL_INTERVAL {
+            cached.stale = true;
+        }
+        let listing = cached.listing.clone();
+        if cached.stale && !cached.loading {
+            cached.stale = false;
+            cached.loading = true;
+            self.spawn_read(dir.to_path_buf(), ctx.clone());
+        }
+        if polling {
+            ctx.request_repaint_after(POLL_INTERVAL);
+        }
+        listing
+    }
+
+    fn spawn_read(&self, dir: PathBuf, ctx: egui::Context) {
+        let inner = Arc::downgrade(&self.inner);
+        std::thread::spawn(move || {
+            let listing = match read_dir(&dir) {
+                Ok(entries) => Listing::Ready(Arc::new(entries)),
+                Err(e) => Listing::Failed(e.to_string()),
+            };
+            let Some(inner) = inner.upgrade() else { return };
+            let Ok(mut inner) = inner.lock() else { return };
+            let Inner { dirs, watcher, watched, .. } = &mut *inner;
+            // 读取期间目录被折叠时丢弃结果
+            let Some(cached) = dirs.get_mut(&dir) else { return };
+            cached.listing = listing;
+            cached.loaded_at = Instant::now();
+            cached.loading = false;
+            if let Some(watcher) = watcher {
+                if !watched.contains(&dir) && watcher.watch(&dir, RecursiveMode::NonRecursive).is_ok() {
+                    watched.insert(dir);
+                }
+            }
+            ctx.request_repaint();
+        });
+    }
+
+    /// 标记目录需要重新读取，用于资源管理器自己的增删改操作，不必等待通知
+    pub fn invalidate(&self, dir: &Path) {
+        if let Ok(mut inner) = self.inner.lock() {
+            if let Some(cached) = inner.dirs.get_mut(dir) {
+                cached.stale = true;
+            }
+        }
+    }
+
+    /// 手动刷新：所有缓存的目录都重新读取
+    pub fn refresh(&self) {
+        if let Ok(mut inner) = self.inner.lock() {
+            for cached in inner.dirs.values_mut() {
+                cached.stale = true;
+            }
+        }
+    }
+
+    /// 目录被折叠后丢弃它及其子目录的缓存并停止监听
+    pub fn forget(&self, dir: &Path) {
+        let Ok(mut inner) = self.inner.lock() else { return };
+        let Inner { dirs, watcher, watched, .. } = &mut *inner;
+        dirs.retain(|path, _| !path.starts_with(dir));
+        watched.retain(|path| {
+            if !path.starts_with(dir) {
+                return true;
+            }
+            if let Some(watcher) = watcher {
+                let _ = watcher.unwatch(path);
+            }
+            false
+        });
+    }
+}
+
+/// 读取目录，文件夹在前，同类按路径排序。信息取自目录项本身，只有符号链接才额外查询目标
+fn read_dir(dir: &Path) -> std::io::Result<Vec<Entry>> {
+    let mut entries: Vec<Entry> = std::fs::read_dir(dir)?
+        .flatten()
+        .map(|entry| {
+            let path = entry.path();
+            let metadata = match entry.file_type() {
+                Ok(kind) if !kind.is_symlink() => entry.metadata(),
+                _ => std::fs::metadata(&path),
+            };
+            let is_dir = metadata.as_ref().map_or_else(|_| path.is_dir(), |m| m.is_dir());
+            let size = metadata.as_ref().map_or(0, |m| if m.is_dir() { 0 } else { m.len() });
+            let modified = metadata.ok().and_then(|m| m.modified().ok());
+            Entry { path, is_dir, size, modified }
+        })
+        .collect();
+    entries.sort_by(|a, b| b.is_dir.cmp(&a.is_dir).then_with(|| a.path.cmp(&b.path)));
+    Ok(entries)
+}
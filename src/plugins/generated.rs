@@ -12,9 +12,15 @@ pub mod code_editor;
 #[cfg(feature = "plugin_file_manager")]
 pub mod file_manager;
 
+#[cfg(feature = "plugin_git")]
+pub mod git;
+
 #[cfg(feature = "plugin_manager")]
 pub mod manager;
 
+#[cfg(feature = "plugin_search")]
+pub mod search;
+
 #[cfg(feature = "plugin_terminal")]
 pub mod terminal;
 
@@ -31,8 +37,12 @@ pub const PLUGIN_NAME_BROWSER: &str = "browser";
 pub const PLUGIN_NAME_CODE_EDITOR: &str = "code_editor";
 #[cfg(feature = "plugin_file_manager")]
 pub const PLUGIN_NAME_FILE_MANAGER: &str = "file_manager";
+#[cfg(feature = "plugin_git")]
+pub const PLUGIN_NAME_GIT: &str = "git";
 #[cfg(feature = "plugin_manager")]
 pub const PLUGIN_NAME_MANAGER: &str = "manager";
+#[cfg(feature = "plugin_search")]
+pub const PLUGIN_NAME_SEARCH: &str = "search";
 #[cfg(feature = "plugin_terminal")]
 pub const PLUGIN_NAME_TERMINAL: &str = "terminal";
 #[cfg(feature = "plugin_test_plugin")]
@@ -64,12 +74,24 @@ pub fn get_extra_plugins() -> Vec<Box<dyn crate::Plugin>> {
             assert_eq!(p.name(), PLUGIN_NAME_FILE_MANAGER, "Plugin name mismatch for file_manager");
             plugins.push(p);
         }
+        #[cfg(feature = "plugin_git")]
+        {
+            let p = Box::new(git::create());
+            assert_eq!(p.name(), PLUGIN_NAME_GIT, "Plugin name mismatch for git");
+            plugins.push(p);
+        }
         #[cfg(feature = "plugin_manager")]
         {
             let p = Box::new(manager::create());
             assert_eq!(p.name(), PLUGIN_NAME_MANAGER, "Plugin name mismatch for manager");
             plugins.push(p);
         }
+        #[cfg(feature = "plugin_search")]
+        {
+            let p = Box::new(search::create());
+            assert_eq!(p.name(), PLUGIN_NAME_SEARCH, "Plugin name mismatch for search");
+            plugins.push(p);
+        }
         #[cfg(feature = "plugin_terminal")]
         {
             let p = Box::new(terminal::create());
@@ -0,0 +1,428 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use egui::{Ui, WidgetText, RichText};
+use parking_lot::RwLock;
+use crate::diff::{DiffSide, DiffTab};
+use crate::power::PowerProfile;
+use crate::{Plugin, AppCommand, TabInstance, Tab, NotificationLevel, VcsProvider, VcsStatus};
+
+mod repo;
+use repo::{FileEntry, Snapshot};
+
+/// 后台刷新状态的最短间隔（秒），实际间隔不小于性能设置中的文件监听间隔
+const MIN_REFRESH_SECS: f64 = 2.0;
+
+// ----------------------------------------------------------------------------
+// 仓库状态：后台线程刷新，标签页、编辑器与文件管理器共用
+// ----------------------------------------------------------------------------
+
+#[derive(Default)]
+struct GitState {
+    snapshot: Option<Snapshot>,
+    /// 工作目录不在仓库中或 git 不可用时的说明
+    error: Option<String>,
+    /// 绝对路径 → 状态，包含含有变更的上级目录
+    statuses: HashMap<PathBuf, VcsStatus>,
+    /// 变更已全部暂存的文件
+    staged: HashSet<PathBuf>,
+    /// 被忽略的文件与目录（绝对路径）
+    ignored: HashSet<PathBuf>,
+    refreshing: bool,
+    last_refresh: f64,
+    /// HEAD 中的文件内容，HEAD 变化后清空；值为 None 表示正在读取或不在版本库中
+    base_texts: HashMap<PathBuf, Option<Arc<String>>>,
+    /// 后台操作产生的通知，由插件转交给宿主
+    pending: Vec<AppCommand>,
+}
+
+#[derive(Clone)]
+struct GitRepo {
+    state: Arc<RwLock<GitState>>,
+    ctx: egui::Context,
+}
+
+fn absolute(path: &Path) -> PathBuf {
+    if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        std::env::current_dir().unwrap_or_default().join(path)
+    }
+}
+
+impl GitRepo {
+    fn new(ctx: &egui::Context) -> Self {
+        Self { state: Arc::new(RwLock::new(GitState::default())), ctx: ctx.clone() }
+    }
+
+    fn snapshot(&self) -> Option<Snapshot> {
+        self.state.read().snapshot.clone()
+    }
+
+    /// 在后台重新读取仓库状态
+    fn refresh(&self) {
+        {
+            let mut state = self.state.write();
+            if state.refreshing {
+                return;
+            }
+            state.refreshing = true;
+        }
+        let repo = self.clone();
+        std::thread::spawn(move || {
+            let cwd = std::env::current_dir().unwrap_or_default();
+            let result = match repo::find_root(&cwd) {
+                Some(root) => repo::snapshot(&root),
+                None => Err("The workspace folder is not inside a git repository.".to_string()),
+            };
+            let mut state = repo.state.write();
+            state.refreshing = false;
+            state.last_refresh = repo.ctx.input(|i| i.time);
+            match result {
+                Ok(snapshot) => {
+                    let mut statuses = HashMap::new();
+                    let mut staged = HashSet::new();
+                    for entry in &snapshot.entries {
+                        let path = snapshot.root.join(&entry.path);
+                        for dir in path.ancestors().skip(1).take_while(|dir| dir.starts_with(&snapshot.root) && *dir != snapshot.root.as_path()) {
+                            statuses.insert(dir.to_path_buf(), VcsStatus::Modified);
+                        }
+                        if entry.is_fully_staged() {
+                            staged.insert(path.clone());
+                        }
+                        statuses.insert(path, entry.status());
+                    }
+                    let ignored = snapshot.ignored.iter().map(|p| snapshot.root.join(p.trim_end_matches('/'))).collect();
+                    let head_changed = state.snapshot.as_ref().map(|s| &s.head) != Some(&snapshot.head);
+                    if head_changed {
+                        state.base_texts.clear();
+                    }
+                    state.statuses = statuses;
+                    state.staged = staged;
+                    state.ignored = ignored;
+                    state.snapshot = Some(snapshot);
+                    state.error = None;
+                }
+                Err(e) => {
+                    state.snapshot = None;
+                    state.statuses.clear();
+                    state.staged.clear();
+                    state.ignored.clear();
+                    state.base_texts.clear();
+                    state.error = Some(e);
+                }
+            }
+            drop(state);
+            repo.ctx.request_repaint();
+        });
+    }
+
+    fn refresh_if_due(&self, time: f64, interval: f64) {
+        let due = {
+            let state = self.state.read();
+            !state.refreshing && (state.last_refresh == 0.0 || time - state.last_refresh >= interval)
+        };
+        if due {
+            self.refresh();
+        }
+    }
+
+    /// 在后台执行一次会修改仓库的操作，完成后刷新状态；失败时发出通知
+    fn run(&self, action: impl FnOnce(&Path) -> Result<Option<String>, String> + Send + 'static) {
+        let Some(root) = self.snapshot().map(|s| s.root) else {
+            return;
+        };
+        let repo = self.clone();
+        std::thread::spawn(move || {
+            let notice = match action(&root) {
+                Ok(Some(message)) => Some(AppCommand::Notify { message, level: NotificationLevel::Success }),
+                Ok(None) => None,
+                Err(message) => Some(AppCommand::Notify { message, level: NotificationLevel::Error }),
+            };
+            repo.state.write().pending.extend(notice);
+            repo.refresh();
+        });
+    }
+
+    fn take_pending(&self) -> Vec<AppCommand> {
+        std::mem::take(&mut self.state.write().pending)
+    }
+}
+
+impl VcsProvider for GitRepo {
+    fn status(&self, path: &Path) -> Option<VcsStatus> {
+        self.state.read().statuses.get(&absolute(path)).copied()
+    }
+
+    fn is_staged(&self, path: &Path) -> bool {
+        self.state.read().staged.contains(&absolute(path))
+    }
+
+    fn is_ignored(&self, path: &Path) -> bool {
+        let state = self.state.read();
+        if state.ignored.is_empty() {
+            return false;
+        }
+        let Some(root) = state.snapshot.as_ref().map(|s| s.root.as_path()) else { return false };
+        // 被忽略目录中的所有内容也都被忽略
+        absolute(path).ancestors().take_while(|p| p.starts_with(root) && *p != root).any(|p| state.ignored.contains(p))
+    }
+
+    fn base_text(&self, path: &Path) -> Option<Arc<String>> {
+        let path = absolute(path);
+        let root = {
+            let state = self.state.read();
+            if let Some(cached) = state.base_texts.get(&path) {
+                return cached.clone();
+            }
+            let snapshot = state.snapshot.as_ref()?;
+            snapshot.head.as_ref()?;
+            // 未跟踪与新增的文件在 HEAD 中不存在
+            if matches!(state.statuses.get(&path), Some(VcsStatus::Untracked | VcsStatus::Added)) {
+                return None;
+            }
+            snapshot.root.clone()
+        };
+        let relative = path.strip_prefix(&root).ok()?.to_string_lossy().replace('\\', "/");
+        self.state.write().base_texts.insert(path.clone(), None);
+        let repo = self.clone();
+        std::thread::spawn(move || {
+            let text = repo::show(&root, &format!("HEAD:{}", relative)).map(Arc::new);
+            if text.is_some() {
+                repo.state.write().base_texts.insert(path, text);
+                repo.ctx.request_repaint();
+            }
+        });
+        None
+    }
+}
+
+// ----------------------------------------------------------------------------
+// Source Control Tab
+// ----------------------------------------------------------------------------
+
+#[derive(Clone)]
+pub struct GitTab {
+    repo: GitRepo,
+    message: String,
+}
+
+impl std::fmt::Debug for GitTab {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GitTab").finish()
+    }
+}
+
+/// 打开一个文件的变更：暂存区相对 HEAD，或工作区相对暂存区
+fn open_diff(snapshot: &Snapshot, entry: &FileEntry, staged: bool, control: &mut Vec<AppCommand>) {
+    let root = &snapshot.root;
+    let name = entry.path.rsplit('/').next().unwrap_or(&entry.path).to_string();
+    let old_path = entry.original.as_deref().unwrap_or(&entry.path);
+    let (left, right) = if staged {
+        let head = repo::show(root, &format!("HEAD:{}", old_path)).unwrap_or_default();
+        let index = repo::show(root, &format!(":{}", entry.path)).unwrap_or_default();
+        (DiffSide::new(format!("{} (HEAD)", name), head), DiffSide::new(format!("{} (Index)", name), index))
+    } else {
+        let index = if entry.is_untracked() { String::new() } else { repo::show(root, &format!(":{}", entry.path)).unwrap_or_default() };
+        let working = DiffSide::from_file(&root.join(&entry.path))
+            .map(|side| DiffSide { label: format!("{} (Working Tree)", name), ..side })
+            .unwrap_or_else(|_| DiffSide::new(format!("{} (Deleted)", name), ""));
+        (DiffSide::new(format!("{} (Index)", name), index), working)
+    };
+    control.push(AppCommand::OpenTab(Tab::new(Box::new(DiffTab::new(left, right).inline()))));
+}
+
+impl GitTab {
+    fn new(repo: GitRepo) -> Self {
+        Self { repo, message: String::new() }
+    }
+
+    fn commit(&mut self) {
+        let message = std::mem::take(&mut self.message);
+        self.repo.run(move |root| repo::commit(root, &message).map(Some));
+    }
+
+    /// 已暂存或未暂存的变更列表，点击文件打开其差异
+    fn entries_ui(&self, ui: &mut Ui, snapshot: &Snapshot, staged: bool, control: &mut Vec<AppCommand>) {
+        let palette = crate::theme::palette(ui.ctx());
+        let entries: Vec<&FileEntry> = snapshot
+            .entries
+            .iter()
+            .filter(|e| if staged { e.staged().is_some() } else { e.unstaged().is_some() })
+            .collect();
+        let title = if staged { "Staged Changes" } else { "Changes" };
+
+        egui::CollapsingHeader::new(format!("{} ({})", title, entries.len()))
+            .id_salt(title)
+            .default_open(true)
+            .show(ui, |ui| {
+                if entries.is_empty() {
+                    ui.weak(if staged { "Nothing staged." } else { "No changes." });
+                    return;
+                }
+                let paths: Vec<String> = entries.iter().map(|e| e.path.clone()).collect();
+                let has_head = snapshot.head.is_some();
+                let all_label = if staged { "− Unstage All" } else { "+ Stage All" };
+                if ui.small_button(all_label).clicked() {
+                    self.repo.run(move |root| if staged { repo::unstage(root, &paths, has_head) } else { repo::stage(root, &paths) }.map(|_| None));
+                }
+
+                for entry in entries {
+                    let status = if staged { entry.staged() } else { entry.unstaged() }.unwrap_or(VcsStatus::Modified);
+                    let (dir, name) = match entry.path.rsplit_once('/') {
+                        Some((dir, name)) => (dir, name),
+                        None => ("", entry.path.as_str()),
+                    };
+                    let response = ui.horizontal(|ui| {
+                        ui.label(RichText::new(status.letter()).monospace().color(status.color(&palette)));
+                        let response = ui.add(egui::Label::new(RichText::new(name).color(status.color(&palette))).sense(egui::Sense::click()));
+                        if !dir.is_empty() {
+                            ui.weak(dir);
+                        }
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            let path = vec![entry.path.clone()];
+                            if staged {
+                                if ui.small_button("−").on_hover_text("Unstage").clicked() {
+                                    self.repo.run(move |root| repo::unstage(root, &path, has_head).map(|_| None));
+                                }
+                            } else if ui.small_button("+").on_hover_text("Stage").clicked() {
+                                self.repo.run(move |root| repo::stage(root, &path).map(|_| None));
+                            }
+                        });
+                        response
+                    }).inner;
+                    let response = response.on_hover_text(&entry.path);
+                    if response.clicked() {
+                        open_diff(snapshot, entry, staged, control);
+                    }
+                    response.context_menu(|ui| {
+                        if ui.button("Open Changes").clicked() {
+                            open_diff(snapshot, entry, staged, control);
+                            ui.close_menu();
+                        }
+                        if status != VcsStatus::Deleted && ui.button("Open File").clicked() {
+                            control.push(AppCommand::OpenFile(snapshot.root.join(&entry.path)));
+                            ui.close_menu();
+                        }
+                    });
+                }
+            });
+    }
+}
+
+impl TabInstance for GitTab {
+    fn title(&self) -> WidgetText {
+        "Source Control".into()
+    }
+
+    fn icon(&self) -> Option<crate::TabIcon> {
+        Some(crate::TabIcon::Emoji("🌿".into()))
+    }
+
+    fn ui(&mut self, ui: &mut Ui, control: &mut Vec<AppCommand>) {
+        let (snapshot, error, refreshing) = {
+            let state = self.repo.state.read();
+            (state.snapshot.clone(), state.error.clone(), state.refreshing)
+        };
+
+        ui.horizontal(|ui| {
+            if let Some(snapshot) = &snapshot {
+                ui.label(RichText::new(format!("🌿 {}", snapshot.branch)).strong());
+            }
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                if ui.add_enabled(!refreshing, egui::Button::new("⟳")).on_hover_text("Refresh").clicked() {
+                    self.repo.refresh();
+                }
+                if refreshing {
+                    ui.spinner();
+                }
+            });
+        });
+        ui.separator();
+
+        let Some(snapshot) = snapshot else {
+            match error {
+                Some(error) => ui.label(error),
+                None => ui.weak("Reading repository status…"),
+            };
+            return;
+        };
+
+        // 提交信息与提交按钮
+        let staged_count = snapshot.entries.iter().filter(|e| e.staged().is_some()).count();
+        let response = ui.add(
+            egui::TextEdit::multiline(&mut self.message)
+                .hint_text("Message (Ctrl + Enter to commit)")
+                .desired_rows(3)
+                .desired_width(f32::INFINITY),
+        );
+        let can_commit = staged_count > 0 && !self.message.trim().is_empty();
+        let shortcut = response.has_focus() && ui.input_mut(|i| i.consume_key(egui::Modifiers::COMMAND, egui::Key::Enter));
+        let button = ui.add_enabled(can_commit, egui::Button::new(format!("✔ Commit ({})", staged_count)).min_size(egui::vec2(ui.available_width(), 0.0)));
+        if can_commit && (button.clicked() || shortcut) {
+            self.commit();
+        }
+        ui.add_space(4.0);
+
+        egui::ScrollArea::vertical().auto_shrink([false, false]).show(ui, |ui| {
+            self.entries_ui(ui, &snapshot, true, control);
+            self.entries_ui(ui, &snapshot, false, control);
+        });
+    }
+
+    fn box_clone(&self) -> Box<dyn TabInstance> {
+        Box::new(self.clone())
+    }
+}
+
+// ----------------------------------------------------------------------------
+// Plugin
+// ----------------------------------------------------------------------------
+
+#[derive(Default)]
+pub struct GitPlugin {
+    repo: Option<GitRepo>,
+}
+
+impl Plugin for GitPlugin {
+    fn name(&self) -> &str { crate::plugins::PLUGIN_NAME_GIT }
+
+    fn dependencies(&self) -> Vec<String> {
+        vec!["core".to_string()]
+    }
+
+    fn on_tab_menu(&mut self, ui: &mut Ui, control: &mut Vec<AppCommand>) {
+        if ui.button("Source Control").clicked() {
+            if let Some(repo) = &self.repo {
+                repo.refresh();
+                control.push(AppCommand::OpenTab(Tab::new(Box::new(GitTab::new(repo.clone())))));
+            }
+            ui.close_menu();
+        }
+    }
+
+    fn on_settings_ui(&mut self, ui: &mut Ui) {
+        ui.label("Git Settings");
+        ui.label("• Uses the git executable on PATH for the repository containing the working directory.");
+        ui.label("• Tab > Source Control lists changes; click a file to view its diff, + / − to stage or unstage.");
+        ui.label("• Modified lines are marked in the editor gutter and changed files are colored in the file explorer.");
+    }
+
+    fn on_global_ui(&mut self, ctx: &egui::Context, control: &mut Vec<AppCommand>) {
+        let repo = self.repo.get_or_insert_with(|| {
+            let repo = GitRepo::new(ctx);
+            crate::register_vcs_provider(ctx, Arc::new(repo.clone()));
+            repo
+        });
+        let profile = PowerProfile::get(ctx);
+        if !profile.watchers_paused {
+            let interval = profile.watcher_interval.as_secs_f64().max(MIN_REFRESH_SECS);
+            repo.refresh_if_due(ctx.input(|i| i.time), interval);
+        }
+        control.extend(repo.take_pending());
+    }
+}
+
+pub fn create() -> GitPlugin {
+    GitPlugin::default()
+}
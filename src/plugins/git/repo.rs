@@ -0,0 +1,187 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use crate::VcsStatus;
+
+// ----------------------------------------------------------------------------
+// git 命令行封装：所有操作都通过调用系统中的 git 完成
+// ----------------------------------------------------------------------------
+
+fn command(dir: &Path, args: &[&str]) -> Command {
+    let mut command = Command::new("git");
+    command.current_dir(dir).args(args).stdin(Stdio::null());
+    // 不让每次调用都弹出控制台窗口
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        command.creation_flags(0x0800_0000); // CREATE_NO_WINDOW
+    }
+    command
+}
+
+/// 执行 git 命令并返回标准输出；失败时返回标准错误的内容
+fn run(dir: &Path, args: &[&str]) -> Result<Vec<u8>, String> {
+    let output = command(dir, args).output().map_err(|e| format!("Failed to run git: {}", e))?;
+    if output.status.success() {
+        Ok(output.stdout)
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
+
+fn run_text(dir: &Path, args: &[&str]) -> Result<String, String> {
+    run(dir, args).map(|out| String::from_utf8_lossy(&out).trim_end().to_string())
+}
+
+/// 包含 `dir` 的仓库根目录
+pub fn find_root(dir: &Path) -> Option<PathBuf> {
+    run_text(dir, &["rev-parse", "--show-toplevel"]).ok().map(PathBuf::from)
+}
+
+/// `git status` 中的一项，路径相对仓库根目录并使用 `/` 分隔
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileEntry {
+    pub path: String,
+    /// 重命名前的路径
+    pub original: Option<String>,
+    /// 暂存区状态字母（X）
+    pub index: char,
+    /// 工作区状态字母（Y）
+    pub worktree: char,
+}
+
+fn status_of(code: char) -> Option<VcsStatus> {
+    match code {
+        'M' | 'T' => Some(VcsStatus::Modified),
+        'A' => Some(VcsStatus::Added),
+        'D' => Some(VcsStatus::Deleted),
+        'R' | 'C' => Some(VcsStatus::Renamed),
+        '?' => Some(VcsStatus::Untracked),
+        'U' => Some(VcsStatus::Conflicted),
+        _ => None,
+    }
+}
+
+impl FileEntry {
+    pub fn is_conflicted(&self) -> bool {
+        self.index == 'U' || self.worktree == 'U' || matches!((self.index, self.worktree), ('A', 'A') | ('D', 'D'))
+    }
+
+    pub fn is_untracked(&self) -> bool {
+        self.index == '?'
+    }
+
+    /// 已暂存的变更
+    pub fn staged(&self) -> Option<VcsStatus> {
+        if self.is_conflicted() || self.is_untracked() {
+            return None;
+        }
+        status_of(self.index)
+    }
+
+    /// 尚未暂存的变更（包括未跟踪的文件）
+    pub fn unstaged(&self) -> Option<VcsStatus> {
+        if self.is_conflicted() {
+            return Some(VcsStatus::Conflicted);
+        }
+        if self.is_untracked() {
+            return Some(VcsStatus::Untracked);
+        }
+        status_of(self.worktree)
+    }
+
+    /// 变更都已暂存，工作区中没有其它修改
+    pub fn is_fully_staged(&self) -> bool {
+        self.unstaged().is_none() && self.staged().is_some()
+    }
+
+    /// 文件树中显示的综合状态：工作区的变更优先
+    pub fn status(&self) -> VcsStatus {
+        self.unstaged().or_else(|| self.staged()).unwrap_or(VcsStatus::Modified)
+    }
+}
+
+/// 解析 `git status --porcelain=v1 -z` 的输出
+fn parse_status(output: &[u8]) -> Vec<FileEntry> {
+    let mut fields = output.split(|b| *b == 0).filter(|f| !f.is_empty());
+    let mut entries = Vec::new();
+    while let Some(field) = fields.next() {
+        if field.len() < 4 {
+            continue;
+        }
+        let index = field[0] as char;
+        let worktree = field[1] as char;
+        let path = String::from_utf8_lossy(&field[3..]).to_string();
+        // 重命名与复制在新路径后紧跟原路径
+        let original = matches!(index, 'R' | 'C')
+            .then(|| fields.next().map(|f| String::from_utf8_lossy(f).to_string()))
+            .flatten();
+        entries.push(FileEntry { path, original, index, worktree });
+    }
+    entries
+}
+
+/// 一次状态读取的结果
+#[derive(Debug, Clone, Default)]
+pub struct Snapshot {
+    pub root: PathBuf,
+    pub branch: String,
+    /// 当前提交；尚无提交的新仓库为 None
+    pub head: Option<String>,
+    pub entries: Vec<FileEntry>,
+    /// 被忽略的未跟踪文件，路径相对仓库根目录；整个目录被忽略时只列出目录（以 `/` 结尾）
+    pub ignored: Vec<String>,
+}
+
+pub fn snapshot(root: &Path) -> Result<Snapshot, String> {
+    let entries = parse_status(&run(root, &["status", "--porcelain=v1", "-z", "--untracked-files=all"])?);
+    let head = run_text(root, &["rev-parse", "--verify", "-q", "HEAD"]).ok();
+    let branch = match run_text(root, &["branch", "--show-current"]) {
+        Ok(branch) if !branch.is_empty() => branch,
+        // 分离 HEAD 时显示提交的短哈希
+        _ => head.as_deref().map(|h| format!("({})", &h[..h.len().min(8)])).unwrap_or_default(),
+    };
+    let ignored = run(root, &["ls-files", "--others", "--ignored", "--exclude-standard", "--directory", "-z"])
+        .map(|out| out.split(|b| *b == 0).filter(|f| !f.is_empty()).map(|f| String::from_utf8_lossy(f).to_string()).collect())
+        .unwrap_or_default();
+    Ok(Snapshot { root: root.to_path_buf(), branch, head, entries, ignored })
+}
+
+pub fn stage(root: &Path, paths: &[String]) -> Result<(), String> {
+    let mut args = vec!["add", "-A", "--"];
+    args.extend(paths.iter().map(String::as_str));
+    run(root, &args).map(|_| ())
+}
+
+pub fn unstage(root: &Path, paths: &[String], has_head: bool) -> Result<(), String> {
+    // 新仓库还没有 HEAD，只能把文件从暂存区移除
+    let mut args = if has_head { vec!["reset", "-q", "HEAD", "--"] } else { vec!["rm", "--cached", "-q", "-r", "--"] };
+    args.extend(paths.iter().map(String::as_str));
+    run(root, &args).map(|_| ())
+}
+
+/// 提交已暂存的变更，返回 git 输出的第一行（分支与提交摘要）
+pub fn commit(root: &Path, message: &str) -> Result<String, String> {
+    let mut child = command(root, &["commit", "-F", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run git: {}", e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(message.as_bytes()).map_err(|e| e.to_string())?;
+    }
+    let output = child.wait_with_output().map_err(|e| e.to_string())?;
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).lines().next().unwrap_or_default().to_string())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        // "nothing to commit" 之类的提示写在标准输出中
+        Err(if stderr.is_empty() { String::from_utf8_lossy(&output.stdout).trim().to_string() } else { stderr })
+    }
+}
+
+/// 读取某个版本中的文件内容：`HEAD:path` 为最近一次提交，`:path` 为暂存区
+pub fn show(root: &Path, spec: &str) -> Option<String> {
+    run(root, &["show", spec]).ok().map(|out| String::from_utf8_lossy(&out).replace("\r\n", "\n"))
+}
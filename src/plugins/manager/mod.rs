@@ -7,7 +7,7 @@ use std::process::{Command, Stdio};
 use std::io::{BufRead, BufReader};
 use serde::{Deserialize, Serialize};
 use toml_edit::{DocumentMut, value};
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashSet};
 
 // --- 数据模型 (严格对照独立启动器) ---
 
@@ -57,6 +57,73 @@ struct PluginInfo {
     dependencies: Vec<String>,
 }
 
+/// 首次使用时获取 Verbium 源码的方式
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum BootstrapSource {
+    /// 使用 git 克隆仓库
+    GitClone,
+    /// 解压模板压缩包（默认查找启动器旁的 verbium-template.zip）
+    Template,
+}
+
+const DEFAULT_REPO_URL: &str = "https://github.com/CuteZaiyuan2333/Verbium.git";
+const BUNDLED_TEMPLATE: &str = "verbium-template.zip";
+
+#[derive(Debug, Clone)]
+struct BootstrapForm {
+    source: BootstrapSource,
+    repo_url: String,
+    template_path: Option<PathBuf>,
+    parent_dir: Option<PathBuf>,
+    folder_name: String,
+}
+
+impl Default for BootstrapForm {
+    fn default() -> Self {
+        let bundled = std::env::current_exe().ok()
+            .and_then(|exe| exe.parent().map(|d| d.join(BUNDLED_TEMPLATE)))
+            .filter(|p| p.exists());
+        Self {
+            source: BootstrapSource::GitClone,
+            repo_url: DEFAULT_REPO_URL.to_string(),
+            template_path: bundled,
+            parent_dir: None,
+            folder_name: "Verbium".to_string(),
+        }
+    }
+}
+
+/// 可单独清理的构建产物类别
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum CleanScope {
+    All,
+    DebugOnly,
+    ReleaseOnly,
+    /// 仅删除各 profile 下的 incremental 增量编译缓存
+    Incremental,
+}
+
+/// 最近构建出的可执行文件
+#[derive(Debug, Clone)]
+struct BuiltBinary {
+    path: PathBuf,
+    size: u64,
+    modified: Option<std::time::SystemTime>,
+}
+
+/// target/ 目录的占用统计
+#[derive(Debug, Clone, Default)]
+struct ArtifactReport {
+    total: u64,
+    debug: u64,
+    release: u64,
+    incremental: u64,
+    binaries: Vec<BuiltBinary>,
+}
+
+/// 列表中最多显示的可执行文件数量
+const MAX_LISTED_BINARIES: usize = 5;
+
 #[derive(Debug, Clone)]
 struct PluginEntry {
     id: String,
@@ -72,6 +139,18 @@ pub struct LauncherTab {
     plugins: Arc<Mutex<Vec<PluginEntry>>>,
     logs: Arc<Mutex<String>>,
     is_running: Arc<Mutex<bool>>,
+    /// 后台任务结束后置位，用于让标签页请求注意
+    finished: Arc<Mutex<bool>>,
+    show_bootstrap: bool,
+    bootstrap: BootstrapForm,
+    /// 克隆 / 解压成功后的项目目录，由 UI 线程取走并设为当前项目
+    bootstrapped: Arc<Mutex<Option<PathBuf>>>,
+    /// 后台扫描得到的 target/ 统计，None 表示尚未扫描完成
+    artifacts: Arc<Mutex<Option<ArtifactReport>>>,
+    /// 需要重新扫描 target/（切换项目或构建 / 清理结束后）
+    artifacts_stale: bool,
+    /// 上一帧是否有任务在运行，用于检测任务结束
+    was_running: bool,
 }
 
 impl LauncherTab {
@@ -82,6 +161,13 @@ impl LauncherTab {
             plugins: Arc::new(Mutex::new(Vec::new())),
             logs: Arc::new(Mutex::new(String::new())),
             is_running: Arc::new(Mutex::new(false)),
+            finished: Arc::new(Mutex::new(false)),
+            show_bootstrap: false,
+            bootstrap: BootstrapForm::default(),
+            bootstrapped: Arc::new(Mutex::new(None)),
+            artifacts: Arc::new(Mutex::new(None)),
+            artifacts_stale: true,
+            was_running: false,
         };
         s.refresh_plugins();
         s
@@ -175,11 +261,29 @@ impl LauncherTab {
         }
 
         // 2. 同步并去重外部依赖
+        let begin_dep = "# --- BEGIN PLUGIN DEPENDENCIES ---";
+        let end_dep = "# --- END PLUGIN DEPENDENCIES ---";
+
+        // 宿主自身（标记区块之外）已声明的依赖不再重复写入，避免 Cargo.toml 出现重复键
+        let host_deps: HashSet<String> = match (content.find(begin_dep), content.find(end_dep)) {
+            (Some(start_idx), Some(end_idx)) if start_idx < end_idx => {
+                let mut host_only = content.clone();
+                host_only.replace_range(start_idx..end_idx, "");
+                host_only.parse::<DocumentMut>().ok()
+                    .and_then(|d| d.get("dependencies").and_then(|v| v.as_table()).map(|t| {
+                        t.iter().map(|(k, _)| k.to_string()).collect()
+                    }))
+                    .unwrap_or_default()
+            }
+            _ => HashSet::new(),
+        };
+
         let mut merged_deps: BTreeMap<String, (toml::Value, Vec<String>)> = BTreeMap::new();
         for plugin in plugins.iter() {
             if plugin.enabled {
                 if let Some(deps) = &plugin.meta.external_dependencies {
                     for (name, val) in deps {
+                        if host_deps.contains(name) { continue; }
                         let entry = merged_deps.entry(name.clone()).or_insert_with(|| (val.clone(), Vec::new()));
                         entry.1.push(plugin.id.clone());
                     }
@@ -194,8 +298,6 @@ impl LauncherTab {
         }
 
         let mut final_content = doc.to_string();
-        let begin_dep = "# --- BEGIN PLUGIN DEPENDENCIES ---";
-        let end_dep = "# --- END PLUGIN DEPENDENCIES ---";
 
         if let (Some(start_idx), Some(end_idx)) = (final_content.find(begin_dep), final_content.find(end_dep)) {
             final_content.replace_range((start_idx + begin_dep.len())..end_idx, &dep_string);
@@ -206,27 +308,43 @@ impl LauncherTab {
     }
 
     fn run_cargo_command(&self, args: Vec<String>) {
-        if *self.is_running.lock().unwrap() { return; }
-        
         let Some(main_dir) = self.config.project_dir.clone() else { return; };
+        self.run_process("cargo", args, main_dir, None);
+    }
+
+    /// 在后台运行外部命令并将输出写入控制台；成功退出时把 `on_success` 记为新的项目目录
+    fn run_process(&self, program: &str, args: Vec<String>, cwd: PathBuf, on_success: Option<PathBuf>) {
+        if *self.is_running.lock().unwrap() { return; }
+
+        let program = program.to_string();
         let logs = self.logs.clone();
         let is_running = self.is_running.clone();
+        let finished = self.finished.clone();
+        let bootstrapped = self.bootstrapped.clone();
 
         *is_running.lock().unwrap() = true;
         {
             let mut l = logs.lock().unwrap();
             l.clear();
-            l.push_str(&format!("Executing: cargo {}\n", args.join(" ")));
+            l.push_str(&format!("Executing: {} {}\n", program, args.join(" ")));
         }
 
         std::thread::spawn(move || {
-            let mut child = Command::new("cargo")
+            let spawned = Command::new(&program)
                 .args(&args)
-                .current_dir(&main_dir)
+                .current_dir(&cwd)
                 .stdout(Stdio::piped())
                 .stderr(Stdio::piped())
-                .spawn()
-                .expect("Failed to start cargo");
+                .spawn();
+            let mut child = match spawned {
+                Ok(child) => child,
+                Err(e) => {
+                    logs.lock().unwrap().push_str(&format!("Failed to start {}: {}\n", program, e));
+                    *is_running.lock().unwrap() = false;
+                    *finished.lock().unwrap() = true;
+                    return;
+                }
+            };
 
             let stdout = child.stdout.take().unwrap();
             let stderr = child.stderr.take().unwrap();
@@ -248,20 +366,247 @@ impl LauncherTab {
             });
 
             let status = child.wait();
-            *is_running.lock().unwrap() = false;
-            
-            if let Ok(s) = status {
+
+            if let Ok(s) = &status {
                 let mut l = logs.lock().unwrap();
                 l.push_str(&format!("\nProcess finished with exit code: {:?}\n", s.code()));
+                if s.success() {
+                    *bootstrapped.lock().unwrap() = on_success;
+                }
             }
+            *is_running.lock().unwrap() = false;
+            *finished.lock().unwrap() = true;
         });
     }
 
-    fn start_build_process(&self) {
+    /// 在后台统计 target/ 的大小，避免大目录阻塞 UI
+    fn scan_artifacts(&mut self) {
+        self.artifacts_stale = false;
+        let Some(main_dir) = self.config.project_dir.clone() else { return; };
+        let store = self.artifacts.clone();
+        *store.lock().unwrap() = None;
+        std::thread::spawn(move || {
+            let report = scan_target_dir(&main_dir.join("target"));
+            *store.lock().unwrap() = Some(report);
+        });
+    }
+
+    fn clean_artifacts(&self, scope: CleanScope) {
+        match scope {
+            CleanScope::All => self.run_cargo_command(vec!["clean".to_string()]),
+            CleanScope::DebugOnly => self.run_cargo_command(vec!["clean".to_string(), "--profile".to_string(), "dev".to_string()]),
+            CleanScope::ReleaseOnly => self.run_cargo_command(vec!["clean".to_string(), "--release".to_string()]),
+            CleanScope::Incremental => {
+                // cargo clean 没有只清理增量缓存的选项，直接删除 incremental 目录
+                let Some(main_dir) = self.config.project_dir.clone() else { return; };
+                if *self.is_running.lock().unwrap() { return; }
+                *self.is_running.lock().unwrap() = true;
+                self.logs.lock().unwrap().clear();
+
+                let logs = self.logs.clone();
+                let is_running = self.is_running.clone();
+                let finished = self.finished.clone();
+                std::thread::spawn(move || {
+                    for profile in ["debug", "release"] {
+                        let dir = main_dir.join("target").join(profile).join("incremental");
+                        if !dir.exists() { continue; }
+                        let size = dir_size(&dir);
+                        let line = match fs::remove_dir_all(&dir) {
+                            Ok(()) => format!("Removed {} ({})\n", dir.display(), format_size(size)),
+                            Err(e) => format!("Failed to remove {}: {}\n", dir.display(), e),
+                        };
+                        logs.lock().unwrap().push_str(&line);
+                    }
+                    logs.lock().unwrap().push_str("Incremental cache cleaned.\n");
+                    *is_running.lock().unwrap() = false;
+                    *finished.lock().unwrap() = true;
+                });
+            }
+        }
+    }
+
+    fn artifacts_ui(&mut self, ui: &mut Ui, running: bool) {
+        let report = self.artifacts.lock().unwrap().clone();
+        ui.horizontal(|ui| {
+            ui.label("Target:");
+            match &report {
+                Some(r) => {
+                    ui.monospace(format_size(r.total));
+                    ui.weak(format!(
+                        "(debug {}, release {}, incremental {})",
+                        format_size(r.debug),
+                        format_size(r.release),
+                        format_size(r.incremental)
+                    ));
+                }
+                None => { ui.spinner(); }
+            }
+            if ui.small_button("🔄").on_hover_text("Rescan target directory").clicked() {
+                self.artifacts_stale = true;
+            }
+
+            ui.add_enabled_ui(!running, |ui| {
+                ui.menu_button("🧹 Clean", |ui| {
+                    let mut scope = None;
+                    if ui.button("Debug only").clicked() { scope = Some(CleanScope::DebugOnly); }
+                    if ui.button("Release only").clicked() { scope = Some(CleanScope::ReleaseOnly); }
+                    if ui.button("Incremental cache").clicked() { scope = Some(CleanScope::Incremental); }
+                    ui.separator();
+                    if ui.button("Everything (cargo clean)").clicked() { scope = Some(CleanScope::All); }
+                    if let Some(scope) = scope {
+                        self.clean_artifacts(scope);
+                        ui.close_menu();
+                    }
+                });
+            });
+        });
+
+        if let Some(r) = report.filter(|r| !r.binaries.is_empty()) {
+            ui.collapsing("Recent binaries", |ui| {
+                egui::Grid::new("recent_binaries").num_columns(3).striped(true).show(ui, |ui| {
+                    for bin in &r.binaries {
+                        ui.monospace(bin.path.to_string_lossy()).on_hover_text(bin.path.to_string_lossy());
+                        ui.label(format_size(bin.size));
+                        ui.weak(bin.modified.map(format_age).unwrap_or_default());
+                        ui.end_row();
+                    }
+                });
+            });
+        }
+    }
+
+    /// 目标目录必须不存在或为空
+    fn bootstrap_target(&self) -> anyhow::Result<PathBuf> {
+        let Some(parent) = &self.bootstrap.parent_dir else {
+            return Err(anyhow::anyhow!("Choose a parent directory first."));
+        };
+        let name = self.bootstrap.folder_name.trim();
+        if name.is_empty() {
+            return Err(anyhow::anyhow!("Folder name must not be empty."));
+        }
+        let target = parent.join(name);
+        if target.exists() && fs::read_dir(&target)?.next().is_some() {
+            return Err(anyhow::anyhow!("{} already exists and is not empty.", target.display()));
+        }
+        Ok(target)
+    }
+
+    fn start_bootstrap(&mut self) {
+        let target = match self.bootstrap_target() {
+            Ok(t) => t,
+            Err(e) => {
+                self.logs.lock().unwrap().push_str(&format!("Bootstrap Error: {}\n", e));
+                return;
+            }
+        };
+
+        match self.bootstrap.source {
+            BootstrapSource::GitClone => {
+                let parent = self.bootstrap.parent_dir.clone().unwrap_or_default();
+                let args = vec![
+                    "clone".to_string(),
+                    "--depth".to_string(),
+                    "1".to_string(),
+                    self.bootstrap.repo_url.trim().to_string(),
+                    target.to_string_lossy().to_string(),
+                ];
+                self.run_process("git", args, parent, Some(target));
+            }
+            BootstrapSource::Template => {
+                let Some(template) = self.bootstrap.template_path.clone() else {
+                    self.logs.lock().unwrap().push_str("Bootstrap Error: Choose a template archive first.\n");
+                    return;
+                };
+                if *self.is_running.lock().unwrap() { return; }
+                *self.is_running.lock().unwrap() = true;
+                self.logs.lock().unwrap().push_str(&format!("Unpacking {} to {}\n", template.display(), target.display()));
+
+                let logs = self.logs.clone();
+                let is_running = self.is_running.clone();
+                let finished = self.finished.clone();
+                let bootstrapped = self.bootstrapped.clone();
+                std::thread::spawn(move || {
+                    match unpack_template(&template, &target) {
+                        Ok(project_dir) => {
+                            logs.lock().unwrap().push_str(&format!("Project ready at {}\n", project_dir.display()));
+                            *bootstrapped.lock().unwrap() = Some(project_dir);
+                        }
+                        Err(e) => logs.lock().unwrap().push_str(&format!("Unpack Error: {}\n", e)),
+                    }
+                    *is_running.lock().unwrap() = false;
+                    *finished.lock().unwrap() = true;
+                });
+            }
+        }
+    }
+
+    fn bootstrap_ui(&mut self, ui: &mut Ui, running: bool) {
+        ui.heading("🧬 Get Verbium Source");
+        ui.label("No Verbium project yet? Clone the repository or unpack a template, then it becomes the project directory.");
+        ui.add_space(8.0);
+
+        ui.horizontal(|ui| {
+            ui.radio_value(&mut self.bootstrap.source, BootstrapSource::GitClone, "Git clone");
+            ui.radio_value(&mut self.bootstrap.source, BootstrapSource::Template, "Unpack template");
+        });
+
+        egui::Grid::new("bootstrap_grid").num_columns(2).show(ui, |ui| {
+            match self.bootstrap.source {
+                BootstrapSource::GitClone => {
+                    ui.label("Repository:");
+                    ui.add(egui::TextEdit::singleline(&mut self.bootstrap.repo_url).desired_width(320.0));
+                    ui.end_row();
+                }
+                BootstrapSource::Template => {
+                    ui.label("Template:");
+                    let text = self.bootstrap.template_path.as_ref()
+                        .map(|p| p.to_string_lossy().to_string())
+                        .unwrap_or_else(|| "Select .zip...".into());
+                    if ui.button(egui::RichText::new(text).monospace()).clicked() {
+                        if let Some(path) = rfd::FileDialog::new().add_filter("Template", &["zip"]).pick_file() {
+                            self.bootstrap.template_path = Some(path);
+                        }
+                    }
+                    ui.end_row();
+                }
+            }
+
+            ui.label("Location:");
+            let text = self.bootstrap.parent_dir.as_ref()
+                .map(|p| p.to_string_lossy().to_string())
+                .unwrap_or_else(|| "Select folder...".into());
+            if ui.button(egui::RichText::new(text).monospace()).clicked() {
+                if let Some(path) = rfd::FileDialog::new().pick_folder() {
+                    self.bootstrap.parent_dir = Some(path);
+                }
+            }
+            ui.end_row();
+
+            ui.label("Folder name:");
+            ui.text_edit_singleline(&mut self.bootstrap.folder_name);
+            ui.end_row();
+        });
+
+        ui.add_space(8.0);
+        ui.horizontal(|ui| {
+            ui.add_enabled_ui(!running && self.bootstrap.parent_dir.is_some(), |ui| {
+                if ui.button("🚀 Create Project").clicked() {
+                    self.start_bootstrap();
+                }
+            });
+            if self.config.project_dir.is_some() && ui.button("Cancel").clicked() {
+                self.show_bootstrap = false;
+            }
+            if running { ui.spinner(); }
+        });
+    }
+
+    /// 同步 Cargo.toml 并返回本次构建的 cargo 参数；同步失败时写入控制台并返回 None
+    fn prepare_build(&self) -> Option<Vec<String>> {
         if let Err(e) = self.sync_cargo_toml() {
             let mut l = self.logs.lock().unwrap();
             l.push_str(&format!("Error syncing Cargo.toml: {}\n", e));
-            return;
+            return None;
         }
 
         let mut args = if self.config.build_and_run {
@@ -273,8 +618,21 @@ impl LauncherTab {
         if self.config.build_mode == BuildMode::Release {
             args.push("--release".to_string());
         }
+        Some(args)
+    }
 
-        self.run_cargo_command(args);
+    fn start_build_process(&self) {
+        if let Some(args) = self.prepare_build() {
+            self.run_cargo_command(args);
+        }
+    }
+
+    /// 交给终端插件执行构建，便于与构建出的程序交互
+    fn start_build_in_terminal(&self, control: &mut Vec<AppCommand>) {
+        let Some(cwd) = self.config.project_dir.clone() else { return };
+        if let Some(args) = self.prepare_build() {
+            control.push(AppCommand::RunInTerminal { cwd, program: "cargo".to_string(), args });
+        }
     }
 
     fn import_plugin(&mut self, path: PathBuf) -> anyhow::Result<()> {
@@ -311,10 +669,127 @@ impl LauncherTab {
     }
 }
 
+fn dir_size(dir: &Path) -> u64 {
+    walkdir::WalkDir::new(dir)
+        .into_iter()
+        .flatten()
+        .filter_map(|e| e.metadata().ok())
+        .filter(|m| m.is_file())
+        .map(|m| m.len())
+        .sum()
+}
+
+fn scan_target_dir(target: &Path) -> ArtifactReport {
+    let mut report = ArtifactReport {
+        total: dir_size(target),
+        ..Default::default()
+    };
+
+    for profile in ["debug", "release"] {
+        let profile_dir = target.join(profile);
+        let size = dir_size(&profile_dir);
+        match profile {
+            "debug" => report.debug = size,
+            _ => report.release = size,
+        }
+        report.incremental += dir_size(&profile_dir.join("incremental"));
+
+        // profile 目录顶层的可执行文件即为最终产物（Windows 下为 .exe，其它平台无扩展名）
+        let Ok(entries) = fs::read_dir(&profile_dir) else { continue };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Ok(meta) = entry.metadata() else { continue };
+            let is_binary = if cfg!(windows) {
+                path.extension().is_some_and(|e| e == "exe")
+            } else {
+                path.extension().is_none() && !entry.file_name().to_string_lossy().starts_with('.')
+            };
+            if meta.is_file() && is_binary {
+                report.binaries.push(BuiltBinary {
+                    path: path.strip_prefix(target).map(Path::to_path_buf).unwrap_or(path),
+                    size: meta.len(),
+                    modified: meta.modified().ok(),
+                });
+            }
+        }
+    }
+
+    report.binaries.sort_by(|a, b| b.modified.cmp(&a.modified));
+    report.binaries.truncate(MAX_LISTED_BINARIES);
+    report
+}
+
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 { format!("{} B", bytes) } else { format!("{:.1} {}", size, UNITS[unit]) }
+}
+
+fn format_age(time: std::time::SystemTime) -> String {
+    let secs = time.elapsed().map(|d| d.as_secs()).unwrap_or(0);
+    match secs {
+        0..=59 => "just now".to_string(),
+        60..=3599 => format!("{} min ago", secs / 60),
+        3600..=86399 => format!("{} h ago", secs / 3600),
+        _ => format!("{} d ago", secs / 86400),
+    }
+}
+
+/// 解压模板，返回包含 Cargo.toml 的项目根目录（兼容压缩包内多一层顶级目录的情况）
+fn unpack_template(template: &Path, target: &Path) -> anyhow::Result<PathBuf> {
+    let file = fs::File::open(template)?;
+    let mut archive = zip::ZipArchive::new(file)?;
+    fs::create_dir_all(target)?;
+    archive.extract(target)?;
+
+    if target.join("Cargo.toml").exists() {
+        return Ok(target.to_path_buf());
+    }
+    let entries: Vec<PathBuf> = fs::read_dir(target)?.flatten().map(|e| e.path()).collect();
+    if let [only] = entries.as_slice() {
+        if only.join("Cargo.toml").exists() {
+            return Ok(only.clone());
+        }
+    }
+    Err(anyhow::anyhow!("Template does not contain a Cargo.toml"))
+}
+
 impl TabInstance for LauncherTab {
     fn title(&self) -> WidgetText { "Verbium Launcher".into() }
 
-    fn ui(&mut self, ui: &mut Ui, _control: &mut Vec<AppCommand>) {
+    fn icon(&self) -> Option<crate::TabIcon> { Some(crate::TabIcon::Emoji("🚀".into())) }
+
+    fn ui(&mut self, ui: &mut Ui, control: &mut Vec<AppCommand>) {
+        // 克隆 / 解压完成后自动设为项目目录
+        let bootstrapped = self.bootstrapped.lock().unwrap().take();
+        if let Some(dir) = bootstrapped {
+            self.config.project_dir = Some(dir);
+            let _ = self.save_config();
+            self.refresh_plugins();
+            self.show_bootstrap = false;
+            self.artifacts_stale = true;
+        }
+
+        // 构建 / 清理结束后重新统计产物大小
+        let running = *self.is_running.lock().unwrap();
+        if self.was_running && !running {
+            self.artifacts_stale = true;
+        }
+        self.was_running = running;
+        if self.artifacts_stale {
+            self.scan_artifacts();
+        }
+        // 只在任务运行或扫描进行中时定时刷新；未选择项目时不会有扫描
+        let scanning = self.config.project_dir.is_some() && self.artifacts.lock().unwrap().is_none();
+        if running || scanning {
+            ui.ctx().request_repaint_after(std::time::Duration::from_millis(200));
+        }
+
         egui::SidePanel::right("launcher_console")
             .resizable(true)
             .default_width(320.0)
@@ -358,11 +833,15 @@ impl TabInstance for LauncherTab {
                                 self.config.project_dir = Some(path);
                                 let _ = self.save_config();
                                 self.refresh_plugins();
+                                self.artifacts_stale = true;
                             }
                         }
                                                 if ui.button("🔄").clicked() { 
                                                     self.refresh_plugins(); 
                                                 }
+                                                if ui.button("🧬 New...").on_hover_text("Clone or unpack a new Verbium project").clicked() {
+                                                    self.show_bootstrap = true;
+                                                }
                                             });
                         
                                             ui.add_space(4.0);
@@ -390,13 +869,18 @@ impl TabInstance for LauncherTab {
                             if ui.button(btn_text).clicked() {
                                 self.start_build_process();
                             }
-                            if ui.button("Clean").clicked() {
-                                self.run_cargo_command(vec!["clean".to_string()]);
+                            if ui.button("⌨ In Terminal").on_hover_text("Run the same cargo command in a terminal tab").clicked() {
+                                self.start_build_in_terminal(control);
                             }
                         });
                         if running { ui.spinner(); }
                     });
 
+                    if self.config.project_dir.is_some() {
+                        ui.add_space(4.0);
+                        self.artifacts_ui(ui, running);
+                    }
+
                     ui.separator();
 
                     ui.horizontal(|ui| {
@@ -424,6 +908,15 @@ impl TabInstance for LauncherTab {
             });
 
         egui::CentralPanel::default().show_inside(ui, |ui| {
+            if self.show_bootstrap || self.config.project_dir.is_none() {
+                let running = *self.is_running.lock().unwrap();
+                self.bootstrap_ui(ui, running);
+                if running {
+                    ui.ctx().request_repaint_after(std::time::Duration::from_millis(200));
+                }
+                return;
+            }
+
             ui.vertical(|ui| {
                 ui.horizontal(|ui| {
                     ui.heading("Plugins");
@@ -469,6 +962,10 @@ impl TabInstance for LauncherTab {
         });
     }
 
+    fn needs_attention(&mut self) -> bool {
+        std::mem::take(&mut *self.finished.lock().unwrap())
+    }
+
     fn box_clone(&self) -> Box<dyn TabInstance> { Box::new(self.clone()) }
 }
 
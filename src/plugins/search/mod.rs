@@ -0,0 +1,436 @@
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use egui::{Ui, WidgetText, RichText};
+use crate::{Plugin, AppCommand, TabInstance, Tab};
+use serde_json::Value;
+
+/// 单个文件超过此大小时跳过
+const MAX_FILE_SIZE: u64 = 4 * 1024 * 1024;
+/// 结果总数上限，防止过于宽泛的查询占满内存
+const MAX_RESULTS: usize = 5000;
+/// Agent 搜索工具返回的匹配行数上限
+const MAX_TOOL_MATCHES: usize = 200;
+/// 搜索时跳过的目录
+const IGNORED_DIRS: &[&str] = &[".git", "target", "node_modules", ".svn", ".hg"];
+
+// ----------------------------------------------------------------------------
+// 搜索任务
+// ----------------------------------------------------------------------------
+
+#[derive(Debug, Clone)]
+struct LineMatch {
+    /// 行号（从 1 开始）
+    line: usize,
+    /// 列号（从 1 开始，按字符计）
+    column: usize,
+    text: String,
+    /// 匹配部分在 text 中的字节范围
+    range: std::ops::Range<usize>,
+}
+
+#[derive(Debug, Clone)]
+struct FileMatches {
+    path: PathBuf,
+    matches: Vec<LineMatch>,
+}
+
+/// 一次后台搜索：遍历线程产出文件路径，工作线程池读取并匹配
+#[derive(Clone)]
+struct SearchJob {
+    results: Arc<Mutex<Vec<FileMatches>>>,
+    cancel: Arc<AtomicBool>,
+    finished: Arc<AtomicBool>,
+    files_scanned: Arc<AtomicUsize>,
+    match_count: Arc<AtomicUsize>,
+}
+
+impl SearchJob {
+    fn start(root: PathBuf, query: String, case_sensitive: bool) -> Self {
+        let job = Self {
+            results: Arc::new(Mutex::new(Vec::new())),
+            cancel: Arc::new(AtomicBool::new(false)),
+            finished: Arc::new(AtomicBool::new(false)),
+            files_scanned: Arc::new(AtomicUsize::new(0)),
+            match_count: Arc::new(AtomicUsize::new(0)),
+        };
+
+        let worker_job = job.clone();
+        std::thread::spawn(move || {
+            let job = worker_job;
+            let needle = if case_sensitive { query } else { query.to_lowercase() };
+            let (tx, rx) = mpsc::channel::<PathBuf>();
+            let rx = Arc::new(Mutex::new(rx));
+
+            let worker_count = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(4).min(8);
+            let workers: Vec<_> = (0..worker_count)
+                .map(|_| {
+                    let rx = rx.clone();
+                    let job = job.clone();
+                    let needle = needle.clone();
+                    std::thread::spawn(move || loop {
+                        let next = rx.lock().ok().and_then(|r| r.recv().ok());
+                        let Some(path) = next else { break };
+                        if job.cancel.load(Ordering::Relaxed) {
+                            continue;
+                        }
+                        if let Some(matches) = search_file(&path, &needle, case_sensitive) {
+                            let added = matches.len();
+                            if job.match_count.fetch_add(added, Ordering::Relaxed) + added >= MAX_RESULTS {
+                                job.cancel.store(true, Ordering::Relaxed);
+                            }
+                            if let Ok(mut results) = job.results.lock() {
+                                results.push(FileMatches { path, matches });
+                            }
+                        }
+                        job.files_scanned.fetch_add(1, Ordering::Relaxed);
+                    })
+                })
+                .collect();
+
+            let walker = walkdir::WalkDir::new(&root).into_iter().filter_entry(|e| {
+                !(e.file_type().is_dir() && e.depth() > 0 && IGNORED_DIRS.contains(&e.file_name().to_string_lossy().as_ref()))
+            });
+            for entry in walker.flatten() {
+                if job.cancel.load(Ordering::Relaxed) {
+                    break;
+                }
+                if entry.file_type().is_file() && tx.send(entry.into_path()).is_err() {
+                    break;
+                }
+            }
+            drop(tx);
+
+            for worker in workers {
+                let _ = worker.join();
+            }
+            if let Ok(mut results) = job.results.lock() {
+                results.sort_by(|a, b| a.path.cmp(&b.path));
+            }
+            job.finished.store(true, Ordering::Relaxed);
+        });
+
+        job
+    }
+
+    fn is_finished(&self) -> bool {
+        self.finished.load(Ordering::Relaxed)
+    }
+}
+
+/// 在单个文件中查找，跳过过大或二进制文件
+fn search_file(path: &Path, needle: &str, case_sensitive: bool) -> Option<Vec<LineMatch>> {
+    if std::fs::metadata(path).ok()?.len() > MAX_FILE_SIZE {
+        return None;
+    }
+    let bytes = std::fs::read(path).ok()?;
+    if bytes.iter().take(8000).any(|b| *b == 0) {
+        return None;
+    }
+    let content = String::from_utf8(bytes).ok()?;
+
+    let mut matches = Vec::new();
+    for (idx, line) in content.lines().enumerate() {
+        let haystack = if case_sensitive { line.to_string() } else { line.to_lowercase() };
+        // 小写转换可能改变字节长度，此时无法映射回原文，退化为不高亮
+        let same_layout = haystack.len() == line.len();
+        let mut start = 0;
+        while let Some(pos) = haystack[start..].find(needle) {
+            let byte_pos = start + pos;
+            let (column, range) = if same_layout {
+                (line[..byte_pos].chars().count() + 1, byte_pos..byte_pos + needle.len())
+            } else {
+                (haystack[..byte_pos].chars().count() + 1, 0..0)
+            };
+            matches.push(LineMatch {
+                line: idx + 1,
+                column,
+                text: line.to_string(),
+                range,
+            });
+            start = byte_pos + needle.len().max(1);
+            if start >= haystack.len() {
+                break;
+            }
+        }
+    }
+    if matches.is_empty() { None } else { Some(matches) }
+}
+
+// ----------------------------------------------------------------------------
+// Tab Instance
+// ----------------------------------------------------------------------------
+
+#[derive(Clone)]
+pub struct SearchTab {
+    root: PathBuf,
+    query: String,
+    case_sensitive: bool,
+    job: Option<SearchJob>,
+    /// 首次显示时聚焦输入框
+    focus_query: bool,
+}
+
+impl std::fmt::Debug for SearchTab {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SearchTab")
+            .field("root", &self.root)
+            .field("query", &self.query)
+            .finish()
+    }
+}
+
+impl SearchTab {
+    /// 在工作区的主根目录中搜索；未打开工作区时使用当前工作目录
+    fn new(ctx: &egui::Context) -> Self {
+        let root = crate::workspace::current(ctx).primary_root().map(PathBuf::from);
+        Self {
+            root: root.or_else(|| std::env::current_dir().ok()).unwrap_or_else(|| PathBuf::from(".")),
+            query: String::new(),
+            case_sensitive: false,
+            job: None,
+            focus_query: true,
+        }
+    }
+
+    fn run_search(&mut self) {
+        if let Some(job) = &self.job {
+            job.cancel.store(true, Ordering::Relaxed);
+        }
+        self.job = if self.query.is_empty() {
+            None
+        } else {
+            Some(SearchJob::start(self.root.clone(), self.query.clone(), self.case_sensitive))
+        };
+    }
+
+    fn match_label(ui: &Ui, m: &LineMatch) -> egui::text::LayoutJob {
+        let font = egui::FontId::monospace(12.0);
+        let normal = ui.visuals().text_color();
+        let mut job = egui::text::LayoutJob::default();
+        job.append(&format!("{:>5}  ", m.line), 0.0, egui::TextFormat::simple(font.clone(), ui.visuals().weak_text_color()));
+
+        // 去掉行首缩进，并保证高亮范围仍然有效
+        let indent = m.text.len() - m.text.trim_start().len();
+        let text = &m.text[indent..];
+        if m.range.is_empty() || m.range.start < indent {
+            job.append(text, 0.0, egui::TextFormat::simple(font, normal));
+        } else {
+            let (start, end) = (m.range.start - indent, m.range.end - indent);
+            job.append(&text[..start], 0.0, egui::TextFormat::simple(font.clone(), normal));
+            job.append(&text[start..end], 0.0, egui::TextFormat {
+                background: ui.visuals().selection.bg_fill,
+                ..egui::TextFormat::simple(font.clone(), ui.visuals().strong_text_color())
+            });
+            job.append(&text[end..], 0.0, egui::TextFormat::simple(font, normal));
+        }
+        job
+    }
+}
+
+impl TabInstance for SearchTab {
+    fn title(&self) -> WidgetText {
+        if self.query.is_empty() {
+            "Search".into()
+        } else {
+            self.query.clone().into()
+        }
+    }
+
+    fn icon(&self) -> Option<crate::TabIcon> {
+        Some(crate::TabIcon::Emoji("🔍".into()))
+    }
+
+    fn ui(&mut self, ui: &mut Ui, control: &mut Vec<AppCommand>) {
+        ui.horizontal(|ui| {
+            let response = ui.add(
+                egui::TextEdit::singleline(&mut self.query)
+                    .hint_text("Search in files...")
+                    .desired_width(300.0),
+            );
+            if self.focus_query {
+                response.request_focus();
+                self.focus_query = false;
+            }
+            let submitted = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+
+            let case_toggled = ui
+                .selectable_label(self.case_sensitive, "Aa")
+                .on_hover_text("Match case")
+                .clicked();
+            if case_toggled {
+                self.case_sensitive = !self.case_sensitive;
+            }
+
+            if submitted || case_toggled || ui.button("Search").clicked() {
+                self.run_search();
+            }
+
+            if ui.button("📁").on_hover_text("Choose search folder").clicked() {
+                if let Some(dir) = rfd::FileDialog::new().set_directory(&self.root).pick_folder() {
+                    self.root = dir;
+                    self.run_search();
+                }
+            }
+            ui.label(RichText::new(self.root.to_string_lossy()).weak());
+        });
+
+        let Some(job) = self.job.clone() else {
+            ui.separator();
+            ui.label("Type a query and press Enter. (Ctrl+Shift+F opens a new search)");
+            return;
+        };
+
+        let files_scanned = job.files_scanned.load(Ordering::Relaxed);
+        let match_count = job.match_count.load(Ordering::Relaxed).min(MAX_RESULTS);
+        let results = job.results.lock().map(|r| r.clone()).unwrap_or_default();
+
+        ui.horizontal(|ui| {
+            if job.is_finished() {
+                ui.label(format!("{} matches in {} files ({} files scanned)", match_count, results.len(), files_scanned));
+                if match_count >= MAX_RESULTS {
+                    ui.label(RichText::new("Result limit reached").color(ui.visuals().warn_fg_color));
+                }
+            } else {
+                ui.spinner();
+                ui.label(format!("Searching... {} files scanned, {} matches", files_scanned, match_count));
+                if ui.button("Cancel").clicked() {
+                    job.cancel.store(true, Ordering::Relaxed);
+                }
+                ui.ctx().request_repaint_after(std::time::Duration::from_millis(100));
+            }
+        });
+        ui.separator();
+
+        egui::ScrollArea::vertical()
+            .id_salt("search_results_scroll")
+            .auto_shrink([false, false])
+            .show(ui, |ui| {
+                for file in &results {
+                    let display = file.path.strip_prefix(&self.root).unwrap_or(&file.path);
+                    egui::CollapsingHeader::new(format!("📄 {}  ({})", display.to_string_lossy(), file.matches.len()))
+                        .id_salt(&file.path)
+                        .default_open(true)
+                        .show(ui, |ui| {
+                            for m in &file.matches {
+                                let label = egui::Label::new(Self::match_label(ui, m)).sense(egui::Sense::click());
+                                let response = ui.add(label).on_hover_cursor(egui::CursorIcon::PointingHand);
+                                if response.clicked() {
+                                    control.push(AppCommand::OpenFileAt {
+                                        path: file.path.clone(),
+                                        line: m.line,
+                                        column: m.column,
+                                    });
+                                }
+                            }
+                        });
+                }
+            });
+    }
+
+    fn box_clone(&self) -> Box<dyn TabInstance> {
+        Box::new(self.clone())
+    }
+}
+
+// ----------------------------------------------------------------------------
+// Agent 工具
+// ----------------------------------------------------------------------------
+
+/// 供 Agent 调用的全文搜索，在工作区的每个根目录中查找
+struct SearchWorkspaceTool;
+
+impl crate::AgentTool for SearchWorkspaceTool {
+    fn name(&self) -> &str {
+        "search_workspace"
+    }
+
+    fn description(&self) -> &str {
+        "Search all text files in the workspace for a literal string. Returns matching lines as path:line: text."
+    }
+
+    fn parameters(&self) -> &str {
+        r#"{"type":"object","properties":{"query":{"type":"string","description":"Text to search for"},"case_sensitive":{"type":"boolean"}},"required":["query"]}"#
+    }
+
+    fn call(&self, arguments: &str, roots: &[PathBuf], _control: &mut Vec<AppCommand>) -> Result<String, String> {
+        let args: Value = serde_json::from_str(arguments).map_err(|e| format!("Invalid arguments: {}", e))?;
+        let query = args["query"].as_str().filter(|q| !q.is_empty()).ok_or("Missing argument \"query\"")?;
+        let case_sensitive = args["case_sensitive"].as_bool().unwrap_or(false);
+        if roots.is_empty() {
+            return Err("No folder is open in the workspace".into());
+        }
+
+        let mut lines = Vec::new();
+        let mut total = 0;
+        for root in roots {
+            let job = SearchJob::start(root.clone(), query.to_string(), case_sensitive);
+            while !job.is_finished() {
+                std::thread::sleep(std::time::Duration::from_millis(20));
+            }
+            let results = job.results.lock().map(|r| r.clone()).unwrap_or_default();
+            for file in results {
+                let path = file.path.strip_prefix(root).unwrap_or(&file.path).display().to_string();
+                for m in file.matches {
+                    total += 1;
+                    if lines.len() < MAX_TOOL_MATCHES {
+                        lines.push(format!("{}:{}: {}", path, m.line, m.text.trim()));
+                    }
+                }
+            }
+        }
+        if lines.is_empty() {
+            return Ok("No matches".into());
+        }
+        if total > lines.len() {
+            lines.push(format!("... {} more matches not shown", total - lines.len()));
+        }
+        Ok(lines.join("\n"))
+    }
+}
+
+// ----------------------------------------------------------------------------
+// Plugin
+// ----------------------------------------------------------------------------
+
+pub struct SearchPlugin {
+    /// 是否已向 Agent 注册搜索工具
+    tool_registered: bool,
+}
+
+impl Plugin for SearchPlugin {
+    fn name(&self) -> &str { crate::plugins::PLUGIN_NAME_SEARCH }
+
+    fn dependencies(&self) -> Vec<String> {
+        vec!["core".to_string()]
+    }
+
+    fn on_tab_menu(&mut self, ui: &mut Ui, control: &mut Vec<AppCommand>) {
+        if ui.button("Find in Files (Ctrl+Shift+F)").clicked() {
+            control.push(AppCommand::OpenTab(Tab::new(Box::new(SearchTab::new(ui.ctx())))));
+            ui.close_menu();
+        }
+    }
+
+    fn on_settings_ui(&mut self, ui: &mut Ui) {
+        ui.label("Search Settings");
+        ui.label("• Ctrl + Shift + F to search across the workspace (the working directory when no workspace is open).");
+        ui.label(format!("• Skipped folders: {}", IGNORED_DIRS.join(", ")));
+    }
+
+    fn on_global_ui(&mut self, ctx: &egui::Context, control: &mut Vec<AppCommand>) {
+        if !self.tool_registered {
+            self.tool_registered = true;
+            crate::register_agent_tool(ctx, Arc::new(SearchWorkspaceTool));
+        }
+        let shortcut = egui::KeyboardShortcut::new(egui::Modifiers::COMMAND | egui::Modifiers::SHIFT, egui::Key::F);
+        // 终端获得焦点时 Ctrl + Shift + F 用于搜索终端输出
+        if !crate::is_shortcut_claimed(ctx, shortcut) && ctx.input_mut(|i| i.consume_shortcut(&shortcut)) {
+            control.push(AppCommand::OpenTab(Tab::new(Box::new(SearchTab::new(ctx)))));
+        }
+    }
+}
+
+pub fn create() -> SearchPlugin {
+    SearchPlugin { tool_registered: false }
+}
@@ -0,0 +1,199 @@
+use egui::Color32;
+use serde::{Deserialize, Serialize};
+
+// ----------------------------------------------------------------------------
+// 终端配色
+// ----------------------------------------------------------------------------
+
+/// 单元格颜色。保存的是语义（默认色 / 调色板序号 / 真彩色）而不是最终颜色，
+/// 切换配色方案时已有的屏幕内容与历史记录随之更新
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum TermColor {
+    #[default]
+    Default,
+    Indexed(u8),
+    Rgb(Color32),
+}
+
+/// 色立方每个分量的 6 级取值
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ColorScheme {
+    pub background: Color32,
+    pub foreground: Color32,
+    pub cursor: Color32,
+    /// 16 色调色板：0-7 为标准色，8-15 为高亮色
+    pub ansi: [Color32; 16],
+}
+
+impl ColorScheme {
+    /// xterm 256 色：0-15 取自方案，16-231 为 6×6×6 色立方，232-255 为灰阶
+    pub fn indexed(&self, index: u8) -> Color32 {
+        match index {
+            0..=15 => self.ansi[index as usize],
+            16..=231 => {
+                let i = index - 16;
+                Color32::from_rgb(CUBE_LEVELS[(i / 36) as usize], CUBE_LEVELS[(i / 6 % 6) as usize], CUBE_LEVELS[(i % 6) as usize])
+            }
+            232..=255 => Color32::from_gray(8 + 10 * (index - 232)),
+        }
+    }
+
+    pub fn fg(&self, color: TermColor) -> Color32 {
+        match color {
+            TermColor::Default => self.foreground,
+            TermColor::Indexed(index) => self.indexed(index),
+            TermColor::Rgb(color) => color,
+        }
+    }
+
+    /// 背景色；默认背景返回 None，由终端底色统一绘制
+    pub fn bg(&self, color: TermColor) -> Option<Color32> {
+        match color {
+            TermColor::Default => None,
+            TermColor::Indexed(index) => Some(self.indexed(index)),
+            TermColor::Rgb(color) => Some(color),
+        }
+    }
+}
+
+/// 内置配色方案
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum SchemeId {
+    /// 跟随应用的明暗主题
+    #[default]
+    FollowApp,
+    VerbiumDark,
+    VerbiumLight,
+    SolarizedDark,
+    SolarizedLight,
+    Dracula,
+    OneDark,
+}
+
+impl SchemeId {
+    pub const ALL: [SchemeId; 7] = [
+        SchemeId::FollowApp,
+        SchemeId::VerbiumDark,
+        SchemeId::VerbiumLight,
+        SchemeId::SolarizedDark,
+        SchemeId::SolarizedLight,
+        SchemeId::Dracula,
+        SchemeId::OneDark,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            SchemeId::FollowApp => "Follow App Theme",
+            SchemeId::VerbiumDark => "Verbium Dark",
+            SchemeId::VerbiumLight => "Verbium Light",
+            SchemeId::SolarizedDark => "Solarized Dark",
+            SchemeId::SolarizedLight => "Solarized Light",
+            SchemeId::Dracula => "Dracula",
+            SchemeId::OneDark => "One Dark",
+        }
+    }
+
+    /// 实际使用的配色；`dark_mode` 取自当前界面主题
+    pub fn scheme(&self, dark_mode: bool) -> ColorScheme {
+        match self {
+            SchemeId::FollowApp if dark_mode => VERBIUM_DARK,
+            SchemeId::FollowApp => VERBIUM_LIGHT,
+            SchemeId::VerbiumDark => VERBIUM_DARK,
+            SchemeId::VerbiumLight => VERBIUM_LIGHT,
+            SchemeId::SolarizedDark => solarized(true),
+            SchemeId::SolarizedLight => solarized(false),
+            SchemeId::Dracula => DRACULA,
+            SchemeId::OneDark => ONE_DARK,
+        }
+    }
+}
+
+const fn rgb(hex: u32) -> Color32 {
+    Color32::from_rgb((hex >> 16) as u8, (hex >> 8) as u8, hex as u8)
+}
+
+const VERBIUM_DARK: ColorScheme = ColorScheme {
+    background: Color32::from_rgb(15, 15, 15),
+    foreground: Color32::from_rgb(210, 210, 210),
+    cursor: Color32::from_rgb(200, 200, 200),
+    ansi: [
+        Color32::from_rgb(0, 0, 0),
+        Color32::from_rgb(205, 0, 0),
+        Color32::from_rgb(0, 205, 0),
+        Color32::from_rgb(205, 205, 0),
+        Color32::from_rgb(0, 0, 238),
+        Color32::from_rgb(205, 0, 205),
+        Color32::from_rgb(0, 205, 205),
+        Color32::from_rgb(229, 229, 229),
+        Color32::from_rgb(127, 127, 127),
+        Color32::from_rgb(255, 0, 0),
+        Color32::from_rgb(0, 255, 0),
+        Color32::from_rgb(255, 255, 0),
+        Color32::from_rgb(92, 92, 255),
+        Color32::from_rgb(255, 0, 255),
+        Color32::from_rgb(0, 255, 255),
+        Color32::from_rgb(255, 255, 255),
+    ],
+};
+
+const VERBIUM_LIGHT: ColorScheme = ColorScheme {
+    background: Color32::from_rgb(250, 250, 250),
+    foreground: Color32::from_rgb(40, 40, 40),
+    cursor: Color32::from_rgb(60, 60, 60),
+    ansi: [
+        Color32::from_rgb(0, 0, 0),
+        Color32::from_rgb(180, 30, 30),
+        Color32::from_rgb(0, 130, 0),
+        Color32::from_rgb(150, 120, 0),
+        Color32::from_rgb(0, 60, 200),
+        Color32::from_rgb(160, 0, 160),
+        Color32::from_rgb(0, 130, 150),
+        Color32::from_rgb(150, 150, 150),
+        Color32::from_rgb(100, 100, 100),
+        Color32::from_rgb(220, 50, 50),
+        Color32::from_rgb(30, 160, 30),
+        Color32::from_rgb(180, 150, 0),
+        Color32::from_rgb(40, 100, 230),
+        Color32::from_rgb(200, 40, 200),
+        Color32::from_rgb(0, 160, 180),
+        Color32::from_rgb(60, 60, 60),
+    ],
+};
+
+/// Solarized 的明暗两版共用同一套强调色，只交换基础色
+fn solarized(dark: bool) -> ColorScheme {
+    let [base03, base02, base01, base00, base0, base1, base2, base3] =
+        [0x002b36, 0x073642, 0x586e75, 0x657b83, 0x839496, 0x93a1a1, 0xeee8d5, 0xfdf6e3].map(rgb);
+    let (background, foreground, cursor) = if dark { (base03, base0, base1) } else { (base3, base00, base01) };
+    ColorScheme {
+        background,
+        foreground,
+        cursor,
+        ansi: [
+            base02, rgb(0xdc322f), rgb(0x859900), rgb(0xb58900), rgb(0x268bd2), rgb(0xd33682), rgb(0x2aa198), base2,
+            base03, rgb(0xcb4b16), base01, base00, base0, rgb(0x6c71c4), base1, base3,
+        ],
+    }
+}
+
+const DRACULA: ColorScheme = ColorScheme {
+    background: rgb(0x282a36),
+    foreground: rgb(0xf8f8f2),
+    cursor: rgb(0xf8f8f2),
+    ansi: [
+        rgb(0x21222c), rgb(0xff5555), rgb(0x50fa7b), rgb(0xf1fa8c), rgb(0xbd93f9), rgb(0xff79c6), rgb(0x8be9fd), rgb(0xf8f8f2),
+        rgb(0x6272a4), rgb(0xff6e6e), rgb(0x69ff94), rgb(0xffffa5), rgb(0xd6acff), rgb(0xff92df), rgb(0xa4ffff), rgb(0xffffff),
+    ],
+};
+
+const ONE_DARK: ColorScheme = ColorScheme {
+    background: rgb(0x282c34),
+    foreground: rgb(0xabb2bf),
+    cursor: rgb(0x528bff),
+    ansi: [
+        rgb(0x282c34), rgb(0xe06c75), rgb(0x98c379), rgb(0xe5c07b), rgb(0x61afef), rgb(0xc678dd), rgb(0x56b6c2), rgb(0xabb2bf),
+        rgb(0x5c6370), rgb(0xe06c75), rgb(0x98c379), rgb(0xe5c07b), rgb(0x61afef), rgb(0xc678dd), rgb(0x56b6c2), rgb(0xffffff),
+    ],
+};
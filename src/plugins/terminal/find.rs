@@ -0,0 +1,106 @@
+use std::collections::VecDeque;
+use super::Cell;
+
+/// 一处匹配：所在行（历史行在前，接着是当前屏幕）与起止列
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FindMatch {
+    pub row: usize,
+    pub col: usize,
+    pub len: usize,
+}
+
+/// 终端查找栏状态
+#[derive(Debug, Clone, Default)]
+pub struct TerminalFind {
+    pub query: String,
+    pub matches: Vec<FindMatch>,
+    pub current: usize,
+    /// 匹配结果对应的查询词与终端内容版本，任一变化时重新搜索
+    searched: Option<(String, u64)>,
+    /// 下一帧把当前匹配滚动到可见区域
+    pub scroll_pending: bool,
+    pub focus_pending: bool,
+}
+
+impl TerminalFind {
+    pub fn new() -> Self {
+        Self {
+            focus_pending: true,
+            ..Default::default()
+        }
+    }
+
+    /// 查询词或终端内容变化后重新搜索，尽量保持当前匹配所在的行
+    pub fn refresh(&mut self, history: &VecDeque<Vec<Cell>>, grid: &[Vec<Cell>], generation: u64) {
+        if self.searched.as_ref().is_some_and(|(q, g)| *q == self.query && *g == generation) {
+            return;
+        }
+        let query_changed = self.searched.as_ref().is_none_or(|(q, _)| *q != self.query);
+        let previous_row = self.matches.get(self.current).map(|m| m.row);
+
+        self.matches = find_matches(history.iter().chain(grid.iter()), &self.query);
+        self.searched = Some((self.query.clone(), generation));
+
+        if query_changed {
+            // 新的查询从最近的输出（末尾）开始
+            self.current = self.matches.len().saturating_sub(1);
+            self.scroll_pending = !self.matches.is_empty();
+        } else if let Some(row) = previous_row {
+            self.current = self.matches.iter().position(|m| m.row >= row).unwrap_or(self.matches.len().saturating_sub(1));
+        }
+    }
+
+    /// 跳到下一处 / 上一处匹配，首尾循环
+    pub fn step(&mut self, forward: bool) {
+        let count = self.matches.len();
+        if count == 0 {
+            return;
+        }
+        self.current = if forward { (self.current + 1) % count } else { (self.current + count - 1) % count };
+        self.scroll_pending = true;
+    }
+
+    pub fn current_match(&self) -> Option<FindMatch> {
+        self.matches.get(self.current).copied()
+    }
+}
+
+/// 不区分大小写的逐行搜索；匹配位置以终端列表示（宽字符占两列）
+fn find_matches<'a>(rows: impl Iterator<Item = &'a Vec<Cell>>, query: &str) -> Vec<FindMatch> {
+    let needle: Vec<char> = query.chars().map(fold_case).collect();
+    if needle.is_empty() {
+        return Vec::new();
+    }
+
+    let mut matches = Vec::new();
+    for (row, cells) in rows.enumerate() {
+        // 每个字符及其起始列，跳过宽字符的占位格
+        let chars: Vec<(char, usize)> = cells
+            .iter()
+            .enumerate()
+            .filter(|(_, cell)| !cell.is_wide_continuation)
+            .map(|(col, cell)| (fold_case(cell.c), col))
+            .collect();
+        if chars.len() < needle.len() {
+            continue;
+        }
+
+        let mut i = 0;
+        while i + needle.len() <= chars.len() {
+            if chars[i..i + needle.len()].iter().map(|(c, _)| *c).eq(needle.iter().copied()) {
+                let col = chars[i].1;
+                let last = chars[i + needle.len() - 1].1;
+                let last_width = if cells.get(last + 1).is_some_and(|c| c.is_wide_continuation) { 2 } else { 1 };
+                matches.push(FindMatch { row, col, len: last + last_width - col });
+                i += needle.len();
+            } else {
+                i += 1;
+            }
+        }
+    }
+    matches
+}
+
+fn fold_case(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
@@ -0,0 +1,110 @@
+use std::path::{Path, PathBuf};
+use super::Cell;
+
+/// 终端输出中的 `路径:行[:列]` 引用（如 cargo 报错中的 `src/app.rs:42:7`）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileLink {
+    /// 起始列与占用的列数
+    pub col: usize,
+    pub len: usize,
+    pub path: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl FileLink {
+    /// 相对路径基于 cwd 解析
+    pub fn resolve(&self, cwd: &Path) -> PathBuf {
+        let path = Path::new(&self.path);
+        if path.is_absolute() { path.to_path_buf() } else { cwd.join(path) }
+    }
+}
+
+/// 不属于路径的分隔字符
+fn is_delimiter(c: char) -> bool {
+    c.is_whitespace() || "\"'`()[]{}<>,;|".contains(c)
+}
+
+/// 找出一行中所有形如 `路径:行[:列]` 的片段
+pub fn find_file_links(cells: &[Cell]) -> Vec<FileLink> {
+    // 每个字符及其起始列，跳过宽字符的占位格
+    let chars: Vec<(char, usize)> = cells
+        .iter()
+        .enumerate()
+        .filter(|(_, cell)| !cell.is_wide_continuation)
+        .map(|(col, cell)| (cell.c, col))
+        .collect();
+
+    let mut links = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        if is_delimiter(chars[i].0) {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        while i < chars.len() && !is_delimiter(chars[i].0) {
+            i += 1;
+        }
+        let token: String = chars[start..i].iter().map(|(c, _)| *c).collect();
+        // 去掉句末标点，例如 "see src/lib.rs:10."
+        let trimmed = token.trim_end_matches(['.', ':']);
+        if let Some((path, line, column)) = parse_reference(trimmed) {
+            let count = trimmed.chars().count();
+            let col = chars[start].1;
+            let last = chars[start + count - 1].1;
+            let last_width = if cells.get(last + 1).is_some_and(|c| c.is_wide_continuation) { 2 } else { 1 };
+            links.push(FileLink { col, len: last + last_width - col, path, line, column });
+        }
+    }
+    links
+}
+
+/// 解析 `路径:行` 或 `路径:行:列`；路径需包含扩展名或目录分隔符，以排除 `error:42` 之类的文本
+fn parse_reference(token: &str) -> Option<(String, usize, usize)> {
+    if token.contains("://") {
+        return None;
+    }
+    let mut parts: Vec<&str> = token.rsplitn(3, ':').collect();
+    parts.reverse();
+    let is_number = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_digit());
+
+    let (path, line, column) = match parts.as_slice() {
+        [path, line, column] if is_number(line) && is_number(column) => (path.to_string(), *line, *column),
+        [head, path_tail, line] if is_number(line) => (format!("{}:{}", head, path_tail), *line, "1"),
+        [path, line] if is_number(line) => (path.to_string(), *line, "1"),
+        _ => return None,
+    };
+    let file_name = path.rsplit(['/', '\\']).next().unwrap_or("");
+    if file_name.is_empty() || !(path.contains(['/', '\\']) || file_name.contains('.')) {
+        return None;
+    }
+    Some((path, line.parse().ok()?, column.parse().ok()?))
+}
+
+/// 解析 OSC 7 报告的工作目录（`file://主机/路径`，路径经过百分号编码）
+pub fn parse_osc7_cwd(uri: &str) -> Option<PathBuf> {
+    let rest = uri.strip_prefix("file://")?;
+    let path = &rest[rest.find('/')?..];
+    let decoded = percent_decode(path)?;
+    // Windows 下形如 /C:/Users/...
+    let bytes = decoded.as_bytes();
+    if bytes.len() >= 3 && bytes[2] == b':' && bytes[1].is_ascii_alphabetic() {
+        return Some(PathBuf::from(&decoded[1..]));
+    }
+    Some(PathBuf::from(decoded))
+}
+
+fn percent_decode(s: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(s.len());
+    let mut iter = s.bytes();
+    while let Some(b) = iter.next() {
+        if b == b'%' {
+            let hex = [iter.next()?, iter.next()?];
+            bytes.push(u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()?);
+        } else {
+            bytes.push(b);
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
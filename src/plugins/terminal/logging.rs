@@ -0,0 +1,95 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use super::Cell;
+
+// ----------------------------------------------------------------------------
+// 会话录制与文本导出
+// ----------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    /// 原样写入 PTY 输出，包括转义序列，可用 `cat` 回放
+    Raw,
+    /// 只写入可打印字符与换行
+    Transcript,
+}
+
+/// 正在进行的录制，由读取线程在处理输出时写入
+pub struct SessionLog {
+    pub path: PathBuf,
+    pub format: LogFormat,
+    file: BufWriter<File>,
+    /// 纯文本格式下尚未写出的当前行，退格时从中删除字符
+    line: String,
+}
+
+impl SessionLog {
+    pub fn create(path: &Path, format: LogFormat) -> std::io::Result<Self> {
+        Ok(Self {
+            path: path.to_path_buf(),
+            format,
+            file: BufWriter::new(File::create(path)?),
+            line: String::new(),
+        })
+    }
+
+    pub fn raw(&mut self, bytes: &[u8]) {
+        if self.format == LogFormat::Raw {
+            let _ = self.file.write_all(bytes);
+        }
+    }
+
+    pub fn print(&mut self, c: char) {
+        if self.format == LogFormat::Transcript {
+            self.line.push(c);
+        }
+    }
+
+    pub fn newline(&mut self) {
+        if self.format == LogFormat::Transcript {
+            let _ = writeln!(self.file, "{}", self.line.trim_end());
+            self.line.clear();
+        }
+    }
+
+    pub fn backspace(&mut self) {
+        if self.format == LogFormat::Transcript {
+            self.line.pop();
+        }
+    }
+
+    /// 每批输出处理完后调用，让录制文件随时可读
+    pub fn flush(&mut self) {
+        let _ = self.file.flush();
+    }
+}
+
+impl Drop for SessionLog {
+    fn drop(&mut self) {
+        if !self.line.is_empty() {
+            let _ = write!(self.file, "{}", self.line.trim_end());
+        }
+        let _ = self.file.flush();
+    }
+}
+
+/// 把若干行单元格转成文本：自动换行产生的行重新拼接，去掉行尾空白与末尾的空行
+pub fn rows_text<'a>(rows: impl Iterator<Item = &'a Vec<Cell>>, clusters: &[String]) -> String {
+    let mut text = String::new();
+    for cells in rows {
+        for cell in cells.iter().filter(|c| !c.is_wide_continuation) {
+            match clusters.get((cell.cluster as usize).wrapping_sub(1)) {
+                Some(cluster) => text.push_str(cluster),
+                None => text.push(cell.c),
+            }
+        }
+        if !cells.last().is_some_and(|c| c.wrapped) {
+            text.truncate(text.trim_end_matches(' ').len());
+            text.push('\n');
+        }
+    }
+    text.truncate(text.trim_end().len());
+    text.push('\n');
+    text
+}
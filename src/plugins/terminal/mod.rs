@@ -1,68 +1,83 @@
+use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
 use std::io::{Write, Read};
+use std::path::{Path, PathBuf};
 use parking_lot::Mutex;
-use portable_pty::{native_pty_system, CommandBuilder, PtySize, MasterPty};
+use portable_pty::{native_pty_system, ChildKiller, PtySize, MasterPty};
 use vte::{Parser, Perform};
-use egui::{Ui, WidgetText, Color32, FontId, Rect, Vec2, Key, Sense};
-use egui::text::{LayoutJob, TextFormat};
-use crate::{Tab, Plugin, AppCommand, TabInstance};
+use egui::{Ui, WidgetText, Color32, FontId, Rect, RichText, Vec2, Key, Sense};
+use crate::{Tab, Plugin, AppCommand, TabInstance, TerminalSignal};
+
+mod colors;
+mod find;
+mod links;
+mod logging;
+mod mouse;
+mod panes;
+mod profiles;
+mod render;
+use colors::{ColorScheme, SchemeId, TermColor};
+use find::TerminalFind;
+use links::FileLink;
+use logging::{LogFormat, SessionLog};
+use mouse::MouseTracking;
+use panes::{Direction, PaneNode, SplitDir};
+use render::RowCache;
+use profiles::{CursorShape, CursorStyle, EnvVar, PaneSettings, SshTarget, TerminalConfig, TerminalProfile};
+use profiles::{MAX_FONT_SIZE, MAX_LINE_SPACING, MIN_FONT_SIZE, MIN_LINE_SPACING};
 
 // ----------------------------------------------------------------------------
 // Constants & Colors
 // ----------------------------------------------------------------------------
 
-const TERM_BG: Color32 = Color32::from_rgb(15, 15, 15);
-const TERM_FG: Color32 = Color32::from_rgb(210, 210, 210);
+/// OSC 标题的最大字符数，避免异常输出撑开标签栏
+const MAX_TITLE_LEN: usize = 80;
+/// 光标闪烁周期（秒），亮暗各占一半
+const CURSOR_BLINK_PERIOD: f64 = 1.0;
+/// 默认的回滚缓冲区行数
+const DEFAULT_SCROLLBACK: usize = 5000;
+/// 终端字体在 egui 中注册的字体族名称
+const TERMINAL_FONT: &str = "verbium_terminal";
 
 #[derive(Clone, Copy, Debug, PartialEq)]
 struct Cell {
     c: char,
-    fg: Color32,
-    bg: Color32,
+    fg: TermColor,
+    bg: TermColor,
     bold: bool,
     italic: bool,
     underline: bool,
     inverse: bool,
     is_wide_continuation: bool,
+    /// 仅用于行末单元格：该行因自动换行延续到下一行，调整列数时据此重排
+    wrapped: bool,
+    /// OSC 8 超链接在 `TerminalState::links` 中的序号加一，0 表示没有链接
+    link: u32,
+    /// shell 通过 OSC 133 ; A 标记的提示符起点，用于在命令之间跳转
+    prompt: bool,
+    /// 字素簇在 `TerminalState::clusters` 中的序号加一，0 表示单元格只有 `c` 一个字符
+    cluster: u32,
 }
 
 impl Default for Cell {
     fn default() -> Self {
         Self {
             c: ' ',
-            fg: TERM_FG,
-            bg: Color32::TRANSPARENT,
+            fg: TermColor::Default,
+            bg: TermColor::Default,
             bold: false,
             italic: false,
             underline: false,
             inverse: false,
-            is_wide_continuation: false 
+            is_wide_continuation: false,
+            wrapped: false,
+            link: 0,
+            prompt: false,
+            cluster: 0,
         }
     }
 }
 
-fn ansi_color(code: u8) -> Color32 {
-    match code {
-        0 => Color32::from_rgb(0, 0, 0),        // Black
-        1 => Color32::from_rgb(205, 0, 0),      // Red
-        2 => Color32::from_rgb(0, 205, 0),      // Green
-        3 => Color32::from_rgb(205, 205, 0),    // Yellow
-        4 => Color32::from_rgb(0, 0, 238),      // Blue
-        5 => Color32::from_rgb(205, 0, 205),    // Magenta
-        6 => Color32::from_rgb(0, 205, 205),    // Cyan
-        7 => Color32::from_rgb(229, 229, 229),  // White
-        8 => Color32::from_rgb(127, 127, 127),  // Bright Black
-        9 => Color32::from_rgb(255, 0, 0),      // Bright Red
-        10 => Color32::from_rgb(0, 255, 0),     // Bright Green
-        11 => Color32::from_rgb(255, 255, 0),   // Bright Yellow
-        12 => Color32::from_rgb(92, 92, 255),   // Bright Blue
-        13 => Color32::from_rgb(255, 0, 255),   // Bright Magenta
-        14 => Color32::from_rgb(0, 255, 255),   // Bright Cyan
-        15 => Color32::from_rgb(255, 255, 255), // Bright White
-        _ => TERM_FG,
-    }
-}
-
 // ----------------------------------------------------------------------------
 // Terminal State
 // ----------------------------------------------------------------------------
@@ -76,23 +91,52 @@ struct TerminalState {
     
     primary_grid: Vec<Vec<Cell>>,
     alt_grid: Vec<Vec<Cell>>,
-    history: Vec<Vec<Cell>>,
+    history: VecDeque<Vec<Cell>>,
+    /// 回滚缓冲区的最大行数，None 表示不限制
+    scrollback_limit: Option<usize>,
     is_alt_screen: bool,
     
-    current_fg: Color32,
-    current_bg: Color32,
+    current_fg: TermColor,
+    current_bg: TermColor,
     current_bold: bool,
     current_italic: bool,
     current_underline: bool,
     current_inverse: bool,
+    current_link: u32,
+    /// 出现过的超链接地址，单元格通过序号引用以保持 `Cell` 为 Copy
+    links: Vec<String>,
+    /// 出现过的多字符字素簇（组合字符、emoji 序列），同样通过序号引用
+    clusters: Vec<String>,
+    cluster_ids: HashMap<String, u32>,
+
+    /// 应用通过 OSC 0 / 2 设置的窗口标题
+    title: Option<String>,
+    /// shell 通过 OSC 7 报告的当前目录，用于解析输出中的相对路径
+    cwd: Option<PathBuf>,
     
     cursor_visible: bool,
+    /// 应用通过 DECSCUSR 设置的光标样式，None 表示使用用户默认样式
+    cursor_style: Option<CursorStyle>,
     application_cursor: bool,
+    /// 应用通过 DECSET 1000 / 1002 请求的鼠标上报
+    mouse_tracking: Option<MouseTracking>,
+    /// DECSET 1006：使用 SGR 格式上报鼠标事件
+    sgr_mouse: bool,
+    /// DECSET 2004：粘贴内容以 ESC [200~ 与 ESC [201~ 包围
+    bracketed_paste: bool,
 
     scroll_top: usize,
     scroll_bottom: usize,
     
     dirty: bool,
+    /// 收到 BEL 后置位，由标签页取走用于请求注意
+    bell: bool,
+    /// 每次处理 PTY 输出后递增，用于判断查找结果是否过期
+    generation: u64,
+    /// shell 进程退出后的退出码
+    exit_code: Option<u32>,
+    /// 正在进行的会话录制
+    log: Option<SessionLog>,
 }
 
 impl TerminalState {
@@ -105,19 +149,34 @@ impl TerminalState {
             saved_cursor: (0, 0),
             primary_grid: vec![vec![Cell::default(); cols]; rows],
             alt_grid: vec![vec![Cell::default(); cols]; rows],
-            history: Vec::new(),
+            history: VecDeque::new(),
+            scrollback_limit: Some(DEFAULT_SCROLLBACK),
             is_alt_screen: false,
-            current_fg: TERM_FG,
-            current_bg: Color32::TRANSPARENT,
+            current_fg: TermColor::Default,
+            current_bg: TermColor::Default,
             current_bold: false,
             current_italic: false,
             current_underline: false,
             current_inverse: false,
+            current_link: 0,
+            links: Vec::new(),
+            clusters: Vec::new(),
+            cluster_ids: HashMap::new(),
+            title: None,
+            cwd: None,
             cursor_visible: true,
+            cursor_style: None,
             application_cursor: false,
+            mouse_tracking: None,
+            sgr_mouse: false,
+            bracketed_paste: false,
             scroll_top: 0,
             scroll_bottom: rows.saturating_sub(1),
             dirty: true,
+            bell: false,
+            generation: 0,
+            exit_code: None,
+            log: None,
         }
     }
 
@@ -129,6 +188,138 @@ impl TerminalState {
         if self.is_alt_screen { &self.alt_grid } else { &self.primary_grid }
     }
 
+    /// 第 row 行（历史行在前）
+    fn row(&self, row: usize) -> Option<&Vec<Cell>> {
+        if row < self.history.len() { self.history.get(row) } else { self.grid().get(row - self.history.len()) }
+    }
+
+    /// 第 row 行中包含第 col 列的单词的起止列（含）；点在边界字符上时只选中该字符
+    fn word_at(&self, row: usize, col: usize, delimiters: &str) -> Option<(usize, usize)> {
+        let cells = self.row(row)?;
+        let mut col = col.min(cells.len().checked_sub(1)?);
+        while col > 0 && cells[col].is_wide_continuation {
+            col -= 1;
+        }
+        let is_boundary = |cell: &Cell| !cell.is_wide_continuation && (cell.c.is_whitespace() || delimiters.contains(cell.c));
+        if is_boundary(&cells[col]) {
+            return Some((col, col));
+        }
+        let start = cells[..col].iter().rposition(is_boundary).map_or(0, |p| p + 1);
+        let end = cells[col..].iter().position(is_boundary).map_or(cells.len(), |p| col + p) - 1;
+        Some((start, end))
+    }
+
+    /// 修改回滚上限，超出的旧行立即丢弃
+    fn set_scrollback_limit(&mut self, limit: Option<usize>) {
+        if self.scrollback_limit != limit {
+            self.scrollback_limit = limit;
+            self.trim_history();
+            self.generation = self.generation.wrapping_add(1);
+        }
+    }
+
+    fn trim_history(&mut self) {
+        if let Some(limit) = self.scrollback_limit {
+            let excess = self.history.len().saturating_sub(limit);
+            if excess > 0 {
+                self.history.drain(..excess);
+            }
+        }
+    }
+
+    fn clear_scrollback(&mut self) {
+        self.history.clear();
+        self.dirty = true;
+        self.generation = self.generation.wrapping_add(1);
+    }
+
+    /// 包含第 row 行的逻辑行（自动换行前后的各行）的首尾行号
+    fn line_span(&self, row: usize) -> (usize, usize) {
+        let wrapped = |r: usize| self.row(r).and_then(|cells| cells.last()).is_some_and(|c| c.wrapped);
+        let mut first = row;
+        while first > 0 && wrapped(first - 1) {
+            first -= 1;
+        }
+        let mut last = row;
+        while wrapped(last) && self.row(last + 1).is_some() {
+            last += 1;
+        }
+        (first, last)
+    }
+
+    /// 把单元格的完整字符（含字素簇的后续字符）追加到 out
+    fn push_cell_text(&self, out: &mut String, cell: &Cell) {
+        match self.clusters.get((cell.cluster as usize).wrapping_sub(1)) {
+            Some(cluster) => out.push_str(cluster),
+            None => out.push(cell.c),
+        }
+    }
+
+    /// 把组合字符、变体选择符、肤色修饰符、ZWJ 之后的字符以及成对的区域指示符
+    /// 并入光标前的单元格，返回是否已并入
+    fn join_cluster(&mut self, c: char) -> bool {
+        let r = self.cursor_row;
+        if self.cursor_col == 0 || r >= self.rows { return false; }
+        let cols = self.cols;
+        let mut col = (self.cursor_col - 1).min(cols - 1);
+        if col > 0 && self.grid()[r][col].is_wide_continuation { col -= 1; }
+        let base = self.grid()[r][col];
+        if base.c == ' ' && base.cluster == 0 { return false; }
+
+        let mut text = String::new();
+        self.push_cell_text(&mut text, &base);
+        let is_regional = |c: char| ('\u{1F1E6}'..='\u{1F1FF}').contains(&c);
+        let joins = unicode_width::UnicodeWidthChar::width(c) == Some(0)
+            || text.ends_with('\u{200D}')
+            || ('\u{1F3FB}'..='\u{1F3FF}').contains(&c)
+            || (is_regional(c) && is_regional(base.c) && text.chars().count() == 1);
+        if !joins { return false; }
+        text.push(c);
+
+        let id = match self.cluster_ids.get(&text) {
+            Some(id) => *id,
+            None => {
+                self.clusters.push(text.clone());
+                let id = self.clusters.len() as u32;
+                self.cluster_ids.insert(text, id);
+                id
+            }
+        };
+        self.grid_mut()[r][col].cluster = id;
+
+        // 变体选择符 16 与国旗以 emoji 形式显示，原本单宽的字符扩展为两格
+        let widen = c == '\u{FE0F}' || is_regional(c);
+        let is_wide = col + 1 < cols && self.grid()[r][col + 1].is_wide_continuation;
+        if widen && !is_wide && col + 1 < cols && self.cursor_col == col + 1 {
+            let mut continuation = self.grid()[r][col];
+            continuation.c = ' ';
+            continuation.cluster = 0;
+            continuation.prompt = false;
+            continuation.is_wide_continuation = true;
+            self.grid_mut()[r][col + 1] = continuation;
+            self.cursor_col += 1;
+        }
+        self.dirty = true;
+        true
+    }
+
+    /// 带有提示符标记的行（历史行在前，接着是当前屏幕），按从上到下排列
+    fn prompt_rows(&self) -> Vec<usize> {
+        self.history.iter()
+            .chain(self.grid().iter())
+            .enumerate()
+            .filter(|(_, cells)| cells.iter().any(|c| c.prompt))
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// 第 row 行第 col 列单元格的超链接地址
+    fn link_at(&self, row: usize, col: usize) -> Option<&str> {
+        let link = self.row(row)?.get(col)?.link;
+        if link == 0 { return None; }
+        self.links.get(link as usize - 1).map(String::as_str)
+    }
+
     fn scroll_up(&mut self) {
         let (top, bottom) = (self.scroll_top, self.scroll_bottom);
         let (r, c) = (self.rows, self.cols);
@@ -142,8 +333,8 @@ impl TerminalState {
             let old_row = grid.remove(0);
             grid.push(vec![Cell::default(); c]);
             if !is_alt {
-                self.history.push(old_row);
-                if self.history.len() > 5000 { self.history.remove(0); }
+                self.history.push_back(old_row);
+                self.trim_history();
             }
         } else {
             grid.remove(top);
@@ -152,6 +343,8 @@ impl TerminalState {
         self.dirty = true;
     }
 
+    /// 调整尺寸。主屏幕连同回滚缓冲区按新列数重排自动换行的长行，
+    /// 备用屏幕由全屏应用自行重绘，只做截断与补齐
     fn resize(&mut self, new_rows: usize, new_cols: usize) {
         if new_rows == 0 || new_cols == 0 { return; }
         if new_rows == self.rows && new_cols == self.cols { return; }
@@ -169,17 +362,134 @@ impl TerminalState {
             }
         };
 
-        resize_one(&mut self.primary_grid);
+        // 备用屏幕下主屏幕光标保存在 saved_cursor 中
+        let (primary_row, primary_col) = if self.is_alt_screen { self.saved_cursor } else { (self.cursor_row, self.cursor_col) };
+        let (cursor_row, cursor_col) = self.reflow_primary(new_rows, new_cols, (primary_row, primary_col));
         resize_one(&mut self.alt_grid);
 
+        if self.is_alt_screen {
+            self.saved_cursor = (cursor_row, cursor_col);
+            self.cursor_row = self.cursor_row.min(new_rows - 1);
+            self.cursor_col = self.cursor_col.min(new_cols - 1);
+        } else {
+            self.cursor_row = cursor_row;
+            self.cursor_col = cursor_col;
+        }
         self.rows = new_rows;
         self.cols = new_cols;
         self.scroll_top = 0;
         self.scroll_bottom = new_rows.saturating_sub(1);
-        self.cursor_row = self.cursor_row.min(new_rows - 1);
-        self.cursor_col = self.cursor_col.min(new_cols - 1);
         self.dirty = true;
+        // 行号已改变，使查找结果失效
+        self.generation = self.generation.wrapping_add(1);
+    }
+
+    /// 把回滚缓冲区与主屏幕拼回逻辑行，按 new_cols 重新折行后再分回两部分。
+    /// 返回光标在新主屏幕中的位置
+    fn reflow_primary(&mut self, new_rows: usize, new_cols: usize, cursor: (usize, usize)) -> (usize, usize) {
+        // 光标以下的空行不参与重排，避免缩小时把内容挤进回滚缓冲区
+        let used_rows = self.primary_grid
+            .iter()
+            .rposition(|row| row.iter().any(|c| *c != Cell::default()))
+            .map_or(0, |r| r + 1)
+            .max(cursor.0 + 1)
+            .min(self.primary_grid.len());
+        let cursor_abs = self.history.len() + cursor.0;
+
+        let mut old_rows: Vec<Vec<Cell>> = std::mem::take(&mut self.history).into();
+        old_rows.extend(self.primary_grid.drain(..used_rows));
+
+        // 1. 拼成逻辑行，同时记下光标所在的逻辑行与行内偏移
+        let mut lines: Vec<Vec<Cell>> = Vec::new();
+        let mut cursor_line = (0, 0);
+        let mut current: Vec<Cell> = Vec::new();
+        for (i, mut row) in old_rows.into_iter().enumerate() {
+            if i == cursor_abs {
+                cursor_line = (lines.len(), current.len() + cursor.1);
+            }
+            let wrapped = row.last().is_some_and(|c| c.wrapped);
+            if let Some(last) = row.last_mut() {
+                last.wrapped = false;
+            }
+            if wrapped {
+                current.extend(row);
+            } else {
+                let len = row.iter().rposition(|c| *c != Cell::default()).map_or(0, |p| p + 1);
+                row.truncate(len);
+                current.extend(row);
+                lines.push(std::mem::take(&mut current));
+            }
+        }
+        if !current.is_empty() {
+            lines.push(current);
+        }
+
+        // 2. 按新列数折行
+        let mut rows: Vec<Vec<Cell>> = Vec::new();
+        let mut new_cursor = (0, 0);
+        for (i, line) in lines.into_iter().enumerate() {
+            let start = rows.len();
+            let offset = if i == cursor_line.0 { Some(cursor_line.1) } else { None };
+            rows.extend(wrap_line(line, new_cols, offset.unwrap_or(0)));
+            if let Some(offset) = offset {
+                let row = (offset / new_cols).min(rows.len() - start - 1);
+                new_cursor = (start + row, offset - row * new_cols);
+            }
+        }
+
+        // 3. 末尾 new_rows 行（且包含光标）作为屏幕，其余进入回滚缓冲区
+        let screen_start = rows.len().saturating_sub(new_rows).min(new_cursor.0);
+        let mut screen = rows.split_off(screen_start);
+        screen.truncate(new_rows);
+        while screen.len() < new_rows {
+            screen.push(vec![Cell::default(); new_cols]);
+        }
+        self.history = rows.into();
+        self.trim_history();
+        self.primary_grid = screen;
+        (new_cursor.0 - screen_start, new_cursor.1)
+    }
+}
+
+/// 粘贴内容转换为写入 PTY 的文本：换行统一为回车；
+/// 括号粘贴模式下加上起止标记，并去掉内容中的结束标记，防止粘贴内容提前结束粘贴
+fn paste_sequence(text: &str, bracketed: bool) -> String {
+    let text = text.replace("\r\n", "\r").replace('\n', "\r");
+    if bracketed {
+        format!("\x1b[200~{}\x1b[201~", text.replace("\x1b[201~", ""))
+    } else {
+        text
+    }
+}
+
+/// 把一条逻辑行折成宽度为 cols 的若干行，min_len 保证光标所在位置也有对应的行。
+/// 宽字符不会被拆开，放不下时连同续格一起移到下一行
+fn wrap_line(line: Vec<Cell>, cols: usize, min_len: usize) -> Vec<Vec<Cell>> {
+    let mut rows = Vec::new();
+    let mut row: Vec<Cell> = Vec::with_capacity(cols);
+    let mut i = 0;
+    while i < line.len() {
+        let width = if line.get(i + 1).is_some_and(|c| c.is_wide_continuation) { 2 } else { 1 };
+        if row.len() + width > cols && !row.is_empty() {
+            row.resize(cols, Cell::default());
+            if let Some(last) = row.last_mut() { last.wrapped = true; }
+            rows.push(std::mem::replace(&mut row, Vec::with_capacity(cols)));
+        }
+        row.extend(line[i..(i + width).min(line.len())].iter().copied().take(cols));
+        i += width;
+    }
+    let consumed = rows.len() * cols + row.len();
+    let mut extra = min_len.saturating_sub(consumed);
+    while extra > 0 && row.len() + extra > cols {
+        // 光标位于行尾之后较远处（例如提示符后的空格），补出空行
+        extra -= cols - row.len();
+        row.resize(cols, Cell::default());
+        if let Some(last) = row.last_mut() { last.wrapped = true; }
+        rows.push(std::mem::replace(&mut row, Vec::with_capacity(cols)));
     }
+    row.resize(cols, Cell::default());
+    rows.push(row);
+    rows
 }
 
 // ----------------------------------------------------------------------------
@@ -192,11 +502,17 @@ struct LogHandler<'a> {
 
 impl<'a> Perform for LogHandler<'a> {
     fn print(&mut self, c: char) {
+        if let Some(log) = &mut self.state.log { log.print(c); }
+        if self.state.join_cluster(c) { return; }
         let is_wide = unicode_width::UnicodeWidthChar::width(c).unwrap_or(1) > 1;
         let width = if is_wide { 2 } else { 1 };
         
         let cols = self.state.cols;
         if self.state.cursor_col + width > cols {
+            let r = self.state.cursor_row;
+            if let Some(last) = self.state.grid_mut().get_mut(r).and_then(|row| row.last_mut()) {
+                last.wrapped = true;
+            }
             self.state.cursor_col = 0;
             self.state.cursor_row += 1;
         }
@@ -208,6 +524,8 @@ impl<'a> Perform for LogHandler<'a> {
         let r = self.state.cursor_row;
         let c_idx = self.state.cursor_col;
         if r < self.state.rows {
+            // 提示符本身会覆盖标记所在的单元格，标记需要保留
+            let prompt = self.state.grid()[r][c_idx].prompt;
             let cell_style = Cell {
                 c,
                 fg: self.state.current_fg,
@@ -217,6 +535,10 @@ impl<'a> Perform for LogHandler<'a> {
                 underline: self.state.current_underline,
                 inverse: self.state.current_inverse,
                 is_wide_continuation: false,
+                wrapped: false,
+                link: self.state.current_link,
+                prompt,
+                cluster: 0,
             };
 
             let grid = self.state.grid_mut();
@@ -225,6 +547,7 @@ impl<'a> Perform for LogHandler<'a> {
             if is_wide && c_idx + 1 < cols {
                 let mut continuation = cell_style;
                 continuation.c = ' ';
+                continuation.prompt = false;
                 continuation.is_wide_continuation = true;
                 grid[r][c_idx + 1] = continuation;
             }
@@ -237,18 +560,22 @@ impl<'a> Perform for LogHandler<'a> {
         match byte {
             b'\r' => self.state.cursor_col = 0,
             b'\n' | b'\x0b' | b'\x0c' => {
+                if let Some(log) = &mut self.state.log { log.newline(); }
                 self.state.cursor_row += 1;
                 if self.state.cursor_row > self.state.scroll_bottom {
                     self.state.cursor_row = self.state.scroll_bottom;
                     self.state.scroll_up();
                 }
             }
-            b'\x08' => { if self.state.cursor_col > 0 { self.state.cursor_col -= 1; } }
+            b'\x08' => {
+                if let Some(log) = &mut self.state.log { log.backspace(); }
+                if self.state.cursor_col > 0 { self.state.cursor_col -= 1; }
+            }
             b'\t' => {
                 let next = (self.state.cursor_col / 8 + 1) * 8;
                 self.state.cursor_col = next.min(self.state.cols - 1);
             }
-            7 => { /* Bell */ } // This is ASCII BEL character
+            7 => self.state.bell = true, // This is ASCII BEL character
             _ => {} // Other control characters are ignored for now
         }
         self.state.dirty = true;
@@ -263,8 +590,8 @@ impl<'a> Perform for LogHandler<'a> {
                 while let Some(param) = it.next() {
                     match param[0] {
                         0 => {
-                            self.state.current_fg = TERM_FG;
-                            self.state.current_bg = Color32::TRANSPARENT;
+                            self.state.current_fg = TermColor::Default;
+                            self.state.current_bg = TermColor::Default;
                             self.state.current_bold = false;
                             self.state.current_italic = false;
                             self.state.current_underline = false;
@@ -278,36 +605,36 @@ impl<'a> Perform for LogHandler<'a> {
                         23 => self.state.current_italic = false,
                         24 => self.state.current_underline = false,
                         27 => self.state.current_inverse = false,
-                        30..=37 => self.state.current_fg = ansi_color(param[0] as u8 - 30),
+                        30..=37 => self.state.current_fg = TermColor::Indexed(param[0] as u8 - 30),
                         38 => {
                             match it.next().map(|v| v[0]) {
-                                Some(5) => if let Some(v) = it.next() { self.state.current_fg = ansi_color(v[0] as u8); },
+                                Some(5) => if let Some(v) = it.next() { self.state.current_fg = TermColor::Indexed(v[0] as u8); },
                                 Some(2) => {
                                     let r = it.next().map(|v| v[0] as u8).unwrap_or(0);
                                     let g = it.next().map(|v| v[0] as u8).unwrap_or(0);
                                     let b = it.next().map(|v| v[0] as u8).unwrap_or(0);
-                                    self.state.current_fg = Color32::from_rgb(r, g, b);
+                                    self.state.current_fg = TermColor::Rgb(Color32::from_rgb(r, g, b));
                                 }
                                 _ => {} // Ignore unsupported SGR color modes
                             }
                         }
-                        39 => self.state.current_fg = TERM_FG,
-                        40..=47 => self.state.current_bg = ansi_color(param[0] as u8 - 40),
+                        39 => self.state.current_fg = TermColor::Default,
+                        40..=47 => self.state.current_bg = TermColor::Indexed(param[0] as u8 - 40),
                         48 => {
                             match it.next().map(|v| v[0]) {
-                                Some(5) => if let Some(v) = it.next() { self.state.current_bg = ansi_color(v[0] as u8); },
+                                Some(5) => if let Some(v) = it.next() { self.state.current_bg = TermColor::Indexed(v[0] as u8); },
                                 Some(2) => {
                                     let r = it.next().map(|v| v[0] as u8).unwrap_or(0);
                                     let g = it.next().map(|v| v[0] as u8).unwrap_or(0);
                                     let b = it.next().map(|v| v[0] as u8).unwrap_or(0);
-                                    self.state.current_bg = Color32::from_rgb(r, g, b);
+                                    self.state.current_bg = TermColor::Rgb(Color32::from_rgb(r, g, b));
                                 }
                                 _ => {} // Ignore unsupported SGR color modes
                             }
                         }
-                        49 => self.state.current_bg = Color32::TRANSPARENT,
-                        90..=97 => self.state.current_fg = ansi_color(param[0] as u8 - 90 + 8),
-                        100..=107 => self.state.current_bg = ansi_color(param[0] as u8 - 100 + 8),
+                        49 => self.state.current_bg = TermColor::Default,
+                        90..=97 => self.state.current_fg = TermColor::Indexed(param[0] as u8 - 90 + 8),
+                        100..=107 => self.state.current_bg = TermColor::Indexed(param[0] as u8 - 100 + 8),
                         _ => {} // Ignore unsupported SGR parameters
                     }
                 }
@@ -422,11 +749,18 @@ impl<'a> Perform for LogHandler<'a> {
                 self.state.scroll_top = top;
                 self.state.scroll_bottom = bot.min(self.state.rows - 1);
             }
+            'q' if intermediates == b" " => { // DECSCUSR - Set Cursor Style
+                self.state.cursor_style = CursorStyle::from_decscusr(p(0) as u16);
+            }
             'h' if intermediates == b"?" => {
                 for param in params.iter() {
                     match param[0] {
                         1 => self.state.application_cursor = true,
                         25 => self.state.cursor_visible = true,
+                        1000 => self.state.mouse_tracking = Some(MouseTracking::Click),
+                        1002 => self.state.mouse_tracking = Some(MouseTracking::Drag),
+                        1006 => self.state.sgr_mouse = true,
+                        2004 => self.state.bracketed_paste = true,
                         1049 => {
                             self.state.saved_cursor = (self.state.cursor_row, self.state.cursor_col);
                             self.state.is_alt_screen = true;
@@ -443,6 +777,9 @@ impl<'a> Perform for LogHandler<'a> {
                     match param[0] {
                         1 => self.state.application_cursor = false,
                         25 => self.state.cursor_visible = false,
+                        1000 | 1002 => self.state.mouse_tracking = None,
+                        1006 => self.state.sgr_mouse = false,
+                        2004 => self.state.bracketed_paste = false,
                         1049 => {
                             self.state.is_alt_screen = false;
                             self.state.cursor_row = self.state.saved_cursor.0.min(self.state.rows - 1);
@@ -484,17 +821,60 @@ impl<'a> Perform for LogHandler<'a> {
     fn hook(&mut self, _params: &vte::Params, _intermediates: &[u8], _ignore: bool, _c: char) {}
     fn put(&mut self, _byte: u8) {}
     fn unhook(&mut self) {}
-    fn osc_dispatch(&mut self, _params: &[&[u8]], _bell_terminated: bool) {}
+    fn osc_dispatch(&mut self, params: &[&[u8]], _bell_terminated: bool) {
+        // 参数中可能包含被拆开的 ';'，重新拼接
+        let join = |parts: &[&[u8]]| {
+            parts.iter().map(|p| String::from_utf8_lossy(p)).collect::<Vec<_>>().join(";")
+        };
+        match params.first().copied() {
+            Some(b"0") | Some(b"2") => {
+                let title: String = join(&params[1..]).chars().filter(|c| !c.is_control()).take(MAX_TITLE_LEN).collect();
+                self.state.title = if title.trim().is_empty() { None } else { Some(title) };
+            }
+            Some(b"8") if params.len() >= 3 => {
+                // OSC 8 ; 参数 ; URI —— URI 为空表示链接结束
+                let uri = join(&params[2..]);
+                if uri.is_empty() {
+                    self.state.current_link = 0;
+                } else {
+                    if self.state.links.last() != Some(&uri) {
+                        self.state.links.push(uri);
+                    }
+                    self.state.current_link = self.state.links.len() as u32;
+                }
+            }
+            Some(b"7") if params.len() >= 2 => {
+                if let Some(cwd) = links::parse_osc7_cwd(&join(&params[1..])) {
+                    self.state.cwd = Some(cwd);
+                }
+            }
+            Some(b"133") if params.len() >= 2 => {
+                // 只用 A（提示符开始）定位命令；B / C / D 不影响显示
+                if params[1].first() == Some(&b'A') && !self.state.is_alt_screen {
+                    let (r, c) = (self.state.cursor_row, self.state.cursor_col.min(self.state.cols.saturating_sub(1)));
+                    if let Some(cell) = self.state.grid_mut().get_mut(r).and_then(|row| row.get_mut(c)) {
+                        cell.prompt = true;
+                    }
+                }
+            }
+            _ => {} // Ignore unsupported OSC sequences
+        }
+        self.state.dirty = true;
+    }
 }
 
 // ----------------------------------------------------------------------------
 // Tab Implementation
 // ----------------------------------------------------------------------------
 
-pub struct TerminalTab {
+/// 标签页内的一个窗格，各自拥有独立的 shell 进程
+struct TerminalPane {
+    id: usize,
     state: Arc<Mutex<TerminalState>>,
     writer: Arc<Mutex<Box<dyn Write + Send>>>,
     master: Arc<Mutex<Box<dyn MasterPty + Send>>>,
+    /// shell 子进程的终止句柄，复制出的标签页共享同一个进程
+    killer: Arc<Mutex<Box<dyn ChildKiller + Send + Sync>>>,
     last_size: (usize, usize),
     ctx: egui::Context,
     input_buffer: String,
@@ -502,20 +882,38 @@ pub struct TerminalTab {
     selection_start: Option<(usize, usize)>,
     selection_end: Option<(usize, usize)>,
     drag_start: Option<(usize, usize)>,
+    /// 回滚缓冲区查找栏（Ctrl + Shift + F）
+    find: Option<TerminalFind>,
+    /// 用户默认的光标样式与配色，与插件设置共享
+    settings: Arc<Mutex<PaneSettings>>,
+    /// 最近一次输入的时间，输入后光标保持常亮并重新开始闪烁
+    blink_epoch: f64,
+    /// 启动目录；shell 未通过 OSC 7 报告目录时用它解析相对路径
+    cwd: PathBuf,
+    /// 下一帧让输入框获得焦点（键盘切换窗格、新建分屏后）
+    focus_pending: bool,
+    /// 已向应用上报按下、尚未释放的鼠标按钮编号及其所在单元格
+    mouse_pressed: Option<(u8, (usize, usize))>,
+    /// 可见行的排版缓存，内容未变化的行不重新排版
+    row_cache: RowCache,
+    /// shell 进程 ID
+    pid: Option<u32>,
+    /// 上一帧视口顶部所在的行
+    viewport_top: usize,
+    /// 下一帧滚动到视口顶部的行
+    scroll_to_row: Option<usize>,
+    /// Ctrl + 滚轮在设置字号之上的增减
+    font_zoom: f32,
 }
 
-impl std::fmt::Debug for TerminalTab {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("TerminalTab").finish()
-    }
-}
-
-impl Clone for TerminalTab {
+impl Clone for TerminalPane {
     fn clone(&self) -> Self {
         Self {
+            id: self.id,
             state: self.state.clone(),
             writer: self.writer.clone(),
             master: self.master.clone(),
+            killer: self.killer.clone(),
             last_size: self.last_size,
             ctx: self.ctx.clone(),
             input_buffer: String::new(),
@@ -523,21 +921,272 @@ impl Clone for TerminalTab {
             selection_start: None,
             selection_end: None,
             drag_start: None,
+            find: None,
+            settings: self.settings.clone(),
+            blink_epoch: 0.0,
+            cwd: self.cwd.clone(),
+            focus_pending: false,
+            mouse_pressed: None,
+            row_cache: RowCache::default(),
+            pid: self.pid,
+            viewport_top: 0,
+            scroll_to_row: None,
+            font_zoom: self.font_zoom,
         }
     }
 }
 
-impl TabInstance for TerminalTab {
-    fn title(&self) -> WidgetText { "Terminal".into() }
+/// 窗格快捷键或右键菜单触发的布局操作，由所在标签页执行
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PaneAction {
+    Split(SplitDir),
+    Focus(Direction),
+    Resize(Direction),
+    Close,
+    /// 进程退出后在原位置重新启动 shell
+    Restart,
+}
+
+impl PaneAction {
+    /// Alt + Shift + = / - 分屏，Alt + 方向键切换窗格，Alt + Shift + 方向键调整大小，Ctrl + Shift + W 关闭窗格
+    fn from_key(key: Key, modifiers: &egui::Modifiers) -> Option<Self> {
+        let direction = match key {
+            Key::ArrowLeft => Some(Direction::Left),
+            Key::ArrowRight => Some(Direction::Right),
+            Key::ArrowUp => Some(Direction::Up),
+            Key::ArrowDown => Some(Direction::Down),
+            _ => None,
+        };
+        if modifiers.alt && !modifiers.ctrl && !modifiers.command {
+            match (key, direction) {
+                (_, Some(direction)) if modifiers.shift => return Some(PaneAction::Resize(direction)),
+                (_, Some(direction)) => return Some(PaneAction::Focus(direction)),
+                (Key::Equals | Key::Plus, _) if modifiers.shift => return Some(PaneAction::Split(SplitDir::Horizontal)),
+                (Key::Minus, _) if modifiers.shift => return Some(PaneAction::Split(SplitDir::Vertical)),
+                _ => {}
+            }
+        }
+        if key == Key::W && modifiers.command && modifiers.shift && !modifiers.alt {
+            return Some(PaneAction::Close);
+        }
+        None
+    }
+}
+
+impl TerminalPane {
+    /// 选中的文本；plain 为 true 时去掉每行末尾的空白
+    fn selection_text(&self, plain: bool) -> Option<String> {
+        let (start, end) = (self.selection_start?, self.selection_end?);
+        let (s, e) = if start <= end { (start, end) } else { (end, start) };
+        let state = self.state.lock();
+        let mut text = String::new();
+        for r in s.0..=e.0 {
+            let Some(cells) = state.row(r) else { break };
+            let c_start = if r == s.0 { s.1 } else { 0 };
+            let c_end = if r == e.0 { (e.1 + 1).min(cells.len()) } else { cells.len() };
+            let mut line = String::new();
+            for c in c_start..c_end.max(c_start) {
+                let cell = &cells[c];
+                if !cell.is_wide_continuation { state.push_cell_text(&mut line, cell); }
+            }
+            // 自动换行产生的行之间不插入换行符
+            let wrapped = cells.last().is_some_and(|c| c.wrapped);
+            if plain && !wrapped {
+                line.truncate(line.trim_end().len());
+            }
+            text.push_str(&line);
+            if r != e.0 && !wrapped { text.push('\n'); }
+        }
+        Some(text)
+    }
+
+    /// 清空回滚缓冲区；行号随之改变，选区一并清除
+    fn clear_scrollback(&mut self) {
+        self.state.lock().clear_scrollback();
+        self.selection_start = None;
+        self.selection_end = None;
+        self.drag_start = None;
+    }
+
+    fn select_all(&mut self) {
+        let state = self.state.lock();
+        let last_row = (state.history.len() + state.rows).saturating_sub(1);
+        let last_col = state.cols.saturating_sub(1);
+        drop(state);
+        self.selection_start = Some((0, 0));
+        self.selection_end = Some((last_row, last_col));
+        self.drag_start = None;
+    }
 
-    fn ui(&mut self, ui: &mut Ui, _control: &mut Vec<AppCommand>) {
-        let font_id = FontId::monospace(14.0);
-        let char_size = ui.fonts(|f| {
+    /// 跳到视口顶部之上（或之下）最近的提示符；下方没有提示符时回到底部
+    fn jump_to_prompt(&mut self, previous: bool) {
+        let state = self.state.lock();
+        let rows = state.prompt_rows();
+        let bottom = state.history.len() + state.rows;
+        drop(state);
+        let top = self.viewport_top;
+        let target = if previous {
+            rows.iter().rev().find(|r| **r < top).copied()
+        } else {
+            Some(rows.iter().find(|r| **r > top).copied().unwrap_or(bottom))
+        };
+        if target.is_some() {
+            self.scroll_to_row = target;
+            self.ctx.request_repaint();
+        }
+    }
+
+    /// 开始录制之后的输出；已有录制时先结束它
+    fn start_recording(&mut self, format: LogFormat, control: &mut Vec<AppCommand>) {
+        let (name, ext) = match format {
+            LogFormat::Raw => ("session.log", "log"),
+            LogFormat::Transcript => ("transcript.txt", "txt"),
+        };
+        let Some(path) = rfd::FileDialog::new()
+            .set_directory(&self.cwd)
+            .set_file_name(name)
+            .add_filter(ext, &[ext])
+            .save_file()
+        else { return };
+        match SessionLog::create(&path, format) {
+            Ok(log) => self.state.lock().log = Some(log),
+            Err(e) => control.push(AppCommand::Notify {
+                message: format!("Failed to record to {}: {}", path.display(), e),
+                level: crate::NotificationLevel::Error,
+            }),
+        }
+    }
+
+    fn stop_recording(&mut self, control: &mut Vec<AppCommand>) {
+        if let Some(log) = self.state.lock().log.take() {
+            control.push(AppCommand::Notify {
+                message: format!("Recording saved to {}", log.path.display()),
+                level: crate::NotificationLevel::Info,
+            });
+        }
+    }
+
+    /// 把回滚缓冲区与当前屏幕以纯文本保存到文件
+    fn save_transcript(&mut self, control: &mut Vec<AppCommand>) {
+        let Some(path) = rfd::FileDialog::new()
+            .set_directory(&self.cwd)
+            .set_file_name("transcript.txt")
+            .add_filter("txt", &["txt"])
+            .save_file()
+        else { return };
+        let text = {
+            let state = self.state.lock();
+            logging::rows_text(state.history.iter().chain(state.grid().iter()), &state.clusters)
+        };
+        if let Err(e) = std::fs::write(&path, text) {
+            control.push(AppCommand::Notify {
+                message: format!("Failed to save transcript: {}", e),
+                level: crate::NotificationLevel::Error,
+            });
+        }
+    }
+
+    /// 查找栏：Enter / Shift + Enter 或箭头按钮在匹配间循环跳转，Esc 关闭
+    fn find_bar_ui(&mut self, ui: &mut Ui) {
+        let Some(find) = &mut self.find else { return };
+        {
+            let state = self.state.lock();
+            find.refresh(&state.history, state.grid(), state.generation);
+        }
+
+        let mut close = false;
+        ui.horizontal(|ui| {
+            ui.label("🔍");
+            let response = ui.add(
+                egui::TextEdit::singleline(&mut find.query)
+                    .hint_text("Find in terminal")
+                    .desired_width(220.0),
+            );
+            if std::mem::take(&mut find.focus_pending) {
+                response.request_focus();
+            }
+            if response.lost_focus() && ui.input(|i| i.key_pressed(Key::Enter)) {
+                let backward = ui.input(|i| i.modifiers.shift);
+                find.step(!backward);
+                response.request_focus();
+            }
+            if response.has_focus() && ui.input(|i| i.key_pressed(Key::Escape)) {
+                close = true;
+            }
+
+            if !find.query.is_empty() {
+                if find.matches.is_empty() {
+                    ui.colored_label(crate::theme::palette(ui.ctx()).error, "No results");
+                } else {
+                    ui.label(format!("{} / {}", find.current + 1, find.matches.len()));
+                }
+            }
+            if ui.small_button("⬆").on_hover_text("Previous (Shift + Enter)").clicked() {
+                find.step(false);
+            }
+            if ui.small_button("⬇").on_hover_text("Next (Enter)").clicked() {
+                find.step(true);
+            }
+            if ui.small_button("✖").on_hover_text("Close (Esc)").clicked() {
+                close = true;
+            }
+        });
+
+        if close {
+            self.find = None;
+        }
+    }
+
+    /// 绘制窗格，返回输入框是否拥有焦点以及触发的布局操作。`split` 表示标签页中还有其他窗格，
+    /// 只有这时窗格才能单独关闭
+    fn ui(&mut self, ui: &mut Ui, split: bool, control: &mut Vec<AppCommand>) -> (bool, Option<PaneAction>) {
+        let mut has_focus = false;
+        let mut action = None;
+        let settings = self.settings.lock().clone();
+        let scheme = settings.scheme.scheme(ui.visuals().dark_mode);
+        // 反显时交换前景与背景；返回 None 表示使用终端底色
+        let cell_bg = |cell: &Cell| if cell.inverse { Some(scheme.fg(cell.fg)) } else { scheme.bg(cell.bg) };
+        // Ctrl + 滚轮（或触控板捏合）缩放当前窗格的字号，网格与 PTY 尺寸随之重新计算
+        if ui.rect_contains_pointer(ui.max_rect()) {
+            let zoom = ui.input(|i| i.zoom_delta());
+            if zoom != 1.0 {
+                let size = ((settings.font_size + self.font_zoom) * zoom).clamp(MIN_FONT_SIZE, MAX_FONT_SIZE);
+                self.font_zoom = size - settings.font_size;
+            }
+        }
+        // 字体在注册后的下一帧才可用，此前使用内置等宽字体
+        let family = egui::FontFamily::Name(TERMINAL_FONT.into());
+        let family = if settings.custom_font && crate::fonts::has_family(ui.ctx(), &family) { family } else { egui::FontFamily::Monospace };
+        // 取半磅整数倍，避免缩放过程中为每个中间字号生成字形
+        let font_size = ((settings.font_size + self.font_zoom) * 2.0).round().clamp(MIN_FONT_SIZE * 2.0, MAX_FONT_SIZE * 2.0) / 2.0;
+        let font_id = FontId::new(font_size, family);
+        let (char_size, baseline_offset) = ui.fonts(|f| {
             let width = f.glyph_width(&font_id, 'M');
             let height = f.row_height(&font_id);
-            Vec2::new(width, height)
+            // 额外的行距平均分配到字形上下
+            (Vec2::new(width, (height * settings.line_spacing).round()), (height * (settings.line_spacing - 1.0) / 2.0).round())
         });
 
+        self.find_bar_ui(ui);
+
+        let exit_code = {
+            let mut state = self.state.lock();
+            state.set_scrollback_limit(settings.scrollback_limit);
+            state.exit_code
+        };
+        if let Some(code) = exit_code {
+            let palette = crate::theme::palette(ui.ctx());
+            let color = if code == 0 { palette.info } else { palette.error };
+            let restart = ui.horizontal(|ui| {
+                ui.label(RichText::new(format!("Process exited with code {}", code)).color(color).strong());
+                ui.weak("— press Enter to restart");
+                ui.button("Restart").clicked()
+            }).inner;
+            if restart {
+                action = Some(PaneAction::Restart);
+            }
+        }
+
         // 1. Calculate PTY size based on available area
         let available_size = ui.available_size();
         let cols = (available_size.x / char_size.x).floor() as usize;
@@ -545,6 +1194,10 @@ impl TabInstance for TerminalTab {
         
         if cols > 0 && rows > 0 && (cols != self.last_size.0 || rows != self.last_size.1) {
             self.state.lock().resize(rows, cols);
+            // 重排后行号不再对应原来的内容
+            self.selection_start = None;
+            self.selection_end = None;
+            self.drag_start = None;
             let _ = self.master.lock().resize(PtySize {
                 rows: rows as u16,
                 cols: cols as u16,
@@ -558,15 +1211,37 @@ impl TabInstance for TerminalTab {
         let history_len = state_lock.history.len();
         let grid_len = state_lock.rows;
         let total_rows = history_len + grid_len;
+        // 应用请求鼠标上报时，鼠标事件交给应用处理；按住 Shift 仍可选择文本与滚动
+        let mouse_reporting = state_lock.mouse_tracking
+            .filter(|_| !ui.input(|i| i.modifiers.shift))
+            .map(|tracking| (tracking, state_lock.sgr_mouse));
         drop(state_lock);
+        if mouse_reporting.is_none() {
+            self.mouse_pressed = None;
+        }
 
         let mut output_to_write = String::new();
+        let mut open_find = false;
 
         // 2. Use ScrollArea for native scrolling and scrollbar
-        egui::ScrollArea::vertical()
+        let mut scroll_area = egui::ScrollArea::vertical()
             .auto_shrink([false; 2])
             .stick_to_bottom(true)
-            .show_viewport(ui, |ui, viewport| {
+            .enable_scrolling(mouse_reporting.is_none());
+        // 把当前查找结果滚动到视口中央
+        if let Some(find) = &mut self.find {
+            if std::mem::take(&mut find.scroll_pending) {
+                if let Some(m) = find.current_match() {
+                    let offset = m.row as f32 * char_size.y - available_size.y / 2.0;
+                    scroll_area = scroll_area.vertical_scroll_offset(offset.max(0.0));
+                }
+            }
+        }
+        if let Some(row) = self.scroll_to_row.take() {
+            scroll_area = scroll_area.vertical_scroll_offset(row as f32 * char_size.y);
+        }
+        let find_shortcut = egui::KeyboardShortcut::new(egui::Modifiers::COMMAND | egui::Modifiers::SHIFT, Key::F);
+        scroll_area.show_viewport(ui, |ui, viewport| {
                 let content_size = Vec2::new(available_size.x, total_rows as f32 * char_size.y);
                 let (rect, response) = ui.allocate_at_least(content_size, Sense::click_and_drag());
                 
@@ -599,27 +1274,241 @@ impl TabInstance for TerminalTab {
                 if response.clicked() {
                     ui.memory_mut(|m| m.request_focus(response.id));
                 }
+                if std::mem::take(&mut self.focus_pending) {
+                    input_response.request_focus();
+                }
+                // 鼠标上报时右键交给应用
+                if mouse_reporting.is_none() {
+                    input_response.context_menu(|ui| {
+                        let has_selection = self.selection_start.is_some() && self.selection_end.is_some();
+                        if ui.add_enabled(has_selection, egui::Button::new("Copy").shortcut_text("Ctrl+C")).clicked() {
+                            if let Some(text) = self.selection_text(false) { ui.ctx().copy_text(text); }
+                            ui.close_menu();
+                        }
+                        if ui.add_enabled(has_selection, egui::Button::new("Copy as Plain Text"))
+                            .on_hover_text("Trailing spaces are removed from each line.")
+                            .clicked()
+                        {
+                            if let Some(text) = self.selection_text(true) { ui.ctx().copy_text(text); }
+                            ui.close_menu();
+                        }
+                        if ui.add(egui::Button::new("Paste").shortcut_text("Ctrl+V")).clicked() {
+                            // 剪贴板内容以 Paste 事件在下一帧送达，由输入框写入 PTY
+                            self.focus_pending = true;
+                            ui.ctx().send_viewport_cmd(egui::ViewportCommand::RequestPaste);
+                            ui.close_menu();
+                        }
+                        if ui.button("Select All").clicked() {
+                            self.select_all();
+                            ui.close_menu();
+                        }
+                        if ui.add(egui::Button::new("Clear Scrollback").shortcut_text("Ctrl+Shift+K")).clicked() {
+                            self.clear_scrollback();
+                            ui.close_menu();
+                        }
+                        if self.font_zoom != 0.0 && ui.button("Reset Zoom").clicked() {
+                            self.font_zoom = 0.0;
+                            ui.close_menu();
+                        }
+                        ui.separator();
+                        if ui.button("Reveal Current Directory")
+                            .on_hover_text("Uses the directory reported by the shell (OSC 7), or the starting directory.")
+                            .clicked()
+                        {
+                            let dir = self.state.lock().cwd.clone().unwrap_or_else(|| self.cwd.clone());
+                            control.push(AppCommand::RevealInShell(dir));
+                            ui.close_menu();
+                        }
+                        let hint = "Requires a shell that reports prompts with OSC 133.";
+                        if ui.add(egui::Button::new("Previous Command").shortcut_text("Ctrl+Shift+↑")).on_hover_text(hint).clicked() {
+                            self.jump_to_prompt(true);
+                            ui.close_menu();
+                        }
+                        if ui.add(egui::Button::new("Next Command").shortcut_text("Ctrl+Shift+↓")).on_hover_text(hint).clicked() {
+                            self.jump_to_prompt(false);
+                            ui.close_menu();
+                        }
+                        ui.separator();
+                        let recording = self.state.lock().log.as_ref().map(|log| log.path.clone());
+                        if let Some(path) = recording {
+                            if ui.button("Stop Recording").on_hover_text(path.display().to_string()).clicked() {
+                                self.stop_recording(control);
+                                ui.close_menu();
+                            }
+                        } else {
+                            ui.menu_button("Record Session", |ui| {
+                                if ui.button("Raw Output…").on_hover_text("Includes escape sequences; replay with cat.").clicked() {
+                                    ui.close_menu();
+                                    self.start_recording(LogFormat::Raw, control);
+                                }
+                                if ui.button("Plain-Text Transcript…").clicked() {
+                                    ui.close_menu();
+                                    self.start_recording(LogFormat::Transcript, control);
+                                }
+                            });
+                        }
+                        if ui.button("Save Transcript…").clicked() {
+                            ui.close_menu();
+                            self.save_transcript(control);
+                        }
+                        ui.separator();
+                        ui.menu_button("Send Signal", |ui| {
+                            let signals = [
+                                ("Interrupt (SIGINT)", TerminalSignal::Interrupt),
+                                ("Terminate (SIGTERM)", TerminalSignal::Terminate),
+                                ("Force Kill Shell", TerminalSignal::Kill),
+                            ];
+                            for (label, signal) in signals {
+                                if ui.button(label).clicked() {
+                                    if let Err(e) = self.send_signal(signal) {
+                                        control.push(AppCommand::Notify {
+                                            message: format!("Failed to send signal: {}", e),
+                                            level: crate::NotificationLevel::Error,
+                                        });
+                                    }
+                                    ui.close_menu();
+                                }
+                            }
+                        });
+                        ui.separator();
+                        let items = [
+                            ("Split Right", "Alt+Shift+=", PaneAction::Split(SplitDir::Horizontal), true),
+                            ("Split Down", "Alt+Shift+-", PaneAction::Split(SplitDir::Vertical), true),
+                            ("Close Pane", "Ctrl+Shift+W", PaneAction::Close, split),
+                        ];
+                        for (label, shortcut, item, enabled) in items {
+                            if ui.add_enabled(enabled, egui::Button::new(label).shortcut_text(shortcut)).clicked() {
+                                action = Some(item);
+                                ui.close_menu();
+                            }
+                        }
+                    });
+                }
+
+                // 悬停的链接：OSC 8 超链接在浏览器标签页中打开，
+                // 输出中的 `路径:行[:列]`（如 cargo 报错）在编辑器中打开，仅在文件存在时可点击
+                let hovered_cell = ui.input(|i| i.pointer.hover_pos())
+                    .filter(|pos| input_rect.contains(*pos))
+                    .map(|pos| {
+                        let rel_pos = pos - rect.min;
+                        ((rel_pos.y / char_size.y).floor() as usize, (rel_pos.x / char_size.x).floor() as usize)
+                    });
+                let mut hovered_link = None;
+                let mut hovered_file: Option<(usize, FileLink, PathBuf)> = None;
+                if let Some((row, col)) = hovered_cell {
+                    let state = self.state.lock();
+                    hovered_link = state.link_at(row, col).map(str::to_string);
+                    if hovered_link.is_none() {
+                        let cwd = state.cwd.as_deref().unwrap_or(&self.cwd);
+                        hovered_file = state.row(row)
+                            .and_then(|cells| links::find_file_links(cells).into_iter().find(|l| (l.col..l.col + l.len).contains(&col)))
+                            .map(|link| { let path = link.resolve(cwd); (row, link, path) })
+                            .filter(|(_, _, path)| path.is_file());
+                    }
+                }
+                let link_clicked = response.clicked() || input_response.clicked();
+                if let Some(url) = &hovered_link {
+                    ui.ctx().set_cursor_icon(egui::CursorIcon::PointingHand);
+                    if link_clicked {
+                        control.push(AppCommand::OpenInBrowser(url.clone()));
+                    }
+                    input_response.clone().on_hover_text_at_pointer(url);
+                } else if let Some((_, link, path)) = &hovered_file {
+                    ui.ctx().set_cursor_icon(egui::CursorIcon::PointingHand);
+                    if link_clicked {
+                        control.push(AppCommand::OpenFileAt { path: path.clone(), line: link.line, column: link.column });
+                    }
+                    input_response.clone().on_hover_text_at_pointer(format!("Open {} at line {}", path.display(), link.line));
+                }
+
+                if let Some((tracking, sgr)) = mouse_reporting {
+                    let screen_cell = |pos: egui::Pos2| {
+                        let rel_pos = pos - rect.min;
+                        let row = ((rel_pos.y / char_size.y).floor().max(0.0) as usize).saturating_sub(history_len);
+                        let col = (rel_pos.x / char_size.x).floor().max(0.0) as usize;
+                        (col.min(cols.saturating_sub(1)), row.min(grid_len.saturating_sub(1)))
+                    };
+                    let mut reports = Vec::new();
+                    ui.input(|i| {
+                        for event in &i.events {
+                            match event {
+                                egui::Event::PointerButton { pos, button, pressed, modifiers } => {
+                                    let Some(code) = mouse::button_code(*button) else { continue };
+                                    let code = code | mouse::modifier_bits(modifiers);
+                                    let cell = screen_cell(*pos);
+                                    if *pressed && input_rect.contains(*pos) {
+                                        self.mouse_pressed = Some((code, cell));
+                                        reports.extend(mouse::encode(code, cell.0, cell.1, false, sgr));
+                                    } else if !*pressed && self.mouse_pressed.take().is_some() {
+                                        reports.extend(mouse::encode(code, cell.0, cell.1, true, sgr));
+                                    }
+                                }
+                                egui::Event::PointerMoved(pos) if tracking == MouseTracking::Drag => {
+                                    let cell = screen_cell(*pos);
+                                    if let Some((code, last)) = &mut self.mouse_pressed {
+                                        if *last != cell {
+                                            *last = cell;
+                                            reports.extend(mouse::encode(*code | mouse::MOTION, cell.0, cell.1, false, sgr));
+                                        }
+                                    }
+                                }
+                                egui::Event::MouseWheel { delta, modifiers, .. } if delta.y != 0.0 => {
+                                    let Some(pos) = i.pointer.hover_pos().filter(|p| input_rect.contains(*p)) else { continue };
+                                    let cell = screen_cell(pos);
+                                    let code = if delta.y > 0.0 { mouse::WHEEL_UP } else { mouse::WHEEL_DOWN };
+                                    reports.extend(mouse::encode(code | mouse::modifier_bits(modifiers), cell.0, cell.1, false, sgr));
+                                }
+                                _ => {}
+                            }
+                        }
+                    });
+                    if !reports.is_empty() {
+                        let mut writer = self.writer.lock();
+                        for report in reports {
+                            let _ = writer.write_all(&report);
+                        }
+                    }
+                }
 
                 // 2. Render Background (Middle Layer)
                 // This will overwrite/cover anything drawn by the TextEdit above.
                 let painter = ui.painter_at(rect);
-                painter.rect_filled(viewport.translate(rect.min.to_vec2()), 0.0, TERM_BG);
+                painter.rect_filled(viewport.translate(rect.min.to_vec2()), 0.0, scheme.background);
 
                 // 3. Handle Input Events
-                if input_response.has_focus() || input_response.lost_focus() {
+                if (input_response.has_focus() || input_response.lost_focus()) && mouse_reporting.is_none() {
                     if let Some(pos) = input_response.interact_pointer_pos() {
                         let rel_pos = pos - rect.min;
                         let col = (rel_pos.x / char_size.x).floor() as usize;
                         let row_idx = (rel_pos.y / char_size.y).floor() as usize;
                         
-                        if input_response.drag_started() {
+                        let shift = ui.input(|i| i.modifiers.shift);
+                        if input_response.triple_clicked() {
+                            // 三击选中整个逻辑行
+                            let (first, last) = self.state.lock().line_span(row_idx);
+                            self.selection_start = Some((first, 0));
+                            self.selection_end = Some((last, cols.saturating_sub(1)));
+                            self.drag_start = None;
+                        } else if input_response.double_clicked() {
+                            let word = self.state.lock().word_at(row_idx, col, &settings.word_delimiters);
+                            if let Some((start, end)) = word {
+                                self.selection_start = Some((row_idx, start));
+                                self.selection_end = Some((row_idx, end));
+                            }
+                            self.drag_start = None;
+                        } else if input_response.drag_started() {
                             self.drag_start = Some((row_idx, col));
-                            self.selection_start = Some((row_idx, col));
+                            if !(shift && self.selection_start.is_some()) {
+                                self.selection_start = Some((row_idx, col));
+                            }
                             self.selection_end = Some((row_idx, col));
                         } else if input_response.dragged() {
                             if let Some(_) = self.drag_start {
                                 self.selection_end = Some((row_idx, col));
                             }
+                        } else if input_response.clicked() && shift && self.selection_start.is_some() {
+                            // Shift + 单击把选区扩展到点击位置
+                            self.selection_end = Some((row_idx, col));
                         } else if input_response.clicked() {
                             self.selection_start = None;
                             self.selection_end = None;
@@ -628,13 +1517,40 @@ impl TabInstance for TerminalTab {
                     }
                 }
 
+                if mouse_reporting.is_none() {
+                    let shift = ui.input(|i| i.modifiers.shift);
+                    let selection_completed = input_response.drag_stopped()
+                        || input_response.double_clicked()
+                        || input_response.triple_clicked()
+                        || (input_response.clicked() && shift);
+                    if settings.copy_on_select && selection_completed {
+                        if let Some(text) = self.selection_text(false).filter(|t| !t.is_empty()) {
+                            ui.ctx().copy_text(text);
+                        }
+                    }
+                    if settings.paste_on_middle_click && input_response.middle_clicked() {
+                        // 剪贴板内容以 Paste 事件在下一帧送达
+                        input_response.request_focus();
+                        ui.ctx().send_viewport_cmd(egui::ViewportCommand::RequestPaste);
+                    }
+                }
+
+                if input_response.has_focus() {
+                    crate::claim_shortcut(ui.ctx(), find_shortcut);
+                    // Ctrl + P 是 shell 的上一条历史，不打开快速打开
+                    crate::claim_shortcut(ui.ctx(), egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::P));
+                }
+
                 if input_response.has_focus() || input_response.lost_focus() {
                     let mut writer = self.writer.lock();
                     let state = self.state.lock();
                     let is_app_mode = state.application_cursor;
+                    let bracketed_paste = state.bracketed_paste;
                     drop(state);
         
-                    let mut text_to_copy = None;
+                    let mut copy_requested = false;
+                    let mut clear_requested = false;
+                    let mut prompt_jump = None;
                     ui.input(|i| {
                         for event in &i.events {
                             match event {
@@ -647,6 +1563,8 @@ impl TabInstance for TerminalTab {
                                     }
                                 }
                                 egui::Event::Text(text) => {
+                                    // 分屏快捷键（如 Alt + Shift + =）产生的字符不写入 shell
+                                    if action.is_some() { continue; }
                                     let is_handled_control = if text.len() == 1 {
                                         let c = text.chars().next().unwrap();
                                         c == '\n' || c == '\r' || c == '\t' || c == '\x08' || c == '\x7f' || c == '\x1b'
@@ -654,33 +1572,31 @@ impl TabInstance for TerminalTab {
                                     if !is_handled_control { output_to_write.push_str(&text.replace("\n", "\r")); }
                                 }
                                 egui::Event::Paste(text) => {
-                                    if !self.is_composing { output_to_write.push_str(&text.replace("\n", "\r")); }
-                                }
-                                egui::Event::Copy => {
-                                    if let (Some(start), Some(end)) = (self.selection_start, self.selection_end) {
-                                        let (s, e) = if start <= end { (start, end) } else { (end, start) };
-                                        let state = self.state.lock();
-                                        let mut text = String::new();
-                                        let history_len = state.history.len();
-                                        let total_rows = history_len + state.rows;
-                                        for r in s.0..=e.0 {
-                                            if r >= total_rows { break; }
-                                            let cells = if r < history_len { &state.history[r] } else { &state.grid()[r - history_len] };
-                                            let c_start = if r == s.0 { s.1 } else { 0 };
-                                            let c_end = if r == e.0 { (e.1 + 1).min(cells.len()) } else { cells.len() };
-                                            for c in c_start..c_end {
-                                                if c < cells.len() {
-                                                    let cell = &cells[c];
-                                                    if !cell.is_wide_continuation { text.push(cell.c); }
-                                                } else { text.push(' '); }
-                                            }
-                                            if r != e.0 { text.push('\n'); }
-                                        }
-                                        text_to_copy = Some(text);
-                                    }
+                                    if !self.is_composing { output_to_write.push_str(&paste_sequence(text, bracketed_paste)); }
                                 }
+                                egui::Event::Copy => copy_requested = true,
                                 egui::Event::Key { key, pressed: true, modifiers, .. } => {
                                     if self.is_composing { continue; }
+                                    if *key == find_shortcut.logical_key && modifiers.matches_logically(find_shortcut.modifiers) {
+                                        open_find = true;
+                                        continue;
+                                    }
+                                    if let Some(pane_action) = PaneAction::from_key(*key, modifiers) {
+                                        action = Some(pane_action);
+                                        continue;
+                                    }
+                                    if *key == Key::K && modifiers.command && modifiers.shift {
+                                        clear_requested = true;
+                                        continue;
+                                    }
+                                    if matches!(key, Key::ArrowUp | Key::ArrowDown) && modifiers.command && modifiers.shift {
+                                        prompt_jump = Some(*key == Key::ArrowUp);
+                                        continue;
+                                    }
+                                    if exit_code.is_some() {
+                                        if *key == Key::Enter { action = Some(PaneAction::Restart); }
+                                        continue;
+                                    }
                                     if *key == Key::C && modifiers.ctrl {
                                         if self.selection_start.is_some() && self.selection_end.is_some() { continue; }
                                     }
@@ -726,17 +1642,33 @@ impl TabInstance for TerminalTab {
                             }
                         }
                     });
-                    if let Some(text) = text_to_copy { ui.output_mut(|o| o.copied_text = text); }
+                    if let Some(text) = copy_requested.then(|| self.selection_text(false)).flatten() {
+                        ui.output_mut(|o| o.copied_text = text);
+                    }
                     if !self.is_composing { self.input_buffer.clear(); }
-                    if !output_to_write.is_empty() { let _ = writer.write_all(output_to_write.as_bytes()); }
+                    if !output_to_write.is_empty() {
+                        let _ = writer.write_all(output_to_write.as_bytes());
+                        self.blink_epoch = ui.input(|i| i.time);
+                    }
+                    drop(writer);
+                    if clear_requested {
+                        self.clear_scrollback();
+                    }
+                    if let Some(previous) = prompt_jump {
+                        self.jump_to_prompt(previous);
+                    }
                 }
 
                 // Render visible content
+                let focused = input_response.has_focus();
+                has_focus = focused;
                 let state = self.state.lock();
                 let history = &state.history;
                 let grid = state.grid();
                 let start_row = (viewport.min.y / char_size.y).floor() as usize;
                 let end_row = (viewport.max.y / char_size.y).ceil() as usize;
+                self.viewport_top = start_row;
+                self.row_cache.begin_frame(ui);
 
                 for row_idx in start_row..end_row.min(total_rows) {
                     let cells = if row_idx < history.len() { &history[row_idx] } else { &grid[row_idx - history.len()] };
@@ -761,65 +1693,823 @@ impl TabInstance for TerminalTab {
                     let mut c_idx = 0;
                     while c_idx < cells.len().min(cols) {
                         let cell = &cells[c_idx];
-                        let mut bg = cell.bg;
-                        if cell.inverse { bg = if cell.fg == Color32::TRANSPARENT { TERM_FG } else { cell.fg }; }
+                        let bg = cell_bg(cell);
                         let start_x = c_idx;
                         c_idx += 1;
                         while c_idx < cells.len().min(cols) {
                             let next = &cells[c_idx];
-                            let mut next_bg = next.bg;
-                            if next.inverse { next_bg = if next.fg == Color32::TRANSPARENT { TERM_FG } else { next.fg }; }
-                            if next_bg != bg { break; }
+                            if cell_bg(next) != bg { break; }
                             c_idx += 1;
                         }
-                        if bg != Color32::TRANSPARENT && bg != TERM_BG {
+                        if let Some(bg) = bg.filter(|bg| *bg != scheme.background) {
                             let bg_rect = Rect::from_min_size(row_pos + Vec2::new(start_x as f32 * char_size.x, 0.0), Vec2::new((c_idx - start_x) as f32 * char_size.x, char_size.y));
                             painter.rect_filled(bg_rect, 0.0, bg);
                         }
                     }
 
-                    for (c_idx, cell) in cells.iter().enumerate().take(cols) {
-                        if cell.is_wide_continuation || cell.c == ' ' { continue; }
-                        let mut fg = cell.fg;
-                        if cell.inverse { fg = if cell.bg == Color32::TRANSPARENT { TERM_BG } else { cell.bg }; }
-                        if fg == Color32::TRANSPARENT { fg = TERM_FG; }
-                        let cell_pos = row_pos + Vec2::new(c_idx as f32 * char_size.x, 0.0);
-                        let mut job = LayoutJob::default();
-                        job.append(&cell.c.to_string(), 0.0, TextFormat { font_id: font_id.clone(), color: fg, ..Default::default() });
-                        painter.galley(cell_pos, ui.fonts(|f| f.layout_job(job)), Color32::TRANSPARENT);
+                    let cell_fg = |cell: &Cell| if cell.inverse { scheme.bg(cell.bg).unwrap_or(scheme.background) } else { scheme.fg(cell.fg) };
+                    for (c_idx, galley) in self.row_cache.row(ui, cells, cols, &font_id, &state.clusters, cell_fg) {
+                        let cell_pos = row_pos + Vec2::new(*c_idx as f32 * char_size.x, baseline_offset);
+                        painter.galley(cell_pos, galley.clone(), Color32::TRANSPARENT);
+                    }
+
+                    // 超链接下划线，连续的同一链接画成一条线
+                    let mut c_idx = 0;
+                    while c_idx < cells.len().min(cols) {
+                        let link = cells[c_idx].link;
+                        let start_x = c_idx;
+                        c_idx += 1;
+                        while c_idx < cells.len().min(cols) && cells[c_idx].link == link { c_idx += 1; }
+                        if link == 0 { continue; }
+                        let hovered = hovered_link.as_deref().is_some() && hovered_link.as_deref() == state.links.get(link as usize - 1).map(String::as_str);
+                        let y = row_pos.y + char_size.y - 1.0;
+                        let color = if hovered { scheme.foreground } else { scheme.foreground.gamma_multiply(0.6) };
+                        painter.hline(
+                            (row_pos.x + start_x as f32 * char_size.x)..=(row_pos.x + c_idx as f32 * char_size.x),
+                            y,
+                            egui::Stroke::new(1.0, color),
+                        );
+                    }
+
+                    if let Some((_, link, _)) = hovered_file.as_ref().filter(|(row, _, _)| *row == row_idx) {
+                        painter.hline(
+                            (row_pos.x + link.col as f32 * char_size.x)..=(row_pos.x + (link.col + link.len) as f32 * char_size.x),
+                            row_pos.y + char_size.y - 1.0,
+                            egui::Stroke::new(1.0, scheme.foreground),
+                        );
+                    }
+
+                    if let Some(find) = &self.find {
+                        let palette = crate::theme::palette(ui.ctx());
+                        let current = find.current_match();
+                        let first = find.matches.partition_point(|m| m.row < row_idx);
+                        for m in find.matches[first..].iter().take_while(|m| m.row == row_idx) {
+                            let highlight = Rect::from_min_size(
+                                row_pos + Vec2::new(m.col as f32 * char_size.x, 0.0),
+                                Vec2::new(m.len as f32 * char_size.x, char_size.y),
+                            );
+                            if current == Some(*m) {
+                                painter.rect_stroke(highlight, 0.0, egui::Stroke::new(1.5, palette.warning));
+                            } else {
+                                painter.rect_stroke(highlight, 0.0, egui::Stroke::new(1.0, palette.warning.gamma_multiply(0.6)));
+                            }
+                        }
                     }
 
                     if state.cursor_visible && (row_idx == (history.len() + state.cursor_row)) {
-                        let cursor_pos = row_pos + Vec2::new(state.cursor_col as f32 * char_size.x, 0.0);
-                        painter.rect_filled(Rect::from_min_size(cursor_pos, char_size), 0.0, Color32::from_gray(200).linear_multiply(0.5));
+                        // 光标下的字符；宽字符占两格时光标同样覆盖两格
+                        let cursor_cell = grid.get(state.cursor_row).and_then(|row| row.get(state.cursor_col)).copied();
+                        let cursor_wide = grid.get(state.cursor_row)
+                            .and_then(|row| row.get(state.cursor_col + 1))
+                            .is_some_and(|cell| cell.is_wide_continuation);
+                        let cursor_width = if cursor_wide { 2.0 } else { 1.0 };
+                        let cursor_rect = Rect::from_min_size(
+                            row_pos + Vec2::new(state.cursor_col as f32 * char_size.x, 0.0),
+                            Vec2::new(char_size.x * cursor_width, char_size.y),
+                        );
+                        let style = state.cursor_style.unwrap_or(settings.cursor);
+                        let cursor_color = scheme.cursor;
+                        if !focused {
+                            // 失去焦点时统一显示空心方块，且不闪烁
+                            painter.rect_stroke(cursor_rect.shrink(0.5), 0.0, egui::Stroke::new(1.0, cursor_color.linear_multiply(0.7)));
+                        } else {
+                            let elapsed = ui.input(|i| i.time) - self.blink_epoch;
+                            let lit = !style.blink || elapsed % CURSOR_BLINK_PERIOD < CURSOR_BLINK_PERIOD / 2.0;
+                            if style.blink {
+                                let until_toggle = CURSOR_BLINK_PERIOD / 2.0 - elapsed % (CURSOR_BLINK_PERIOD / 2.0);
+                                ui.ctx().request_repaint_after(std::time::Duration::from_secs_f64(until_toggle));
+                            }
+                            if lit {
+                                let shape_rect = match style.shape {
+                                    CursorShape::Block => cursor_rect,
+                                    CursorShape::Beam => Rect::from_min_size(cursor_rect.min, Vec2::new(2.0, char_size.y)),
+                                    CursorShape::Underline => Rect::from_min_max(cursor_rect.left_bottom() - Vec2::new(0.0, 2.0), cursor_rect.max),
+                                };
+                                painter.rect_filled(shape_rect, 0.0, cursor_color);
+                                // 实心方块光标遮住了字符，用底色重新绘制
+                                if style.shape == CursorShape::Block {
+                                    if let Some(cell) = cursor_cell.filter(|cell| cell.c != ' ' || cell.cluster != 0) {
+                                        let mut text = String::new();
+                                        state.push_cell_text(&mut text, &cell);
+                                        let galley = ui.fonts(|f| f.layout_no_wrap(text, font_id.clone(), scheme.background));
+                                        painter.galley(cursor_rect.min + Vec2::new(0.0, baseline_offset), galley, Color32::TRANSPARENT);
+                                    }
+                                }
+                            }
+                        }
                     }
                 }
+                self.row_cache.end_frame();
             });
 
-        ui.ctx().request_repaint();
+        if open_find {
+            match &mut self.find {
+                Some(find) => find.focus_pending = true,
+                None => self.find = Some(TerminalFind::new()),
+            }
+        }
+
+        (has_focus, action)
+    }
+
+    fn run_in_terminal(&mut self, cwd: &Path, program: &str, args: &[String]) {
+        self.state.lock().cwd = Some(cwd.to_path_buf());
+        let mut line = command_line(cwd, program, args);
+        line.push('\r');
+        let _ = self.writer.lock().write_all(line.as_bytes());
+        self.ctx.request_repaint();
+    }
+
+    fn kill(&self) {
+        // 进程可能已经退出（或被共享该进程的副本结束），忽略错误
+        let _ = self.killer.lock().kill();
+    }
+
+    /// 中断通过 PTY 写入 Ctrl + C，由终端驱动转发给前台进程；
+    /// 结束信号发给前台进程组，强制结束针对 shell 本身
+    fn send_signal(&self, signal: TerminalSignal) -> std::io::Result<()> {
+        match signal {
+            TerminalSignal::Interrupt => self.writer.lock().write_all(b"\x03"),
+            #[cfg(unix)]
+            TerminalSignal::Terminate => {
+                let group = self.master.lock().process_group_leader()
+                    .map(|leader| format!("-{}", leader))
+                    .or_else(|| self.pid.map(|pid| pid.to_string()))
+                    .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::NotFound, "no foreground process"))?;
+                signal_process("-TERM", &group)
+            }
+            #[cfg(unix)]
+            TerminalSignal::Kill => match self.pid {
+                Some(pid) => signal_process("-KILL", &pid.to_string()),
+                None => self.killer.lock().kill(),
+            },
+            // Windows 的伪终端无法单独向前台进程发送结束信号，直接结束 shell
+            #[cfg(not(unix))]
+            TerminalSignal::Terminate | TerminalSignal::Kill => self.killer.lock().kill(),
+        }
+    }
+}
+
+#[cfg(unix)]
+fn signal_process(signal: &str, target: &str) -> std::io::Result<()> {
+    let status = std::process::Command::new("kill").args([signal, "--", target]).status()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(std::io::Error::new(std::io::ErrorKind::Other, format!("kill {} {} failed", signal, target)))
+    }
+}
+
+/// 终端标签页：一个或多个窗格按分割树排列
+#[derive(Clone)]
+pub struct TerminalTab {
+    panes: Vec<TerminalPane>,
+    layout: PaneNode,
+    focused: usize,
+    next_pane_id: usize,
+    /// 新建分屏时沿用的启动方案
+    profile: TerminalProfile,
+    /// 上一帧各窗格的区域，用于键盘切换窗格
+    pane_rects: Vec<(usize, Rect)>,
+    /// 关闭后重新打开或复制出的标签页，下一次绘制时在原目录重新启动所有窗格
+    respawn: bool,
+}
+
+impl std::fmt::Debug for TerminalTab {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TerminalTab").field("panes", &self.panes.len()).finish()
+    }
+}
+
+impl TerminalTab {
+    fn focused_pane(&mut self) -> Option<&mut TerminalPane> {
+        let focused = self.focused;
+        self.panes.iter_mut().find(|p| p.id == focused)
+    }
+
+    /// 在窗格的当前目录重新启动 shell，替换原来的窗格
+    fn restart(&mut self, ctx: &egui::Context, pane_id: usize, control: &mut Vec<AppCommand>) -> Option<&mut TerminalPane> {
+        let pos = self.panes.iter().position(|p| p.id == pane_id)?;
+        let old = &self.panes[pos];
+        let cwd = old.state.lock().cwd.clone().unwrap_or_else(|| old.cwd.clone());
+        match spawn_pane(ctx.clone(), pane_id, &self.profile, Some(&cwd), old.settings.clone()) {
+            Ok(pane) => {
+                self.panes[pos] = pane;
+                Some(&mut self.panes[pos])
+            }
+            Err(e) => {
+                control.push(AppCommand::Notify {
+                    message: format!("Failed to start {}: {}", self.profile.shell, e),
+                    level: crate::NotificationLevel::Error,
+                });
+                None
+            }
+        }
+    }
+
+    fn apply(&mut self, ctx: &egui::Context, pane_id: usize, action: PaneAction, control: &mut Vec<AppCommand>) {
+        match action {
+            PaneAction::Split(dir) => {
+                let Some(source) = self.panes.iter().find(|p| p.id == pane_id) else { return };
+                // 新窗格从源窗格的当前目录启动
+                let cwd = source.state.lock().cwd.clone().unwrap_or_else(|| source.cwd.clone());
+                let settings = source.settings.clone();
+                let id = self.next_pane_id;
+                match spawn_pane(ctx.clone(), id, &self.profile, Some(&cwd), settings) {
+                    Ok(mut pane) => {
+                        self.next_pane_id += 1;
+                        self.layout.split(pane_id, dir, id);
+                        pane.focus_pending = true;
+                        self.panes.push(pane);
+                        self.focused = id;
+                    }
+                    Err(e) => control.push(AppCommand::Notify {
+                        message: format!("Failed to start {}: {}", self.profile.shell, e),
+                        level: crate::NotificationLevel::Error,
+                    }),
+                }
+            }
+            PaneAction::Focus(direction) => {
+                if let Some(target) = panes::neighbor(&self.pane_rects, pane_id, direction) {
+                    self.focused = target;
+                    if let Some(pane) = self.focused_pane() {
+                        pane.focus_pending = true;
+                    }
+                }
+            }
+            PaneAction::Resize(direction) => {
+                self.layout.resize(pane_id, direction);
+            }
+            PaneAction::Restart => {
+                if let Some(pane) = self.restart(ctx, pane_id, control) {
+                    pane.focus_pending = true;
+                    self.focused = pane_id;
+                }
+            }
+            PaneAction::Close => {
+                // 只剩一个窗格时不能关闭（菜单项已禁用），应关闭整个标签页
+                if self.panes.len() > 1 && self.layout.remove(pane_id) {
+                    if let Some(pos) = self.panes.iter().position(|p| p.id == pane_id) {
+                        self.panes.remove(pos).kill();
+                    }
+                    self.focused = self.panes[0].id;
+                    self.panes[0].focus_pending = true;
+                }
+            }
+        }
+    }
+}
+
+impl TabInstance for TerminalTab {
+    fn title(&self) -> WidgetText {
+        let Some(pane) = self.panes.iter().find(|p| p.id == self.focused) else { return "Terminal".into() };
+        let state = pane.state.lock();
+        let title = state.title.clone().unwrap_or_else(|| "Terminal".into());
+        // 录制中的窗格在标题前加上标记
+        if state.log.is_some() { format!("⏺ {}", title).into() } else { title.into() }
+    }
+
+    fn icon(&self) -> Option<crate::TabIcon> { Some(crate::TabIcon::Emoji("🖥".into())) }
+
+    fn ui(&mut self, ui: &mut Ui, control: &mut Vec<AppCommand>) {
+        if std::mem::take(&mut self.respawn) {
+            let ids: Vec<usize> = self.panes.iter().map(|p| p.id).collect();
+            for id in ids {
+                self.restart(ui.ctx(), id, control);
+            }
+        }
+
+        let rect = ui.available_rect_before_wrap();
+        let mut rects = Vec::new();
+        self.layout.layout(ui, rect, &mut rects);
+
+        let split = rects.len() > 1;
+        let mut action = None;
+        for (id, pane_rect) in &rects {
+            let Some(pane) = self.panes.iter_mut().find(|p| p.id == *id) else { continue };
+            let mut pane_ui = ui.new_child(
+                egui::UiBuilder::new()
+                    .max_rect(*pane_rect)
+                    .id_salt(("terminal_pane", *id))
+                    .layout(*ui.layout()),
+            );
+            pane_ui.set_clip_rect(pane_rect.intersect(ui.clip_rect()));
+            let (has_focus, pane_action) = pane.ui(&mut pane_ui, split, control);
+            if has_focus {
+                self.focused = *id;
+            }
+            if let Some(pane_action) = pane_action {
+                action = Some((*id, pane_action));
+            }
+            if split && *id == self.focused {
+                ui.painter().rect_stroke(pane_rect.shrink(0.5), 0.0, ui.visuals().selection.stroke);
+            }
+        }
+        ui.allocate_rect(rect, Sense::hover());
+        self.pane_rects = rects;
+
+        if let Some((id, action)) = action {
+            self.apply(ui.ctx(), id, action, control);
+        }
+
+        // 输出由读取线程主动触发刷新，这里仅按配置的间隔保持后台刷新
+        ui.ctx().request_repaint_after(crate::power::PowerProfile::get(ui.ctx()).background_repaint);
+    }
+
+    fn needs_attention(&mut self) -> bool {
+        // 取走所有窗格的响铃标记
+        self.panes.iter().fold(false, |bell, pane| std::mem::take(&mut pane.state.lock().bell) || bell)
+    }
+
+    fn run_in_terminal(&mut self, cwd: &Path, program: &str, args: &[String]) -> bool {
+        // 远程 shell 中没有本地路径，已退出的窗格无法接收输入，都交给新的本地终端
+        if self.profile.ssh.is_some() {
+            return false;
+        }
+        match self.focused_pane() {
+            Some(pane) if pane.state.lock().exit_code.is_none() => {
+                pane.run_in_terminal(cwd, program, args);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn send_signal(&mut self, signal: TerminalSignal) -> Result<(), String> {
+        let pane = self.focused_pane().ok_or("No terminal pane is focused")?;
+        pane.send_signal(signal).map_err(|e| e.to_string())
+    }
+
+    fn on_app_exit(&mut self) {
+        for pane in &self.panes {
+            pane.kill();
+        }
+    }
+
+    fn on_zoom_changed(&mut self, _zoom: f32) {
+        // 缩放后可用的逻辑尺寸改变，下一帧强制重新计算网格并同步 PTY 尺寸
+        for pane in &mut self.panes {
+            pane.last_size = (0, 0);
+        }
+    }
+
+    fn on_close(&mut self) {
+        // 关闭的标签页只保留布局与目录，重新打开时启动新的 shell
+        for pane in &self.panes {
+            pane.kill();
+        }
+        self.respawn = true;
     }
 
     fn box_clone(&self) -> Box<dyn TabInstance> {
         Box::new(self.clone())
     }
+
+    fn duplicate(&self) -> Box<dyn TabInstance> {
+        // 副本不与原标签页共用进程，在相同目录启动新的 shell
+        Box::new(Self { respawn: true, ..self.clone() })
+    }
 }
 
-pub struct TerminalPlugin;
+pub struct TerminalPlugin {
+    config: TerminalConfig,
+    /// 与所有终端标签页共享的默认光标样式和配色，设置修改后立即生效
+    settings: Arc<Mutex<PaneSettings>>,
+    /// 设置界面中正在编辑的方案
+    selected_profile: usize,
+    /// 终端字体需要（重新）注册
+    font_pending: bool,
+    /// 配置的字体族在系统中找不到
+    font_missing: bool,
+    /// 系统等宽字体列表，首次打开字体选择框时加载
+    monospace_families: Option<Vec<String>>,
+}
+
+impl TerminalPlugin {
+    /// 加载配置的字体并注册为 `TERMINAL_FONT`，找不到时窗格回退到内置等宽字体
+    fn install_font(&mut self, ctx: &egui::Context) {
+        self.font_pending = false;
+        let data = (!self.config.font_family.is_empty())
+            .then(|| crate::fonts::load_system_family(&self.config.font_family))
+            .flatten();
+        self.font_missing = !self.config.font_family.is_empty() && data.is_none();
+        crate::fonts::set_named_family(ctx, TERMINAL_FONT, data);
+    }
+}
 
 impl Plugin for TerminalPlugin {
     fn name(&self) -> &str { crate::plugins::PLUGIN_NAME_TERMINAL }
 
+    fn create_terminal(&mut self, ctx: &egui::Context, cwd: &Path) -> Option<Box<dyn TabInstance>> {
+        // 以本地目录为起点，SSH 方案不适用
+        let profile = self.config.local_profile();
+        create_terminal_tab(ctx.clone(), &profile, Some(cwd), self.settings.clone()).ok().map(|tab| Box::new(tab) as Box<dyn TabInstance>)
+    }
+
+    fn on_global_ui(&mut self, ctx: &egui::Context, _control: &mut Vec<AppCommand>) {
+        // 字体需要 egui 上下文才能注册，放在首帧进行
+        if self.font_pending {
+            self.install_font(ctx);
+        }
+    }
+
     fn on_tab_menu(&mut self, ui: &mut Ui, control: &mut Vec<AppCommand>) {
-        if ui.button("New Terminal").clicked() {
-            if let Ok(tab) = create_terminal_tab(ui.ctx().clone()) {
-                control.push(AppCommand::OpenTab(Tab::new(Box::new(tab))));
+        let default_name = self.config.default_profile().name;
+        ui.menu_button("New Terminal", |ui| {
+            let mut chosen = None;
+            for profile in &self.config.profiles {
+                let label = if profile.name == default_name {
+                    format!("{} (default)", profile.name)
+                } else {
+                    profile.name.clone()
+                };
+                if ui.button(label).on_hover_text(&profile.shell).clicked() {
+                    chosen = Some(profile.clone());
+                }
+            }
+            if self.config.profiles.is_empty() && ui.button("Default Shell").clicked() {
+                chosen = Some(self.config.default_profile());
+            }
+            if let Some(profile) = chosen {
+                match create_terminal_tab(ui.ctx().clone(), &profile, None, self.settings.clone()) {
+                    Ok(tab) => control.push(AppCommand::OpenTab(Tab::new(Box::new(tab)))),
+                    Err(e) => control.push(AppCommand::Notify {
+                        message: format!("Failed to start {}: {}", profile.shell, e),
+                        level: crate::NotificationLevel::Error,
+                    }),
+                }
+                ui.close_menu();
+            }
+        });
+    }
+
+    fn on_settings_ui(&mut self, ui: &mut Ui) {
+        ui.vertical(|ui| {
+            ui.heading("Terminal Settings");
+            ui.add_space(4.0);
+
+            let mut changed = false;
+            ui.group(|ui| {
+                ui.label("Profiles");
+                ui.weak("Each profile appears under Tab > New Terminal. The default profile is also used when other plugins open a terminal.");
+                ui.add_space(4.0);
+
+                ui.horizontal(|ui| {
+                    let selected_name = self.config.profiles.get(self.selected_profile).map(|p| p.name.clone()).unwrap_or_default();
+                    egui::ComboBox::from_id_salt("terminal_profile_select")
+                        .selected_text(selected_name)
+                        .show_ui(ui, |ui| {
+                            for (i, profile) in self.config.profiles.iter().enumerate() {
+                                ui.selectable_value(&mut self.selected_profile, i, &profile.name);
+                            }
+                        });
+                    if ui.button("➕ Add").clicked() {
+                        let mut profile = self.config.default_profile();
+                        profile.name = unique_profile_name(&self.config.profiles, "New Profile");
+                        self.config.profiles.push(profile);
+                        self.selected_profile = self.config.profiles.len() - 1;
+                        changed = true;
+                    }
+                    if ui.add_enabled(self.config.profiles.len() > 1, egui::Button::new("🗑 Remove")).clicked() {
+                        self.config.profiles.remove(self.selected_profile);
+                        self.selected_profile = self.selected_profile.min(self.config.profiles.len().saturating_sub(1));
+                        changed = true;
+                    }
+                });
+
+                let default_name = self.config.default_profile().name;
+                if let Some(profile) = self.config.profiles.get_mut(self.selected_profile) {
+                    let was_default = profile.name == default_name;
+                    ui.add_space(4.0);
+                    changed |= profile_editor_ui(ui, profile);
+                    if was_default && profile.name != default_name {
+                        // 重命名默认方案时保持其默认状态
+                        self.config.default_profile = profile.name.clone();
+                    }
+                    ui.add_space(4.0);
+                    if was_default {
+                        ui.weak("This is the default profile.");
+                    } else if ui.button("Set as Default").clicked() {
+                        self.config.default_profile = profile.name.clone();
+                        changed = true;
+                    }
+                }
+            });
+
+            ui.add_space(8.0);
+
+            ui.group(|ui| {
+                ui.label("Cursor");
+                ui.horizontal(|ui| {
+                    ui.label("Style");
+                    egui::ComboBox::from_id_salt("terminal_cursor_shape")
+                        .selected_text(self.config.cursor.shape.label())
+                        .show_ui(ui, |ui| {
+                            for shape in CursorShape::ALL {
+                                changed |= ui.selectable_value(&mut self.config.cursor.shape, shape, shape.label()).changed();
+                            }
+                        });
+                    changed |= ui.checkbox(&mut self.config.cursor.blink, "Blink").changed();
+                });
+                ui.weak("Applications may change the cursor style with DECSCUSR. Unfocused terminals show a hollow block.");
+            });
+
+            ui.add_space(8.0);
+
+            ui.group(|ui| {
+                ui.label("Colors");
+                ui.horizontal(|ui| {
+                    ui.label("Scheme");
+                    egui::ComboBox::from_id_salt("terminal_color_scheme")
+                        .selected_text(self.config.color_scheme.label())
+                        .show_ui(ui, |ui| {
+                            for scheme in SchemeId::ALL {
+                                changed |= ui.selectable_value(&mut self.config.color_scheme, scheme, scheme.label()).changed();
+                            }
+                        });
+                });
+                let scheme = self.config.color_scheme.scheme(ui.visuals().dark_mode);
+                scheme_preview(ui, &scheme);
+            });
+
+            ui.add_space(8.0);
+
+            ui.group(|ui| {
+                ui.label("Font");
+                egui::Grid::new("terminal_font_grid").num_columns(2).spacing([8.0, 4.0]).show(ui, |ui| {
+                    ui.label("Family");
+                    let selected = if self.config.font_family.is_empty() { "Built-in Monospace" } else { self.config.font_family.as_str() };
+                    let mut family_changed = false;
+                    egui::ComboBox::from_id_salt("terminal_font_family")
+                        .selected_text(selected)
+                        .show_ui(ui, |ui| {
+                            family_changed |= ui.selectable_value(&mut self.config.font_family, String::new(), "Built-in Monospace").changed();
+                            let families = self.monospace_families.get_or_insert_with(crate::fonts::monospace_families);
+                            for family in families.iter() {
+                                family_changed |= ui.selectable_value(&mut self.config.font_family, family.clone(), family).changed();
+                            }
+                        });
+                    if family_changed {
+                        self.install_font(ui.ctx());
+                        changed = true;
+                    }
+                    ui.end_row();
+
+                    ui.label("Size");
+                    changed |= ui.add(egui::DragValue::new(&mut self.config.font_size).range(MIN_FONT_SIZE..=MAX_FONT_SIZE).speed(0.5).suffix(" pt")).changed();
+                    ui.end_row();
+
+                    ui.label("Line spacing");
+                    changed |= ui.add(egui::Slider::new(&mut self.config.line_spacing, MIN_LINE_SPACING..=MAX_LINE_SPACING).step_by(0.05)).changed();
+                    ui.end_row();
+                });
+                if self.font_missing {
+                    ui.colored_label(crate::theme::palette(ui.ctx()).warning, format!("Font \"{}\" was not found; using the built-in monospace font.", self.config.font_family));
+                }
+                ui.weak("Ctrl + scroll zooms a single terminal pane; Reset Zoom in its context menu restores the size above.");
+            });
+
+            ui.add_space(8.0);
+
+            ui.group(|ui| {
+                ui.label("Selection");
+                ui.horizontal(|ui| {
+                    ui.label("Word delimiters");
+                    changed |= ui.add(egui::TextEdit::singleline(&mut self.config.word_delimiters).font(egui::TextStyle::Monospace)).changed();
+                });
+                changed |= ui.checkbox(&mut self.config.copy_on_select, "Copy on select").changed();
+                changed |= ui.checkbox(&mut self.config.paste_on_middle_click, "Paste on middle click").changed();
+                ui.weak("Double-click selects a word, triple-click selects a line and Shift + click extends the selection. Whitespace always separates words.");
+            });
+
+            ui.add_space(8.0);
+
+            ui.group(|ui| {
+                ui.label("Scrollback");
+                ui.horizontal(|ui| {
+                    let mut unlimited = self.config.scrollback_lines == 0;
+                    if ui.checkbox(&mut unlimited, "Unlimited").changed() {
+                        self.config.scrollback_lines = if unlimited { 0 } else { DEFAULT_SCROLLBACK };
+                        changed = true;
+                    }
+                    if !unlimited {
+                        changed |= ui.add(egui::DragValue::new(&mut self.config.scrollback_lines).range(100..=1_000_000).suffix(" lines")).changed();
+                    }
+                });
+                ui.weak("Unlimited scrollback keeps all output in memory. Use Clear Scrollback (Ctrl+Shift+K) to free it.");
+            });
+
+            if changed {
+                *self.settings.lock() = self.config.pane_settings();
+                self.config.save();
             }
-            ui.close_menu();
+        });
+    }
+}
+
+/// 配色预览：底色上显示前景文字与 16 色调色板
+fn scheme_preview(ui: &mut Ui, scheme: &ColorScheme) {
+    let swatch = Vec2::splat(16.0);
+    egui::Frame::none().fill(scheme.background).inner_margin(6.0).show(ui, |ui| {
+        ui.label(RichText::new("user@verbium:~$ ls").monospace().color(scheme.foreground));
+        for row in scheme.ansi.chunks(8) {
+            ui.horizontal(|ui| {
+                ui.spacing_mut().item_spacing.x = 2.0;
+                for color in row {
+                    let (rect, _) = ui.allocate_exact_size(swatch, Sense::hover());
+                    ui.painter().rect_filled(rect, 2.0, *color);
+                }
+            });
         }
+    });
+}
+
+/// 单个方案的编辑表单，返回是否有修改
+fn profile_editor_ui(ui: &mut Ui, profile: &mut TerminalProfile) -> bool {
+    let mut changed = false;
+    egui::Grid::new("terminal_profile_grid").num_columns(2).spacing([8.0, 4.0]).show(ui, |ui| {
+        ui.label("Name");
+        changed |= ui.text_edit_singleline(&mut profile.name).changed();
+        ui.end_row();
+
+        ui.label("Connection");
+        ui.horizontal(|ui| {
+            let mut remote = profile.ssh.is_some();
+            changed |= ui.radio_value(&mut remote, false, "Local Shell").changed();
+            changed |= ui.radio_value(&mut remote, true, "SSH").changed();
+            if remote != profile.ssh.is_some() {
+                profile.ssh = remote.then(SshTarget::default);
+                // 本地 shell 参数与 ssh 选项互不适用
+                profile.args.clear();
+            }
+        });
+        ui.end_row();
+
+        if let Some(target) = &mut profile.ssh {
+            ui.label("Host");
+            ui.horizontal(|ui| {
+                changed |= ui.add(egui::TextEdit::singleline(&mut target.host).desired_width(200.0).hint_text("example.com")).changed();
+                ui.label("Port");
+                changed |= ui.add(egui::DragValue::new(&mut target.port).range(1..=65535)).changed();
+            });
+            ui.end_row();
+
+            ui.label("User");
+            changed |= ui.add(egui::TextEdit::singleline(&mut target.user).hint_text("Default")).changed();
+            ui.end_row();
+
+            ui.label("Identity File");
+            ui.horizontal(|ui| {
+                let key_str = target.identity_file.as_ref()
+                    .map(|p| p.to_string_lossy().to_string())
+                    .unwrap_or_else(|| "Agent / SSH config / password".into());
+                ui.label(key_str);
+                if ui.button("Select...").clicked() {
+                    if let Some(path) = rfd::FileDialog::new().pick_file() {
+                        target.identity_file = Some(path);
+                        changed = true;
+                    }
+                }
+                if target.identity_file.is_some() && ui.button("Reset").clicked() {
+                    target.identity_file = None;
+                    changed = true;
+                }
+            });
+            ui.end_row();
+
+            ui.label("SSH Options");
+            changed |= ui.text_edit_singleline(&mut profile.args)
+                .on_hover_text("Extra options passed to ssh before the host, e.g. -o ServerAliveInterval=30. Requires an ssh client on PATH.")
+                .changed();
+            ui.end_row();
+        } else {
+            ui.label("Shell");
+            changed |= ui.text_edit_singleline(&mut profile.shell).changed();
+            ui.end_row();
+
+            ui.label("Arguments");
+            changed |= ui.text_edit_singleline(&mut profile.args)
+                .on_hover_text("Separated by spaces; wrap arguments containing spaces in double quotes.")
+                .changed();
+            ui.end_row();
+        }
+
+        ui.label("Starting Directory");
+        ui.horizontal(|ui| {
+            let path_str = profile.cwd.as_ref()
+                .map(|p| p.to_string_lossy().to_string())
+                .unwrap_or_else(|| "Current working directory".into());
+            ui.label(path_str);
+            if ui.button("Select...").clicked() {
+                if let Some(path) = rfd::FileDialog::new().pick_folder() {
+                    profile.cwd = Some(path);
+                    changed = true;
+                }
+            }
+            if profile.cwd.is_some() && ui.button("Reset").clicked() {
+                profile.cwd = None;
+                changed = true;
+            }
+        });
+        ui.end_row();
+    });
+
+    ui.add_space(4.0);
+    ui.label("Environment Variables");
+    let mut remove = None;
+    egui::Grid::new("terminal_profile_env").num_columns(3).spacing([8.0, 4.0]).show(ui, |ui| {
+        for (i, var) in profile.env.iter_mut().enumerate() {
+            changed |= ui.add(egui::TextEdit::singleline(&mut var.name).desired_width(140.0).hint_text("NAME")).changed();
+            changed |= ui.add(egui::TextEdit::singleline(&mut var.value).desired_width(220.0).hint_text("value")).changed();
+            if ui.small_button("🗑").clicked() {
+                remove = Some(i);
+            }
+            ui.end_row();
+        }
+    });
+    if let Some(i) = remove {
+        profile.env.remove(i);
+        changed = true;
+    }
+    if ui.small_button("➕ Add Variable").clicked() {
+        profile.env.push(EnvVar::default());
+        changed = true;
     }
+    changed
 }
 
-fn create_terminal_tab(ctx: egui::Context) -> anyhow::Result<TerminalTab> {
+fn unique_profile_name(profiles: &[TerminalProfile], base: &str) -> String {
+    std::iter::once(base.to_string())
+        .chain((2..).map(|n| format!("{} {}", base, n)))
+        .find(|name| !profiles.iter().any(|p| &p.name == name))
+        .unwrap_or_default()
+}
+
+/// 按 shell 语法拼接命令行：先切换到 cwd，再执行 program（为空时只切换目录）
+fn command_line(cwd: &Path, program: &str, args: &[String]) -> String {
+    #[cfg(windows)]
+    {
+        // PowerShell：单引号字符串内用两个单引号转义
+        let quote = |s: &str| format!("'{}'", s.replace('\'', "''"));
+        let mut line = format!("Set-Location -LiteralPath {}", quote(&cwd.to_string_lossy()));
+        if !program.is_empty() {
+            line.push_str("; & ");
+            line.push_str(&quote(program));
+            for arg in args {
+                line.push(' ');
+                line.push_str(&quote(arg));
+            }
+        }
+        line
+    }
+    #[cfg(not(windows))]
+    {
+        let quote = |s: &str| {
+            if !s.is_empty() && s.chars().all(|c| c.is_ascii_alphanumeric() || "-_./=:,+@".contains(c)) {
+                s.to_string()
+            } else {
+                format!("'{}'", s.replace('\'', "'\\''"))
+            }
+        };
+        let mut line = format!("cd {}", quote(&cwd.to_string_lossy()));
+        if !program.is_empty() {
+            line.push_str(" && ");
+            line.push_str(&quote(program));
+            for arg in args {
+                line.push(' ');
+                line.push_str(&quote(arg));
+            }
+        }
+        line
+    }
+}
+
+/// 按方案启动终端；`cwd` 不为空时覆盖方案的起始目录
+fn create_terminal_tab(
+    ctx: egui::Context,
+    profile: &TerminalProfile,
+    cwd: Option<&Path>,
+    settings: Arc<Mutex<PaneSettings>>,
+) -> anyhow::Result<TerminalTab> {
+    let pane = spawn_pane(ctx, 0, profile, cwd, settings)?;
+    Ok(TerminalTab {
+        panes: vec![pane],
+        layout: PaneNode::Leaf(0),
+        focused: 0,
+        next_pane_id: 1,
+        profile: profile.clone(),
+        pane_rects: Vec::new(),
+        respawn: false,
+    })
+}
+
+fn spawn_pane(
+    ctx: egui::Context,
+    id: usize,
+    profile: &TerminalProfile,
+    cwd: Option<&Path>,
+    settings: Arc<Mutex<PaneSettings>>,
+) -> anyhow::Result<TerminalPane> {
     let pty_system = native_pty_system();
     let pair = pty_system.openpty(PtySize {
         rows: 24,
@@ -828,12 +2518,13 @@ fn create_terminal_tab(ctx: egui::Context) -> anyhow::Result<TerminalTab> {
         pixel_height: 0,
     })?;
 
-    #[cfg(windows)]
-    let cmd = CommandBuilder::new("powershell.exe");
-    #[cfg(not(windows))]
-    let cmd = CommandBuilder::new("bash");
-
-    let mut _child = pair.slave.spawn_command(cmd)?;
+    let mut child = pair.slave.spawn_command(profile.command(cwd))?;
+    let start_dir = cwd.map(Path::to_path_buf)
+        .or_else(|| profile.cwd.clone())
+        .or_else(|| std::env::current_dir().ok())
+        .unwrap_or_default();
+    let killer = child.clone_killer();
+    let pid = child.process_id();
     
     let writer = pair.master.take_writer()?;
     let mut reader = pair.master.try_clone_reader()?;
@@ -849,19 +2540,33 @@ fn create_terminal_tab(ctx: egui::Context) -> anyhow::Result<TerminalTab> {
             if n == 0 { break; }
             {
                 let mut s = s_thread.lock();
+                if let Some(log) = &mut s.log { log.raw(&buffer[..n]); }
                 let mut handler = LogHandler { state: &mut *s };
                 for byte in &buffer[..n] {
                     parser.advance(&mut handler, *byte);
                 }
+                if let Some(log) = &mut s.log { log.flush(); }
+                s.generation = s.generation.wrapping_add(1);
             }
             ctx_thread.request_repaint();
         }
     });
 
-    Ok(TerminalTab {
+    // 等待 shell 退出并记录退出码，窗格据此显示重新启动提示
+    let s_wait = state.clone();
+    let ctx_wait = ctx.clone();
+    std::thread::spawn(move || {
+        let code = child.wait().map(|status| status.exit_code()).unwrap_or(1);
+        s_wait.lock().exit_code = Some(code);
+        ctx_wait.request_repaint();
+    });
+
+    Ok(TerminalPane {
+        id,
         state,
         writer: Arc::new(Mutex::new(writer)),
         master: Arc::new(Mutex::new(pair.master)),
+        killer: Arc::new(Mutex::new(killer)),
         last_size: (80, 24),
         ctx,
         input_buffer: String::new(),
@@ -869,10 +2574,29 @@ fn create_terminal_tab(ctx: egui::Context) -> anyhow::Result<TerminalTab> {
         selection_start: None,
         selection_end: None,
         drag_start: None,
+        find: None,
+        settings,
+        blink_epoch: 0.0,
+        cwd: start_dir,
+        focus_pending: false,
+        mouse_pressed: None,
+        row_cache: RowCache::default(),
+        pid,
+        viewport_top: 0,
+        scroll_to_row: None,
+        font_zoom: 0.0,
     })
 }
 
 
 pub fn create() -> TerminalPlugin {
-    TerminalPlugin
+    let config = TerminalConfig::load();
+    TerminalPlugin {
+        settings: Arc::new(Mutex::new(config.pane_settings())),
+        font_pending: !config.font_family.is_empty(),
+        config,
+        selected_profile: 0,
+        font_missing: false,
+        monospace_families: None,
+    }
 }
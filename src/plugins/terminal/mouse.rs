@@ -0,0 +1,52 @@
+use egui::{Modifiers, PointerButton};
+
+// ----------------------------------------------------------------------------
+// 鼠标上报（DECSET 1000 / 1002 / 1006）
+// ----------------------------------------------------------------------------
+
+/// 应用请求的鼠标事件范围
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MouseTracking {
+    /// 1000：仅按下与释放
+    Click,
+    /// 1002：另外上报按住按钮时的移动
+    Drag,
+}
+
+/// 滚轮在上报协议中的按钮编号
+pub const WHEEL_UP: u8 = 64;
+pub const WHEEL_DOWN: u8 = 65;
+/// 按住按钮移动时加在按钮编号上的标志
+pub const MOTION: u8 = 32;
+
+pub fn button_code(button: PointerButton) -> Option<u8> {
+    match button {
+        PointerButton::Primary => Some(0),
+        PointerButton::Middle => Some(1),
+        PointerButton::Secondary => Some(2),
+        _ => None,
+    }
+}
+
+pub fn modifier_bits(modifiers: &Modifiers) -> u8 {
+    let mut bits = 0;
+    if modifiers.shift { bits |= 4; }
+    if modifiers.alt { bits |= 8; }
+    if modifiers.ctrl { bits |= 16; }
+    bits
+}
+
+/// 编码一个鼠标事件，col / row 从 0 开始。
+/// SGR 格式（1006）直接写十进制坐标；旧格式坐标超过 222 时无法表示，返回 None
+pub fn encode(code: u8, col: usize, row: usize, release: bool, sgr: bool) -> Option<Vec<u8>> {
+    if sgr {
+        let suffix = if release { 'm' } else { 'M' };
+        return Some(format!("\x1b[<{};{};{}{}", code, col + 1, row + 1, suffix).into_bytes());
+    }
+    if col > 222 || row > 222 {
+        return None;
+    }
+    // 旧格式不区分释放的是哪个按钮
+    let code = if release { 3 | (code & !3) } else { code };
+    Some(vec![0x1b, b'[', b'M', 32 + code, 33 + col as u8, 33 + row as u8])
+}
@@ -0,0 +1,195 @@
+use egui::{Rect, Sense, Ui, Vec2};
+
+/// 分隔条的宽度（像素）
+const DIVIDER_WIDTH: f32 = 4.0;
+/// 分割比例的范围，避免某个窗格被压缩到不可见
+const MIN_RATIO: f32 = 0.1;
+const MAX_RATIO: f32 = 0.9;
+/// 键盘调整分割比例的步长
+const RESIZE_STEP: f32 = 0.05;
+
+/// 分割方向：Horizontal 为左右并排，Vertical 为上下堆叠
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SplitDir {
+    Horizontal,
+    Vertical,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+impl Direction {
+    fn axis(&self) -> SplitDir {
+        match self {
+            Direction::Left | Direction::Right => SplitDir::Horizontal,
+            Direction::Up | Direction::Down => SplitDir::Vertical,
+        }
+    }
+}
+
+/// 终端标签页内的窗格布局树，叶子为窗格 ID
+#[derive(Debug, Clone)]
+pub enum PaneNode {
+    Leaf(usize),
+    Split {
+        dir: SplitDir,
+        /// 第一个子节点所占的比例
+        ratio: f32,
+        first: Box<PaneNode>,
+        second: Box<PaneNode>,
+    },
+}
+
+impl PaneNode {
+    /// 计算每个窗格的区域，并处理分隔条的拖动
+    pub fn layout(&mut self, ui: &mut Ui, rect: Rect, out: &mut Vec<(usize, Rect)>) {
+        match self {
+            PaneNode::Leaf(id) => out.push((*id, rect)),
+            PaneNode::Split { dir, ratio, first, second } => {
+                let (first_rect, divider, second_rect) = split_rect(rect, *dir, *ratio);
+
+                let id = ui.id().with(("terminal_divider", first.first_leaf(), second.first_leaf()));
+                let response = ui.interact(divider, id, Sense::drag());
+                let cursor = match dir {
+                    SplitDir::Horizontal => egui::CursorIcon::ResizeHorizontal,
+                    SplitDir::Vertical => egui::CursorIcon::ResizeVertical,
+                };
+                if response.hovered() || response.dragged() {
+                    ui.ctx().set_cursor_icon(cursor);
+                }
+                if response.dragged() {
+                    let (delta, extent) = match dir {
+                        SplitDir::Horizontal => (response.drag_delta().x, rect.width()),
+                        SplitDir::Vertical => (response.drag_delta().y, rect.height()),
+                    };
+                    if extent > 0.0 {
+                        *ratio = (*ratio + delta / extent).clamp(MIN_RATIO, MAX_RATIO);
+                    }
+                }
+                let stroke = if response.hovered() || response.dragged() {
+                    ui.visuals().widgets.hovered.bg_stroke
+                } else {
+                    ui.visuals().widgets.noninteractive.bg_stroke
+                };
+                let center = divider.center();
+                match dir {
+                    SplitDir::Horizontal => ui.painter().vline(center.x, divider.y_range(), stroke),
+                    SplitDir::Vertical => ui.painter().hline(divider.x_range(), center.y, stroke),
+                };
+
+                first.layout(ui, first_rect, out);
+                second.layout(ui, second_rect, out);
+            }
+        }
+    }
+
+    fn first_leaf(&self) -> usize {
+        match self {
+            PaneNode::Leaf(id) => *id,
+            PaneNode::Split { first, .. } => first.first_leaf(),
+        }
+    }
+
+    fn contains(&self, pane: usize) -> bool {
+        match self {
+            PaneNode::Leaf(id) => *id == pane,
+            PaneNode::Split { first, second, .. } => first.contains(pane) || second.contains(pane),
+        }
+    }
+
+    /// 把窗格 target 一分为二，新窗格位于右侧 / 下方
+    pub fn split(&mut self, target: usize, dir: SplitDir, new_pane: usize) -> bool {
+        match self {
+            PaneNode::Leaf(id) if *id == target => {
+                *self = PaneNode::Split {
+                    dir,
+                    ratio: 0.5,
+                    first: Box::new(PaneNode::Leaf(target)),
+                    second: Box::new(PaneNode::Leaf(new_pane)),
+                };
+                true
+            }
+            PaneNode::Leaf(_) => false,
+            PaneNode::Split { first, second, .. } => first.split(target, dir, new_pane) || second.split(target, dir, new_pane),
+        }
+    }
+
+    /// 移除窗格，其所在的分割由兄弟节点取代；最后一个窗格不可移除
+    pub fn remove(&mut self, target: usize) -> bool {
+        let PaneNode::Split { first, second, .. } = self else { return false };
+        let sibling = if matches!(**first, PaneNode::Leaf(id) if id == target) {
+            second.as_ref().clone()
+        } else if matches!(**second, PaneNode::Leaf(id) if id == target) {
+            first.as_ref().clone()
+        } else {
+            return first.remove(target) || second.remove(target);
+        };
+        *self = sibling;
+        true
+    }
+
+    /// 朝 direction 移动离窗格 target 最近的同向分隔条
+    pub fn resize(&mut self, target: usize, direction: Direction) -> bool {
+        let PaneNode::Split { dir, ratio, first, second } = self else { return false };
+        // 先尝试更内层的分割
+        if first.resize(target, direction) || second.resize(target, direction) {
+            return true;
+        }
+        if *dir != direction.axis() || !(first.contains(target) || second.contains(target)) {
+            return false;
+        }
+        let step = match direction {
+            Direction::Right | Direction::Down => RESIZE_STEP,
+            Direction::Left | Direction::Up => -RESIZE_STEP,
+        };
+        *ratio = (*ratio + step).clamp(MIN_RATIO, MAX_RATIO);
+        true
+    }
+}
+
+fn split_rect(rect: Rect, dir: SplitDir, ratio: f32) -> (Rect, Rect, Rect) {
+    match dir {
+        SplitDir::Horizontal => {
+            let x = rect.min.x + (rect.width() - DIVIDER_WIDTH) * ratio;
+            (
+                Rect::from_min_max(rect.min, egui::pos2(x, rect.max.y)),
+                Rect::from_min_size(egui::pos2(x, rect.min.y), Vec2::new(DIVIDER_WIDTH, rect.height())),
+                Rect::from_min_max(egui::pos2(x + DIVIDER_WIDTH, rect.min.y), rect.max),
+            )
+        }
+        SplitDir::Vertical => {
+            let y = rect.min.y + (rect.height() - DIVIDER_WIDTH) * ratio;
+            (
+                Rect::from_min_max(rect.min, egui::pos2(rect.max.x, y)),
+                Rect::from_min_size(egui::pos2(rect.min.x, y), Vec2::new(rect.width(), DIVIDER_WIDTH)),
+                Rect::from_min_max(egui::pos2(rect.min.x, y + DIVIDER_WIDTH), rect.max),
+            )
+        }
+    }
+}
+
+/// 在上一帧的窗格区域中找到 from 朝 direction 方向最近的相邻窗格
+pub fn neighbor(rects: &[(usize, Rect)], from: usize, direction: Direction) -> Option<usize> {
+    let (_, current) = rects.iter().find(|(id, _)| *id == from)?;
+    let center = current.center();
+    rects
+        .iter()
+        .filter(|(id, _)| *id != from)
+        .filter(|(_, r)| match direction {
+            Direction::Left => r.max.x <= current.min.x + 1.0 && r.y_range().intersects(current.y_range()),
+            Direction::Right => r.min.x >= current.max.x - 1.0 && r.y_range().intersects(current.y_range()),
+            Direction::Up => r.max.y <= current.min.y + 1.0 && r.x_range().intersects(current.x_range()),
+            Direction::Down => r.min.y >= current.max.y - 1.0 && r.x_range().intersects(current.x_range()),
+        })
+        .min_by(|(_, a), (_, b)| {
+            let da = a.center().distance_sq(center);
+            let db = b.center().distance_sq(center);
+            da.total_cmp(&db)
+        })
+        .map(|(id, _)| *id)
+}
@@ -0,0 +1,342 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use super::colors::SchemeId;
+
+const CONFIG_FILE: &str = "terminal_config.toml";
+
+pub const MIN_FONT_SIZE: f32 = 6.0;
+pub const MAX_FONT_SIZE: f32 = 48.0;
+pub const MIN_LINE_SPACING: f32 = 0.8;
+pub const MAX_LINE_SPACING: f32 = 2.0;
+
+// ----------------------------------------------------------------------------
+// 终端配置方案
+// ----------------------------------------------------------------------------
+
+/// 一个具名的终端配置：使用的 shell、参数、起始目录与额外的环境变量
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct TerminalProfile {
+    pub name: String,
+    pub shell: String,
+    /// 以空白分隔的参数，可用双引号包裹含空格的参数
+    #[serde(default)]
+    pub args: String,
+    /// 起始目录，None 表示使用 Verbium 的工作目录
+    #[serde(default)]
+    pub cwd: Option<PathBuf>,
+    /// 额外的环境变量，按顺序设置，同名时后者覆盖前者
+    #[serde(default)]
+    pub env: Vec<EnvVar>,
+    /// 设置后通过系统的 ssh 客户端连接远程主机，不再启动本地 shell
+    #[serde(default)]
+    pub ssh: Option<SshTarget>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct EnvVar {
+    pub name: String,
+    pub value: String,
+}
+
+/// 远程主机；认证交给 ssh 自身（密钥代理、`~/.ssh/config` 或在终端中输入密码）
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SshTarget {
+    pub host: String,
+    #[serde(default = "default_ssh_port")]
+    pub port: u16,
+    /// 登录用户名，留空时使用 ssh 的默认值
+    #[serde(default)]
+    pub user: String,
+    /// 私钥文件，None 表示由 ssh 自行选择
+    #[serde(default)]
+    pub identity_file: Option<PathBuf>,
+}
+
+fn default_ssh_port() -> u16 { 22 }
+
+impl Default for SshTarget {
+    fn default() -> Self {
+        Self {
+            host: String::new(),
+            port: default_ssh_port(),
+            user: String::new(),
+            identity_file: None,
+        }
+    }
+}
+
+impl SshTarget {
+    /// ssh 的参数：端口、私钥与 `user@host`，最后附加配置中的额外参数
+    fn args(&self, extra: &str) -> Vec<String> {
+        let mut args = vec!["-p".to_string(), self.port.to_string()];
+        if let Some(key) = &self.identity_file {
+            args.push("-i".to_string());
+            args.push(key.to_string_lossy().to_string());
+        }
+        args.extend(split_args(extra));
+        if self.user.is_empty() {
+            args.push(self.host.clone());
+        } else {
+            args.push(format!("{}@{}", self.user, self.host));
+        }
+        args
+    }
+}
+
+impl TerminalProfile {
+    fn new(name: &str, shell: &str, args: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            shell: shell.to_string(),
+            args: args.to_string(),
+            cwd: None,
+            env: Vec::new(),
+            ssh: None,
+        }
+    }
+
+    /// 构造启动命令；`cwd` 优先于配置中的起始目录。
+    /// 远程方案启动 ssh，此时 `args` 作为额外的 ssh 选项放在目标主机之前
+    pub fn command(&self, cwd: Option<&Path>) -> portable_pty::CommandBuilder {
+        let mut cmd = match &self.ssh {
+            Some(target) => {
+                let mut cmd = portable_pty::CommandBuilder::new("ssh");
+                cmd.args(target.args(&self.args));
+                cmd
+            }
+            None => {
+                let mut cmd = portable_pty::CommandBuilder::new(&self.shell);
+                cmd.args(split_args(&self.args));
+                cmd
+            }
+        };
+        if let Some(dir) = cwd.or(self.cwd.as_deref()) {
+            cmd.cwd(dir);
+        }
+        for var in self.env.iter().filter(|v| !v.name.is_empty()) {
+            cmd.env(&var.name, &var.value);
+        }
+        cmd
+    }
+}
+
+fn default_profiles() -> Vec<TerminalProfile> {
+    if cfg!(windows) {
+        vec![
+            TerminalProfile::new("PowerShell", "powershell.exe", "-NoLogo"),
+            TerminalProfile::new("Command Prompt", "cmd.exe", ""),
+        ]
+    } else {
+        vec![
+            TerminalProfile::new("Bash", "bash", ""),
+            TerminalProfile::new("Login Shell", "bash", "-l"),
+        ]
+    }
+}
+
+// ----------------------------------------------------------------------------
+// 光标样式
+// ----------------------------------------------------------------------------
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum CursorShape {
+    #[default]
+    Block,
+    /// 竖线
+    Beam,
+    Underline,
+}
+
+impl CursorShape {
+    pub const ALL: [CursorShape; 3] = [CursorShape::Block, CursorShape::Beam, CursorShape::Underline];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            CursorShape::Block => "Block",
+            CursorShape::Beam => "Beam",
+            CursorShape::Underline => "Underline",
+        }
+    }
+}
+
+/// 用户默认的光标样式；应用可通过 DECSCUSR（`CSI Ps SP q`）临时覆盖
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CursorStyle {
+    pub shape: CursorShape,
+    pub blink: bool,
+}
+
+impl Default for CursorStyle {
+    fn default() -> Self {
+        Self { shape: CursorShape::Block, blink: true }
+    }
+}
+
+impl CursorStyle {
+    /// 解析 DECSCUSR 参数，0 表示恢复用户默认样式（返回 None）
+    pub fn from_decscusr(ps: u16) -> Option<Self> {
+        let (shape, blink) = match ps {
+            1 => (CursorShape::Block, true),
+            2 => (CursorShape::Block, false),
+            3 => (CursorShape::Underline, true),
+            4 => (CursorShape::Underline, false),
+            5 => (CursorShape::Beam, true),
+            6 => (CursorShape::Beam, false),
+            _ => return None,
+        };
+        Some(Self { shape, blink })
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TerminalConfig {
+    #[serde(default = "default_profiles")]
+    pub profiles: Vec<TerminalProfile>,
+    /// 默认方案的名称，用于快捷新建终端与 `RunInTerminal`
+    #[serde(default)]
+    pub default_profile: String,
+    #[serde(default)]
+    pub cursor: CursorStyle,
+    #[serde(default)]
+    pub color_scheme: SchemeId,
+    /// 双击选词时作为单词边界的字符（空白总是边界）
+    #[serde(default = "default_word_delimiters")]
+    pub word_delimiters: String,
+    /// 回滚缓冲区行数，0 表示不限制
+    #[serde(default = "default_scrollback_lines")]
+    pub scrollback_lines: usize,
+    /// 选择完成后自动复制到剪贴板
+    #[serde(default)]
+    pub copy_on_select: bool,
+    /// 鼠标中键粘贴
+    #[serde(default)]
+    pub paste_on_middle_click: bool,
+    /// 系统等宽字体族名称，空表示使用内置等宽字体
+    #[serde(default)]
+    pub font_family: String,
+    #[serde(default = "default_font_size")]
+    pub font_size: f32,
+    /// 行高相对字体行高的倍数
+    #[serde(default = "default_line_spacing")]
+    pub line_spacing: f32,
+}
+
+fn default_word_delimiters() -> String {
+    "()[]{}<>'\"`|;,".to_string()
+}
+
+fn default_scrollback_lines() -> usize { super::DEFAULT_SCROLLBACK }
+
+fn default_font_size() -> f32 { 14.0 }
+
+fn default_line_spacing() -> f32 { 1.0 }
+
+/// 所有终端窗格共享的外观与选择设置，在设置中修改后立即生效
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct PaneSettings {
+    pub cursor: CursorStyle,
+    pub scheme: SchemeId,
+    pub word_delimiters: String,
+    pub scrollback_limit: Option<usize>,
+    pub copy_on_select: bool,
+    pub paste_on_middle_click: bool,
+    /// 是否使用插件注册的终端字体族
+    pub custom_font: bool,
+    pub font_size: f32,
+    pub line_spacing: f32,
+}
+
+impl Default for TerminalConfig {
+    fn default() -> Self {
+        Self {
+            profiles: default_profiles(),
+            default_profile: String::new(),
+            cursor: CursorStyle::default(),
+            color_scheme: SchemeId::default(),
+            word_delimiters: default_word_delimiters(),
+            scrollback_lines: default_scrollback_lines(),
+            copy_on_select: false,
+            paste_on_middle_click: false,
+            font_family: String::new(),
+            font_size: default_font_size(),
+            line_spacing: default_line_spacing(),
+        }
+    }
+}
+
+impl TerminalConfig {
+    pub fn load() -> Self {
+        std::fs::read_to_string(CONFIG_FILE)
+            .ok()
+            .and_then(|content| toml::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        if let Ok(content) = toml::to_string_pretty(self) {
+            let _ = std::fs::write(CONFIG_FILE, content);
+        }
+    }
+
+    pub fn pane_settings(&self) -> PaneSettings {
+        PaneSettings {
+            cursor: self.cursor,
+            scheme: self.color_scheme,
+            word_delimiters: self.word_delimiters.clone(),
+            scrollback_limit: (self.scrollback_lines > 0).then_some(self.scrollback_lines),
+            copy_on_select: self.copy_on_select,
+            paste_on_middle_click: self.paste_on_middle_click,
+            custom_font: !self.font_family.is_empty(),
+            font_size: self.font_size.clamp(MIN_FONT_SIZE, MAX_FONT_SIZE),
+            line_spacing: self.line_spacing.clamp(MIN_LINE_SPACING, MAX_LINE_SPACING),
+        }
+    }
+
+    /// 默认方案；名称不存在时使用第一个，全部删除时回退到内置方案
+    pub fn default_profile(&self) -> TerminalProfile {
+        self.profiles
+            .iter()
+            .find(|p| p.name == self.default_profile)
+            .or(self.profiles.first())
+            .cloned()
+            .unwrap_or_else(|| default_profiles().remove(0))
+    }
+
+    /// 在本机运行的方案：默认方案是 SSH 时使用第一个本地方案，没有时回退到内置方案
+    pub fn local_profile(&self) -> TerminalProfile {
+        Some(self.default_profile())
+            .filter(|p| p.ssh.is_none())
+            .or_else(|| self.profiles.iter().find(|p| p.ssh.is_none()).cloned())
+            .unwrap_or_else(|| default_profiles().remove(0))
+    }
+}
+
+/// 按空白拆分参数，双引号内的空白保留
+fn split_args(args: &str) -> Vec<String> {
+    let mut result = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut has_token = false;
+    for c in args.chars() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                has_token = true;
+            }
+            c if c.is_whitespace() && !in_quotes => {
+                if has_token {
+                    result.push(std::mem::take(&mut current));
+                    has_token = false;
+                }
+            }
+            c => {
+                current.push(c);
+                has_token = true;
+            }
+        }
+    }
+    if has_token {
+        result.push(current);
+    }
+    result
+}
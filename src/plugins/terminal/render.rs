@@ -0,0 +1,66 @@
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+use egui::{Color32, FontId, Galley, Ui};
+use egui::text::{LayoutJob, TextFormat};
+use super::Cell;
+
+// ----------------------------------------------------------------------------
+// 行排版缓存
+// ----------------------------------------------------------------------------
+
+/// 一行中需要绘制的字符：所在列与排版结果
+pub type RowGlyphs = Vec<(usize, Arc<Galley>)>;
+
+/// 按行内容缓存排版结果。
+/// 每帧对可见行的字符与颜色求哈希，哈希不变的行（包括随输出上移的行）直接复用上次的排版，
+/// 只有内容变化的行才重新排版；本帧未用到的行在帧末丢弃
+#[derive(Default)]
+pub struct RowCache {
+    rows: HashMap<u64, RowGlyphs>,
+    used: HashSet<u64>,
+    /// 字体图集的标识；图集重建（缩放变化、图集写满）后旧的排版结果失效
+    atlas: usize,
+}
+
+impl RowCache {
+    pub fn begin_frame(&mut self, ui: &Ui) {
+        let atlas = ui.fonts(|f| Arc::as_ptr(&f.texture_atlas()) as usize);
+        if atlas != self.atlas {
+            self.atlas = atlas;
+            self.rows.clear();
+        }
+    }
+
+    pub fn end_frame(&mut self) {
+        let used = std::mem::take(&mut self.used);
+        self.rows.retain(|key, _| used.contains(key));
+    }
+
+    /// 取得一行的排版结果，缓存未命中时排版；clusters 为字素簇表，color 给出单元格的前景色
+    pub fn row(&mut self, ui: &Ui, cells: &[Cell], cols: usize, font_id: &FontId, clusters: &[String], color: impl Fn(&Cell) -> Color32) -> &RowGlyphs {
+        let visible = || cells.iter().enumerate().take(cols).filter(|(_, cell)| !cell.is_wide_continuation && cell.c != ' ');
+
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        font_id.size.to_bits().hash(&mut hasher);
+        for (col, cell) in visible() {
+            (col, cell.c, cell.cluster, color(cell).to_array()).hash(&mut hasher);
+        }
+        let key = hasher.finish();
+
+        self.used.insert(key);
+        self.rows.entry(key).or_insert_with(|| {
+            visible()
+                .map(|(col, cell)| {
+                    let text = match clusters.get((cell.cluster as usize).wrapping_sub(1)) {
+                        Some(cluster) => cluster.clone(),
+                        None => cell.c.to_string(),
+                    };
+                    let mut job = LayoutJob::default();
+                    job.append(&text, 0.0, TextFormat { font_id: font_id.clone(), color: color(cell), ..Default::default() });
+                    (col, ui.fonts(|f| f.layout_job(job)))
+                })
+                .collect()
+        })
+    }
+}
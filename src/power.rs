@@ -0,0 +1,205 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+
+// ----------------------------------------------------------------------------
+// 性能 / 电源配置
+// ----------------------------------------------------------------------------
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum BatterySaverMode {
+    /// 从不启用省电模式
+    Off,
+    /// 仅在使用电池供电时启用
+    #[default]
+    Auto,
+    /// 始终启用
+    Always,
+}
+
+/// 用户可配置的后台活动间隔（保存在 AppConfig 中）
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PerformanceSettings {
+    /// 自动保存间隔（秒），0 表示关闭
+    #[serde(default)]
+    pub autosave_interval_secs: u32,
+    /// 文件监视轮询间隔（毫秒）
+    #[serde(default = "default_watcher_interval")]
+    pub watcher_interval_ms: u64,
+    /// 后台持续刷新（如终端）的最小间隔（毫秒）。用户输入仍会立即重绘，并非严格的帧率上限
+    #[serde(default = "default_repaint_interval")]
+    pub background_repaint_ms: u64,
+    #[serde(default)]
+    pub battery_saver: BatterySaverMode,
+}
+
+fn default_watcher_interval() -> u64 { 1000 }
+fn default_repaint_interval() -> u64 { 16 }
+
+impl Default for PerformanceSettings {
+    fn default() -> Self {
+        Self {
+            autosave_interval_secs: 0,
+            watcher_interval_ms: default_watcher_interval(),
+            background_repaint_ms: default_repaint_interval(),
+            battery_saver: BatterySaverMode::default(),
+        }
+    }
+}
+
+/// 当前帧生效的性能参数，宿主每帧写入 egui Context，标签页通过 `PowerProfile::get` 读取
+#[derive(Clone, Debug)]
+pub struct PowerProfile {
+    pub autosave_interval: Option<Duration>,
+    pub watcher_interval: Duration,
+    pub background_repaint: Duration,
+    /// 省电模式下暂停文件监视等后台轮询
+    pub watchers_paused: bool,
+    pub battery_saver_active: bool,
+}
+
+impl Default for PowerProfile {
+    fn default() -> Self {
+        Self::from_settings(&PerformanceSettings::default(), false)
+    }
+}
+
+impl PowerProfile {
+    fn id() -> egui::Id {
+        egui::Id::new("verbium_power_profile")
+    }
+
+    pub fn from_settings(settings: &PerformanceSettings, saver_active: bool) -> Self {
+        let autosave_interval = (settings.autosave_interval_secs > 0)
+            .then(|| Duration::from_secs(settings.autosave_interval_secs as u64));
+        let mut repaint_ms = settings.background_repaint_ms;
+        if saver_active {
+            // 省电模式将后台刷新放慢到每秒一次左右
+            repaint_ms = repaint_ms.max(1000);
+        }
+        Self {
+            autosave_interval,
+            watcher_interval: Duration::from_millis(settings.watcher_interval_ms.max(100)),
+            background_repaint: Duration::from_millis(repaint_ms),
+            watchers_paused: saver_active,
+            battery_saver_active: saver_active,
+        }
+    }
+
+    pub fn get(ctx: &egui::Context) -> Self {
+        ctx.data(|d| d.get_temp(Self::id())).unwrap_or_default()
+    }
+
+    pub fn store(self, ctx: &egui::Context) {
+        ctx.data_mut(|d| d.insert_temp(Self::id(), self));
+    }
+}
+
+// ----------------------------------------------------------------------------
+// 电源状态检测
+// ----------------------------------------------------------------------------
+
+/// 在后台线程中定期检测是否使用电池供电
+pub struct PowerMonitor {
+    on_battery: Arc<Mutex<Option<bool>>>,
+}
+
+impl PowerMonitor {
+    pub fn start() -> Self {
+        let on_battery = Arc::new(Mutex::new(None));
+        let state = on_battery.clone();
+        std::thread::spawn(move || loop {
+            let value = detect_on_battery();
+            if let Ok(mut s) = state.lock() {
+                *s = value;
+            }
+            std::thread::sleep(Duration::from_secs(30));
+        });
+        Self { on_battery }
+    }
+
+    /// None 表示无法判断（例如台式机或不支持的平台）
+    pub fn on_battery(&self) -> Option<bool> {
+        self.on_battery.lock().ok().and_then(|s| *s)
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn detect_on_battery() -> Option<bool> {
+    // 很多笔记本只提供 Battery 与 USB / UCSI 电源而没有 Mains 节点，因此以电池自身的状态为准；
+    // scope 为 Device 的是鼠标等外设的电池，不计入
+    let entries = std::fs::read_dir("/sys/class/power_supply").ok()?;
+    let read = |dir: &std::path::Path, name: &str| std::fs::read_to_string(dir.join(name)).unwrap_or_default().trim().to_string();
+    let mut result = None;
+    for entry in entries.flatten() {
+        let dir = entry.path();
+        match read(&dir, "type").as_str() {
+            "Mains" if read(&dir, "online") == "1" => return Some(false),
+            "Battery" if read(&dir, "scope") != "Device" => match read(&dir, "status").as_str() {
+                "Discharging" => result = Some(true),
+                "Charging" | "Full" | "Not charging" => result = result.or(Some(false)),
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+    result
+}
+
+#[cfg(target_os = "windows")]
+fn detect_on_battery() -> Option<bool> {
+    #[repr(C)]
+    #[allow(dead_code)]
+    struct SystemPowerStatus {
+        ac_line_status: u8,
+        battery_flag: u8,
+        battery_life_percent: u8,
+        system_status_flag: u8,
+        battery_life_time: u32,
+        battery_full_life_time: u32,
+    }
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetSystemPowerStatus(status: *mut SystemPowerStatus) -> i32;
+    }
+
+    let mut status = SystemPowerStatus {
+        ac_line_status: 255,
+        battery_flag: 255,
+        battery_life_percent: 255,
+        system_status_flag: 0,
+        battery_life_time: 0,
+        battery_full_life_time: 0,
+    };
+    if unsafe { GetSystemPowerStatus(&mut status) } == 0 {
+        return None;
+    }
+    // 128 = 无电池
+    if status.battery_flag == 128 {
+        return None;
+    }
+    match status.ac_line_status {
+        0 => Some(true),
+        1 => Some(false),
+        _ => None,
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn detect_on_battery() -> Option<bool> {
+    let output = std::process::Command::new("pmset").args(["-g", "batt"]).output().ok()?;
+    let text = String::from_utf8_lossy(&output.stdout);
+    if text.contains("'Battery Power'") {
+        Some(true)
+    } else if text.contains("'AC Power'") {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows", target_os = "macos")))]
+fn detect_on_battery() -> Option<bool> {
+    None
+}
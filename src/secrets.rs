@@ -0,0 +1,60 @@
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+
+/// 系统钥匙串中的服务名
+const SERVICE: &str = "Verbium";
+
+// ----------------------------------------------------------------------------
+// 密钥存储：API Key 等敏感信息保存在系统钥匙串中
+// （Windows 凭据管理器 / macOS 钥匙串 / Linux Secret Service），而不是明文配置文件
+// ----------------------------------------------------------------------------
+
+/// 已读取的密钥。访问钥匙串可能较慢（macOS 还可能弹出授权提示），同一密钥只读取一次
+fn cache() -> &'static Mutex<HashMap<String, Option<String>>> {
+    static CACHE: OnceLock<Mutex<HashMap<String, Option<String>>>> = OnceLock::new();
+    CACHE.get_or_init(Default::default)
+}
+
+fn entry(name: &str) -> Result<keyring::Entry, String> {
+    keyring::Entry::new(SERVICE, name).map_err(|e| e.to_string())
+}
+
+/// 读取密钥，未保存时返回 None
+pub fn get(name: &str) -> Result<Option<String>, String> {
+    if let Some(secret) = cache().lock().unwrap().get(name) {
+        return Ok(secret.clone());
+    }
+    let secret = match entry(name)?.get_password() {
+        Ok(secret) => Some(secret),
+        Err(keyring::Error::NoEntry) => None,
+        Err(e) => return Err(e.to_string()),
+    };
+    cache().lock().unwrap().insert(name.to_string(), secret.clone());
+    Ok(secret)
+}
+
+/// 保存密钥，覆盖同名的旧值
+pub fn set(name: &str, secret: &str) -> Result<(), String> {
+    entry(name)?.set_password(secret).map_err(|e| e.to_string())?;
+    cache().lock().unwrap().insert(name.to_string(), Some(secret.to_string()));
+    Ok(())
+}
+
+/// 删除密钥，未保存过时不算错误
+pub fn delete(name: &str) -> Result<(), String> {
+    match entry(name)?.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => {}
+        Err(e) => return Err(e.to_string()),
+    }
+    cache().lock().unwrap().insert(name.to_string(), None);
+    Ok(())
+}
+
+/// 界面中显示的遮盖形式，只保留末尾四个字符，例如 `••••••••3xYz`
+pub fn mask(secret: &str) -> String {
+    let chars: Vec<char> = secret.chars().collect();
+    if chars.len() <= 8 {
+        return "•".repeat(chars.len());
+    }
+    format!("{}{}", "•".repeat(8), chars[chars.len() - 4..].iter().collect::<String>())
+}
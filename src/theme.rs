@@ -0,0 +1,142 @@
+use egui::Color32;
+use serde::{Deserialize, Serialize};
+use crate::NotificationLevel;
+
+// ----------------------------------------------------------------------------
+// 状态配色
+// ----------------------------------------------------------------------------
+
+/// 内置的状态配色预设。色盲友好预设基于 Okabe-Ito 调色板，
+/// 避免仅靠红 / 绿区分成功与失败、新增与删除
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum PalettePreset {
+    #[default]
+    Standard,
+    /// 红绿色盲（绿色弱）
+    Deuteranopia,
+    /// 红绿色盲（红色弱）
+    Protanopia,
+    /// 蓝黄色盲
+    Tritanopia,
+    HighContrast,
+}
+
+impl PalettePreset {
+    pub const ALL: [PalettePreset; 5] = [
+        PalettePreset::Standard,
+        PalettePreset::Deuteranopia,
+        PalettePreset::Protanopia,
+        PalettePreset::Tritanopia,
+        PalettePreset::HighContrast,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            PalettePreset::Standard => "Standard",
+            PalettePreset::Deuteranopia => "Deuteranopia (green-weak)",
+            PalettePreset::Protanopia => "Protanopia (red-weak)",
+            PalettePreset::Tritanopia => "Tritanopia (blue-yellow)",
+            PalettePreset::HighContrast => "High Contrast",
+        }
+    }
+
+    pub fn palette(&self) -> StatusPalette {
+        match self {
+            PalettePreset::Standard => StatusPalette::default(),
+            PalettePreset::Deuteranopia => StatusPalette {
+                info: Color32::from_rgb(86, 180, 233),
+                success: Color32::from_rgb(0, 114, 178),
+                warning: Color32::from_rgb(240, 228, 66),
+                error: Color32::from_rgb(213, 94, 0),
+                added: Color32::from_rgb(0, 114, 178),
+                modified: Color32::from_rgb(230, 159, 0),
+                deleted: Color32::from_rgb(213, 94, 0),
+                untracked: Color32::from_rgb(204, 121, 167),
+            },
+            PalettePreset::Protanopia => StatusPalette {
+                info: Color32::from_rgb(86, 180, 233),
+                success: Color32::from_rgb(0, 114, 178),
+                warning: Color32::from_rgb(240, 228, 66),
+                // 红色弱对暗红不敏感，错误 / 删除使用更亮的橙色
+                error: Color32::from_rgb(230, 159, 0),
+                added: Color32::from_rgb(0, 114, 178),
+                modified: Color32::from_rgb(240, 228, 66),
+                deleted: Color32::from_rgb(230, 159, 0),
+                untracked: Color32::from_rgb(204, 121, 167),
+            },
+            PalettePreset::Tritanopia => StatusPalette {
+                info: Color32::from_rgb(0, 158, 115),
+                success: Color32::from_rgb(0, 170, 170),
+                warning: Color32::from_rgb(255, 140, 170),
+                error: Color32::from_rgb(213, 50, 50),
+                added: Color32::from_rgb(0, 170, 170),
+                modified: Color32::from_rgb(255, 140, 170),
+                deleted: Color32::from_rgb(213, 50, 50),
+                untracked: Color32::from_rgb(170, 170, 170),
+            },
+            PalettePreset::HighContrast => StatusPalette {
+                info: Color32::from_rgb(0, 200, 255),
+                success: Color32::from_rgb(0, 255, 0),
+                warning: Color32::from_rgb(255, 255, 0),
+                error: Color32::from_rgb(255, 0, 80),
+                added: Color32::from_rgb(0, 255, 0),
+                modified: Color32::from_rgb(255, 255, 0),
+                deleted: Color32::from_rgb(255, 0, 80),
+                untracked: Color32::from_rgb(255, 0, 255),
+            },
+        }
+    }
+}
+
+/// 通知、版本控制状态与差异显示共用的语义颜色
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StatusPalette {
+    pub info: Color32,
+    pub success: Color32,
+    pub warning: Color32,
+    pub error: Color32,
+    /// 新增的文件 / 行（git 状态与 diff）
+    pub added: Color32,
+    pub modified: Color32,
+    pub deleted: Color32,
+    pub untracked: Color32,
+}
+
+impl Default for StatusPalette {
+    fn default() -> Self {
+        Self {
+            info: Color32::from_rgb(100, 150, 255),
+            success: Color32::from_rgb(100, 200, 100),
+            warning: Color32::from_rgb(255, 200, 100),
+            error: Color32::from_rgb(255, 100, 100),
+            added: Color32::from_rgb(100, 200, 100),
+            modified: Color32::from_rgb(100, 150, 255),
+            deleted: Color32::from_rgb(255, 100, 100),
+            untracked: Color32::from_rgb(200, 170, 80),
+        }
+    }
+}
+
+impl StatusPalette {
+    pub fn level(&self, level: &NotificationLevel) -> Color32 {
+        match level {
+            NotificationLevel::Info => self.info,
+            NotificationLevel::Success => self.success,
+            NotificationLevel::Warning => self.warning,
+            NotificationLevel::Error => self.error,
+        }
+    }
+}
+
+fn palette_id() -> egui::Id {
+    egui::Id::new("verbium_status_palette")
+}
+
+/// 将当前配色写入 egui 上下文，插件通过 [`palette`] 读取，无需访问宿主配置
+pub fn install(ctx: &egui::Context, preset: PalettePreset) {
+    ctx.data_mut(|d| d.insert_temp(palette_id(), preset.palette()));
+}
+
+pub fn palette(ctx: &egui::Context) -> StatusPalette {
+    ctx.data(|d| d.get_temp(palette_id())).unwrap_or_default()
+}
@@ -0,0 +1,122 @@
+use egui::{Ui, WidgetText, RichText};
+use std::path::{Path, PathBuf};
+use crate::{AppCommand, NotificationLevel, TabIcon, TabInstance};
+
+const TIPS: &[&str] = &[
+    "Right-click a tab to duplicate it or detach it into its own window.",
+    "Ctrl + Shift + T reopens the most recently closed tab.",
+    "Ctrl + Shift + F searches text across the workspace.",
+    "Ctrl + Tab opens a quick switcher listing every open tab.",
+    "Ctrl + Shift + F inside a terminal searches its scrollback.",
+    "Alt + Shift + = / - splits a terminal into panes; Alt + arrows moves between them.",
+    "Ctrl + Shift + ↑ / ↓ jumps between commands in a terminal whose shell reports prompts (OSC 133).",
+    "Ctrl + Up / Down in the editor jumps between headings and functions.",
+    "Press / or Ctrl + F in the editor to find as you type; Enter cycles matches.",
+    "Edit > Settings lets you choose what Verbium shows on startup.",
+];
+
+// ----------------------------------------------------------------------------
+// Welcome Tab
+// ----------------------------------------------------------------------------
+
+/// 欢迎页：最近工作区、快捷操作与使用提示
+#[derive(Debug, Clone)]
+pub struct WelcomeTab {
+    recent_workspaces: Vec<PathBuf>,
+    current_dir: PathBuf,
+}
+
+impl WelcomeTab {
+    pub fn new(recent_workspaces: Vec<PathBuf>) -> Self {
+        Self {
+            recent_workspaces,
+            current_dir: std::env::current_dir().unwrap_or_default(),
+        }
+    }
+
+    /// 配置与会话都保存在工作目录中，因此在另一个目录中启动新的 Verbium 实例来打开工作区
+    fn open_workspace(dir: &Path, control: &mut Vec<AppCommand>) {
+        let result = std::env::current_exe().and_then(|exe| std::process::Command::new(exe).current_dir(dir).spawn());
+        if let Err(e) = result {
+            control.push(AppCommand::Notify {
+                message: format!("Failed to open workspace: {}", e),
+                level: NotificationLevel::Error,
+            });
+        }
+    }
+}
+
+impl TabInstance for WelcomeTab {
+    fn title(&self) -> WidgetText {
+        "Welcome".into()
+    }
+
+    fn icon(&self) -> Option<TabIcon> {
+        Some(TabIcon::Emoji("👋".into()))
+    }
+
+    fn ui(&mut self, ui: &mut Ui, control: &mut Vec<AppCommand>) {
+        egui::ScrollArea::vertical().auto_shrink([false, false]).show(ui, |ui| {
+            ui.add_space(24.0);
+            ui.vertical_centered(|ui| {
+                ui.heading(RichText::new("Verbium").size(32.0).strong());
+                ui.weak("A plugin-based extensible editor framework.");
+            });
+            ui.add_space(24.0);
+
+            ui.columns(2, |columns| {
+                // 左侧：快捷操作
+                let ui = &mut columns[0];
+                ui.label(RichText::new("Start").strong());
+                ui.add_space(4.0);
+                if ui.link("📂 Open File...").clicked() {
+                    if let Some(path) = rfd::FileDialog::new().set_directory(&self.current_dir).pick_file() {
+                        control.push(AppCommand::OpenFile(path));
+                    }
+                }
+                if ui.link("🗁 Open Folder in New Window...").clicked() {
+                    if let Some(dir) = rfd::FileDialog::new().pick_folder() {
+                        Self::open_workspace(&dir, control);
+                    }
+                }
+                if ui.link("↺ Reopen Closed Tab").clicked() {
+                    control.push(AppCommand::ReopenLastClosed);
+                }
+                if ui.link("⚙ Settings").clicked() {
+                    control.push(AppCommand::ToggleSettings);
+                }
+
+                // 右侧：最近工作区
+                let ui = &mut columns[1];
+                ui.label(RichText::new("Recent Workspaces").strong());
+                ui.add_space(4.0);
+                if self.recent_workspaces.is_empty() {
+                    ui.weak("No recent workspaces.");
+                }
+                for dir in &self.recent_workspaces {
+                    let name = dir.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_else(|| dir.to_string_lossy().to_string());
+                    ui.horizontal(|ui| {
+                        if *dir == self.current_dir {
+                            ui.label(format!("📁 {}", name));
+                            ui.weak("(current)");
+                        } else if ui.link(format!("📁 {}", name)).on_hover_text("Open in a new window").clicked() {
+                            Self::open_workspace(dir, control);
+                        }
+                        ui.weak(dir.to_string_lossy());
+                    });
+                }
+            });
+
+            ui.add_space(24.0);
+            ui.separator();
+            ui.label(RichText::new("Tips").strong());
+            for tip in TIPS {
+                ui.label(format!("• {}", tip));
+            }
+        });
+    }
+
+    fn box_clone(&self) -> Box<dyn TabInstance> {
+        Box::new(self.clone())
+    }
+}
@@ -0,0 +1,79 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use egui::Context;
+
+const WORKSPACE_FILE: &str = "verbium_workspace.toml";
+
+// ----------------------------------------------------------------------------
+// 工作区：一个或多个根文件夹。由宿主保存在工作目录下的 verbium_workspace.toml，
+// 插件通过 `current` 读取，通过 `AppCommand::SetWorkspaceRoot` 等命令修改
+// ----------------------------------------------------------------------------
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct Workspace {
+    /// 根文件夹（绝对路径），第一个为主根目录
+    #[serde(default)]
+    pub roots: Vec<PathBuf>,
+}
+
+impl Workspace {
+    /// 读取保存的工作区；从未保存过时以工作目录为唯一根目录
+    pub fn load() -> Self {
+        let saved = std::fs::read_to_string(WORKSPACE_FILE)
+            .ok()
+            .and_then(|content| toml::from_str::<Workspace>(&content).ok());
+        match saved {
+            Some(mut workspace) => {
+                workspace.roots.retain(|root| root.is_dir());
+                workspace
+            }
+            None => Self { roots: std::env::current_dir().into_iter().collect() },
+        }
+    }
+
+    pub fn save(&self) {
+        if let Ok(content) = toml::to_string_pretty(self) {
+            let _ = std::fs::write(WORKSPACE_FILE, content);
+        }
+    }
+
+    /// 主根目录：快速打开等只针对单个目录的功能使用它
+    pub fn primary_root(&self) -> Option<&Path> {
+        self.roots.first().map(PathBuf::as_path)
+    }
+
+    /// 以该文件夹作为唯一的根目录
+    pub fn set_root(&mut self, root: PathBuf) {
+        self.roots = vec![root];
+    }
+
+    /// 追加根目录；已存在时返回 false
+    pub fn add_root(&mut self, root: PathBuf) -> bool {
+        if self.roots.contains(&root) {
+            return false;
+        }
+        self.roots.push(root);
+        true
+    }
+
+    pub fn remove_root(&mut self, root: &Path) -> bool {
+        let before = self.roots.len();
+        self.roots.retain(|r| r != root);
+        self.roots.len() != before
+    }
+}
+
+fn workspace_id() -> egui::Id {
+    egui::Id::new("verbium_workspace")
+}
+
+/// 当前工作区
+pub fn current(ctx: &Context) -> Arc<Workspace> {
+    ctx.data(|d| d.get_temp(workspace_id())).unwrap_or_default()
+}
+
+/// 由宿主在启动及工作区变化时调用
+pub(crate) fn install(ctx: &Context, workspace: Workspace) {
+    ctx.data_mut(|d| d.insert_temp(workspace_id(), Arc::new(workspace)));
+}